@@ -1,6 +1,6 @@
 // build.rs
-extern crate napi_build;
-
 fn main() {
+  // Only the Node addon needs napi's linker setup
+  #[cfg(feature = "node")]
   napi_build::setup();
 }
@@ -3,28 +3,1162 @@
 
 /* auto-generated by NAPI-RS */
 
+/** Optimization hints from the preparation phase */
+export interface OptimizationHints {
+  operationType: string
+  complexity: string
+  expectedCores: number
+  shouldUseRust: boolean
+  isHotFunction: boolean
+  estimatedMemory: number
+  batchSizeHint?: number
+  functionHash: string
+  includeReceipt?: boolean
+  deterministic?: boolean
+  outputPrecision?: number
+  rounding?: string
+  locale?: string
+  canonicalJson?: boolean
+  priority?: string
+  typedResult?: boolean
+  group?: string
+  after?: string
+  chunkSize?: number
+  tenant?: string
+  aggregate?: string
+  maxQueueAgeMs?: number
+  dedupe?: boolean
+  child?: string
+  idempotencyKey?: string
+}
+/** Enhanced result with optimization metadata */
+export interface OptimizedWorkerResult {
+  id: string
+  result?: string
+  error?: string
+  durationMs: number
+  workerId: number
+  executionType: string
+  optimizationUsed: string
+  cacheHit: boolean
+  timedOut: boolean
+  meta?: any
+  receipt?: ExecutionReceipt
+  typed?: TypedResult
+  binary?: Buffer
+  fallback?: FallbackAttempt
+}
+/** A result and its JavaScript type */
+export interface TypedResult {
+  /** "number", "string", "boolean", "array", "object" or "null" */
+  type: string
+  value: any
+}
+/** Typed snapshot of optimization statistics */
+export interface ExecutorStats {
+  rustNativeHits: number
+  jsWorkerFallbacks: number
+  cacheHits: number
+  totalExecutions: number
+  binaryResults: number
+  binaryBytes: number
+  avgLatencyMs: number
+  throughputTasksPerSec: number
+  optimizationRatio: number
+  cacheHitRatio: number
+}
+/** Learning-state entry for a single function hash */
+export interface FunctionProfile {
+  functionHash: string
+  executionCount: number
+  lastLatencyMs?: number
+  lastThroughput?: number
+  optimizationType?: string
+  lastSuccess?: boolean
+}
+/** Blue/green state of a registered function */
+export interface RolloutStatus {
+  name: string
+  /** Latest registered version, if any */
+  stable?: FunctionProfile
+  /** Staged candidate, if a rollout is in progress */
+  staged?: FunctionProfile
+  /** Fraction of matching submissions routed to the staged version */
+  trafficFraction: number
+}
+/** Everything needed to reproduce a computation exactly */
+export interface ExecutionReceipt {
+  /** Caller-supplied function hash from the hints */
+  functionHash: string
+  /** FNV-1a hash of the exact function code, as hex */
+  codeHash: string
+  /** FNV-1a hash of the exact input data, as hex */
+  dataHash: string
+  /** Path that produced the result ("rust_optimized", "needs_js_worker", ...) */
+  backend: string
+  crateVersion: string
+  /** Architecture and OS the native module was built for */
+  target: string
+  /** Compile-time SIMD target features and crate features */
+  features: Array<string>
+  /** Executor RNG seed (fault injection, traffic splitting), as hex */
+  seed: string
+}
+/** A rate limit: sustained tasks per second, with up to `burst` at once */
+export interface RateLimitConfig {
+  tasksPerSecond: number
+  /** Tasks that may start back to back after an idle spell (default 1) */
+  burst?: number
+}
+/** Budget of a child executor; unset limits are the parent's */
+export interface ChildOptions {
+  /** Tasks of the child running at once; at most the parent's worker count */
+  maxConcurrency?: number
+  /**
+   * Input bytes the child's unfinished tasks may hold; at most the parent's
+   * `memory_watermark_mb`
+   */
+  maxBytes?: number
+  /**
+   * Milliseconds the child accepts and starts tasks for, from when it opens.
+   * Its tasks' timeouts are cut to fit.
+   */
+  timeBudgetMs?: number
+}
+/** Snapshot returned by `get_child_status` */
+export interface ChildStatus {
+  childId: string
+  /** Tasks of the child running now */
+  running: number
+  /** Tasks submitted and not yet finished, running ones included */
+  unfinished: number
+  /** Input bytes those tasks hold */
+  bytes: number
+  maxConcurrency: number
+  maxBytes?: number
+  /** Time left of the budget, if it has one */
+  remainingMs?: number
+}
+/** One task of a `submit_graph` submission */
+export interface GraphNode {
+  /** Name other nodes use in `depends_on`, unique within the graph */
+  name: string
+  functionCode: string
+  /** Input of a node without dependencies; other nodes take their parents' output */
+  data?: string
+  dependsOn?: Array<string>
+  /** Replaces the graph's shared hints for this node */
+  hints?: OptimizationHints
+  /**
+   * Code run on this node's output to undo it, should a node of the graph
+   * fail after this one succeeded
+   */
+  compensationCode?: string
+}
+/** Snapshot returned by `get_group_status` */
+export interface GroupStatus {
+  name: string
+  /** Members submitted but not yet finished */
+  pending: number
+  /** Tasks held until the group completes */
+  waiting: number
+  sealed: boolean
+  /** Sealed with every member finished; waiting tasks have been released */
+  complete: boolean
+}
+/** A spec's tasks as `submit_graph` takes them */
+export interface TaskSpec {
+  nodes: Array<GraphNode>
+  /** Shared by every node without its own; imported nodes always have their own */
+  hints?: OptimizationHints
+  timeoutMs?: number
+}
+/**
+ * What one side of the protocol speaks. A client leaving out `min_version`
+ * speaks only `version`; leaving out `encodings` or `operations` asks for
+ * everything the core has.
+ */
+export interface ProtocolSupport {
+  version: number
+  minVersion?: number
+  encodings?: Array<string>
+  operations?: Array<string>
+}
+/** Outcome of `negotiate_protocol` */
+export interface ProtocolAgreement {
+  version: number
+  /** Encodings both sides read, in the client's order of preference */
+  encodings: Array<string>
+  /** Operations the client asked for that the core has */
+  operations: Array<string>
+  /** Operations the client asked for that the core lacks */
+  unsupportedOperations: Array<string>
+  coreVersion: string
+}
+/** Bounds and thresholds for the autoscaler */
+export interface AutoscaleConfig {
+  /**
+   * Fewest workers to scale down to; defaults to 1, or one more than
+   * `operation_pools` dedicate
+   */
+  minWorkers?: number
+  /** Most workers to scale up to; defaults to `max_worker_count` */
+  maxWorkers?: number
+  /** How often load is sampled; defaults to 1s */
+  intervalMs?: number
+  /**
+   * Add workers once the oldest queued task has waited this long; defaults
+   * to 50ms
+   */
+  maxWaitMs?: number
+  /**
+   * Share of the time workers should spend running tasks, which sizes the
+   * pool in both directions; defaults to 0.75
+   */
+  targetUtilization?: number
+  /**
+   * Remove workers only once the pool has been larger than needed for this
+   * long, so a lull between bursts doesn't shrink it; defaults to 10s
+   */
+  scaleDownDelayMs?: number
+}
+/** What `get_background_stats` reports */
+export interface BackgroundStats {
+  enabled: boolean
+  passes: number
+  /** Milliseconds spent ranking hot functions */
+  rankingMs: number
+  /** Milliseconds spent compacting the performance cache and run counts */
+  compactionMs: number
+  /** Milliseconds spent compiling promoted functions */
+  precompileMs: number
+  /** Milliseconds spent refreshing the throttling calibration */
+  calibrationMs: number
+  /** All of the above */
+  totalMs: number
+  /** Functions currently compiled */
+  compiledFunctions: number
+  /** Performance cache entries and run counts dropped by compaction */
+  compactedEntries: number
+}
+/** What `import_state` took in */
+export interface StateImport {
+  /** Registered and staged function versions */
+  functions: number
+  routingOverrides: number
+  /** Performance cache entries */
+  cacheEntries: number
+  /** Handed-off tasks queued here */
+  queuedTasks: number
+}
+/** Snapshot returned by `get_task_status` */
+export interface TaskStatus {
+  taskId: string
+  /** "waiting", "queued", "running", "completed", "failed" or "cancelled" */
+  status: string
+  /** Tasks ahead of this one, counting higher-priority lanes (0 = next up), while queued */
+  queuePosition?: number
+  /** Time since submission, frozen once the task finishes */
+  elapsedMs: number
+  /** Time spent executing, once a worker has picked the task up */
+  runningMs?: number
+  workerId?: number
+}
+/** Snapshot returned by `get_queue_stats` */
+export interface QueueStats {
+  /** Tasks in the shared queue, across every priority */
+  pending: number
+  /** Shared queue depth per priority */
+  critical: number
+  high: number
+  normal: number
+  low: number
+  /** Tasks held for a task group, a delay, a limit or their dependencies */
+  waiting: number
+  /** Time since submission of the task that has been queued longest */
+  oldestTaskAgeMs?: number
+  /** Queued task IDs in the order workers will take them, when asked for */
+  taskIds?: Array<string>
+}
+/** Estimated native memory held by the executor, from `getMemoryUsage` */
+export interface MemoryUsage {
+  /** Tasks waiting in the shared queue */
+  queuedBytes: number
+  /** Results nobody has collected yet */
+  resultBytes: number
+  cacheBytes: number
+  totalBytes: number
+  /** `memory_watermark_mb` in bytes, if set */
+  watermarkBytes?: number
+}
+/**
+ * Worker thread options that only some platforms support. Options for other
+ * platforms are still validated, then ignored, so one config works everywhere.
+ */
+export interface ThreadTuning {
+  /** Windows only: "idle", "lowest", "below_normal", "normal", "above_normal" or "highest" */
+  windowsPriority?: string
+  /** musl only: worker stack size in KiB (musl's 128 KiB default overflows easily) */
+  muslStackSizeKb?: number
+}
+/**
+ * Executor settings; every field is optional and defaults to the previous
+ * hardcoded behaviour. From Rust, fill in what you need and take the rest
+ * from `ExecutorConfig::default()`.
+ */
+export interface ExecutorConfig {
+  /** Defaults to the number of CPU cores */
+  workerCount?: number
+  /**
+   * Most workers `set_worker_count` can grow the pool to; defaults to the
+   * larger of `worker_count` and the number of CPU cores
+   */
+  maxWorkerCount?: number
+  /**
+   * Scale the pool with load between these bounds from the start; off by
+   * default. See `set_autoscale`.
+   */
+  autoscale?: AutoscaleConfig
+  /**
+   * How often dead worker threads are looked for and respawned; defaults to
+   * 100ms, and 0 turns supervision off, leaving a dead worker's slot empty
+   */
+  supervisionIntervalMs?: number
+  /** Tasks allowed to wait in the shared queue; unbounded by default */
+  queueCapacity?: number
+  /**
+   * What a submission to a full queue does: "reject" (default) with a
+   * `QueueFull` error, "block" until workers make room, or "drop_oldest" to
+   * fail the oldest queued task of the lowest priority in its place. Needs
+   * `queue_capacity`.
+   */
+  overflowPolicy?: string
+  /**
+   * How idle workers wait for work: "sleep" (default) until woken for it, "yield"
+   * or "spin"
+   */
+  idleStrategy?: string
+  /** Worker stack size in bytes; the platform default otherwise */
+  threadStackSize?: number
+  /** Worker threads are named `{prefix}{worker_id}`; defaults to "threader-worker-" */
+  threadNamePrefix?: string
+  /** Execution budget for tasks submitted without a `timeout_ms` */
+  defaultTimeoutMs?: number
+  /** Uncollected results kept before the oldest are dropped; unbounded by default */
+  resultBufferLimit?: number
+  /** Platform-specific thread options */
+  tuning?: ThreadTuning
+  /** Run `selfTest` during construction and fail it if any check fails */
+  selfTest?: boolean
+  /**
+   * Fail tasks whose function the native paths don't recognize exactly, rather
+   * than guess from its text; defaults to `setStrictDefault`
+   */
+  strict?: boolean
+  /**
+   * Run shared-queue tasks with `complexity: "low"` and at most this many
+   * bytes of data on the submitting thread, where queueing would cost more
+   * than the task itself; off by default
+   */
+  inlineThresholdBytes?: number
+  /**
+   * Longest an `execute_inline_batch` call is estimated to take and still run
+   * on the calling thread rather than the pool; 0 always uses the pool.
+   * Defaults to 1ms.
+   */
+  inlineBatchBudgetMs?: number
+  /**
+   * "low_latency", "balanced" or "max_throughput": sets idle strategy, inline
+   * threshold, batch segment size, stats merging and (on Windows) worker thread
+   * priority for that goal, so they needn't be tuned one by one. Fields set
+   * explicitly win over the profile. Workers aren't pinned to cores either way.
+   */
+  profile?: string
+  /**
+   * Seed for every random choice the executor makes (fault injection, traffic
+   * splitting between function versions), as the hex a receipt reports; taken
+   * from the clock otherwise. A seeded executor also numbers its task IDs from
+   * zero, so the same submissions in the same order get the same IDs and draws.
+   * With several workers, which task draws which number still depends on timing.
+   */
+  seed?: string
+  /**
+   * Bytes each of a worker's scratch buffers (numbers parsed out of arrays,
+   * serialized output) keeps between tasks; 1 MiB by default. Larger ones
+   * are shrunk back after the task that needed them.
+   */
+  scratchLimitBytes?: number
+  /**
+   * Estimated native memory (queued tasks, uncollected results, caches) above
+   * which submissions fail with `QueueFull`, the performance cache is cleared
+   * and `onMemoryPressure` is called; untracked by default
+   */
+  memoryWatermarkMb?: number
+  /**
+   * Send tasks with the same `function_hash` to the same worker while it keeps
+   * up, so what it has cached for the function stays warm; idle workers still
+   * steal them. Off by default.
+   */
+  stickyRouting?: boolean
+  /**
+   * Workers set aside for tasks of one `operation_type` each, by type:
+   * "mathematical", "string_operations" or "array_operations". Those tasks
+   * run only on their pool's workers, and the other workers never take them,
+   * so a flood of one type can't hold up another. `worker_count` must leave
+   * at least one worker for everything else, and defaults to enough to; only
+   * those are resized or reserved for batches. None by default.
+   */
+  operationPools?: Record<string, number>
+  /**
+   * Order queued tasks are taken in: "priority" (default, by the `priority`
+   * hint, oldest first), "fifo" (submission order, `priority` ignored) or
+   * "lifo" (newest first, for recursive fan-out)
+   */
+  queueDiscipline?: string
+  /**
+   * A priority lane left unserved moves up one priority for every `aging_ms`
+   * it waits, until it overtakes the lanes above it; strict priority by default
+   */
+  agingMs?: number
+  /**
+   * Emit a `task_starved` event for each task still queued this long after it
+   * was queued; off by default
+   */
+  starvationWarningMs?: number
+  /**
+   * How long a completed task's result answers resubmissions with its
+   * `idempotency_key`; 5 minutes by default
+   */
+  idempotencyTtlMs?: number
+  /**
+   * Let idle workers rank hot functions, compact caches, compile promoted
+   * functions ahead of their next run and refresh the throttling calibration;
+   * off by default. See `get_background_stats`.
+   */
+  backgroundOptimization?: boolean
+}
+/**
+ * What to run when a function's native execution fails: exactly one of
+ * `function_code` or `registered`
+ */
+export interface FallbackConfig {
+  /** Alternate code run natively on the same input */
+  functionCode?: string
+  /** Hints for `function_code`; defaults to the failed task's */
+  optimizationHints?: OptimizationHints
+  /**
+   * Registered function to run instead, as `name` or `name@range`; resolved
+   * again each time it runs, so it follows newly registered versions
+   */
+  registered?: string
+}
+/** Both attempts of a task whose fallback ran */
+export interface FallbackAttempt {
+  /** Registered `name@version` that ran, or "function_code" */
+  function: string
+  /** Error the native execution failed with */
+  primaryError: string
+  primaryDurationMs: number
+  fallbackDurationMs: number
+}
+/** Outcome of `health_check` */
+export interface HealthStatus {
+  /**
+   * "healthy", "degraded" (canary ran, but workers are parked while the CPU
+   * is throttled) or "unhealthy" (canary failed or missed its deadline)
+   */
+  status: string
+  /** Submission to result, when the canary finished */
+  latencyMs?: number
+  workerId?: number
+  workerCount: number
+  effectiveParallelism: number
+  /** Shared queue depth when the canary was submitted */
+  queuedTasks: number
+  /** Why the executor is unhealthy */
+  error?: string
+}
+/** One known-answer check */
+export interface SelfTestCheck {
+  /** Fast path under test, e.g. "array.parallel_sum" */
+  name: string
+  /** "passed", "failed" or "skipped" (the feature it needs is not compiled in) */
+  status: string
+  expected: string
+  actual?: string
+  /** Why the check failed or was skipped */
+  error?: string
+}
+/** Outcome of `self_test`, with what this build of the native module can do */
+export interface SelfTestReport {
+  /** No check failed; skipped checks don't count against it */
+  passed: boolean
+  checks: Array<SelfTestCheck>
+  crateVersion: string
+  /** Architecture and OS the native module was built for */
+  target: string
+  /** Compile-time SIMD target features and crate features */
+  features: Array<string>
+  cpuCores: number
+  durationMs: number
+}
+/** Where and how often to push metrics */
+export interface StatsdConfig {
+  /** Defaults to "127.0.0.1" */
+  host?: string
+  /** Defaults to 8125 */
+  port?: number
+  /** Prepended to every metric name with a dot; defaults to "threader" */
+  prefix?: string
+  /** Push interval; defaults to 10s */
+  intervalMs?: number
+  /** DogStatsD tags such as "env:prod", attached to every metric */
+  tags?: Array<string>
+}
+/** A task for the `TaskExecutor` entry points */
+export interface ExecutorTask {
+  id: string
+  functionString: string
+  data: any
+  /** Per-task timeout; defaults to the executor's 30s */
+  timeoutMs?: number
+}
+/** Winner of `race` or `executeAny` */
+export interface RaceWinner {
+  id: string
+  value: any
+  durationMs: number
+}
+/** Error object for a task that produced no value */
+export interface TaskErrorInfo {
+  /** "execution", "timeout" or "shutdown" */
+  kind: string
+  message: string
+}
+/** One entry of `executeAllSettled`, shaped like a `Promise.allSettled` result */
+export interface SettledTaskResult {
+  id: string
+  /** "fulfilled" or "rejected" */
+  status: string
+  value?: any
+  reason?: TaskErrorInfo
+  durationMs: number
+}
 /** Check if optimized multi-core execution is available */
 export declare function isOptimizedMulticoreAvailable(): boolean
-/** Get enhanced system information with optimization capabilities */
+/**
+ * Run known-answer checks against every native fast path and report what this
+ * build supports; a failure means the module should not take production traffic
+ */
+export declare function selfTest(): SelfTestReport
+/**
+ * Protocol versions, encodings and operations this core speaks (see
+ * `negotiate_protocol`)
+ */
+export declare function getProtocolSupport(): ProtocolSupport
+/**
+ * Get enhanced system information with optimization capabilities, and under
+ * `host` each core's frequency, the caches, SIMD support, cgroup limits, NUMA
+ * layout and memory, probed on the first call
+ */
 export declare function getOptimizedMulticoreInfo(): string
+/** Create an executor and register it under `name` for `getExecutor` */
+export declare function createExecutor(name: string, config?: ExecutorConfig | undefined | null): OptimizedMultiCoreExecutor
+/** Executor registered under `name` with `createExecutor` */
+export declare function getExecutor(name: string): OptimizedMultiCoreExecutor | null
+/**
+ * Unregister `name`, returning whether it existed. The executor is not shut
+ * down; call `shutdown` on it first if nothing else uses it.
+ */
+export declare function removeExecutor(name: string): boolean
+/** Names registered with `createExecutor`, sorted */
+export declare function executorNames(): Array<string>
+/**
+ * Make strict mode the default for executors created afterwards (unless their
+ * config sets `strict`) and for the `executeAllSettled`/`race`/`executeAny` pool
+ */
+export declare function setStrictDefault(enabled: boolean): void
+/**
+ * Hammer a fresh executor for `duration_ms`, sampling RSS, thread count and
+ * cache sizes. Returns a JSON report, or an error carrying the report if any
+ * of them kept growing. Blocks the calling thread for the whole run.
+ */
+export declare function runSoakTest(durationMs: number, workerCount?: number | undefined | null): string
+/**
+ * Run every task and report each outcome, like `Promise.allSettled`: a failing
+ * task yields a `rejected` entry instead of rejecting the whole batch
+ */
+export declare function executeAllSettled(tasks: Array<ExecutorTask>): Promise<Array<SettledTaskResult>>
+/**
+ * Run tasks and settle with the first to finish, like `Promise.race`: resolves
+ * with its value, or rejects with its error. Losing tasks that haven't started
+ * are cancelled, so they stop taking workers once the winner is known.
+ */
+export declare function race(tasks: Array<ExecutorTask>): Promise<RaceWinner>
+/**
+ * Run tasks and resolve with the first to succeed, like `Promise.any`: failures
+ * are ignored while any task is still running. Rejects only if every task fails,
+ * listing each task's error. Remaining tasks are cancelled as in `race`.
+ */
+export declare function executeAny(tasks: Array<ExecutorTask>): Promise<RaceWinner>
+/** Parse a task spec document; `format` is `"json"` (default) or `"yaml"` */
+export declare function parseTaskSpec(spec: string, format?: string | undefined | null): TaskSpec
+/** Write tasks as a spec document; `format` is `"json"` (default) or `"yaml"` */
+export declare function exportTaskSpec(spec: TaskSpec, format?: string | undefined | null): string
+/**
+ * Mock executor with the same API as `OptimizedMultiCoreExecutor`.
+ *
+ * Tasks run synchronously on submission and never spawn threads. Results
+ * come from scripted outcomes when any are queued, otherwise from the real
+ * Rust fast paths. Scripted latencies are reported, not slept.
+ */
+export declare class MockExecutor {
+  /** Create a mock executor reporting `config.worker_count` workers (default 1) */
+  constructor(config?: ExecutorConfig | undefined | null)
+  /** Script a successful result, optionally only for one function hash */
+  scriptResult(result: string, latencyMs?: number | undefined | null, functionHash?: string | undefined | null): void
+  /** Script an error, optionally only for one function hash */
+  scriptError(error: string, latencyMs?: number | undefined | null, functionHash?: string | undefined | null): void
+  /** Task IDs submitted so far, in submission order */
+  getSubmittedTaskIds(): Array<string>
+  /** No-op, kept for API parity */
+  warmCoresForWorkload(operationType: string, cores: number): void
+  /** Run a task synchronously and queue its result; an already-aborted `signal` throws */
+  submitOptimizedTask(functionCode: string, data: string, optimizationHints: OptimizationHints, meta?: any | undefined | null, timeoutMs?: number | undefined | null, signal?: AbortSignal | undefined | null): string
+  /**
+   * Run a task synchronously and queue its result, with hints and `meta` as JSON text
+   *
+   * @deprecated Use `submitOptimizedTask` with a hints object.
+   */
+  submitOptimizedTaskJson(functionCode: string, data: string, optimizationHints: string, meta?: string | undefined | null): string
+  /** Run a task synchronously, ignoring `delay_ms`: mock tasks are never delayed */
+  submitDelayed(functionCode: string, data: string, optimizationHints: OptimizationHints, delayMs: number): string
+  /** Run a task synchronously and discard its result */
+  submitFire(functionCode: string, data: string, optimizationHints: OptimizationHints): void
+  /** Run a task synchronously on a copy of `data`, which must be UTF-8 text */
+  submitBorrowed(functionCode: string, data: Buffer, optimizationHints: OptimizationHints, meta?: any | undefined | null, timeoutMs?: number | undefined | null): string
+  /** Always false: mock tasks have read their data by the time this can be called */
+  releaseBorrowed(taskId: string): boolean
+  /**
+   * Run a task synchronously and return an already-settled Promise of its result.
+   * An already-aborted `signal` rejects with `AbortError` without running the task.
+   */
+  runTask(functionCode: string, data: string, optimizationHints: OptimizationHints, meta?: any | undefined | null, timeoutMs?: number | undefined | null, signal?: AbortSignal | undefined | null): Promise<OptimizedWorkerResult>
+  /**
+   * Run `function_code` on each of `items` synchronously and return their
+   * results, parsed, in the same order; nothing is queued for collection
+   */
+  executeInlineBatch(functionCode: string, items: Array<any>, optimizationHints?: OptimizationHints | undefined | null): Array<any>
+  /**
+   * Run a batch synchronously and queue its results. `reserved_workers` is
+   * validated like the real executor's but otherwise has no effect.
+   */
+  submitOptimizedBatch(tasks: Array<[string, string]>, optimizationHints: OptimizationHints, metas?: Array<any> | undefined | null, taskHints?: Array<OptimizationHints | undefined | null> | undefined | null, timeoutMs?: number | undefined | null, reservedWorkers?: number | undefined | null): Array<string>
+  /**
+   * Pop the next completed result; errors instead of blocking when none are queued
+   *
+   * @deprecated Use `getResultById`, or `runTask`.
+   */
+  getOptimizedResult(timeoutMs?: number | undefined | null): OptimizedWorkerResult
+  /**
+   * Pop the next completed result as JSON text
+   *
+   * @deprecated Use `getResultById`, or `runTask`.
+   */
+  getOptimizedResultJson(timeoutMs?: number | undefined | null): string
+  /** Pop every queued result (up to `max_results`) */
+  pollResults(maxResults?: number | undefined | null): Array<OptimizedWorkerResult>
+  /** Pop the queued result of `task_id`; errors instead of blocking when it isn't queued */
+  getResultById(taskId: string, timeoutMs?: number | undefined | null): OptimizedWorkerResult
+  /**
+   * Pop `task_count` completed results
+   *
+   * @deprecated Use `getResultById` for each task ID, or `streamBatchResults`.
+   */
+  getOptimizedBatchResults(taskCount: number, timeoutMs?: number | undefined | null): Array<OptimizedWorkerResult>
+  /**
+   * Stream the queued results of `task_ids` to `callback`; errors up front if any
+   * of them has no queued result, since nothing else will ever complete
+   */
+  streamBatchResults(taskIds: Array<string>, callback: (err: Error | null, result: OptimizedWorkerResult) => void, timeoutMs?: number | undefined | null): Promise<number>
+  /** Drop a queued result; tasks run on submission, so there is nothing else to cancel */
+  cancelTask(taskId: string): void
+  /** Drop every queued result */
+  cancelAll(): void
+  /**
+   * Status of a submitted task: always finished, since mock tasks run on submission.
+   * Tasks whose queued result was dropped by `cancel_task`/`cancel_all` report cancelled.
+   */
+  getTaskStatus(taskId: string): TaskStatus
+  /** Always 0: mock tasks run on submission, so nothing ever waits in a queue */
+  getQueueDepth(): number
+  /** An empty queue: mock tasks run on submission */
+  getQueueStats(includeTaskIds?: boolean | undefined | null): QueueStats
+  /** Memory of the uncollected results; the mock queues and caches nothing */
+  getMemoryUsage(): MemoryUsage
+  /** No-op: the mock has no watermark to cross */
+  onMemoryPressure(callback: ((usage: MemoryUsage) => void) | null): void
+  /**
+   * Run `fallback` in place of a failed fast path like the real executor.
+   * Registered fallbacks are rejected: the mock has no function registry.
+   */
+  setFallback(functionHash: string, fallback?: FallbackConfig | undefined | null): void
+  /**
+   * Record a concurrency limit like the real executor; mock tasks already run
+   * one at a time
+   */
+  setConcurrencyLimit(functionHash: string, limit?: number | undefined | null): void
+  /** Concurrency limit recorded by `set_concurrency_limit`, if any */
+  getConcurrencyLimit(functionHash: string): number | null
+  /** Record a function rate limit like the real executor; mock tasks are never delayed */
+  setFunctionRateLimit(functionHash: string, limit?: RateLimitConfig | undefined | null): void
+  /** Rate limit recorded for `function_hash`, if any */
+  getFunctionRateLimit(functionHash: string): RateLimitConfig | null
+  /** Record a tenant rate limit like the real executor; mock tasks are never delayed */
+  setTenantRateLimit(tenant: string, limit?: RateLimitConfig | undefined | null): void
+  /** Rate limit recorded for `tenant`, if any */
+  getTenantRateLimit(tenant: string): RateLimitConfig | null
+  /**
+   * Open a child executor like the real one; its budget is only reported, since
+   * mock tasks finish on submission
+   */
+  openChild(options?: ChildOptions | undefined | null): string
+  /** Close a child executor; later submissions to it are rejected */
+  closeChild(childId: string): boolean
+  /** Budget of an open child executor, with nothing ever in use */
+  getChildStatus(childId: string): ChildStatus
+  /** Seal a task group; its members have already finished */
+  sealGroup(name: string): void
+  /** A task group as the real executor reports it once every member has finished */
+  getGroupStatus(name: string): GroupStatus | null
+  /** Forget a sealed task group; unsealed ones have not completed */
+  removeGroup(name: string): boolean
+  /**
+   * Resolves true at once for a group some task has named: its members
+   * finished on submission
+   */
+  waitForGroup(name: string, timeoutMs?: number | undefined | null): Promise<boolean>
+  /** Always 0: group members finish on submission, so none are left to cancel */
+  cancelGroup(name: string): number
+  /** Record a CPU cap like the real executor; mock tasks never sleep */
+  setMaxCpuFraction(maxCpuFraction?: number | undefined | null): void
+  /** CPU cap recorded by `set_max_cpu_fraction`, if any */
+  getMaxCpuFraction(): number | null
+  /** Accepted for parity; the mock never detects throttling */
+  setThrottleDetection(enabled: boolean): void
+  /** Always the full worker count */
+  getEffectiveParallelism(): number
+  /** Accepted for parity; the mock runs no background passes */
+  setBackgroundOptimization(enabled: boolean): void
+  /** No passes, as the mock runs none */
+  getBackgroundStats(): BackgroundStats
+  /** Healthy with zero latency until shut down; no canary task is recorded */
+  healthCheck(timeoutMs?: number | undefined | null): Promise<HealthStatus>
+  /**
+   * Stats in the same shape as the real executor
+   *
+   * @deprecated Use `getStatsObject`.
+   */
+  getOptimizationStats(): string
+  /** Stats as a typed object */
+  getStatsObject(): ExecutorStats
+  /** Reset stats and drop queued results and scripts */
+  resetOptimizationState(): void
+  get workerCount(): number
+  /**
+   * Report `workers` workers from now on, up to `max_worker_count`; tasks
+   * still run inline
+   */
+  setWorkerCount(workers: number): void
+  /**
+   * Checks `config` like the real executor; the mock's load never changes,
+   * so its worker count stays where it is
+   */
+  setAutoscale(config?: AutoscaleConfig | undefined | null): void
+  /** Always zero, like the seed in mock receipts: the mock has no RNG */
+  getSeed(): string
+  /** Negotiates like the real executor; mock results are JSON whatever is agreed */
+  negotiateProtocol(offer: ProtocolSupport): ProtocolAgreement
+  /** Reject later submissions and resolve true at once: mock tasks finish on submission */
+  drain(timeoutMs?: number | undefined | null): Promise<boolean>
+  /** Resolves true at once; nothing is ever queued or running */
+  waitForIdle(timeoutMs?: number | undefined | null): Promise<boolean>
+  shutdown(): void
+}
 /** Enhanced multi-core executor with optimization capabilities */
 export declare class OptimizedMultiCoreExecutor {
   /** Create enhanced executor with optimization capabilities */
-  constructor(workerCount?: number | undefined | null)
+  constructor(config?: ExecutorConfig | undefined | null)
   /** Warm up specific cores for expected workload */
   warmCoresForWorkload(operationType: string, expectedCores: number): void
-  /** Submit optimized task with preparation hints */
-  submitOptimizedTask(functionCode: string, data: string, optimizationHints: string): string
-  /** Submit optimized batch with adaptive sizing */
-  submitOptimizedBatch(tasks: Array<[string, string]>, optimizationHints: string): Array<string>
-  /** Get optimized result with performance metrics */
-  getOptimizedResult(timeoutMs?: number | undefined | null): string
-  /** Get batch results with optimization tracking */
-  getOptimizedBatchResults(taskCount: number, timeoutMs?: number | undefined | null): Array<string>
-  /** Get optimization performance statistics */
+  /** Submit optimized task with preparation hints. Aborting `signal` cancels the task. */
+  submitOptimizedTask(functionCode: string, data: string, optimizationHints: OptimizationHints, meta?: any | undefined | null, timeoutMs?: number | undefined | null, signal?: AbortSignal | undefined | null): string
+  /**
+   * `submit_optimized_task` as it was before hints became an object: hints
+   * and `meta` as JSON text. The first call reports a `deprecated_call` event.
+   *
+   * @deprecated Use `submitOptimizedTask` with a hints object.
+   */
+  submitOptimizedTaskJson(functionCode: string, data: string, optimizationHints: string, meta?: string | undefined | null): string
+  /**
+   * Submit a task that joins the queue only once `delay_ms` has passed, instead
+   * of a setTimeout around `submit_optimized_task`. It reports `waiting` until
+   * then; delays are rounded up to the timer wheel's 5ms tick.
+   */
+  submitDelayed(functionCode: string, data: string, optimizationHints: OptimizationHints, delayMs: number): string
+  /**
+   * Submit a task whose result is discarded: no result channel, status tracking
+   * or receipt, for telemetry-style work where only the side effect matters.
+   * Cancellation via `cancel_all` still applies.
+   */
+  submitFire(functionCode: string, data: string, optimizationHints: OptimizationHints): void
+  /**
+   * Submit a task that reads `data` where it is instead of copying it, for
+   * payloads of hundreds of megabytes. The Buffer must hold UTF-8 text, and is
+   * referenced until the task is done; don't modify it until the result
+   * arrives or `release_borrowed` returns true.
+   */
+  submitBorrowed(functionCode: string, data: Buffer, optimizationHints: OptimizationHints, meta?: any | undefined | null, timeoutMs?: number | undefined | null): string
+  /**
+   * Let the caller reuse a `submit_borrowed` task's Buffer before the task
+   * runs. True if no worker had started on it: the task is cancelled and its
+   * bytes are never read. False once a worker has, or for other tasks; a
+   * Buffer in use is free again when its task's result arrives.
+   */
+  releaseBorrowed(taskId: string): boolean
+  /**
+   * Run a task and return a Promise resolving to its result (rejects on task error).
+   * `timeout_ms` bounds both the task's execution and the wait for its result.
+   * Aborting `signal` cancels the task and rejects the Promise with `AbortError`.
+   */
+  runTask(functionCode: string, data: string, optimizationHints: OptimizationHints, meta?: any | undefined | null, timeoutMs?: number | undefined | null, signal?: AbortSignal | undefined | null): Promise<OptimizedWorkerResult>
+  /**
+   * Run `function_code` on each of `items` and return their results, parsed,
+   * in the same order: one call, no task IDs to collect. A batch estimated to
+   * finish within `inline_batch_budget_ms` runs on the calling thread; a
+   * costlier one, or one whose hints keep tasks off it, goes to the pool and
+   * the call blocks until every item is done. Throws with the first item that
+   * failed. Hints default as for a task spec's tasks.
+   */
+  executeInlineBatch(functionCode: string, items: Array<any>, optimizationHints?: OptimizationHints | undefined | null): Array<any>
+  /**
+   * Submit optimized batch with adaptive sizing. With `reserved_workers`, the batch
+   * runs only on that many workers held exclusively until it finishes; it waits
+   * in line if they aren't free yet. Otherwise a batch mixing light and heavy
+   * tasks, by their `complexity` and `estimated_memory` hints, is split across
+   * workers so the heavy ones don't end up on the same worker, and one with a
+   * `batch_size_hint` is cut into sub-batches that workers each run in one pass.
+   */
+  submitOptimizedBatch(tasks: Array<[string, string]>, optimizationHints: OptimizationHints, metas?: Array<any> | undefined | null, taskHints?: Array<OptimizationHints | undefined | null> | undefined | null, timeoutMs?: number | undefined | null, reservedWorkers?: number | undefined | null): Array<string>
+  /**
+   * Submit tasks that depend on each other as one graph. A node runs once every
+   * node in its `depends_on` has succeeded, taking their output as its input: a
+   * single parent's result as is, several as a JSON array in `depends_on` order.
+   * When a node fails or is cancelled, everything downstream fails unrun, and
+   * the `compensation_code` of every node that had succeeded runs on its
+   * output, latest first. Returns task IDs in node order.
+   */
+  submitGraph(nodes: Array<GraphNode>, optimizationHints: OptimizationHints, timeoutMs?: number | undefined | null): Array<string>
+  /**
+   * Submit the tasks of a JSON or YAML task spec (see `parse_task_spec`) as one
+   * graph. Returns task IDs in spec order.
+   */
+  submitTaskSpec(spec: string, format?: string | undefined | null): Array<string>
+  /**
+   * Get the next result to arrive, whichever task it's for. The first call
+   * reports a `deprecated_call` event.
+   *
+   * @deprecated Use `getResultById`, or `runTask`.
+   */
+  getOptimizedResult(timeoutMs?: number | undefined | null): OptimizedWorkerResult
+  /**
+   * `get_optimized_result` as it was before results became objects: the
+   * result as JSON text. The first call reports a `deprecated_call` event.
+   *
+   * @deprecated Use `getResultById`, or `runTask`.
+   */
+  getOptimizedResultJson(timeoutMs?: number | undefined | null): string
+  /** Return every result that is ready right now (up to `max_results`) without blocking */
+  pollResults(maxResults?: number | undefined | null): Array<OptimizedWorkerResult>
+  /**
+   * Get the result of a specific task, regardless of completion order. A task
+   * submitted with an `idempotency_key` returns its result every time, until
+   * `idempotency_ttl_ms` after it completed.
+   */
+  getResultById(taskId: string, timeoutMs?: number | undefined | null): OptimizedWorkerResult
+  /**
+   * Get the next `task_count` results to arrive, whichever tasks they're for.
+   * The first call reports a `deprecated_call` event.
+   *
+   * @deprecated Use `getResultById` for each task ID, or `streamBatchResults`.
+   */
+  getOptimizedBatchResults(taskCount: number, timeoutMs?: number | undefined | null): Array<OptimizedWorkerResult>
+  /**
+   * Call `callback(err, result)` for each of `task_ids` as it completes, instead of
+   * buffering the whole batch. Resolves with the number of results streamed;
+   * `timeout_ms` bounds the whole stream. The callback runs in the async context
+   * of this call, so it sees the `AsyncLocalStorage` stores set around it.
+   */
+  streamBatchResults(taskIds: Array<string>, callback: (err: Error | null, result: OptimizedWorkerResult) => void, timeoutMs?: number | undefined | null): Promise<number>
+  /**
+   * Get optimization performance statistics as JSON text. The first call
+   * reports a `deprecated_call` event.
+   *
+   * @deprecated Use `getStatsObject`.
+   */
   getOptimizationStats(): string
+  /** Get optimization statistics as a typed object */
+  getStatsObject(): ExecutorStats
+  /** Performance cache entries, slowest last execution first */
+  getPerformanceCacheEntries(topN?: number | undefined | null): Array<FunctionProfile>
+  /** Most frequently executed functions, highest count first */
+  getHotFunctions(topN?: number | undefined | null): Array<FunctionProfile>
   /** Clear optimization caches and reset stats */
   resetOptimizationState(): void
+  /**
+   * Warm state for `import_state` in the process taking over from this one:
+   * registered functions, routing overrides, caches and learned sub-batch
+   * sizes, as JSON. With `hand_off_queue`, tasks waiting in the shared queue
+   * that can run elsewhere go too, failing here with a `handed_off` result;
+   * the importing executor queues them under the same IDs. See `snapshot`.
+   */
+  exportState(handOffQueue?: boolean | undefined | null): string
+  /**
+   * Take in state from `export_state`, replacing this executor's entries for
+   * the same functions, overrides and cache keys, and queue any tasks handed
+   * off with it. Nothing changes if the functions or overrides are invalid; if
+   * queueing fails, the error says how many handed-off tasks were queued.
+   */
+  importState(state: string): StateImport
+  /** Force a function onto the `rust` or `js` path, or restore hint-based routing with `auto` */
+  setRoutingOverride(functionHash: string, backend: string): void
+  /** Active routing overrides, function hash to backend */
+  getRoutingOverrides(): Record<string, string>
+  /**
+   * Run `fallback` on the same input whenever a task with this `function_hash`
+   * fails on the Rust path, instead of failing it; null removes it. The
+   * result's `fallback` reports how long both attempts took, and a
+   * `fallback_ran` event is published.
+   */
+  setFallback(functionHash: string, fallback?: FallbackConfig | undefined | null): void
+  /**
+   * Register a function as `name@version` (e.g. `transform@1.2.0`). Each version
+   * gets its own function hash, so caches and stats never mix between versions.
+   * Returns the versioned key.
+   */
+  registerFunction(spec: string, functionCode: string, optimizationHints: OptimizationHints): string
+  /**
+   * Submit against the highest registered version matching `name@range`
+   * (e.g. `transform@^1.2`); a bare name picks the latest version
+   */
+  submitRegistered(spec: string, data: string, meta?: any | undefined | null, timeoutMs?: number | undefined | null): string
+  /**
+   * Update `view`, a running aggregate of a registered function with the
+   * `aggregate` hint, with only the elements that changed:
+   * `{"added": [...], "removed": [...], "changed": [{"before": x, "after": y}]}`.
+   * The result is the updated aggregate; a view's first delta starts from empty.
+   * Unlike `submit_registered`, the spec never routes to a staged version.
+   */
+  submitDelta(spec: string, view: string, delta: string, meta?: any | undefined | null, timeoutMs?: number | undefined | null): string
+  /**
+   * Forget an incremental view, so its next delta starts from empty. Returns
+   * false if there was no such view.
+   */
+  dropView(view: string): boolean
+  /**
+   * Stage a new version of a registered function that receives `traffic_fraction`
+   * (0..=1) of `submit_registered` calls whose range it satisfies. Returns its key.
+   */
+  stageFunction(spec: string, functionCode: string, optimizationHints: OptimizationHints, trafficFraction: number): string
+  /** Change the traffic fraction of a staged rollout */
+  setStagedTraffic(name: string, trafficFraction: number): void
+  /** Register the staged version of `name` so it serves all matching traffic */
+  promoteStaged(name: string): string
+  /** Drop the staged version of `name`, returning all traffic to registered versions */
+  rollbackStaged(name: string): string
+  /** Stable and staged versions of `name` with their separate learning-state profiles */
+  getRolloutStatus(name: string): RolloutStatus
+  /** Remove `name@version`, or every version of a bare `name` */
+  unregisterFunction(spec: string): void
+  /** Registered functions as sorted `name@version` keys */
+  listFunctions(): Array<string>
+  /** Cancel a task: it is skipped if still queued, and its result is dropped if running */
+  cancelTask(taskId: string): void
+  /** Cancel every task submitted so far, queued or running */
+  cancelAll(): void
+  /**
+   * Where a submitted task is: queued (with its queue position), running, or finished.
+   * Errors for unknown IDs and for tasks that finished long ago.
+   */
+  getTaskStatus(taskId: string): TaskStatus
+  /**
+   * Tasks waiting in the shared queue, across every priority. Tasks held for a
+   * group, a delay or a limit aren't counted until they're queued.
+   */
+  getQueueDepth(): number
+  /**
+   * Backlog of the shared queue: depth per priority, tasks held back, and how
+   * long the oldest queued task has waited. `include_task_ids` also lists the
+   * queued tasks in the order workers will take them. Tasks in reserved
+   * batches aren't counted; fire-and-forget tasks count towards depth only.
+   */
+  getQueueStats(includeTaskIds?: boolean | undefined | null): QueueStats
+  /**
+   * Estimated native memory held by queued tasks, uncollected results and the
+   * performance cache, as checked against `memory_watermark_mb`
+   */
+  getMemoryUsage(): MemoryUsage
+  /**
+   * Call `callback(usage)` each time estimated native memory goes over
+   * `memory_watermark_mb`, e.g. to run `global.gc()` or shed caches of its own;
+   * null removes it. The callback runs in the async context of this call and
+   * doesn't keep the process alive.
+   */
+  onMemoryPressure(callback: ((usage: MemoryUsage) => void) | null): void
+  /**
+   * Let at most `limit` tasks with this `function_hash` run at once; the rest wait
+   * in the queue. Null lifts the limit. Reserved batches are exempt.
+   */
+  setConcurrencyLimit(functionHash: string, limit?: number | undefined | null): void
+  /** Concurrency limit set for `function_hash`, if any */
+  getConcurrencyLimit(functionHash: string): number | null
+  /**
+   * Let tasks with this `function_hash` start at most `tasks_per_second` times a
+   * second, `burst` at once; the rest wait in the queue. Null lifts the limit.
+   */
+  setFunctionRateLimit(functionHash: string, limit?: RateLimitConfig | undefined | null): void
+  /** Rate limit set for `function_hash`, if any */
+  getFunctionRateLimit(functionHash: string): RateLimitConfig | null
+  /**
+   * Rate limit shared by every task submitted with this `tenant` hint; a task
+   * under both a function and a tenant limit needs a token from each
+   */
+  setTenantRateLimit(tenant: string, limit?: RateLimitConfig | undefined | null): void
+  /** Rate limit set for `tenant`, if any */
+  getTenantRateLimit(tenant: string): RateLimitConfig | null
+  /**
+   * Open a child executor for one request: tasks submitted with its ID as the
+   * `child` hint share its concurrency, byte and time budget, carved out of
+   * this pool. Returns the ID.
+   */
+  openChild(options?: ChildOptions | undefined | null): string
+  /**
+   * Close a child executor when its request ends: its tasks are cancelled,
+   * queued or running, and new ones are refused. `false` if it was unknown or
+   * already closed.
+   */
+  closeChild(childId: string): boolean
+  /** Budget and usage of a child executor; errors once it's closed */
+  getChildStatus(childId: string): ChildStatus
+  /**
+   * Close task group `name` to new members. Once every member has finished, tasks
+   * submitted with `after: name` are queued, including ones submitted later.
+   */
+  sealGroup(name: string): void
+  /** Members and waiting tasks of a task group, or null if no task has named it */
+  getGroupStatus(name: string): GroupStatus | null
+  /**
+   * Forget a completed task group so its name can be reused. Returns false for
+   * unknown groups and errors for ones still pending.
+   */
+  removeGroup(name: string): boolean
+  /**
+   * Resolve once every member of task group `name` submitted so far has
+   * finished (true), or with false after `timeout_ms` or on shutdown. Rejects
+   * if no task has named the group. The group needn't be sealed.
+   */
+  waitForGroup(name: string, timeoutMs?: number | undefined | null): Promise<boolean>
+  /**
+   * Cancel every unfinished member of task group `name`, queued or running, and
+   * return how many there were. Tasks waiting on the group with `after` still
+   * run once it completes.
+   */
+  cancelGroup(name: string): number
+  /**
+   * Cap the pool's aggregate CPU use at `max_cpu_fraction` (in `(0, 1]`) of the
+   * machine's cores by idling workers between tasks, or lift the cap with null
+   */
+  setMaxCpuFraction(maxCpuFraction?: number | undefined | null): void
+  /** Active CPU cap as a fraction of the machine's cores, if any */
+  getMaxCpuFraction(): number | null
+  /**
+   * Enable or disable throttling detection (off by default). Enabling it
+   * calibrates against the CPU as it runs then; while later probes keep finding
+   * it slower, fewer workers take tasks and `throttling_detected`/
+   * `throttling_recovered` events are emitted.
+   */
+  setThrottleDetection(enabled: boolean): void
+  /** Workers currently taking tasks; below `worker_count` while throttled */
+  getEffectiveParallelism(): number
+  /**
+   * Enable or disable background passes run by idle workers (see
+   * `background_optimization`); disabling drops the functions they compiled
+   */
+  setBackgroundOptimization(enabled: boolean): void
+  /** Background passes run so far and the time spent on each step */
+  getBackgroundStats(): BackgroundStats
+  /**
+   * Readiness probe: runs a tiny canary task through the shared queue and a
+   * worker, resolving within `timeout_ms` (default 1s) with its latency and a
+   * "healthy", "degraded" or "unhealthy" status. Never rejects.
+   */
+  healthCheck(timeoutMs?: number | undefined | null): Promise<HealthStatus>
+  /**
+   * Push counters, timers and gauges to a statsd/DogStatsD agent every
+   * `interval_ms`, replacing any running reporter
+   */
+  startStatsdReporter(config: StatsdConfig): void
+  /** Stop pushing to statsd; a no-op if no reporter is running */
+  stopStatsdReporter(): void
+  /** Enable chaos mode with JSON-serialized `FaultConfig` rates, or disable it with null */
+  setFaultInjection(config?: string | undefined | null): void
+  /**
+   * Take buffered events as JSON strings: diagnostics (poisoned locks, worker failures)
+   * and `partial_result` chunks of tasks submitted with `chunk_size`
+   */
+  drainEvents(): Array<string>
   get workerCount(): number
+  /**
+   * Grow or shrink the pool to `workers`, up to `max_worker_count`, keeping
+   * queued tasks. New workers start at once; surplus ones finish the task
+   * they're running, and the sub-batch or reservation they're serving, then
+   * retire, leaving what was routed to them to the rest.
+   */
+  setWorkerCount(workers: number): void
+  /**
+   * Scale the pool with queue depth, wait time and worker utilization within
+   * `config`'s bounds, replacing any running autoscaler, or stop with null
+   * and keep the current count. While it runs, `set_worker_count` only
+   * lasts until its next adjustment.
+   */
+  setAutoscale(config?: AutoscaleConfig | undefined | null): void
+  /**
+   * Seed behind the executor's random choices, as hex; pass it as the config's
+   * `seed` to replay them
+   */
+  getSeed(): string
+  /**
+   * Agree with a client on a protocol version, encodings and operations (see
+   * `get_protocol_support`); results are shaped for the agreement from then on
+   */
+  negotiateProtocol(offer: ProtocolSupport): ProtocolAgreement
+  /**
+   * Stop accepting tasks, then resolve once every queued and running task has
+   * finished: true when idle, false if `timeout_ms` passed first or the
+   * executor was shut down meanwhile. Follow with `shutdown` to stop the workers.
+   */
+  drain(timeoutMs?: number | undefined | null): Promise<boolean>
+  /**
+   * Resolve once no task is queued or running (true), or with false after
+   * `timeout_ms`. Submissions stay open, so the executor may be busy again
+   * by the time the promise settles.
+   */
+  waitForIdle(timeoutMs?: number | undefined | null): Promise<boolean>
+  /**
+   * Stop the workers. Queued tasks are discarded and results not yet
+   * collected are dropped; `drain` first to let in-flight work finish. Idle
+   * workers exit at once and are joined; busy ones are waited for up to a
+   * second, then left to exit after their current task.
+   */
   shutdown(): void
 }
@@ -0,0 +1,37 @@
+// src/abort.rs - AbortSignal support for task submission
+use napi::{ Env, JsFunction, JsObject, JsUnknown, Result, Status };
+
+/// Error used when a signal cancels a task, matching the DOM `AbortError` name
+pub fn abort_error() -> napi::Error {
+  napi::Error::new(Status::Cancelled, "AbortError".to_string())
+}
+
+/// Whether the signal (or any object with an `aborted` flag) has already fired
+pub fn is_aborted(signal: &JsObject) -> Result<bool> {
+  signal.get_named_property::<JsUnknown>("aborted")?.coerce_to_bool()?.get_value()
+}
+
+/// Run `callback` once when the signal fires. Works with `AbortSignal` and any
+/// abort handle exposing a DOM-style `addEventListener`.
+pub fn on_abort<F>(env: &Env, signal: &JsObject, callback: F) -> Result<()>
+  where F: Fn() + 'static
+{
+  let listener = env.create_function_from_closure("onabort", move |_ctx| {
+    callback();
+    Ok(())
+  })?;
+
+  let mut options = env.create_object()?;
+  options.set_named_property("once", true)?;
+
+  let add_event_listener: JsFunction = signal.get_named_property("addEventListener")?;
+  add_event_listener.call(
+    Some(signal),
+    &[
+      env.create_string("abort")?.into_unknown(),
+      listener.into_unknown(),
+      options.into_unknown(),
+    ]
+  )?;
+  Ok(())
+}
@@ -0,0 +1,236 @@
+// src/actor.ts - Named persistent workers that keep state across messages
+import {Worker} from 'worker_threads'
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/**
+ * Handles one message. `state` is the value returned by the actor's init
+ * function and persists on the worker, so mutate it in place to keep changes.
+ */
+export type ActorHandler<S, M, R> = (state: S, message: M) => R | Promise<R>
+
+export interface ActorOptions<D> {
+  /** Passed to the init function */
+  initData?: D
+  /** Per-message time limit in milliseconds; the actor keeps running on timeout */
+  timeout?: number
+}
+
+interface PendingReply {
+  readonly resolve: (result: any) => void
+  readonly reject: (error: Error) => void
+  readonly timeoutId?: NodeJS.Timeout
+}
+
+interface ActorInstance {
+  readonly worker: Worker
+  readonly pending: Map<number, PendingReply>
+  readonly timeout?: number
+  nextMessageId: number
+}
+
+// ============================================================================
+// WORKER SCRIPT
+// ============================================================================
+
+// Messages are handled one at a time in arrival order: the mailbox is the chain
+// of handler calls, so a handler never sees state mid-update by another message
+const actorScript = `
+  const { parentPort } = require('worker_threads')
+
+  const compile = fnString => new Function('return (' + fnString + ')')()
+  const errorMessage = error => (error && error.message) || String(error)
+
+  let state
+  let handler
+  let mailbox = Promise.resolve()
+
+  parentPort.on('message', message => {
+    if (message.type === 'init') {
+      mailbox = mailbox.then(async () => {
+        try {
+          handler = compile(message.handler)
+          state = await compile(message.init)(message.data)
+          parentPort.postMessage({ type: 'ready' })
+        } catch (error) {
+          parentPort.postMessage({ type: 'init_failed', error: errorMessage(error) })
+        }
+      })
+      return
+    }
+
+    mailbox = mailbox.then(async () => {
+      try {
+        const result = await handler(state, message.message)
+        parentPort.postMessage({ type: 'reply', id: message.id, success: true, result })
+      } catch (error) {
+        parentPort.postMessage({
+          type: 'reply',
+          id: message.id,
+          success: false,
+          error: errorMessage(error)
+        })
+      }
+    })
+  })
+`
+
+// ============================================================================
+// ACTOR REGISTRY
+// ============================================================================
+
+const actors = new Map<string, ActorInstance>()
+
+const lookup = (name: string): ActorInstance => {
+  const instance = actors.get(name)
+  if (!instance) {
+    throw new Error(`Unknown actor '${name}'`)
+  }
+  return instance
+}
+
+// Reject every outstanding send once the worker is gone
+const failPending = (instance: ActorInstance, error: Error): void => {
+  for (const reply of instance.pending.values()) {
+    if (reply.timeoutId) clearTimeout(reply.timeoutId)
+    reply.reject(error)
+  }
+  instance.pending.clear()
+}
+
+const settle = (
+  instance: ActorInstance,
+  {id, success, result, error}: any
+): void => {
+  const reply = instance.pending.get(id)
+  if (!reply) return
+  instance.pending.delete(id)
+  if (reply.timeoutId) clearTimeout(reply.timeoutId)
+
+  if (success) {
+    reply.resolve(result)
+  } else {
+    reply.reject(new Error(`Actor handler failed: ${error}`))
+  }
+}
+
+// ============================================================================
+// ACTOR API
+// ============================================================================
+
+export const actor = {
+  /**
+   * Start a named actor on its own worker. Resolves once `init` has produced
+   * the initial state; rejects (and stops the worker) if it throws.
+   * Like threader functions, `init` and `handler` must be self-contained.
+   */
+  spawn<D, S, M, R>(
+    name: string,
+    init: (data: D) => S | Promise<S>,
+    handler: ActorHandler<S, M, R>,
+    options: ActorOptions<D> = {}
+  ): Promise<void> {
+    if (actors.has(name)) {
+      return Promise.reject(new Error(`Actor '${name}' already exists`))
+    }
+
+    const worker = new Worker(actorScript, {eval: true})
+    const instance: ActorInstance = {
+      worker,
+      pending: new Map(),
+      nextMessageId: 0,
+      ...(options.timeout !== undefined && {timeout: options.timeout})
+    }
+    actors.set(name, instance)
+
+    return new Promise<void>((resolve, reject) => {
+      let ready = false
+
+      worker.on('message', message => {
+        if (message.type === 'ready') {
+          ready = true
+          resolve()
+        } else if (message.type === 'init_failed') {
+          actors.delete(name)
+          worker.terminate()
+          reject(new Error(`Actor '${name}' failed to initialize: ${message.error}`))
+        } else {
+          settle(instance, message)
+        }
+      })
+
+      worker.on('error', error => {
+        if (!ready) reject(new Error(`Actor '${name}' failed: ${error.message}`))
+        failPending(instance, new Error(`Actor '${name}' failed: ${error.message}`))
+      })
+
+      worker.on('exit', () => {
+        if (actors.get(name) === instance) actors.delete(name)
+        if (!ready) reject(new Error(`Actor '${name}' exited during init`))
+        failPending(instance, new Error(`Actor '${name}' stopped`))
+      })
+
+      worker.postMessage({
+        type: 'init',
+        init: init.toString(),
+        handler: handler.toString(),
+        data: options.initData
+      })
+    })
+  },
+
+  /**
+   * Post `message` to the actor's mailbox. Resolves with the handler's return
+   * value once every earlier message has been handled.
+   */
+  send<M, R = unknown>(name: string, message: M): Promise<R> {
+    let instance: ActorInstance
+    try {
+      instance = lookup(name)
+    } catch (error) {
+      return Promise.reject(error)
+    }
+
+    const id = instance.nextMessageId++
+    return new Promise<R>((resolve, reject) => {
+      const timeoutId =
+        instance.timeout === undefined
+          ? undefined
+          : setTimeout(() => {
+              instance.pending.delete(id)
+              reject(
+                new Error(`Actor '${name}' did not reply within ${instance.timeout}ms`)
+              )
+            }, instance.timeout)
+
+      instance.pending.set(id, {
+        resolve,
+        reject,
+        ...(timeoutId !== undefined && {timeoutId})
+      })
+      instance.worker.postMessage({type: 'message', id, message})
+    })
+  },
+
+  /**
+   * Terminate the actor's worker, dropping its state. Unanswered sends reject.
+   */
+  async stop(name: string): Promise<void> {
+    const instance = lookup(name)
+    actors.delete(name)
+    await instance.worker.terminate()
+    failPending(instance, new Error(`Actor '${name}' stopped`))
+  },
+
+  /** Names of running actors */
+  list(): string[] {
+    return [...actors.keys()]
+  },
+
+  /** Stop every actor */
+  async shutdown(): Promise<void> {
+    await Promise.all([...actors.keys()].map(name => actor.stop(name)))
+  }
+}
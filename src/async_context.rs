@@ -0,0 +1,86 @@
+// src/async_context.rs - Runs JS callbacks in the async context they were passed in
+//
+// Callbacks handed to the executor are called later, from a native thread's
+// queue rather than from the JS call that passed them, so `AsyncLocalStorage`
+// stores such as request IDs or APM spans set around that call could read as
+// empty inside them. `bind` captures the async context of the call, as an
+// `AsyncResource` created there would, and returns a function that enters it
+// around every call of the callback, as `runInAsyncScope` does.
+use napi::{ sys, Env, Error, JsFunction, JsObject, JsUnknown, NapiRaw, Ref, Result, Status };
+use std::ptr;
+
+// The captured context and the callback to run in it, freed along with the
+// function returned by `bind`
+struct Scope {
+  env: sys::napi_env,
+  context: sys::napi_async_context,
+  // The context's resource, which Node looks the stores up on while the
+  // callback runs; it's also the callback's `this`
+  resource: Ref<()>,
+  callback: Ref<()>,
+}
+
+impl Drop for Scope {
+  fn drop(&mut self) {
+    // Finalizers run on the JS thread, so the env is still usable
+    let env = unsafe { Env::from_raw(self.env) };
+    let _ = self.resource.unref(env);
+    let _ = self.callback.unref(env);
+    unsafe {
+      sys::napi_async_destroy(self.env, self.context);
+    }
+  }
+}
+
+fn check(status: sys::napi_status, what: &str) -> Result<()> {
+  match status {
+    sys::Status::napi_ok => Ok(()),
+    status => Err(Error::new(Status::from(status), what.to_string())),
+  }
+}
+
+/// `callback`, run in the async context of the JS call this is made from
+/// whenever it's called later. `name` labels the context for `async_hooks`.
+pub fn bind(env: &Env, callback: JsFunction, name: &str) -> Result<JsFunction> {
+  let resource = env.create_object()?;
+  let resource_name = env.create_string(name)?;
+  let callback = env.create_reference(callback)?;
+  let mut context = ptr::null_mut();
+  check(
+    unsafe {
+      sys::napi_async_init(env.raw(), resource.raw(), resource_name.raw(), &mut context)
+    },
+    "Failed to capture async context"
+  )?;
+  let scope = Scope {
+    env: env.raw(),
+    context,
+    resource: env.create_reference(resource)?,
+    callback,
+  };
+
+  env.create_function_from_closure(name, move |ctx| {
+    let resource: JsObject = ctx.env.get_reference_value(&scope.resource)?;
+    let callback: JsFunction = ctx.env.get_reference_value(&scope.callback)?;
+    let args = (0..ctx.length)
+      .map(|index| ctx.get::<JsUnknown>(index).map(|arg| unsafe { arg.raw() }))
+      .collect::<Result<Vec<_>>>()?;
+    let mut result = ptr::null_mut();
+    let status = unsafe {
+      sys::napi_make_callback(
+        ctx.env.raw(),
+        scope.context,
+        resource.raw(),
+        callback.raw(),
+        args.len(),
+        args.as_ptr(),
+        &mut result
+      )
+    };
+    // A throwing callback leaves its exception pending for our caller to report
+    match status {
+      sys::Status::napi_pending_exception => Ok(()),
+      status => check(status, "Failed to call callback"),
+    }
+  })
+}
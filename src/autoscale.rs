@@ -0,0 +1,208 @@
+// src/autoscale.rs - Scales the worker pool with load
+use crossbeam::channel;
+use std::thread;
+use std::time::{ Duration, Instant };
+
+use crate::bindings::napi;
+use crate::clock::SharedClock;
+use crate::resize::Resizer;
+
+const DEFAULT_INTERVAL_MS: u32 = 1000;
+const MIN_INTERVAL_MS: u32 = 10;
+const DEFAULT_MAX_WAIT_MS: u32 = 50;
+const DEFAULT_TARGET_UTILIZATION: f64 = 0.75;
+const DEFAULT_SCALE_DOWN_DELAY_MS: u32 = 10_000;
+
+/// Bounds and thresholds for the autoscaler
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct AutoscaleConfig {
+  /// Fewest workers to scale down to; defaults to 1, or one more than
+  /// `operation_pools` dedicate
+  pub min_workers: Option<u32>,
+  /// Most workers to scale up to; defaults to `max_worker_count`
+  pub max_workers: Option<u32>,
+  /// How often load is sampled; defaults to 1s
+  pub interval_ms: Option<u32>,
+  /// Add workers once the oldest queued task has waited this long; defaults
+  /// to 50ms
+  pub max_wait_ms: Option<u32>,
+  /// Share of the time workers should spend running tasks, which sizes the
+  /// pool in both directions; defaults to 0.75
+  pub target_utilization: Option<f64>,
+  /// Remove workers only once the pool has been larger than needed for this
+  /// long, so a lull between bursts doesn't shrink it; defaults to 10s
+  pub scale_down_delay_ms: Option<u32>,
+}
+
+impl AutoscaleConfig {
+  /// Check the settings against a pool of `fewest` to `capacity` workers
+  pub fn policy(&self, fewest: usize, capacity: usize) -> Result<Policy, String> {
+    let min = self.min_workers.map_or(fewest, |min| min as usize);
+    let max = self.max_workers.map_or(capacity, |max| max as usize);
+    if min < fewest || min > max || max > capacity {
+      return Err(
+        format!(
+          "Autoscaling needs {} <= min_workers <= max_workers <= {}, got {} and {}",
+          fewest,
+          capacity,
+          min,
+          max
+        )
+      );
+    }
+    let interval_ms = self.interval_ms.unwrap_or(DEFAULT_INTERVAL_MS);
+    if interval_ms < MIN_INTERVAL_MS {
+      return Err(
+        format!("interval_ms must be at least {}, got {}", MIN_INTERVAL_MS, interval_ms)
+      );
+    }
+    let target_utilization = self.target_utilization.unwrap_or(DEFAULT_TARGET_UTILIZATION);
+    if !(target_utilization > 0.0 && target_utilization <= 1.0) {
+      return Err(format!("target_utilization must be in (0, 1], got {}", target_utilization));
+    }
+    let millis = |ms: u32| Duration::from_millis(ms as u64);
+    Ok(Policy {
+      min,
+      max,
+      interval: millis(interval_ms),
+      max_wait: millis(self.max_wait_ms.unwrap_or(DEFAULT_MAX_WAIT_MS)),
+      target_utilization,
+      scale_down_delay: millis(self.scale_down_delay_ms.unwrap_or(DEFAULT_SCALE_DOWN_DELAY_MS)),
+    })
+  }
+}
+
+/// A validated `AutoscaleConfig`
+#[derive(Debug, Clone, Copy)]
+pub struct Policy {
+  min: usize,
+  max: usize,
+  interval: Duration,
+  max_wait: Duration,
+  target_utilization: f64,
+  scale_down_delay: Duration,
+}
+
+/// Load on the pool, sampled once per interval
+#[derive(Debug, Clone, Copy)]
+pub struct Load {
+  /// Tasks waiting in the shared queue
+  pub queued: usize,
+  /// How long the oldest tracked one has waited
+  pub oldest_wait: Option<Duration>,
+  /// Time every worker has spent running tasks so far
+  pub busy: Duration,
+}
+
+/// Background thread resizing the pool within its bounds until dropped.
+///
+/// Each interval it compares the time workers spent running tasks with the
+/// time they had, and sizes the pool so they'd be busy `target_utilization`
+/// of it. A backlog (the oldest queued task waiting `max_wait_ms`, or more
+/// tasks queued than workers to take them) adds at least one worker right
+/// away; workers are removed only after `scale_down_delay_ms` without one.
+pub struct Autoscaler {
+  // Dropping this wakes the thread and stops it
+  stop: Option<channel::Sender<()>>,
+  handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Autoscaler {
+  /// Bring the pool within `policy`'s bounds, then keep resizing it with
+  /// `resizer` by what `sample` returns, timing busy periods with `clock`
+  pub fn start<F>(
+    policy: Policy,
+    resizer: Resizer,
+    clock: SharedClock,
+    sample: F
+  ) -> Result<Self, String>
+    where F: Fn() -> Load + Send + 'static
+  {
+    let workers = resizer.slots.target();
+    let bounded = workers.clamp(policy.min, policy.max);
+    if bounded != workers {
+      resizer.resize(bounded, "to fit the autoscaler's bounds")?;
+    }
+    let (stop, stopped) = channel::bounded::<()>(0);
+
+    let handle = thread::spawn(move || {
+      let mut last = (clock.now(), sample().busy);
+      let mut underused_since: Option<Instant> = None;
+      // Disconnects when the autoscaler is dropped
+      while let Err(channel::RecvTimeoutError::Timeout) = stopped.recv_timeout(policy.interval) {
+        let (now, load) = (clock.now(), sample());
+        let elapsed = now.saturating_duration_since(last.0);
+        if elapsed.is_zero() {
+          continue;
+        }
+        let workers = resizer.slots.target();
+        let busy = load.busy.saturating_sub(last.1);
+        last = (now, load.busy);
+        let utilization = busy.as_secs_f64() / (elapsed.as_secs_f64() * (workers as f64));
+        let utilization = utilization.min(1.0);
+        let wanted = policy.wanted(workers, &load, utilization, now, &mut underused_since);
+        let Some(wanted) = wanted else {
+          continue;
+        };
+        let why = format!(
+          "by the autoscaler: {} tasks queued{}, workers {:.0}% busy",
+          load.queued,
+          load.oldest_wait.map_or_else(String::new, |wait| {
+            format!(", the oldest for {}ms", wait.as_millis())
+          }),
+          utilization * 100.0
+        );
+        // Fails only once the pool is shut down
+        if resizer.resize(wanted, &why).is_err() {
+          return;
+        }
+      }
+    });
+
+    Ok(Self {
+      stop: Some(stop),
+      handle: Some(handle),
+    })
+  }
+}
+
+impl Policy {
+  /// Workers to resize to from `workers` under `load` at `now`, if the pool
+  /// should change; `underused_since` tracks how long it's been too large
+  fn wanted(
+    &self,
+    workers: usize,
+    load: &Load,
+    utilization: f64,
+    now: Instant,
+    underused_since: &mut Option<Instant>
+  ) -> Option<usize> {
+    // Workers the recent work would keep busy at the target utilization
+    let needed = ((utilization * (workers as f64)) / self.target_utilization).ceil() as usize;
+    let needed = needed.clamp(self.min, self.max);
+    let backlogged =
+      load.queued > workers || load.oldest_wait.is_some_and(|wait| wait >= self.max_wait);
+
+    if backlogged || needed >= workers {
+      *underused_since = None;
+      let wanted = if backlogged { needed.max(workers + 1).min(self.max) } else { needed };
+      return (wanted > workers).then_some(wanted);
+    }
+    let since = *underused_since.get_or_insert(now);
+    if now.saturating_duration_since(since) < self.scale_down_delay {
+      return None;
+    }
+    *underused_since = None;
+    Some(needed)
+  }
+}
+
+impl Drop for Autoscaler {
+  fn drop(&mut self) {
+    drop(self.stop.take());
+    if let Some(handle) = self.handle.take() {
+      let _ = handle.join();
+    }
+  }
+}
@@ -0,0 +1,348 @@
+// src/background.rs - Maintenance run by workers with nothing else to do
+//
+// With `background_optimization` on, a worker about to go idle first runs a
+// background pass if one is due: at most one a second, and only after tasks
+// have run since the last, so an executor left alone goes quiet. A pass
+//
+//   ranks functions by how often they ran, promoting the `RANKED_FUNCTIONS`
+//     most run that ran at least `PROMOTE_RUNS` times;
+//   compacts the performance cache and run counts once they track over
+//     `MAX_TRACKED` functions, dropping the least run;
+//   compiles newly promoted functions: their code, normalized the way the
+//     native paths match it, and in strict mode the forms it's recognized as,
+//     is kept so their later runs skip both steps;
+//   refreshes the throttling calibration while the machine is quiet, so a
+//     recovery is noticed without waiting for the next task.
+//
+// Run counts are kept by function hash, so a promoted function is compiled
+// from the code it's next run with; a compiled function is only used by tasks
+// running that same code. Demoted functions are dropped from the compiled set.
+// `get_background_stats` reports the passes run and the time spent on each step.
+use std::collections::{ HashMap, HashSet };
+use std::sync::atomic::{ AtomicBool, AtomicU64, AtomicUsize, Ordering };
+use std::time::{ Duration, Instant };
+
+use crate::OptimizedMultiCoreExecutor as Executor;
+use crate::bindings::napi;
+use crate::clock::SharedClock;
+use crate::events::EventLog;
+use crate::strict::{ self, Recognized };
+use crate::sync::{ Arc, Mutex, lock_recovering };
+use crate::throttle::ThrottleMonitor;
+use crate::{ OptimizedWorkerTask, PerformanceMetric };
+
+const PASS_INTERVAL: Duration = Duration::from_secs(1);
+/// Functions kept compiled, at most
+const RANKED_FUNCTIONS: usize = 32;
+/// Runs before a function can be promoted
+const PROMOTE_RUNS: u32 = 100;
+/// Functions the performance cache and run counts track before compaction
+const MAX_TRACKED: usize = 4096;
+
+/// A step of a background pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+  Ranking,
+  Compaction,
+  Precompile,
+  Calibration,
+}
+
+impl Step {
+  const ALL: [Step; 4] = [Step::Ranking, Step::Compaction, Step::Precompile, Step::Calibration];
+}
+
+/// A hot function prepared ahead of its next run
+#[derive(Debug)]
+pub struct CompiledFunction {
+  code: String,
+  // `code` as the native paths match it
+  clean: String,
+  // In strict mode, what `code` is recognized as, or why it's rejected
+  recognized: Option<Result<Recognized, String>>,
+}
+
+impl CompiledFunction {
+  pub fn new(code: &str, strict: bool) -> Self {
+    Self {
+      code: code.to_string(),
+      clean: Executor::clean_code(code).into_owned(),
+      recognized: strict.then(|| strict::recognize(code)),
+    }
+  }
+
+  /// The code the native paths run for `data`: in strict mode its canonical
+  /// form, or why strict mode rejects it
+  pub fn function_code(&self, data: &serde_json::Value) -> Result<&str, String> {
+    match &self.recognized {
+      Some(Ok(recognized)) => recognized.canonical(data),
+      Some(Err(error)) => Err(error.clone()),
+      None => Ok(&self.clean),
+    }
+  }
+}
+
+/// Promoted functions, compiled or waiting for their code
+#[derive(Debug, Default)]
+struct Compiled {
+  functions: HashMap<String, Arc<CompiledFunction>>,
+  // Promoted functions by hash, with their code once a worker has run them
+  wanted: HashMap<String, Option<String>>,
+}
+
+/// What `get_background_stats` reports
+#[napi(object)]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BackgroundStats {
+  pub enabled: bool,
+  pub passes: u32,
+  /// Milliseconds spent ranking hot functions
+  pub ranking_ms: f64,
+  /// Milliseconds spent compacting the performance cache and run counts
+  pub compaction_ms: f64,
+  /// Milliseconds spent compiling promoted functions
+  pub precompile_ms: f64,
+  /// Milliseconds spent refreshing the throttling calibration
+  pub calibration_ms: f64,
+  /// All of the above
+  pub total_ms: f64,
+  /// Functions currently compiled
+  pub compiled_functions: u32,
+  /// Performance cache entries and run counts dropped by compaction
+  pub compacted_entries: f64,
+}
+
+/// Background passes of an executor and what they've compiled
+pub struct BackgroundPasses {
+  enabled: AtomicBool,
+  strict: bool,
+  performance_cache: Arc<Mutex<HashMap<String, PerformanceMetric>>>,
+  hot_functions: Arc<Mutex<HashMap<String, u32>>>,
+  throttle: Arc<ThrottleMonitor>,
+  events: Arc<EventLog>,
+  compiled: Mutex<Compiled>,
+  // Lets workers skip the lock while nothing is compiled or wanted
+  watched: AtomicUsize,
+  // Tasks run, and how many had when the last pass started
+  runs: AtomicU64,
+  runs_at_pass: AtomicU64,
+  // Milliseconds since `started` at which the next pass is due
+  next_pass_ms: AtomicU64,
+  clock: SharedClock,
+  started: Instant,
+  passes: AtomicU64,
+  step_nanos: [AtomicU64; Step::ALL.len()],
+  compacted: AtomicU64,
+}
+
+impl BackgroundPasses {
+  pub(crate) fn new(
+    enabled: bool,
+    strict: bool,
+    performance_cache: Arc<Mutex<HashMap<String, PerformanceMetric>>>,
+    hot_functions: Arc<Mutex<HashMap<String, u32>>>,
+    throttle: Arc<ThrottleMonitor>,
+    events: Arc<EventLog>,
+    clock: SharedClock
+  ) -> Self {
+    Self {
+      enabled: AtomicBool::new(enabled),
+      strict,
+      performance_cache,
+      hot_functions,
+      throttle,
+      events,
+      compiled: Mutex::new(Compiled::default()),
+      watched: AtomicUsize::new(0),
+      runs: AtomicU64::new(0),
+      runs_at_pass: AtomicU64::new(0),
+      next_pass_ms: AtomicU64::new(0),
+      started: clock.now(),
+      clock,
+      passes: AtomicU64::new(0),
+      step_nanos: Default::default(),
+      compacted: AtomicU64::new(0),
+    }
+  }
+
+  /// Turn passes on or off; turning them off drops what they compiled
+  pub fn set_enabled(&self, enabled: bool) {
+    self.enabled.store(enabled, Ordering::Release);
+    if !enabled {
+      self.clear();
+    }
+  }
+
+  /// Forget what passes compiled, e.g. along with the run counts they ranked
+  pub fn clear(&self) {
+    let mut compiled = lock_recovering(&self.compiled, || {});
+    *compiled = Compiled::default();
+    self.watched.store(0, Ordering::Release);
+  }
+
+  /// Count a task run towards the next pass
+  pub fn ran(&self) {
+    self.runs.fetch_add(1, Ordering::Relaxed);
+  }
+
+  /// The compiled form of `task`'s function, if it has one; otherwise notes
+  /// the function's code if it was promoted
+  pub fn compiled(&self, task: &OptimizedWorkerTask) -> Option<Arc<CompiledFunction>> {
+    if self.watched.load(Ordering::Acquire) == 0 {
+      return None;
+    }
+    let hash = &task.optimization_hints.function_hash;
+    let mut compiled = lock_recovering(&self.compiled, || {});
+    if let Some(function) = compiled.functions.get(hash) {
+      return (function.code == task.function_code).then(|| Arc::clone(function));
+    }
+    if let Some(code @ None) = compiled.wanted.get_mut(hash) {
+      *code = Some(task.function_code.clone());
+    }
+    None
+  }
+
+  /// How long until a pass is due, if one will be
+  pub fn next_due(&self) -> Option<Duration> {
+    if !self.pending() {
+      return None;
+    }
+    let now_ms = self.elapsed_ms();
+    let due_ms = self.next_pass_ms.load(Ordering::Acquire);
+    Some(Duration::from_millis(due_ms.saturating_sub(now_ms)))
+  }
+
+  fn elapsed_ms(&self) -> u64 {
+    self.clock.now().saturating_duration_since(self.started).as_millis() as u64
+  }
+
+  fn pending(&self) -> bool {
+    self.enabled.load(Ordering::Acquire) &&
+      self.runs.load(Ordering::Relaxed) != self.runs_at_pass.load(Ordering::Acquire)
+  }
+
+  /// Run a pass on `worker_id` if one is due
+  pub fn run_if_due(&self, worker_id: usize) {
+    if !self.pending() {
+      return;
+    }
+    let now_ms = self.elapsed_ms();
+    let due_ms = self.next_pass_ms.load(Ordering::Acquire);
+    let next_ms = now_ms + (PASS_INTERVAL.as_millis() as u64);
+    // Only one worker claims each pass
+    if
+      now_ms < due_ms ||
+      self.next_pass_ms
+        .compare_exchange(due_ms, next_ms, Ordering::AcqRel, Ordering::Acquire)
+        .is_err()
+    {
+      return;
+    }
+    self.runs_at_pass.store(self.runs.load(Ordering::Relaxed), Ordering::Release);
+    self.passes.fetch_add(1, Ordering::Relaxed);
+
+    let ranked = self.timed(Step::Ranking, || self.rank(worker_id));
+    self.timed(Step::Compaction, || self.compact(&ranked, worker_id));
+    self.timed(Step::Precompile, || self.precompile(&ranked));
+    self.timed(Step::Calibration, || self.throttle.calibrate(&self.events));
+  }
+
+  fn timed<T>(&self, step: Step, run: impl FnOnce() -> T) -> T {
+    let start = self.clock.now();
+    let output = run();
+    let index = Step::ALL.iter().position(|known| *known == step).unwrap_or_default();
+    let spent = self.clock.now().saturating_duration_since(start);
+    self.step_nanos[index].fetch_add(spent.as_nanos() as u64, Ordering::Relaxed);
+    output
+  }
+
+  // Functions by run count, most run first
+  fn rank(&self, worker_id: usize) -> Vec<(String, u32)> {
+    let mut counts: Vec<(String, u32)> = self.events
+      .lock(&self.hot_functions, "hot_functions", Some(worker_id))
+      .iter()
+      .map(|(hash, count)| (hash.clone(), *count))
+      .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+  }
+
+  fn compact(&self, ranked: &[(String, u32)], worker_id: usize) {
+    if ranked.len() <= MAX_TRACKED {
+      return;
+    }
+    let dropped: HashSet<&str> = ranked[MAX_TRACKED..]
+      .iter()
+      .map(|(hash, _)| hash.as_str())
+      .collect();
+    let mut removed = 0;
+    {
+      let mut hot_functions = self.events.lock(
+        &self.hot_functions,
+        "hot_functions",
+        Some(worker_id)
+      );
+      let before = hot_functions.len();
+      hot_functions.retain(|hash, _| !dropped.contains(hash.as_str()));
+      removed += before - hot_functions.len();
+    }
+    {
+      let mut cache = self.events.lock(
+        &self.performance_cache,
+        "performance_cache",
+        Some(worker_id)
+      );
+      let before = cache.len();
+      cache.retain(|hash, _| !dropped.contains(hash.as_str()));
+      removed += before - cache.len();
+    }
+    self.compacted.fetch_add(removed as u64, Ordering::Relaxed);
+  }
+
+  fn precompile(&self, ranked: &[(String, u32)]) {
+    let promoted: HashSet<&str> = ranked
+      .iter()
+      .take(RANKED_FUNCTIONS)
+      .filter(|(_, count)| *count >= PROMOTE_RUNS)
+      .map(|(hash, _)| hash.as_str())
+      .collect();
+
+    let mut compiled = lock_recovering(&self.compiled, || {});
+    let Compiled { functions, wanted } = &mut *compiled;
+    functions.retain(|hash, _| promoted.contains(hash.as_str()));
+    wanted.retain(|hash, _| promoted.contains(hash.as_str()));
+    for hash in promoted {
+      if !functions.contains_key(hash) && !wanted.contains_key(hash) {
+        wanted.insert(hash.to_string(), None);
+      }
+    }
+    let ready: Vec<(String, String)> = wanted
+      .iter_mut()
+      .filter_map(|(hash, code)| code.take().map(|code| (hash.clone(), code)))
+      .collect();
+    for (hash, code) in ready {
+      wanted.remove(&hash);
+      functions.insert(hash, Arc::new(CompiledFunction::new(&code, self.strict)));
+    }
+    self.watched.store(functions.len() + wanted.len(), Ordering::Release);
+  }
+
+  pub fn stats(&self) -> BackgroundStats {
+    let ms = |step: Step| {
+      let index = Step::ALL.iter().position(|known| *known == step).unwrap_or_default();
+      (self.step_nanos[index].load(Ordering::Relaxed) as f64) / 1e6
+    };
+    let compiled_functions = lock_recovering(&self.compiled, || {}).functions.len();
+    BackgroundStats {
+      enabled: self.enabled.load(Ordering::Acquire),
+      passes: self.passes.load(Ordering::Relaxed).min(u32::MAX as u64) as u32,
+      ranking_ms: ms(Step::Ranking),
+      compaction_ms: ms(Step::Compaction),
+      precompile_ms: ms(Step::Precompile),
+      calibration_ms: ms(Step::Calibration),
+      total_ms: Step::ALL.into_iter().map(ms).sum(),
+      compiled_functions: compiled_functions as u32,
+      compacted_entries: self.compacted.load(Ordering::Relaxed) as f64,
+    }
+  }
+}
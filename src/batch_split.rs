@@ -0,0 +1,61 @@
+// src/batch_split.rs - Spreading a batch's heavy tasks across workers
+//
+// Workers take tasks from the shared queue in runs, so a batch whose expensive
+// items were submitted next to each other can land on one worker while the
+// rest finish early and idle. When a batch mixes light and heavy tasks, each
+// task is weighed by its `complexity` and `estimated_memory` hints and sent to
+// a worker's inbox instead: the heaviest first, each to the least loaded
+// worker, then the light ones in runs of submission order filling each worker
+// up to an even share. A worker that falls behind still has tasks stolen from
+// its inbox, so a wrong estimate costs balance, not progress.
+use crate::OptimizationHints;
+
+// Estimated memory counted as one more unit of work, on top of the first
+const BYTES_PER_UNIT: f64 = 1024.0 * 1024.0;
+
+/// Relative cost of a task: 1, 4 or 16 by complexity, scaled up by every MiB
+/// of estimated memory
+pub fn weight(hints: &OptimizationHints) -> f64 {
+  let complexity = match hints.complexity.as_str() {
+    "low" => 1.0,
+    "high" => 16.0,
+    _ => 4.0,
+  };
+  complexity * (1.0 + (hints.estimated_memory.max(0) as f64) / BYTES_PER_UNIT)
+}
+
+/// The worker for each task, in task order, or `None` when the tasks weigh
+/// the same and the shared queue already spreads them evenly
+pub fn split(weights: &[f64], workers: usize) -> Option<Vec<usize>> {
+  let lightest = weights.iter().copied().fold(f64::INFINITY, f64::min);
+  if workers < 2 || weights.iter().all(|&weight| weight == lightest) {
+    return None;
+  }
+  let share = weights.iter().sum::<f64>() / (workers as f64);
+  let mut loads = vec![0.0; workers];
+  let mut assigned = vec![0; weights.len()];
+
+  let mut heavy: Vec<usize> = (0..weights.len()).filter(|&i| weights[i] > lightest).collect();
+  heavy.sort_by(|&a, &b| weights[b].total_cmp(&weights[a]));
+  for i in heavy {
+    let worker = least_loaded(&loads);
+    assigned[i] = worker;
+    loads[worker] += weights[i];
+  }
+
+  let mut worker = 0;
+  for i in (0..weights.len()).filter(|&i| weights[i] == lightest) {
+    while worker < workers && loads[worker] + lightest > share {
+      worker += 1;
+    }
+    // Every worker has its share; what's left goes where it evens things most
+    let target = if worker < workers { worker } else { least_loaded(&loads) };
+    assigned[i] = target;
+    loads[target] += lightest;
+  }
+  Some(assigned)
+}
+
+fn least_loaded(loads: &[f64]) -> usize {
+  (0..loads.len()).min_by(|&a, &b| loads[a].total_cmp(&loads[b])).unwrap_or(0)
+}
@@ -0,0 +1,46 @@
+// src/batch_split_tests.rs - Weighing batch tasks and spreading the heavy ones
+use crate::batch_split::{ split, weight };
+use crate::task_spec;
+
+fn weighing(complexity: &str, estimated_memory: i64) -> f64 {
+  let mut hints = task_spec::resolve_hints("x => x * 2", &[]).expect("hints");
+  hints.complexity = complexity.to_string();
+  hints.estimated_memory = estimated_memory;
+  weight(&hints)
+}
+
+#[test]
+fn weight_grows_with_complexity_and_memory() {
+  assert_eq!(weighing("low", 0), 1.0);
+  assert_eq!(weighing("medium", 0), 4.0);
+  assert_eq!(weighing("high", 0), 16.0);
+  // Every MiB of estimated memory counts as one more unit
+  assert_eq!(weighing("medium", 2 * 1024 * 1024), 12.0);
+  assert_eq!(weighing("low", -1), 1.0);
+}
+
+#[test]
+fn even_batches_are_left_to_the_shared_queue() {
+  assert_eq!(split(&[4.0; 6], 3), None);
+  assert_eq!(split(&[1.0, 16.0], 1), None);
+}
+
+#[test]
+fn heavy_tasks_go_to_different_workers() {
+  assert_eq!(split(&[16.0, 16.0, 16.0, 1.0, 1.0, 1.0], 3), Some(vec![0, 1, 2, 0, 1, 2]));
+}
+
+#[test]
+fn light_tasks_fill_each_worker_in_runs_up_to_its_share() {
+  let weights = [4.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0];
+  assert_eq!(split(&weights, 2), Some(vec![0, 0, 0, 1, 1, 1, 1, 1, 1]));
+
+  // What's left once every worker has its share goes to the least loaded
+  let weights = [16.0, 16.0, 1.0, 1.0, 1.0];
+  let assigned = split(&weights, 2).expect("split");
+  let mut loads = [0.0; 2];
+  for (worker, weight) in assigned.iter().zip(weights) {
+    loads[*worker] += weight;
+  }
+  assert_eq!(loads, [18.0, 17.0]);
+}
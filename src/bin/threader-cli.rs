@@ -0,0 +1,187 @@
+// src/bin/threader-cli.rs - Run a task spec on the native engine, without Node
+//
+// For cron jobs and for checking specs outside the app. Built with the `cli`
+// feature and without `node`:
+//
+//   cargo build --release --no-default-features --features cli
+//   threader-cli pipeline.yaml --results results.json --metrics metrics.json
+//
+// Results are written as a JSON array in spec order, each with its task's name;
+// metrics as a JSON object with counts, wall time and the executor's stats.
+// Exits 0 when every task succeeded, 1 when any failed and 2 when the spec or
+// the arguments are invalid.
+// napi's symbols only exist inside a Node process, so the binary can't link
+#[cfg(feature = "node")]
+compile_error!(
+  "threader-cli runs without Node: build it with --no-default-features --features cli"
+);
+
+use std::path::{ Path, PathBuf };
+use std::process::ExitCode;
+use std::time::{ Duration, Instant };
+
+use serde_json::{ Value, json };
+use threader::OptimizedMultiCoreExecutor;
+use threader::executor_config::ExecutorConfig;
+use threader::task_spec;
+
+const USAGE: &str =
+  "Usage: threader-cli <spec> [--format json|yaml] [--workers N] [--wait-ms N] \
+   [--results FILE] [--metrics FILE]";
+
+// Time to wait for every result unless `--wait-ms` says otherwise
+const DEFAULT_WAIT: Duration = Duration::from_secs(300);
+
+#[derive(Debug)]
+struct Args {
+  spec: PathBuf,
+  format: Option<String>,
+  workers: Option<u32>,
+  wait: Duration,
+  results: Option<PathBuf>,
+  metrics: Option<PathBuf>,
+}
+
+impl Args {
+  fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+    let mut spec = None;
+    let mut parsed = Args {
+      spec: PathBuf::new(),
+      format: None,
+      workers: None,
+      wait: DEFAULT_WAIT,
+      results: None,
+      metrics: None,
+    };
+    while let Some(arg) = args.next() {
+      let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+      match arg.as_str() {
+        "--format" => {
+          parsed.format = Some(value()?);
+        }
+        "--workers" => {
+          parsed.workers = Some(number(&arg, &value()?)?);
+        }
+        "--wait-ms" => {
+          parsed.wait = Duration::from_millis(number(&arg, &value()?)? as u64);
+        }
+        "--results" => {
+          parsed.results = Some(value()?.into());
+        }
+        "--metrics" => {
+          parsed.metrics = Some(value()?.into());
+        }
+        flag if flag.starts_with("--") => {
+          return Err(format!("Unknown option {}", flag));
+        }
+        path if spec.is_none() => {
+          spec = Some(PathBuf::from(path));
+        }
+        extra => {
+          return Err(format!("Unexpected argument {}", extra));
+        }
+      }
+    }
+    parsed.spec = spec.ok_or("Missing spec file")?;
+    // YAML by extension unless `--format` says otherwise
+    if parsed.format.is_none() && is_yaml(&parsed.spec) {
+      parsed.format = Some("yaml".to_string());
+    }
+    Ok(parsed)
+  }
+}
+
+fn number(flag: &str, value: &str) -> Result<u32, String> {
+  value.parse().map_err(|_| format!("{} takes a whole number, got '{}'", flag, value))
+}
+
+fn is_yaml(path: &Path) -> bool {
+  path.extension().is_some_and(|extension| extension == "yaml" || extension == "yml")
+}
+
+fn write(path: Option<&Path>, value: &Value) -> Result<(), String> {
+  let text = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+  match path {
+    Some(path) =>
+      std::fs::write(path, text + "\n").map_err(|e| {
+        format!("Can't write {}: {}", path.display(), e)
+      }),
+    None => {
+      println!("{}", text);
+      Ok(())
+    }
+  }
+}
+
+/// Run the spec; whether every task succeeded
+fn run(args: &Args) -> Result<bool, String> {
+  let spec = std::fs::read_to_string(&args.spec).map_err(|e| {
+    format!("Can't read {}: {}", args.spec.display(), e)
+  })?;
+  let names: Vec<String> = task_spec::import(&spec, args.format.as_deref())
+    .map_err(|e| e.to_string())?
+    .nodes.into_iter()
+    .map(|node| node.name)
+    .collect();
+
+  let config = ExecutorConfig { worker_count: args.workers, ..ExecutorConfig::default() };
+  let executor = OptimizedMultiCoreExecutor::new(Some(config)).map_err(|e| e.to_string())?;
+  let start = Instant::now();
+  let task_ids = executor
+    .submit_task_spec(spec, args.format.clone())
+    .map_err(|e| e.to_string())?;
+
+  let deadline = start + args.wait;
+  let mut failed = 0;
+  let mut results = Vec::with_capacity(task_ids.len());
+  for (name, task_id) in names.into_iter().zip(task_ids) {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    let wait_ms = remaining.as_millis().min(u32::MAX as u128) as u32;
+    let mut entry = match executor.get_result_by_id(task_id.clone(), Some(wait_ms)) {
+      Ok(result) => serde_json::to_value(&result).map_err(|e| e.to_string())?,
+      Err(e) => json!({ "id": task_id, "error": e.to_string() }),
+    };
+    if !entry["error"].is_null() {
+      failed += 1;
+    }
+    if let Value::Object(fields) = &mut entry {
+      fields.insert("name".to_string(), Value::String(name));
+    }
+    results.push(entry);
+  }
+  let wall_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+  let metrics = json!({
+    "tasks": results.len(),
+    "succeeded": results.len() - failed,
+    "failed": failed,
+    "wall_ms": wall_ms,
+    "workers": executor.worker_count(),
+    "stats": executor.stats_json(),
+  });
+  let _ = executor.shutdown();
+
+  write(args.results.as_deref(), &Value::Array(results))?;
+  if let Some(path) = args.metrics.as_deref() {
+    write(Some(path), &metrics)?;
+  }
+  Ok(failed == 0)
+}
+
+fn main() -> ExitCode {
+  let args = match Args::parse(std::env::args().skip(1)) {
+    Ok(args) => args,
+    Err(message) => {
+      eprintln!("{}\n{}", message, USAGE);
+      return ExitCode::from(2);
+    }
+  };
+  match run(&args) {
+    Ok(true) => ExitCode::SUCCESS,
+    Ok(false) => ExitCode::from(1),
+    Err(message) => {
+      eprintln!("{}", message);
+      ExitCode::from(2)
+    }
+  }
+}
@@ -0,0 +1,113 @@
+// src/binary.rs - Raw byte results, carried to JavaScript as Buffers
+//
+// Fast paths normally return JSON text. Functions submitted with operation
+// type "binary_operations" build bytes (encoding a string, decoding base64 or
+// hex, packing a byte array) and return them as-is in
+// `OptimizedWorkerResult::binary` rather than as base64 inside JSON. Other
+// types never take this path: code that merely mentions `Buffer.from` may do
+// anything with the bytes.
+use serde::{ Deserialize, Serialize };
+
+/// What a native fast path produced
+#[derive(Debug, Clone, PartialEq)]
+pub enum FastPathOutput {
+  /// JSON text, returned in `OptimizedWorkerResult::result`
+  Json(String),
+  /// Raw bytes, returned in `OptimizedWorkerResult::binary`
+  Bytes(Vec<u8>),
+}
+
+/// Bytes of a binary result; a `Buffer` in JavaScript
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ResultBytes(pub Vec<u8>);
+
+impl ResultBytes {
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+}
+
+/// UTF-8 bytes of a string, as `new TextEncoder().encode(s)` or `Buffer.from(s)`
+pub fn utf8(s: &str) -> Vec<u8> {
+  s.as_bytes().to_vec()
+}
+
+/// Bytes of a numeric array, wrapped into 0..=255 like `Uint8Array.from(xs)`
+pub fn from_numbers(values: &[serde_json::Value]) -> Result<Vec<u8>, String> {
+  values
+    .iter()
+    .map(|value| {
+      let number = value.as_f64().ok_or_else(|| format!("Expected number, got {}", value))?;
+      // ToUint8: NaN and infinities become 0, anything else is truncated modulo 256
+      Ok(if number.is_finite() { number.trunc().rem_euclid(256.0) as u8 } else { 0 })
+    })
+    .collect()
+}
+
+/// Decode standard or URL-safe base64, ignoring whitespace and padding
+pub fn from_base64(s: &str) -> Result<Vec<u8>, String> {
+  let mut bytes = Vec::with_capacity((s.len() * 3) / 4);
+  let mut buffer = 0u32;
+  let mut bits = 0;
+
+  for c in s.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+    let sextet = match c {
+      'A'..='Z' => (c as u32) - ('A' as u32),
+      'a'..='z' => (c as u32) - ('a' as u32) + 26,
+      '0'..='9' => (c as u32) - ('0' as u32) + 52,
+      '+' | '-' => 62,
+      '/' | '_' => 63,
+      _ => {
+        return Err(format!("Invalid base64 character '{}'", c));
+      }
+    };
+    buffer = (buffer << 6) | sextet;
+    bits += 6;
+    if bits >= 8 {
+      bits -= 8;
+      bytes.push((buffer >> bits) as u8);
+      buffer &= (1 << bits) - 1;
+    }
+  }
+  Ok(bytes)
+}
+
+/// Decode a hex string of whole bytes
+pub fn from_hex(s: &str) -> Result<Vec<u8>, String> {
+  if !s.len().is_multiple_of(2) {
+    return Err(format!("Hex string has an odd number of digits ({})", s.len()));
+  }
+  (0..s.len())
+    .step_by(2)
+    .map(|i| {
+      s.get(i..i + 2)
+        .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+        .ok_or_else(|| format!("Invalid hex digits at offset {}", i))
+    })
+    .collect()
+}
+
+#[cfg(feature = "node")]
+mod js {
+  use napi::bindgen_prelude::{ Buffer, FromNapiValue, ToNapiValue };
+  use napi::{ Result, sys };
+
+  use super::ResultBytes;
+
+  impl ToNapiValue for ResultBytes {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> Result<sys::napi_value> {
+      unsafe { Buffer::to_napi_value(env, Buffer::from(val.0)) }
+    }
+  }
+
+  impl FromNapiValue for ResultBytes {
+    unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> Result<Self> {
+      let buffer = unsafe { Buffer::from_napi_value(env, napi_val)? };
+      Ok(ResultBytes(buffer.to_vec()))
+    }
+  }
+}
@@ -0,0 +1,60 @@
+// src/bindings.rs - Node bindings, or a plain Rust API without them
+//
+// Executor code imports `napi`, `Error`, `Result` and `Status` from here
+// instead of the napi crates. With the `node` feature (the default, used by the npm package)
+// `#[napi]` exports items to JavaScript and fallible methods return
+// `napi::Result`. Without it napi isn't compiled at all: `#[napi]` is a no-op
+// and `Error` is a plain message with a status, so Rust services can embed the
+// same engine, and the `python` feature can wrap it (see `python.rs`).
+
+#[cfg(feature = "node")]
+pub use napi::{ Error, Result, Status };
+
+#[cfg(feature = "node")]
+pub use napi_derive::napi;
+
+#[cfg(not(feature = "node"))]
+pub use threader_macros::napi;
+
+/// Kind of failure, for the few that callers handle specifically; the subset
+/// of `napi::Status` the executor uses
+#[cfg(not(feature = "node"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+  GenericFailure,
+  /// The shared queue had no room for a submission
+  QueueFull,
+}
+
+/// Why an executor call failed
+#[cfg(not(feature = "node"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+  pub status: Status,
+  pub reason: String,
+}
+
+#[cfg(not(feature = "node"))]
+impl Error {
+  /// Same constructors as `napi::Error`, so call sites work under both builds
+  pub fn new(status: Status, reason: impl Into<String>) -> Self {
+    Self { status, reason: reason.into() }
+  }
+
+  pub fn from_reason(reason: impl Into<String>) -> Self {
+    Self::new(Status::GenericFailure, reason)
+  }
+}
+
+#[cfg(not(feature = "node"))]
+impl std::fmt::Display for Error {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(&self.reason)
+  }
+}
+
+#[cfg(not(feature = "node"))]
+impl std::error::Error for Error {}
+
+#[cfg(not(feature = "node"))]
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,37 @@
+// src/bindings_tests.rs - The executor as a plain Rust library, without napi
+use std::error::Error as StdError;
+
+use crate::bindings::{ Error, Status };
+use crate::executor_config::ExecutorConfig;
+use crate::{ OptimizedMultiCoreExecutor, task_spec };
+
+// How an embedding service would call the engine: errors convert with `?`
+fn double(executor: &OptimizedMultiCoreExecutor, data: &str) -> Result<String, Box<dyn StdError>> {
+  let hints = task_spec::resolve_hints("x => x * 2", &[])?;
+  let task_id = executor.submit_task("x => x * 2".into(), data.into(), hints, None, None)?;
+  let result = executor.get_result_by_id(task_id, Some(5000))?;
+  Ok(result.result.unwrap_or_default())
+}
+
+#[test]
+fn executor_is_usable_without_node() {
+  let config = ExecutorConfig {
+    worker_count: Some(1),
+    queue_capacity: Some(1),
+    ..ExecutorConfig::default()
+  };
+  let executor = OptimizedMultiCoreExecutor::new(Some(config)).expect("executor");
+  assert_eq!(double(&executor, "21").unwrap(), "42");
+
+  // Holds the only worker while the queue fills
+  executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 200}"#.into())).unwrap();
+  let hints = task_spec::resolve_hints("x => x * 2", &[]).unwrap();
+  let full = (0..3)
+    .map(|_| executor.submit_task("x => x * 2".into(), "1".into(), hints.clone(), None, None))
+    .find_map(Result::err)
+    .expect("a full queue");
+  // Callers can tell a full queue from other failures
+  assert_eq!(full.status, Status::QueueFull);
+  assert_eq!(full, Error::new(Status::QueueFull, full.to_string()));
+  executor.shutdown().unwrap();
+}
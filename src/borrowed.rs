@@ -0,0 +1,126 @@
+// src/borrowed.rs - Task input read in place from the caller's memory
+//
+// `submit_borrowed` takes a task's data as a Buffer and keeps a reference to
+// it instead of copying it into a string, which matters for payloads of
+// hundreds of megabytes. The task holds the reference, so the Buffer can't be
+// collected while the task is queued. In return the caller must leave the
+// bytes alone until the task's result arrives, or until `release_borrowed`
+// confirms that no worker will read them.
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{ AtomicU8, Ordering };
+
+use crate::sync::{ Arc, Mutex, lock_recovering };
+
+// Lifecycle of borrowed data: queued until a worker claims it for reading, or
+// the caller releases it first. Neither can happen after the other.
+const QUEUED: u8 = 0;
+const READING: u8 = 1;
+const RELEASED: u8 = 2;
+
+// Tasks dropped without running (cancelled, shed, failed dependencies) leave
+// their entry behind; the table sweeps those whenever it doubles past this
+const SWEEP_FROM: usize = 64;
+
+/// Bytes a task reads without owning them: a Buffer from JavaScript, or any
+/// shared byte container from Rust. A plain `std` Arc even under loom, which
+/// has nothing to model in bytes that are only read.
+pub type SharedBytes = std::sync::Arc<dyn AsRef<[u8]> + Send + Sync>;
+
+/// Input of a task submitted with `submit_borrowed`
+#[derive(Clone)]
+pub struct BorrowedData {
+  bytes: SharedBytes,
+  state: Arc<AtomicU8>,
+}
+
+impl fmt::Debug for BorrowedData {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("BorrowedData")
+      .field("len", &(*self.bytes).as_ref().len())
+      .field("state", &self.state.load(Ordering::Relaxed))
+      .finish()
+  }
+}
+
+impl BorrowedData {
+  /// Borrow `bytes`, which must be UTF-8 text like any other task data
+  pub fn new(bytes: SharedBytes) -> Result<Self, String> {
+    std::str
+      ::from_utf8((*bytes).as_ref())
+      .map_err(|e| format!("Borrowed data is not valid UTF-8: {}", e))?;
+    Ok(Self { bytes, state: Arc::new(AtomicU8::new(QUEUED)) })
+  }
+
+  /// Mark the data as being read by a worker; `false` if it was released first
+  pub fn claim(&self) -> bool {
+    match self.state.compare_exchange(QUEUED, READING, Ordering::AcqRel, Ordering::Acquire) {
+      Ok(_) => true,
+      Err(state) => state == READING,
+    }
+  }
+
+  /// The data as text, once claimed. Checked again because only the caller's
+  /// promise keeps it from changing after submission.
+  pub fn as_str(&self) -> Result<&str, String> {
+    if self.state.load(Ordering::Acquire) != READING {
+      return Err("Borrowed data was released before the task ran".to_string());
+    }
+    std::str
+      ::from_utf8((*self.bytes).as_ref())
+      .map_err(|_| "Borrowed data changed after submission and is no longer UTF-8".to_string())
+  }
+}
+
+/// A JavaScript Buffer held for the lifetime of a task
+#[cfg(feature = "node")]
+pub struct JsBytes(napi::bindgen_prelude::Buffer);
+
+#[cfg(feature = "node")]
+impl From<napi::bindgen_prelude::Buffer> for JsBytes {
+  fn from(buffer: napi::bindgen_prelude::Buffer) -> Self {
+    Self(buffer)
+  }
+}
+
+#[cfg(feature = "node")]
+impl AsRef<[u8]> for JsBytes {
+  fn as_ref(&self) -> &[u8] {
+    self.0.as_ref()
+  }
+}
+
+// SAFETY: `Buffer` is `Send` and only hands out its bytes through `&self`;
+// nothing mutates it from Rust, and its reference is released from the JS
+// thread whichever thread drops it
+#[cfg(feature = "node")]
+unsafe impl Sync for JsBytes {}
+
+/// Lifecycle state of every borrowed task that hasn't run or been released,
+/// by task ID. Only the state: the bytes go with the task once it's done.
+#[derive(Debug, Default)]
+pub struct BorrowTable {
+  states: Mutex<HashMap<String, Arc<AtomicU8>>>,
+}
+
+impl BorrowTable {
+  pub fn track(&self, task_id: &str, data: &BorrowedData) {
+    let mut states = lock_recovering(&self.states, || {});
+    let len = states.len();
+    if len >= SWEEP_FROM && len.is_power_of_two() {
+      states.retain(|_, state| {
+        Arc::strong_count(state) > 1 && state.load(Ordering::Acquire) == QUEUED
+      });
+    }
+    states.insert(task_id.to_string(), Arc::clone(&data.state));
+  }
+
+  /// Give up a task's data before a worker reads it; `false` once one has
+  /// started, or when the task isn't a queued borrowed task
+  pub fn release(&self, task_id: &str) -> bool {
+    let Some(state) = lock_recovering(&self.states, || {}).remove(task_id) else {
+      return false;
+    };
+    state.compare_exchange(QUEUED, RELEASED, Ordering::AcqRel, Ordering::Acquire).is_ok()
+  }
+}
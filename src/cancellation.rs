@@ -0,0 +1,75 @@
+// src/cancellation.rs - Cancellation of queued and in-flight tasks
+use std::collections::HashSet;
+use std::sync::atomic::{ AtomicU64, AtomicUsize, Ordering };
+
+use crate::sync::Mutex;
+
+/// Tracks which tasks have been cancelled.
+///
+/// `cancel_all` bumps an epoch instead of listing tasks: every task stamped
+/// with an older epoch is dropped. Single cancellations are kept by ID until
+/// a worker encounters the task.
+#[derive(Debug, Default)]
+pub struct CancellationSet {
+  epoch: AtomicU64,
+  cancelled: Mutex<HashSet<String>>,
+  // Lets workers skip the lock while nothing is individually cancelled
+  cancelled_count: AtomicUsize,
+}
+
+impl CancellationSet {
+  /// Epoch to stamp on newly submitted tasks
+  pub fn current_epoch(&self) -> u64 {
+    self.epoch.load(Ordering::Acquire)
+  }
+
+  pub fn cancel(&self, task_id: String) {
+    if let Ok(mut cancelled) = self.cancelled.lock() && cancelled.insert(task_id) {
+      self.cancelled_count.fetch_add(1, Ordering::Release);
+    }
+  }
+
+  /// Cancel every task submitted so far
+  pub fn cancel_all(&self) {
+    self.epoch.fetch_add(1, Ordering::AcqRel);
+    if let Ok(mut cancelled) = self.cancelled.lock() {
+      cancelled.clear();
+      self.cancelled_count.store(0, Ordering::Release);
+    }
+  }
+
+  /// Tasks cancelled individually that no worker has dropped yet
+  #[cfg(all(test, not(feature = "loom")))]
+  pub(crate) fn pending(&self) -> usize {
+    self.cancelled_count.load(Ordering::Acquire)
+  }
+
+  /// Whether a task should be dropped, leaving its individual cancellation in place
+  pub fn is_cancelled(&self, task_id: &str, epoch: u64) -> bool {
+    epoch < self.current_epoch() ||
+      (self.cancelled_count.load(Ordering::Acquire) > 0 &&
+        self.cancelled
+          .lock()
+          .map(|cancelled| cancelled.contains(task_id))
+          .unwrap_or(false))
+  }
+
+  /// Whether a task should be dropped, consuming its individual cancellation if any
+  pub fn take(&self, task_id: &str, epoch: u64) -> bool {
+    if epoch < self.current_epoch() {
+      return true;
+    }
+    if self.cancelled_count.load(Ordering::Acquire) == 0 {
+      return false;
+    }
+
+    let removed = self.cancelled
+      .lock()
+      .map(|mut cancelled| cancelled.remove(task_id))
+      .unwrap_or(false);
+    if removed {
+      self.cancelled_count.fetch_sub(1, Ordering::Release);
+    }
+    removed
+  }
+}
@@ -0,0 +1,104 @@
+// src/canonical_json.rs - Canonical result JSON for hashing and comparison
+//
+// Follows the JSON Canonicalization Scheme (RFC 8785): no whitespace, object
+// keys sorted by UTF-16 code units, strings escaped as `JSON.stringify` does and
+// numbers formatted like JavaScript's `Number.prototype.toString`, so 42, 42.0
+// and 4.2e1 all become `42`.
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Re-serialize `json` canonically. Fails for output that isn't JSON at all,
+/// such as `NaN` or `inf` from a numeric fast path.
+pub fn canonicalize(json: &str) -> Result<String, String> {
+  let value: Value = serde_json::from_str(json).map_err(|_| {
+    format!("Result is not valid JSON, so it has no canonical form: {}", json)
+  })?;
+  let mut out = String::with_capacity(json.len());
+  write_value(&mut out, &value)?;
+  Ok(out)
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<(), String> {
+  match value {
+    Value::Null | Value::Bool(_) | Value::String(_) => {
+      out.push_str(&serde_json::to_string(value).map_err(|e| e.to_string())?);
+    }
+    Value::Number(number) =>
+      match (number.as_i64(), number.as_u64(), number.as_f64()) {
+        (Some(integer), _, _) => {
+          let _ = write!(out, "{}", integer);
+        }
+        (_, Some(integer), _) => {
+          let _ = write!(out, "{}", integer);
+        }
+        (_, _, Some(float)) => write_float(out, float),
+        _ => {
+          return Err(format!("Number {} has no canonical form", number));
+        }
+      }
+    Value::Array(items) => {
+      out.push('[');
+      for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+          out.push(',');
+        }
+        write_value(out, item)?;
+      }
+      out.push(']');
+    }
+    Value::Object(map) => {
+      let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+      entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+      out.push('{');
+      for (i, (key, item)) in entries.into_iter().enumerate() {
+        if i > 0 {
+          out.push(',');
+        }
+        out.push_str(&serde_json::to_string(key).map_err(|e| e.to_string())?);
+        out.push(':');
+        write_value(out, item)?;
+      }
+      out.push('}');
+    }
+  }
+  Ok(())
+}
+
+/// `Number.prototype.toString` for a finite double
+fn write_float(out: &mut String, value: f64) {
+  if value == 0.0 {
+    // Covers -0, which JavaScript also prints as 0
+    out.push('0');
+    return;
+  }
+  if value < 0.0 {
+    out.push('-');
+  }
+
+  // Shortest round-tripping digits and exponent, e.g. "1.5e-7"
+  let scientific = format!("{:e}", value.abs());
+  let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+  let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+  let k = digits.len() as i32;
+  // Position of the decimal point relative to the first digit
+  let n = exponent.parse::<i32>().unwrap_or(0) + 1;
+
+  if k <= n && n <= 21 {
+    out.push_str(&digits);
+    out.extend(std::iter::repeat_n('0', (n - k) as usize));
+  } else if 0 < n && n <= 21 {
+    let (whole, fraction) = digits.split_at(n as usize);
+    let _ = write!(out, "{}.{}", whole, fraction);
+  } else if -6 < n && n <= 0 {
+    out.push_str("0.");
+    out.extend(std::iter::repeat_n('0', -n as usize));
+    out.push_str(&digits);
+  } else {
+    let (first, rest) = digits.split_at(1);
+    out.push_str(first);
+    if !rest.is_empty() {
+      let _ = write!(out, ".{}", rest);
+    }
+    let _ = write!(out, "e{}{}", if n > 0 { "+" } else { "-" }, (n - 1).abs());
+  }
+}
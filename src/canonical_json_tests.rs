@@ -0,0 +1,52 @@
+// src/canonical_json_tests.rs - Canonical JSON as RFC 8785 and JavaScript print it
+use crate::canonical_json::canonicalize;
+use crate::executor_config::ExecutorConfig;
+use crate::{ OptimizedMultiCoreExecutor, task_spec };
+
+fn canonical(json: &str) -> String {
+  canonicalize(json).expect("canonical form")
+}
+
+#[test]
+fn numbers_print_as_javascript_prints_them() {
+  for (json, expected) in [
+    ("42.0", "42"),
+    ("4.2e1", "42"),
+    ("-0.0", "0"),
+    ("1.5", "1.5"),
+    ("0.000001", "0.000001"),
+    ("1e-7", "1e-7"),
+    ("123e18", "123000000000000000000"),
+    ("1e21", "1e+21"),
+    ("-1.25e-10", "-1.25e-10"),
+  ] {
+    assert_eq!(canonical(json), expected, "{}", json);
+  }
+}
+
+#[test]
+fn keys_sort_by_utf16_code_units_without_whitespace() {
+  let json = r#"{ "b": [1, 2.50], "a": { "z": null, "y": "\u00e9" } }"#;
+  assert_eq!(canonical(json), r#"{"a":{"y":"é","z":null},"b":[1,2.5]}"#);
+  // The emoji's surrogate pair sorts before U+E000, though its UTF-8 sorts after
+  assert_eq!(canonical("{\"\u{e000}\": 1, \"\u{1f600}\": 2}"), "{\"\u{1f600}\":2,\"\u{e000}\":1}");
+  assert!(canonicalize("NaN").unwrap_err().starts_with("Result is not valid JSON"));
+}
+
+#[test]
+fn canonical_results_are_opt_in_per_task() {
+  let config = ExecutorConfig { worker_count: Some(1), ..ExecutorConfig::default() };
+  let executor = OptimizedMultiCoreExecutor::new(Some(config)).expect("executor");
+  let run = |canonical_json: Option<bool>| {
+    let mut hints = task_spec::resolve_hints("x => Math.sqrt(x)", &[]).expect("hints");
+    hints.canonical_json = canonical_json;
+    let task_id = executor
+      .submit_task("x => Math.sqrt(x)".into(), "1e42".into(), hints, None, None)
+      .expect("submit");
+    executor.get_result_by_id(task_id, Some(5000)).expect("result").result.unwrap()
+  };
+  assert_eq!(run(Some(true)), "1e+21");
+  // As serde_json prints it, unlike JavaScript
+  assert_eq!(run(None), "1000000000000000000000");
+  executor.shutdown().unwrap();
+}
@@ -0,0 +1,313 @@
+// src/child.rs - Per-request child executors carved out of the pool
+//
+// A server can give each request its own slice of the pool without a pool per
+// request: `open_child` returns an ID that tasks join with the `child` hint.
+// A child caps how many of its tasks run at once, how many bytes of input its
+// unfinished tasks hold, and how long it lives. Its limits default to, and are
+// capped by, the parent's: its worker count and memory watermark. Once its
+// time budget is used up, new submissions are refused and tasks that haven't
+// started fail; `close_child` also cancels the ones still running, for when
+// the request ends first.
+use std::collections::{ HashMap, VecDeque };
+use std::sync::atomic::{ AtomicU64, Ordering };
+use std::time::{ Duration, Instant };
+
+use crate::OptimizedWorkerTask;
+use crate::bindings::napi;
+use crate::clock::SharedClock;
+use crate::priority::{ Priority, Requeue };
+use crate::sync::{ Mutex, MutexGuard, lock_recovering };
+
+/// Budget of a child executor; unset limits are the parent's
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct ChildOptions {
+  /// Tasks of the child running at once; at most the parent's worker count
+  pub max_concurrency: Option<u32>,
+  /// Input bytes the child's unfinished tasks may hold; at most the parent's
+  /// `memory_watermark_mb`
+  pub max_bytes: Option<u32>,
+  /// Milliseconds the child accepts and starts tasks for, from when it opens.
+  /// Its tasks' timeouts are cut to fit.
+  pub time_budget_ms: Option<u32>,
+}
+
+impl ChildOptions {
+  pub fn validate(&self) -> Result<(), String> {
+    if self.max_concurrency == Some(0) {
+      return Err("max_concurrency must be at least 1".to_string());
+    }
+    if self.max_bytes == Some(0) {
+      return Err("max_bytes must be at least 1".to_string());
+    }
+    if self.time_budget_ms == Some(0) {
+      return Err("time_budget_ms must be at least 1".to_string());
+    }
+    Ok(())
+  }
+}
+
+/// Snapshot returned by `get_child_status`
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct ChildStatus {
+  pub child_id: String,
+  /// Tasks of the child running now
+  pub running: u32,
+  /// Tasks submitted and not yet finished, running ones included
+  pub unfinished: u32,
+  /// Input bytes those tasks hold
+  pub bytes: f64,
+  pub max_concurrency: u32,
+  pub max_bytes: Option<f64>,
+  /// Time left of the budget, if it has one
+  pub remaining_ms: Option<f64>,
+}
+
+/// Why a child refused a task
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChildError {
+  /// Over the child's byte budget; the submitter may retry later
+  Full(String),
+  /// Unknown, closed or out of time
+  Closed(String),
+}
+
+#[derive(Debug)]
+struct Child {
+  max_concurrency: usize,
+  max_bytes: Option<usize>,
+  deadline: Option<Instant>,
+  time_budget_ms: u32,
+  running: usize,
+  // Input bytes of each unfinished task, by task ID
+  tasks: HashMap<String, usize>,
+  // Tasks waiting for one of the child's running tasks to finish
+  parked: VecDeque<(OptimizedWorkerTask, Priority)>,
+}
+
+impl Child {
+  fn bytes(&self) -> usize {
+    self.tasks.values().sum()
+  }
+
+  fn expired(&self, now: Instant) -> bool {
+    self.deadline.is_some_and(|deadline| now >= deadline)
+  }
+
+  fn out_of_time(&self, child_id: &str) -> String {
+    format!("Child executor '{}' used up its {}ms time budget", child_id, self.time_budget_ms)
+  }
+}
+
+/// Child executors by ID
+pub struct ChildTable {
+  children: Mutex<HashMap<String, Child>>,
+  next_id: AtomicU64,
+  requeue: Requeue,
+  clock: SharedClock,
+}
+
+impl ChildTable {
+  pub fn new(requeue: Requeue, clock: SharedClock) -> Self {
+    Self {
+      children: Mutex::new(HashMap::new()),
+      next_id: AtomicU64::new(0),
+      requeue,
+      clock,
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, HashMap<String, Child>> {
+    lock_recovering(&self.children, || {})
+  }
+
+  /// Open a child within a parent of `workers` workers and an optional memory
+  /// watermark; returns its ID
+  pub fn open(
+    &self,
+    options: &ChildOptions,
+    workers: usize,
+    watermark: Option<usize>
+  ) -> Result<String, String> {
+    options.validate()?;
+    let max_bytes = match (options.max_bytes.map(|bytes| bytes as usize), watermark) {
+      (Some(bytes), Some(watermark)) => Some(bytes.min(watermark)),
+      (bytes, watermark) => bytes.or(watermark),
+    };
+    let now = self.clock.now();
+    let child = Child {
+      max_concurrency: options.max_concurrency.map_or(workers, |max| (max as usize).min(workers)),
+      max_bytes,
+      deadline: options.time_budget_ms.map(|ms| now + Duration::from_millis(ms as u64)),
+      time_budget_ms: options.time_budget_ms.unwrap_or_default(),
+      running: 0,
+      tasks: HashMap::new(),
+      parked: VecDeque::new(),
+    };
+    let child_id = format!("child_{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+    self.lock().insert(child_id.clone(), child);
+    Ok(child_id)
+  }
+
+  /// Count a submitted task against its child's budget, cutting its timeout to
+  /// the time left. Tasks without the `child` hint pass through.
+  pub fn enter(&self, task: &mut OptimizedWorkerTask) -> Result<(), ChildError> {
+    let Some(child_id) = task.optimization_hints.child.as_deref() else {
+      return Ok(());
+    };
+    let now = self.clock.now();
+    let mut children = self.lock();
+    let Some(child) = children.get_mut(child_id) else {
+      return Err(ChildError::Closed(format!("Unknown or closed child executor '{}'", child_id)));
+    };
+    if child.expired(now) {
+      return Err(ChildError::Closed(child.out_of_time(child_id)));
+    }
+    let bytes = task.function_code.len() + task.data.len();
+    if let Some(max_bytes) = child.max_bytes && child.bytes() + bytes > max_bytes {
+      return Err(
+        ChildError::Full(
+          format!(
+            "Child executor '{}' is over its {} byte budget; wait for its tasks to finish",
+            child_id,
+            max_bytes
+          )
+        )
+      );
+    }
+    if let Some(deadline) = child.deadline {
+      let remaining = deadline.saturating_duration_since(now).as_millis().max(1) as u64;
+      task.timeout_ms = Some(task.timeout_ms.map_or(remaining, |timeout| timeout.min(remaining)));
+    }
+    child.tasks.insert(task.id.clone(), bytes);
+    Ok(())
+  }
+
+  /// Claim one of the child's running slots for `task`, or park it while the
+  /// child is at its limit. `on_park` runs before the lock is released, so a
+  /// requeue can't overtake it. Tasks of a closed or expired child pass through
+  /// without a slot, for `check` to fail them.
+  pub fn admit(
+    &self,
+    task: OptimizedWorkerTask,
+    priority: Priority,
+    on_park: impl FnOnce(&OptimizedWorkerTask)
+  ) -> Option<(OptimizedWorkerTask, Option<ChildSlot<'_>>)> {
+    let Some(child_id) = task.optimization_hints.child.clone() else {
+      return Some((task, None));
+    };
+    let now = self.clock.now();
+    let mut children = self.lock();
+    let Some(child) = children.get_mut(&child_id).filter(|child| !child.expired(now)) else {
+      return Some((task, None));
+    };
+    if child.running >= child.max_concurrency {
+      on_park(&task);
+      child.parked.push_back((task, priority));
+      return None;
+    }
+    child.running += 1;
+    Some((task, Some(ChildSlot { children: self, child_id })))
+  }
+
+  /// Whether `task` may still start: `Err` once its child is closed or out of time
+  pub fn check(&self, task: &OptimizedWorkerTask) -> Result<(), String> {
+    let Some(child_id) = task.optimization_hints.child.as_deref() else {
+      return Ok(());
+    };
+    let now = self.clock.now();
+    match self.lock().get(child_id) {
+      Some(child) if child.expired(now) => Err(child.out_of_time(child_id)),
+      Some(_) => Ok(()),
+      None => Err(format!("Child executor '{}' was closed", child_id)),
+    }
+  }
+
+  /// A task of the child is done with, run or not: its bytes are freed, and an
+  /// expired child is forgotten with its last task
+  pub fn finish(&self, child_id: &str, task_id: &str) {
+    let now = self.clock.now();
+    let mut children = self.lock();
+    let Some(child) = children.get_mut(child_id) else {
+      return;
+    };
+    child.tasks.remove(task_id);
+    if child.expired(now) && child.tasks.is_empty() {
+      children.remove(child_id);
+    }
+  }
+
+  /// Close a child: new submissions are refused and its parked tasks go back on
+  /// the queue to be dropped. Returns the IDs of its unfinished tasks, for the
+  /// caller to cancel; `None` if the child is unknown or already closed.
+  pub fn close(&self, child_id: &str) -> Option<Vec<String>> {
+    let child = self.lock().remove(child_id)?;
+    self.requeue.send(child.parked.into_iter().collect());
+    Some(child.tasks.into_keys().collect())
+  }
+
+  pub fn status(&self, child_id: &str) -> Option<ChildStatus> {
+    let now = self.clock.now();
+    let children = self.lock();
+    let child = children.get(child_id)?;
+    Some(ChildStatus {
+      child_id: child_id.to_string(),
+      running: child.running.min(u32::MAX as usize) as u32,
+      unfinished: child.tasks.len().min(u32::MAX as usize) as u32,
+      bytes: child.bytes() as f64,
+      max_concurrency: child.max_concurrency.min(u32::MAX as usize) as u32,
+      max_bytes: child.max_bytes.map(|bytes| bytes as f64),
+      remaining_ms: child.deadline.map(|deadline| {
+        deadline.saturating_duration_since(now).as_secs_f64() * 1000.0
+      }),
+    })
+  }
+
+  fn release(&self, child_id: &str) {
+    let now = self.clock.now();
+    let released: Vec<_> = {
+      let mut children = self.lock();
+      let Some(child) = children.get_mut(child_id) else {
+        return;
+      };
+      child.running = child.running.saturating_sub(1);
+      // Out of time, every parked task is due to fail rather than wait its turn
+      let count = if child.expired(now) { child.parked.len() } else { 1 };
+      let count = count.min(child.parked.len());
+      child.parked.drain(..count).collect()
+    };
+    self.requeue.send(released);
+  }
+}
+
+/// A running task's claim on its child's concurrency; frees it when dropped
+pub struct ChildSlot<'a> {
+  children: &'a ChildTable,
+  child_id: String,
+}
+
+impl Drop for ChildSlot<'_> {
+  fn drop(&mut self) {
+    self.children.release(&self.child_id);
+  }
+}
+
+/// A task's share of its child's budget, freed when dropped however the task ends
+pub struct ChildTask<'a> {
+  children: &'a ChildTable,
+  child_id: String,
+  task_id: String,
+}
+
+impl<'a> ChildTask<'a> {
+  pub fn new(children: &'a ChildTable, child_id: String, task_id: String) -> Self {
+    Self { children, child_id, task_id }
+  }
+}
+
+impl Drop for ChildTask<'_> {
+  fn drop(&mut self) {
+    self.children.finish(&self.child_id, &self.task_id);
+  }
+}
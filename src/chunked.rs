@@ -0,0 +1,67 @@
+// src/chunked.rs - Element-wise maps over large arrays, reported chunk by chunk
+//
+// A task with the `chunk_size` hint maps its function over each element of an
+// array input. Chunks run in parallel, and each one is published as a
+// `partial_result` event the moment it is done, so a caller can show progress
+// and early rows long before the whole array is mapped.
+//
+// Each chunk starts at a preemption point. There a worker running anything
+// below critical priority first serves the critical tasks queued since, so
+// they wait for a chunk to finish rather than for the whole array.
+use rayon::prelude::*;
+use serde::Serialize;
+
+/// A finished chunk, the message of a `partial_result` event
+#[derive(Debug, Clone, Serialize)]
+pub struct PartialResult {
+  pub task_id: String,
+  pub chunk_index: usize,
+  pub chunk_count: usize,
+  /// Input index of the chunk's first element
+  pub offset: usize,
+  pub values: Vec<serde_json::Value>,
+}
+
+/// Map `values` through `map` in chunks of `chunk_size`, calling `preempt`
+/// before each chunk and `on_chunk` as each finishes (in completion order, not
+/// input order). The result keeps input order; the first failing element fails
+/// the whole map.
+pub fn map_chunks<F>(
+  task_id: &str,
+  values: &[serde_json::Value],
+  chunk_size: usize,
+  map: F,
+  preempt: &(dyn Fn() + Sync),
+  on_chunk: &(dyn Fn(PartialResult) + Sync)
+) -> Result<Vec<serde_json::Value>, String>
+  where F: Fn(&serde_json::Value) -> Result<serde_json::Value, String> + Sync
+{
+  if chunk_size == 0 {
+    return Err("chunk_size must be at least 1".to_string());
+  }
+  let chunk_count = values.len().div_ceil(chunk_size);
+
+  let chunks: Vec<Vec<serde_json::Value>> = values
+    .par_chunks(chunk_size)
+    .enumerate()
+    .map(|(chunk_index, chunk)| {
+      preempt();
+      let offset = chunk_index * chunk_size;
+      let mapped = chunk
+        .iter()
+        .enumerate()
+        .map(|(i, value)| map(value).map_err(|e| format!("Element {}: {}", offset + i, e)))
+        .collect::<Result<Vec<_>, String>>()?;
+      on_chunk(PartialResult {
+        task_id: task_id.to_string(),
+        chunk_index,
+        chunk_count,
+        offset,
+        values: mapped.clone(),
+      });
+      Ok(mapped)
+    })
+    .collect::<Result<_, String>>()?;
+
+  Ok(chunks.concat())
+}
@@ -0,0 +1,87 @@
+// src/clock.rs - Time source abstraction for deterministic timing
+use std::sync::{ Arc, Mutex };
+use std::time::{ Duration, Instant, SystemTime, UNIX_EPOCH };
+
+/// Source of monotonic and wall-clock time used by the executor
+pub trait Clock: Send + Sync {
+  /// Monotonic time, used for durations, deadlines and timeouts
+  fn now(&self) -> Instant;
+
+  /// Wall-clock time, used for task IDs and timestamps
+  fn system_now(&self) -> SystemTime;
+
+  /// Wall-clock time since the Unix epoch
+  fn unix_time(&self) -> Duration {
+    self.system_now().duration_since(UNIX_EPOCH).unwrap_or_default()
+  }
+}
+
+/// Clock handle shared between the executor and its workers
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Real clock backed by the operating system
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl SystemClock {
+  /// Shared handle to the system clock
+  pub fn shared() -> SharedClock {
+    Arc::new(SystemClock)
+  }
+}
+
+impl Clock for SystemClock {
+  fn now(&self) -> Instant {
+    Instant::now()
+  }
+
+  fn system_now(&self) -> SystemTime {
+    SystemTime::now()
+  }
+}
+
+/// Manually driven clock for tests: time only moves when advanced
+#[derive(Debug)]
+pub struct MockClock {
+  base_instant: Instant,
+  base_system: SystemTime,
+  elapsed: Mutex<Duration>,
+}
+
+impl MockClock {
+  /// Create a mock clock frozen at the current real time
+  pub fn new() -> Self {
+    Self {
+      base_instant: Instant::now(),
+      base_system: SystemTime::now(),
+      elapsed: Mutex::new(Duration::ZERO),
+    }
+  }
+
+  /// Move the clock forward
+  pub fn advance(&self, duration: Duration) {
+    let mut elapsed = self.elapsed.lock().unwrap_or_else(|e| e.into_inner());
+    *elapsed += duration;
+  }
+
+  /// Total time the clock has been advanced
+  pub fn elapsed(&self) -> Duration {
+    *self.elapsed.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
+
+impl Default for MockClock {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Clock for MockClock {
+  fn now(&self) -> Instant {
+    self.base_instant + self.elapsed()
+  }
+
+  fn system_now(&self) -> SystemTime {
+    self.base_system + self.elapsed()
+  }
+}
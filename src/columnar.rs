@@ -0,0 +1,137 @@
+// src/columnar.rs - Column-at-a-time execution for homogeneous batches
+//
+// A batch that runs one function over rows of the same shape spends most of
+// its time parsing each row and working out again what the function does.
+// When a batch qualifies, its rows are split into segments. The first worker
+// to pick up a row of a segment converts the whole segment to columns, runs
+// the operation over them in one tight loop, and keeps the output column; the
+// segment's other rows just read their value back. Every row is still its own
+// task on the queue, so status, cancellation and results work as for any
+// other batch.
+use std::sync::OnceLock;
+
+use crate::sync::Arc;
+
+/// Rows converted and computed together unless the profile says otherwise.
+/// Small enough that a worker waiting on another's conversion isn't held up for long.
+pub const SEGMENT_ROWS: usize = 256;
+/// Smaller batches aren't worth the conversion
+pub const MIN_BATCH_ROWS: usize = 32;
+
+/// An operation with a column-at-a-time implementation: the `{ a, b }` pair
+/// operations of the mathematical fast path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernel {
+  Multiply,
+  Add,
+  Subtract,
+  Divide,
+}
+
+impl Kernel {
+  /// Kernel for a batch's function, matched the way the row path matches it
+  pub fn for_function(function_code: &str, operation_type: &str) -> Option<Self> {
+    if operation_type != "mathematical" {
+      return None;
+    }
+    let clean_fn = function_code.replace([' ', '\n', '\t'], "").to_lowercase();
+    if clean_fn.contains('*') {
+      Some(Kernel::Multiply)
+    } else if clean_fn.contains('+') {
+      Some(Kernel::Add)
+    } else if clean_fn.contains('-') {
+      Some(Kernel::Subtract)
+    } else if clean_fn.contains('/') {
+      Some(Kernel::Divide)
+    } else {
+      None
+    }
+  }
+
+  fn run(self, a: &[f64], b: &[f64]) -> Vec<f64> {
+    let pairs = a.iter().zip(b);
+    match self {
+      Kernel::Multiply => pairs.map(|(a, b)| a * b).collect(),
+      Kernel::Add => pairs.map(|(a, b)| a + b).collect(),
+      Kernel::Subtract => pairs.map(|(a, b)| a - b).collect(),
+      Kernel::Divide => pairs.map(|(a, b)| a / b).collect(),
+    }
+  }
+}
+
+#[derive(Debug)]
+struct Columns {
+  b: Vec<f64>,
+  // Whether both operands were integer literals, for number formatting
+  integral: Vec<bool>,
+  output: Vec<f64>,
+}
+
+impl Columns {
+  /// Columns of `rows`, or `None` unless every row is an object with numeric
+  /// `a` and `b` fields
+  fn convert(kernel: Kernel, rows: &[String]) -> Option<Self> {
+    let mut a = Vec::with_capacity(rows.len());
+    let mut b = Vec::with_capacity(rows.len());
+    let mut integral = Vec::with_capacity(rows.len());
+    for row in rows {
+      let value: serde_json::Value = serde_json::from_str(row).ok()?;
+      let (a_value, b_value) = (value.get("a")?, value.get("b")?);
+      a.push(a_value.as_f64()?);
+      b.push(b_value.as_f64()?);
+      integral.push(is_integer(a_value) && is_integer(b_value));
+    }
+    let output = kernel.run(&a, &b);
+    Some(Self { b, integral, output })
+  }
+}
+
+fn is_integer(value: &serde_json::Value) -> bool {
+  value.is_i64() || value.is_u64()
+}
+
+#[derive(Debug)]
+struct Segment {
+  kernel: Kernel,
+  rows: Vec<String>,
+  // Converted on first use; `None` if the rows turned out not to share a shape
+  columns: OnceLock<Option<Columns>>,
+}
+
+/// A batch row's place in its segment
+#[derive(Debug, Clone)]
+pub struct ColumnarRow {
+  segment: Arc<Segment>,
+  index: usize,
+}
+
+impl ColumnarRow {
+  /// The row's output and whether its operands were integers, computing the
+  /// whole segment if this is its first row to run. `None` sends the row down
+  /// the row path, as when the segment's rows don't share a shape.
+  pub fn output(&self) -> Option<Result<(f64, bool), String>> {
+    let segment = &self.segment;
+    let columns = segment.columns
+      .get_or_init(|| Columns::convert(segment.kernel, &segment.rows))
+      .as_ref()?;
+    let i = self.index;
+    // Same as the row path, which has no answer for a zero divisor
+    if segment.kernel == Kernel::Divide && columns.b[i] == 0.0 {
+      return Some(Err("Unsupported mathematical operation".to_string()));
+    }
+    Some(Ok((columns.output[i], columns.integral[i])))
+  }
+}
+
+/// Split a batch's row data into segments of `segment_rows`, one handle per row in order
+pub fn segment_rows(kernel: Kernel, rows: Vec<String>, segment_rows: usize) -> Vec<ColumnarRow> {
+  let mut handles = Vec::with_capacity(rows.len());
+  let mut rows = rows.into_iter().peekable();
+  while rows.peek().is_some() {
+    let chunk: Vec<String> = rows.by_ref().take(segment_rows).collect();
+    let len = chunk.len();
+    let segment = Arc::new(Segment { kernel, rows: chunk, columns: OnceLock::new() });
+    handles.extend((0..len).map(|index| ColumnarRow { segment: Arc::clone(&segment), index }));
+  }
+  handles
+}
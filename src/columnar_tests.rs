@@ -0,0 +1,39 @@
+// src/columnar_tests.rs - Batches computed a column at a time
+use crate::columnar::{ Kernel, segment_rows };
+
+fn rows(rows: &[&str]) -> Vec<String> {
+  rows
+    .iter()
+    .map(|row| row.to_string())
+    .collect()
+}
+
+#[test]
+fn kernels_match_the_row_path() {
+  assert_eq!(Kernel::for_function("({a, b}) => a * b", "mathematical"), Some(Kernel::Multiply));
+  assert_eq!(Kernel::for_function("x => x.a - x.b", "mathematical"), Some(Kernel::Subtract));
+  assert_eq!(Kernel::for_function("x => Math.sqrt(x)", "mathematical"), None);
+  assert_eq!(Kernel::for_function("({a, b}) => a * b", "general"), None);
+}
+
+#[test]
+fn every_row_reads_its_segments_output() {
+  let data = rows(&[r#"{"a": 2, "b": 3}"#, r#"{"a": 1.5, "b": 2}"#, r#"{"a": -4, "b": 5}"#]);
+  let handles = segment_rows(Kernel::Multiply, data, 2);
+  let outputs: Vec<(f64, bool)> = handles
+    .iter()
+    .map(|row| row.output().expect("columnar").expect("output"))
+    .collect();
+  // Integral only where both operands were integers
+  assert_eq!(outputs, [(6.0, true), (3.0, false), (-20.0, true)]);
+}
+
+#[test]
+fn odd_rows_fall_back_to_the_row_path() {
+  // A segment converts whole or not at all; the next segment is unaffected
+  let data = rows(&[r#"{"a": 1, "b": 2}"#, r#"{"a": 1}"#, r#"{"a": 8, "b": 0}"#]);
+  let handles = segment_rows(Kernel::Divide, data, 2);
+  assert!(handles[0].output().is_none());
+  assert!(handles[1].output().is_none());
+  assert_eq!(handles[2].output(), Some(Err("Unsupported mathematical operation".to_string())));
+}
@@ -0,0 +1,134 @@
+// src/concurrency_limit.rs - Caps on how many tasks of one function run at once
+//
+// A function that talks to a rate-limited or single-connection resource can't
+// use the whole pool. A worker that dequeues a task whose function is already
+// at its limit parks it instead of running it; when one of the running tasks
+// finishes, the oldest parked task goes back on the shared queue.
+use std::collections::{ HashMap, VecDeque };
+
+use crate::OptimizedWorkerTask;
+use crate::priority::{ Priority, Requeue };
+use crate::sync::{ Mutex, MutexGuard, lock_recovering };
+
+#[derive(Debug, Default)]
+struct Limit {
+  max: usize,
+  running: usize,
+  parked: VecDeque<(OptimizedWorkerTask, Priority)>,
+}
+
+/// Concurrency limits by function hash. Reserved batches are exempt: their
+/// reservation already bounds how many of their tasks run at once.
+pub struct ConcurrencyLimits {
+  limits: Mutex<HashMap<String, Limit>>,
+  requeue: Requeue,
+}
+
+impl ConcurrencyLimits {
+  pub fn new(requeue: Requeue) -> Self {
+    Self {
+      limits: Mutex::new(HashMap::new()),
+      requeue,
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, HashMap<String, Limit>> {
+    lock_recovering(&self.limits, || {})
+  }
+
+  /// Limit `function_hash` to `max` concurrent tasks, or lift the limit with
+  /// `None`. Parked tasks the new limit has room for are requeued.
+  pub fn set(&self, function_hash: &str, max: Option<usize>) -> Result<(), String> {
+    let released = {
+      let mut limits = self.lock();
+      match max {
+        Some(0) => {
+          return Err("Concurrency limit must be at least 1".to_string());
+        }
+        Some(max) => {
+          let limit = limits.entry(function_hash.to_string()).or_default();
+          limit.max = max;
+          // Requeued tasks claim their slot when a worker picks them up again
+          let room = max.saturating_sub(limit.running).min(limit.parked.len());
+          limit.parked.drain(..room).collect()
+        }
+        None =>
+          match limits.get_mut(function_hash) {
+            // Running tasks still hold slots; the entry goes once they finish
+            Some(limit) => {
+              limit.max = usize::MAX;
+              let released: Vec<_> = limit.parked.drain(..).collect();
+              if limit.running == 0 {
+                limits.remove(function_hash);
+              }
+              released
+            }
+            None => Vec::new(),
+          }
+      }
+    };
+    self.requeue.send(released);
+    Ok(())
+  }
+
+  /// Active limit for `function_hash`, if any
+  pub fn get(&self, function_hash: &str) -> Option<usize> {
+    self
+      .lock()
+      .get(function_hash)
+      .map(|limit| limit.max)
+      .filter(|max| *max != usize::MAX)
+  }
+
+  /// Claim a slot for `task`, or park it when its function is at its limit.
+  /// Returns the task and its slot (if limited) when it may run now. `on_park`
+  /// runs before the lock is released, so a requeue can't overtake it.
+  pub fn admit(
+    &self,
+    task: OptimizedWorkerTask,
+    priority: Priority,
+    on_park: impl FnOnce(&OptimizedWorkerTask)
+  ) -> Option<(OptimizedWorkerTask, Option<Slot<'_>>)> {
+    let function_hash = task.optimization_hints.function_hash.clone();
+    let mut limits = self.lock();
+    let Some(limit) = limits.get_mut(&function_hash) else {
+      return Some((task, None));
+    };
+    if limit.running >= limit.max {
+      on_park(&task);
+      limit.parked.push_back((task, priority));
+      return None;
+    }
+    limit.running += 1;
+    Some((task, Some(Slot { limits: self, function_hash })))
+  }
+
+  fn finish(&self, function_hash: &str) {
+    let released = {
+      let mut limits = self.lock();
+      let Some(limit) = limits.get_mut(function_hash) else {
+        return;
+      };
+      limit.running = limit.running.saturating_sub(1);
+      let released: Vec<_> = limit.parked.pop_front().into_iter().collect();
+      // A lifted limit is forgotten once its last running task is done
+      if limit.max == usize::MAX && limit.running == 0 {
+        limits.remove(function_hash);
+      }
+      released
+    };
+    self.requeue.send(released);
+  }
+}
+
+/// A running task's claim on its function's limit; frees it when dropped
+pub struct Slot<'a> {
+  limits: &'a ConcurrencyLimits,
+  function_hash: String,
+}
+
+impl Drop for Slot<'_> {
+  fn drop(&mut self) {
+    self.limits.finish(&self.function_hash);
+  }
+}
@@ -0,0 +1,75 @@
+// src/cpu_budget.rs - Caps aggregate worker CPU to leave headroom for the event loop
+use std::sync::atomic::{ AtomicU64, AtomicUsize, Ordering };
+use std::time::Duration;
+
+// Pauses shorter than this are carried over instead of slept, so sub-millisecond
+// tasks are throttled in aggregate rather than not at all
+const MIN_PAUSE: Duration = Duration::from_millis(1);
+
+/// Duty-cycle limit shared by every worker.
+///
+/// With `max_cpu_fraction` set, each worker may be busy for at most
+/// `max_cpu_fraction * cores / workers` of the time: after running for `t`
+/// it owes `t * (1 / duty - 1)` of idle time before taking the next task.
+/// Every worker honours its share, so the whole pool stays under the cap.
+#[derive(Debug)]
+pub struct CpuBudget {
+  // f64 bits of the per-worker duty cycle; 1.0 means unthrottled
+  duty_cycle: AtomicU64,
+  cores: usize,
+  workers: AtomicUsize,
+}
+
+impl CpuBudget {
+  pub fn new(cores: usize, workers: usize) -> Self {
+    Self {
+      duty_cycle: AtomicU64::new((1.0f64).to_bits()),
+      cores: cores.max(1),
+      workers: AtomicUsize::new(workers.max(1)),
+    }
+  }
+
+  /// Limit the pool to `max_cpu_fraction` of the machine's cores, or lift the limit
+  pub fn configure(&self, max_cpu_fraction: Option<f64>) -> Result<(), String> {
+    let duty_cycle = match max_cpu_fraction {
+      Some(fraction) if !(fraction > 0.0 && fraction <= 1.0) => {
+        return Err(format!("max_cpu_fraction must be in (0, 1], got {}", fraction));
+      }
+      Some(fraction) => ((fraction * (self.cores as f64)) / (self.workers() as f64)).min(1.0),
+      None => 1.0,
+    };
+    self.duty_cycle.store(duty_cycle.to_bits(), Ordering::Release);
+    Ok(())
+  }
+
+  /// Current cap as a fraction of the machine's cores, if any
+  pub fn max_cpu_fraction(&self) -> Option<f64> {
+    let duty_cycle = f64::from_bits(self.duty_cycle.load(Ordering::Acquire));
+    (duty_cycle < 1.0).then(|| (duty_cycle * (self.workers() as f64)) / (self.cores as f64))
+  }
+
+  /// Share the same cap among `workers` workers after the pool is resized
+  pub fn set_workers(&self, workers: usize) {
+    let max_cpu_fraction = self.max_cpu_fraction();
+    self.workers.store(workers.max(1), Ordering::Release);
+    // Already validated when it was configured
+    let _ = self.configure(max_cpu_fraction);
+  }
+
+  fn workers(&self) -> usize {
+    self.workers.load(Ordering::Acquire)
+  }
+
+  /// Add the idle time owed for `busy` to `debt`; returns the pause to take now, if
+  /// the debt is large enough to be worth sleeping for
+  pub fn pause_after(&self, busy: Duration, debt: &mut Duration) -> Option<Duration> {
+    let duty_cycle = f64::from_bits(self.duty_cycle.load(Ordering::Acquire));
+    if duty_cycle >= 1.0 {
+      *debt = Duration::ZERO;
+      return None;
+    }
+
+    *debt += busy.mul_f64(1.0 / duty_cycle - 1.0);
+    (*debt >= MIN_PAUSE).then(|| std::mem::take(debt))
+  }
+}
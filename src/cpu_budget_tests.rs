@@ -0,0 +1,53 @@
+// src/cpu_budget_tests.rs - How much idle time a CPU cap asks of each worker
+use std::time::Duration;
+
+use crate::cpu_budget::CpuBudget;
+
+const MS: Duration = Duration::from_millis(1);
+
+#[test]
+fn capped_workers_owe_idle_time_for_their_busy_time() {
+  // Half of 4 cores over 4 workers: each is busy at most half the time
+  let budget = CpuBudget::new(4, 4);
+  budget.configure(Some(0.5)).unwrap();
+  assert_eq!(budget.max_cpu_fraction(), Some(0.5));
+
+  let mut debt = Duration::ZERO;
+  assert_eq!(budget.pause_after(10 * MS, &mut debt), Some(10 * MS));
+  assert_eq!(debt, Duration::ZERO);
+
+  // Sub-millisecond debts add up until they're worth sleeping for
+  assert_eq!(budget.pause_after(MS / 2, &mut debt), None);
+  assert_eq!(budget.pause_after(MS / 2, &mut debt), Some(MS));
+}
+
+#[test]
+fn uncapped_or_undersubscribed_pools_never_pause() {
+  let budget = CpuBudget::new(4, 2);
+  let mut debt = Duration::ZERO;
+  assert_eq!(budget.pause_after(10 * MS, &mut debt), None);
+
+  // Two workers can't use more than half of 4 cores anyway
+  budget.configure(Some(0.5)).unwrap();
+  assert_eq!(budget.max_cpu_fraction(), None);
+  assert_eq!(budget.pause_after(10 * MS, &mut debt), None);
+}
+
+#[test]
+fn cap_is_validated_and_kept_across_resizes() {
+  let budget = CpuBudget::new(2, 2);
+  for fraction in [0.0, -0.5, 1.5, f64::NAN] {
+    let error = budget.configure(Some(fraction)).unwrap_err();
+    assert!(error.starts_with("max_cpu_fraction must be in (0, 1]"), "{}", error);
+  }
+
+  budget.configure(Some(0.25)).unwrap();
+  budget.set_workers(4);
+  assert_eq!(budget.max_cpu_fraction(), Some(0.25));
+  // A quarter of 2 cores over 4 workers: busy one eighth of the time
+  let mut debt = Duration::ZERO;
+  assert_eq!(budget.pause_after(MS, &mut debt), Some(7 * MS));
+
+  budget.configure(None).unwrap();
+  assert_eq!(budget.max_cpu_fraction(), None);
+}
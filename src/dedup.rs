@@ -0,0 +1,134 @@
+// src/dedup.rs - One execution for identical tasks in flight at the same time
+//
+// A task submitted with `dedupe: true` while an identical one is queued or
+// running doesn't run itself: it's attached to that execution and gets a
+// copy of its result under its own task ID. Identical means the same function,
+// data, hints and timeout, compared by a 64-bit fingerprint. If the execution
+// is dropped without running (cancelled, shed or expired), the first attached
+// task takes its place in the queue, with the rest attached to it.
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{ Hash, Hasher };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+
+use crate::OptimizedWorkerTask;
+use crate::priority::{ Priority, Requeue };
+use crate::sync::{ Mutex, MutexGuard, lock_recovering };
+
+/// Fingerprint of a task's function, data, hints and timeout
+pub type Fingerprint = u64;
+
+/// The fingerprint of a task that asked to be deduplicated and can be. Tasks
+/// with a group, a child executor, a view or borrowed data always run on their
+/// own.
+pub fn fingerprint(task: &OptimizedWorkerTask) -> Option<Fingerprint> {
+  let hints = &task.optimization_hints;
+  if
+    hints.dedupe != Some(true) ||
+    hints.group.is_some() ||
+    hints.after.is_some() ||
+    hints.child.is_some() ||
+    task.view.is_some() ||
+    task.borrowed.is_some()
+  {
+    return None;
+  }
+  let mut hasher = DefaultHasher::new();
+  task.function_code.hash(&mut hasher);
+  task.data.hash(&mut hasher);
+  task.timeout_ms.hash(&mut hasher);
+  serde_json::to_string(hints).ok()?.hash(&mut hasher);
+  Some(hasher.finish())
+}
+
+#[derive(Debug, Default)]
+struct DedupState {
+  // Tasks waiting on each execution's result, in submission order
+  followers: HashMap<Fingerprint, Vec<(OptimizedWorkerTask, Priority)>>,
+  // Fingerprint of each queued or running execution, by its task ID
+  leaders: HashMap<String, Fingerprint>,
+}
+
+/// Executions that identical submissions can attach to
+pub struct DedupTable {
+  state: Mutex<DedupState>,
+  // Lets workers skip the lock after each task while nothing is deduplicated
+  leader_count: AtomicUsize,
+  requeue: Requeue,
+}
+
+impl DedupTable {
+  pub fn new(requeue: Requeue) -> Self {
+    Self {
+      state: Mutex::new(DedupState::default()),
+      leader_count: AtomicUsize::new(0),
+      requeue,
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, DedupState> {
+    lock_recovering(&self.state, || {})
+  }
+
+  /// Attach the task to an identical execution and return `None`, or return it
+  /// to be queued as the execution the next identical submissions attach to
+  pub fn join(
+    &self,
+    fingerprint: Fingerprint,
+    task: OptimizedWorkerTask,
+    priority: Priority
+  ) -> Option<OptimizedWorkerTask> {
+    let mut state = self.lock();
+    if let Some(followers) = state.followers.get_mut(&fingerprint) {
+      followers.push((task, priority));
+      return None;
+    }
+    state.followers.insert(fingerprint, Vec::new());
+    state.leaders.insert(task.id.clone(), fingerprint);
+    self.leader_count.store(state.leaders.len(), Ordering::Release);
+    Some(task)
+  }
+
+  /// A task ran; the tasks attached to it, to receive copies of its result
+  pub fn finish(&self, task_id: &str) -> Vec<OptimizedWorkerTask> {
+    if self.leader_count.load(Ordering::Acquire) == 0 {
+      return Vec::new();
+    }
+    let mut state = self.lock();
+    let Some(fingerprint) = state.leaders.remove(task_id) else {
+      return Vec::new();
+    };
+    let followers = state.followers.remove(&fingerprint).unwrap_or_default();
+    self.leader_count.store(state.leaders.len(), Ordering::Release);
+    followers.into_iter().map(|(task, _)| task).collect()
+  }
+
+  /// A task was dropped without running. The first task attached to it is
+  /// queued in its place, with the rest attached to that one.
+  pub fn unexecuted(&self, task_id: &str) {
+    if self.leader_count.load(Ordering::Acquire) == 0 {
+      return;
+    }
+    let promoted = {
+      let mut state = self.lock();
+      let Some(fingerprint) = state.leaders.remove(task_id) else {
+        return;
+      };
+      let followers = state.followers.entry(fingerprint).or_default();
+      let promoted = (!followers.is_empty()).then(|| followers.remove(0));
+      match promoted.as_ref() {
+        Some((task, _)) => {
+          state.leaders.insert(task.id.clone(), fingerprint);
+        }
+        None => {
+          state.followers.remove(&fingerprint);
+        }
+      }
+      self.leader_count.store(state.leaders.len(), Ordering::Release);
+      promoted
+    };
+    if let Some(promoted) = promoted {
+      self.requeue.send(vec![promoted]);
+    }
+  }
+}
@@ -0,0 +1,89 @@
+// src/discipline.rs - The order workers take queued tasks in
+//
+// The shared queue is four lanes, served highest first. A queue discipline
+// decides which lane each task waits in and, per lane, whether its oldest or
+// newest task goes first. The built-in ones, chosen with `queue_discipline`:
+//
+//   priority  the lane the `priority` hint asks for, oldest first (default)
+//   fifo      one lane, oldest first: submission order, `priority` ignored
+//   lifo      one lane, newest first: under recursive fan-out, tasks run
+//             right after the task that submitted them, while its data is
+//             still in cache
+//
+// Embedders implement `QueueDiscipline` for other orderings, e.g. a lane per
+// tenant tier, and pass it to `OptimizedMultiCoreExecutor::with_discipline`.
+// Whatever the discipline, tasks sent to a particular worker (sticky routing,
+// batch splits and sub-batches) wait in that worker's inbox, oldest first. Queue
+// positions reported by `get_task_status` assume every lane is oldest first.
+use std::sync::Arc;
+
+use crate::OptimizationHints;
+use crate::priority::Priority;
+
+/// Which end of a lane workers take from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LaneOrder {
+  /// Oldest first
+  #[default]
+  Fifo,
+  /// Newest first
+  Lifo,
+}
+
+/// Decides where in the shared queue tasks wait
+pub trait QueueDiscipline: Send + Sync {
+  /// Lane a task with `hints` waits in, given the priority they ask for
+  fn lane(&self, hints: &OptimizationHints, priority: Priority) -> Priority;
+
+  /// Order `lane` is served in
+  fn order(&self, _lane: Priority) -> LaneOrder {
+    LaneOrder::Fifo
+  }
+}
+
+/// Discipline shared between the executor and its queue
+pub type SharedDiscipline = Arc<dyn QueueDiscipline>;
+
+/// Lanes by `priority` hint, oldest first
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PriorityDiscipline;
+
+impl QueueDiscipline for PriorityDiscipline {
+  fn lane(&self, _hints: &OptimizationHints, priority: Priority) -> Priority {
+    priority
+  }
+}
+
+/// One lane, oldest first
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FifoDiscipline;
+
+impl QueueDiscipline for FifoDiscipline {
+  fn lane(&self, _hints: &OptimizationHints, _priority: Priority) -> Priority {
+    Priority::Normal
+  }
+}
+
+/// One lane, newest first
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LifoDiscipline;
+
+impl QueueDiscipline for LifoDiscipline {
+  fn lane(&self, _hints: &OptimizationHints, _priority: Priority) -> Priority {
+    Priority::Normal
+  }
+
+  fn order(&self, _lane: Priority) -> LaneOrder {
+    LaneOrder::Lifo
+  }
+}
+
+/// Parse `"priority"` (the default), `"fifo"` or `"lifo"`
+pub fn parse(discipline: Option<&str>) -> Result<SharedDiscipline, String> {
+  match discipline.unwrap_or("priority") {
+    "priority" => Ok(Arc::new(PriorityDiscipline)),
+    "fifo" => Ok(Arc::new(FifoDiscipline)),
+    "lifo" => Ok(Arc::new(LifoDiscipline)),
+    other => Err(format!("Unknown queue_discipline '{}', expected priority, fifo or lifo", other)),
+  }
+}
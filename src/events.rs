@@ -0,0 +1,83 @@
+// src/events.rs - Diagnostic events surfaced to JavaScript
+use serde::Serialize;
+use std::collections::VecDeque;
+
+use crate::clock::SharedClock;
+use crate::sync::{ Mutex, MutexGuard, lock_recovering };
+
+// Oldest events are dropped once the log is full
+const MAX_BUFFERED_EVENTS: usize = 1024;
+
+/// Something noteworthy happened inside the executor
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutorEvent {
+  pub kind: String,
+  pub worker_id: Option<usize>,
+  pub message: String,
+  pub timestamp_ms: u64,
+}
+
+/// Bounded buffer of diagnostic events, drained by the JS side
+pub struct EventLog {
+  events: Mutex<VecDeque<ExecutorEvent>>,
+  clock: SharedClock,
+}
+
+impl EventLog {
+  pub fn new(clock: SharedClock) -> Self {
+    Self {
+      events: Mutex::new(VecDeque::new()),
+      clock,
+    }
+  }
+
+  /// Record an event and mirror it to the tracing log
+  pub fn emit(&self, kind: &str, worker_id: Option<usize>, message: impl Into<String>) {
+    let message = message.into();
+    tracing::warn!(kind, ?worker_id, "{}", message);
+    self.push(kind, worker_id, message);
+  }
+
+  /// Record an event that isn't a problem, such as a partial result; traced at debug level
+  pub fn publish(&self, kind: &str, worker_id: Option<usize>, message: impl Into<String>) {
+    let message = message.into();
+    tracing::debug!(kind, ?worker_id, "{}", message);
+    self.push(kind, worker_id, message);
+  }
+
+  fn push(&self, kind: &str, worker_id: Option<usize>, message: String) {
+    let event = ExecutorEvent {
+      kind: kind.to_string(),
+      worker_id,
+      message,
+      timestamp_ms: self.clock.unix_time().as_millis() as u64,
+    };
+
+    let mut events = lock_recovering(&self.events, || {});
+    if events.len() >= MAX_BUFFERED_EVENTS {
+      events.pop_front();
+    }
+    events.push_back(event);
+  }
+
+  /// Take all buffered events, oldest first
+  pub fn drain(&self) -> Vec<ExecutorEvent> {
+    lock_recovering(&self.events, || {}).drain(..).collect()
+  }
+
+  /// Lock executor state, recovering from poisoning with a `mutex_poisoned` event
+  pub fn lock<'a, T>(
+    &self,
+    mutex: &'a Mutex<T>,
+    name: &str,
+    worker_id: Option<usize>
+  ) -> MutexGuard<'a, T> {
+    lock_recovering(mutex, || {
+      self.emit(
+        "mutex_poisoned",
+        worker_id,
+        format!("Recovered poisoned `{}` lock after a panic in another thread", name)
+      );
+    })
+  }
+}
@@ -0,0 +1,388 @@
+// src/executor_config.rs - Construction-time executor settings
+use std::collections::HashMap;
+
+use crate::autoscale::AutoscaleConfig;
+use crate::bindings::napi;
+use crate::columnar;
+use crate::discipline::{ self, SharedDiscipline };
+use crate::operation_pools::OperationPools;
+use crate::scratch;
+use crate::strict;
+use crate::thread_tuning::{ ThreadTuning, WorkerThreadConfig };
+
+const DEFAULT_THREAD_NAME_PREFIX: &str = "threader-worker-";
+const DEFAULT_STATS_FLUSH_INTERVAL: u64 = 64;
+// Smallest stack that comfortably fits the executor's own frames
+const MIN_THREAD_STACK_SIZE: u32 = 64 * 1024;
+
+/// Executor settings; every field is optional and defaults to the previous
+/// hardcoded behaviour. From Rust, fill in what you need and take the rest
+/// from `ExecutorConfig::default()`.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct ExecutorConfig {
+  /// Defaults to the number of CPU cores
+  pub worker_count: Option<u32>,
+  /// Most workers `set_worker_count` can grow the pool to; defaults to the
+  /// larger of `worker_count` and the number of CPU cores
+  pub max_worker_count: Option<u32>,
+  /// Scale the pool with load between these bounds from the start; off by
+  /// default. See `set_autoscale`.
+  pub autoscale: Option<AutoscaleConfig>,
+  /// How often dead worker threads are looked for and respawned; defaults to
+  /// 100ms, and 0 turns supervision off, leaving a dead worker's slot empty
+  pub supervision_interval_ms: Option<u32>,
+  /// Tasks allowed to wait in the shared queue; unbounded by default
+  pub queue_capacity: Option<u32>,
+  /// What a submission to a full queue does: "reject" (default) with a
+  /// `QueueFull` error, "block" until workers make room, or "drop_oldest" to
+  /// fail the oldest queued task of the lowest priority in its place. Needs
+  /// `queue_capacity`.
+  pub overflow_policy: Option<String>,
+  /// How idle workers wait for work: "sleep" (default) until woken for it, "yield"
+  /// or "spin"
+  pub idle_strategy: Option<String>,
+  /// Worker stack size in bytes; the platform default otherwise
+  pub thread_stack_size: Option<u32>,
+  /// Worker threads are named `{prefix}{worker_id}`; defaults to "threader-worker-"
+  pub thread_name_prefix: Option<String>,
+  /// Execution budget for tasks submitted without a `timeout_ms`
+  pub default_timeout_ms: Option<u32>,
+  /// Uncollected results kept before the oldest are dropped; unbounded by default
+  pub result_buffer_limit: Option<u32>,
+  /// Platform-specific thread options
+  pub tuning: Option<ThreadTuning>,
+  /// Run `selfTest` during construction and fail it if any check fails
+  pub self_test: Option<bool>,
+  /// Fail tasks whose function the native paths don't recognize exactly, rather
+  /// than guess from its text; defaults to `setStrictDefault`
+  pub strict: Option<bool>,
+  /// Run shared-queue tasks with `complexity: "low"` and at most this many
+  /// bytes of data on the submitting thread, where queueing would cost more
+  /// than the task itself; off by default
+  pub inline_threshold_bytes: Option<u32>,
+  /// Longest an `execute_inline_batch` call is estimated to take and still run
+  /// on the calling thread rather than the pool; 0 always uses the pool.
+  /// Defaults to 1ms.
+  pub inline_batch_budget_ms: Option<u32>,
+  /// "low_latency", "balanced" or "max_throughput": sets idle strategy, inline
+  /// threshold, batch segment size, stats merging and (on Windows) worker thread
+  /// priority for that goal, so they needn't be tuned one by one. Fields set
+  /// explicitly win over the profile. Workers aren't pinned to cores either way.
+  pub profile: Option<String>,
+  /// Seed for every random choice the executor makes (fault injection, traffic
+  /// splitting between function versions), as the hex a receipt reports; taken
+  /// from the clock otherwise. A seeded executor also numbers its task IDs from
+  /// zero, so the same submissions in the same order get the same IDs and draws.
+  /// With several workers, which task draws which number still depends on timing.
+  pub seed: Option<String>,
+  /// Bytes each of a worker's scratch buffers (numbers parsed out of arrays,
+  /// serialized output) keeps between tasks; 1 MiB by default. Larger ones
+  /// are shrunk back after the task that needed them.
+  pub scratch_limit_bytes: Option<u32>,
+  /// Estimated native memory (queued tasks, uncollected results, caches) above
+  /// which submissions fail with `QueueFull`, the performance cache is cleared
+  /// and `onMemoryPressure` is called; untracked by default
+  pub memory_watermark_mb: Option<u32>,
+  /// Send tasks with the same `function_hash` to the same worker while it keeps
+  /// up, so what it has cached for the function stays warm; idle workers still
+  /// steal them. Off by default.
+  pub sticky_routing: Option<bool>,
+  /// Workers set aside for tasks of one `operation_type` each, by type:
+  /// "mathematical", "string_operations" or "array_operations". Those tasks
+  /// run only on their pool's workers, and the other workers never take them,
+  /// so a flood of one type can't hold up another. `worker_count` must leave
+  /// at least one worker for everything else, and defaults to enough to; only
+  /// those are resized or reserved for batches. None by default.
+  pub operation_pools: Option<HashMap<String, u32>>,
+  /// Order queued tasks are taken in: "priority" (default, by the `priority`
+  /// hint, oldest first), "fifo" (submission order, `priority` ignored) or
+  /// "lifo" (newest first, for recursive fan-out)
+  pub queue_discipline: Option<String>,
+  /// A priority lane left unserved moves up one priority for every `aging_ms`
+  /// it waits, until it overtakes the lanes above it; strict priority by default
+  pub aging_ms: Option<u32>,
+  /// Emit a `task_starved` event for each task still queued this long after it
+  /// was queued; off by default
+  pub starvation_warning_ms: Option<u32>,
+  /// How long a completed task's result answers resubmissions with its
+  /// `idempotency_key`; 5 minutes by default
+  pub idempotency_ttl_ms: Option<u32>,
+  /// Let idle workers rank hot functions, compact caches, compile promoted
+  /// functions ahead of their next run and refresh the throttling calibration;
+  /// off by default. See `get_background_stats`.
+  pub background_optimization: Option<bool>,
+}
+
+/// A named bundle of scheduling settings, from `ExecutorConfig::profile`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+  /// Answer each task as soon as possible: idle workers yield rather than nap,
+  /// tiny tasks skip the queue, batches are computed in small segments and
+  /// workers run above normal priority
+  LowLatency,
+  /// Small tasks skip the queue; everything else as without a profile
+  Balanced,
+  /// Keep workers busy on as much work as possible: nothing runs on the
+  /// submitting thread, batches are computed in large segments and stats are
+  /// merged rarely
+  MaxThroughput,
+}
+
+impl Profile {
+  pub fn parse(value: &str) -> Result<Self, String> {
+    match value {
+      "low_latency" => Ok(Profile::LowLatency),
+      "balanced" => Ok(Profile::Balanced),
+      "max_throughput" => Ok(Profile::MaxThroughput),
+      other =>
+        Err(
+          format!("Unknown profile '{}', expected low_latency, balanced or max_throughput", other)
+        ),
+    }
+  }
+
+  fn idle_strategy(self) -> &'static str {
+    match self {
+      Profile::LowLatency => "yield",
+      Profile::Balanced | Profile::MaxThroughput => "sleep",
+    }
+  }
+
+  fn inline_threshold_bytes(self) -> Option<u32> {
+    match self {
+      Profile::LowLatency => Some(256),
+      Profile::Balanced => Some(64),
+      Profile::MaxThroughput => None,
+    }
+  }
+
+  fn windows_priority(self) -> Option<&'static str> {
+    match self {
+      Profile::LowLatency => Some("above_normal"),
+      Profile::Balanced | Profile::MaxThroughput => None,
+    }
+  }
+
+  fn scheduling(self) -> Scheduling {
+    match self {
+      Profile::LowLatency => Scheduling { segment_rows: 64, stats_flush_interval: 16 },
+      Profile::Balanced => Scheduling::default(),
+      Profile::MaxThroughput => Scheduling { segment_rows: 1024, stats_flush_interval: 256 },
+    }
+  }
+}
+
+/// Scheduling settings only a profile changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scheduling {
+  /// Batch rows converted and computed together when a batch runs a column at a time
+  pub segment_rows: usize,
+  /// Results a worker aggregates locally before merging into shared stats
+  pub stats_flush_interval: u64,
+}
+
+impl Default for Scheduling {
+  fn default() -> Self {
+    Self {
+      segment_rows: columnar::SEGMENT_ROWS,
+      stats_flush_interval: DEFAULT_STATS_FLUSH_INTERVAL,
+    }
+  }
+}
+
+/// What a submission does when the shared queue is at `queue_capacity`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+  /// Fail the submission with a `QueueFull` error
+  Reject,
+  /// Wait on the submitting thread until workers take enough tasks
+  Block,
+  /// Fail queued tasks, oldest of the lowest priority first, to make room
+  DropOldest,
+}
+
+/// What an idle worker does between queue checks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IdleStrategy {
+  /// Sleep until woken for new work, a delayed or rate-limited task comes due
+  /// or shutdown; no CPU while idle
+  Sleep,
+  /// Give up the time slice; lower latency, some CPU while idle
+  Yield,
+  /// Busy-wait; lowest latency, a full core per idle worker
+  Spin,
+}
+
+impl ExecutorConfig {
+  /// Check every field, so a bad config fails construction instead of a worker
+  pub fn validate(&self) -> Result<(), String> {
+    if self.worker_count == Some(0) {
+      return Err("worker_count must be at least 1".to_string());
+    }
+    if let Some(max) = self.max_worker_count && (max as usize) < self.worker_count() {
+      return Err(
+        format!(
+          "max_worker_count must be at least worker_count ({}), got {}",
+          self.worker_count(),
+          max
+        )
+      );
+    }
+    let fewest = self.operation_pools()?.fewest_workers();
+    if self.worker_count() < fewest {
+      return Err(
+        format!(
+          "operation_pools dedicate {} workers, so worker_count must be at least {}, got {}",
+          fewest - 1,
+          fewest,
+          self.worker_count()
+        )
+      );
+    }
+    if let Some(autoscale) = self.autoscale.as_ref() {
+      autoscale.policy(fewest, self.max_worker_count())?;
+    }
+    if self.queue_capacity == Some(0) {
+      return Err("queue_capacity must be at least 1".to_string());
+    }
+    if self.result_buffer_limit == Some(0) {
+      return Err("result_buffer_limit must be at least 1".to_string());
+    }
+    if self.inline_threshold_bytes == Some(0) {
+      return Err("inline_threshold_bytes must be at least 1".to_string());
+    }
+    if self.memory_watermark_mb == Some(0) {
+      return Err("memory_watermark_mb must be at least 1".to_string());
+    }
+    if self.overflow_policy.is_some() && self.queue_capacity.is_none() {
+      return Err("overflow_policy needs a queue_capacity".to_string());
+    }
+    self.profile()?;
+    self.seed()?;
+    self.overflow_policy()?;
+    self.queue_discipline()?;
+    self.idle_strategy()?;
+    self.thread_config()?;
+    Ok(())
+  }
+
+  pub fn profile(&self) -> Result<Option<Profile>, String> {
+    self.profile.as_deref().map(Profile::parse).transpose()
+  }
+
+  /// The config with its profile's settings in every field left unset
+  pub fn with_profile(mut self) -> Result<Self, String> {
+    let Some(profile) = self.profile()? else {
+      return Ok(self);
+    };
+    self.idle_strategy.get_or_insert_with(|| profile.idle_strategy().to_string());
+    if self.inline_threshold_bytes.is_none() {
+      self.inline_threshold_bytes = profile.inline_threshold_bytes();
+    }
+    if let Some(priority) = profile.windows_priority() {
+      let tuning = self.tuning.get_or_insert_with(ThreadTuning::default);
+      tuning.windows_priority.get_or_insert_with(|| priority.to_string());
+    }
+    Ok(self)
+  }
+
+  pub fn scheduling(&self) -> Result<Scheduling, String> {
+    Ok(self.profile()?.map_or_else(Scheduling::default, Profile::scheduling))
+  }
+
+  /// Workers to start with: the core count by default, or more if
+  /// `operation_pools` need them
+  pub fn worker_count(&self) -> usize {
+    self.worker_count.map_or_else(
+      || {
+        let fewest = self.operation_pools().map_or(1, |pools| pools.fewest_workers());
+        num_cpus::get().max(fewest)
+      },
+      |count| count as usize
+    )
+  }
+
+  pub fn max_worker_count(&self) -> usize {
+    self.max_worker_count.map_or_else(
+      || self.worker_count().max(num_cpus::get()),
+      |count| count as usize
+    )
+  }
+
+  /// `seed` as a number: 1 to 16 hex digits, optionally after `0x`
+  pub fn seed(&self) -> Result<Option<u64>, String> {
+    let Some(seed) = self.seed.as_deref() else {
+      return Ok(None);
+    };
+    let digits = seed.strip_prefix("0x").unwrap_or(seed);
+    if digits.is_empty() || digits.len() > 16 {
+      return Err(format!("seed must be 1 to 16 hex digits, got '{}'", seed));
+    }
+    u64
+      ::from_str_radix(digits, 16)
+      .map(Some)
+      .map_err(|_| format!("seed must be 1 to 16 hex digits, got '{}'", seed))
+  }
+
+  pub fn overflow_policy(&self) -> Result<OverflowPolicy, String> {
+    match self.overflow_policy.as_deref() {
+      None | Some("reject") => Ok(OverflowPolicy::Reject),
+      Some("block") => Ok(OverflowPolicy::Block),
+      Some("drop_oldest") => Ok(OverflowPolicy::DropOldest),
+      Some(other) =>
+        Err(format!("Unknown overflow_policy '{}', expected reject, block or drop_oldest", other)),
+    }
+  }
+
+  pub fn queue_discipline(&self) -> Result<SharedDiscipline, String> {
+    discipline::parse(self.queue_discipline.as_deref())
+  }
+
+  pub fn idle_strategy(&self) -> Result<IdleStrategy, String> {
+    match self.idle_strategy.as_deref() {
+      None | Some("sleep") => Ok(IdleStrategy::Sleep),
+      Some("yield") => Ok(IdleStrategy::Yield),
+      Some("spin") => Ok(IdleStrategy::Spin),
+      Some(other) =>
+        Err(format!("Unknown idle_strategy '{}', expected sleep, yield or spin", other)),
+    }
+  }
+
+  pub fn thread_config(&self) -> Result<WorkerThreadConfig, String> {
+    let stack_size = match self.thread_stack_size {
+      Some(size) if size < MIN_THREAD_STACK_SIZE => {
+        return Err(
+          format!("thread_stack_size must be at least {}, got {}", MIN_THREAD_STACK_SIZE, size)
+        );
+      }
+      size => size.map(|size| size as usize),
+    };
+    WorkerThreadConfig::from_tuning(self.tuning.as_ref(), stack_size)
+  }
+
+  pub fn scratch_limit_bytes(&self) -> usize {
+    self.scratch_limit_bytes.map_or(scratch::DEFAULT_LIMIT_BYTES, |bytes| bytes as usize)
+  }
+
+  pub fn background_optimization(&self) -> bool {
+    self.background_optimization.unwrap_or(false)
+  }
+
+  pub fn operation_pools(&self) -> Result<OperationPools, String> {
+    self.operation_pools.as_ref().map_or_else(|| Ok(OperationPools::default()), OperationPools::new)
+  }
+
+  pub fn sticky_routing(&self) -> bool {
+    self.sticky_routing.unwrap_or(false)
+  }
+
+  pub fn strict(&self) -> bool {
+    self.strict.unwrap_or_else(strict::default_enabled)
+  }
+
+  pub fn thread_name(&self, worker_id: usize) -> String {
+    let prefix = self.thread_name_prefix.as_deref().unwrap_or(DEFAULT_THREAD_NAME_PREFIX);
+    format!("{}{}", prefix, worker_id)
+  }
+}
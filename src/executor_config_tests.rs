@@ -0,0 +1,51 @@
+// src/executor_config_tests.rs - Scheduling profiles and how they meet explicit settings
+use crate::executor_config::{ ExecutorConfig, Scheduling };
+
+fn profiled(profile: &str, config: ExecutorConfig) -> ExecutorConfig {
+  ExecutorConfig { profile: Some(profile.to_string()), ..config }
+    .with_profile()
+    .expect("profile")
+}
+
+#[test]
+fn profiles_fill_in_unset_fields() {
+  let low_latency = profiled("low_latency", ExecutorConfig::default());
+  assert_eq!(low_latency.idle_strategy.as_deref(), Some("yield"));
+  assert_eq!(low_latency.inline_threshold_bytes, Some(256));
+  let priority = low_latency.tuning.as_ref().and_then(|tuning| tuning.windows_priority.as_deref());
+  assert_eq!(priority, Some("above_normal"));
+  assert_eq!(low_latency.scheduling().unwrap(), Scheduling {
+    segment_rows: 64,
+    stats_flush_interval: 16,
+  });
+
+  let throughput = profiled("max_throughput", ExecutorConfig::default());
+  assert_eq!(throughput.idle_strategy.as_deref(), Some("sleep"));
+  assert_eq!(throughput.inline_threshold_bytes, None);
+  assert!(throughput.tuning.is_none());
+  assert_eq!(throughput.scheduling().unwrap().segment_rows, 1024);
+
+  // Balanced only turns on inlining
+  let balanced = profiled("balanced", ExecutorConfig::default());
+  assert_eq!(balanced.inline_threshold_bytes, Some(64));
+  assert_eq!(balanced.scheduling().unwrap(), Scheduling::default());
+  assert_eq!(ExecutorConfig::default().scheduling().unwrap(), Scheduling::default());
+}
+
+#[test]
+fn explicit_fields_win_over_the_profile() {
+  let explicit = ExecutorConfig {
+    idle_strategy: Some("spin".to_string()),
+    inline_threshold_bytes: Some(8),
+    ..ExecutorConfig::default()
+  };
+  let config = profiled("low_latency", explicit);
+  assert_eq!(config.idle_strategy.as_deref(), Some("spin"));
+  assert_eq!(config.inline_threshold_bytes, Some(8));
+
+  let unknown = ExecutorConfig { profile: Some("fast".to_string()), ..ExecutorConfig::default() };
+  assert_eq!(
+    unknown.validate().unwrap_err(),
+    "Unknown profile 'fast', expected low_latency, balanced or max_throughput"
+  );
+}
@@ -0,0 +1,162 @@
+// src/executor_registry.rs - Named executors shared across an application
+use std::collections::BTreeMap;
+use std::sync::{ Arc, Mutex, MutexGuard };
+
+use crate::OptimizedMultiCoreExecutor;
+use crate::bindings::{ Error, Result };
+use crate::executor_config::ExecutorConfig;
+
+/// Executors by name, so modules can look up a pool ("image-processing",
+/// "metrics", ...) instead of having an instance passed through to them
+pub struct ExecutorRegistry<T> {
+  executors: BTreeMap<String, T>,
+}
+
+impl<T> ExecutorRegistry<T> {
+  pub const fn new() -> Self {
+    Self { executors: BTreeMap::new() }
+  }
+
+  /// Add `executor` under `name`; names are never silently replaced
+  pub fn insert(&mut self, name: &str, executor: T) -> std::result::Result<(), String> {
+    self.check_available(name)?;
+    self.executors.insert(name.to_string(), executor);
+    Ok(())
+  }
+
+  pub fn get(&self, name: &str) -> Option<&T> {
+    self.executors.get(name)
+  }
+
+  pub fn remove(&mut self, name: &str) -> Option<T> {
+    self.executors.remove(name)
+  }
+
+  /// Registered names, sorted
+  pub fn names(&self) -> Vec<String> {
+    self.executors.keys().cloned().collect()
+  }
+
+  /// Whether `name` can be registered; checked before building an executor for it
+  pub fn check_available(&self, name: &str) -> std::result::Result<(), String> {
+    if name.is_empty() {
+      return Err("Executor name must not be empty".to_string());
+    }
+    if self.executors.contains_key(name) {
+      return Err(format!("Executor '{}' already exists", name));
+    }
+    Ok(())
+  }
+}
+
+impl<T> Default for ExecutorRegistry<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+type SharedExecutors = ExecutorRegistry<Arc<OptimizedMultiCoreExecutor>>;
+
+// Process-wide registry for Rust callers
+static EXECUTORS: Mutex<SharedExecutors> = Mutex::new(ExecutorRegistry::new());
+
+fn executors() -> MutexGuard<'static, SharedExecutors> {
+  EXECUTORS.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Build an executor and register it under `name`
+pub fn create_executor(
+  name: &str,
+  config: ExecutorConfig
+) -> Result<Arc<OptimizedMultiCoreExecutor>> {
+  let mut registry = executors();
+  registry.check_available(name).map_err(Error::from_reason)?;
+  let executor = Arc::new(OptimizedMultiCoreExecutor::new(Some(config))?);
+  registry.insert(name, Arc::clone(&executor)).map_err(Error::from_reason)?;
+  Ok(executor)
+}
+
+pub fn get_executor(name: &str) -> Option<Arc<OptimizedMultiCoreExecutor>> {
+  executors().get(name).cloned()
+}
+
+/// Unregister `name`. The executor keeps running until shut down or dropped.
+pub fn remove_executor(name: &str) -> Option<Arc<OptimizedMultiCoreExecutor>> {
+  executors().remove(name)
+}
+
+pub fn executor_names() -> Vec<String> {
+  executors().names()
+}
+
+/// The same functions for JS. Class instances belong to the JS thread that created
+/// them, so each main or worker thread has its own registry.
+#[cfg(feature = "node")]
+pub mod js {
+  use napi::Env;
+  use napi::bindgen_prelude::Reference;
+  use std::cell::RefCell;
+
+  use super::ExecutorRegistry;
+  use crate::OptimizedMultiCoreExecutor;
+  use crate::bindings::{ Error, Result, napi };
+  use crate::executor_config::ExecutorConfig;
+
+  type Executors = ExecutorRegistry<Reference<OptimizedMultiCoreExecutor>>;
+
+  thread_local! {
+    // `None` until first use, when an env cleanup hook is registered to empty it
+    static EXECUTORS: RefCell<Option<Executors>> = const { RefCell::new(None) };
+  }
+
+  fn with_executors<R>(mut env: Env, f: impl FnOnce(&mut Executors) -> R) -> Result<R> {
+    if EXECUTORS.with_borrow(Option::is_none) {
+      // References must be released before the env they belong to goes away
+      env.add_env_cleanup_hook((), |_| drop(EXECUTORS.with_borrow_mut(Option::take)))?;
+      EXECUTORS.with_borrow_mut(|executors| *executors = Some(Executors::new()));
+    }
+    Ok(EXECUTORS.with_borrow_mut(|executors| f(executors.get_or_insert_with(Executors::new))))
+  }
+
+  /// Create an executor and register it under `name` for `getExecutor`
+  #[napi]
+  pub fn create_executor(
+    env: Env,
+    name: String,
+    config: Option<ExecutorConfig>
+  ) -> Result<Reference<OptimizedMultiCoreExecutor>> {
+    with_executors(env, |executors| executors.check_available(&name))?.map_err(
+      Error::from_reason
+    )?;
+    let executor = OptimizedMultiCoreExecutor::new(config)?;
+    let executor = OptimizedMultiCoreExecutor::into_reference(executor, env)?;
+    let registered = executor.clone(env)?;
+    with_executors(env, |executors| executors.insert(&name, registered))?.map_err(
+      Error::from_reason
+    )?;
+    Ok(executor)
+  }
+
+  /// Executor registered under `name` with `createExecutor`
+  #[napi]
+  pub fn get_executor(
+    env: Env,
+    name: String
+  ) -> Result<Option<Reference<OptimizedMultiCoreExecutor>>> {
+    with_executors(env, |executors| executors.get(&name).map(|executor| executor.clone(env)))?
+      .transpose()
+  }
+
+  /// Unregister `name`, returning whether it existed. The executor is not shut
+  /// down; call `shutdown` on it first if nothing else uses it.
+  #[napi]
+  pub fn remove_executor(env: Env, name: String) -> Result<bool> {
+    with_executors(env, |executors| executors.remove(&name).is_some())
+  }
+
+  /// Names registered with `createExecutor`, sorted
+  #[napi]
+  pub fn executor_names(env: Env) -> Result<Vec<String>> {
+    with_executors(env, |executors| executors.names())
+  }
+}
@@ -0,0 +1,50 @@
+// src/executor_registry_tests.rs - Named executors in the process-wide registry
+use std::sync::Arc;
+
+use crate::executor_config::ExecutorConfig;
+use crate::executor_registry::{
+  create_executor,
+  executor_names,
+  get_executor,
+  remove_executor,
+};
+
+fn workers(count: u32) -> ExecutorConfig {
+  ExecutorConfig { worker_count: Some(count), ..ExecutorConfig::default() }
+}
+
+#[test]
+fn named_pools_are_independent_and_shared_by_name() {
+  let images = create_executor("registry-images", workers(2)).unwrap();
+  let metrics = create_executor("registry-metrics", workers(1)).unwrap();
+  assert_eq!((images.worker_count(), metrics.worker_count()), (2, 1));
+
+  let found = get_executor("registry-images").expect("registered");
+  assert!(Arc::ptr_eq(&found, &images));
+  let names = executor_names();
+  assert!(names.contains(&"registry-images".to_string()));
+  assert!(names.contains(&"registry-metrics".to_string()));
+
+  // Unregistered, but still usable by whoever holds it
+  let removed = remove_executor("registry-metrics").expect("registered");
+  assert!(get_executor("registry-metrics").is_none());
+  assert_eq!(removed.worker_count(), 1);
+
+  for executor in [images, metrics] {
+    executor.shutdown().unwrap();
+  }
+  remove_executor("registry-images");
+}
+
+#[test]
+fn names_are_never_silently_replaced() {
+  let first = create_executor("registry-taken", workers(1)).unwrap();
+  let taken = create_executor("registry-taken", workers(1)).err().expect("duplicate refused");
+  assert_eq!(taken.reason, "Executor 'registry-taken' already exists");
+  let empty = create_executor("", workers(1)).err().expect("empty name refused");
+  assert_eq!(empty.reason, "Executor name must not be empty");
+
+  assert!(Arc::ptr_eq(&get_executor("registry-taken").unwrap(), &first));
+  first.shutdown().unwrap();
+  remove_executor("registry-taken");
+}
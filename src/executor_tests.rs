@@ -0,0 +1,2278 @@
+// src/executor_tests.rs - Executor behaviour end to end, through the Rust API
+use serde_json::Value;
+use std::cell::Cell;
+use std::collections::{ HashMap, HashSet };
+use std::net::UdpSocket;
+use std::sync::Arc;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::time::{ Duration, Instant };
+
+use crate::autoscale::AutoscaleConfig;
+use crate::background::BackgroundStats;
+use crate::bindings::Status;
+use crate::borrowed::SharedBytes;
+use crate::child::ChildOptions;
+use crate::clock::{ MockClock, SharedClock, SystemClock };
+use crate::discipline::QueueDiscipline;
+use crate::executor_config::ExecutorConfig;
+use crate::fallback::FallbackConfig;
+use crate::memory::PressureListener;
+use crate::priority::Priority;
+use crate::protocol::ProtocolSupport;
+use crate::rate_limit::RateLimitConfig;
+use crate::statsd::StatsdConfig;
+use crate::task_graph::GraphNode;
+use crate::task_spec;
+use crate::thread_tuning::ThreadTuning;
+use crate::{ OptimizationHints, OptimizedMultiCoreExecutor, OptimizedWorkerResult };
+
+const WAIT_MS: u32 = 5000;
+
+fn executor(config: ExecutorConfig) -> OptimizedMultiCoreExecutor {
+  OptimizedMultiCoreExecutor::new(Some(config)).expect("executor")
+}
+
+// An executor whose clock only moves when the test advances it
+fn mock_clocked(config: ExecutorConfig) -> (OptimizedMultiCoreExecutor, Arc<MockClock>) {
+  let clock = Arc::new(MockClock::new());
+  let shared: SharedClock = clock.clone();
+  (OptimizedMultiCoreExecutor::with_clock(config, shared).expect("executor"), clock)
+}
+
+// Poll `done` until it holds, failing the test after `WAIT_MS`
+fn eventually(what: &str, done: impl Fn() -> bool) {
+  let deadline = Instant::now() + Duration::from_millis(WAIT_MS as u64);
+  while !done() {
+    assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+    std::thread::sleep(Duration::from_millis(5));
+  }
+}
+
+fn workers(count: u32) -> ExecutorConfig {
+  ExecutorConfig { worker_count: Some(count), ..ExecutorConfig::default() }
+}
+
+fn submit(executor: &OptimizedMultiCoreExecutor, function_code: &str, data: &str) -> String {
+  let hints = task_spec::resolve_hints(function_code, &[]).expect("hints");
+  executor
+    .submit_task(function_code.to_string(), data.to_string(), hints, None, None)
+    .expect("submit")
+}
+
+fn hints(function_code: &str) -> OptimizationHints {
+  task_spec::resolve_hints(function_code, &[]).expect("hints")
+}
+
+fn run(
+  executor: &OptimizedMultiCoreExecutor,
+  function_code: &str,
+  data: &str
+) -> OptimizedWorkerResult {
+  let task_id = submit(executor, function_code, data);
+  executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result")
+}
+
+fn run_as(
+  executor: &OptimizedMultiCoreExecutor,
+  operation_type: &str,
+  function_code: &str,
+  data: &str
+) -> OptimizedWorkerResult {
+  let mut hints = task_spec::resolve_hints(function_code, &[]).expect("hints");
+  hints.operation_type = operation_type.to_string();
+  let task_id = executor
+    .submit_task(function_code.to_string(), data.to_string(), hints, None, None)
+    .expect("submit");
+  executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result")
+}
+
+// Events drained so far, parsed
+fn events(executor: &OptimizedMultiCoreExecutor) -> Vec<Value> {
+  executor
+    .drain_events()
+    .expect("events")
+    .iter()
+    .map(|event| serde_json::from_str(event).expect("event JSON"))
+    .collect()
+}
+
+#[test]
+fn native_panic_fails_only_its_task() {
+  let executor = executor(ExecutorConfig { supervision_interval_ms: Some(0), ..workers(1) });
+  executor.set_fault_injection(Some(r#"{"native_panic_rate": 1.0}"#.to_string())).unwrap();
+  let failed = run(&executor, "x => x * 2", "21");
+  let error = failed.error.expect("panic reported as the task's error");
+  assert!(error.starts_with("Native execution panicked: Injected native panic"), "{}", error);
+  assert_eq!(failed.optimization_used, "rust_failed");
+
+  // With nothing to respawn it, only the same worker can serve the next task
+  executor.set_fault_injection(None).unwrap();
+  let next = run(&executor, "x => x * 2", "21");
+  assert_eq!(next.result.as_deref(), Some("42"));
+  assert_eq!(next.worker_id, failed.worker_id);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn dead_worker_is_respawned() {
+  let executor = executor(ExecutorConfig { supervision_interval_ms: Some(10), ..workers(1) });
+  executor.set_fault_injection(Some(r#"{"worker_panic_rate": 1.0}"#.to_string())).unwrap();
+  // The worker panics taking this task, which is lost with it
+  submit(&executor, "x => x * 2", "1");
+
+  let deadline = Instant::now() + Duration::from_millis(WAIT_MS as u64);
+  let respawned = loop {
+    let respawned = events(&executor)
+      .into_iter()
+      .find(|event| event["kind"] == "worker_respawned");
+    if let Some(event) = respawned {
+      break event;
+    }
+    assert!(Instant::now() < deadline, "no worker_respawned event");
+    std::thread::sleep(Duration::from_millis(5));
+  };
+  assert_eq!(respawned["worker_id"], 0);
+  let message = respawned["message"].as_str().unwrap_or_default();
+  assert!(message.contains("panicked: Injected worker panic on worker 0"), "{}", message);
+
+  // The pool's only slot serves again
+  executor.set_fault_injection(None).unwrap();
+  let next = run(&executor, "x => x * 2", "21");
+  assert_eq!(next.result.as_deref(), Some("42"));
+  assert_eq!(next.worker_id, 0);
+  executor.shutdown().unwrap();
+}
+
+fn node(name: &str, function_code: &str, depends_on: &[&str], compensation: &str) -> GraphNode {
+  GraphNode {
+    name: name.to_string(),
+    function_code: function_code.to_string(),
+    data: depends_on.is_empty().then(|| "10".to_string()),
+    depends_on: (!depends_on.is_empty())
+      .then(|| depends_on.iter().map(|parent| parent.to_string()).collect()),
+    hints: Some(task_spec::resolve_hints(function_code, &[]).expect("hints")),
+    compensation_code: (!compensation.is_empty()).then(|| compensation.to_string()),
+  }
+}
+
+// Graph nodes whose compensations have run, in the order they ran
+fn compensated(executor: &OptimizedMultiCoreExecutor) -> Vec<String> {
+  events(executor)
+    .into_iter()
+    .filter(|event| event["kind"] == "node_compensated")
+    .map(|event| event["message"].as_str().unwrap_or_default().to_string())
+    .collect()
+}
+
+#[test]
+fn failed_graph_node_compensates_earlier_nodes_in_reverse() {
+  let executor = executor(workers(2));
+  let nodes = vec![
+    node("debit", "x => x * 2", &[], "x => Math.sqrt(x)"),
+    node("credit", "x => x + 5", &["debit"], "x => x + 10"),
+    node("notify", "x => fetch(x)", &["credit"], "x => x * x")
+  ];
+  let hints = nodes[0].hints.clone().unwrap();
+  let task_ids = executor.submit_graph(nodes, hints, None).expect("submit");
+
+  let failed = executor.get_result_by_id(task_ids[2].clone(), Some(WAIT_MS)).expect("result");
+  assert!(failed.error.is_some());
+  // The failed node's own compensation doesn't run, and the others ran first
+  assert_eq!(compensated(&executor), [
+    "Compensated graph node 'credit' after node 'notify' failed",
+    "Compensated graph node 'debit' after node 'notify' failed",
+  ]);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn succeeded_graph_runs_no_compensations() {
+  let executor = executor(workers(2));
+  let nodes = vec![
+    node("debit", "x => x * 2", &[], "x => Math.sqrt(x)"),
+    node("credit", "x => x + 5", &["debit"], "x => x + 10")
+  ];
+  let hints = nodes[0].hints.clone().unwrap();
+  let task_ids = executor.submit_graph(nodes, hints, None).expect("submit");
+
+  let last = executor.get_result_by_id(task_ids[1].clone(), Some(WAIT_MS)).expect("result");
+  assert_eq!(last.result.as_deref(), Some("25"));
+  assert!(compensated(&executor).is_empty());
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn only_binary_operations_return_bytes() {
+  let executor = executor(workers(1));
+  let encoded = run_as(&executor, "binary_operations", "s => Buffer.from(s)", r#""hi""#);
+  assert_eq!(encoded.binary.map(|bytes| bytes.0), Some(b"hi".to_vec()));
+  assert_eq!(encoded.result, None);
+
+  // Mentioning Buffer.from doesn't make a general function a byte encoder
+  let general = run_as(&executor, "general", "s => Buffer.from(s).toString('hex')", r#""hi""#);
+  assert!(general.binary.is_none());
+  assert_eq!(general.error.as_deref(), Some("General function requires JavaScript worker"));
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn background_pass_waits_for_the_clock() {
+  let config = ExecutorConfig { background_optimization: Some(true), ..workers(1) };
+  let (executor, clock) = mock_clocked(config);
+  let started = Instant::now();
+  // The first pass is due right away, once a task has run
+  run(&executor, "x => x * 2", "1");
+  eventually("the first pass", || executor.get_background_stats().passes == 1);
+
+  // However long it really takes, the next isn't due until a second has passed
+  run(&executor, "x => x * 2", "2");
+  clock.advance(Duration::from_millis(999));
+  run(&executor, "x => x * 2", "3");
+  std::thread::sleep(Duration::from_millis(50));
+  assert_eq!(executor.get_background_stats().passes, 1);
+
+  clock.advance(Duration::from_millis(1));
+  run(&executor, "x => x * 2", "4");
+  eventually("the second pass", || executor.get_background_stats().passes == 2);
+  // Due by the real clock, it would have taken a second
+  assert!(started.elapsed() < Duration::from_millis(900), "{:?}", started.elapsed());
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn health_check_deadline_follows_the_clock() {
+  let (executor, clock) = mock_clocked(workers(1));
+  // The canary is held up far longer than the test takes
+  let delay = r#"{"delay_rate": 1.0, "delay_ms": 500}"#;
+  executor.set_fault_injection(Some(delay.to_string())).unwrap();
+
+  let started = Instant::now();
+  let check = executor.start_health_check(Some(100));
+  clock.advance(Duration::from_millis(101));
+  let status = check.wait();
+  assert_eq!(status.status, "unhealthy");
+  assert_eq!(status.error.as_deref(), Some("Canary task did not finish within 100ms"));
+  // Past its deadline on the clock, the check doesn't wait in real time
+  assert!(started.elapsed() < Duration::from_millis(100), "{:?}", started.elapsed());
+  executor.shutdown().unwrap();
+}
+
+// Task IDs submitted by `count` threads at once, `per_thread` each or until
+// the executor turns one away
+fn submit_concurrently(
+  executor: &Arc<OptimizedMultiCoreExecutor>,
+  count: usize,
+  per_thread: usize
+) -> Vec<String> {
+  let submitters: Vec<_> = (0..count)
+    .map(|submitter| {
+      let executor = Arc::clone(executor);
+      std::thread::spawn(move || {
+        let hints = task_spec::resolve_hints("x => x * 2", &[]).expect("hints");
+        (0..per_thread)
+          .map_while(|i| {
+            let data = (submitter * per_thread + i).to_string();
+            executor.submit_task("x => x * 2".to_string(), data, hints.clone(), None, None).ok()
+          })
+          .collect::<Vec<_>>()
+      })
+    })
+    .collect();
+  submitters
+    .into_iter()
+    .flat_map(|submitter| submitter.join().expect("submitter"))
+    .collect()
+}
+
+// Collect the result of each of `task_ids`, failing on any task that ran twice
+fn collect_once(executor: &OptimizedMultiCoreExecutor, task_ids: &[String]) {
+  let unique: HashSet<&String> = task_ids.iter().collect();
+  assert_eq!(unique.len(), task_ids.len(), "task IDs handed out twice");
+  for task_id in task_ids {
+    let result = executor.get_result_by_id(task_id.clone(), Some(WAIT_MS)).expect("result");
+    assert!(result.error.is_none(), "{:?}", result.error);
+  }
+  // A task run twice leaves its second result behind
+  assert!(executor.poll_results(None).expect("poll").is_empty());
+}
+
+#[test]
+fn concurrently_submitted_tasks_complete_once() {
+  let executor = Arc::new(executor(workers(4)));
+  let task_ids = submit_concurrently(&executor, 4, 250);
+  assert_eq!(task_ids.len(), 1000);
+  collect_once(&executor, &task_ids);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn drain_keeps_every_accepted_task_through_shutdown() {
+  let executor = Arc::new(executor(workers(4)));
+  let submitting = {
+    let executor = Arc::clone(&executor);
+    std::thread::spawn(move || submit_concurrently(&executor, 4, 500))
+  };
+  std::thread::sleep(Duration::from_millis(2));
+  // Races the submitters: whatever it doesn't turn away must finish
+  assert!(executor.start_drain(Some(WAIT_MS)).wait());
+  let task_ids = submitting.join().expect("submitters");
+  collect_once(&executor, &task_ids);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn task_released_after_shutdown_is_reported() {
+  // The second worker parks the second task while the first runs
+  let executor = executor(workers(2));
+  let hints = task_spec::resolve_hints("x => x * 2", &[]).expect("hints");
+  executor.set_concurrency_limit(hints.function_hash.clone(), Some(1)).unwrap();
+  let delay = r#"{"delay_rate": 1.0, "delay_ms": 300}"#;
+  executor.set_fault_injection(Some(delay.to_string())).unwrap();
+  submit(&executor, "x => x * 2", "1");
+  let parked = submit(&executor, "x => x * 2", "2");
+  eventually("the second task to park", || {
+    executor.get_task_status(parked.clone()).is_ok_and(|status| status.status == "waiting")
+  });
+
+  executor.shutdown().unwrap();
+  executor.set_concurrency_limit(hints.function_hash, None).unwrap();
+  assert_eq!(executor.get_task_status(parked.clone()).unwrap().status, "failed");
+  let dropped: Vec<Value> = events(&executor)
+    .into_iter()
+    .filter(|event| event["kind"] == "task_dropped")
+    .collect();
+  assert_eq!(dropped.len(), 1);
+  let message = dropped[0]["message"].as_str().unwrap_or_default();
+  assert!(message.contains(&parked), "{}", message);
+}
+
+#[test]
+fn submit_to_idle_pool_completes_promptly() {
+  let executor = executor(workers(2));
+  for round in 0..10 {
+    // Long enough for both workers to have gone to sleep
+    std::thread::sleep(Duration::from_millis(20));
+    let started = Instant::now();
+    let result = run(&executor, "x => x * 2", &round.to_string());
+    assert_eq!(result.result, Some((round * 2).to_string()));
+    let took = started.elapsed();
+    assert!(took < Duration::from_millis(50), "round {} took {:?}", round, took);
+  }
+  executor.shutdown().unwrap();
+}
+
+// Threads of this process whose name starts with `prefix`
+#[cfg(target_os = "linux")]
+fn threads_named(prefix: &str) -> usize {
+  std::fs::read_dir("/proc/self/task")
+    .expect("threads")
+    .filter_map(|task| std::fs::read_to_string(task.ok()?.path().join("comm")).ok())
+    .filter(|name| name.starts_with(prefix))
+    .count()
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn shutdown_joins_parked_workers() {
+  let config = ExecutorConfig { thread_name_prefix: Some("parked-".to_string()), ..workers(4) };
+  let executor = executor(config);
+  run(&executor, "x => x * 2", "1");
+  eventually("the workers to start", || threads_named("parked-") == 4);
+  std::thread::sleep(Duration::from_millis(20));
+
+  let started = Instant::now();
+  executor.shutdown().unwrap();
+  assert_eq!(threads_named("parked-"), 0);
+  // Parked workers leave at once, well before the wait for busy ones gives up
+  assert!(started.elapsed() < Duration::from_millis(500), "{:?}", started.elapsed());
+}
+
+#[test]
+fn overrun_is_reported_at_its_deadline() {
+  let executor = executor(workers(1));
+  let delay = r#"{"delay_rate": 1.0, "delay_ms": 600}"#;
+  executor.set_fault_injection(Some(delay.to_string())).unwrap();
+  let hints = task_spec::resolve_hints("x => x * 2", &[]).expect("hints");
+
+  let started = Instant::now();
+  let task_id = executor
+    .submit_task("x => x * 2".to_string(), "1".to_string(), hints, None, Some(50))
+    .expect("submit");
+  let result = executor.get_result_by_id(task_id.clone(), Some(WAIT_MS)).expect("result");
+  assert!(result.timed_out);
+  assert_eq!(result.error.as_deref(), Some("Task exceeded its 50ms timeout (still running)"));
+  // Reported while the task still holds its worker
+  assert!(started.elapsed() < Duration::from_millis(400), "{:?}", started.elapsed());
+  assert_eq!(executor.get_task_status(task_id).unwrap().status, "failed");
+
+  // The worker takes the next task once the overrun one finishes, whose late
+  // result is discarded
+  run(&executor, "x => x * 2", "2");
+  assert!(executor.poll_results(None).unwrap().is_empty());
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn string_api_takes_and_returns_json() {
+  let executor = executor(workers(1));
+  let hints = task_spec::resolve_hints("x => x * 2", &[]).expect("hints");
+  let hints = serde_json::to_string(&hints).expect("hints JSON");
+  let meta = Some(r#"{"request":7}"#.to_string());
+  let task_id = executor
+    .submit_optimized_task_json("x => x * 2".to_string(), "21".to_string(), hints, meta)
+    .expect("submit");
+
+  let result: Value = serde_json
+    ::from_str(&executor.get_optimized_result_json(Some(WAIT_MS)).expect("result"))
+    .expect("result JSON");
+  assert_eq!(result["id"], task_id.as_str());
+  assert_eq!(result["result"], "42");
+  assert_eq!(result["meta"]["request"], 7);
+  let bad = executor.submit_optimized_task_json("x => x".into(), "1".into(), "{}".into(), None);
+  assert!(bad.unwrap_err().reason.starts_with("Invalid optimization hints"));
+
+  let notices: Vec<Value> = events(&executor)
+    .into_iter()
+    .filter(|event| event["kind"] == "deprecated_call")
+    .collect();
+  assert_eq!(notices.len(), 2);
+  let message = notices[0]["message"].as_str().unwrap_or_default();
+  assert!(message.starts_with("submitOptimizedTaskJson"), "{}", message);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn cancelling_finished_or_unknown_tasks_records_nothing() {
+  let executor = executor(workers(1));
+  let finished = run(&executor, "x => x * 2", "1").id;
+  executor.cancel_task(finished);
+  executor.cancel_task("no-such-task".to_string());
+  assert_eq!(executor.cancellations.pending(), 0);
+
+  // A queued task is dropped by the worker that reaches it, which clears it
+  let delay = r#"{"delay_rate": 1.0, "delay_ms": 100}"#;
+  executor.set_fault_injection(Some(delay.to_string())).unwrap();
+  submit(&executor, "x => x * 2", "2");
+  let queued = submit(&executor, "x => x * 2", "3");
+  executor.cancel_task(queued.clone());
+  assert_eq!(executor.cancellations.pending(), 1);
+  assert_eq!(executor.get_task_status(queued).unwrap().status, "cancelled");
+  eventually("the worker to drop the task", || executor.cancellations.pending() == 0);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn meta_is_echoed_back_with_each_result() {
+  let executor = executor(workers(2));
+  let meta = serde_json::json!({ "request": "r-1", "attempt": 2 });
+  let code = "x => x * 2".to_string();
+  let task_id = executor
+    .submit_task(code, "1".to_string(), hints("x => x * 2"), Some(meta.clone()), None)
+    .expect("submit");
+  let result = executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result");
+  assert_eq!(result.meta, Some(meta));
+
+  let tasks = vec![("x => x * 2".to_string(), "1".to_string()); 3];
+  let metas: Vec<Value> = (0..3).map(|i| serde_json::json!({ "index": i })).collect();
+  let ids = executor
+    .submit_optimized_batch(tasks.clone(), hints("x => x * 2"), Some(metas), None, None, None)
+    .expect("batch");
+  for (index, task_id) in ids.into_iter().enumerate() {
+    let result = executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result");
+    assert_eq!(result.meta, Some(serde_json::json!({ "index": index })));
+  }
+
+  // One meta per task, or none at all
+  let short = vec![serde_json::json!(1)];
+  let mismatched = executor
+    .submit_optimized_batch(tasks, hints("x => x * 2"), Some(short), None, None, None);
+  assert!(mismatched.is_err());
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn injected_faults_surface_as_documented() {
+  let executor = executor(workers(1));
+  executor.set_fault_injection(Some(r#"{"queue_full_rate": 1.0}"#.to_string())).unwrap();
+  let rejected = executor.submit_task("x => x".into(), "1".into(), hints("x => x"), None, None);
+  assert_eq!(rejected.unwrap_err().reason, "Task queue is full (injected fault)");
+
+  executor.set_fault_injection(Some(r#"{"timeout_rate": 1.0}"#.to_string())).unwrap();
+  let timed_out = run(&executor, "x => x * 2", "1");
+  assert!(timed_out.timed_out);
+  assert_eq!(timed_out.error.as_deref(), Some("Task timed out (injected fault)"));
+  assert_eq!(timed_out.optimization_used, "fault_injected");
+
+  // Malformed rates are refused rather than half applied
+  assert!(executor.set_fault_injection(Some("{\"delay_rate\": \"often\"}".into())).is_err());
+  executor.set_fault_injection(None).unwrap();
+  assert_eq!(run(&executor, "x => x * 2", "21").result.as_deref(), Some("42"));
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn soak_run_reports_steady_caches() {
+  // Other tests start and stop threads meanwhile, so a growth failure is
+  // possible here; the report comes back either way
+  let report = match crate::soak::run_soak_test(300, Some(2)) {
+    Ok(report) => report,
+    Err(err) => err.reason
+      .strip_prefix("Soak test detected unbounded growth: ")
+      .expect("growth report")
+      .to_string(),
+  };
+  let report: Value = serde_json::from_str(&report).expect("report JSON");
+  assert!(report["tasks_completed"].as_u64().unwrap() >= 2 * 4 * 256, "{}", report);
+  for table in ["performance_cache_entries", "hot_function_entries"] {
+    assert!(report["final_sample"][table].as_u64().unwrap() <= 4, "{}", report);
+  }
+  let failures = report["failures"].as_array().unwrap();
+  assert!(failures.iter().all(|failure| !failure.as_str().unwrap().contains("entries")));
+}
+
+#[test]
+fn poisoned_lock_is_recovered_and_reported_once() {
+  let executor = executor(workers(1));
+  let stats = Arc::clone(&executor.optimization_stats);
+  let _ = std::thread::spawn(move || {
+    let _held = stats.lock().unwrap();
+    panic!("poisoning the stats lock");
+  }).join();
+  assert!(executor.optimization_stats.is_poisoned());
+
+  run(&executor, "x => x * 2", "1");
+  run(&executor, "x => x * 2", "2");
+  assert_eq!(executor.get_stats_object().total_executions, 2);
+  let poisoned: Vec<Value> = events(&executor)
+    .into_iter()
+    .filter(|event| event["kind"] == "mutex_poisoned")
+    .collect();
+  assert_eq!(poisoned.len(), 1);
+  let message = poisoned[0]["message"].as_str().unwrap_or_default();
+  assert!(message.contains("`optimization_stats`"), "{}", message);
+  executor.shutdown().unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn shutdown_lets_busy_workers_finish_then_exit() {
+  let config = ExecutorConfig { thread_name_prefix: Some("busy-".to_string()), ..workers(2) };
+  let executor = executor(config);
+  let delay = r#"{"delay_rate": 1.0, "delay_ms": 100}"#;
+  executor.set_fault_injection(Some(delay.to_string())).unwrap();
+  let task_id = submit(&executor, "x => x * 2", "1");
+  eventually("the task to start", || {
+    executor.get_task_status(task_id.clone()).is_ok_and(|status| status.status == "running")
+  });
+
+  executor.shutdown().unwrap();
+  // Told to stop mid-task, the busy worker leaves as soon as the task is done
+  assert_eq!(threads_named("busy-"), 0);
+  assert_eq!(executor.get_task_status(task_id).unwrap().status, "completed");
+  let rejected = executor.submit_task("x => x".into(), "1".into(), hints("x => x"), None, None);
+  assert_eq!(rejected.unwrap_err().reason, "Worker pool is shut down");
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn every_worker_delivers_its_own_results() {
+  let executor = executor(workers(4));
+  // Held up long enough that each worker takes one of the tasks
+  let delay = r#"{"delay_rate": 1.0, "delay_ms": 50}"#;
+  executor.set_fault_injection(Some(delay.to_string())).unwrap();
+  let task_ids: Vec<String> = (0..4)
+    .map(|i| submit(&executor, "x => x * 2", &i.to_string()))
+    .collect();
+  let served: HashSet<u32> = task_ids
+    .into_iter()
+    .map(|task_id| executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result").worker_id)
+    .collect();
+  assert_eq!(served.len(), 4);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn batch_is_accounted_for_once_collected() {
+  let executor = executor(workers(4));
+  let tasks = vec![("x => x * 2".to_string(), "1".to_string()); 500];
+  let task_ids = executor
+    .submit_optimized_batch(tasks, hints("x => x * 2"), None, None, None, None)
+    .expect("batch");
+  for task_id in task_ids {
+    executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result");
+  }
+  // Workers that didn't deliver the last result merge their counts once idle
+  eventually("every result counted", || executor.get_stats_object().total_executions == 500);
+  assert_eq!(executor.get_stats_object().rust_native_hits, 500);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn typed_stats_match_the_json_ones() {
+  let executor = executor(workers(1));
+  run(&executor, "x => x * 2", "1");
+  run(&executor, "x => x * 2", "2");
+  // Left to a JavaScript worker: counted as a fallback
+  let mut js_only = hints("x => fetch(x)");
+  js_only.should_use_rust = false;
+  let task_id = executor
+    .submit_task("x => fetch(x)".into(), "1".into(), js_only, None, None)
+    .expect("submit");
+  executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result");
+
+  let stats = executor.get_stats_object();
+  assert_eq!(stats.total_executions, 3);
+  assert_eq!(stats.rust_native_hits, 2);
+  assert_eq!(stats.js_worker_fallbacks, 1);
+  assert!((stats.optimization_ratio - 2.0 / 3.0).abs() < 1e-9);
+  let json = executor.stats_json();
+  assert_eq!(json["total_executions"], stats.total_executions);
+  assert_eq!(json["optimization_ratio"], stats.optimization_ratio);
+  assert_eq!(json["cache_hit_ratio"], stats.cache_hit_ratio);
+
+  executor.reset_optimization_state().unwrap();
+  assert_eq!(executor.get_stats_object().total_executions, 0);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn hot_functions_are_ranked_by_count() {
+  let executor = executor(workers(1));
+  for i in 0..3 {
+    run(&executor, "x => x * 2", &i.to_string());
+  }
+  run(&executor, "s => s.toUpperCase()", r#""a""#);
+
+  let hot = executor.get_hot_functions(None);
+  assert_eq!(hot.len(), 2);
+  assert_eq!(hot[0].function_hash, hints("x => x * 2").function_hash);
+  assert_eq!(hot[0].execution_count, 3);
+  assert_eq!(hot[1].execution_count, 1);
+  assert_eq!(executor.get_hot_functions(Some(1)).len(), 1);
+
+  let cached = executor.get_performance_cache_entries(None);
+  assert_eq!(cached.len(), 2);
+  assert!(cached.iter().all(|profile| profile.last_success == Some(true)));
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn routing_override_pins_a_function_to_a_backend() {
+  let executor = executor(workers(1));
+  let hash = hints("x => x * 2").function_hash;
+  executor.set_routing_override(hash.clone(), "js".to_string()).unwrap();
+  assert_eq!(executor.get_routing_overrides().get(&hash).map(String::as_str), Some("js"));
+  assert_eq!(run(&executor, "x => x * 2", "21").optimization_used, "needs_js_worker");
+  // Other functions keep routing by their hints
+  assert_eq!(run(&executor, "s => s.toUpperCase()", r#""a""#).result.as_deref(), Some(r#""A""#));
+
+  executor.set_routing_override(hash.clone(), "auto".to_string()).unwrap();
+  assert!(executor.get_routing_overrides().is_empty());
+  assert_eq!(run(&executor, "x => x * 2", "21").result.as_deref(), Some("42"));
+  assert!(executor.set_routing_override(hash, "gpu".to_string()).is_err());
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn poll_returns_at_once_with_up_to_max_results() {
+  let executor = executor(workers(1));
+  let started = Instant::now();
+  assert!(executor.poll_results(None).unwrap().is_empty());
+  assert!(started.elapsed() < Duration::from_millis(50), "{:?}", started.elapsed());
+
+  let task_ids: Vec<String> = (0..3)
+    .map(|i| submit(&executor, "x => x * 2", &i.to_string()))
+    .collect();
+  // The only worker delivers in order, so the three are in once this one is
+  run(&executor, "x => x * 2", "3");
+  assert_eq!(executor.poll_results(Some(2)).unwrap().len(), 2);
+  let rest = executor.poll_results(None).unwrap();
+  assert_eq!(rest.len(), 1);
+  assert!(task_ids.contains(&rest[0].id));
+  assert!(executor.poll_results(None).unwrap().is_empty());
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn registered_ranges_resolve_to_the_highest_match() {
+  let executor = executor(workers(1));
+  let versions = [("1.0.0", "x => x * 2"), ("1.5.0", "x => Math.sqrt(x)"), ("2.0.0", "x => x * 2")];
+  for (version, code) in versions {
+    let key = executor.register_function(format!("f@{}", version), code.to_string(), hints(code));
+    assert_eq!(key.unwrap(), format!("f@{}", version));
+  }
+  assert_eq!(executor.list_functions(), ["f@1.0.0", "f@1.5.0", "f@2.0.0"]);
+
+  let registered = |spec: &str| {
+    let task_id = executor.submit_registered(spec.to_string(), "16".to_string(), None, None);
+    executor.get_result_by_id(task_id.expect("submit"), Some(WAIT_MS)).expect("result")
+  };
+  assert_eq!(registered("f@^1").result.as_deref(), Some("4"));
+  assert_eq!(registered("f@~1.0").result.as_deref(), Some("32"));
+  assert_eq!(registered("f").result.as_deref(), Some("32"));
+  // Each version keeps its own learning state
+  let hot = executor.get_hot_functions(None);
+  let count = |key: &str| hot.iter().find(|p| p.function_hash == key).map(|p| p.execution_count);
+  assert_eq!((count("f@1.0.0"), count("f@1.5.0"), count("f@2.0.0")), (Some(1), Some(1), Some(1)));
+
+  assert!(executor.submit_registered("f@^3".into(), "1".into(), None, None).is_err());
+  executor.unregister_function("f@1.5.0".to_string()).unwrap();
+  assert_eq!(registered("f@^1").result.as_deref(), Some("32"));
+  executor.unregister_function("f".to_string()).unwrap();
+  assert!(executor.list_functions().is_empty());
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn staged_version_takes_its_share_until_promoted_or_rolled_back() {
+  let executor = executor(workers(2));
+  executor.register_function("f@1.0.0".into(), "x => x * 2".into(), hints("x => x * 2")).unwrap();
+  let sqrt = "x => Math.sqrt(x)";
+  // A registered version can't be staged again
+  assert!(executor.stage_function("f@1.0.0".into(), sqrt.into(), hints(sqrt), 0.5).is_err());
+  assert!(executor.stage_function("f@2.0.0".into(), sqrt.into(), hints(sqrt), 1.5).is_err());
+  executor.stage_function("f@2.0.0".into(), sqrt.into(), hints(sqrt), 0.25).unwrap();
+
+  let results: Vec<String> = (0..400)
+    .map(|_| executor.submit_registered("f".into(), "16".into(), None, None).expect("submit"))
+    .collect::<Vec<_>>()
+    .into_iter()
+    .map(|task_id| executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result"))
+    .map(|result| result.result.expect("output"))
+    .collect();
+  let staged = results.iter().filter(|output| *output == "4").count();
+  assert!((60..140).contains(&staged), "{} of 400 went to the staged version", staged);
+  let status = executor.get_rollout_status("f".to_string());
+  assert_eq!(status.traffic_fraction, 0.25);
+  assert_eq!(status.staged.unwrap().execution_count as usize, staged);
+  assert_eq!(status.stable.unwrap().execution_count as usize, 400 - staged);
+
+  let registered = |spec: &str| {
+    let task_id = executor.submit_registered(spec.to_string(), "16".to_string(), None, None);
+    executor.get_result_by_id(task_id.expect("submit"), Some(WAIT_MS)).expect("result").result
+  };
+  // A range the staged version doesn't satisfy never reaches it
+  executor.set_staged_traffic("f".to_string(), 1.0).unwrap();
+  assert_eq!(registered("f@^1").as_deref(), Some("32"));
+  assert_eq!(registered("f").as_deref(), Some("4"));
+
+  assert_eq!(executor.rollback_staged("f".to_string()).unwrap(), "f@2.0.0");
+  assert_eq!(registered("f").as_deref(), Some("32"));
+  executor.stage_function("f@2.0.0".into(), sqrt.into(), hints(sqrt), 0.0).unwrap();
+  assert_eq!(registered("f").as_deref(), Some("32"));
+  assert_eq!(executor.promote_staged("f".to_string()).unwrap(), "f@2.0.0");
+  assert_eq!(registered("f").as_deref(), Some("4"));
+  assert!(executor.get_rollout_status("f".to_string()).staged.is_none());
+  assert!(executor.promote_staged("f".to_string()).is_err());
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn results_carry_native_values() {
+  let executor = executor(workers(2));
+  let tasks = vec![("x => x * 2".to_string(), "21".to_string()); 4];
+  let metas = vec![serde_json::json!({ "tags": ["a", "b"], "n": 1.5 }); 4];
+  let task_ids = executor
+    .submit_optimized_batch(tasks, hints("x => x * 2"), Some(metas.clone()), None, None, None)
+    .expect("batch");
+
+  let results = executor.get_optimized_batch_results(4, Some(WAIT_MS)).expect("results");
+  let mut ids: Vec<String> = results.iter().map(|result| result.id.clone()).collect();
+  ids.sort();
+  let mut submitted = task_ids.clone();
+  submitted.sort();
+  assert_eq!(ids, submitted);
+  for result in results {
+    // Meta comes back as the object it went in as, not as JSON text
+    assert_eq!(result.meta.as_ref(), Some(&metas[0]));
+    assert_eq!(result.result.as_deref(), Some("42"));
+    assert!(result.duration_ms >= 0.0);
+  }
+  // Nothing left to collect: the batch call gives up at its deadline
+  assert!(executor.get_optimized_batch_results(1, Some(20)).is_err());
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn receipt_identifies_the_exact_computation() {
+  let executor = executor(workers(1));
+  let receipt = |code: &str, data: &str| {
+    let mut hints = hints(code);
+    hints.include_receipt = Some(true);
+    let task_id = executor
+      .submit_task(code.to_string(), data.to_string(), hints, None, None)
+      .expect("submit");
+    let result = executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result");
+    result.receipt.expect("receipt")
+  };
+  let first = receipt("x => x * 2", "21");
+  assert_eq!(first.backend, "rust_optimized");
+  assert_eq!(first.seed, executor.get_seed());
+  assert_eq!(first.crate_version, env!("CARGO_PKG_VERSION"));
+  assert_eq!(first.function_hash, hints("x => x * 2").function_hash);
+
+  // Same computation, same hashes; any change in code or input shows
+  let again = receipt("x => x * 2", "21");
+  assert_eq!((&again.code_hash, &again.data_hash), (&first.code_hash, &first.data_hash));
+  assert_ne!(receipt("x => x * 2", "22").data_hash, first.data_hash);
+  assert_ne!(receipt("x  =>  x * 2", "21").code_hash, first.code_hash);
+
+  // Only on request
+  assert!(run(&executor, "x => x * 2", "21").receipt.is_none());
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn batch_tasks_can_override_the_shared_hints() {
+  let executor = executor(workers(2));
+  let tasks = vec![
+    ("x => x * 2".to_string(), "21".to_string()),
+    ("s => s.toUpperCase()".to_string(), r#""a""#.to_string()),
+    ("x => x * 2".to_string(), "4".to_string())
+  ];
+  let mut js_only = hints("x => x * 2");
+  js_only.should_use_rust = false;
+  let overrides = vec![None, Some(hints("s => s.toUpperCase()")), Some(js_only)];
+  let task_ids = executor
+    .submit_optimized_batch(tasks.clone(), hints("x => x * 2"), None, Some(overrides), None, None)
+    .expect("batch");
+  let results: Vec<OptimizedWorkerResult> = task_ids
+    .into_iter()
+    .map(|task_id| executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result"))
+    .collect();
+  assert_eq!(results[0].result.as_deref(), Some("42"));
+  assert_eq!(results[1].result.as_deref(), Some(r#""A""#));
+  assert_eq!(results[2].optimization_used, "needs_js_worker");
+
+  let short = Some(vec![None]);
+  let mismatched = executor
+    .submit_optimized_batch(tasks, hints("x => x * 2"), None, short, None, None)
+    .unwrap_err();
+  assert_eq!(mismatched.reason, "Expected 3 hint entries, got 1");
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn deterministic_reduction_is_bit_identical() {
+  // Far past the parallel threshold, with sums whose last bits depend on order
+  let values: Vec<f64> = (0..50_000).map(|i| 0.1 * ((i % 97) as f64) + 1e-7 * (i as f64)).collect();
+  let data = serde_json::to_string(&values).unwrap();
+  // Fixed chunks summed in order: what deterministic mode promises
+  let expected: f64 = values
+    .chunks(4_096)
+    .map(|chunk| chunk.iter().sum::<f64>())
+    .collect::<Vec<f64>>()
+    .iter()
+    .sum();
+
+  let code = "xs => xs.reduce((sum, x) => sum + x, 0)";
+  for worker_count in [1, 4] {
+    let executor = executor(workers(worker_count));
+    for _ in 0..5 {
+      let mut hints = hints(code);
+      hints.deterministic = Some(true);
+      let task_id = executor
+        .submit_task(code.to_string(), data.clone(), hints, None, None)
+        .expect("submit");
+      let result = executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result");
+      let sum: f64 = result.result.expect("sum").parse().unwrap();
+      assert_eq!(sum.to_bits(), expected.to_bits(), "{} vs {}", sum, expected);
+    }
+    executor.shutdown().unwrap();
+  }
+}
+
+#[test]
+fn numeric_results_follow_precision_and_rounding() {
+  let executor = executor(workers(1));
+  let formatted = |code: &str, data: &str, precision: Option<u32>, rounding: Option<&str>| {
+    let mut hints = hints(code);
+    hints.output_precision = precision;
+    hints.rounding = rounding.map(str::to_string);
+    let task_id = executor
+      .submit_task(code.to_string(), data.to_string(), hints, None, None)
+      .expect("submit");
+    let result = executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result");
+    result.result.or(result.error).unwrap_or_default()
+  };
+  assert_eq!(formatted("x => Math.sqrt(x)", "2", Some(3), None), "1.414");
+  assert_eq!(formatted("x => Math.sqrt(x)", "2", Some(3), Some("ceil")), "1.415");
+  // A mode without a precision rounds to an integer
+  assert_eq!(formatted("x => x * 2", "1.25", None, Some("half_up")), "3");
+  assert_eq!(formatted("x => x * 2", "1.25", None, Some("half_even")), "2");
+  assert_eq!(formatted("x => x * 2", "-1.3", None, Some("trunc")), "-2");
+  assert_eq!(formatted("x => x * 2", "1.3", Some(0), Some("floor")), "2");
+  assert_eq!(formatted("x => x * 2", "1.3", None, None), "2.6");
+  let unknown = formatted("x => x * 2", "1", None, Some("nearest"));
+  assert!(unknown.contains("Unknown rounding mode 'nearest'"), "{}", unknown);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn reserved_batch_runs_only_on_its_own_workers() {
+  let executor = executor(workers(3));
+  executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 20}"#.into())).unwrap();
+  let tasks = vec![("x => x * 2".to_string(), "1".to_string()); 6];
+  let reserved = executor
+    .submit_optimized_batch(tasks.clone(), hints("x => x * 2"), None, None, None, Some(2))
+    .expect("batch");
+  // Waits in line for the first reservation's workers
+  let whole_pool = executor
+    .submit_optimized_batch(tasks.clone(), hints("x => x * 2"), None, None, None, Some(3))
+    .expect("batch");
+  // Reserved tasks stay out of the shared queue, which keeps flowing to the
+  // worker left over
+  assert_eq!(executor.get_queue_depth(), 0);
+  let shared: Vec<String> = (0..3).map(|_| submit(&executor, "x => x * 2", "1")).collect();
+
+  let worker_ids = |task_ids: Vec<String>| -> HashSet<u32> {
+    task_ids
+      .into_iter()
+      .map(|task_id| executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result").worker_id)
+      .collect()
+  };
+  let shared_workers = worker_ids(shared);
+  let reserved_workers = worker_ids(reserved);
+  assert!(reserved_workers.len() <= 2);
+  assert!(reserved_workers.is_disjoint(&shared_workers), "{:?}", shared_workers);
+  for task_id in whole_pool {
+    let result = executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result");
+    assert_eq!(result.result.as_deref(), Some("2"));
+  }
+
+  let too_many = executor
+    .submit_optimized_batch(tasks, hints("x => x * 2"), None, None, None, Some(4))
+    .unwrap_err();
+  assert_eq!(too_many.reason, "Cannot reserve 4 of 3 workers");
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn platform_tuning_is_validated_everywhere_and_ignored_elsewhere() {
+  let tuned = |windows_priority: &str, musl_stack_size_kb: u32| ExecutorConfig {
+    tuning: Some(ThreadTuning {
+      windows_priority: Some(windows_priority.to_string()),
+      musl_stack_size_kb: Some(musl_stack_size_kb),
+    }),
+    ..workers(1)
+  };
+  let executor = executor(tuned("below_normal", 512));
+  assert_eq!(run(&executor, "x => x * 2", "21").result.as_deref(), Some("42"));
+  executor.shutdown().unwrap();
+
+  let refused = |config: ExecutorConfig| match OptimizedMultiCoreExecutor::new(Some(config)) {
+    Ok(_) => panic!("tuning accepted"),
+    Err(error) => error.reason,
+  };
+  let unknown = refused(tuned("realtime", 512));
+  assert!(unknown.starts_with("Unknown windows_priority 'realtime'"), "{}", unknown);
+  assert_eq!(refused(tuned("normal", 16)), "musl_stack_size_kb must be at least 64, got 16");
+}
+
+#[test]
+fn statsd_reporter_pushes_counter_increases() {
+  let agent = UdpSocket::bind("127.0.0.1:0").unwrap();
+  agent.set_read_timeout(Some(Duration::from_millis(WAIT_MS as u64))).unwrap();
+  let receive = || {
+    let mut packet = [0u8; 2048];
+    let size = agent.recv(&mut packet).expect("statsd packet");
+    String::from_utf8(packet[..size].to_vec()).unwrap()
+  };
+
+  let executor = executor(workers(1));
+  for _ in 0..3 {
+    run(&executor, "x => x * 2", "1");
+  }
+  eventually("every result counted", || executor.get_stats_object().total_executions == 3);
+  let config = StatsdConfig {
+    port: Some(agent.local_addr().unwrap().port() as u32),
+    prefix: Some("app".to_string()),
+    interval_ms: Some(100),
+    tags: Some(vec!["env:test".to_string()]),
+    ..StatsdConfig::default()
+  };
+  executor.start_statsd_reporter(config).unwrap();
+
+  let first = receive();
+  let lines: Vec<&str> = first.lines().collect();
+  assert!(lines.contains(&"app.executions:3|c|#env:test"), "{}", first);
+  assert!(lines.contains(&"app.workers:1|g|#env:test"), "{}", first);
+  // Counters push what changed since the last push
+  assert!(receive().lines().any(|line| line == "app.executions:0|c|#env:test"));
+  executor.stop_statsd_reporter();
+
+  let too_often = StatsdConfig { interval_ms: Some(10), ..StatsdConfig::default() };
+  let refused = executor.start_statsd_reporter(too_often).unwrap_err();
+  assert_eq!(refused.reason, "interval_ms must be at least 100, got 10");
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn fired_tasks_run_but_deliver_nothing() {
+  let executor = executor(workers(1));
+  for i in 0..10 {
+    executor.submit_fire("x => x * 2".into(), i.to_string(), hints("x => x * 2")).unwrap();
+  }
+  // The only worker runs in order, so the fired tasks are done once this one is
+  let last = run(&executor, "x => x * 2", "21");
+  assert_eq!(last.result.as_deref(), Some("42"));
+  assert!(executor.poll_results(None).unwrap().is_empty());
+  eventually("fired tasks counted", || executor.get_stats_object().total_executions == 11);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn config_tunes_timeouts_buffers_and_idling() {
+  let refused = |config: ExecutorConfig| match OptimizedMultiCoreExecutor::new(Some(config)) {
+    Ok(_) => panic!("config accepted"),
+    Err(error) => error.reason,
+  };
+  let unknown = ExecutorConfig { idle_strategy: Some("nap".to_string()), ..workers(1) };
+  assert_eq!(refused(unknown), "Unknown idle_strategy 'nap', expected sleep, yield or spin");
+  let no_buffer = ExecutorConfig { result_buffer_limit: Some(0), ..workers(1) };
+  assert_eq!(refused(no_buffer), "result_buffer_limit must be at least 1");
+
+  let config = ExecutorConfig {
+    idle_strategy: Some("yield".to_string()),
+    default_timeout_ms: Some(50),
+    result_buffer_limit: Some(2),
+    ..workers(1)
+  };
+  let executor = executor(config);
+  let kept: Vec<String> = (0..4).map(|i| submit(&executor, "x => x * 2", &i.to_string())).collect();
+  let evicted = Cell::new(0);
+  eventually("the oldest results dropped", || {
+    let drained = events(&executor);
+    evicted.set(evicted.get() + drained.iter().filter(|e| e["kind"] == "result_evicted").count());
+    evicted.get() == 2
+  });
+  // Only the two most recent uncollected results are kept
+  let polled = executor.poll_results(None).unwrap();
+  assert_eq!(polled.into_iter().map(|result| result.id).collect::<Vec<_>>(), kept[2..]);
+
+  // Tasks without their own timeout get the default one
+  executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 300}"#.into())).unwrap();
+  let overran = run(&executor, "x => x * 2", "1");
+  assert!(overran.timed_out);
+  assert_eq!(overran.error.as_deref(), Some("Task exceeded its 50ms timeout (still running)"));
+  executor.shutdown().unwrap();
+}
+
+// Hold the only worker of `executor` until the test lets it go, so tasks queue
+fn hold_the_worker(executor: &OptimizedMultiCoreExecutor, delay_ms: u32) -> String {
+  let delay = format!(r#"{{"delay_rate": 1.0, "delay_ms": {}}}"#, delay_ms);
+  executor.set_fault_injection(Some(delay)).unwrap();
+  let task_id = submit(executor, "x => x * 2", "0");
+  eventually("the task to start", || {
+    executor.get_task_status(task_id.clone()).is_ok_and(|status| status.status == "running")
+  });
+  executor.set_fault_injection(None).unwrap();
+  task_id
+}
+
+#[test]
+fn higher_priorities_overtake_queued_work() {
+  let executor = executor(workers(1));
+  let held = hold_the_worker(&executor, 100);
+  let submit_as = |priority: &str, data: &str| {
+    let mut hints = hints("x => x * 2");
+    hints.priority = Some(priority.to_string());
+    executor.submit_task("x => x * 2".into(), data.into(), hints, None, None)
+  };
+  for i in 1..=3 {
+    submit_as("low", &i.to_string()).unwrap();
+  }
+  let normal = submit(&executor, "x => x * 2", "4");
+  let high = submit_as("high", "5").unwrap();
+  let critical = submit_as("critical", "6").unwrap();
+
+  let delivered: Vec<String> = (0..6)
+    .map(|_| executor.get_optimized_result(Some(WAIT_MS)).expect("result").id)
+    .collect();
+  assert_eq!(delivered[..4], [held, critical, high, normal]);
+
+  let unknown = submit_as("urgent", "7").unwrap_err();
+  assert_eq!(unknown.reason, "Unknown priority 'urgent', expected low, normal, high or critical");
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn phase_waits_for_its_sealed_group_to_finish() {
+  let executor = executor(workers(2));
+  let submit_in = |data: &str, group: Option<&str>, after: Option<&str>| {
+    let mut hints = hints("x => x * 2");
+    hints.group = group.map(str::to_string);
+    hints.after = after.map(str::to_string);
+    executor.submit_task("x => x * 2".into(), data.into(), hints, None, None)
+  };
+  executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 30}"#.into())).unwrap();
+  let phase1: Vec<String> = (0..4)
+    .map(|i| submit_in(&i.to_string(), Some("phase1"), None).unwrap())
+    .collect();
+  let phase2: Vec<String> = (0..2)
+    .map(|i| submit_in(&i.to_string(), None, Some("phase1")).unwrap())
+    .collect();
+
+  // Unsealed, the group may still grow, so phase 2 waits even once phase 1 is done
+  assert!(executor.group_waiter("phase1".into(), Some(WAIT_MS)).wait().unwrap());
+  let status = executor.get_group_status("phase1".into()).unwrap();
+  assert_eq!((status.pending, status.waiting, status.complete), (0, 2, false));
+  assert_eq!(executor.get_task_status(phase2[0].clone()).unwrap().status, "waiting");
+  let early = executor.remove_group("phase1".into()).unwrap_err();
+  assert_eq!(early.reason, "Task group 'phase1' has not completed");
+
+  executor.seal_group("phase1".into());
+  let delivered: Vec<String> = (0..6)
+    .map(|_| executor.get_optimized_result(Some(WAIT_MS)).expect("result").id)
+    .collect();
+  assert_eq!(delivered[..4].iter().collect::<HashSet<_>>(), phase1.iter().collect());
+  assert_eq!(delivered[4..].iter().collect::<HashSet<_>>(), phase2.iter().collect());
+
+  let late = submit_in("5", Some("phase1"), None);
+  assert_eq!(late.unwrap_err().reason, "Task group 'phase1' is sealed");
+  assert!(executor.remove_group("phase1".into()).unwrap());
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn concurrency_limit_holds_excess_tasks_of_its_function() {
+  let executor = executor(workers(4));
+  let limited = hints("x => x * 2").function_hash;
+  executor.set_concurrency_limit(limited.clone(), Some(1)).unwrap();
+  assert_eq!(executor.get_concurrency_limit(limited.clone()), Some(1));
+  executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 40}"#.into())).unwrap();
+
+  let started = Instant::now();
+  let task_ids: Vec<String> = (0..3)
+    .map(|i| submit(&executor, "x => x * 2", &i.to_string()))
+    .collect();
+  let statuses = || -> Vec<String> {
+    let status = |id: &String| executor.get_task_status(id.clone()).unwrap().status;
+    task_ids.iter().map(status).collect()
+  };
+  // Held back by the workers that picked them up
+  eventually("two tasks held", || statuses().iter().filter(|s| *s == "waiting").count() == 2);
+
+  // Other functions use the rest of the pool meanwhile
+  let other = run(&executor, "s => s.toUpperCase()", r#""a""#);
+  assert_eq!(other.result.as_deref(), Some(r#""A""#));
+  assert!(statuses().contains(&"waiting".to_string()), "{:?}", statuses());
+  collect_once(&executor, &task_ids);
+  assert!(started.elapsed() >= Duration::from_millis(120), "{:?}", started.elapsed());
+
+  assert_eq!(
+    executor.set_concurrency_limit(limited.clone(), Some(0)).unwrap_err().reason,
+    "Concurrency limit must be at least 1"
+  );
+  executor.set_concurrency_limit(limited.clone(), None).unwrap();
+  assert_eq!(executor.get_concurrency_limit(limited), None);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn chunked_map_publishes_each_chunk_as_it_finishes() {
+  let executor = executor(workers(1));
+  let run_chunked = |data: &str| {
+    let mut hints = hints("x => x * 2");
+    hints.chunk_size = Some(3);
+    let task_id = executor
+      .submit_task("x => x * 2".into(), data.into(), hints, None, None)
+      .expect("submit");
+    executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result")
+  };
+
+  let mapped = run_chunked("[0,1,2,3,4,5,6,7,8,9]");
+  assert_eq!(mapped.result.as_deref(), Some("[0,2,4,6,8,10,12,14,16,18]"));
+  let mut chunks: Vec<Value> = events(&executor)
+    .into_iter()
+    .filter(|event| event["kind"] == "partial_result")
+    .map(|event| serde_json::from_str(event["message"].as_str().unwrap()).unwrap())
+    .collect();
+  // In completion order, which needn't be input order
+  chunks.sort_by_key(|chunk| chunk["chunk_index"].as_u64());
+  assert_eq!(chunks.len(), 4);
+  assert!(chunks.iter().all(|chunk| chunk["task_id"] == mapped.id && chunk["chunk_count"] == 4));
+  let offsets: Vec<u64> = chunks.iter().map(|chunk| chunk["offset"].as_u64().unwrap()).collect();
+  assert_eq!(offsets, [0, 3, 6, 9]);
+  let values: Vec<Value> = chunks
+    .iter()
+    .flat_map(|chunk| chunk["values"].as_array().unwrap().clone())
+    .collect();
+  assert_eq!(Value::Array(values).to_string(), mapped.result.unwrap());
+
+  let failed = run_chunked(r#"[1,2,3,4,"five",6]"#);
+  assert!(failed.error.as_deref().is_some_and(|e| e.starts_with("Element 4: ")), "{:?}", failed);
+  let scalar = run_chunked("7");
+  assert_eq!(scalar.error.as_deref(), Some("chunk_size needs an array input"));
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn rate_limit_starts_tasks_as_tokens_refill() {
+  let (executor, clock) = mock_clocked(workers(2));
+  let limited = hints("x => x + 5").function_hash;
+  let limit = |tasks_per_second, burst| Some(RateLimitConfig { tasks_per_second, burst });
+  executor.set_function_rate_limit(limited.clone(), limit(10.0, Some(2))).unwrap();
+  assert_eq!(executor.get_function_rate_limit(limited.clone()).unwrap().burst, Some(2));
+
+  let task_ids: Vec<String> = (0..4)
+    .map(|i| submit(&executor, "x => x + 5", &i.to_string()))
+    .collect();
+  let count = |status: &str| {
+    let status_of = |id: &String| executor.get_task_status(id.clone()).unwrap().status;
+    task_ids.iter().filter(|id| status_of(id) == status).count()
+  };
+  // The burst starts at once; the rest wait for the clock
+  eventually("the burst", || count("completed") == 2 && count("waiting") == 2);
+  std::thread::sleep(Duration::from_millis(150));
+  assert_eq!(count("completed"), 2);
+  assert_eq!(run(&executor, "x => x * 2", "2").result.as_deref(), Some("4"));
+
+  clock.advance(Duration::from_millis(100));
+  eventually("one refilled token", || count("completed") == 3);
+  clock.advance(Duration::from_millis(100));
+  eventually("another", || count("completed") == 4);
+  collect_once(&executor, &task_ids);
+
+  // A tenant's limit covers every function it submits
+  executor.set_tenant_rate_limit("acme".into(), limit(1.0, None)).unwrap();
+  let tenant_ids: Vec<String> = ["x => x + 10", "x => x * x"]
+    .into_iter()
+    .map(|function_code| {
+      let mut hints = hints(function_code);
+      hints.tenant = Some("acme".into());
+      executor.submit_task(function_code.into(), "1".into(), hints, None, None).unwrap()
+    })
+    .collect();
+  let status_of = |id: &String| executor.get_task_status(id.clone()).unwrap().status;
+  eventually("one tenant task held", || tenant_ids.iter().any(|id| status_of(id) == "waiting"));
+  // Lifting the limit releases it without the clock moving
+  executor.set_tenant_rate_limit("acme".into(), None).unwrap();
+  collect_once(&executor, &tenant_ids);
+
+  let refused = |limit| executor.set_function_rate_limit(limited.clone(), limit).unwrap_err();
+  assert_eq!(refused(limit(0.0, None)).reason, "tasks_per_second must be a positive number, got 0");
+  assert_eq!(refused(limit(1.0, Some(0))).reason, "burst must be at least 1");
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn delayed_task_joins_the_queue_once_its_delay_passes() {
+  let (executor, clock) = mock_clocked(workers(1));
+  let delay = |function_code: &str, hints, delay_ms| {
+    executor.submit_delayed(function_code.into(), "21".into(), hints, delay_ms)
+  };
+  // Longer than a turn of the wheel
+  let delayed = delay("x => x * 2", hints("x => x * 2"), 2000).unwrap();
+  let status = || executor.get_task_status(delayed.clone()).unwrap().status;
+  assert_eq!(status(), "waiting");
+
+  // Tasks submitted later overtake it
+  assert_eq!(run(&executor, "x => x + 5", "1").result.as_deref(), Some("6"));
+  clock.advance(Duration::from_millis(1999));
+  std::thread::sleep(Duration::from_millis(50));
+  assert_eq!(status(), "waiting");
+  clock.advance(Duration::from_millis(1));
+  let result = executor.get_result_by_id(delayed, Some(WAIT_MS)).unwrap();
+  assert_eq!(result.result.as_deref(), Some("42"));
+
+  let mut grouped = hints("x => x * 2");
+  grouped.after = Some("batch".into());
+  assert_eq!(
+    delay("x => x * 2", grouped, 10).unwrap_err().reason,
+    "Delayed tasks can't wait on a task group"
+  );
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn columnar_batch_gives_each_row_its_own_result() {
+  let executor = executor(workers(4));
+  let function_code = "({a, b}) => a * b";
+  let mut hints = hints(function_code);
+  hints.operation_type = "mathematical".into();
+  let tasks: Vec<(String, String)> = (0..40)
+    .map(|i| (function_code.to_string(), format!(r#"{{"a": {}, "b": 3}}"#, i)))
+    .collect();
+  let task_ids = executor
+    .submit_optimized_batch(tasks, hints, None, None, None, None)
+    .expect("batch");
+  for (i, task_id) in task_ids.into_iter().enumerate() {
+    let result = executor.get_result_by_id(task_id, Some(WAIT_MS)).expect("result");
+    let output: Value = serde_json::from_str(&result.result.expect("output")).unwrap();
+    assert_eq!(output["result"], i * 3, "row {}", i);
+  }
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn small_low_complexity_tasks_run_on_the_submitting_thread() {
+  let config = ExecutorConfig { inline_threshold_bytes: Some(8), ..workers(1) };
+  let executor = executor(config);
+  let submit_with = |data: &str, complexity: &str| {
+    let mut hints = hints("x => x * 2");
+    hints.complexity = complexity.into();
+    executor.submit_task("x => x * 2".into(), data.into(), hints, None, None).unwrap()
+  };
+  // Too big to run inline, so it holds the only worker
+  executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 200}"#.into())).unwrap();
+  let held = submit_with("1000000000", "low");
+  let status = |id: &String| executor.get_task_status(id.clone()).unwrap().status;
+  eventually("the worker to be busy", || status(&held) == "running");
+  executor.set_fault_injection(None).unwrap();
+
+  let inline = submit_with("21", "low");
+  assert_eq!(status(&inline), "completed");
+  let result = executor.get_result_by_id(inline, Some(WAIT_MS)).unwrap();
+  assert_eq!((result.result.as_deref(), result.execution_type.as_str()), (Some("42"), "inline"));
+  assert_eq!(status(&held), "running");
+  // Costlier tasks queue behind it
+  let queued = submit_with("21", "high");
+  assert_eq!(status(&queued), "queued");
+  collect_once(&executor, &[held, queued]);
+  executor.shutdown().unwrap();
+
+  let off = ExecutorConfig { inline_threshold_bytes: Some(0), ..workers(1) };
+  let refused = OptimizedMultiCoreExecutor::new(Some(off)).err().expect("refused");
+  assert_eq!(refused.reason, "inline_threshold_bytes must be at least 1");
+}
+
+#[test]
+fn graph_nodes_run_on_their_parents_outputs() {
+  let executor = executor(workers(2));
+  // 10 * 2 = 20, then 25 and 400 side by side, summed
+  let nodes = vec![
+    node("double", "x => x * 2", &[], ""),
+    node("shift", "x => x + 5", &["double"], ""),
+    node("square", "x => x * x", &["double"], ""),
+    node("total", "xs => sum(xs)", &["shift", "square"], "")
+  ];
+  let hints = nodes[0].hints.clone().unwrap();
+  let task_ids = executor.submit_graph(nodes, hints.clone(), None).expect("submit");
+  let total = executor.get_result_by_id(task_ids[3].clone(), Some(WAIT_MS)).unwrap();
+  assert_eq!(total.result.as_deref(), Some("425"));
+
+  // Everything downstream of a failure is skipped without running
+  let nodes = vec![
+    node("fetch", "x => fetch(x)", &[], ""),
+    node("parse", "x => x * 2", &["fetch"], ""),
+    node("store", "x => x + 5", &["parse"], "")
+  ];
+  let task_ids = executor.submit_graph(nodes, hints.clone(), None).expect("submit");
+  let stored = executor.get_result_by_id(task_ids[2].clone(), Some(WAIT_MS)).unwrap();
+  assert_eq!(stored.error.as_deref(), Some("Skipped because dependency 'fetch' failed"));
+
+  let refused = |nodes| executor.submit_graph(nodes, hints.clone(), None).unwrap_err().reason;
+  let cycle = vec![
+    node("root", "x => x * 2", &[], ""),
+    node("left", "x => x * 2", &["root", "right"], ""),
+    node("right", "x => x * 2", &["left"], "")
+  ];
+  assert!(refused(cycle).starts_with("Graph has a dependency cycle through node"));
+  let unknown = vec![node("child", "x => x * 2", &["ghost"], "")];
+  assert_eq!(refused(unknown), "Node 'child' depends on unknown node 'ghost'");
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn group_is_joined_or_cancelled_as_a_unit() {
+  let executor = executor(workers(1));
+  executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 100}"#.into())).unwrap();
+  let mut grouped = hints("x => x * 2");
+  grouped.group = Some("job".into());
+  let members: Vec<String> = (0..3)
+    .map(|i| {
+      let data = i.to_string();
+      executor.submit_task("x => x * 2".into(), data, grouped.clone(), None, None).unwrap()
+    })
+    .collect();
+  let outsider = submit(&executor, "x => x * 2", "5");
+  let status = |id: &String| executor.get_task_status(id.clone()).unwrap().status;
+  eventually("the first member to start", || status(&members[0]) == "running");
+
+  let wait = |timeout_ms| executor.group_waiter("job".into(), Some(timeout_ms)).wait();
+  assert_eq!(wait(10), Ok(false));
+  // The running member counts as well as the queued ones
+  assert_eq!(executor.cancel_group("job".into()), 3);
+  assert_eq!(wait(WAIT_MS), Ok(true));
+  assert!(members.iter().all(|id| status(id) == "cancelled"));
+  assert_eq!(executor.cancel_group("job".into()), 0);
+
+  let result = executor.get_result_by_id(outsider, Some(WAIT_MS)).unwrap();
+  assert_eq!(result.result.as_deref(), Some("10"));
+  let unknown = executor.group_waiter("nobody".into(), Some(10)).wait();
+  assert_eq!(unknown, Err("Unknown task group 'nobody'".to_string()));
+  executor.shutdown().unwrap();
+}
+
+// A one-worker executor whose queue of 2 is full behind a held worker, with
+// the IDs of the held task and the queued ones
+fn full_queue(overflow_policy: &str) -> (OptimizedMultiCoreExecutor, String, Vec<String>) {
+  let executor = executor(ExecutorConfig {
+    queue_capacity: Some(2),
+    overflow_policy: Some(overflow_policy.to_string()),
+    ..workers(1)
+  });
+  let held = hold_the_worker(&executor, 150);
+  let queued: Vec<String> = ["1", "2"]
+    .into_iter()
+    .map(|data| submit(&executor, "x => x * 2", data))
+    .collect();
+  assert_eq!(executor.get_queue_depth(), 2);
+  (executor, held, queued)
+}
+
+#[test]
+fn full_queue_follows_its_overflow_policy() {
+  let overflow = |executor: &OptimizedMultiCoreExecutor| {
+    executor.submit_task("x => x * 2".into(), "3".into(), hints("x => x * 2"), None, None)
+  };
+
+  let (rejecting, _, _) = full_queue("reject");
+  let refused = overflow(&rejecting).unwrap_err();
+  assert_eq!(refused.status, Status::QueueFull);
+  assert_eq!(refused.reason, "Task queue is full (capacity 2)");
+  rejecting.shutdown().unwrap();
+
+  // The oldest queued task fails unrun in the newcomer's place
+  let (dropping, held, queued) = full_queue("drop_oldest");
+  let newest = overflow(&dropping).unwrap();
+  let dropped = dropping.get_result_by_id(queued[0].clone(), Some(WAIT_MS)).unwrap();
+  let error = "Dropped from the full task queue to make room for newer tasks";
+  assert_eq!(dropped.error.as_deref(), Some(error));
+  assert!(events(&dropping).iter().any(|event| event["kind"] == "task_dropped"));
+  collect_once(&dropping, &[held, queued[1].clone(), newest]);
+  dropping.shutdown().unwrap();
+
+  // The submission waits until the worker takes a queued task
+  let (blocking, held, queued) = full_queue("block");
+  let started = Instant::now();
+  let accepted = overflow(&blocking).unwrap();
+  assert!(started.elapsed() >= Duration::from_millis(50), "{:?}", started.elapsed());
+  collect_once(&blocking, &[held, queued[0].clone(), queued[1].clone(), accepted]);
+  blocking.shutdown().unwrap();
+
+  let unbounded = ExecutorConfig { overflow_policy: Some("block".into()), ..workers(1) };
+  let refused = OptimizedMultiCoreExecutor::new(Some(unbounded)).err().expect("refused");
+  assert_eq!(refused.reason, "overflow_policy needs a queue_capacity");
+}
+
+#[test]
+fn seeded_executors_replay_ids_and_faults() {
+  // Which of 20 tasks an executor seeded with `seed` fails, by ID
+  let replay = |seed: &str| -> (String, Vec<(String, bool)>) {
+    let executor = executor(ExecutorConfig { seed: Some(seed.to_string()), ..workers(1) });
+    executor.set_fault_injection(Some(r#"{"timeout_rate": 0.5}"#.into())).unwrap();
+    let outcomes = (0..20)
+      .map(|i| {
+        let result = run(&executor, "x => x * 2", &i.to_string());
+        (result.id, result.error.is_some())
+      })
+      .collect();
+    let seed = executor.get_seed();
+    executor.shutdown().unwrap();
+    (seed, outcomes)
+  };
+
+  let (seed, outcomes) = replay("0xdecafbad");
+  assert_eq!(seed, "00000000decafbad");
+  assert_eq!(outcomes[0].0, "opt_task_00000000decafbad_0");
+  let failures = outcomes.iter().filter(|(_, failed)| *failed).count();
+  assert!(failures > 0 && failures < 20, "{} of 20 failed", failures);
+  // The reported seed replays the same run
+  assert_eq!(replay(&seed).1, outcomes);
+  // Another seed fails other tasks
+  let failed = |outcomes: &[(String, bool)]| -> Vec<bool> {
+    outcomes.iter().map(|(_, failed)| *failed).collect()
+  };
+  assert_ne!(failed(&replay("0xfeed").1), failed(&outcomes));
+
+  let unseeded = executor(workers(1));
+  assert!(!run(&unseeded, "x => x * 2", "1").id.starts_with("opt_task_0000"));
+  unseeded.shutdown().unwrap();
+  let bad = ExecutorConfig { seed: Some("0xgg".into()), ..workers(1) };
+  let refused = OptimizedMultiCoreExecutor::new(Some(bad)).err().expect("refused");
+  assert_eq!(refused.reason, "seed must be 1 to 16 hex digits, got '0xgg'");
+}
+
+#[test]
+fn queue_stats_break_the_backlog_down() {
+  let (executor, clock) = mock_clocked(workers(1));
+  let held = hold_the_worker(&executor, 100);
+  let submit_as = |priority: &str, data: &str| {
+    let mut hints = hints("x => x * 2");
+    hints.priority = Some(priority.to_string());
+    executor.submit_task("x => x * 2".into(), data.into(), hints, None, None).unwrap()
+  };
+  let low = submit_as("low", "1");
+  clock.advance(Duration::from_millis(250));
+  let normal = submit_as("normal", "2");
+  let high = submit_as("high", "3");
+  let delayed = executor
+    .submit_delayed("x => x * 2".into(), "4".into(), hints("x => x * 2"), 1000)
+    .unwrap();
+
+  let stats = executor.get_queue_stats(Some(true));
+  let depths = (stats.pending, stats.critical, stats.high, stats.normal, stats.low);
+  assert_eq!(depths, (3, 0, 1, 1, 1));
+  assert_eq!(stats.waiting, 1);
+  // The low priority task was queued first
+  assert_eq!(stats.oldest_task_age_ms, Some(250.0));
+  assert_eq!(stats.task_ids, Some(vec![high.clone(), normal.clone(), low.clone()]));
+  assert_eq!(executor.get_queue_stats(None).task_ids, None);
+
+  clock.advance(Duration::from_millis(1000));
+  collect_once(&executor, &[held, low, normal, high, delayed]);
+  let drained = executor.get_queue_stats(Some(true));
+  assert_eq!((drained.pending, drained.waiting, drained.oldest_task_age_ms), (0, 0, None));
+  assert_eq!(drained.task_ids, Some(Vec::new()));
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn borrowed_data_is_read_in_place_until_released() {
+  let executor = executor(workers(1));
+  let lend = |bytes: &Arc<Vec<u8>>| {
+    let shared: SharedBytes = bytes.clone();
+    executor.submit_borrowed_bytes("x => x * 2".into(), shared, hints("x => x * 2"), None, None)
+  };
+  let bytes = Arc::new(b"21".to_vec());
+  let task_id = lend(&bytes).unwrap();
+  let result = executor.get_result_by_id(task_id.clone(), Some(WAIT_MS)).unwrap();
+  assert_eq!(result.result.as_deref(), Some("42"));
+  // The finished task let go of the bytes, and there's nothing left to release
+  eventually("the task to drop its reference", || Arc::strong_count(&bytes) == 1);
+  assert!(!executor.release_borrowed(task_id));
+
+  let held = hold_the_worker(&executor, 100);
+  let queued = lend(&bytes).unwrap();
+  assert!(executor.release_borrowed(queued.clone()));
+  assert!(!executor.release_borrowed(queued.clone()));
+  assert_eq!(executor.get_task_status(queued).unwrap().status, "cancelled");
+  assert!(!executor.release_borrowed(held.clone()));
+  collect_once(&executor, &[held]);
+
+  let invalid = lend(&Arc::new(vec![0xff, 0xfe])).unwrap_err();
+  assert!(invalid.reason.starts_with("Borrowed data is not valid UTF-8"), "{}", invalid.reason);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn task_queued_past_its_max_age_fails_unrun() {
+  let (executor, clock) = mock_clocked(workers(1));
+  let held = hold_the_worker(&executor, 100);
+  let submit_aged = |max_queue_age_ms| {
+    let mut hints = hints("x => x * 2");
+    hints.max_queue_age_ms = Some(max_queue_age_ms);
+    executor.submit_task("x => x * 2".into(), "21".into(), hints, None, None).unwrap()
+  };
+  let stale = submit_aged(50);
+  let fresh = submit_aged(500);
+  clock.advance(Duration::from_millis(100));
+
+  let expired = executor.get_result_by_id(stale.clone(), Some(WAIT_MS)).unwrap();
+  assert_eq!(expired.error.as_deref(), Some("Expired after waiting over 50ms to start"));
+  assert_eq!(expired.optimization_used, "queue_expired");
+  let message = format!("Task {} waited over 50ms to start", stale);
+  assert!(events(&executor).iter().any(|event| {
+    event["kind"] == "task_expired" && event["message"] == message.as_str()
+  }));
+  collect_once(&executor, &[held, fresh]);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn identical_dedupe_tasks_share_one_execution() {
+  let executor = executor(workers(1));
+  let held = hold_the_worker(&executor, 100);
+  let submit_deduped = |data: &str, dedupe| {
+    let mut hints = hints("x => x * 2");
+    hints.dedupe = Some(dedupe);
+    executor.submit_task("x => x * 2".into(), data.into(), hints, None, None).unwrap()
+  };
+  let first = submit_deduped("21", true);
+  let attached = submit_deduped("21", true);
+  let other_data = submit_deduped("4", true);
+  let opted_out = submit_deduped("21", false);
+  // Dropped before it ran, the first hands its place to the task attached to it
+  let cancelled = submit_deduped("8", true);
+  let successor = submit_deduped("8", true);
+  executor.cancel_task(cancelled);
+
+  let result = |task_id: &String| executor.get_result_by_id(task_id.clone(), Some(WAIT_MS));
+  let shared = [&first, &attached].map(|task_id| result(task_id).unwrap());
+  assert_eq!(shared.each_ref().map(|r| r.result.as_deref()), [Some("42"), Some("42")]);
+  assert_eq!(shared[1].id, attached);
+  assert_eq!(result(&successor).unwrap().result.as_deref(), Some("16"));
+  collect_once(&executor, &[held, other_data, opted_out]);
+  // The held task, one of each pair, and the two that didn't match
+  eventually("every execution counted", || executor.get_stats_object().total_executions == 5);
+  std::thread::sleep(Duration::from_millis(20));
+  assert_eq!(executor.get_stats_object().total_executions, 5);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn memory_watermark_pushes_back_until_results_are_collected() {
+  let config = ExecutorConfig { memory_watermark_mb: Some(1), ..workers(1) };
+  let executor = executor(config);
+  let notified = Arc::new(AtomicUsize::new(0));
+  let counter = Arc::clone(&notified);
+  let listener: PressureListener = Box::new(move |usage| {
+    assert!(usage.total_bytes > usage.watermark_bytes.unwrap());
+    counter.fetch_add(1, Ordering::SeqCst);
+  });
+  executor.set_memory_pressure_listener(Some(listener));
+  let upper = "s => s.toUpperCase()";
+  let large = format!(r#""{}""#, "a".repeat(300 * 1024));
+  // Below the watermark until their results pile up uncollected
+  let task_ids: Vec<String> = (0..4).map(|_| submit(&executor, upper, &large)).collect();
+  eventually("the results to pile up", || {
+    executor.get_memory_usage().result_bytes > 1024.0 * 1024.0
+  });
+
+  let submit_small = || {
+    executor.submit_task(upper.into(), r#""b""#.into(), hints(upper), None, None)
+  };
+  for _ in 0..2 {
+    let refused = submit_small().unwrap_err();
+    assert_eq!(refused.status, Status::QueueFull);
+    assert!(refused.reason.contains("over the 1048576 byte watermark"), "{}", refused.reason);
+  }
+  // Each excursion is reported once
+  assert_eq!(notified.load(Ordering::SeqCst), 1);
+  let reported = events(&executor)
+    .into_iter()
+    .filter(|event| event["kind"] == "memory_pressure")
+    .count();
+  assert_eq!(reported, 1);
+
+  collect_once(&executor, &task_ids);
+  let accepted = submit_small().unwrap();
+  collect_once(&executor, &[accepted]);
+  assert_eq!(executor.get_memory_usage().watermark_bytes, Some(1024.0 * 1024.0));
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn critical_task_runs_between_chunks_of_a_running_task() {
+  let executor = executor(workers(1));
+  let submit_as = |priority: &str, chunk_size, data: &str| {
+    let mut hints = hints("x => x * 2");
+    hints.priority = Some(priority.to_string());
+    hints.chunk_size = chunk_size;
+    executor.submit_task("x => x * 2".into(), data.into(), hints, None, None).unwrap()
+  };
+  // Each task starts by sleeping, so the critical one arrives before any chunk
+  let run_behind = |chunk_size, data: &str| {
+    executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 100}"#.into())).unwrap();
+    let low = submit_as("low", chunk_size, data);
+    let status = || executor.get_task_status(low.clone()).unwrap().status;
+    eventually("the low priority task to start", || status() == "running");
+    executor.set_fault_injection(None).unwrap();
+    let critical = submit_as("critical", None, "21");
+    let first = executor.get_optimized_result(Some(WAIT_MS)).unwrap();
+    let second = executor.get_optimized_result(Some(WAIT_MS)).unwrap();
+    assert_eq!([first.error, second.error], [None, None]);
+    (low, critical, [first.id, second.id])
+  };
+
+  let (low, critical, delivered) = run_behind(Some(1), "[1,2,3,4]");
+  assert_eq!(delivered, [critical.clone(), low.clone()]);
+  let message = format!("Task {} paused between chunks for critical task {}", low, critical);
+  let preempted: Vec<Value> = events(&executor)
+    .into_iter()
+    .filter(|event| event["kind"] == "task_preempted")
+    .collect();
+  assert_eq!(preempted.len(), 1);
+  assert_eq!(preempted[0]["message"], message.as_str());
+
+  // Without chunks there's nowhere to pause
+  let (low, critical, delivered) = run_behind(None, "4");
+  assert_eq!(delivered, [low, critical]);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn child_executor_keeps_to_its_budget() {
+  let (executor, clock) = mock_clocked(workers(2));
+  let options = ChildOptions {
+    max_concurrency: Some(1),
+    max_bytes: Some(100),
+    time_budget_ms: Some(1000),
+  };
+  let child = executor.open_child(Some(options)).unwrap();
+  let submit_to = |child: &str, data: &str| {
+    let mut hints = hints("x => x * 2");
+    hints.child = Some(child.to_string());
+    executor.submit_task("x => x * 2".into(), data.into(), hints, None, None)
+  };
+
+  // One of its tasks at a time, though the pool has two workers
+  executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 50}"#.into())).unwrap();
+  let task_ids: Vec<String> = ["1", "2"].map(|data| submit_to(&child, data).unwrap()).into();
+  let statuses = || task_ids.iter().map(|id| executor.get_task_status(id.clone()).unwrap().status);
+  eventually("one task held", || statuses().filter(|status| status == "waiting").count() == 1);
+  executor.set_fault_injection(None).unwrap();
+  let status = executor.get_child_status(child.clone()).unwrap();
+  assert_eq!((status.running, status.unfinished, status.bytes), (1, 2, 22.0));
+  assert_eq!(status.remaining_ms, Some(1000.0));
+  let over = submit_to(&child, &"1".repeat(100)).unwrap_err();
+  assert_eq!(over.status, Status::QueueFull);
+  assert_eq!(over.reason, format!("Child executor '{}' is over its 100 byte budget; \
+    wait for its tasks to finish", child));
+  collect_once(&executor, &task_ids);
+
+  clock.advance(Duration::from_millis(1000));
+  let late = submit_to(&child, "3").unwrap_err();
+  assert_eq!(late.reason, format!("Child executor '{}' used up its 1000ms time budget", child));
+
+  // Closing ends the request early
+  let request = executor.open_child(None).unwrap();
+  assert_eq!(executor.get_child_status(request.clone()).unwrap().max_concurrency, 2);
+  assert!(executor.close_child(request.clone()));
+  assert!(!executor.close_child(request.clone()));
+  let closed = format!("Unknown or closed child executor '{}'", request);
+  assert_eq!(submit_to(&request, "4").unwrap_err().reason, closed);
+  assert_eq!(executor.get_child_status(request).unwrap_err().reason, closed);
+  let refused = executor.open_child(Some(ChildOptions {
+    max_concurrency: Some(0),
+    ..ChildOptions::default()
+  }));
+  assert_eq!(refused.unwrap_err().reason, "max_concurrency must be at least 1");
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn sticky_routing_keeps_a_function_on_one_worker_while_it_keeps_up() {
+  let executor = executor(ExecutorConfig { sticky_routing: Some(true), ..workers(4) });
+  // One at a time, with its worker idle again before the next, once they've all started
+  run(&executor, "x => x * 2", "0");
+  let ran_on: Vec<u32> = (0..20)
+    .map(|i| {
+      std::thread::sleep(Duration::from_millis(5));
+      run(&executor, "x => x * 2", &i.to_string()).worker_id
+    })
+    .collect();
+  assert!(ran_on.iter().all(|worker| *worker == ran_on[0]), "{:?}", ran_on);
+
+  // A backlog past what one worker should hold spills over to the others
+  executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 10}"#.into())).unwrap();
+  let task_ids: Vec<String> =
+    (0..40).map(|i| submit(&executor, "x => x * 2", &i.to_string())).collect();
+  let workers: HashSet<u32> = task_ids
+    .into_iter()
+    .map(|task_id| executor.get_result_by_id(task_id, Some(WAIT_MS)).unwrap().worker_id)
+    .collect();
+  assert!(workers.len() > 1, "{:?}", workers);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn task_spec_runs_as_a_graph() {
+  let executor = executor(workers(2));
+  let spec = r#"
+version: 1
+data:
+  start: "10"
+tasks:
+  - name: double
+    function: "x => x * 2"
+    data_ref: start
+  - name: shift
+    function: "x => x + 5"
+    depends_on: [double]
+"#;
+  let task_ids = executor.submit_task_spec(spec.into(), Some("yaml".into())).expect("submit");
+  assert_eq!(task_ids.len(), 2);
+  let shifted = executor.get_result_by_id(task_ids[1].clone(), Some(WAIT_MS)).unwrap();
+  assert_eq!(shifted.result.as_deref(), Some("25"));
+  collect_once(&executor, &task_ids[..1]);
+
+  let refused = executor.submit_task_spec("version: 1\ntasks: []".into(), Some("yaml".into()));
+  assert_eq!(refused.unwrap_err().reason, "Task spec has no tasks");
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn hinted_batch_runs_in_sub_batches() {
+  let executor = executor(workers(2));
+  let mut hinted = hints("x => x * 2");
+  hinted.batch_size_hint = Some(4);
+  let tasks: Vec<(String, String)> = (0..20)
+    .map(|i| ("x => x * 2".to_string(), i.to_string()))
+    .collect();
+  let task_ids = executor
+    .submit_optimized_batch(tasks, hinted.clone(), None, None, None, None)
+    .expect("batch");
+  for (i, task_id) in task_ids.iter().enumerate() {
+    let result = executor.get_result_by_id(task_id.clone(), Some(WAIT_MS)).expect("result");
+    assert_eq!(result.result, Some((i * 2).to_string()));
+  }
+  assert!(executor.poll_results(None).expect("poll").is_empty());
+
+  // The size learned for the function is kept with the executor's state
+  let state = executor.export_state(None).unwrap();
+  let state: serde_json::Value = serde_json::from_str(&state).unwrap();
+  assert!(state["sub_batch_sizes"][&hinted.function_hash].is_u64(), "{}", state);
+  executor.shutdown().unwrap();
+}
+
+// Everything in one lane, string work jumping ahead of the rest
+struct StringsFirst;
+
+impl QueueDiscipline for StringsFirst {
+  fn lane(&self, hints: &OptimizationHints, _priority: Priority) -> Priority {
+    if hints.operation_type == "string_operations" { Priority::Critical } else { Priority::Normal }
+  }
+}
+
+#[test]
+fn queue_discipline_decides_the_order_queued_tasks_run_in() {
+  // Delivery order of a low, a normal, a high priority and a string task queued
+  // behind a held worker
+  let order = |executor: OptimizedMultiCoreExecutor| {
+    let held = hold_the_worker(&executor, 100);
+    let mut task_ids = Vec::new();
+    for (priority, data) in [("low", "1"), ("normal", "2"), ("high", "3")] {
+      let mut hints = hints("x => x * 2");
+      hints.priority = Some(priority.to_string());
+      let submitted = executor.submit_task("x => x * 2".into(), data.into(), hints, None, None);
+      task_ids.push(submitted.unwrap());
+    }
+    let mut shout = hints("s => s.toUpperCase()");
+    shout.operation_type = "string_operations".to_string();
+    let code = "s => s.toUpperCase()".to_string();
+    task_ids.push(executor.submit_task(code, r#""a""#.into(), shout, None, None).unwrap());
+
+    let delivered: Vec<String> = (0..5)
+      .map(|_| executor.get_optimized_result(Some(WAIT_MS)).expect("result").id)
+      .collect();
+    executor.shutdown().unwrap();
+    assert_eq!(delivered[0], held);
+    delivered[1..]
+      .iter()
+      .map(|id| task_ids.iter().position(|task_id| task_id == id).unwrap())
+      .collect::<Vec<usize>>()
+  };
+  let disciplined = |discipline: &str| {
+    executor(ExecutorConfig { queue_discipline: Some(discipline.to_string()), ..workers(1) })
+  };
+
+  assert_eq!(order(disciplined("priority")), [2, 1, 3, 0]);
+  assert_eq!(order(disciplined("fifo")), [0, 1, 2, 3]);
+  assert_eq!(order(disciplined("lifo")), [3, 2, 1, 0]);
+  let custom = Arc::new(StringsFirst);
+  let clock: SharedClock = Arc::new(SystemClock);
+  let custom = OptimizedMultiCoreExecutor::with_discipline(workers(1), clock, custom);
+  assert_eq!(order(custom.expect("executor")), [3, 0, 1, 2]);
+
+  let unknown = OptimizedMultiCoreExecutor::new(Some(ExecutorConfig {
+    queue_discipline: Some("random".to_string()),
+    ..workers(1)
+  }));
+  assert_eq!(
+    unknown.err().unwrap().reason,
+    "Unknown queue_discipline 'random', expected priority, fifo or lifo"
+  );
+}
+
+#[test]
+fn unserved_lanes_age_past_busier_ones_and_starved_tasks_are_reported() {
+  let (executor, clock) = mock_clocked(ExecutorConfig {
+    aging_ms: Some(100),
+    starvation_warning_ms: Some(400),
+    ..workers(1)
+  });
+  // Each task holds the worker long enough to move the clock while it runs
+  executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 50}"#.into())).unwrap();
+  let submit_as = |priority: &str, data: &str| {
+    let mut hints = hints("x => x * 2");
+    hints.priority = Some(priority.to_string());
+    executor.submit_task("x => x * 2".into(), data.into(), hints, None, None).unwrap()
+  };
+  let status = |task_id: &String| executor.get_task_status(task_id.clone()).unwrap().status;
+  let held = submit(&executor, "x => x * 2", "0");
+  eventually("the task to start", || status(&held) == "running");
+  let low = submit_as("low", "1");
+  let high: Vec<String> = ["2", "3", "4"].iter().map(|data| submit_as("high", data)).collect();
+
+  // The low lane has waited a second by the next pick, ten agings, and the
+  // high lane starts aging afresh each time it's served
+  eventually("the first high task to start", || status(&high[0]) == "running");
+  clock.advance(Duration::from_millis(1000));
+  let delivered: Vec<String> = (0..5)
+    .map(|_| executor.get_optimized_result(Some(WAIT_MS)).expect("result").id)
+    .collect();
+  assert_eq!(delivered, [&held, &high[0], &high[1], &low, &high[2]].map(String::clone));
+
+  // Reported once each, the low task among them
+  let starved: Vec<String> = events(&executor)
+    .into_iter()
+    .filter(|event| event["kind"] == "task_starved")
+    .map(|event| event["message"].as_str().unwrap().to_string())
+    .collect();
+  let expected = format!("Task {} has waited 1000ms in the low lane", low);
+  assert_eq!(starved.iter().filter(|message| **message == expected).count(), 1, "{:?}", starved);
+  assert!(starved.iter().all(|message| !message.contains(&held)), "{:?}", starved);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn clients_without_binary_get_bytes_as_json() {
+  let executor = executor(workers(1));
+  let encode = || run_as(&executor, "binary_operations", "s => Buffer.from(s)", r#""hi""#);
+  assert_eq!(encode().binary.map(|bytes| bytes.0), Some(b"hi".to_vec()));
+
+  let agreement = executor
+    .negotiate_protocol(ProtocolSupport {
+      version: 1,
+      min_version: None,
+      encodings: Some(vec!["json".to_string()]),
+      operations: None,
+    })
+    .expect("agreement");
+  assert_eq!(agreement.encodings, ["json"]);
+  let encoded = encode();
+  assert!(encoded.binary.is_none());
+  assert_eq!(encoded.result.as_deref(), Some("[104,105]"));
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn idempotency_key_answers_resubmissions_until_its_ttl_expires() {
+  let (executor, clock) = mock_clocked(ExecutorConfig {
+    idempotency_ttl_ms: Some(1000),
+    ..workers(1)
+  });
+  let submit_keyed = |function_code: &str, key: &str| {
+    let mut hints = hints(function_code);
+    hints.idempotency_key = Some(key.to_string());
+    executor.submit_task(function_code.into(), "21".into(), hints, None, None).expect("submit")
+  };
+
+  // Pending or completed, the key answers with the first task
+  let first = submit_keyed("x => x * 2", "order-1");
+  assert_eq!(submit_keyed("x => x * 2", "order-1"), first);
+  for _ in 0..2 {
+    let result = executor.get_result_by_id(first.clone(), Some(WAIT_MS)).expect("result");
+    assert_eq!(result.result.as_deref(), Some("42"));
+  }
+  clock.advance(Duration::from_millis(999));
+  assert_eq!(submit_keyed("x => x * 2", "order-1"), first);
+  eventually("one execution counted", || executor.get_stats_object().total_executions == 1);
+
+  // Past the TTL the key runs afresh
+  clock.advance(Duration::from_millis(1));
+  let second = submit_keyed("x => x * 2", "order-1");
+  assert_ne!(second, first);
+
+  // A failed task frees its key for a retry
+  let failed = submit_keyed("x => fetch(x)", "order-2");
+  let result = executor.get_result_by_id(failed.clone(), Some(WAIT_MS)).expect("result");
+  assert!(result.error.is_some());
+  let retried = submit_keyed("x => x * 2", "order-2");
+  assert_ne!(retried, failed);
+  collect_once(&executor, &[second, retried]);
+
+  let mut keyed = hints("x => x * 2");
+  keyed.idempotency_key = Some("order-3".to_string());
+  let batch = vec![("x => x * 2".to_string(), "1".to_string())];
+  let refused = executor.submit_optimized_batch(batch, keyed, None, None, None, None);
+  assert_eq!(
+    refused.unwrap_err().reason,
+    "idempotency_key is only honored by submit_optimized_task and submit_borrowed"
+  );
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn exported_state_and_queue_carry_over_to_the_next_executor() {
+  let old = executor(workers(1));
+  old.register_function("f@1.0.0".into(), "x => x * 2".into(), hints("x => x * 2")).unwrap();
+  let shout = hints("s => s.toUpperCase()").function_hash;
+  old.set_routing_override(shout.clone(), "js".to_string()).unwrap();
+  run(&old, "x => x * 2", "1");
+
+  // Queued behind a held worker, two tasks are handed off with the state
+  let held = hold_the_worker(&old, 100);
+  let plain = submit(&old, "x => x + 5", "1");
+  let mut keyed = hints("x => x * 2");
+  keyed.idempotency_key = Some("order-1".to_string());
+  let keyed_id = old.submit_task("x => x * 2".into(), "21".into(), keyed.clone(), None, None);
+  let keyed_id = keyed_id.unwrap();
+  let state = old.export_state(Some(true)).unwrap();
+  for task_id in [&plain, &keyed_id] {
+    let result = old.get_result_by_id(task_id.clone(), Some(WAIT_MS)).unwrap();
+    assert_eq!(result.error.as_deref(), Some("Handed off to another executor by export_state"));
+  }
+  collect_once(&old, &[held]);
+  old.shutdown().unwrap();
+
+  let new = executor(workers(1));
+  let imported = new.import_state(state).unwrap();
+  assert_eq!((imported.functions, imported.routing_overrides, imported.queued_tasks), (1, 1, 2));
+  assert!(imported.cache_entries >= 1);
+  assert_eq!(new.list_functions(), ["f@1.0.0"]);
+  assert_eq!(new.get_routing_overrides().get(&shout).map(String::as_str), Some("js"));
+
+  // Under the same IDs, and the same idempotency key
+  let result = new.get_result_by_id(plain, Some(WAIT_MS)).unwrap();
+  assert_eq!(result.result.as_deref(), Some("6"));
+  let resubmitted = new.submit_task("x => x * 2".into(), "21".into(), keyed, None, None);
+  assert_eq!(resubmitted.unwrap(), keyed_id);
+  let result = new.get_result_by_id(keyed_id, Some(WAIT_MS)).unwrap();
+  assert_eq!(result.result.as_deref(), Some("42"));
+
+  let stale = new.import_state(r#"{"version": 0}"#.to_string()).unwrap_err();
+  assert_eq!(stale.reason, "Unsupported executor state version 0, expected 1");
+  new.shutdown().unwrap();
+}
+
+#[test]
+fn background_passes_compile_hot_functions_until_turned_off() {
+  // Off by default
+  let quiet = executor(workers(1));
+  run(&quiet, "x => x * 2", "1");
+  assert_eq!(quiet.get_background_stats(), BackgroundStats::default());
+  quiet.shutdown().unwrap();
+
+  let config = ExecutorConfig { background_optimization: Some(true), ..workers(1) };
+  let (executor, clock) = mock_clocked(config);
+  // Queued whole behind a held worker, so no pass runs partway through
+  let held = hold_the_worker(&executor, 50);
+  let tasks: Vec<(String, String)> = (0..100)
+    .map(|i| ("x => x * 2".to_string(), i.to_string()))
+    .collect();
+  let mut task_ids = executor
+    .submit_optimized_batch(tasks, hints("x => x * 2"), None, None, None, None)
+    .expect("batch");
+  task_ids.push(held);
+  collect_once(&executor, &task_ids);
+
+  // Past 100 runs the first pass promotes the function; the next run shows
+  // its code, and the pass after compiles it
+  eventually("the first pass", || executor.get_background_stats().passes == 1);
+  assert_eq!(executor.get_background_stats().compiled_functions, 0);
+  let run_and_pass = |data: &str| {
+    let passes = executor.get_background_stats().passes;
+    clock.advance(Duration::from_secs(1));
+    let result = run(&executor, "x => x * 2", data);
+    eventually("a background pass", || executor.get_background_stats().passes > passes);
+    result
+  };
+  run_and_pass("1");
+  let stats = executor.get_background_stats();
+  assert!(stats.enabled);
+  assert_eq!(stats.compiled_functions, 1);
+  assert!(stats.total_ms >= stats.precompile_ms);
+  assert_eq!(run_and_pass("21").result.as_deref(), Some("42"));
+
+  executor.set_background_optimization(false);
+  let stats = executor.get_background_stats();
+  assert_eq!((stats.enabled, stats.compiled_functions), (false, 0));
+  clock.advance(Duration::from_secs(1));
+  run(&executor, "x => x * 2", "3");
+  std::thread::sleep(Duration::from_millis(50));
+  assert_eq!(executor.get_background_stats().passes, stats.passes);
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn pool_grows_and_shrinks_without_losing_queued_work() {
+  let executor = executor(ExecutorConfig { max_worker_count: Some(4), ..workers(1) });
+  executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 20}"#.into())).unwrap();
+  let task_ids: Vec<String> =
+    (0..12).map(|i| submit(&executor, "x => x * 2", &i.to_string())).collect();
+
+  // New workers take over what's queued
+  executor.set_worker_count(4).unwrap();
+  assert_eq!(executor.worker_count(), 4);
+  let ran_on: HashSet<u32> = task_ids
+    .iter()
+    .map(|task_id| executor.get_result_by_id(task_id.clone(), Some(WAIT_MS)).unwrap().worker_id)
+    .collect();
+  assert!(ran_on.len() > 1, "{:?}", ran_on);
+
+  // Surplus workers retire, and the one left runs what follows
+  executor.set_fault_injection(None).unwrap();
+  executor.set_worker_count(1).unwrap();
+  let retired = AtomicUsize::new(0);
+  eventually("three workers to retire", || {
+    let events = events(&executor);
+    let count = events.iter().filter(|event| event["kind"] == "worker_retired").count();
+    retired.fetch_add(count, Ordering::Relaxed) + count == 3
+  });
+  let task_ids: Vec<String> =
+    (0..5).map(|i| submit(&executor, "x => x * 2", &i.to_string())).collect();
+  let ran_on: HashSet<u32> = task_ids
+    .into_iter()
+    .map(|task_id| executor.get_result_by_id(task_id, Some(WAIT_MS)).unwrap().worker_id)
+    .collect();
+  assert_eq!(ran_on.len(), 1);
+
+  let too_many = executor.set_worker_count(5).unwrap_err().reason;
+  assert_eq!(too_many, "Worker count must be between 1 and 4, got 5");
+  assert!(executor.set_worker_count(0).is_err());
+  executor.shutdown().unwrap();
+  assert_eq!(executor.set_worker_count(2).unwrap_err().reason, "Worker pool is shut down");
+}
+
+#[test]
+fn autoscaler_grows_the_pool_for_a_backlog_and_shrinks_it_after() {
+  let executor = executor(ExecutorConfig { max_worker_count: Some(4), ..workers(1) });
+  let bounds = |min_workers, max_workers| AutoscaleConfig {
+    min_workers: Some(min_workers),
+    max_workers: Some(max_workers),
+    interval_ms: Some(10),
+    max_wait_ms: Some(5),
+    scale_down_delay_ms: Some(100),
+    ..AutoscaleConfig::default()
+  };
+  // Brought within bounds at once
+  executor.set_autoscale(Some(bounds(2, 3))).unwrap();
+  assert_eq!(executor.worker_count(), 2);
+
+  executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 30}"#.into())).unwrap();
+  let task_ids: Vec<String> =
+    (0..20).map(|i| submit(&executor, "x => x * 2", &i.to_string())).collect();
+  eventually("the pool to grow", || executor.worker_count() == 3);
+  executor.set_fault_injection(None).unwrap();
+  collect_once(&executor, &task_ids);
+  // Never past its bounds
+  assert!(executor.worker_count() <= 3);
+  eventually("the pool to shrink", || executor.worker_count() == 2);
+
+  // Stopped, it leaves the pool as it is
+  executor.set_autoscale(None).unwrap();
+  executor.set_worker_count(4).unwrap();
+  std::thread::sleep(Duration::from_millis(150));
+  assert_eq!(executor.worker_count(), 4);
+
+  let refused = |config| executor.set_autoscale(Some(config)).unwrap_err().reason;
+  assert_eq!(
+    refused(bounds(3, 2)),
+    "Autoscaling needs 1 <= min_workers <= max_workers <= 4, got 3 and 2"
+  );
+  assert_eq!(
+    refused(AutoscaleConfig { interval_ms: Some(5), ..bounds(1, 4) }),
+    "interval_ms must be at least 10, got 5"
+  );
+  assert_eq!(
+    refused(AutoscaleConfig { target_utilization: Some(0.0), ..bounds(1, 4) }),
+    "target_utilization must be in (0, 1], got 0"
+  );
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn inline_batch_answers_in_order_from_the_calling_thread_or_the_pool() {
+  let cheap = executor(workers(1));
+  let costly = executor(ExecutorConfig { inline_batch_budget_ms: Some(0), ..workers(1) });
+  let items: Vec<Value> = (1..=3).map(Value::from).collect();
+  for executor in [&cheap, &costly] {
+    // Only tasks on the pool wait out the delay
+    executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 100}"#.into())).unwrap();
+  }
+
+  let started = Instant::now();
+  let doubled = cheap.execute_inline_batch("x => x * 2".into(), items.clone(), None).unwrap();
+  assert_eq!(doubled, [2, 4, 6].map(Value::from));
+  assert!(started.elapsed() < Duration::from_millis(100), "{:?}", started.elapsed());
+
+  let started = Instant::now();
+  let doubled = costly.execute_inline_batch("x => x * 2".into(), items.clone(), None).unwrap();
+  assert_eq!(doubled, [2, 4, 6].map(Value::from));
+  assert!(started.elapsed() >= Duration::from_millis(300), "{:?}", started.elapsed());
+
+  let failed = cheap.execute_inline_batch("x => fetch(x)".into(), items, None).unwrap_err();
+  assert!(failed.reason.starts_with("Item 0: "), "{}", failed.reason);
+  assert!(cheap.execute_inline_batch("x => x * 2".into(), Vec::new(), None).unwrap().is_empty());
+  // Nothing is left behind to collect
+  assert!(cheap.poll_results(None).unwrap().is_empty());
+  cheap.shutdown().unwrap();
+  costly.shutdown().unwrap();
+}
+
+#[test]
+fn fallback_runs_in_place_of_a_failed_native_execution() {
+  let executor = executor(workers(1));
+  let hash = hints("x => x * 2").function_hash;
+  let code = |function_code: &str| FallbackConfig {
+    function_code: Some(function_code.to_string()),
+    ..FallbackConfig::default()
+  };
+  // Doubling fails natively on an array; summing it doesn't
+  executor.set_fallback(hash.clone(), Some(code("xs => sum(xs)"))).unwrap();
+  let summed = run(&executor, "x => x * 2", "[1, 2]");
+  assert_eq!(summed.result.as_deref(), Some("3"));
+  assert_eq!(summed.optimization_used, "rust_fallback");
+  let attempt = summed.fallback.expect("attempt");
+  assert_eq!(attempt.function, "function_code");
+  assert!(!attempt.primary_error.is_empty());
+  let ran = events(&executor).into_iter().filter(|event| event["kind"] == "fallback_ran").count();
+  assert_eq!(ran, 1);
+  // A native success leaves it unused
+  let doubled = run(&executor, "x => x * 2", "3");
+  assert_eq!((doubled.result.as_deref(), doubled.fallback.is_none()), (Some("6"), true));
+
+  let sum = "xs => sum(xs)";
+  let total = executor.register_function("total@1.0.0".into(), sum.into(), hints(sum));
+  assert_eq!(total.unwrap(), "total@1.0.0");
+  let registered = FallbackConfig { registered: Some("total".to_string()), ..Default::default() };
+  executor.set_fallback(hash.clone(), Some(registered)).unwrap();
+  let summed = run(&executor, "x => x * 2", "[1, 2]");
+  assert_eq!(summed.result.as_deref(), Some("3"));
+  assert_eq!(summed.fallback.expect("attempt").function, "total@1.0.0");
+
+  // A failed fallback reports both errors
+  executor.set_fallback(hash.clone(), Some(code("x => fetch(x)"))).unwrap();
+  let failed = run(&executor, "x => x * 2", "[1, 2]");
+  assert!(failed.error.unwrap().contains("; fallback function_code also failed: "));
+  executor.set_fallback(hash.clone(), None).unwrap();
+  let failed = run(&executor, "x => x * 2", "[1, 2]");
+  assert!(failed.error.is_some() && failed.fallback.is_none());
+
+  let refused = |config| executor.set_fallback(hash.clone(), Some(config)).unwrap_err().reason;
+  let both = FallbackConfig { registered: Some("total".to_string()), ..code("xs => sum(xs)") };
+  assert_eq!(refused(both), "A fallback needs exactly one of function_code and registered");
+  let hinted = FallbackConfig {
+    registered: Some("total".to_string()),
+    optimization_hints: Some(hints("xs => sum(xs)")),
+    ..Default::default()
+  };
+  assert_eq!(refused(hinted), "A registered fallback runs with its own hints");
+  let unknown = FallbackConfig { registered: Some("nobody".to_string()), ..Default::default() };
+  assert!(executor.set_fallback(hash, Some(unknown)).is_err());
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn operation_pools_keep_each_type_on_its_own_workers() {
+  let pooled: HashMap<String, u32> =
+    [("mathematical".to_string(), 1), ("string_operations".to_string(), 1)].into();
+  let executor = executor(ExecutorConfig { operation_pools: Some(pooled.clone()), ..workers(3) });
+
+  // A backlog of general work on the last worker doesn't hold up the others
+  executor.set_fault_injection(Some(r#"{"delay_rate": 1.0, "delay_ms": 20}"#.into())).unwrap();
+  let general: Vec<String> =
+    (0..10).map(|i| submit(&executor, "x => x * 2", &i.to_string())).collect();
+  let started = Instant::now();
+  let math = run_as(&executor, "mathematical", "x => x * 2", "21");
+  let shout = run_as(&executor, "string_operations", "s => s.toUpperCase()", r#""a""#);
+  assert!(started.elapsed() < Duration::from_millis(150), "{:?}", started.elapsed());
+  assert_eq!((math.worker_id, shout.worker_id), (0, 1));
+  for task_id in general {
+    assert_eq!(executor.get_result_by_id(task_id, Some(WAIT_MS)).unwrap().worker_id, 2);
+  }
+  executor.shutdown().unwrap();
+
+  let crowded = OptimizedMultiCoreExecutor::new(Some(ExecutorConfig {
+    operation_pools: Some(pooled),
+    ..workers(2)
+  }));
+  assert_eq!(
+    crowded.err().unwrap().reason,
+    "operation_pools dedicate 2 workers, so worker_count must be at least 3, got 2"
+  );
+}
@@ -0,0 +1,177 @@
+// src/fallback.rs - Alternate functions run when a native execution fails
+//
+// A function the native paths can't handle for some inputs would otherwise
+// need a JavaScript wrapper that catches the error and resubmits something
+// else. With a fallback set for its function hash, a worker whose native
+// execution fails runs the fallback on the same input in its place, and the
+// result reports how long each attempt took.
+use serde::{ Deserialize, Serialize };
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::bindings::napi;
+use crate::binary::FastPathOutput;
+use crate::clock::SharedClock;
+use crate::function_registry::FunctionRegistry;
+use crate::sync::{ Arc, Mutex, MutexGuard, lock_recovering };
+use crate::{ OptimizationHints, OptimizedWorkerTask };
+
+/// What to run when a function's native execution fails: exactly one of
+/// `function_code` or `registered`
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct FallbackConfig {
+  /// Alternate code run natively on the same input
+  pub function_code: Option<String>,
+  /// Hints for `function_code`; defaults to the failed task's
+  pub optimization_hints: Option<OptimizationHints>,
+  /// Registered function to run instead, as `name` or `name@range`; resolved
+  /// again each time it runs, so it follows newly registered versions
+  pub registered: Option<String>,
+}
+
+/// Both attempts of a task whose fallback ran
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FallbackAttempt {
+  /// Registered `name@version` that ran, or "function_code"
+  pub function: String,
+  /// Error the native execution failed with
+  pub primary_error: String,
+  pub primary_duration_ms: f64,
+  pub fallback_duration_ms: f64,
+}
+
+impl FallbackAttempt {
+  /// Message of the `fallback_ran` event for `task_id`
+  pub fn describe(&self, task_id: &str, succeeded: bool) -> String {
+    format!(
+      "Task {} failed natively after {:.1}ms ({}); fallback {} {} after {:.1}ms",
+      task_id,
+      self.primary_duration_ms,
+      self.primary_error,
+      self.function,
+      if succeeded { "succeeded" } else { "failed" },
+      self.fallback_duration_ms
+    )
+  }
+}
+
+#[derive(Debug, Clone)]
+enum Fallback {
+  Code {
+    function_code: String,
+    hints: Option<Box<OptimizationHints>>,
+  },
+  Registered(String),
+}
+
+/// Fallbacks by function hash
+#[derive(Debug)]
+pub struct Fallbacks {
+  fallbacks: Mutex<HashMap<String, Fallback>>,
+  functions: Arc<Mutex<FunctionRegistry>>,
+}
+
+impl Default for Fallbacks {
+  /// Fallbacks with no registered functions to name, for the mock
+  fn default() -> Self {
+    Self::new(Arc::new(Mutex::new(FunctionRegistry::new(0))))
+  }
+}
+
+impl Fallbacks {
+  /// Fallbacks whose `registered` names resolve in `functions`
+  pub fn new(functions: Arc<Mutex<FunctionRegistry>>) -> Self {
+    Self {
+      fallbacks: Mutex::new(HashMap::new()),
+      functions,
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, HashMap<String, Fallback>> {
+    lock_recovering(&self.fallbacks, || {})
+  }
+
+  /// Set the fallback for `function_hash`, or remove it with `None`. A
+  /// registered fallback must resolve now, too.
+  pub fn set(&self, function_hash: String, config: Option<FallbackConfig>) -> Result<(), String> {
+    let Some(config) = config else {
+      self.lock().remove(&function_hash);
+      return Ok(());
+    };
+    let fallback = match (config.function_code, config.registered) {
+      (Some(function_code), None) => Fallback::Code {
+        function_code,
+        hints: config.optimization_hints.map(Box::new),
+      },
+      (None, Some(spec)) => {
+        if config.optimization_hints.is_some() {
+          return Err("A registered fallback runs with its own hints".to_string());
+        }
+        lock_recovering(&self.functions, || {}).resolve(&spec)?;
+        Fallback::Registered(spec)
+      }
+      _ => {
+        return Err("A fallback needs exactly one of function_code and registered".to_string());
+      }
+    };
+    self.lock().insert(function_hash, fallback);
+    Ok(())
+  }
+
+  /// After `task`'s native execution failed with `error` after `primary`, run
+  /// its function's fallback on the same input with `execute`, if it has one.
+  /// Returns the fallback's outcome, which names both errors if it failed
+  /// too, and the record of both attempts, timed with `clock`.
+  pub fn run(
+    &self,
+    task: &OptimizedWorkerTask,
+    error: &str,
+    primary: Duration,
+    clock: &SharedClock,
+    execute: impl FnOnce(&OptimizedWorkerTask) -> Result<FastPathOutput, String>
+  ) -> Option<(Result<FastPathOutput, String>, FallbackAttempt)> {
+    let fallback = self.lock().get(&task.optimization_hints.function_hash).cloned()?;
+
+    let start = clock.now();
+    let (function, outcome) = match fallback {
+      Fallback::Code { function_code, hints } => {
+        let hints = hints.map_or_else(|| task.optimization_hints.clone(), |hints| *hints);
+        let fallback_task = OptimizedWorkerTask {
+          function_code,
+          optimization_hints: hints,
+          ..task.clone()
+        };
+        ("function_code".to_string(), execute(&fallback_task))
+      }
+      Fallback::Registered(spec) => {
+        let resolved = lock_recovering(&self.functions, || {}).resolve(&spec).cloned();
+        match resolved {
+          Ok(registered) => {
+            let fallback_task = OptimizedWorkerTask {
+              function_code: registered.function_code.clone(),
+              optimization_hints: registered.hints.clone(),
+              ..task.clone()
+            };
+            (registered.key(), execute(&fallback_task))
+          }
+          Err(err) => (spec, Err(err)),
+        }
+      }
+    };
+    let elapsed = clock.now().saturating_duration_since(start);
+
+    let outcome = outcome.map_err(|fallback_error| {
+      format!("{}; fallback {} also failed: {}", error, function, fallback_error)
+    });
+    let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+    let attempt = FallbackAttempt {
+      function,
+      primary_error: error.to_string(),
+      primary_duration_ms: millis(primary),
+      fallback_duration_ms: millis(elapsed),
+    };
+    Some((outcome, attempt))
+  }
+}
@@ -0,0 +1,108 @@
+// src/fault_injection.rs - Chaos mode for exercising caller retry/fallback paths
+use serde::{ Deserialize, Serialize };
+use std::sync::atomic::{ AtomicU64, Ordering };
+
+use crate::sync::Mutex;
+
+/// Fault rates, each a probability in `0.0..=1.0`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FaultConfig {
+  pub delay_rate: f64,
+  pub delay_ms: u64,
+  pub worker_panic_rate: f64,
+  pub native_panic_rate: f64,
+  pub queue_full_rate: f64,
+  pub timeout_rate: f64,
+}
+
+/// Fault chosen for a single task execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerFault {
+  /// Sleep before executing
+  Delay(u64),
+  /// Worker thread dies, dropping the task without a result
+  Panic,
+  /// Native execution panics, failing the task
+  NativePanic,
+  /// Task reports a timeout instead of executing
+  Timeout,
+}
+
+/// Randomly injects faults according to the active `FaultConfig`
+#[derive(Debug)]
+pub struct FaultInjector {
+  config: Mutex<Option<FaultConfig>>,
+  seed: u64,
+  rng_state: AtomicU64,
+}
+
+impl FaultInjector {
+  pub fn new(seed: u64) -> Self {
+    Self {
+      config: Mutex::new(None),
+      seed,
+      // xorshift must never be seeded with zero
+      rng_state: AtomicU64::new(seed | 1),
+    }
+  }
+
+  /// Enable chaos with the given rates, or disable it with `None`
+  pub fn configure(&self, config: Option<FaultConfig>) {
+    if let Ok(mut current) = self.config.lock() {
+      *current = config;
+    }
+  }
+
+  /// Seed the fault RNG started from, reported in execution receipts
+  pub fn seed(&self) -> u64 {
+    self.seed
+  }
+
+  pub fn config(&self) -> Option<FaultConfig> {
+    self.config
+      .lock()
+      .ok()
+      .and_then(|c| c.clone())
+  }
+
+  /// Whether a submission should be rejected as if the queue were full
+  pub fn should_reject_submission(&self) -> bool {
+    match self.config() {
+      Some(config) => self.roll(config.queue_full_rate),
+      None => false,
+    }
+  }
+
+  /// Pick the fault, if any, to apply to the next task execution
+  pub fn next_worker_fault(&self) -> Option<WorkerFault> {
+    let config = self.config()?;
+
+    if self.roll(config.worker_panic_rate) {
+      Some(WorkerFault::Panic)
+    } else if self.roll(config.native_panic_rate) {
+      Some(WorkerFault::NativePanic)
+    } else if self.roll(config.timeout_rate) {
+      Some(WorkerFault::Timeout)
+    } else if self.roll(config.delay_rate) {
+      Some(WorkerFault::Delay(config.delay_ms))
+    } else {
+      None
+    }
+  }
+
+  fn roll(&self, rate: f64) -> bool {
+    rate > 0.0 && self.next_f64() < rate
+  }
+
+  /// xorshift64*, good enough for fault sampling
+  fn next_f64(&self) -> f64 {
+    let mut x = self.rng_state.load(Ordering::Relaxed);
+    x ^= x >> 12;
+    x ^= x << 25;
+    x ^= x >> 27;
+    self.rng_state.store(x, Ordering::Relaxed);
+    let value = x.wrapping_mul(0x2545_f491_4f6c_dd1d);
+    ((value >> 11) as f64) / ((1u64 << 53) as f64)
+  }
+}
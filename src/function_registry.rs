@@ -0,0 +1,301 @@
+// src/function_registry.rs - Named, semver-versioned function registrations
+use semver::{ Version, VersionReq };
+use std::collections::{ BTreeMap, HashMap };
+
+use crate::OptimizationHints;
+use crate::incremental::Aggregation;
+use crate::snapshot::FunctionState;
+
+/// A function registered under `name@version`
+#[derive(Debug, Clone)]
+pub struct RegisteredFunction {
+  pub name: String,
+  pub version: Version,
+  pub function_code: String,
+  pub hints: OptimizationHints,
+}
+
+impl RegisteredFunction {
+  /// Versioned key, also used as the function hash so each version keeps its own
+  /// cache entries and stats
+  pub fn key(&self) -> String {
+    format!("{}@{}", self.name, self.version)
+  }
+}
+
+/// A candidate version receiving a fraction of a function's traffic
+#[derive(Debug, Clone)]
+pub struct StagedRollout {
+  pub function: RegisteredFunction,
+  pub traffic_fraction: f64,
+}
+
+/// Registry of functions by name, each with any number of versions
+#[derive(Debug)]
+pub struct FunctionRegistry {
+  functions: HashMap<String, BTreeMap<Version, RegisteredFunction>>,
+  staged: HashMap<String, StagedRollout>,
+  rng_state: u64,
+}
+
+impl FunctionRegistry {
+  pub fn new(seed: u64) -> Self {
+    Self {
+      functions: HashMap::new(),
+      staged: HashMap::new(),
+      // xorshift must never be seeded with zero
+      rng_state: seed | 1,
+    }
+  }
+
+  /// Register `name@version` (e.g. `transform@1.2.0`), replacing that exact version
+  pub fn register(
+    &mut self,
+    spec: &str,
+    function_code: String,
+    hints: OptimizationHints
+  ) -> Result<RegisteredFunction, String> {
+    let registered = build_function(spec, function_code, hints)?;
+    self.insert(registered.clone());
+    Ok(registered)
+  }
+
+  /// Stage a new, not yet registered version that receives `traffic_fraction` of the
+  /// submissions for its name. Replaces any rollout already staged for that name.
+  pub fn stage(
+    &mut self,
+    spec: &str,
+    function_code: String,
+    hints: OptimizationHints,
+    traffic_fraction: f64
+  ) -> Result<RegisteredFunction, String> {
+    validate_fraction(traffic_fraction)?;
+    let function = build_function(spec, function_code, hints)?;
+    if
+      self.functions
+        .get(&function.name)
+        .is_some_and(|versions| versions.contains_key(&function.version))
+    {
+      return Err(format!("'{}' is already registered", function.key()));
+    }
+
+    self.staged.insert(function.name.clone(), StagedRollout {
+      function: function.clone(),
+      traffic_fraction,
+    });
+    Ok(function)
+  }
+
+  /// Change the traffic fraction of a staged rollout
+  pub fn set_staged_traffic(&mut self, name: &str, traffic_fraction: f64) -> Result<(), String> {
+    validate_fraction(traffic_fraction)?;
+    self.staged
+      .get_mut(name)
+      .map(|rollout| rollout.traffic_fraction = traffic_fraction)
+      .ok_or_else(|| format!("No staged rollout for '{}'", name))
+  }
+
+  /// Register the staged version so it serves all matching traffic
+  pub fn promote(&mut self, name: &str) -> Result<RegisteredFunction, String> {
+    let rollout = self.staged
+      .remove(name)
+      .ok_or_else(|| format!("No staged rollout for '{}'", name))?;
+    self.insert(rollout.function.clone());
+    Ok(rollout.function)
+  }
+
+  /// Drop the staged version, returning all traffic to the registered versions
+  pub fn rollback(&mut self, name: &str) -> Result<RegisteredFunction, String> {
+    self.staged
+      .remove(name)
+      .map(|rollout| rollout.function)
+      .ok_or_else(|| format!("No staged rollout for '{}'", name))
+  }
+
+  /// Rollout currently staged for `name`, if any
+  pub fn staged(&self, name: &str) -> Option<&StagedRollout> {
+    self.staged.get(name)
+  }
+
+  /// Pick the function for a submission: the staged version for its share of traffic
+  /// when it satisfies the requested range, otherwise the resolved registered version
+  pub fn route(&mut self, spec: &str) -> Result<RegisteredFunction, String> {
+    let stable = self.resolve(spec)?.clone();
+    let staged = match self.staged.get(&stable.name) {
+      Some(rollout) if requirement_for(spec)?.matches(&rollout.function.version) => rollout,
+      _ => return Ok(stable),
+    };
+
+    let traffic_fraction = staged.traffic_fraction;
+    let staged = staged.function.clone();
+    if traffic_fraction > 0.0 && self.next_f64() < traffic_fraction {
+      Ok(staged)
+    } else {
+      Ok(stable)
+    }
+  }
+
+  /// Highest registered version matching `name@range` (`transform@^1.2`, `transform@*`,
+  /// or bare `transform` for the latest)
+  pub fn resolve(&self, spec: &str) -> Result<&RegisteredFunction, String> {
+    let name = spec.split_once('@').map_or(spec, |(name, _)| name);
+    let requirement = requirement_for(spec)?;
+
+    self.functions
+      .get(name)
+      .and_then(|versions| {
+        versions
+          .iter()
+          .rev()
+          .find(|(version, _)| requirement.matches(version))
+          .map(|(_, function)| function)
+      })
+      .ok_or_else(|| format!("No registered function matches '{}'", spec))
+  }
+
+  /// Remove an exact `name@version`, or every version with a bare `name`
+  pub fn unregister(&mut self, spec: &str) -> Result<(), String> {
+    match spec.split_once('@') {
+      Some(_) => {
+        let (name, version) = split_spec(spec)?;
+        let version = Version::parse(version).map_err(|e|
+          format!("Invalid version in '{}': {}", spec, e)
+        )?;
+        let versions = self.functions
+          .get_mut(name)
+          .ok_or_else(|| format!("No registered function '{}'", name))?;
+        versions.remove(&version).ok_or_else(|| format!("No registered function '{}'", spec))?;
+        if versions.is_empty() {
+          self.functions.remove(name);
+        }
+        Ok(())
+      }
+      None => {
+        let staged = self.staged.remove(spec);
+        match self.functions.remove(spec) {
+          Some(_) => Ok(()),
+          None if staged.is_some() => Ok(()),
+          None => Err(format!("No registered function '{}'", spec)),
+        }
+      }
+    }
+  }
+
+  /// All registered `name@version` keys, sorted
+  pub fn list(&self) -> Vec<String> {
+    let mut keys: Vec<String> = self.functions
+      .values()
+      .flat_map(|versions| versions.values().map(RegisteredFunction::key))
+      .collect();
+    keys.sort();
+    keys
+  }
+
+  /// Every registered version, then every staged rollout, for `export_state`
+  pub fn export(&self) -> Vec<FunctionState> {
+    let state = |function: &RegisteredFunction, traffic_fraction| FunctionState {
+      spec: function.key(),
+      function_code: function.function_code.clone(),
+      hints: function.hints.clone(),
+      traffic_fraction,
+    };
+    let mut exported: Vec<FunctionState> = self.functions
+      .values()
+      .flat_map(|versions| versions.values().map(|function| state(function, None)))
+      .collect();
+    exported.extend(
+      self.staged.values().map(|rollout| state(&rollout.function, Some(rollout.traffic_fraction)))
+    );
+    exported
+  }
+
+  /// Register and stage exported functions, replacing the same versions and
+  /// rollouts here. Nothing changes unless every one is valid. A staged
+  /// version already registered here stays registered.
+  pub fn import(&mut self, functions: Vec<FunctionState>) -> Result<(), String> {
+    let mut built = Vec::with_capacity(functions.len());
+    for function in functions {
+      if let Some(traffic_fraction) = function.traffic_fraction {
+        validate_fraction(traffic_fraction)?;
+      }
+      let registered = build_function(&function.spec, function.function_code, function.hints)?;
+      built.push((registered, function.traffic_fraction));
+    }
+    for (function, traffic_fraction) in built {
+      match traffic_fraction {
+        None => self.insert(function),
+        Some(traffic_fraction) => {
+          let registered = self.functions
+            .get(&function.name)
+            .is_some_and(|versions| versions.contains_key(&function.version));
+          if !registered {
+            self.staged.insert(function.name.clone(), StagedRollout { function, traffic_fraction });
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+
+  fn insert(&mut self, function: RegisteredFunction) {
+    self.functions
+      .entry(function.name.clone())
+      .or_default()
+      .insert(function.version.clone(), function);
+  }
+
+  /// xorshift64*, good enough for traffic splitting
+  fn next_f64(&mut self) -> f64 {
+    let mut x = self.rng_state;
+    x ^= x >> 12;
+    x ^= x << 25;
+    x ^= x >> 27;
+    self.rng_state = x;
+    let value = x.wrapping_mul(0x2545_f491_4f6c_dd1d);
+    ((value >> 11) as f64) / ((1u64 << 53) as f64)
+  }
+}
+
+fn build_function(
+  spec: &str,
+  function_code: String,
+  mut hints: OptimizationHints
+) -> Result<RegisteredFunction, String> {
+  let (name, version) = split_spec(spec)?;
+  let version = Version::parse(version).map_err(|e|
+    format!("Invalid version in '{}': {}", spec, e)
+  )?;
+  // A bad aggregate should fail registration, not the first delta
+  if let Some(aggregate) = hints.aggregate.as_deref() {
+    Aggregation::parse(aggregate)?;
+  }
+
+  hints.function_hash = format!("{}@{}", name, version);
+  Ok(RegisteredFunction {
+    name: name.to_string(),
+    version,
+    function_code,
+    hints,
+  })
+}
+
+/// Version range of a `name@range` spec; a bare name matches every version
+fn requirement_for(spec: &str) -> Result<VersionReq, String> {
+  let range = spec.split_once('@').map_or("*", |(_, range)| range);
+  VersionReq::parse(range).map_err(|e| format!("Invalid version range in '{}': {}", spec, e))
+}
+
+fn validate_fraction(traffic_fraction: f64) -> Result<(), String> {
+  if (0.0..=1.0).contains(&traffic_fraction) {
+    Ok(())
+  } else {
+    Err(format!("Traffic fraction must be between 0 and 1, got {}", traffic_fraction))
+  }
+}
+
+fn split_spec(spec: &str) -> Result<(&str, &str), String> {
+  match spec.split_once('@') {
+    Some((name, version)) if !name.is_empty() && !version.is_empty() => Ok((name, version)),
+    _ => Err(format!("Expected 'name@version', got '{}'", spec)),
+  }
+}
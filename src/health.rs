@@ -0,0 +1,160 @@
+// src/health.rs - Readiness probe that runs a canary task end to end
+use crossbeam::channel;
+#[cfg(feature = "node")]
+use napi::{ Env, Result, Task };
+use std::time::{ Duration, Instant };
+
+use crate::bindings::napi;
+use crate::clock::SharedClock;
+use crate::{ OptimizationHints, OptimizedWorkerResult };
+
+/// Function hash of the canary; workers leave it out of stats and learning state
+pub const CANARY_FUNCTION_HASH: &str = "__threader_health_check__";
+pub const DEFAULT_TIMEOUT_MS: u32 = 1000;
+
+/// Outcome of `health_check`
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct HealthStatus {
+  /// "healthy", "degraded" (canary ran, but workers are parked while the CPU
+  /// is throttled) or "unhealthy" (canary failed or missed its deadline)
+  pub status: String,
+  /// Submission to result, when the canary finished
+  pub latency_ms: Option<f64>,
+  pub worker_id: Option<u32>,
+  pub worker_count: u32,
+  pub effective_parallelism: u32,
+  /// Shared queue depth when the canary was submitted
+  pub queued_tasks: u32,
+  /// Why the executor is unhealthy
+  pub error: Option<String>,
+}
+
+/// Canary input for the Rust fast path: doubling a number
+pub fn canary_task() -> (String, String, OptimizationHints) {
+  let hints = OptimizationHints {
+    operation_type: "mathematical".to_string(),
+    complexity: "low".to_string(),
+    expected_cores: 1,
+    should_use_rust: true,
+    is_hot_function: false,
+    estimated_memory: 0,
+    batch_size_hint: None,
+    function_hash: CANARY_FUNCTION_HASH.to_string(),
+    include_receipt: None,
+    deterministic: None,
+    output_precision: None,
+    rounding: None,
+    locale: None,
+    canonical_json: None,
+    priority: None,
+    typed_result: None,
+    group: None,
+    after: None,
+    chunk_size: None,
+    tenant: None,
+    aggregate: None,
+    max_queue_age_ms: None,
+    dedupe: None,
+    child: None,
+    idempotency_key: None,
+  };
+  ("x => x * 2".to_string(), "21".to_string(), hints)
+}
+
+/// Waits for the canary's result, up to the deadline; off the JS thread when
+/// run as an `AsyncTask`.
+///
+/// Never fails: every problem is reported as an "unhealthy" status, so
+/// a readiness endpoint can serialize the result as-is.
+pub struct HealthCheck {
+  // The canary's result channel, or why it couldn't be submitted
+  probe: std::result::Result<channel::Receiver<OptimizedWorkerResult>, String>,
+  clock: SharedClock,
+  submitted: Instant,
+  timeout: Duration,
+  worker_count: u32,
+  effective_parallelism: u32,
+  queued_tasks: u32,
+}
+
+impl HealthCheck {
+  pub fn new(
+    probe: std::result::Result<channel::Receiver<OptimizedWorkerResult>, String>,
+    timeout_ms: Option<u32>,
+    worker_count: u32,
+    effective_parallelism: u32,
+    queued_tasks: u32,
+    clock: SharedClock
+  ) -> Self {
+    Self {
+      probe,
+      submitted: clock.now(),
+      clock,
+      timeout: Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS) as u64),
+      worker_count,
+      effective_parallelism,
+      queued_tasks,
+    }
+  }
+
+  fn elapsed(&self) -> Duration {
+    self.clock.now().saturating_duration_since(self.submitted)
+  }
+
+  /// Block until the canary finishes or the deadline passes
+  pub fn wait(&self) -> HealthStatus {
+    let outcome = match &self.probe {
+      Err(error) => Err(error.clone()),
+      Ok(receiver) => {
+        let remaining = self.timeout.saturating_sub(self.elapsed());
+        match receiver.recv_timeout(remaining) {
+          Ok(result) =>
+            match result.error {
+              Some(error) => Err(format!("Canary task failed: {}", error)),
+              None => Ok(result),
+            }
+          Err(channel::RecvTimeoutError::Timeout) =>
+            Err(format!("Canary task did not finish within {}ms", self.timeout.as_millis())),
+          Err(channel::RecvTimeoutError::Disconnected) =>
+            Err("Canary task was dropped before finishing".to_string()),
+        }
+      }
+    };
+
+    let status = match &outcome {
+      Err(_) => "unhealthy",
+      Ok(_) if self.effective_parallelism < self.worker_count => "degraded",
+      Ok(_) => "healthy",
+    };
+    let (latency_ms, worker_id, error) = match outcome {
+      Ok(result) =>
+        (Some(self.elapsed().as_secs_f64() * 1000.0), Some(result.worker_id), None),
+      Err(error) => (None, None, Some(error)),
+    };
+
+    HealthStatus {
+      status: status.to_string(),
+      latency_ms,
+      worker_id,
+      worker_count: self.worker_count,
+      effective_parallelism: self.effective_parallelism,
+      queued_tasks: self.queued_tasks,
+      error,
+    }
+  }
+}
+
+#[cfg(feature = "node")]
+impl Task for HealthCheck {
+  type Output = HealthStatus;
+  type JsValue = HealthStatus;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(self.wait())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
@@ -0,0 +1,266 @@
+// src/host_info.rs - What the machine offers, for `get_optimized_multicore_info`
+//
+// Sizing a pool well takes more than a core count: a container's CPU quota
+// can be a fraction of the cores it sees, and a pool spanning NUMA nodes or
+// outgrowing the shared cache runs slower per worker. Everything here comes
+// from sysfs, procfs and the process's cgroup on Linux, and is left empty
+// where those don't exist. It's probed on first use and cached, as none of it
+// changes while the process runs.
+use serde::Serialize;
+use std::fs;
+use std::path::{ Path, PathBuf };
+use std::sync::OnceLock;
+
+use crate::self_test;
+
+const CPU_DIR: &str = "/sys/devices/system/cpu";
+const NODE_DIR: &str = "/sys/devices/system/node";
+const CGROUP_DIR: &str = "/sys/fs/cgroup";
+
+// cgroup v1 reports no memory limit as the largest page-aligned i64
+const UNLIMITED_BYTES: u64 = 1 << 62;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+const SIMD_FEATURES: &[&str] = &["sse2", "sse4.2", "avx", "avx2", "fma"];
+#[cfg(target_arch = "aarch64")]
+const SIMD_FEATURES: &[&str] = &["neon"];
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+const SIMD_FEATURES: &[&str] = &[];
+
+static HOST: OnceLock<HostInfo> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HostInfo {
+  /// Each online core's maximum frequency, or its current one where the
+  /// maximum isn't exposed
+  pub cores: Vec<CoreInfo>,
+  /// Caches of the first core; `shared_by` says how many cores share each
+  pub caches: Vec<CacheInfo>,
+  /// SIMD features this CPU supports, of those the fast paths can use
+  pub simd: Vec<String>,
+  pub cgroup: CgroupLimits,
+  pub numa_nodes: Vec<NumaNode>,
+  pub memory_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CoreInfo {
+  pub core: usize,
+  pub frequency_mhz: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheInfo {
+  pub level: u32,
+  /// "Data", "Instruction" or "Unified"
+  pub kind: String,
+  pub size_bytes: u64,
+  pub shared_by: usize,
+}
+
+/// Limits of the process's cgroup, each None where unlimited or unknown
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CgroupLimits {
+  /// 1 or 2, or None outside a cgroup
+  pub version: Option<u8>,
+  /// CPU quota as a number of cores, e.g. 1.5
+  pub cpu_quota_cores: Option<f64>,
+  pub memory_limit_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NumaNode {
+  pub node: usize,
+  pub cores: Vec<usize>,
+  pub memory_bytes: Option<u64>,
+}
+
+/// The host, probed on the first call
+pub fn get() -> &'static HostInfo {
+  HOST.get_or_init(probe)
+}
+
+fn probe() -> HostInfo {
+  HostInfo {
+    cores: cores(),
+    caches: caches(),
+    simd: SIMD_FEATURES.iter()
+      .filter(|feature| self_test::cpu_supports(feature))
+      .map(|feature| feature.to_string())
+      .collect(),
+    cgroup: cgroup_limits(),
+    numa_nodes: numa_nodes(),
+    memory_bytes: fs::read_to_string("/proc/meminfo")
+      .ok()
+      .and_then(|meminfo| kib_field(&meminfo, "MemTotal:")),
+  }
+}
+
+fn cores() -> Vec<CoreInfo> {
+  let online = read(Path::new(CPU_DIR).join("online"))
+    .map(|list| cpu_list(&list))
+    .unwrap_or_else(|| (0..num_cpus::get()).collect());
+  // /proc/cpuinfo lists a "cpu MHz" line per online core, in order
+  let current: Vec<u32> = fs::read_to_string("/proc/cpuinfo")
+    .map(|cpuinfo| {
+      cpuinfo
+        .lines()
+        .filter(|line| line.starts_with("cpu MHz"))
+        .filter_map(|line| line.split(':').nth(1)?.trim().parse::<f64>().ok())
+        .map(|mhz| mhz.round() as u32)
+        .collect()
+    })
+    .unwrap_or_default();
+
+  online
+    .into_iter()
+    .enumerate()
+    .map(|(index, core)| {
+      let max_khz = read(Path::new(CPU_DIR).join(format!("cpu{}/cpufreq/cpuinfo_max_freq", core)))
+        .and_then(|khz| khz.parse::<u64>().ok());
+      CoreInfo {
+        core,
+        frequency_mhz: max_khz
+          .map(|khz| (khz / 1000) as u32)
+          .or_else(|| current.get(index).copied()),
+      }
+    })
+    .collect()
+}
+
+fn caches() -> Vec<CacheInfo> {
+  let dir = Path::new(CPU_DIR).join("cpu0/cache");
+  let mut caches: Vec<CacheInfo> = numbered_entries(&dir, "index")
+    .into_iter()
+    .filter_map(|(_, index)| {
+      Some(CacheInfo {
+        level: read(index.join("level"))?.parse().ok()?,
+        kind: read(index.join("type"))?,
+        size_bytes: size_bytes(&read(index.join("size"))?)?,
+        shared_by: read(index.join("shared_cpu_list")).map_or(1, |list| cpu_list(&list).len()),
+      })
+    })
+    .collect();
+  caches.sort_by(|a, b| a.level.cmp(&b.level).then_with(|| a.kind.cmp(&b.kind)));
+  caches
+}
+
+fn numa_nodes() -> Vec<NumaNode> {
+  numbered_entries(Path::new(NODE_DIR), "node")
+    .into_iter()
+    .map(|(node, dir)| NumaNode {
+      node,
+      cores: read(dir.join("cpulist")).map(|list| cpu_list(&list)).unwrap_or_default(),
+      // Lines read "Node 0 MemTotal:  6158152 kB"
+      memory_bytes: read(dir.join("meminfo")).and_then(|meminfo| kib_field(&meminfo, "MemTotal:")),
+    })
+    .collect()
+}
+
+fn cgroup_limits() -> CgroupLimits {
+  let Some(membership) = read(PathBuf::from("/proc/self/cgroup")) else {
+    return CgroupLimits::default();
+  };
+  let unified = Path::new(CGROUP_DIR).join("cgroup.controllers").exists();
+  if unified {
+    // A single "0::/path" line
+    let path = membership.lines().find_map(|line| line.strip_prefix("0::")).unwrap_or("/");
+    let cpu_quota_cores = cgroup_file(None, path, "cpu.max").and_then(|max| {
+      let mut fields = max.split_whitespace();
+      quota_cores(fields.next()?.parse().ok()?, fields.next()?.parse().ok()?)
+    });
+    let memory_limit_bytes = cgroup_file(None, path, "memory.max").and_then(|max| max.parse().ok());
+    return CgroupLimits { version: Some(2), cpu_quota_cores, memory_limit_bytes };
+  }
+
+  // One "id:controller,controller:/path" line per hierarchy
+  let path_of = |controller: &str| {
+    membership.lines().find_map(|line| {
+      let mut fields = line.splitn(3, ':');
+      let controllers = fields.nth(1)?;
+      let path = fields.next()?;
+      controllers.split(',').any(|name| name == controller).then_some(path)
+    })
+  };
+  let cpu_quota_cores = path_of("cpu").and_then(|path| {
+    let quota: i64 = cgroup_file(Some("cpu"), path, "cpu.cfs_quota_us")?.parse().ok()?;
+    let period = cgroup_file(Some("cpu"), path, "cpu.cfs_period_us")?.parse().ok()?;
+    quota_cores(u64::try_from(quota).ok()?, period)
+  });
+  let memory_limit_bytes = path_of("memory").and_then(|path| {
+    let limit: u64 = cgroup_file(Some("memory"), path, "memory.limit_in_bytes")?.parse().ok()?;
+    (limit < UNLIMITED_BYTES).then_some(limit)
+  });
+  let version = (path_of("cpu").is_some() || path_of("memory").is_some()).then_some(1);
+  CgroupLimits { version, cpu_quota_cores, memory_limit_bytes }
+}
+
+// A cgroup's file, falling back to the hierarchy's root where the process's
+// own cgroup isn't mounted in its namespace
+fn cgroup_file(controller: Option<&str>, path: &str, file: &str) -> Option<String> {
+  let root = controller.map_or_else(|| PathBuf::from(CGROUP_DIR), |name| {
+    Path::new(CGROUP_DIR).join(name)
+  });
+  read(root.join(path.trim_start_matches('/')).join(file)).or_else(|| read(root.join(file)))
+}
+
+pub(crate) fn quota_cores(quota_us: u64, period_us: u64) -> Option<f64> {
+  (period_us > 0).then(|| (quota_us as f64) / (period_us as f64))
+}
+
+fn read(path: PathBuf) -> Option<String> {
+  fs::read_to_string(path).ok().map(|text| text.trim().to_string())
+}
+
+// Entries of `dir` named `prefix` and a number, by number
+fn numbered_entries(dir: &Path, prefix: &str) -> Vec<(usize, PathBuf)> {
+  let mut entries: Vec<(usize, PathBuf)> = fs::read_dir(dir)
+    .map(|entries| {
+      entries
+        .filter_map(|entry| {
+          let entry = entry.ok()?;
+          let number = entry.file_name().to_str()?.strip_prefix(prefix)?.parse().ok()?;
+          Some((number, entry.path()))
+        })
+        .collect()
+    })
+    .unwrap_or_default();
+  entries.sort();
+  entries
+}
+
+// Core numbers in a list like "0-3,8,10-11"
+pub(crate) fn cpu_list(list: &str) -> Vec<usize> {
+  list
+    .split(',')
+    .filter_map(|range| {
+      let (first, last) = range.trim().split_once('-').unwrap_or((range.trim(), range.trim()));
+      Some(first.parse::<usize>().ok()?..=last.parse::<usize>().ok()?)
+    })
+    .flatten()
+    .collect()
+}
+
+// A size like "48K" or "2M"
+pub(crate) fn size_bytes(size: &str) -> Option<u64> {
+  let digits_end = size.find(|c: char| !c.is_ascii_digit()).unwrap_or(size.len());
+  let (digits, unit) = size.split_at(digits_end);
+  let multiplier = match unit {
+    "" => 1,
+    "K" => 1 << 10,
+    "M" => 1 << 20,
+    "G" => 1 << 30,
+    _ => {
+      return None;
+    }
+  };
+  Some(digits.parse::<u64>().ok()? * multiplier)
+}
+
+// The value of a "<name> <n> kB" line, in bytes
+pub(crate) fn kib_field(text: &str, name: &str) -> Option<u64> {
+  text.lines().find_map(|line| {
+    let rest = &line[line.find(name)? + name.len()..];
+    Some(rest.split_whitespace().next()?.parse::<u64>().ok()? * 1024)
+  })
+}
@@ -0,0 +1,47 @@
+// src/host_info_tests.rs - Host cores, caches, SIMD, cgroup limits and NUMA layout
+use crate::host_info::{ self, cpu_list, kib_field, quota_cores, size_bytes };
+
+#[test]
+fn cpu_lists_expand_ranges() {
+  assert_eq!(cpu_list("0-3,8,10-11"), [0, 1, 2, 3, 8, 10, 11]);
+  assert_eq!(cpu_list("5"), [5]);
+  assert!(cpu_list("").is_empty());
+}
+
+#[test]
+fn sizes_and_meminfo_fields_are_read_as_bytes() {
+  assert_eq!(size_bytes("48K"), Some(48 << 10));
+  assert_eq!(size_bytes("2M"), Some(2 << 20));
+  assert_eq!(size_bytes("512"), Some(512));
+  assert_eq!(size_bytes("3T"), None);
+
+  let meminfo = "MemTotal:       16318504 kB\nMemFree:         1204620 kB";
+  assert_eq!(kib_field(meminfo, "MemFree:"), Some(1204620 * 1024));
+  assert_eq!(kib_field("Node 1 MemTotal:  6158152 kB", "MemTotal:"), Some(6158152 * 1024));
+  assert_eq!(kib_field(meminfo, "SwapTotal:"), None);
+}
+
+#[test]
+fn cpu_quota_is_counted_in_cores() {
+  assert_eq!(quota_cores(150_000, 100_000), Some(1.5));
+  assert_eq!(quota_cores(50_000, 0), None);
+}
+
+#[test]
+fn system_info_reports_the_probed_host() {
+  let info: serde_json::Value =
+    serde_json::from_str(&crate::get_optimized_multicore_info().unwrap()).unwrap();
+  let host = host_info::get();
+  assert_eq!(info["host"], serde_json::to_value(host).unwrap());
+  // Probed once: every call sees the same host
+  assert!(std::ptr::eq(host, host_info::get()));
+
+  if cfg!(target_os = "linux") {
+    assert!(!host.cores.is_empty());
+    assert!(host.memory_bytes.unwrap() > 0);
+  }
+  assert!(host.cores.windows(2).all(|pair| pair[0].core < pair[1].core));
+  assert!(host.caches.iter().all(|cache| cache.size_bytes > 0 && cache.shared_by >= 1));
+  assert!(host.simd.iter().all(|feature| crate::self_test::cpu_supports(feature)));
+  assert!(host.cgroup.cpu_quota_cores.is_none_or(|cores| cores > 0.0));
+}
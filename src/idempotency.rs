@@ -0,0 +1,179 @@
+// src/idempotency.rs - One execution per idempotency key
+//
+// A task submitted with an `idempotency_key` hint while an earlier task with
+// the same key is waiting, queued or running isn't queued again: the
+// submission returns the earlier task's ID. Once that task completes, its
+// result is kept for `idempotency_ttl_ms` (5 minutes by default), resubmissions
+// in that window get its ID too, and `get_result_by_id` returns a copy of the
+// result every time it's asked. A key whose task failed or was cancelled is
+// free again, so a retry runs afresh.
+//
+// Keys are honored by `submit_optimized_task` and `submit_borrowed`, whose
+// results are collected by ID; other submissions reject them.
+use std::collections::{ HashMap, VecDeque };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::time::{ Duration, Instant };
+
+use crate::OptimizedWorkerResult;
+use crate::clock::SharedClock;
+use crate::sync::{ Arc, Mutex, MutexGuard, lock_recovering };
+use crate::task_status::{ TaskState, TaskTracker };
+
+/// How long completed results answer resubmissions unless configured
+pub const DEFAULT_RETENTION: Duration = Duration::from_secs(300);
+
+// Keys are checked for failed and cancelled tasks once this many are held
+const MIN_SWEEP_LEN: usize = 1024;
+
+#[derive(Debug)]
+struct Entry {
+  task_id: String,
+  // The task's result and when it arrived, once it completed
+  result: Option<(OptimizedWorkerResult, Instant)>,
+}
+
+#[derive(Debug)]
+struct IdempotencyState {
+  entries: HashMap<String, Entry>,
+  // Key of each task holding one
+  keys: HashMap<String, String>,
+  // Keys in the order their results arrived, for expiry
+  completed: VecDeque<(Instant, String)>,
+  next_sweep: usize,
+}
+
+impl IdempotencyState {
+  fn remove(&mut self, key: &str) {
+    if let Some(entry) = self.entries.remove(key) {
+      self.keys.remove(&entry.task_id);
+    }
+  }
+}
+
+/// Tasks by idempotency key, and the results of those that completed recently
+pub struct IdempotencyTable {
+  state: Mutex<IdempotencyState>,
+  // Lets the result router skip the lock while no task has a key
+  key_count: AtomicUsize,
+  retention: Duration,
+  tasks: Arc<TaskTracker>,
+  clock: SharedClock,
+}
+
+impl IdempotencyTable {
+  pub fn new(retention: Duration, tasks: Arc<TaskTracker>, clock: SharedClock) -> Self {
+    let state = IdempotencyState {
+      entries: HashMap::new(),
+      keys: HashMap::new(),
+      completed: VecDeque::new(),
+      next_sweep: MIN_SWEEP_LEN,
+    };
+    Self { state: Mutex::new(state), key_count: AtomicUsize::new(0), retention, tasks, clock }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, IdempotencyState> {
+    let mut state = lock_recovering(&self.state, || {});
+    let now = self.clock.now();
+    while let Some((arrived, key)) = state.completed.front().cloned() {
+      if now.saturating_duration_since(arrived) < self.retention {
+        break;
+      }
+      state.completed.pop_front();
+      let current = state.entries
+        .get(&key)
+        .and_then(|entry| entry.result.as_ref())
+        .is_some_and(|(_, completed)| *completed == arrived);
+      if current {
+        state.remove(&key);
+      }
+    }
+    self.update_count(&state);
+    state
+  }
+
+  fn update_count(&self, state: &IdempotencyState) {
+    self.key_count.store(state.keys.len(), Ordering::Release);
+  }
+
+  /// The task that holds `key`, or `None` after giving it to `task_id`
+  pub fn claim(&self, key: &str, task_id: &str) -> Option<String> {
+    let mut state = self.lock();
+    if let Some(entry) = state.entries.get(key) {
+      if entry.result.is_some() || self.holds(&entry.task_id) {
+        return Some(entry.task_id.clone());
+      }
+      state.remove(key);
+    }
+    if state.entries.len() >= state.next_sweep {
+      state.entries.retain(|_, entry| entry.result.is_some() || self.holds(&entry.task_id));
+      state.keys = state.entries
+        .iter()
+        .map(|(key, entry)| (entry.task_id.clone(), key.clone()))
+        .collect();
+      state.next_sweep = (state.entries.len() * 2).max(MIN_SWEEP_LEN);
+    }
+    state.entries.insert(key.to_string(), Entry { task_id: task_id.to_string(), result: None });
+    state.keys.insert(task_id.to_string(), key.to_string());
+    self.update_count(&state);
+    None
+  }
+
+  // Whether a task without a result yet still holds its key: it hasn't
+  // finished, or completed with its result on the way to the router
+  fn holds(&self, task_id: &str) -> bool {
+    self.tasks
+      .state(task_id)
+      .is_some_and(|state| !state.is_finished() || state == TaskState::Completed)
+  }
+
+  /// The task never made it into the queue; its key is free again
+  pub fn release(&self, task_id: &str) {
+    if self.key_count.load(Ordering::Acquire) == 0 {
+      return;
+    }
+    let mut state = self.lock();
+    if let Some(key) = state.keys.get(task_id).cloned() {
+      state.remove(&key);
+      self.update_count(&state);
+    }
+  }
+
+  /// Keep the result of a task holding a key, or free the key if it failed
+  pub fn completed(&self, result: &OptimizedWorkerResult) {
+    if self.key_count.load(Ordering::Acquire) == 0 {
+      return;
+    }
+    let now = self.clock.now();
+    let mut state = self.lock();
+    let Some(key) = state.keys.get(&result.id).cloned() else {
+      return;
+    };
+    if result.error.is_some() {
+      state.remove(&key);
+      self.update_count(&state);
+      return;
+    }
+    if let Some(entry) = state.entries.get_mut(&key) {
+      entry.result = Some((result.clone(), now));
+    }
+    state.completed.push_back((now, key));
+  }
+
+  /// The key `task_id` holds, if any
+  pub fn key(&self, task_id: &str) -> Option<String> {
+    if self.key_count.load(Ordering::Acquire) == 0 {
+      return None;
+    }
+    self.lock().keys.get(task_id).cloned()
+  }
+
+  /// A copy of a kept result
+  pub fn result(&self, task_id: &str) -> Option<OptimizedWorkerResult> {
+    if self.key_count.load(Ordering::Acquire) == 0 {
+      return None;
+    }
+    let state = self.lock();
+    let key = state.keys.get(task_id)?;
+    state.entries.get(key)?.result.as_ref().map(|(result, _)| result.clone())
+  }
+}
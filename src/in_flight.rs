@@ -0,0 +1,145 @@
+// src/in_flight.rs - Count of unfinished tasks behind `drain` and `wait_for_idle`
+#[cfg(feature = "node")]
+use napi::{ Env, Result, Task };
+use std::time::{ Duration, Instant };
+
+use crate::sync::{ Arc, Condvar, Mutex, MutexGuard, lock_recovering };
+
+#[derive(Debug, Default)]
+struct InFlightState {
+  // Submitted tasks not yet completed, skipped or lost
+  count: usize,
+  // Set by `drain`: submissions are rejected from then on
+  draining: bool,
+  // Set by `shutdown`: queued tasks will never run, so waiters give up
+  closed: bool,
+}
+
+/// Tasks between submission and completion, whichever queue they sit in
+#[derive(Debug, Default)]
+pub struct InFlight {
+  state: Mutex<InFlightState>,
+  idle: Condvar,
+}
+
+impl InFlight {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  fn lock(&self) -> MutexGuard<'_, InFlightState> {
+    lock_recovering(&self.state, || {})
+  }
+
+  /// Count a task about to be queued, unless the executor is draining
+  pub fn begin(&self) -> std::result::Result<(), String> {
+    let mut state = self.lock();
+    if state.draining {
+      return Err("Executor is draining".to_string());
+    }
+    state.count += 1;
+    Ok(())
+  }
+
+  /// A counted task finished, was dropped unexecuted or never made it into a queue
+  pub fn end(&self) {
+    let mut state = self.lock();
+    state.count = state.count.saturating_sub(1);
+    if state.count == 0 {
+      drop(state);
+      self.idle.notify_all();
+    }
+  }
+
+  /// `end` once the returned guard is dropped, however the worker's iteration ends
+  pub fn ending(&self) -> Ending<'_> {
+    Ending(self)
+  }
+
+  /// Reject every later submission
+  pub fn start_draining(&self) {
+    self.lock().draining = true;
+  }
+
+  /// Wake every waiter for good; called on shutdown
+  pub fn close(&self) {
+    let mut state = self.lock();
+    state.draining = true;
+    state.closed = true;
+    drop(state);
+    self.idle.notify_all();
+  }
+
+  /// Wait until no task is in flight, up to `timeout` (forever if `None`).
+  /// False if the deadline passed or the executor shut down first.
+  pub fn wait_idle(&self, timeout: Option<Duration>) -> bool {
+    let deadline = timeout.map(|t| Instant::now() + t);
+    let mut state = self.lock();
+
+    loop {
+      if state.count == 0 {
+        return true;
+      }
+      if state.closed {
+        return false;
+      }
+
+      state = match deadline {
+        Some(deadline) => {
+          let remaining = deadline.saturating_duration_since(Instant::now());
+          if remaining.is_zero() {
+            return false;
+          }
+          self.idle
+            .wait_timeout(state, remaining)
+            .map(|(guard, _)| guard)
+            .unwrap_or_else(|poisoned| poisoned.into_inner().0)
+        }
+        None => self.idle.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner()),
+      };
+    }
+  }
+}
+
+/// Guard returned by `InFlight::ending`
+pub struct Ending<'a>(&'a InFlight);
+
+impl Drop for Ending<'_> {
+  fn drop(&mut self) {
+    self.0.end();
+  }
+}
+
+/// Waits for the executor to go idle; off the JS thread when run as an `AsyncTask`
+pub struct IdleWait {
+  in_flight: Arc<InFlight>,
+  timeout: Option<Duration>,
+}
+
+impl IdleWait {
+  pub fn new(in_flight: Arc<InFlight>, timeout_ms: Option<u32>) -> Self {
+    Self {
+      in_flight,
+      timeout: timeout_ms.map(|t| Duration::from_millis(t as u64)),
+    }
+  }
+
+  /// Block until every task has finished (true) or the wait gives up (false)
+  pub fn wait(&self) -> bool {
+    self.in_flight.wait_idle(self.timeout)
+  }
+}
+
+#[cfg(feature = "node")]
+impl Task for IdleWait {
+  type Output = bool;
+  type JsValue = bool;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(self.wait())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
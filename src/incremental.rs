@@ -0,0 +1,236 @@
+// src/incremental.rs - Aggregates kept up to date from deltas
+//
+// A registered function with the `aggregate` hint can maintain named views of
+// its result over an array that changes over time. Rather than resubmitting
+// the whole array, `submit_delta` sends only the elements added, removed or
+// changed, and a worker folds them into the view's previous result. Sums,
+// counts and group-by counts all have inverses, so a removal costs as little
+// as an addition.
+use serde::Deserialize;
+use std::collections::{ BTreeMap, HashMap };
+
+use crate::number_format::NumberFormat;
+use crate::sync::{ Mutex, MutexGuard, lock_recovering };
+
+/// An aggregate that can be updated from a delta, from the `aggregate` hint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Aggregation {
+  /// Sum of numeric elements
+  Sum,
+  /// Number of elements
+  Count,
+  /// Number of elements per value of a field
+  GroupBy(String),
+}
+
+impl Aggregation {
+  /// "sum", "count" or "group_by:<field>"
+  pub fn parse(value: &str) -> Result<Self, String> {
+    match value {
+      "sum" => Ok(Aggregation::Sum),
+      "count" => Ok(Aggregation::Count),
+      _ =>
+        match value.strip_prefix("group_by:") {
+          Some(field) if !field.is_empty() => Ok(Aggregation::GroupBy(field.to_string())),
+          _ =>
+            Err(
+              format!("Unknown aggregate '{}', expected sum, count or group_by:<field>", value)
+            ),
+        }
+    }
+  }
+}
+
+/// Elements that changed since the previous delta; `changed` pairs count as a
+/// removal of `before` and an addition of `after`
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Delta {
+  #[serde(default)]
+  added: Vec<serde_json::Value>,
+  #[serde(default)]
+  removed: Vec<serde_json::Value>,
+  #[serde(default)]
+  changed: Vec<Change>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Change {
+  before: serde_json::Value,
+  after: serde_json::Value,
+}
+
+impl Delta {
+  fn parse(delta: &str) -> Result<Self, String> {
+    serde_json::from_str(delta).map_err(|e| format!("Invalid delta: {}", e))
+  }
+
+  fn added(&self) -> impl Iterator<Item = &serde_json::Value> {
+    self.added.iter().chain(self.changed.iter().map(|change| &change.after))
+  }
+
+  fn removed(&self) -> impl Iterator<Item = &serde_json::Value> {
+    self.removed.iter().chain(self.changed.iter().map(|change| &change.before))
+  }
+}
+
+#[derive(Debug)]
+enum Totals {
+  // Non-integer elements are counted so a sum of integers prints as one
+  Sum {
+    total: f64,
+    fractional: usize,
+  },
+  Count(usize),
+  GroupBy(BTreeMap<String, usize>),
+}
+
+impl Totals {
+  fn new(aggregation: &Aggregation) -> Self {
+    match aggregation {
+      Aggregation::Sum => Totals::Sum { total: 0.0, fractional: 0 },
+      Aggregation::Count => Totals::Count(0),
+      Aggregation::GroupBy(_) => Totals::GroupBy(BTreeMap::new()),
+    }
+  }
+
+  /// Fold `delta` in, leaving the totals untouched if any of it is invalid
+  fn apply(&mut self, aggregation: &Aggregation, delta: &Delta) -> Result<(), String> {
+    match (self, aggregation) {
+      (Totals::Sum { total, fractional }, Aggregation::Sum) => {
+        let (added, added_fractional) = sum(delta.added())?;
+        let (removed, removed_fractional) = sum(delta.removed())?;
+        *fractional = (*fractional + added_fractional)
+          .checked_sub(removed_fractional)
+          .ok_or("Delta removes elements the view doesn't hold")?;
+        *total += added - removed;
+      }
+      (Totals::Count(count), Aggregation::Count) => {
+        let added = delta.added().count();
+        let removed = delta.removed().count();
+        *count = (*count + added)
+          .checked_sub(removed)
+          .ok_or("Delta removes more elements than the view holds")?;
+      }
+      (Totals::GroupBy(counts), Aggregation::GroupBy(field)) => {
+        let mut net: BTreeMap<String, isize> = BTreeMap::new();
+        for value in delta.added() {
+          *net.entry(group_key(value, field)?).or_default() += 1;
+        }
+        for value in delta.removed() {
+          *net.entry(group_key(value, field)?).or_default() -= 1;
+        }
+        let mut updated = Vec::with_capacity(net.len());
+        for (key, change) in net {
+          let current = counts.get(&key).copied().unwrap_or(0);
+          let count = current
+            .checked_add_signed(change)
+            .ok_or_else(|| format!("Delta removes more '{}' elements than the view holds", key))?;
+          updated.push((key, count));
+        }
+        for (key, count) in updated {
+          if count == 0 {
+            counts.remove(&key);
+          } else {
+            counts.insert(key, count);
+          }
+        }
+      }
+      _ => {
+        return Err("View was built with a different aggregate".to_string());
+      }
+    }
+    Ok(())
+  }
+
+  fn render(&self, format: &NumberFormat) -> Result<String, String> {
+    match self {
+      Totals::Sum { total, fractional } => Ok(format.format(*total, *fractional == 0)),
+      Totals::Count(count) => Ok(count.to_string()),
+      Totals::GroupBy(counts) => serde_json::to_string(counts).map_err(|e| e.to_string()),
+    }
+  }
+}
+
+/// Sum of `values`, with how many of them aren't integers
+fn sum<'a>(values: impl Iterator<Item = &'a serde_json::Value>) -> Result<(f64, usize), String> {
+  let mut total = 0.0;
+  let mut fractional = 0;
+  for value in values {
+    let number = value.as_f64().ok_or_else(|| format!("Can't sum non-number {}", value))?;
+    if !(value.is_i64() || value.is_u64()) {
+      fractional += 1;
+    }
+    total += number;
+  }
+  Ok((total, fractional))
+}
+
+/// Group of an object element: its `field` value, strings unquoted
+fn group_key(value: &serde_json::Value, field: &str) -> Result<String, String> {
+  match value.get(field) {
+    Some(serde_json::Value::String(key)) => Ok(key.clone()),
+    Some(key) => Ok(key.to_string()),
+    None => Err(format!("Element {} has no '{}' field", value, field)),
+  }
+}
+
+#[derive(Debug)]
+struct View {
+  // Registered function key (`name@version`) the view belongs to
+  function: String,
+  totals: Totals,
+}
+
+/// Named incremental views. Every supported aggregate commutes, so a view ends
+/// up the same whichever order workers apply its deltas in.
+#[derive(Debug, Default)]
+pub struct IncrementalViews {
+  views: Mutex<HashMap<String, View>>,
+}
+
+impl IncrementalViews {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  fn lock(&self) -> MutexGuard<'_, HashMap<String, View>> {
+    lock_recovering(&self.views, || {})
+  }
+
+  /// Fold `delta` into `view`, created empty on its first delta, and return the
+  /// updated aggregate. A view only takes deltas for the function that created it.
+  pub fn apply(
+    &self,
+    view: &str,
+    function: &str,
+    aggregation: &Aggregation,
+    delta: &str,
+    format: &NumberFormat
+  ) -> Result<String, String> {
+    let delta = Delta::parse(delta)?;
+    let mut views = self.lock();
+    match views.get_mut(view) {
+      Some(entry) if entry.function != function =>
+        Err(format!("View '{}' belongs to '{}', not '{}'", view, entry.function, function)),
+      Some(entry) => {
+        entry.totals.apply(aggregation, &delta)?;
+        entry.totals.render(format)
+      }
+      // Only a delta that applies cleanly creates the view
+      None => {
+        let mut totals = Totals::new(aggregation);
+        totals.apply(aggregation, &delta)?;
+        let rendered = totals.render(format);
+        views.insert(view.to_string(), View { function: function.to_string(), totals });
+        rendered
+      }
+    }
+  }
+
+  /// Forget `view`, so its next delta starts from empty. False if there was none.
+  pub fn remove(&self, view: &str) -> bool {
+    self.lock().remove(view).is_some()
+  }
+}
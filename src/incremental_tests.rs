@@ -0,0 +1,62 @@
+// src/incremental_tests.rs - Incremental views folding deltas into aggregates
+use crate::incremental::{ Aggregation, IncrementalViews };
+use crate::number_format::NumberFormat;
+
+fn apply(views: &IncrementalViews, aggregate: &str, delta: &str) -> Result<String, String> {
+  let aggregation = Aggregation::parse(aggregate).expect("aggregate");
+  views.apply("totals", "report@1.0.0", &aggregation, delta, &NumberFormat::default())
+}
+
+#[test]
+fn sums_and_counts_follow_their_deltas() {
+  let sums = IncrementalViews::new();
+  assert_eq!(apply(&sums, "sum", r#"{"added": [1, 2, 3]}"#).unwrap(), "6");
+  let changed = r#"{"removed": [1], "changed": [{"before": 2, "after": 20}]}"#;
+  assert_eq!(apply(&sums, "sum", changed).unwrap(), "23");
+  assert_eq!(apply(&sums, "sum", r#"{"added": [0.5]}"#).unwrap(), "23.5");
+
+  let counts = IncrementalViews::new();
+  assert_eq!(apply(&counts, "count", r#"{"added": ["a", "b", "c"]}"#).unwrap(), "3");
+  assert_eq!(apply(&counts, "count", r#"{"removed": ["a"]}"#).unwrap(), "2");
+  assert_eq!(
+    apply(&counts, "count", r#"{"removed": ["b", "c", "d"]}"#).unwrap_err(),
+    "Delta removes more elements than the view holds"
+  );
+  // The refused delta left the count as it was
+  assert_eq!(apply(&counts, "count", "{}").unwrap(), "2");
+}
+
+#[test]
+fn group_by_counts_move_between_groups() {
+  let views = IncrementalViews::new();
+  let added = r#"{"added": [{"team": "red"}, {"team": "blue"}, {"team": "red"}]}"#;
+  assert_eq!(apply(&views, "group_by:team", added).unwrap(), r#"{"blue":1,"red":2}"#);
+  let moved = r#"{"changed": [{"before": {"team": "blue"}, "after": {"team": "green"}}]}"#;
+  // Emptied groups drop out
+  assert_eq!(apply(&views, "group_by:team", moved).unwrap(), r#"{"green":1,"red":2}"#);
+  assert_eq!(
+    apply(&views, "group_by:team", r#"{"added": [{"name": "x"}]}"#).unwrap_err(),
+    r#"Element {"name":"x"} has no 'team' field"#
+  );
+}
+
+#[test]
+fn views_belong_to_one_function_until_dropped() {
+  let views = IncrementalViews::new();
+  apply(&views, "sum", r#"{"added": [5]}"#).unwrap();
+  let other = views.apply("totals", "audit@1.0.0", &Aggregation::Count, "{}", &Default::default());
+  assert_eq!(other.unwrap_err(), "View 'totals' belongs to 'report@1.0.0', not 'audit@1.0.0'");
+  let mismatched = apply(&views, "count", "{}").unwrap_err();
+  assert_eq!(mismatched, "View was built with a different aggregate");
+
+  assert!(views.remove("totals"));
+  assert!(!views.remove("totals"));
+  assert_eq!(apply(&views, "sum", r#"{"added": [1]}"#).unwrap(), "1");
+
+  assert!(apply(&views, "sum", r#"{"inserted": [1]}"#).unwrap_err().starts_with("Invalid delta"));
+  assert_eq!(
+    Aggregation::parse("max").unwrap_err(),
+    "Unknown aggregate 'max', expected sum, count or group_by:<field>"
+  );
+  assert!(Aggregation::parse("group_by:").is_err());
+}
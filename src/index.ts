@@ -10,14 +10,17 @@
 // Core functional implementation
 export {threader, cache, benchmark} from './threader'
 export {thread} from './thread'
+export {actor} from './actor'
 
 // Default export for convenience
 import {threader} from './threader'
 import {thread} from './thread'
+import {actor} from './actor'
 
 export default {
   threader,
-  thread
+  thread,
+  actor
 }
 
 // ============================================================================
@@ -33,6 +36,8 @@ export type {
   ThreadConfig
 } from './types'
 
+export type {ActorHandler, ActorOptions} from './actor'
+
 // Legacy type exports for compatibility
 export type {ThreadResult as ThreadController} from './types'
 
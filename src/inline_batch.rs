@@ -0,0 +1,51 @@
+// src/inline_batch.rs - Small batches answered in one call (`execute_inline_batch`)
+//
+// A request handler mapping a function over a handful of values wants the
+// answers, not task IDs to collect. The batch runs on the calling thread when
+// it's cheap enough that queueing would cost more than the work, and on the
+// pool otherwise, with the call blocking until every item is done. The cost is
+// estimated from how long the function last took, or from the items' size
+// until it has been measured.
+use serde_json::Value;
+
+use crate::{ OptimizationHints, OptimizedWorkerResult };
+use crate::task_spec;
+
+/// Estimated run time a batch may take on the calling thread unless the config
+/// says otherwise
+pub const DEFAULT_BUDGET_MS: u32 = 1;
+
+// Rough native throughput on item data not yet measured
+const BYTES_PER_MS: f64 = 64.0 * 1024.0;
+
+/// Hints for a batch that sets none: as for a task spec's tasks
+pub fn default_hints(function_code: &str) -> Result<OptimizationHints, String> {
+  task_spec::resolve_hints(function_code, &[])
+}
+
+/// Milliseconds `items` tasks holding `bytes` of data in all should take, given
+/// the function's last measured duration, if any
+pub fn estimated_ms(items: usize, bytes: usize, measured_ms: Option<u64>) -> f64 {
+  let measured = (items as f64) * (measured_ms.unwrap_or(0) as f64);
+  measured.max((bytes as f64) / BYTES_PER_MS)
+}
+
+/// Each item's result, parsed from its JSON text, in order; or the first item
+/// that failed or never produced a result
+pub fn values(results: Vec<Option<OptimizedWorkerResult>>) -> Result<Vec<Value>, String> {
+  results
+    .into_iter()
+    .enumerate()
+    .map(|(item, result)| {
+      let result = result.ok_or_else(|| format!("Item {}: cancelled before it finished", item))?;
+      if let Some(error) = result.error {
+        return Err(format!("Item {}: {}", item, error));
+      }
+      Ok(
+        result.result.map_or(Value::Null, |text| {
+          serde_json::from_str(&text).unwrap_or(Value::String(text))
+        })
+      )
+    })
+    .collect()
+}
@@ -0,0 +1,102 @@
+// src/legacy.rs - The string-based API, kept for callers still migrating off it
+//
+// The first version of the executor took hints as JSON text, handed results
+// back as JSON text in arrival order, whoever's they were, and reported stats
+// the same way. Hints and results are typed objects now, so the calls that
+// took or returned JSON keep their old shape under new names, and existing
+// users can migrate one call site at a time:
+//
+//   submitOptimizedTaskJson    submitOptimizedTask, with a hints object
+//   getOptimizedResultJson     getResultById, or runTask
+//   getOptimizedResult         getResultById, or runTask
+//   getOptimizedBatchResults   getResultById for each ID, or streamBatchResults
+//   getOptimizationStats       getStatsObject
+//
+// The JSON is what it always was: hints and results with snake_case fields.
+// The first call of each on an executor emits a `deprecated_call` event naming
+// its replacement, so what's left to migrate shows up in `drain_events` and
+// the logs.
+use std::sync::atomic::{ AtomicBool, Ordering };
+
+use crate::events::EventLog;
+use crate::{ OptimizationHints, OptimizedWorkerResult };
+
+/// A deprecated call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyCall {
+  OptimizedTaskJson,
+  OptimizedResultJson,
+  OptimizedResult,
+  OptimizedBatchResults,
+  OptimizationStats,
+}
+
+impl LegacyCall {
+  const ALL: [LegacyCall; 5] = [
+    LegacyCall::OptimizedTaskJson,
+    LegacyCall::OptimizedResultJson,
+    LegacyCall::OptimizedResult,
+    LegacyCall::OptimizedBatchResults,
+    LegacyCall::OptimizationStats,
+  ];
+
+  pub fn name(self) -> &'static str {
+    match self {
+      LegacyCall::OptimizedTaskJson => "submitOptimizedTaskJson",
+      LegacyCall::OptimizedResultJson => "getOptimizedResultJson",
+      LegacyCall::OptimizedResult => "getOptimizedResult",
+      LegacyCall::OptimizedBatchResults => "getOptimizedBatchResults",
+      LegacyCall::OptimizationStats => "getOptimizationStats",
+    }
+  }
+
+  /// What to call instead
+  pub fn replacement(self) -> &'static str {
+    match self {
+      LegacyCall::OptimizedTaskJson => "submitOptimizedTask with a hints object",
+      LegacyCall::OptimizedResultJson | LegacyCall::OptimizedResult => {
+        "getResultById, or runTask"
+      }
+      LegacyCall::OptimizedBatchResults => "getResultById for each ID, or streamBatchResults",
+      LegacyCall::OptimizationStats => "getStatsObject",
+    }
+  }
+}
+
+/// Which deprecated calls an executor has reported
+#[derive(Debug, Default)]
+pub struct Deprecations {
+  reported: [AtomicBool; LegacyCall::ALL.len()],
+}
+
+impl Deprecations {
+  /// Emit a `deprecated_call` event if this is the executor's first `call`
+  pub fn report(&self, call: LegacyCall, events: &EventLog) {
+    let index = LegacyCall::ALL.iter().position(|known| *known == call).unwrap_or_default();
+    if self.reported[index].swap(true, Ordering::AcqRel) {
+      return;
+    }
+    events.emit(
+      "deprecated_call",
+      None,
+      format!("{} is deprecated; use {}", call.name(), call.replacement())
+    );
+  }
+}
+
+/// Hints given as JSON text
+pub fn parse_hints(hints: &str) -> Result<OptimizationHints, String> {
+  serde_json::from_str(hints).map_err(|e| format!("Invalid optimization hints: {}", e))
+}
+
+/// Task metadata given as JSON text, echoed back in the result as is
+pub fn parse_meta(meta: Option<&str>) -> Result<Option<serde_json::Value>, String> {
+  meta
+    .map(|meta| serde_json::from_str(meta).map_err(|e| format!("Invalid task meta: {}", e)))
+    .transpose()
+}
+
+/// A result as JSON text
+pub fn result_json(result: &OptimizedWorkerResult) -> Result<String, String> {
+  serde_json::to_string(result).map_err(|e| format!("Failed to serialize result: {}", e))
+}
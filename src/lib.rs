@@ -1,14 +1,214 @@
 // src/lib.rs - Enhanced multi-core backend with optimization support
 #![deny(clippy::all)]
 
-use napi_derive::napi;
-use napi::Result;
-use std::sync::{ Arc, Mutex };
-use std::thread;
+// `catch_native_panic` and worker supervision both rely on panics unwinding
+#[cfg(panic = "abort")]
+compile_error!("threader must be built with panic = \"unwind\"");
+
+use bindings::{ Error, Result, Status, napi };
+#[cfg(feature = "node")]
+use napi::bindgen_prelude::{ AsyncTask, Buffer };
+#[cfg(feature = "node")]
+use napi::threadsafe_function::{
+  ErrorStrategy,
+  ThreadSafeCallContext,
+  ThreadsafeFunction,
+  ThreadsafeFunctionCallMode,
+};
+#[cfg(feature = "node")]
+use napi::{ Env, JsFunction, JsObject };
+use sync::{ Arc, Mutex, MutexGuard, thread };
 use crossbeam::channel;
+use rayon::prelude::*;
 use serde::{ Deserialize, Serialize };
+use std::borrow::Cow;
+use std::ops::ControlFlow;
+use std::panic::{ self, AssertUnwindSafe };
 use std::time::{ Duration, Instant };
 use std::collections::HashMap;
+use std::sync::atomic::{ AtomicU64, Ordering };
+
+#[cfg(feature = "node")]
+pub mod abort;
+#[cfg(feature = "node")]
+pub mod async_context;
+pub mod autoscale;
+pub mod background;
+pub mod batch_split;
+#[cfg(all(test, not(feature = "loom")))]
+mod batch_split_tests;
+pub mod binary;
+pub mod bindings;
+#[cfg(all(test, not(feature = "node"), not(feature = "loom")))]
+mod bindings_tests;
+pub mod borrowed;
+pub mod cancellation;
+pub mod canonical_json;
+#[cfg(all(test, not(feature = "loom")))]
+mod canonical_json_tests;
+pub mod child;
+pub mod chunked;
+pub mod clock;
+pub mod columnar;
+#[cfg(all(test, not(feature = "loom")))]
+mod columnar_tests;
+pub mod concurrency_limit;
+pub mod cpu_budget;
+#[cfg(all(test, not(feature = "loom")))]
+mod cpu_budget_tests;
+pub mod dedup;
+pub mod discipline;
+pub mod events;
+pub mod executor_config;
+#[cfg(all(test, not(feature = "loom")))]
+mod executor_config_tests;
+pub mod executor_registry;
+#[cfg(all(test, not(feature = "loom")))]
+mod executor_registry_tests;
+// Model checks use loom's primitives, which only work inside `loom::model`
+#[cfg(all(test, not(feature = "loom")))]
+mod executor_tests;
+pub mod fallback;
+pub mod fault_injection;
+pub mod function_registry;
+pub mod health;
+pub mod host_info;
+#[cfg(all(test, not(feature = "loom")))]
+mod host_info_tests;
+pub mod idempotency;
+pub mod in_flight;
+pub mod incremental;
+#[cfg(all(test, not(feature = "loom")))]
+mod incremental_tests;
+pub mod inline_batch;
+pub mod legacy;
+pub mod locale;
+#[cfg(all(test, not(feature = "loom")))]
+mod locale_tests;
+#[cfg(all(test, feature = "loom"))]
+mod loom_models;
+pub mod memory;
+#[cfg(feature = "node")]
+pub mod mock_executor;
+#[cfg(all(test, feature = "node", not(feature = "loom")))]
+mod mock_executor_tests;
+pub mod number_format;
+pub mod operation_pools;
+#[cfg(all(test, not(feature = "loom")))]
+mod operation_pools_tests;
+pub mod priority;
+pub mod protocol;
+#[cfg(all(test, not(feature = "loom")))]
+mod protocol_tests;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod rate_limit;
+pub mod receipt;
+pub mod reservation;
+pub mod resize;
+pub mod result_router;
+#[cfg(all(test, not(feature = "loom")))]
+mod result_router_tests;
+pub mod routing;
+pub mod scratch;
+#[cfg(all(test, not(feature = "loom")))]
+mod scratch_tests;
+#[cfg(feature = "node")]
+pub mod run_task;
+#[cfg(all(test, feature = "node", not(feature = "loom")))]
+mod run_task_tests;
+pub mod self_test;
+#[cfg(all(test, not(feature = "loom")))]
+mod self_test_tests;
+pub mod snapshot;
+pub mod soak;
+pub mod starvation;
+pub mod statsd;
+pub mod strict;
+#[cfg(all(test, not(feature = "loom")))]
+mod strict_tests;
+pub mod sub_batch;
+#[cfg(all(test, not(feature = "loom")))]
+mod sub_batch_tests;
+#[cfg(feature = "node")]
+pub mod stream_batch;
+pub mod supervisor;
+pub mod sync;
+#[cfg(feature = "node")]
+pub mod task_api;
+pub mod task_executor;
+#[cfg(all(test, not(feature = "loom")))]
+mod task_executor_tests;
+pub mod task_graph;
+pub mod task_groups;
+pub mod task_spec;
+#[cfg(all(test, not(feature = "loom")))]
+mod task_spec_tests;
+pub mod task_status;
+pub mod thread_tuning;
+pub mod throttle;
+#[cfg(all(test, not(feature = "loom")))]
+mod throttle_tests;
+pub mod timer_wheel;
+pub mod typed_result;
+#[cfg(all(test, not(feature = "loom")))]
+mod typed_result_tests;
+pub mod watchdog;
+pub mod worker_pool;
+
+use autoscale::{ AutoscaleConfig, Autoscaler, Load };
+use background::{ BackgroundPasses, BackgroundStats, CompiledFunction };
+use binary::{ FastPathOutput, ResultBytes };
+#[cfg(feature = "node")]
+use borrowed::JsBytes;
+use borrowed::{ BorrowTable, BorrowedData, SharedBytes };
+use cancellation::CancellationSet;
+use child::{ ChildError, ChildOptions, ChildStatus, ChildTable, ChildTask };
+use chunked::PartialResult;
+use clock::{ SharedClock, SystemClock };
+use columnar::{ ColumnarRow, Kernel };
+use concurrency_limit::ConcurrencyLimits;
+use cpu_budget::CpuBudget;
+use dedup::DedupTable;
+use discipline::SharedDiscipline;
+use events::EventLog;
+use executor_config::{ ExecutorConfig, IdleStrategy, OverflowPolicy, Scheduling };
+use fallback::{ FallbackAttempt, FallbackConfig, Fallbacks };
+use fault_injection::{ FaultConfig, FaultInjector, WorkerFault };
+use receipt::ExecutionReceipt;
+use reservation::ReservationTable;
+use resize::{ Resizer, SpawnWorker, WorkerSlots, WorkerThreads };
+use function_registry::{ FunctionRegistry, RegisteredFunction };
+use health::HealthCheck;
+use idempotency::IdempotencyTable;
+use in_flight::{ IdleWait, InFlight };
+use incremental::{ Aggregation, IncrementalViews };
+use legacy::{ Deprecations, LegacyCall };
+use memory::{ MemoryUsage, MemoryWatermark, PressureListener, Pressure };
+use number_format::NumberFormat;
+use protocol::{ Negotiated, ProtocolAgreement, ProtocolSupport };
+use priority::{ Doorbell, LaneReceiver, LaneSender, Priority, Requeue, ShedReason, WorkerLanes };
+use rate_limit::{ RateKey, RateLimitConfig, RateLimits };
+use result_router::{ ResultRouter, RouterError };
+use self_test::SelfTestReport;
+use routing::{ RoutingBackend, RoutingTable };
+#[cfg(feature = "node")]
+use run_task::RunTask;
+use starvation::StarvationMonitor;
+use statsd::{ Metric, StatsdConfig, StatsdReporter };
+#[cfg(feature = "node")]
+use stream_batch::StreamBatch;
+use snapshot::{ CacheEntry, ExecutorState, QueuedTask, StateImport };
+use sub_batch::{ Pass, SubBatchId, SubBatchTuner };
+use supervisor::{ Heartbeats, Supervisor, panic_cause };
+use task_status::{ QueueId, QueueStats, TaskStatus, TaskTracker };
+use throttle::ThrottleMonitor;
+use timer_wheel::TimerWheel;
+use task_graph::{ Compensate, GraphNode, GraphTable };
+use task_spec::TaskSpec;
+use task_groups::{ GroupStatus, GroupTable, GroupWait, Membership };
+use typed_result::TypedResult;
+use watchdog::{ Deadlines, Watchdog };
 
 /// Enhanced task with optimization metadata
 #[derive(Debug, Clone)]
@@ -16,11 +216,90 @@ pub struct OptimizedWorkerTask {
   pub id: String,
   pub function_code: String,
   pub data: String,
-  pub timeout_ms: Option<u64>,
+  pub timeout_ms: Option<u64>, // Execution budget; overruns are reported as they happen
   pub optimization_hints: OptimizationHints,
+  pub meta: Option<serde_json::Value>, // Opaque caller metadata, echoed back in the result
+  pub reply: Reply, // Where the worker sends the result
+  pub epoch: u64, // Cancellation epoch at submission (see `CancellationSet`)
+  pub view: Option<String>, // Incremental view the task's delta updates (see `submit_delta`)
+  pub columnar: Option<ColumnarRow>, // Row of a batch computed a column at a time
+  pub borrowed: Option<BorrowedData>, // Caller's bytes, read instead of `data` (`submit_borrowed`)
+  pub expires_at: Option<Instant>, // Stale unless started by then (see `max_queue_age_ms`)
+  pub worker: Option<usize>, // Worker whose inbox a batch split or sub-batch sent it to
+  pub sub_batch: Option<SubBatchId>, // Sub-batch run in one pass (see `sub_batch`)
+}
+
+impl OptimizedWorkerTask {
+  /// The task's input: `data`, or the bytes it borrowed
+  pub fn input(&self) -> std::result::Result<&str, String> {
+    match self.borrowed.as_ref() {
+      Some(borrowed) => borrowed.as_str(),
+      None => Ok(&self.data),
+    }
+  }
+
+  /// Claim borrowed input for the worker about to run the task; `false` if the
+  /// caller released it first
+  fn claim_input(&self) -> bool {
+    self.borrowed.as_ref().is_none_or(BorrowedData::claim)
+  }
+
+  /// Whether the task waited past its `max_queue_age_ms` before starting
+  fn expired(&self, now: Instant) -> bool {
+    self.expires_at.is_some_and(|expires_at| now > expires_at)
+  }
+}
+
+/// Destination of a task's result
+#[derive(Debug, Clone, Default)]
+pub enum Reply {
+  /// The shared result queue, collected by task ID
+  #[default]
+  Shared,
+  /// A per-task channel (`run_task`)
+  Channel(channel::Sender<OptimizedWorkerResult>),
+  /// Nowhere (`submit_fire`)
+  Discard,
+}
+
+impl Reply {
+  fn send(self, result: OptimizedWorkerResult, shared: &ResultSink) {
+    match self {
+      Reply::Shared => shared.send(result),
+      Reply::Channel(reply) => {
+        let _ = reply.send(result);
+      }
+      Reply::Discard => {}
+    }
+  }
+}
+
+/// Hands results for the shared queue to the router on the thread that
+/// produced them, over the router's lock-free channel
+#[derive(Clone)]
+struct ResultSink {
+  results: Arc<ResultRouter>,
+  events: Arc<EventLog>,
+  protocol: Arc<Negotiated>,
+  idempotency: Arc<IdempotencyTable>,
+}
+
+impl ResultSink {
+  fn send(&self, mut result: OptimizedWorkerResult) {
+    self.protocol.adapt(&mut result);
+    self.idempotency.completed(&result);
+    if let Some(evicted) = self.results.deliver(result) {
+      self.events.emit(
+        "result_evicted",
+        None,
+        format!("Dropped uncollected result {} to stay within result_buffer_limit", evicted)
+      );
+    }
+  }
 }
 
 /// Optimization hints from the preparation phase
+#[napi(object)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptimizationHints {
   pub operation_type: String, // "mathematical", "string_operations", etc.
@@ -28,24 +307,108 @@ pub struct OptimizationHints {
   pub expected_cores: u32,
   pub should_use_rust: bool,
   pub is_hot_function: bool,
-  pub estimated_memory: u64,
-  pub batch_size_hint: Option<u32>,
+  pub estimated_memory: i64,
+  pub batch_size_hint: Option<u32>, // Sub-batch size a batch starts from (see `sub_batch`)
   pub function_hash: String,
+  pub include_receipt: Option<bool>, // Attach an `ExecutionReceipt` to the result
+  pub deterministic: Option<bool>, // Fixed reduction order: bit-identical across runs and pools
+  pub output_precision: Option<u32>, // Decimal places kept in numeric results
+  pub rounding: Option<String>, // "half_up" (default), "half_even", "floor", "ceil", "trunc"
+  pub locale: Option<String>, // BCP 47 tag for toLocale*/localeCompare (needs the `icu` feature)
+  pub canonical_json: Option<bool>, // Sorted keys and fixed number formatting, for hashing results
+  pub priority: Option<String>, // "low", "normal" (default), "high" or "critical"
+  pub typed_result: Option<bool>, // Attach the result decoded as a `TypedResult`
+  pub group: Option<String>, // Join this task group (see `seal_group`)
+  pub after: Option<String>, // Hold the task until this task group completes
+  pub chunk_size: Option<u32>, // Map over an array input this many elements at a time
+  pub tenant: Option<String>, // Caller the task is run for, for per-tenant rate limits
+  pub aggregate: Option<String>, // "sum", "count" or "group_by:<field>", for `submit_delta`
+  pub max_queue_age_ms: Option<u32>, // Fail unrun if not started this many ms after submission
+  pub dedupe: Option<bool>, // Share the result of an identical task already queued or running
+  pub child: Option<String>, // ID from `open_child`; the task counts against that child's budget
+  pub idempotency_key: Option<String>, // Return the task already submitted with this key, if any
 }
 
 /// Enhanced result with optimization metadata
+#[napi(object)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptimizedWorkerResult {
   pub id: String,
   pub result: Option<String>,
   pub error: Option<String>,
-  pub duration_ms: u64,
-  pub worker_id: usize,
+  pub duration_ms: f64,
+  pub worker_id: u32,
   pub execution_type: String,
   pub optimization_used: String, // "rust_native", "rust_optimized", "needs_js_worker"
   pub cache_hit: bool,
+  #[serde(default)]
+  pub timed_out: bool, // Set when `error` is a timeout rather than an execution failure
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub meta: Option<serde_json::Value>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub receipt: Option<ExecutionReceipt>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub typed: Option<TypedResult>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub binary: Option<ResultBytes>, // Raw bytes of a "binary_operations" task, instead of `result`
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub fallback: Option<FallbackAttempt>, // Both attempts, when the function's fallback ran
+}
+
+/// Typed snapshot of optimization statistics
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct ExecutorStats {
+  pub rust_native_hits: i64,
+  pub js_worker_fallbacks: i64,
+  pub cache_hits: i64,
+  pub total_executions: i64,
+  pub binary_results: i64,
+  pub binary_bytes: i64,
+  pub avg_latency_ms: f64,
+  pub throughput_tasks_per_sec: f64,
+  pub optimization_ratio: f64,
+  pub cache_hit_ratio: f64,
+}
+
+/// Learning-state entry for a single function hash
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct FunctionProfile {
+  pub function_hash: String,
+  pub execution_count: u32,
+  pub last_latency_ms: Option<f64>,
+  pub last_throughput: Option<f64>,
+  pub optimization_type: Option<String>,
+  pub last_success: Option<bool>,
+}
+
+/// Blue/green state of a registered function
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct RolloutStatus {
+  pub name: String,
+  /// Latest registered version, if any
+  pub stable: Option<FunctionProfile>,
+  /// Staged candidate, if a rollout is in progress
+  pub staged: Option<FunctionProfile>,
+  /// Fraction of matching submissions routed to the staged version
+  pub traffic_fraction: f64,
 }
 
+/// Arrays at least this long are reduced in parallel
+pub(crate) const PARALLEL_REDUCTION_THRESHOLD: usize = 16_384;
+
+/// Chunk size for deterministic reductions; fixed so results never depend on the pool size
+const DETERMINISTIC_CHUNK_SIZE: usize = 4_096;
+
+/// Longest an idle worker sleeps while a delayed or rate-limited task waits, so
+/// a clock driven by hand (`MockClock`) is never far behind
+const MAX_IDLE_WAIT: Duration = Duration::from_millis(100);
+
+/// Longest `shutdown` waits for busy workers to finish their current task
+const WORKER_EXIT_GRACE: Duration = Duration::from_secs(1);
+
 /// Performance tracking for optimization learning
 #[derive(Debug, Clone)]
 struct PerformanceMetric {
@@ -56,19 +419,232 @@ struct PerformanceMetric {
   success: bool,
 }
 
+/// What running a task produced, before it is packaged as the task's result
+struct Outcome {
+  result: std::result::Result<FastPathOutput, String>,
+  optimization_used: String,
+  duration: u64,
+  worker_id: usize,
+  cache_hit: bool,
+  timed_out: bool,
+  fallback: Option<FallbackAttempt>,
+}
+
+impl Outcome {
+  /// The task's result, with the receipt or typed view it asked for
+  fn into_result(self, task: OptimizedWorkerTask, seed: u64) -> OptimizedWorkerResult {
+    let hints = &task.optimization_hints;
+    let receipt = hints.include_receipt.unwrap_or(false).then(|| {
+      ExecutionReceipt::new(
+        &hints.function_hash,
+        &task.function_code,
+        task.input().unwrap_or_default(),
+        &self.optimization_used,
+        seed
+      )
+    });
+
+    let (result_ok, binary, result_err) = match self.result {
+      Ok(FastPathOutput::Json(val)) => (Some(val), None, None),
+      Ok(FastPathOutput::Bytes(bytes)) => (None, Some(ResultBytes(bytes)), None),
+      Err(err) => (None, None, Some(err)),
+    };
+    let typed = result_ok
+      .as_deref()
+      .filter(|_| hints.typed_result == Some(true))
+      .map(TypedResult::decode);
+
+    OptimizedWorkerResult {
+      id: task.id,
+      result: result_ok,
+      error: result_err,
+      duration_ms: self.duration as f64,
+      worker_id: self.worker_id as u32,
+      execution_type: self.optimization_used.clone(),
+      optimization_used: self.optimization_used,
+      cache_hit: self.cache_hit,
+      timed_out: self.timed_out,
+      meta: task.meta,
+      receipt,
+      typed,
+      binary,
+      fallback: self.fallback,
+    }
+  }
+}
+
 /// Enhanced multi-core executor with optimization capabilities
 #[napi]
 pub struct OptimizedMultiCoreExecutor {
-  worker_count: usize,
-  task_sender: Arc<Mutex<Option<channel::Sender<OptimizedWorkerTask>>>>,
-  result_receiver: Arc<Mutex<Option<channel::Receiver<OptimizedWorkerResult>>>>,
-  workers_active: Arc<Mutex<bool>>,
+  // Worker slots, of which `set_worker_count` says how many run
+  slots: Arc<WorkerSlots>,
+  // Grows and shrinks the pool
+  resizer: Resizer,
+  // Worker threads not yet joined, which shutdown waits for
+  worker_threads: Arc<WorkerThreads>,
+  task_sender: Arc<Mutex<Option<LaneSender>>>,
+  results: Arc<ResultRouter>,
+  // Closing it wakes every worker to stop
+  doorbell: Doorbell,
 
   // Optimization state
   performance_cache: Arc<Mutex<HashMap<String, PerformanceMetric>>>,
   hot_functions: Arc<Mutex<HashMap<String, u32>>>,
   optimization_stats: Arc<Mutex<OptimizationStats>>,
   warmed_cores: Arc<Mutex<Vec<bool>>>,
+
+  // Time source for durations, deadlines and task IDs
+  clock: SharedClock,
+  // Next task ID number when the config set a seed; IDs come from the clock otherwise
+  seeded_ids: Option<AtomicU64>,
+
+  // Chaos mode, disabled unless configured
+  faults: Arc<FaultInjector>,
+
+  // Diagnostics drained by `drain_events`
+  events: Arc<EventLog>,
+
+  // Operator-pinned backends, applied at submission
+  routing_overrides: Mutex<RoutingTable>,
+
+  // Named, versioned functions for `submit_registered`
+  functions: Arc<Mutex<FunctionRegistry>>,
+  // Run in place of failed native executions (see `set_fallback`)
+  fallbacks: Arc<Fallbacks>,
+
+  // Tasks whose results should be dropped
+  cancellations: Arc<CancellationSet>,
+  // Lifecycle of every submitted task, for `get_task_status`
+  tasks: Arc<TaskTracker>,
+  // Unfinished tasks, for `drain` and `wait_for_idle`
+  in_flight: Arc<InFlight>,
+  // Named groups that tasks join and wait on
+  groups: Arc<GroupTable>,
+  // Graph nodes waiting on the nodes they depend on
+  graphs: Arc<GraphTable>,
+  // Running tasks that identical `dedupe` submissions wait on
+  dedup: Arc<DedupTable>,
+  // Tasks and kept results by `idempotency_key`
+  idempotency: Arc<IdempotencyTable>,
+  // Per-request budgets carved out of the pool
+  children: Arc<ChildTable>,
+  // Borrowed inputs that `release_borrowed` can still give back
+  borrows: BorrowTable,
+  // Submissions are refused above it (see `memory_watermark_mb`)
+  memory: MemoryWatermark,
+  // Per-function caps on concurrently running tasks
+  limits: Arc<ConcurrencyLimits>,
+  // Per-function and per-tenant task start rates
+  rate_limits: Arc<RateLimits>,
+  // Tasks submitted with a delay, until they come due
+  timers: Arc<TimerWheel>,
+  // Running aggregates updated by `submit_delta`
+  views: Arc<IncrementalViews>,
+  // Workers held exclusively by reserved batches
+  reservations: Arc<ReservationTable>,
+  // Optional cap on the pool's share of the machine's CPU
+  cpu_budget: Arc<CpuBudget>,
+  // Parks workers while the CPU is throttled
+  throttle: Arc<ThrottleMonitor>,
+  // Maintenance run by idle workers (see `background_optimization`)
+  background: Arc<BackgroundPasses>,
+  // Sub-batch sizes learned per function (see `batch_size_hint`)
+  sub_batches: Arc<SubBatchTuner>,
+  // Which lane each task waits in (see `queue_discipline`)
+  discipline: SharedDiscipline,
+  // Reports tasks queued too long (see `starvation_warning_ms`)
+  starvation: Option<Arc<StarvationMonitor>>,
+  // Result shapes agreed with the client (see `negotiate_protocol`)
+  protocol: Arc<Negotiated>,
+  // Delivers results for the shared queue to `results`
+  sink: ResultSink,
+  // Deprecated calls already reported
+  deprecations: Deprecations,
+  // Optional push of stats to a statsd agent
+  statsd: Mutex<Option<StatsdReporter>>,
+  // Resizes the pool with load (see `autoscale`)
+  autoscaler: Mutex<Option<Autoscaler>>,
+  // Respawns dead workers, unless `supervision_interval_ms` is 0
+  supervisor: Mutex<Option<Supervisor>>,
+  // Reports running tasks once they overrun their timeout
+  watchdog: Mutex<Option<Watchdog>>,
+  // Shared queue bound, if any
+  queue_capacity: Option<usize>,
+  // What a submission to a full queue does
+  overflow_policy: OverflowPolicy,
+  // Budget for tasks submitted without one
+  default_timeout_ms: Option<u32>,
+  // Largest data run on the submitting thread (see `inline_threshold_bytes`)
+  inline_threshold: Option<usize>,
+  // Costliest `execute_inline_batch` run on the calling thread
+  inline_batch_budget_ms: u32,
+  // Only run functions recognized exactly (see `strict`), for inline tasks
+  strict: bool,
+  // Profile-dependent settings (see `profile`)
+  scheduling: Scheduling,
+}
+
+/// Shared state handed to every worker thread
+#[derive(Clone)]
+struct WorkerContext {
+  // Shared view of the queue, to check whether work is pending and take critical
+  // tasks out of turn
+  queue_probe: LaneReceiver,
+  // Delivers results on the worker's own thread
+  result_sender: ResultSink,
+  optimization_stats: Arc<Mutex<OptimizationStats>>,
+  performance_cache: Arc<Mutex<HashMap<String, PerformanceMetric>>>,
+  hot_functions: Arc<Mutex<HashMap<String, u32>>>,
+  clock: SharedClock,
+  faults: Arc<FaultInjector>,
+  events: Arc<EventLog>,
+  cancellations: Arc<CancellationSet>,
+  // Lifecycle of every submitted task, for `get_task_status`
+  tasks: Arc<TaskTracker>,
+  // Unfinished tasks, for `drain` and `wait_for_idle`
+  in_flight: Arc<InFlight>,
+  // Running tasks with a timeout, for the watchdog
+  deadlines: Arc<Deadlines>,
+  // Named groups that tasks join and wait on
+  groups: Arc<GroupTable>,
+  // Graph nodes waiting on the nodes they depend on
+  graphs: Arc<GraphTable>,
+  // Running tasks that identical `dedupe` submissions wait on
+  dedup: Arc<DedupTable>,
+  // Per-request budgets carved out of the pool
+  children: Arc<ChildTable>,
+  // Per-function caps on concurrently running tasks
+  limits: Arc<ConcurrencyLimits>,
+  // Per-function and per-tenant task start rates
+  rate_limits: Arc<RateLimits>,
+  // Run in place of failed native executions
+  fallbacks: Arc<Fallbacks>,
+  // Tasks submitted with a delay, until they come due
+  timers: Arc<TimerWheel>,
+  // Running aggregates updated by `submit_delta`
+  views: Arc<IncrementalViews>,
+  // Workers held exclusively by reserved batches
+  reservations: Arc<ReservationTable>,
+  // Optional cap on the pool's share of the machine's CPU
+  cpu_budget: Arc<CpuBudget>,
+  // Parks workers while the CPU is throttled
+  throttle: Arc<ThrottleMonitor>,
+  // Maintenance run by idle workers (see `background_optimization`)
+  background: Arc<BackgroundPasses>,
+  // Tells surplus workers to retire after the pool shrinks
+  slots: Arc<WorkerSlots>,
+  // Where workers beat, and leave their end of the queue if they die
+  heartbeats: Arc<Heartbeats>,
+  // Sub-batch sizes learned per function (see `batch_size_hint`)
+  sub_batches: Arc<SubBatchTuner>,
+  starvation: Option<Arc<StarvationMonitor>>,
+  idle_strategy: IdleStrategy,
+  // Only run functions recognized exactly (see `strict`)
+  strict: bool,
+  // Results aggregated locally before merging into shared stats
+  stats_flush_interval: u64,
+  // Bytes each scratch buffer keeps between tasks (see `scratch_limit_bytes`)
+  scratch_limit: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -77,6 +653,8 @@ struct OptimizationStats {
   js_worker_fallbacks: u64,
   cache_hits: u64,
   total_executions: u64,
+  binary_results: u64,
+  binary_bytes: u64,
   avg_latency_ms: f64,
   throughput_tasks_per_sec: f64,
 }
@@ -88,68 +666,385 @@ impl Default for OptimizationStats {
       js_worker_fallbacks: 0,
       cache_hits: 0,
       total_executions: 0,
+      binary_results: 0,
+      binary_bytes: 0,
       avg_latency_ms: 0.0,
       throughput_tasks_per_sec: 0.0,
     }
   }
 }
 
+impl OptimizationStats {
+  /// Fold a single result into the counters and running averages
+  fn record(&mut self, result: &OptimizedWorkerResult) {
+    self.total_executions += 1;
+
+    match result.optimization_used.as_str() {
+      "rust_native" | "rust_optimized" | "rust_fallback" => {
+        self.rust_native_hits += 1;
+      }
+      "needs_js_worker" => {
+        self.js_worker_fallbacks += 1;
+      }
+      _ => {}
+    }
+
+    if result.cache_hit {
+      self.cache_hits += 1;
+    }
+
+    if let Some(binary) = &result.binary {
+      self.binary_results += 1;
+      self.binary_bytes += binary.len() as u64;
+    }
+
+    // Update running averages
+    let current_latency = self.avg_latency_ms;
+    let total = self.total_executions as f64;
+    self.avg_latency_ms =
+      (current_latency * (total - 1.0) + result.duration_ms) / total;
+  }
+
+  /// Fold a worker's locally aggregated counters into these stats
+  fn merge(&mut self, other: &OptimizationStats) {
+    let total = self.total_executions + other.total_executions;
+    if total > 0 {
+      self.avg_latency_ms =
+        (self.avg_latency_ms * (self.total_executions as f64) +
+          other.avg_latency_ms * (other.total_executions as f64)) /
+        (total as f64);
+    }
+
+    self.total_executions = total;
+    self.rust_native_hits += other.rust_native_hits;
+    self.js_worker_fallbacks += other.js_worker_fallbacks;
+    self.cache_hits += other.cache_hits;
+    self.binary_results += other.binary_results;
+    self.binary_bytes += other.binary_bytes;
+  }
+
+  /// Fold a worker's locally aggregated counters into `shared`, starting them over
+  fn flush_into(&mut self, shared: &Mutex<OptimizationStats>, events: &EventLog, worker_id: usize) {
+    if self.total_executions > 0 {
+      events.lock(shared, "optimization_stats", Some(worker_id)).merge(self);
+      *self = OptimizationStats::default();
+    }
+  }
+
+  fn ratio(&self, hits: u64) -> f64 {
+    if self.total_executions > 0 {
+      (hits as f64) / (self.total_executions as f64)
+    } else {
+      0.0
+    }
+  }
+
+  /// Typed view of the stats, including derived ratios
+  fn to_object(&self) -> ExecutorStats {
+    ExecutorStats {
+      rust_native_hits: self.rust_native_hits as i64,
+      js_worker_fallbacks: self.js_worker_fallbacks as i64,
+      cache_hits: self.cache_hits as i64,
+      total_executions: self.total_executions as i64,
+      binary_results: self.binary_results as i64,
+      binary_bytes: self.binary_bytes as i64,
+      avg_latency_ms: self.avg_latency_ms,
+      throughput_tasks_per_sec: self.throughput_tasks_per_sec,
+      optimization_ratio: self.ratio(self.rust_native_hits),
+      cache_hit_ratio: self.ratio(self.cache_hits),
+    }
+  }
+
+  /// JSON view of the stats, including derived ratios
+  fn to_json(&self) -> serde_json::Value {
+    serde_json::json!({
+        "rust_native_hits": self.rust_native_hits,
+        "js_worker_fallbacks": self.js_worker_fallbacks,
+        "cache_hits": self.cache_hits,
+        "total_executions": self.total_executions,
+        "binary_results": self.binary_results,
+        "binary_bytes": self.binary_bytes,
+        "avg_latency_ms": self.avg_latency_ms,
+        "throughput_tasks_per_sec": self.throughput_tasks_per_sec,
+        "optimization_ratio": self.ratio(self.rust_native_hits),
+        "cache_hit_ratio": self.ratio(self.cache_hits)
+    })
+  }
+}
+
 #[napi]
 impl OptimizedMultiCoreExecutor {
   /// Create enhanced executor with optimization capabilities
   #[napi(constructor)]
-  pub fn new(worker_count: Option<u32>) -> Result<Self> {
-    let cores = worker_count.unwrap_or_else(|| num_cpus::get() as u32) as usize;
+  pub fn new(config: Option<ExecutorConfig>) -> Result<Self> {
+    Self::with_clock(config.unwrap_or_default(), SystemClock::shared())
+  }
+
+  /// Create executor driven by a custom time source (e.g. `MockClock` in tests)
+  pub fn with_clock(config: ExecutorConfig, clock: SharedClock) -> Result<Self> {
+    let discipline = config.queue_discipline().map_err(Error::from_reason)?;
+    Self::with_discipline(config, clock, discipline)
+  }
 
-    let (task_tx, task_rx) = channel::unbounded::<OptimizedWorkerTask>();
-    let (result_tx, result_rx) = channel::unbounded::<OptimizedWorkerResult>();
+  /// `with_clock`, with tasks queued by a custom discipline instead of the
+  /// config's `queue_discipline`
+  pub fn with_discipline(
+    config: ExecutorConfig,
+    clock: SharedClock,
+    discipline: SharedDiscipline
+  ) -> Result<Self> {
+    config.validate().map_err(Error::from_reason)?;
+    let config = config.with_profile().map_err(Error::from_reason)?;
+    let scheduling = config.scheduling().map_err(Error::from_reason)?;
+    if config.self_test == Some(true) {
+      let report = self_test::run();
+      if !report.passed {
+        return Err(Error::from_reason(format!("Self-test failed: {}", report.failure_summary())));
+      }
+    }
+    let thread_config = config.thread_config().map_err(Error::from_reason)?;
+    let idle_strategy = config.idle_strategy().map_err(Error::from_reason)?;
+    let strict = config.strict();
+    let cores = config.worker_count();
+    let max_workers = config.max_worker_count();
+    let pools = config.operation_pools().map_err(Error::from_reason)?;
+    let (dedicated, fewest) = (pools.dedicated(), pools.fewest_workers());
+    let autoscale = config.autoscale
+      .as_ref()
+      .map(|autoscale| autoscale.policy(fewest, max_workers))
+      .transpose()
+      .map_err(Error::from_reason)?;
+    let queue_capacity = config.queue_capacity.map(|capacity| capacity as usize);
+    let overflow_policy = config.overflow_policy().map_err(Error::from_reason)?;
 
-    let task_rx = Arc::new(Mutex::new(task_rx));
-    let result_tx = Arc::new(Mutex::new(result_tx));
-    let workers_active = Arc::new(Mutex::new(true));
+    let (task_tx, queue_probe, worker_lanes) = priority::lanes(
+      queue_capacity,
+      max_workers,
+      cores,
+      config.sticky_routing(),
+      pools,
+      discipline.as_ref(),
+      config.aging_ms.map(|ms| Duration::from_millis(ms as u64))
+    );
+    let doorbell = task_tx.doorbell();
 
     // Initialize optimization state
     let performance_cache = Arc::new(Mutex::new(HashMap::new()));
     let hot_functions = Arc::new(Mutex::new(HashMap::new()));
     let optimization_stats = Arc::new(Mutex::new(OptimizationStats::default()));
     let warmed_cores = Arc::new(Mutex::new(vec![false; cores]));
+    // One seed drives every RNG, so receipts can report it
+    let configured_seed = config.seed().map_err(Error::from_reason)?;
+    let seed = configured_seed.unwrap_or_else(|| Self::clock_seed(&clock));
+    let faults = Arc::new(FaultInjector::new(seed));
+    let functions = Arc::new(Mutex::new(FunctionRegistry::new(seed.rotate_left(32))));
+    let fallbacks = Arc::new(Fallbacks::new(Arc::clone(&functions)));
+    let events = Arc::new(EventLog::new(SharedClock::clone(&clock)));
+    let cancellations = Arc::new(CancellationSet::default());
+    let tasks = Arc::new(TaskTracker::new(SharedClock::clone(&clock)));
+    let in_flight = Arc::new(InFlight::new());
+    let deadlines = Arc::new(Deadlines::default());
+    let task_sender = Arc::new(Mutex::new(Some(task_tx)));
+    let requeue = Requeue::new(
+      Arc::clone(&task_sender),
+      Arc::clone(&tasks),
+      doorbell.clone(),
+      Arc::clone(&events)
+    );
+    let groups = Arc::new(GroupTable::new(requeue.clone()));
+    // Compensations run where a graph's failure is reported, like inline tasks
+    let compensate: Compensate = Box::new(move |task| {
+      let no_partials = &|_| {};
+      Self::execute_rust_optimized(task, max_workers, strict, None, no_partials, &|| {}).map(drop)
+    });
+    let graphs = Arc::new(GraphTable::new(requeue.clone(), compensate, Arc::clone(&events)));
+    let dedup = Arc::new(DedupTable::new(requeue.clone()));
+    let children = Arc::new(ChildTable::new(requeue.clone(), SharedClock::clone(&clock)));
+    let limits = Arc::new(ConcurrencyLimits::new(requeue.clone()));
+    let rate_limits = Arc::new(RateLimits::new(requeue.clone(), SharedClock::clone(&clock)));
+    let timers = Arc::new(TimerWheel::new(requeue, SharedClock::clone(&clock)));
+    let views = Arc::new(IncrementalViews::new());
+    let reservations = Arc::new(
+      ReservationTable::new(max_workers, dedicated, cores, doorbell.clone())
+    );
+    let slots = Arc::new(WorkerSlots::new(worker_lanes, fewest, doorbell.clone()));
+    let heartbeats = Arc::new(Heartbeats::new(max_workers, SharedClock::clone(&clock)));
+    let cpu_budget = Arc::new(CpuBudget::new(num_cpus::get(), cores));
+    let throttle = Arc::new(ThrottleMonitor::new(cores));
+    let background = Arc::new(
+      BackgroundPasses::new(
+        config.background_optimization(),
+        strict,
+        Arc::clone(&performance_cache),
+        Arc::clone(&hot_functions),
+        Arc::clone(&throttle),
+        Arc::clone(&events),
+        SharedClock::clone(&clock)
+      )
+    );
+    let sub_batches = Arc::new(SubBatchTuner::new());
+    let retention = config.idempotency_ttl_ms
+      .map_or(idempotency::DEFAULT_RETENTION, |ms| Duration::from_millis(ms as u64));
+    let idempotency = Arc::new(
+      IdempotencyTable::new(retention, Arc::clone(&tasks), SharedClock::clone(&clock))
+    );
+    let starvation = config.starvation_warning_ms.map(|ms| {
+      Arc::new(StarvationMonitor::new(ms, Arc::clone(&tasks), SharedClock::clone(&clock)))
+    });
+    let results = Arc::new(
+      ResultRouter::with_buffer_limit(config.result_buffer_limit.map(|limit| limit as usize))
+    );
+    let protocol = Arc::new(Negotiated::default());
+    let sink = ResultSink {
+      results: Arc::clone(&results),
+      events: Arc::clone(&events),
+      protocol: Arc::clone(&protocol),
+      idempotency: Arc::clone(&idempotency),
+    };
 
-    // Spawn optimized worker threads
-    for worker_id in 0..cores {
-      let task_rx = Arc::clone(&task_rx);
-      let result_tx = Arc::clone(&result_tx);
-      let workers_active = Arc::clone(&workers_active);
-      let performance_cache = Arc::clone(&performance_cache);
-      let hot_functions = Arc::clone(&hot_functions);
+    let context = WorkerContext {
+      queue_probe,
+      result_sender: sink.clone(),
+      optimization_stats: Arc::clone(&optimization_stats),
+      performance_cache: Arc::clone(&performance_cache),
+      hot_functions: Arc::clone(&hot_functions),
+      clock: SharedClock::clone(&clock),
+      faults: Arc::clone(&faults),
+      events: Arc::clone(&events),
+      cancellations: Arc::clone(&cancellations),
+      tasks: Arc::clone(&tasks),
+      in_flight: Arc::clone(&in_flight),
+      deadlines: Arc::clone(&deadlines),
+      groups: Arc::clone(&groups),
+      graphs: Arc::clone(&graphs),
+      dedup: Arc::clone(&dedup),
+      children: Arc::clone(&children),
+      limits: Arc::clone(&limits),
+      rate_limits: Arc::clone(&rate_limits),
+      fallbacks: Arc::clone(&fallbacks),
+      timers: Arc::clone(&timers),
+      views: Arc::clone(&views),
+      reservations: Arc::clone(&reservations),
+      cpu_budget: Arc::clone(&cpu_budget),
+      throttle: Arc::clone(&throttle),
+      background: Arc::clone(&background),
+      slots: Arc::clone(&slots),
+      heartbeats: Arc::clone(&heartbeats),
+      sub_batches: Arc::clone(&sub_batches),
+      starvation: starvation.clone(),
+      idle_strategy,
+      strict,
+      stats_flush_interval: scheduling.stats_flush_interval,
+      scratch_limit: config.scratch_limit_bytes(),
+    };
 
-      thread::spawn(move || {
-        Self::optimized_worker_thread(
-          worker_id,
-          task_rx,
-          result_tx,
-          workers_active,
-          performance_cache,
-          hot_functions
-        );
-      });
-    }
+    // Spawn optimized worker threads, now and whenever the pool grows
+    let names = config.clone();
+    let worker_threads = Arc::new(WorkerThreads::default());
+    let threads = Arc::clone(&worker_threads);
+    let spawn_worker: SpawnWorker = std::sync::Arc::new(move |lanes: WorkerLanes| {
+      let worker_id = lanes.worker();
+      let context = context.clone();
+      let builder = thread_config.builder(names.thread_name(worker_id));
+      WorkerThreads::spawn(&threads, builder, move || {
+        thread_config.apply_to_current_thread();
+        // Kept out here, so a worker that panics still leaves its end of the
+        // queue for the supervisor to respawn it on
+        let mut lanes = Some(lanes);
+        let died = panic::catch_unwind(AssertUnwindSafe(|| {
+          Self::optimized_worker_thread(worker_id, &context, &mut lanes)
+        }));
+        if let (Err(payload), Some(lanes)) = (died, lanes) {
+          context.heartbeats.flatline(lanes, panic_cause(payload.as_ref()));
+        }
+      }).map_err(|e| format!("Failed to spawn worker {}: {}", worker_id, e))
+    });
+    slots.resize(cores, &spawn_worker).map_err(Error::from_reason)?;
+    let resizer = Resizer {
+      slots: Arc::clone(&slots),
+      spawn: spawn_worker,
+      sender: Arc::clone(&task_sender),
+      reservations: Arc::clone(&reservations),
+      cpu_budget: Arc::clone(&cpu_budget),
+      throttle: Arc::clone(&throttle),
+      warmed_cores: Arc::clone(&warmed_cores),
+      events: Arc::clone(&events),
+    };
+    let supervision_interval = config.supervision_interval_ms
+      .unwrap_or(supervisor::DEFAULT_INTERVAL_MS);
+    let supervisor = (supervision_interval > 0).then(|| {
+      let interval = Duration::from_millis(supervision_interval as u64);
+      Supervisor::start(interval, heartbeats, resizer.clone())
+    });
+    let watchdog = Watchdog::start(deadlines, SharedClock::clone(&clock));
 
-    Ok(Self {
-      worker_count: cores,
-      task_sender: Arc::new(Mutex::new(Some(task_tx))),
-      result_receiver: Arc::new(Mutex::new(Some(result_rx))),
-      workers_active,
+    let executor = Self {
+      slots,
+      resizer,
+      worker_threads,
+      task_sender,
+      results,
+      doorbell: doorbell.clone(),
       performance_cache,
       hot_functions,
       optimization_stats,
       warmed_cores,
-    })
+      clock,
+      seeded_ids: configured_seed.map(|_| AtomicU64::new(0)),
+      faults,
+      events,
+      routing_overrides: Mutex::new(RoutingTable::default()),
+      functions,
+      fallbacks,
+      cancellations,
+      tasks,
+      in_flight,
+      groups,
+      graphs,
+      dedup,
+      idempotency,
+      children,
+      borrows: BorrowTable::default(),
+      memory: MemoryWatermark::new(config.memory_watermark_mb),
+      limits,
+      rate_limits,
+      timers,
+      views,
+      reservations,
+      cpu_budget,
+      throttle,
+      background,
+      sub_batches,
+      discipline,
+      starvation,
+      protocol,
+      sink,
+      deprecations: Deprecations::default(),
+      statsd: Mutex::new(None),
+      autoscaler: Mutex::new(None),
+      supervisor: Mutex::new(supervisor),
+      watchdog: Mutex::new(Some(watchdog)),
+      queue_capacity,
+      overflow_policy,
+      default_timeout_ms: config.default_timeout_ms,
+      inline_threshold: config.inline_threshold_bytes.map(|bytes| bytes as usize),
+      inline_batch_budget_ms: config.inline_batch_budget_ms
+        .unwrap_or(inline_batch::DEFAULT_BUDGET_MS),
+      strict,
+      scheduling,
+    };
+    if let Some(policy) = autoscale {
+      let autoscaler = executor.start_autoscaler(policy).map_err(Error::from_reason)?;
+      *executor.events.lock(&executor.autoscaler, "autoscaler", None) = Some(autoscaler);
+    }
+    Ok(executor)
   }
 
   /// Warm up specific cores for expected workload
   #[napi]
   pub fn warm_cores_for_workload(&self, operation_type: String, expected_cores: u32) -> Result<()> {
-    if let Ok(mut warmed) = self.warmed_cores.lock() {
+    {
+      let mut warmed = self.events.lock(&self.warmed_cores, "warmed_cores", None);
       for i in 0..(expected_cores as usize).min(warmed.len()) {
         warmed[i] = true;
       }
@@ -159,319 +1054,2219 @@ impl OptimizedMultiCoreExecutor {
     Ok(())
   }
 
-  /// Submit optimized task with preparation hints
-  #[napi]
+  /// Submit optimized task with preparation hints. Aborting `signal` cancels the task.
+  #[cfg(feature = "node")]
+  #[napi(
+    ts_args_type = "functionCode: string, data: string, optimizationHints: OptimizationHints, meta?: any | undefined | null, timeoutMs?: number | undefined | null, signal?: AbortSignal | undefined | null"
+  )]
+  #[allow(clippy::too_many_arguments)]
   pub fn submit_optimized_task(
     &self,
+    env: Env,
     function_code: String,
     data: String,
-    optimization_hints: String // JSON serialized OptimizationHints
+    optimization_hints: OptimizationHints,
+    meta: Option<serde_json::Value>, // Echoed back untouched in the result
+    timeout_ms: Option<u32>, // Execution budget for the task
+    signal: Option<JsObject> // AbortSignal or any object with `aborted`/`addEventListener`
   ) -> Result<String> {
-    let task_id = Self::generate_task_id();
-
-    // Parse optimization hints
-    let hints: OptimizationHints = serde_json
-      ::from_str(&optimization_hints)
-      .map_err(|e| napi::Error::from_reason(format!("Invalid optimization hints: {}", e)))?;
+    if let Some(signal) = signal.as_ref() && abort::is_aborted(signal)? {
+      return Err(abort::abort_error());
+    }
 
-    let task = OptimizedWorkerTask {
-      id: task_id.clone(),
-      function_code,
-      data,
-      timeout_ms: None,
-      optimization_hints: hints,
-    };
+    // Still on the JS thread, so the signal can't fire before the listener is added
+    let task_id = self.submit_task(function_code, data, optimization_hints, meta, timeout_ms)?;
+    if let Some(signal) = signal.as_ref() {
+      self.cancel_on_abort(&env, signal, task_id.clone(), None)?;
+    }
+    Ok(task_id)
+  }
 
-    if let Ok(sender_guard) = self.task_sender.lock() {
-      if let Some(sender) = sender_guard.as_ref() {
-        sender.send(task).map_err(|e| napi::Error::from_reason(e.to_string()))?;
-        Ok(task_id)
-      } else {
-        Err(napi::Error::from_reason("Worker pool is shut down".to_string()))
-      }
-    } else {
-      Err(napi::Error::from_reason("Failed to access task sender".to_string()))
+  /// Submit optimized task with preparation hints; `submitOptimizedTask` for
+  /// Rust callers, cancelled with `cancel_task` instead of an AbortSignal
+  pub fn submit_task(
+    &self,
+    function_code: String,
+    data: String,
+    optimization_hints: OptimizationHints,
+    meta: Option<serde_json::Value>,
+    timeout_ms: Option<u32>
+  ) -> Result<String> {
+    let mut hints = optimization_hints;
+    let key = hints.idempotency_key.take();
+    let task = self.prepare_task(function_code, data, hints, meta, timeout_ms)?;
+    if let Some(prior) = self.claim_key(key.as_deref(), &task.id) {
+      return Ok(prior);
     }
+    let task_id = task.id.clone();
+    self.enqueue_task(task).inspect_err(|_| self.idempotency.release(&task_id))?;
+    Ok(task_id)
   }
 
-  /// Submit optimized batch with adaptive sizing
+  /// `submit_optimized_task` as it was before hints became an object: hints
+  /// and `meta` as JSON text. The first call reports a `deprecated_call` event.
+  ///
+  /// @deprecated Use `submitOptimizedTask` with a hints object.
   #[napi]
-  pub fn submit_optimized_batch(
+  pub fn submit_optimized_task_json(
     &self,
-    tasks: Vec<(String, String)>, // (function_code, data)
-    optimization_hints: String // JSON serialized OptimizationHints
-  ) -> Result<Vec<String>> {
-    let mut task_ids = Vec::new();
+    function_code: String,
+    data: String,
+    optimization_hints: String, // JSON serialized OptimizationHints
+    meta: Option<String> // JSON blob echoed back untouched in the result
+  ) -> Result<String> {
+    self.deprecations.report(LegacyCall::OptimizedTaskJson, &self.events);
+    let hints = legacy::parse_hints(&optimization_hints).map_err(Error::from_reason)?;
+    let meta = legacy::parse_meta(meta.as_deref()).map_err(Error::from_reason)?;
+    self.submit_task(function_code, data, hints, meta, None)
+  }
 
-    // Parse optimization hints
-    let hints: OptimizationHints = serde_json
-      ::from_str(&optimization_hints)
-      .map_err(|e| napi::Error::from_reason(format!("Invalid optimization hints: {}", e)))?;
-
-    if let Ok(sender_guard) = self.task_sender.lock() {
-      if let Some(sender) = sender_guard.as_ref() {
-        for (function_code, data) in tasks {
-          let task_id = Self::generate_task_id();
-
-          let task = OptimizedWorkerTask {
-            id: task_id.clone(),
-            function_code,
-            data,
-            timeout_ms: None,
-            optimization_hints: hints.clone(),
-          };
+  /// Submit a task that joins the queue only once `delay_ms` has passed, instead
+  /// of a setTimeout around `submit_optimized_task`. It reports `waiting` until
+  /// then; delays are rounded up to the timer wheel's 5ms tick.
+  #[napi]
+  pub fn submit_delayed(
+    &self,
+    function_code: String,
+    data: String,
+    optimization_hints: OptimizationHints,
+    delay_ms: u32
+  ) -> Result<String> {
+    let task = self.prepare_task(function_code, data, optimization_hints, None, None)?;
+    if task.optimization_hints.after.is_some() {
+      return Err(Error::from_reason("Delayed tasks can't wait on a task group".to_string()));
+    }
+    let priority = self.lane(&task.optimization_hints)?;
+    let task_id = task.id.clone();
 
-          sender.send(task).map_err(|e| napi::Error::from_reason(e.to_string()))?;
-          task_ids.push(task_id);
-        }
-        Ok(task_ids)
-      } else {
-        Err(napi::Error::from_reason("Worker pool is shut down".to_string()))
-      }
-    } else {
-      Err(napi::Error::from_reason("Failed to access task sender".to_string()))
+    // Held while scheduling, so a shutdown can't slip in between
+    let sender_guard = self.events.lock(&self.task_sender, "task_sender", None);
+    if sender_guard.is_none() {
+      return Err(Error::from_reason("Worker pool is shut down".to_string()));
+    }
+    self.in_flight.begin().map_err(Error::from_reason)?;
+    // A group can't complete while one of its members is still on the wheel
+    if let Some(group) = task.optimization_hints.group.as_deref() {
+      self.groups
+        .join(group, &task_id)
+        .map_err(Error::from_reason)
+        .inspect_err(|_| self.in_flight.end())?;
     }
+    self.tasks.waiting(&task_id, priority);
+    self.timers.schedule(task, priority, Duration::from_millis(delay_ms as u64));
+    Ok(task_id)
   }
 
-  /// Get optimized result with performance metrics
+  /// Submit a task whose result is discarded: no result channel, status tracking
+  /// or receipt, for telemetry-style work where only the side effect matters.
+  /// Cancellation via `cancel_all` still applies.
   #[napi]
-  pub fn get_optimized_result(&self, timeout_ms: Option<u32>) -> Result<String> {
-    if let Ok(receiver_guard) = self.result_receiver.lock() {
-      if let Some(receiver) = receiver_guard.as_ref() {
-        let result = if let Some(timeout) = timeout_ms {
-          receiver
-            .recv_timeout(Duration::from_millis(timeout as u64))
-            .map_err(|e| napi::Error::from_reason(e.to_string()))?
-        } else {
-          receiver.recv().map_err(|e| napi::Error::from_reason(e.to_string()))?
-        };
-
-        // Update optimization stats
-        self.update_optimization_stats(&result);
-
-        serde_json::to_string(&result).map_err(|e| napi::Error::from_reason(e.to_string()))
-      } else {
-        Err(napi::Error::from_reason("Worker pool is shut down".to_string()))
-      }
-    } else {
-      Err(napi::Error::from_reason("Failed to access result receiver".to_string()))
-    }
+  pub fn submit_fire(
+    &self,
+    function_code: String,
+    data: String,
+    optimization_hints: OptimizationHints
+  ) -> Result<()> {
+    let mut task = self.prepare_task(function_code, data, optimization_hints, None, None)?;
+    task.optimization_hints.include_receipt = None;
+    task.reply = Reply::Discard;
+    self.enqueue_task(task)
   }
 
-  /// Get batch results with optimization tracking
+  /// Submit a task that reads `data` where it is instead of copying it, for
+  /// payloads of hundreds of megabytes. The Buffer must hold UTF-8 text, and is
+  /// referenced until the task is done; don't modify it until the result
+  /// arrives or `release_borrowed` returns true.
+  #[cfg(feature = "node")]
   #[napi]
-  pub fn get_optimized_batch_results(
+  pub fn submit_borrowed(
     &self,
-    task_count: u32,
+    function_code: String,
+    data: Buffer,
+    optimization_hints: OptimizationHints,
+    meta: Option<serde_json::Value>,
     timeout_ms: Option<u32>
-  ) -> Result<Vec<String>> {
-    let mut results = Vec::new();
-    let deadline = timeout_ms.map(|t| Instant::now() + Duration::from_millis(t as u64));
-    let batch_start = Instant::now();
-
-    if let Ok(receiver_guard) = self.result_receiver.lock() {
-      if let Some(receiver) = receiver_guard.as_ref() {
-        for _ in 0..task_count {
-          let remaining_time = deadline.map(|d| d.saturating_duration_since(Instant::now()));
-
-          let result = if let Some(timeout) = remaining_time {
-            if timeout.is_zero() {
-              return Err(napi::Error::from_reason("Batch timeout exceeded".to_string()));
-            }
-            receiver.recv_timeout(timeout).map_err(|e| napi::Error::from_reason(e.to_string()))?
-          } else {
-            receiver.recv().map_err(|e| napi::Error::from_reason(e.to_string()))?
-          };
-
-          // Update optimization stats for each result
-          self.update_optimization_stats(&result);
-
-          let result_json = serde_json
-            ::to_string(&result)
-            .map_err(|e| napi::Error::from_reason(e.to_string()))?;
-          results.push(result_json);
-        }
-
-        // Update batch performance metrics
-        let batch_duration = batch_start.elapsed().as_millis() as f64;
-        let throughput = ((task_count as f64) / batch_duration) * 1000.0;
-
-        if let Ok(mut stats) = self.optimization_stats.lock() {
-          stats.throughput_tasks_per_sec = throughput;
-        }
-
-        Ok(results)
-      } else {
-        Err(napi::Error::from_reason("Worker pool is shut down".to_string()))
-      }
-    } else {
-      Err(napi::Error::from_reason("Failed to access result receiver".to_string()))
-    }
+  ) -> Result<String> {
+    let bytes: SharedBytes = std::sync::Arc::new(JsBytes::from(data));
+    self.submit_borrowed_bytes(function_code, bytes, optimization_hints, meta, timeout_ms)
   }
 
-  /// Get optimization performance statistics
-  #[napi]
-  pub fn get_optimization_stats(&self) -> Result<String> {
-    if let Ok(stats) = self.optimization_stats.lock() {
-      let stats_json =
-        serde_json::json!({
-                "rust_native_hits": stats.rust_native_hits,
-                "js_worker_fallbacks": stats.js_worker_fallbacks,
-                "cache_hits": stats.cache_hits,
-                "total_executions": stats.total_executions,
-                "avg_latency_ms": stats.avg_latency_ms,
-                "throughput_tasks_per_sec": stats.throughput_tasks_per_sec,
-                "optimization_ratio": if stats.total_executions > 0 {
-                    stats.rust_native_hits as f64 / stats.total_executions as f64
-                } else { 0.0 },
-                "cache_hit_ratio": if stats.total_executions > 0 {
-                    stats.cache_hits as f64 / stats.total_executions as f64
-                } else { 0.0 }
-            });
-
-      Ok(stats_json.to_string())
-    } else {
-      Err(napi::Error::from_reason("Failed to access optimization stats".to_string()))
+  /// `submitBorrowed` for Rust callers, with any shared bytes
+  pub fn submit_borrowed_bytes(
+    &self,
+    function_code: String,
+    data: SharedBytes,
+    optimization_hints: OptimizationHints,
+    meta: Option<serde_json::Value>,
+    timeout_ms: Option<u32>
+  ) -> Result<String> {
+    let borrowed = BorrowedData::new(data).map_err(Error::from_reason)?;
+    let mut hints = optimization_hints;
+    let key = hints.idempotency_key.take();
+    let mut task = self.prepare_task(function_code, String::new(), hints, meta, timeout_ms)?;
+    if let Some(prior) = self.claim_key(key.as_deref(), &task.id) {
+      return Ok(prior);
     }
+    let task_id = task.id.clone();
+    // Tracked first, so the task can be released as soon as it's queued
+    self.borrows.track(&task_id, &borrowed);
+    task.borrowed = Some(borrowed);
+    self.enqueue_task(task).inspect_err(|_| self.idempotency.release(&task_id))?;
+    Ok(task_id)
   }
 
-  /// Clear optimization caches and reset stats
+  /// Let the caller reuse a `submit_borrowed` task's Buffer before the task
+  /// runs. True if no worker had started on it: the task is cancelled and its
+  /// bytes are never read. False once a worker has, or for other tasks; a
+  /// Buffer in use is free again when its task's result arrives.
   #[napi]
-  pub fn reset_optimization_state(&self) -> Result<()> {
-    if let Ok(mut cache) = self.performance_cache.lock() {
-      cache.clear();
+  pub fn release_borrowed(&self, task_id: String) -> bool {
+    let released = self.borrows.release(&task_id);
+    if released {
+      self.tasks.cancel(&task_id);
     }
+    released
+  }
 
-    if let Ok(mut hot_funcs) = self.hot_functions.lock() {
-      hot_funcs.clear();
-    }
+  /// Run a task and return a Promise resolving to its result (rejects on task error).
+  /// `timeout_ms` bounds both the task's execution and the wait for its result.
+  /// Aborting `signal` cancels the task and rejects the Promise with `AbortError`.
+  #[cfg(feature = "node")]
+  #[napi(
+    ts_args_type = "functionCode: string, data: string, optimizationHints: OptimizationHints, meta?: any | undefined | null, timeoutMs?: number | undefined | null, signal?: AbortSignal | undefined | null",
+    ts_return_type = "Promise<OptimizedWorkerResult>"
+  )]
+  #[allow(clippy::too_many_arguments)]
+  pub fn run_task(
+    &self,
+    env: Env,
+    function_code: String,
+    data: String,
+    optimization_hints: OptimizationHints,
+    meta: Option<serde_json::Value>,
+    timeout_ms: Option<u32>,
+    signal: Option<JsObject>
+  ) -> Result<AsyncTask<RunTask>> {
+    let (reply_tx, reply_rx) = channel::bounded(1);
+    let (abort_tx, abort_rx) = channel::bounded(1);
+    let run = RunTask::new(reply_rx, timeout_ms)
+      .with_abort(abort_rx)
+      .with_protocol(Arc::clone(&self.protocol));
 
-    if let Ok(mut stats) = self.optimization_stats.lock() {
-      *stats = OptimizationStats::default();
+    // An already-aborted signal rejects without ever queueing the task
+    if let Some(signal) = signal.as_ref() && abort::is_aborted(signal)? {
+      let _ = abort_tx.send(());
+      return Ok(AsyncTask::new(run));
     }
 
-    if let Ok(mut warmed) = self.warmed_cores.lock() {
-      warmed.fill(false);
+    let mut task = self.prepare_task(function_code, data, optimization_hints, meta, timeout_ms)?;
+    task.reply = Reply::Channel(reply_tx);
+    if let Some(signal) = signal.as_ref() {
+      self.cancel_on_abort(&env, signal, task.id.clone(), Some(abort_tx))?;
     }
+    self.enqueue_task(task)?;
 
-    Ok(())
+    Ok(AsyncTask::new(run))
   }
 
-  /// Enhanced worker thread with optimization awareness
-  fn optimized_worker_thread(
-    worker_id: usize,
-    task_receiver: Arc<Mutex<channel::Receiver<OptimizedWorkerTask>>>,
-    result_sender: Arc<Mutex<channel::Sender<OptimizedWorkerResult>>>,
-    workers_active: Arc<Mutex<bool>>,
-    performance_cache: Arc<Mutex<HashMap<String, PerformanceMetric>>>,
-    hot_functions: Arc<Mutex<HashMap<String, u32>>>
-  ) {
-    while (
+  /// Run `function_code` on each of `items` and return their results, parsed,
+  /// in the same order: one call, no task IDs to collect. A batch estimated to
+  /// finish within `inline_batch_budget_ms` runs on the calling thread; a
+  /// costlier one, or one whose hints keep tasks off it, goes to the pool and
+  /// the call blocks until every item is done. Throws with the first item that
+  /// failed. Hints default as for a task spec's tasks.
+  #[napi]
+  pub fn execute_inline_batch(
+    &self,
+    function_code: String,
+    items: Vec<serde_json::Value>,
+    optimization_hints: Option<OptimizationHints>
+  ) -> Result<Vec<serde_json::Value>> {
+    let hints = match optimization_hints {
+      Some(hints) => hints,
+      None => inline_batch::default_hints(&function_code).map_err(Error::from_reason)?,
+    };
+    let (reply_tx, reply_rx) = channel::bounded(items.len());
+    let mut order = HashMap::with_capacity(items.len());
+    let mut tasks = Vec::with_capacity(items.len());
+    for (item, data) in items.iter().enumerate() {
+      let data = data.to_string();
+      let mut task = self.prepare_task(function_code.clone(), data, hints.clone(), None, None)?;
+      task.reply = Reply::Channel(reply_tx.clone());
+      order.insert(task.id.clone(), item);
+      tasks.push(task);
+    }
+    // Only the tasks' copies are left, so the results end when they've all replied
+    drop(reply_tx);
+    let Some(first) = tasks.first() else {
+      return Ok(Vec::new());
+    };
+
+    let priority = self.lane(&first.optimization_hints)?;
+    let bytes = tasks.iter().map(|task| task.data.len()).sum();
+    let measured_ms = self.events
+      .lock(&self.performance_cache, "performance_cache", None)
+      .get(&first.optimization_hints.function_hash)
+      .map(|metric| metric.execution_time);
+    let cost = inline_batch::estimated_ms(tasks.len(), bytes, measured_ms);
+    if cost <= (self.inline_batch_budget_ms as f64) && self.may_run_inline(first) {
       {
-        if let Ok(active) = workers_active.lock() { *active } else { false }
+        let sender = self.events.lock(&self.task_sender, "task_sender", None);
+        let Some(sender) = sender.as_ref() else {
+          return Err(Error::from_reason("Worker pool is shut down".to_string()));
+        };
+        self.check_memory(sender)?;
+        self.in_flight.begin().map_err(Error::from_reason)?;
       }
-    ) {
-      let task = {
-        if let Ok(receiver_guard) = task_receiver.lock() {
-          receiver_guard.try_recv()
-        } else {
-          break;
-        }
-      };
+      for task in tasks {
+        self.run_inline(task, priority);
+      }
+      self.in_flight.end();
+    } else {
+      for task in tasks {
+        self.enqueue_task(task)?;
+      }
+    }
 
-      match task {
-        Ok(task) => {
-          let start_time = Instant::now();
-
-          // Check cache for hot functions
-          let cache_hit = if task.optimization_hints.is_hot_function {
-            if let Ok(cache) = performance_cache.lock() {
-              cache.contains_key(&task.optimization_hints.function_hash)
-            } else {
-              false
-            }
-          } else {
-            false
-          };
+    let mut results = vec![None; order.len()];
+    for result in reply_rx.iter() {
+      if let Some(&item) = order.get(&result.id) {
+        results[item] = Some(result);
+      }
+    }
+    inline_batch::values(results).map_err(Error::from_reason)
+  }
 
-          let (result, optimization_used) = if task.optimization_hints.should_use_rust {
-            // Use enhanced Rust native execution
-            match Self::execute_rust_optimized(&task, worker_id) {
-              Ok(result) => (Ok(result), "rust_optimized".to_string()),
-              Err(err) => (Err(err), "rust_failed".to_string()),
-            }
-          } else {
-            // Signal for JavaScript worker with optimization hints
-            (Err("NEEDS_JS_WORKER_OPTIMIZED".to_string()), "needs_js_worker".to_string())
-          };
+  /// Submit optimized batch with adaptive sizing. With `reserved_workers`, the batch
+  /// runs only on that many workers held exclusively until it finishes; it waits
+  /// in line if they aren't free yet. Otherwise a batch mixing light and heavy
+  /// tasks, by their `complexity` and `estimated_memory` hints, is split across
+  /// workers so the heavy ones don't end up on the same worker, and one with a
+  /// `batch_size_hint` is cut into sub-batches that workers each run in one pass.
+  #[napi]
+  pub fn submit_optimized_batch(
+    &self,
+    tasks: Vec<(String, String)>, // (function_code, data)
+    optimization_hints: OptimizationHints, // Shared by every task without its own hints
+    metas: Option<Vec<serde_json::Value>>, // Per-task metadata, same order as `tasks`
+    task_hints: Option<Vec<Option<OptimizationHints>>>, // Per-task hint overrides, same order
+    timeout_ms: Option<u32>, // Execution budget for each task
+    reserved_workers: Option<u32> // Workers to hold exclusively for this batch
+  ) -> Result<Vec<String>> {
+    let mut task_ids = Vec::new();
 
-          let duration = start_time.elapsed().as_millis() as u64;
+    Self::check_per_task_len("meta", tasks.len(), metas.as_ref().map(Vec::len))?;
+    Self::check_per_task_len("hint", tasks.len(), task_hints.as_ref().map(Vec::len))?;
+    let reservation = match reserved_workers {
+      Some(workers) if workers as usize > self.slots.target() => {
+        return Err(
+          Error::from_reason(
+            format!("Cannot reserve {} of {} workers", workers, self.slots.target())
+          )
+        );
+      }
+      Some(workers) if workers > 0 => Some(self.reservations.create(workers as usize)),
+      _ => None,
+    };
+    let mut metas = metas.map(|m| m.into_iter());
+    let per_task_hints = task_hints.is_some();
 
-          // Update hot function tracking
-          if let Ok(mut hot_funcs) = hot_functions.lock() {
-            let count = hot_funcs.entry(task.optimization_hints.function_hash.clone()).or_insert(0);
-            *count += 1;
+    // Resolve every task's hints up front so routing overrides are applied once per
+    // task, and an invalid priority or group rejects the batch before any of it is queued
+    let resolved_hints: Vec<(OptimizationHints, Priority)> = {
+      let routing = self.events.lock(&self.routing_overrides, "routing_overrides", None);
+      let mut overrides = task_hints.map(|h| h.into_iter());
+      (0..tasks.len())
+        .map(|_| {
+          let mut hints = overrides
+            .as_mut()
+            .and_then(|h| h.next())
+            .flatten()
+            .unwrap_or_else(|| optimization_hints.clone());
+          routing.apply(&mut hints);
+          let priority = self.lane(&hints)?;
+          if reservation.is_some() && hints.after.is_some() {
+            return Err(
+              Error::from_reason("Tasks in a reserved batch can't wait on a task group".to_string())
+            );
           }
-
-          // Record performance metric
-          if let Ok(mut cache) = performance_cache.lock() {
-            let metric = PerformanceMetric {
-              function_hash: task.optimization_hints.function_hash.clone(),
-              execution_time: duration,
-              throughput: 1000.0 / (duration as f64),
-              optimization_type: optimization_used.clone(),
-              success: result.is_ok(),
-            };
-            cache.insert(task.optimization_hints.function_hash.clone(), metric);
+          if let Some(group) = hints.group.as_deref() && self.groups.is_sealed(group) {
+            return Err(Error::from_reason(format!("Task group '{}' is sealed", group)));
           }
+          Ok((hints, priority))
+        })
+        .collect::<Result<_>>()?
+    };
+    // One function over rows of one shape runs a column at a time
+    let mut columnar = self.columnar_rows(&tasks, &resolved_hints, per_task_hints).map(
+      Vec::into_iter
+    );
+    // Light and heavy tasks mixed are split across workers by weight; otherwise
+    // a `batch_size_hint` cuts the batch into sub-batches, each run in one pass
+    let mut dispatch = reservation
+      .is_none()
+      .then(|| {
+        let weights: Vec<f64> = resolved_hints
+          .iter()
+          .map(|(hints, _)| batch_split::weight(hints))
+          .collect();
+        if let Some(split) = batch_split::split(&weights, self.slots.target()) {
+          return Some(split.into_iter().map(|worker| (worker, None)).collect::<Vec<_>>());
+        }
+        let hint = optimization_hints.batch_size_hint.filter(|&hint| hint > 1)?;
+        let size = self.sub_batches.size(&optimization_hints.function_hash, hint);
+        let plan = self.sub_batches.plan(tasks.len(), size, self.slots.target());
+        Some(plan.into_iter().map(|(worker, sub_batch)| (worker, Some(sub_batch))).collect())
+      })
+      .flatten()
+      .map(Vec::into_iter);
 
-          let (result_ok, result_err) = match result {
-            Ok(val) => (Some(val), None),
-            Err(err) => (None, Some(err)),
-          };
+    let queued = if reservation.is_none() { tasks.len() } else { 0 };
+    let sender_guard = self.sender_with_room(queued);
+    if let Some(sender) = sender_guard.as_ref() {
+      self.check_memory(sender)?;
+      // Reject up front rather than queue part of the batch; holding the sender
+      // lock keeps other submitters out until the batch is in
+      if let Some(capacity) = self.queue_capacity && sender.len() + queued > capacity {
+        return Err(
+          Error::new(
+            Status::QueueFull,
+            format!(
+              "Task queue is full: {} queued, capacity {}, batch of {}",
+              sender.len(),
+              capacity,
+              tasks.len()
+            )
+          )
+        );
+      }
 
-          let worker_result = OptimizedWorkerResult {
-            id: task.id,
-            result: result_ok,
-            error: result_err,
-            duration_ms: duration,
-            worker_id,
-            execution_type: optimization_used.clone(),
-            optimization_used,
-            cache_hit,
-          };
+      for ((function_code, data), (hints, priority)) in tasks.into_iter().zip(resolved_hints) {
+        let task_id = self.generate_task_id();
+        let meta = metas.as_mut().and_then(|m| m.next());
 
-          if let Ok(sender_guard) = result_sender.lock() {
-            let _ = sender_guard.send(worker_result);
+        let rejected = if self.faults.should_reject_submission() {
+          Err("Task queue is full (injected fault)".to_string())
+        } else {
+          self.in_flight.begin()
+        };
+        if let Err(reason) = rejected {
+          // Tasks already reserved still run
+          if let Some(reservation) = reservation {
+            self.reservations.request(reservation);
           }
+          return Err(Error::from_reason(reason));
         }
-        Err(channel::TryRecvError::Empty) => {
-          thread::sleep(Duration::from_millis(1));
-        }
-        Err(channel::TryRecvError::Disconnected) => {
-          break;
+
+        let expires_at = self.queue_deadline(&hints);
+        let (worker, sub_batch) = dispatch
+          .as_mut()
+          .and_then(Iterator::next)
+          .map_or((None, None), |(worker, sub_batch)| (Some(worker), sub_batch));
+        let task = OptimizedWorkerTask {
+          id: task_id.clone(),
+          function_code,
+          data,
+          timeout_ms: timeout_ms.or(self.default_timeout_ms).map(u64::from),
+          optimization_hints: hints,
+          meta,
+          reply: Reply::Shared,
+          epoch: self.cancellations.current_epoch(),
+          view: None,
+          columnar: columnar.as_mut().and_then(Iterator::next),
+          borrowed: None,
+          expires_at,
+          worker,
+          sub_batch,
+        };
+
+        match reservation.as_ref() {
+          Some(reservation) => {
+            let group = task.optimization_hints.group.as_deref();
+            let joined = group.map_or(Ok(()), |group| self.groups.join(group, &task_id));
+            if let Err(reason) = joined {
+              self.in_flight.end();
+              self.reservations.request(Arc::clone(reservation));
+              return Err(Error::from_reason(reason));
+            }
+            // A reservation serves its own batch in submission order
+            self.tasks.queued(&task_id, QueueId::Reserved(reservation.id()));
+            reservation.push(task);
+          }
+          None => {
+            if let Err(e) = self.admit(sender, task, priority, true) {
+              self.in_flight.end();
+              return Err(e);
+            }
+          }
         }
+        task_ids.push(task_id);
       }
+
+      // Every task is queued before any reserved worker can drain the reservation
+      if let Some(reservation) = reservation {
+        self.reservations.request(reservation);
+      }
+      Ok(task_ids)
+    } else {
+      Err(Error::from_reason("Worker pool is shut down".to_string()))
     }
   }
 
-  /// Enhanced Rust native execution with optimization hints
-  fn execute_rust_optimized(
+  /// Submit tasks that depend on each other as one graph. A node runs once every
+  /// node in its `depends_on` has succeeded, taking their output as its input: a
+  /// single parent's result as is, several as a JSON array in `depends_on` order.
+  /// When a node fails or is cancelled, everything downstream fails unrun, and
+  /// the `compensation_code` of every node that had succeeded runs on its
+  /// output, latest first. Returns task IDs in node order.
+  #[napi]
+  pub fn submit_graph(
+    &self,
+    nodes: Vec<GraphNode>,
+    optimization_hints: OptimizationHints, // Shared by every node without its own hints
+    timeout_ms: Option<u32> // Execution budget for each node
+  ) -> Result<Vec<String>> {
+    let parents = task_graph::plan(&nodes).map_err(Error::from_reason)?;
+    let resolved_hints: Vec<(OptimizationHints, Priority)> = {
+      let routing = self.events.lock(&self.routing_overrides, "routing_overrides", None);
+      nodes
+        .iter()
+        .map(|node| {
+          let mut hints = node.hints.clone().unwrap_or_else(|| optimization_hints.clone());
+          routing.apply(&mut hints);
+          if hints.group.is_some() || hints.after.is_some() {
+            return Err(
+              Error::from_reason(
+                format!("Graph node '{}' can't join or wait on a task group", node.name)
+              )
+            );
+          }
+          let priority = self.lane(&hints)?;
+          Ok((hints, priority))
+        })
+        .collect::<Result<_>>()?
+    };
+
+    // Only the roots are queued now; the rest are released as their parents finish
+    let root_count = parents.iter().filter(|p| p.is_empty()).count();
+    let sender_guard = self.sender_with_room(root_count);
+    let Some(sender) = sender_guard.as_ref() else {
+      return Err(Error::from_reason("Worker pool is shut down".to_string()));
+    };
+    self.check_memory(sender)?;
+    if let Some(capacity) = self.queue_capacity && sender.len() + root_count > capacity {
+      return Err(
+        Error::new(
+          Status::QueueFull,
+          format!(
+            "Task queue is full: {} queued, capacity {}, graph with {} roots",
+            sender.len(),
+            capacity,
+            root_count
+          )
+        )
+      );
+    }
+    for begun in 0..nodes.len() {
+      if let Err(reason) = self.in_flight.begin() {
+        (0..begun).for_each(|_| self.in_flight.end());
+        return Err(Error::from_reason(reason));
+      }
+    }
+
+    let task_ids: Vec<String> = nodes
+      .iter()
+      .map(|_| self.generate_task_id())
+      .collect();
+    let mut roots = Vec::with_capacity(root_count);
+    let graph = self.graphs.open();
+    // Every node is in the table before any root can run and finish
+    let planned = nodes.into_iter().zip(resolved_hints).zip(parents);
+    for (i, ((node, (hints, priority)), node_parents)) in planned.enumerate() {
+      let expires_at = self.queue_deadline(&hints);
+      let task = OptimizedWorkerTask {
+        id: task_ids[i].clone(),
+        function_code: node.function_code,
+        data: node.data.unwrap_or_default(),
+        timeout_ms: timeout_ms.or(self.default_timeout_ms).map(u64::from),
+        optimization_hints: hints,
+        meta: None,
+        reply: Reply::Shared,
+        epoch: self.cancellations.current_epoch(),
+        view: None,
+        columnar: None,
+        borrowed: None,
+        expires_at,
+        worker: None,
+        sub_batch: None,
+      };
+      let compensation = node.compensation_code.map(|function_code| OptimizedWorkerTask {
+        id: format!("{}-compensation", task.id),
+        function_code,
+        reply: Reply::Discard,
+        ..task.clone()
+      });
+      if node_parents.is_empty() {
+        self.graphs.add(graph, &task.id, &node.name, Vec::new(), None, compensation);
+        roots.push((task, priority));
+      } else {
+        let parent_ids = node_parents
+          .iter()
+          .map(|&parent| task_ids[parent].clone())
+          .collect();
+        self.tasks.waiting(&task.id, priority);
+        let held = Some((task, priority));
+        self.graphs.add(graph, &task_ids[i], &node.name, parent_ids, held, compensation);
+      }
+    }
+
+    let mut roots = roots.into_iter();
+    while let Some((task, priority)) = roots.next() {
+      let task_id = task.id.clone();
+      if let Err(e) = self.admit(sender, task, priority, true) {
+        // Nothing downstream of a root that isn't queued can run
+        let unsent: Vec<String> = std::iter::once(task_id)
+          .chain(roots.map(|(task, _)| task.id))
+          .collect();
+        for root in unsent {
+          self.in_flight.end();
+          let skipped = self.graphs.finish(&root, None);
+          let (worker_id, seed) = (self.slots.capacity(), self.faults.seed());
+          Self::fail_skipped(skipped, worker_id, seed, &self.tasks, &self.in_flight, |r| {
+            self.sink.send(r)
+          });
+        }
+        return Err(e);
+      }
+    }
+    Ok(task_ids)
+  }
+
+  /// Submit the tasks of a JSON or YAML task spec (see `parse_task_spec`) as one
+  /// graph. Returns task IDs in spec order.
+  #[napi]
+  pub fn submit_task_spec(&self, spec: String, format: Option<String>) -> Result<Vec<String>> {
+    let TaskSpec { nodes, hints, timeout_ms } = task_spec::import(&spec, format.as_deref())?;
+    // Imported nodes carry their own hints, so any of them serve as the shared ones
+    let Some(hints) = hints.or_else(|| nodes.first().and_then(|node| node.hints.clone())) else {
+      return Ok(Vec::new());
+    };
+    self.submit_graph(nodes, hints, timeout_ms)
+  }
+
+  /// Get the next result to arrive, whichever task it's for. The first call
+  /// reports a `deprecated_call` event.
+  ///
+  /// @deprecated Use `getResultById`, or `runTask`.
+  #[napi]
+  pub fn get_optimized_result(&self, timeout_ms: Option<u32>) -> Result<OptimizedWorkerResult> {
+    self.deprecations.report(LegacyCall::OptimizedResult, &self.events);
+    self.results
+      .next(timeout_ms.map(|t| Duration::from_millis(t as u64)))
+      .map_err(RouterError::into_error)
+  }
+
+  /// `get_optimized_result` as it was before results became objects: the
+  /// result as JSON text. The first call reports a `deprecated_call` event.
+  ///
+  /// @deprecated Use `getResultById`, or `runTask`.
+  #[napi]
+  pub fn get_optimized_result_json(&self, timeout_ms: Option<u32>) -> Result<String> {
+    self.deprecations.report(LegacyCall::OptimizedResultJson, &self.events);
+    let result = self.results
+      .next(timeout_ms.map(|t| Duration::from_millis(t as u64)))
+      .map_err(RouterError::into_error)?;
+    legacy::result_json(&result).map_err(Error::from_reason)
+  }
+
+  /// Return every result that is ready right now (up to `max_results`) without blocking
+  #[napi]
+  pub fn poll_results(&self, max_results: Option<u32>) -> Result<Vec<OptimizedWorkerResult>> {
+    self.results
+      .drain(max_results.map_or(usize::MAX, |m| m as usize))
+      .map_err(RouterError::into_error)
+  }
+
+  /// Get the result of a specific task, regardless of completion order. A task
+  /// submitted with an `idempotency_key` returns its result every time, until
+  /// `idempotency_ttl_ms` after it completed.
+  #[napi]
+  pub fn get_result_by_id(
+    &self,
+    task_id: String,
+    timeout_ms: Option<u32>
+  ) -> Result<OptimizedWorkerResult> {
+    if let Some(result) = self.idempotency.result(&task_id) {
+      // The kept copy answers every ask, so the queued one is never collected
+      self.results.discard(&task_id);
+      return Ok(result);
+    }
+    self.results
+      .take(&task_id, timeout_ms.map(|t| Duration::from_millis(t as u64)))
+      .map_err(RouterError::into_error)
+  }
+
+  /// Get the next `task_count` results to arrive, whichever tasks they're for.
+  /// The first call reports a `deprecated_call` event.
+  ///
+  /// @deprecated Use `getResultById` for each task ID, or `streamBatchResults`.
+  #[napi]
+  pub fn get_optimized_batch_results(
+    &self,
+    task_count: u32,
+    timeout_ms: Option<u32>
+  ) -> Result<Vec<OptimizedWorkerResult>> {
+    self.deprecations.report(LegacyCall::OptimizedBatchResults, &self.events);
+    let mut results = Vec::new();
+    let deadline = timeout_ms.map(|t| self.clock.now() + Duration::from_millis(t as u64));
+    let batch_start = self.clock.now();
+
+    for _ in 0..task_count {
+      let remaining_time = deadline.map(|d| d.saturating_duration_since(self.clock.now()));
+      if remaining_time.is_some_and(|t| t.is_zero()) {
+        return Err(Error::from_reason("Batch timeout exceeded".to_string()));
+      }
+
+      results.push(self.results.next(remaining_time).map_err(RouterError::into_error)?);
+    }
+
+    // Update batch performance metrics
+    let batch_duration = self.clock
+      .now()
+      .saturating_duration_since(batch_start)
+      .as_millis() as f64;
+    let throughput = ((task_count as f64) / batch_duration) * 1000.0;
+
+    self.events
+      .lock(&self.optimization_stats, "optimization_stats", None)
+      .throughput_tasks_per_sec = throughput;
+
+    Ok(results)
+  }
+
+  /// Call `callback(err, result)` for each of `task_ids` as it completes, instead of
+  /// buffering the whole batch. Resolves with the number of results streamed;
+  /// `timeout_ms` bounds the whole stream. The callback runs in the async context
+  /// of this call, so it sees the `AsyncLocalStorage` stores set around it.
+  #[cfg(feature = "node")]
+  #[napi(
+    ts_args_type = "taskIds: Array<string>, callback: (err: Error | null, result: OptimizedWorkerResult) => void, timeoutMs?: number | undefined | null",
+    ts_return_type = "Promise<number>"
+  )]
+  pub fn stream_batch_results(
+    &self,
+    env: Env,
+    task_ids: Vec<String>,
+    callback: JsFunction,
+    timeout_ms: Option<u32>
+  ) -> Result<AsyncTask<StreamBatch>> {
+    let (results, expected) = ResultRouter::subscribe(&self.results, &task_ids)
+      .map_err(RouterError::into_error)?;
+    Ok(AsyncTask::new(StreamBatch::new(&env, callback, results, expected, timeout_ms)?))
+  }
+
+  /// Get optimization performance statistics as JSON text. The first call
+  /// reports a `deprecated_call` event.
+  ///
+  /// @deprecated Use `getStatsObject`.
+  #[napi]
+  pub fn get_optimization_stats(&self) -> Result<String> {
+    self.deprecations.report(LegacyCall::OptimizationStats, &self.events);
+    Ok(self.stats_json().to_string())
+  }
+
+  /// The statistics `get_optimization_stats` returns, without the notice
+  pub fn stats_json(&self) -> serde_json::Value {
+    self.events.lock(&self.optimization_stats, "optimization_stats", None).to_json()
+  }
+
+  /// Get optimization statistics as a typed object
+  #[napi]
+  pub fn get_stats_object(&self) -> ExecutorStats {
+    self.events.lock(&self.optimization_stats, "optimization_stats", None).to_object()
+  }
+
+  /// Performance cache entries, slowest last execution first
+  #[napi]
+  pub fn get_performance_cache_entries(&self, top_n: Option<u32>) -> Vec<FunctionProfile> {
+    let mut metrics: Vec<PerformanceMetric> = self.events
+      .lock(&self.performance_cache, "performance_cache", None)
+      .values()
+      .cloned()
+      .collect();
+    metrics.sort_by_key(|m| std::cmp::Reverse(m.execution_time));
+    metrics.truncate(top_n.map_or(usize::MAX, |n| n as usize));
+
+    let hot_funcs = self.events.lock(&self.hot_functions, "hot_functions", None);
+    metrics
+      .into_iter()
+      .map(|metric| {
+        let count = hot_funcs.get(&metric.function_hash).copied().unwrap_or(0);
+        Self::function_profile(metric.function_hash.clone(), count, Some(&metric))
+      })
+      .collect()
+  }
+
+  /// Most frequently executed functions, highest count first
+  #[napi]
+  pub fn get_hot_functions(&self, top_n: Option<u32>) -> Vec<FunctionProfile> {
+    let mut counts: Vec<(String, u32)> = self.events
+      .lock(&self.hot_functions, "hot_functions", None)
+      .iter()
+      .map(|(hash, count)| (hash.clone(), *count))
+      .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(top_n.map_or(usize::MAX, |n| n as usize));
+
+    let cache = self.events.lock(&self.performance_cache, "performance_cache", None);
+    counts
+      .into_iter()
+      .map(|(hash, count)| {
+        let metric = cache.get(&hash);
+        Self::function_profile(hash, count, metric)
+      })
+      .collect()
+  }
+
+  /// Clear optimization caches and reset stats
+  #[napi]
+  pub fn reset_optimization_state(&self) -> Result<()> {
+    self.events.lock(&self.performance_cache, "performance_cache", None).clear();
+
+    self.events.lock(&self.hot_functions, "hot_functions", None).clear();
+    self.background.clear();
+
+    *self.events.lock(&self.optimization_stats, "optimization_stats", None) =
+      OptimizationStats::default();
+
+    self.events.lock(&self.warmed_cores, "warmed_cores", None).fill(false);
+
+    self.sub_batches.clear();
+
+    Ok(())
+  }
+
+  /// Warm state for `import_state` in the process taking over from this one:
+  /// registered functions, routing overrides, caches and learned sub-batch
+  /// sizes, as JSON. With `hand_off_queue`, tasks waiting in the shared queue
+  /// that can run elsewhere go too, failing here with a `handed_off` result;
+  /// the importing executor queues them under the same IDs. See `snapshot`.
+  #[napi]
+  pub fn export_state(&self, hand_off_queue: Option<bool>) -> Result<String> {
+    let performance_cache = self.events
+      .lock(&self.performance_cache, "performance_cache", None)
+      .values()
+      .map(|metric| CacheEntry {
+        function_hash: metric.function_hash.clone(),
+        execution_time_ms: metric.execution_time,
+        optimization_type: metric.optimization_type.clone(),
+        success: metric.success,
+      })
+      .collect();
+    let state = ExecutorState {
+      version: snapshot::STATE_VERSION,
+      functions: self.events.lock(&self.functions, "functions", None).export(),
+      routing_overrides: self.get_routing_overrides(),
+      performance_cache,
+      hot_functions: self.events.lock(&self.hot_functions, "hot_functions", None).clone(),
+      sub_batch_sizes: self.sub_batches.sizes(),
+      queued: if hand_off_queue == Some(true) { self.hand_off_queue() } else { Vec::new() },
+    };
+    serde_json::to_string(&state).map_err(|e| Error::from_reason(e.to_string()))
+  }
+
+  /// Take in state from `export_state`, replacing this executor's entries for
+  /// the same functions, overrides and cache keys, and queue any tasks handed
+  /// off with it. Nothing changes if the functions or overrides are invalid; if
+  /// queueing fails, the error says how many handed-off tasks were queued.
+  #[napi]
+  pub fn import_state(&self, state: String) -> Result<StateImport> {
+    let state = snapshot::parse(&state).map_err(Error::from_reason)?;
+    let overrides = state.routing_overrides
+      .into_iter()
+      .map(|(function_hash, backend)| Ok((function_hash, RoutingBackend::parse(&backend)?)))
+      .collect::<std::result::Result<Vec<_>, String>>()
+      .map_err(Error::from_reason)?;
+    let mut imported = StateImport {
+      functions: state.functions.len() as u32,
+      routing_overrides: overrides.len() as u32,
+      cache_entries: state.performance_cache.len() as u32,
+      queued_tasks: 0,
+    };
+    self.events
+      .lock(&self.functions, "functions", None)
+      .import(state.functions)
+      .map_err(Error::from_reason)?;
+    {
+      let mut routing = self.events.lock(&self.routing_overrides, "routing_overrides", None);
+      for (function_hash, backend) in overrides {
+        routing.set(function_hash, backend);
+      }
+    }
+    {
+      let mut cache = self.events.lock(&self.performance_cache, "performance_cache", None);
+      for entry in state.performance_cache {
+        let metric = PerformanceMetric {
+          function_hash: entry.function_hash.clone(),
+          execution_time: entry.execution_time_ms,
+          throughput: 1000.0 / (entry.execution_time_ms as f64),
+          optimization_type: entry.optimization_type,
+          success: entry.success,
+        };
+        cache.insert(entry.function_hash, metric);
+      }
+    }
+    self.events.lock(&self.hot_functions, "hot_functions", None).extend(state.hot_functions);
+    self.sub_batches.seed(state.sub_batch_sizes);
+
+    let handed_off = state.queued.len();
+    for queued in state.queued {
+      let key = queued.idempotency_key.clone();
+      let task = self.handed_off_task(queued)?;
+      if self.claim_key(key.as_deref(), &task.id).is_some() {
+        continue;
+      }
+      let task_id = task.id.clone();
+      if let Err(e) = self.enqueue_task(task) {
+        self.idempotency.release(&task_id);
+        return Err(
+          Error::new(
+            e.status,
+            format!(
+              "Queued {} of {} handed-off tasks: {}",
+              imported.queued_tasks,
+              handed_off,
+              e.reason
+            )
+          )
+        );
+      }
+      imported.queued_tasks += 1;
+    }
+    Ok(imported)
+  }
+
+  /// Force a function onto the `rust` or `js` path, or restore hint-based routing with `auto`
+  #[napi]
+  pub fn set_routing_override(&self, function_hash: String, backend: String) -> Result<()> {
+    let backend = RoutingBackend::parse(&backend).map_err(Error::from_reason)?;
+    self.events
+      .lock(&self.routing_overrides, "routing_overrides", None)
+      .set(function_hash, backend);
+    Ok(())
+  }
+
+  /// Active routing overrides, function hash to backend
+  #[napi]
+  pub fn get_routing_overrides(&self) -> HashMap<String, String> {
+    self.events.lock(&self.routing_overrides, "routing_overrides", None).to_map()
+  }
+
+  /// Run `fallback` on the same input whenever a task with this `function_hash`
+  /// fails on the Rust path, instead of failing it; null removes it. The
+  /// result's `fallback` reports how long both attempts took, and a
+  /// `fallback_ran` event is published.
+  #[napi]
+  pub fn set_fallback(
+    &self,
+    function_hash: String,
+    fallback: Option<FallbackConfig>
+  ) -> Result<()> {
+    self.fallbacks.set(function_hash, fallback).map_err(Error::from_reason)
+  }
+
+  /// Register a function as `name@version` (e.g. `transform@1.2.0`). Each version
+  /// gets its own function hash, so caches and stats never mix between versions.
+  /// Returns the versioned key.
+  #[napi]
+  pub fn register_function(
+    &self,
+    spec: String,
+    function_code: String,
+    optimization_hints: OptimizationHints
+  ) -> Result<String> {
+    let registered = self.events
+      .lock(&self.functions, "functions", None)
+      .register(&spec, function_code, optimization_hints)
+      .map_err(Error::from_reason)?;
+    Ok(registered.key())
+  }
+
+  /// Submit against the highest registered version matching `name@range`
+  /// (e.g. `transform@^1.2`); a bare name picks the latest version
+  #[napi]
+  pub fn submit_registered(
+    &self,
+    spec: String,
+    data: String,
+    meta: Option<serde_json::Value>,
+    timeout_ms: Option<u32>
+  ) -> Result<String> {
+    let function = self.events
+      .lock(&self.functions, "functions", None)
+      .route(&spec)
+      .map_err(Error::from_reason)?;
+
+    let task = self.registered_task(function, data, meta, timeout_ms);
+    let task_id = task.id.clone();
+    self.enqueue_task(task)?;
+    Ok(task_id)
+  }
+
+  /// Update `view`, a running aggregate of a registered function with the
+  /// `aggregate` hint, with only the elements that changed:
+  /// `{"added": [...], "removed": [...], "changed": [{"before": x, "after": y}]}`.
+  /// The result is the updated aggregate; a view's first delta starts from empty.
+  /// Unlike `submit_registered`, the spec never routes to a staged version.
+  #[napi]
+  pub fn submit_delta(
+    &self,
+    spec: String,
+    view: String,
+    delta: String,
+    meta: Option<serde_json::Value>,
+    timeout_ms: Option<u32>
+  ) -> Result<String> {
+    let function = self.events
+      .lock(&self.functions, "functions", None)
+      .resolve(&spec)
+      .cloned()
+      .map_err(Error::from_reason)?;
+    if function.hints.aggregate.is_none() {
+      return Err(
+        Error::from_reason(format!("'{}' has no aggregate hint to apply deltas to", function.key()))
+      );
+    }
+
+    let mut task = self.registered_task(function, delta, meta, timeout_ms);
+    task.view = Some(view);
+    let task_id = task.id.clone();
+    self.enqueue_task(task)?;
+    Ok(task_id)
+  }
+
+  /// Forget an incremental view, so its next delta starts from empty. Returns
+  /// false if there was no such view.
+  #[napi]
+  pub fn drop_view(&self, view: String) -> bool {
+    self.views.remove(&view)
+  }
+
+  /// Stage a new version of a registered function that receives `traffic_fraction`
+  /// (0..=1) of `submit_registered` calls whose range it satisfies. Returns its key.
+  #[napi]
+  pub fn stage_function(
+    &self,
+    spec: String,
+    function_code: String,
+    optimization_hints: OptimizationHints,
+    traffic_fraction: f64
+  ) -> Result<String> {
+    let staged = self.events
+      .lock(&self.functions, "functions", None)
+      .stage(&spec, function_code, optimization_hints, traffic_fraction)
+      .map_err(Error::from_reason)?;
+    Ok(staged.key())
+  }
+
+  /// Change the traffic fraction of a staged rollout
+  #[napi]
+  pub fn set_staged_traffic(&self, name: String, traffic_fraction: f64) -> Result<()> {
+    self.events
+      .lock(&self.functions, "functions", None)
+      .set_staged_traffic(&name, traffic_fraction)
+      .map_err(Error::from_reason)
+  }
+
+  /// Register the staged version of `name` so it serves all matching traffic
+  #[napi]
+  pub fn promote_staged(&self, name: String) -> Result<String> {
+    let promoted = self.events
+      .lock(&self.functions, "functions", None)
+      .promote(&name)
+      .map_err(Error::from_reason)?;
+    Ok(promoted.key())
+  }
+
+  /// Drop the staged version of `name`, returning all traffic to registered versions
+  #[napi]
+  pub fn rollback_staged(&self, name: String) -> Result<String> {
+    let dropped = self.events
+      .lock(&self.functions, "functions", None)
+      .rollback(&name)
+      .map_err(Error::from_reason)?;
+    Ok(dropped.key())
+  }
+
+  /// Stable and staged versions of `name` with their separate learning-state profiles
+  #[napi]
+  pub fn get_rollout_status(&self, name: String) -> RolloutStatus {
+    let (stable_hash, staged_hash, traffic_fraction) = {
+      let functions = self.events.lock(&self.functions, "functions", None);
+      let stable = functions.resolve(&name).ok().map(|f| f.key());
+      let staged = functions.staged(&name);
+      (
+        stable,
+        staged.map(|r| r.function.key()),
+        staged.map_or(0.0, |r| r.traffic_fraction),
+      )
+    };
+
+    let hot_functions = self.events.lock(&self.hot_functions, "hot_functions", None);
+    let cache = self.events.lock(&self.performance_cache, "performance_cache", None);
+    let profile = |hash: String| {
+      let count = hot_functions.get(&hash).copied().unwrap_or(0);
+      let metric = cache.get(&hash);
+      Self::function_profile(hash, count, metric)
+    };
+
+    RolloutStatus {
+      name,
+      stable: stable_hash.map(profile),
+      staged: staged_hash.map(profile),
+      traffic_fraction,
+    }
+  }
+
+  /// Remove `name@version`, or every version of a bare `name`
+  #[napi]
+  pub fn unregister_function(&self, spec: String) -> Result<()> {
+    self.events
+      .lock(&self.functions, "functions", None)
+      .unregister(&spec)
+      .map_err(Error::from_reason)
+  }
+
+  /// Registered functions as sorted `name@version` keys
+  #[napi]
+  pub fn list_functions(&self) -> Vec<String> {
+    self.events.lock(&self.functions, "functions", None).list()
+  }
+
+  /// Cancel a task: it is skipped if still queued, and its result is dropped if running
+  #[napi]
+  pub fn cancel_task(&self, task_id: String) {
+    // A finished or unknown task has nothing left for a worker to drop
+    if self.tasks.cancel(&task_id) {
+      self.cancellations.cancel(task_id);
+    }
+  }
+
+  /// Cancel every task submitted so far, queued or running
+  #[napi]
+  pub fn cancel_all(&self) {
+    self.tasks.cancel_all();
+    self.cancellations.cancel_all();
+  }
+
+  /// Where a submitted task is: queued (with its queue position), running, or finished.
+  /// Errors for unknown IDs and for tasks that finished long ago.
+  #[napi]
+  pub fn get_task_status(&self, task_id: String) -> Result<TaskStatus> {
+    self.tasks
+      .status(&task_id)
+      .ok_or_else(|| Error::from_reason(format!("Unknown task ID '{}'", task_id)))
+  }
+
+  /// Tasks waiting in the shared queue, across every priority. Tasks held for a
+  /// group, a delay or a limit aren't counted until they're queued.
+  #[napi]
+  pub fn get_queue_depth(&self) -> u32 {
+    self.events
+      .lock(&self.task_sender, "task_sender", None)
+      .as_ref()
+      .map_or(0, |sender| sender.len().min(u32::MAX as usize) as u32)
+  }
+
+  /// Backlog of the shared queue: depth per priority, tasks held back, and how
+  /// long the oldest queued task has waited. `include_task_ids` also lists the
+  /// queued tasks in the order workers will take them. Tasks in reserved
+  /// batches aren't counted; fire-and-forget tasks count towards depth only.
+  #[napi]
+  pub fn get_queue_stats(&self, include_task_ids: Option<bool>) -> QueueStats {
+    let [critical, high, normal, low] = {
+      let sender = self.events.lock(&self.task_sender, "task_sender", None);
+      Priority::DESCENDING.map(|priority| {
+        sender.as_ref().map_or(0, |sender| sender.depth(priority).min(u32::MAX as usize) as u32)
+      })
+    };
+    let (waiting, oldest, task_ids) = self.tasks.shared_queue(include_task_ids.unwrap_or(false));
+    QueueStats {
+      pending: critical.saturating_add(high).saturating_add(normal).saturating_add(low),
+      critical,
+      high,
+      normal,
+      low,
+      waiting: waiting.min(u32::MAX as usize) as u32,
+      oldest_task_age_ms: oldest.map(|age| age.as_secs_f64() * 1000.0),
+      task_ids,
+    }
+  }
+
+  /// Estimated native memory held by queued tasks, uncollected results and the
+  /// performance cache, as checked against `memory_watermark_mb`
+  #[napi]
+  pub fn get_memory_usage(&self) -> MemoryUsage {
+    let sender = self.events.lock(&self.task_sender, "task_sender", None);
+    self.memory_usage(sender.as_ref())
+  }
+
+  /// Call `callback(usage)` each time estimated native memory goes over
+  /// `memory_watermark_mb`, e.g. to run `global.gc()` or shed caches of its own;
+  /// null removes it. The callback runs in the async context of this call and
+  /// doesn't keep the process alive.
+  #[cfg(feature = "node")]
+  #[napi(ts_args_type = "callback: ((usage: MemoryUsage) => void) | null")]
+  pub fn on_memory_pressure(&self, env: Env, callback: Option<JsFunction>) -> Result<()> {
+    let Some(callback) = callback else {
+      self.memory.set_listener(None);
+      return Ok(());
+    };
+    let callback = async_context::bind(&env, callback, "threader_memory_pressure")?;
+    let mut callback: ThreadsafeFunction<MemoryUsage, ErrorStrategy::Fatal> =
+      callback.create_threadsafe_function(0, |ctx: ThreadSafeCallContext<MemoryUsage>| {
+        Ok(vec![ctx.value])
+      })?;
+    callback.unref(&env)?;
+    self.set_memory_pressure_listener(
+      Some(
+        Box::new(move |usage| {
+          callback.call(usage, ThreadsafeFunctionCallMode::NonBlocking);
+        })
+      )
+    );
+    Ok(())
+  }
+
+  /// `onMemoryPressure` for Rust callers. The listener runs on the submitting
+  /// thread while it holds the queue, so it mustn't submit tasks itself.
+  pub fn set_memory_pressure_listener(&self, listener: Option<PressureListener>) {
+    self.memory.set_listener(listener);
+  }
+
+  /// Let at most `limit` tasks with this `function_hash` run at once; the rest wait
+  /// in the queue. Null lifts the limit. Reserved batches are exempt.
+  #[napi]
+  pub fn set_concurrency_limit(&self, function_hash: String, limit: Option<u32>) -> Result<()> {
+    self.limits
+      .set(&function_hash, limit.map(|limit| limit as usize))
+      .map_err(Error::from_reason)
+  }
+
+  /// Concurrency limit set for `function_hash`, if any
+  #[napi]
+  pub fn get_concurrency_limit(&self, function_hash: String) -> Option<u32> {
+    self.limits.get(&function_hash).map(|limit| limit.min(u32::MAX as usize) as u32)
+  }
+
+  /// Let tasks with this `function_hash` start at most `tasks_per_second` times a
+  /// second, `burst` at once; the rest wait in the queue. Null lifts the limit.
+  #[napi]
+  pub fn set_function_rate_limit(
+    &self,
+    function_hash: String,
+    limit: Option<RateLimitConfig>
+  ) -> Result<()> {
+    self.rate_limits
+      .set(RateKey::Function(function_hash), limit.as_ref())
+      .map_err(Error::from_reason)
+  }
+
+  /// Rate limit set for `function_hash`, if any
+  #[napi]
+  pub fn get_function_rate_limit(&self, function_hash: String) -> Option<RateLimitConfig> {
+    self.rate_limits.get(&RateKey::Function(function_hash))
+  }
+
+  /// Rate limit shared by every task submitted with this `tenant` hint; a task
+  /// under both a function and a tenant limit needs a token from each
+  #[napi]
+  pub fn set_tenant_rate_limit(
+    &self,
+    tenant: String,
+    limit: Option<RateLimitConfig>
+  ) -> Result<()> {
+    self.rate_limits.set(RateKey::Tenant(tenant), limit.as_ref()).map_err(Error::from_reason)
+  }
+
+  /// Rate limit set for `tenant`, if any
+  #[napi]
+  pub fn get_tenant_rate_limit(&self, tenant: String) -> Option<RateLimitConfig> {
+    self.rate_limits.get(&RateKey::Tenant(tenant))
+  }
+
+  /// Open a child executor for one request: tasks submitted with its ID as the
+  /// `child` hint share its concurrency, byte and time budget, carved out of
+  /// this pool. Returns the ID.
+  #[napi]
+  pub fn open_child(&self, options: Option<ChildOptions>) -> Result<String> {
+    let watermark = self.memory.limit();
+    self.children
+      .open(&options.unwrap_or_default(), self.slots.target(), watermark)
+      .map_err(Error::from_reason)
+  }
+
+  /// Close a child executor when its request ends: its tasks are cancelled,
+  /// queued or running, and new ones are refused. `false` if it was unknown or
+  /// already closed.
+  #[napi]
+  pub fn close_child(&self, child_id: String) -> bool {
+    let Some(task_ids) = self.children.close(&child_id) else {
+      return false;
+    };
+    for task_id in task_ids {
+      self.cancel_task(task_id);
+    }
+    true
+  }
+
+  /// Budget and usage of a child executor; errors once it's closed
+  #[napi]
+  pub fn get_child_status(&self, child_id: String) -> Result<ChildStatus> {
+    self.children
+      .status(&child_id)
+      .ok_or_else(|| Error::from_reason(format!("Unknown or closed child executor '{}'", child_id)))
+  }
+
+  /// Close task group `name` to new members. Once every member has finished, tasks
+  /// submitted with `after: name` are queued, including ones submitted later.
+  #[napi]
+  pub fn seal_group(&self, name: String) {
+    self.groups.seal(&name);
+  }
+
+  /// Members and waiting tasks of a task group, or null if no task has named it
+  #[napi]
+  pub fn get_group_status(&self, name: String) -> Option<GroupStatus> {
+    self.groups.status(&name)
+  }
+
+  /// Forget a completed task group so its name can be reused. Returns false for
+  /// unknown groups and errors for ones still pending.
+  #[napi]
+  pub fn remove_group(&self, name: String) -> Result<bool> {
+    self.groups.remove(&name).map_err(Error::from_reason)
+  }
+
+  /// Resolve once every member of task group `name` submitted so far has
+  /// finished (true), or with false after `timeout_ms` or on shutdown. Rejects
+  /// if no task has named the group. The group needn't be sealed.
+  #[cfg(feature = "node")]
+  #[napi(ts_return_type = "Promise<boolean>")]
+  pub fn wait_for_group(&self, name: String, timeout_ms: Option<u32>) -> AsyncTask<GroupWait> {
+    AsyncTask::new(self.group_waiter(name, timeout_ms))
+  }
+
+  /// `wait_for_group` without the promise; `wait` on the result to block
+  pub fn group_waiter(&self, name: String, timeout_ms: Option<u32>) -> GroupWait {
+    GroupWait::new(Arc::clone(&self.groups), name, timeout_ms)
+  }
+
+  /// Cancel every unfinished member of task group `name`, queued or running, and
+  /// return how many there were. Tasks waiting on the group with `after` still
+  /// run once it completes.
+  #[napi]
+  pub fn cancel_group(&self, name: String) -> u32 {
+    let members = self.groups.pending_members(&name);
+    let count = members.len();
+    for task_id in members {
+      self.cancel_task(task_id);
+    }
+    count.min(u32::MAX as usize) as u32
+  }
+
+  /// Cap the pool's aggregate CPU use at `max_cpu_fraction` (in `(0, 1]`) of the
+  /// machine's cores by idling workers between tasks, or lift the cap with null
+  #[napi]
+  pub fn set_max_cpu_fraction(&self, max_cpu_fraction: Option<f64>) -> Result<()> {
+    self.cpu_budget.configure(max_cpu_fraction).map_err(Error::from_reason)
+  }
+
+  /// Active CPU cap as a fraction of the machine's cores, if any
+  #[napi]
+  pub fn get_max_cpu_fraction(&self) -> Option<f64> {
+    self.cpu_budget.max_cpu_fraction()
+  }
+
+  /// Enable or disable throttling detection (off by default). Enabling it
+  /// calibrates against the CPU as it runs then; while later probes keep finding
+  /// it slower, fewer workers take tasks and `throttling_detected`/
+  /// `throttling_recovered` events are emitted.
+  #[napi]
+  pub fn set_throttle_detection(&self, enabled: bool) {
+    self.throttle.set_enabled(enabled);
+  }
+
+  /// Workers currently taking tasks; below `worker_count` while throttled
+  #[napi]
+  pub fn get_effective_parallelism(&self) -> u32 {
+    self.throttle.effective_parallelism() as u32
+  }
+
+  /// Enable or disable background passes run by idle workers (see
+  /// `background_optimization`); disabling drops the functions they compiled
+  #[napi]
+  pub fn set_background_optimization(&self, enabled: bool) {
+    self.background.set_enabled(enabled);
+  }
+
+  /// Background passes run so far and the time spent on each step
+  #[napi]
+  pub fn get_background_stats(&self) -> BackgroundStats {
+    self.background.stats()
+  }
+
+  /// Readiness probe: runs a tiny canary task through the shared queue and a
+  /// worker, resolving within `timeout_ms` (default 1s) with its latency and a
+  /// "healthy", "degraded" or "unhealthy" status. Never rejects.
+  #[cfg(feature = "node")]
+  #[napi(ts_return_type = "Promise<HealthStatus>")]
+  pub fn health_check(&self, timeout_ms: Option<u32>) -> AsyncTask<HealthCheck> {
+    AsyncTask::new(self.start_health_check(timeout_ms))
+  }
+
+  /// Submit the `health_check` canary; `wait` on the result for its status
+  pub fn start_health_check(&self, timeout_ms: Option<u32>) -> HealthCheck {
+    let (function_code, data, hints) = health::canary_task();
+    let queued_tasks = self.events
+      .lock(&self.task_sender, "task_sender", None)
+      .as_ref()
+      .map_or(0, |sender| sender.len() as u32);
+
+    // Timeout and routing overrides would make the probe test something else
+    let probe = {
+      let (reply_tx, reply_rx) = channel::bounded(1);
+      let task = OptimizedWorkerTask {
+        id: self.generate_task_id(),
+        function_code,
+        data,
+        timeout_ms: None,
+        optimization_hints: hints,
+        meta: None,
+        reply: Reply::Channel(reply_tx),
+        epoch: self.cancellations.current_epoch(),
+        view: None,
+        columnar: None,
+        borrowed: None,
+        expires_at: None,
+        worker: None,
+        sub_batch: None,
+      };
+      self
+        .enqueue_task(task)
+        .map(|()| reply_rx)
+        .map_err(|e| e.reason)
+    };
+
+    HealthCheck::new(
+      probe,
+      timeout_ms,
+      self.slots.target() as u32,
+      self.throttle.effective_parallelism() as u32,
+      queued_tasks,
+      SharedClock::clone(&self.clock)
+    )
+  }
+
+  /// Push counters, timers and gauges to a statsd/DogStatsD agent every
+  /// `interval_ms`, replacing any running reporter
+  #[napi]
+  pub fn start_statsd_reporter(&self, config: StatsdConfig) -> Result<()> {
+    let stats = Arc::clone(&self.optimization_stats);
+    let events = Arc::clone(&self.events);
+    let throttle = Arc::clone(&self.throttle);
+    let slots = Arc::clone(&self.slots);
+
+    let reporter = StatsdReporter::start(config, move || {
+      let stats = events.lock(&stats, "optimization_stats", None);
+      vec![
+        Metric::Counter("executions", stats.total_executions),
+        Metric::Counter("rust_native_hits", stats.rust_native_hits),
+        Metric::Counter("js_worker_fallbacks", stats.js_worker_fallbacks),
+        Metric::Counter("cache_hits", stats.cache_hits),
+        Metric::Counter("binary_results", stats.binary_results),
+        Metric::Counter("binary_bytes", stats.binary_bytes),
+        Metric::Timer("latency", stats.avg_latency_ms),
+        Metric::Gauge("throughput", stats.throughput_tasks_per_sec),
+        Metric::Gauge("workers", slots.target() as f64),
+        Metric::Gauge("workers.effective", throttle.effective_parallelism() as f64)
+      ]
+    }).map_err(Error::from_reason)?;
+
+    // The previous reporter, if any, stops as it drops
+    *self.events.lock(&self.statsd, "statsd", None) = Some(reporter);
+    Ok(())
+  }
+
+  /// Stop pushing to statsd; a no-op if no reporter is running
+  #[napi]
+  pub fn stop_statsd_reporter(&self) {
+    self.events.lock(&self.statsd, "statsd", None).take();
+  }
+
+  /// Enable chaos mode with JSON-serialized `FaultConfig` rates, or disable it with null
+  #[napi]
+  pub fn set_fault_injection(&self, config: Option<String>) -> Result<()> {
+    let config = config
+      .map(|c| serde_json::from_str::<FaultConfig>(&c))
+      .transpose()
+      .map_err(|e| Error::from_reason(format!("Invalid fault config: {}", e)))?;
+
+    self.faults.configure(config);
+    Ok(())
+  }
+
+  /// Take buffered events as JSON strings: diagnostics (poisoned locks, worker failures)
+  /// and `partial_result` chunks of tasks submitted with `chunk_size`
+  #[napi]
+  pub fn drain_events(&self) -> Result<Vec<String>> {
+    self.events
+      .drain()
+      .iter()
+      .map(|event| {
+        serde_json::to_string(event).map_err(|e| Error::from_reason(e.to_string()))
+      })
+      .collect()
+  }
+
+  /// Enhanced worker thread with optimization awareness, serving from `held`
+  /// until it retires or the pool shuts down. A panic leaves its end of the
+  /// queue in `held`.
+  fn optimized_worker_thread(
+    worker_id: usize,
+    context: &WorkerContext,
+    held: &mut Option<WorkerLanes>
+  ) {
+    let WorkerContext {
+      queue_probe,
+      result_sender,
+      faults,
+      tasks,
+      in_flight,
+      groups,
+      graphs,
+      dedup,
+      children,
+      limits,
+      rate_limits,
+      timers,
+      reservations,
+      cpu_budget,
+      throttle,
+      background,
+      slots,
+      heartbeats,
+      sub_batches,
+      starvation,
+      events,
+      clock,
+      idle_strategy,
+      scratch_limit,
+      optimization_stats,
+      ..
+    } = context;
+    scratch::set_limit(*scratch_limit);
+
+    // Stats are aggregated locally and merged once per burst of work
+    let mut local_stats = OptimizationStats::default();
+    // Idle time owed under the CPU budget, carried over until worth sleeping
+    let mut cpu_debt = Duration::ZERO;
+    let mut pass: Option<Pass> = None;
+    let send_shared = |result: OptimizedWorkerResult| {
+      result_sender.send(result);
+    };
+    let Some(mut lanes) = held.as_mut() else {
+      return;
+    };
+
+    loop {
+      heartbeats.beat(worker_id);
+      // A reserved worker serves only its reservation's queue
+      let reservation = reservations.assigned(worker_id);
+      // The rest of a sub-batch runs in the same pass, skipping the checks below
+      let next = match (pass.as_ref(), reservation.as_ref()) {
+        (Some(pass), None) =>
+          lanes
+            .next_in_pass(pass.priority, pass.sub_batch, clock.now())
+            .map(|task| (task, pass.priority)),
+        _ => None,
+      };
+      if next.is_none() {
+        if let Some(pass) = pass.take() {
+          sub_batches.record(&pass, clock.now());
+        }
+
+        if lanes.is_shut_down() {
+          break;
+        }
+
+        // Past the target after the pool shrank: give the slot back and stop,
+        // unless a reservation is counting on us
+        if reservation.is_none() && slots.is_surplus(worker_id) {
+          local_stats.flush_into(optimization_stats, events, worker_id);
+          lanes.hand_back();
+          match held.take().and_then(|lanes| slots.retire(lanes)) {
+            Some(back) => lanes = held.insert(back),
+            None => {
+              events.publish(
+                "worker_retired",
+                Some(worker_id),
+                format!("Worker {} retired after the pool shrank", worker_id)
+              );
+              break;
+            }
+          }
+        }
+
+        // Delayed and rate-limited tasks whose wait is over go on the shared queue
+        timers.release_due();
+        rate_limits.release_due();
+        if let Some(starvation) = starvation {
+          starvation.check(events, Some(worker_id));
+        }
+
+        // A task a full queue dropped (`overflow_policy: "drop_oldest"`) fails
+        // unexecuted, along with whatever depends on it
+        if let Some((task, _, reason)) = queue_probe.try_recv_shed() {
+          let _in_flight = in_flight.ending();
+          let _membership = task.optimization_hints.group
+            .clone()
+            .map(|group| Membership::new(groups, group, task.id.clone()));
+          let _child_task = task.optimization_hints.child
+            .clone()
+            .map(|child| ChildTask::new(children, child, task.id.clone()));
+          let skipped = graphs.finish(&task.id, None);
+          dedup.unexecuted(&task.id);
+          let reply = task.reply.clone();
+          let (error, optimization_used) = match reason {
+            ShedReason::Overflow =>
+              ("Dropped from the full task queue to make room for newer tasks", "queue_overflow"),
+            ShedReason::HandedOff =>
+              ("Handed off to another executor by export_state", "handed_off"),
+          };
+          let result = Outcome {
+            result: Err(error.to_string()),
+            optimization_used: optimization_used.to_string(),
+            duration: 0,
+            worker_id,
+            cache_hit: false,
+            timed_out: false,
+            fallback: None,
+          }.into_result(task, faults.seed());
+          reply.send(result, result_sender);
+          Self::fail_skipped(skipped, worker_id, faults.seed(), tasks, in_flight, send_shared);
+          continue;
+        }
+
+        // Sit out while the CPU is throttled, unless a reservation is counting on us
+        if reservation.is_none() && throttle.is_parked(worker_id) {
+          lanes.stand_aside();
+          if !lanes.sleep(Duration::from_millis(10)) {
+            break;
+          }
+          continue;
+        }
+      }
+      let task = match (next, reservation.as_ref()) {
+        (Some((task, priority)), _) => Ok((task, QueueId::Shared(priority))),
+        (None, Some(reservation)) => {
+          lanes.stand_aside();
+          reservation.try_recv().map(|task| (task, QueueId::Reserved(reservation.id())))
+        }
+        (None, None) =>
+          lanes.try_recv(clock.now()).map(|(task, priority)| (task, QueueId::Shared(priority))),
+      };
+      // A task over its rate limit waits for a token, and one whose function or
+      // child executor is at its concurrency limit waits for a running one to
+      // finish; the slots are freed when this iteration ends
+      let (task, _limit_slot, _child_slot) = match task {
+        Ok((task, QueueId::Shared(priority))) => {
+          let queue = QueueId::Shared(priority);
+          let park = |task: &OptimizedWorkerTask| tasks.park(&task.id, queue);
+          let Some(task) = rate_limits.admit(task, priority, park) else {
+            continue;
+          };
+          let Some((task, limit_slot)) = limits.admit(task, priority, park) else {
+            continue;
+          };
+          match children.admit(task, priority, park) {
+            Some((task, child_slot)) => (Ok((task, queue)), limit_slot, child_slot),
+            None => {
+              continue;
+            }
+          }
+        }
+        task => (task, None, None),
+      };
+      // Released when this iteration ends, however it ends
+      let _reserved_slot = match (&task, reservation.as_ref()) {
+        (Ok(_), Some(reservation)) => Some(reservations.track(reservation)),
+        _ => None,
+      };
+      match task {
+        Ok((task, queue)) => {
+          pass = match (pass.take(), task.sub_batch, queue) {
+            (Some(mut pass), _, _) => {
+              pass.tasks += 1;
+              Some(pass)
+            }
+            (None, Some(sub_batch), QueueId::Shared(priority)) =>
+              Some(Pass {
+                sub_batch,
+                priority,
+                function_hash: task.optimization_hints.function_hash.clone(),
+                tasks: 1,
+                start: clock.now(),
+              }),
+            _ => None,
+          };
+          let flow = Self::serve_task(worker_id, context, task, queue, &mut local_stats);
+          let ControlFlow::Continue(busy) = flow else {
+            panic!("Injected worker panic on worker {}", worker_id);
+          };
+          if let Some(busy) = busy {
+            slots.record_busy(busy);
+          }
+          // Pay back time over the CPU budget, still waking immediately on shutdown
+          let pause = busy.and_then(|busy| cpu_budget.pause_after(busy, &mut cpu_debt));
+          if let Some(pause) = pause && !lanes.sleep(pause) {
+            break;
+          }
+        }
+        Err(channel::TryRecvError::Empty) => {
+          // Another worker may have ended the burst this one's counts belong to
+          local_stats.flush_into(optimization_stats, events, worker_id);
+          // Reserved workers stay ready for their batch
+          if reservation.is_none() {
+            background.run_if_due(worker_id);
+          }
+          match idle_strategy {
+            IdleStrategy::Sleep => {
+              // Until woken for new work, or a delayed or rate-limited task or
+              // a background pass is due
+              let due = [timers.next_due(), rate_limits.next_ready()].into_iter().flatten().min();
+              let timeout = [due.map(|due| due.min(MAX_IDLE_WAIT)), background.next_due()]
+                .into_iter()
+                .flatten()
+                .min();
+              if !lanes.wait(reservation.is_none(), timeout) {
+                break;
+              }
+            }
+            IdleStrategy::Yield => thread::yield_now(),
+            IdleStrategy::Spin => std::hint::spin_loop(),
+          }
+        }
+        Err(channel::TryRecvError::Disconnected) => {
+          break;
+        }
+      }
+    }
+    local_stats.flush_into(optimization_stats, events, worker_id);
+  }
+
+  /// Handle a task taken off a queue, through to delivering its result.
+  /// `Break` when the worker must stop; otherwise the time spent executing the
+  /// task, if it ran.
+  fn serve_task(
+    worker_id: usize,
+    context: &WorkerContext,
+    task: OptimizedWorkerTask,
+    queue: QueueId,
+    local_stats: &mut OptimizationStats
+  ) -> ControlFlow<(), Option<Duration>> {
+    let WorkerContext {
+      queue_probe,
+      result_sender,
+      optimization_stats,
+      performance_cache,
+      hot_functions,
+      clock,
+      faults,
+      events,
+      cancellations,
+      tasks,
+      in_flight,
+      deadlines,
+      groups,
+      graphs,
+      dedup,
+      children,
+      views,
+      throttle,
+      background,
+      fallbacks,
+      strict,
+      stats_flush_interval,
+      ..
+    } = context;
+    let send_shared = |result: OptimizedWorkerResult| {
+      result_sender.send(result);
+    };
+    // Counted as finished once the worker is done with the task
+    let _in_flight = in_flight.ending();
+    // Finishing the last member of a sealed group releases the tasks waiting on it
+    let _membership = task.optimization_hints.group
+      .clone()
+      .map(|group| Membership::new(groups, group, task.id.clone()));
+    // Frees the task's share of its child executor's byte budget
+    let _child_task = task.optimization_hints.child
+      .clone()
+      .map(|child| ChildTask::new(children, child, task.id.clone()));
+
+    if cancellations.take(&task.id, task.epoch) || !task.claim_input() {
+      // Cancelled or released while queued: drop it unexecuted, failing whatever
+      // depends on it
+      tasks.skip(&task.id, queue);
+      let skipped = graphs.finish(&task.id, None);
+      dedup.unexecuted(&task.id);
+      Self::fail_skipped(skipped, worker_id, faults.seed(), tasks, in_flight, send_shared);
+      return ControlFlow::Continue(None);
+    }
+    // Stale by the time a worker got to it, or its child executor is gone: fail
+    // it unexecuted, along with whatever depends on it
+    let unstartable = if task.expired(clock.now()) {
+      let max_age = task.optimization_hints.max_queue_age_ms.unwrap_or_default();
+      events.publish(
+        "task_expired",
+        Some(worker_id),
+        format!("Task {} waited over {}ms to start", task.id, max_age)
+      );
+      Some((format!("Expired after waiting over {}ms to start", max_age), "queue_expired"))
+    } else {
+      children.check(&task).err().map(|error| (error, "child_closed"))
+    };
+    if let Some((error, optimization_used)) = unstartable {
+      tasks.evict(&task.id, queue);
+      let skipped = graphs.finish(&task.id, None);
+      dedup.unexecuted(&task.id);
+      let reply = task.reply.clone();
+      let result = Outcome {
+        result: Err(error),
+        optimization_used: optimization_used.to_string(),
+        duration: 0,
+        worker_id,
+        cache_hit: false,
+        timed_out: false,
+        fallback: None,
+      }.into_result(task, faults.seed());
+      reply.send(result, result_sender);
+      Self::fail_skipped(skipped, worker_id, faults.seed(), tasks, in_flight, send_shared);
+      return ControlFlow::Continue(None);
+    }
+
+    let start_time = clock.now();
+    tasks.start(&task.id, queue, worker_id);
+    // Reported as timed out once its deadline passes, however long it runs on
+    let watch = task.timeout_ms.map(|timeout_ms| {
+      let overrun = Self::overrun_result(&task, worker_id, timeout_ms);
+      let (reply, sink, epoch) = (task.reply.clone(), result_sender.clone(), task.epoch);
+      let (tasks, cancellations) = (Arc::clone(tasks), Arc::clone(cancellations));
+      let due = start_time + Duration::from_millis(timeout_ms);
+      Deadlines::watch(deadlines, due, move || {
+        if !cancellations.is_cancelled(&overrun.id, epoch) {
+          tasks.finish(&overrun.id, false);
+          reply.send(overrun, &sink);
+        }
+      })
+    });
+
+    // Check cache for hot functions
+    let cache_hit = if task.optimization_hints.is_hot_function {
+      events
+        .lock(performance_cache, "performance_cache", Some(worker_id))
+        .contains_key(&task.optimization_hints.function_hash)
+    } else {
+      false
+    };
+
+    let fault = faults.next_worker_fault();
+    match fault {
+      Some(WorkerFault::Panic) => {
+        // Simulate a crashed worker: the task is lost and the worker panics
+        // once back in its loop, until the supervisor respawns it
+        tasks.finish(&task.id, false);
+        let skipped = graphs.finish(&task.id, None);
+        dedup.unexecuted(&task.id);
+        let seed = faults.seed();
+        Self::fail_skipped(skipped, worker_id, seed, tasks, in_flight, send_shared);
+        return ControlFlow::Break(());
+      }
+      Some(WorkerFault::Delay(delay_ms)) => {
+        thread::sleep(Duration::from_millis(delay_ms));
+      }
+      _ => {}
+    }
+
+    // Time this task sat paused while critical tasks ran between its chunks
+    let preempted = AtomicU64::new(0);
+    let mut fallback = None;
+    let (mut result, optimization_used) = if fault == Some(WorkerFault::Timeout) {
+      (Err("Task timed out (injected fault)".to_string()), "fault_injected".to_string())
+    } else if let Some(view) = task.view.as_deref() {
+      // Deltas are folded in natively whatever the function's backend
+      match Self::execute_delta(views, view, &task) {
+        Ok(result) => (Ok(FastPathOutput::Json(result)), "rust_incremental".to_string()),
+        Err(err) => (Err(err), "rust_failed".to_string()),
+      }
+    } else if task.optimization_hints.should_use_rust {
+      // Use enhanced Rust native execution
+      let publish = |partial: PartialResult| {
+        let message = serde_json::to_string(&partial).unwrap_or_default();
+        events.publish("partial_result", Some(worker_id), message);
+      };
+      let preempt = || {
+        let paused = Self::preempt(worker_id, context, &task.id, queue);
+        preempted.fetch_add(paused.as_nanos() as u64, Ordering::Relaxed);
+        if let Some(watch) = &watch {
+          watch.postpone(paused);
+        }
+      };
+      let compiled = background.compiled(&task);
+      let compiled = compiled.as_deref();
+      let outcome = if fault == Some(WorkerFault::NativePanic) {
+        // Panic where the native op runs, as a bug in one would
+        Self::catch_native_panic(|| panic!("Injected native panic on worker {}", worker_id))
+      } else {
+        Self::execute_rust_optimized(&task, worker_id, *strict, compiled, &publish, &preempt)
+      };
+      match outcome {
+        Ok(result) => (Ok(result), "rust_optimized".to_string()),
+        Err(err) => {
+          // The function's fallback, if any, runs on the same input in its place
+          let primary = clock
+            .now()
+            .saturating_duration_since(start_time)
+            .saturating_sub(Duration::from_nanos(preempted.load(Ordering::Relaxed)));
+          let execute = |fallback: &OptimizedWorkerTask| {
+            Self::execute_rust_optimized(fallback, worker_id, *strict, None, &publish, &preempt)
+          };
+          match fallbacks.run(&task, &err, primary, clock, execute) {
+            Some((result, attempt)) => {
+              let message = attempt.describe(&task.id, result.is_ok());
+              events.publish("fallback_ran", Some(worker_id), message);
+              fallback = Some(attempt);
+              let optimization_used = if result.is_ok() { "rust_fallback" } else { "rust_failed" };
+              (result, optimization_used.to_string())
+            }
+            None => (Err(err), "rust_failed".to_string()),
+          }
+        }
+      }
+    } else {
+      // Signal for JavaScript worker with optimization hints
+      (Err("NEEDS_JS_WORKER_OPTIMIZED".to_string()), "needs_js_worker".to_string())
+    };
+
+    // Paused time doesn't count towards the task's duration or timeout
+    let duration = clock
+      .now()
+      .saturating_duration_since(start_time)
+      .saturating_sub(Duration::from_nanos(preempted.into_inner()))
+      .as_millis() as u64;
+
+    // Past its deadline the watchdog has reported the timeout already, and the
+    // late result is discarded
+    let reported = watch.is_some_and(|watch| !watch.finish());
+    // An overrun finishing before the watchdog saw it is reported here instead
+    let timed_out = fault == Some(WorkerFault::Timeout) || match task.timeout_ms {
+      Some(timeout_ms) if reported || duration > timeout_ms => {
+        result = Err(Self::timeout_error(timeout_ms, duration));
+        true
+      }
+      _ => false,
+    };
+
+    // Health-check canaries stay out of stats and learning state
+    let canary = task.optimization_hints.function_hash == health::CANARY_FUNCTION_HASH;
+
+    if !canary {
+      // Update hot function tracking
+      let hash = &task.optimization_hints.function_hash;
+      *events
+        .lock(hot_functions, "hot_functions", Some(worker_id))
+        .entry(hash.clone())
+        .or_insert(0) += 1;
+      background.ran();
+
+      // Record performance metric
+      let metric = PerformanceMetric {
+        function_hash: hash.clone(),
+        execution_time: duration,
+        throughput: 1000.0 / (duration as f64),
+        optimization_type: optimization_used.clone(),
+        success: result.is_ok(),
+      };
+      events
+        .lock(performance_cache, "performance_cache", Some(worker_id))
+        .insert(hash.clone(), metric);
+    }
+
+    let epoch = task.epoch;
+    let reply = task.reply.clone();
+    let worker_result = Outcome {
+      result,
+      optimization_used,
+      duration,
+      worker_id,
+      cache_hit,
+      timed_out,
+      fallback,
+    }.into_result(task, faults.seed());
+
+    // Merge before delivering the last result of a burst, so callers that
+    // read stats right after collecting a batch usually see it accounted for;
+    // counts of workers that finished earlier are merged as they go idle
+    if !canary {
+      local_stats.record(&worker_result);
+    }
+    if
+      local_stats.total_executions >= *stats_flush_interval ||
+      queue_probe.is_empty()
+    {
+      local_stats.flush_into(optimization_stats, events, worker_id);
+    }
+
+    // Cancelled while running: the work is done, but nobody wants the result
+    let cancelled = cancellations.take(&worker_result.id, epoch);
+    // Graph nodes depending on this task run with its output, or fail without it
+    let output = worker_result.result
+      .as_deref()
+      .filter(|_| !cancelled && worker_result.error.is_none());
+    let skipped = graphs.finish(&worker_result.id, output);
+    // Identical submissions attached to this one get copies of its result
+    let followers = dedup.finish(&worker_result.id);
+    let result = &worker_result;
+    Self::fan_out(followers, result, cancellations, tasks, in_flight, result_sender);
+    if !cancelled && !reported {
+      tasks.finish(&worker_result.id, worker_result.error.is_none());
+      reply.send(worker_result, result_sender);
+    }
+    Self::fail_skipped(skipped, worker_id, faults.seed(), tasks, in_flight, send_shared);
+
+    throttle.maybe_probe(events);
+
+    ControlFlow::Continue(Some(clock.now().saturating_duration_since(start_time)))
+  }
+
+  /// Preemption point of a running task taken from `queue`: unless the task is
+  /// critical itself, serve the critical tasks queued since it started before
+  /// it carries on. Returns the time spent on them.
+  fn preempt(worker_id: usize, context: &WorkerContext, paused: &str, queue: QueueId) -> Duration {
+    let WorkerContext {
+      queue_probe,
+      optimization_stats,
+      clock,
+      events,
+      tasks,
+      limits,
+      rate_limits,
+      children,
+      ..
+    } = context;
+    let priority = Priority::Critical;
+    // Reserved tasks keep their worker to themselves
+    if
+      !matches!(queue, QueueId::Shared(running) if running < priority) ||
+      queue_probe.lane_is_empty(priority)
+    {
+      return Duration::ZERO;
+    }
+
+    let start = clock.now();
+    let mut local_stats = OptimizationStats::default();
+    while let Some(task) = queue_probe.try_recv_from(priority, worker_id) {
+      let queue = QueueId::Shared(priority);
+      let park = |task: &OptimizedWorkerTask| tasks.park(&task.id, queue);
+      let Some(task) = rate_limits.admit(task, priority, park) else {
+        continue;
+      };
+      let Some((task, _limit_slot)) = limits.admit(task, priority, park) else {
+        continue;
+      };
+      let Some((task, _child_slot)) = children.admit(task, priority, park) else {
+        continue;
+      };
+      events.publish(
+        "task_preempted",
+        Some(worker_id),
+        format!("Task {} paused between chunks for critical task {}", paused, task.id)
+      );
+      // An injected worker panic loses the task, but can't stop the worker from here
+      let _ = Self::serve_task(worker_id, context, task, queue, &mut local_stats);
+    }
+    if local_stats.total_executions > 0 {
+      events
+        .lock(optimization_stats, "optimization_stats", Some(worker_id))
+        .merge(&local_stats);
+    }
+    clock.now().saturating_duration_since(start)
+  }
+
+  /// Enhanced Rust native execution with optimization hints. In strict mode the
+  /// function must be recognized exactly and runs in its canonical form. A
+  /// function compiled by a background pass skips both steps. A panic fails
+  /// the task instead of killing the worker.
+  fn execute_rust_optimized(
+    task: &OptimizedWorkerTask,
+    worker_id: usize,
+    strict: bool,
+    compiled: Option<&CompiledFunction>,
+    on_partial: &(dyn Fn(PartialResult) + Sync),
+    preempt: &(dyn Fn() + Sync)
+  ) -> std::result::Result<FastPathOutput, String> {
+    Self::catch_native_panic(|| {
+      Self::execute_native(task, worker_id, strict, compiled, on_partial, preempt)
+    })
+  }
+
+  /// Run native code, turning a panic into an error so the worker survives it
+  fn catch_native_panic<T>(
+    execute: impl FnOnce() -> std::result::Result<T, String>
+  ) -> std::result::Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(execute)).unwrap_or_else(|payload| {
+      Err(format!("Native execution {}", panic_cause(payload.as_ref())))
+    })
+  }
+
+  fn execute_native(
+    task: &OptimizedWorkerTask,
+    worker_id: usize,
+    strict: bool,
+    compiled: Option<&CompiledFunction>,
+    on_partial: &(dyn Fn(PartialResult) + Sync),
+    preempt: &(dyn Fn() + Sync)
+  ) -> std::result::Result<FastPathOutput, String> {
+    let input = task.input()?;
+    let parsed_data: serde_json::Value = match serde_json::from_str(input) {
+      Ok(val) => val,
+      Err(_) => serde_json::Value::String(input.to_string()),
+    };
+
+    // A columnar batch row reads its value from the segment's output column
+    if !strict && let Some(output) = task.columnar.as_ref().and_then(ColumnarRow::output) {
+      let format = NumberFormat::from_hints(&task.optimization_hints)?;
+      let (value, integral) = output?;
+      return Self::canonical_output(task, Self::pair_output(value, integral, worker_id, &format));
+    }
+
+    // Map over the elements chunk by chunk, reporting each chunk as it completes
+    if let Some(chunk_size) = task.optimization_hints.chunk_size {
+      let serde_json::Value::Array(values) = &parsed_data else {
+        return Err("chunk_size needs an array input".to_string());
+      };
+      let mapped = chunked::map_chunks(
+        &task.id,
+        values,
+        chunk_size as usize,
+        |value| Self::execute_element(task, value, worker_id, strict, compiled),
+        preempt,
+        on_partial
+      )?;
+      let json = scratch::to_json(&mapped)?;
+      return Self::canonical_output(task, json);
+    }
+
+    Self::execute_parsed(task, &parsed_data, worker_id, strict, compiled)
+  }
+
+  /// JSON output, canonicalized if the task asked for it
+  fn canonical_output(
+    task: &OptimizedWorkerTask,
+    json: String
+  ) -> std::result::Result<FastPathOutput, String> {
+    if task.optimization_hints.canonical_json == Some(true) {
+      canonical_json::canonicalize(&json).map(FastPathOutput::Json)
+    } else {
+      Ok(FastPathOutput::Json(json))
+    }
+  }
+
+  /// Fold a `submit_delta` task's delta into its view
+  fn execute_delta(
+    views: &IncrementalViews,
+    view: &str,
+    task: &OptimizedWorkerTask
+  ) -> std::result::Result<String, String> {
+    let hints = &task.optimization_hints;
+    let aggregation = Aggregation::parse(hints.aggregate.as_deref().unwrap_or_default())?;
+    let format = NumberFormat::from_hints(hints)?;
+    let input = task.input()?;
+    Self::catch_native_panic(|| {
+      views.apply(view, &hints.function_hash, &aggregation, input, &format)
+    })
+  }
+
+  /// One element of a chunked task, decoded so the mapped array can be assembled
+  fn execute_element(
+    task: &OptimizedWorkerTask,
+    value: &serde_json::Value,
+    worker_id: usize,
+    strict: bool,
+    compiled: Option<&CompiledFunction>
+  ) -> std::result::Result<serde_json::Value, String> {
+    Ok(match Self::execute_parsed(task, value, worker_id, strict, compiled)? {
+      FastPathOutput::Json(json) =>
+        serde_json::from_str(&json).unwrap_or(serde_json::Value::String(json)),
+      FastPathOutput::Bytes(bytes) => serde_json::json!(bytes),
+    })
+  }
+
+  fn execute_parsed(
     task: &OptimizedWorkerTask,
-    worker_id: usize
-  ) -> std::result::Result<String, String> {
-    let parsed_data: serde_json::Value = match serde_json::from_str(&task.data) {
-      Ok(val) => val,
-      Err(_) => serde_json::Value::String(task.data.clone()),
+    parsed_data: &serde_json::Value,
+    worker_id: usize,
+    strict: bool,
+    compiled: Option<&CompiledFunction>
+  ) -> std::result::Result<FastPathOutput, String> {
+    let function_code = match compiled {
+      Some(compiled) => compiled.function_code(parsed_data)?,
+      None if strict => strict::canonical_function(&task.function_code, parsed_data)?,
+      None => task.function_code.as_str(),
     };
 
+    let deterministic = task.optimization_hints.deterministic.unwrap_or(false);
+    let format = NumberFormat::from_hints(&task.optimization_hints)?;
+    let locale = task.optimization_hints.locale.as_deref();
+
     // Use optimization hints for better performance
-    match task.optimization_hints.operation_type.as_str() {
+    let result = match task.optimization_hints.operation_type.as_str() {
       "mathematical" =>
-        Self::execute_mathematical_optimized(&task.function_code, &parsed_data, worker_id),
-      "string_operations" => Self::execute_string_optimized(&task.function_code, &parsed_data),
-      "array_operations" => Self::execute_array_optimized(&task.function_code, &parsed_data),
-      _ => Self::execute_general_optimized(&task.function_code, &parsed_data, worker_id),
+        Self::execute_mathematical_optimized(
+          function_code,
+          parsed_data,
+          worker_id,
+          &format
+        ).map(FastPathOutput::Json),
+      "string_operations" =>
+        Self::execute_string_optimized(function_code, parsed_data, locale).map(
+          FastPathOutput::Json
+        ),
+      "array_operations" =>
+        Self::execute_array_optimized(
+          function_code,
+          parsed_data,
+          deterministic,
+          &format,
+          locale
+        ).map(FastPathOutput::Json),
+      "binary_operations" =>
+        Self::execute_binary_optimized(function_code, parsed_data).map(FastPathOutput::Bytes),
+      _ =>
+        Self::execute_general_optimized(
+          function_code,
+          parsed_data,
+          worker_id,
+          deterministic,
+          &format,
+          locale
+        ),
+    };
+
+    match result {
+      Ok(FastPathOutput::Json(json)) if task.optimization_hints.canonical_json == Some(true) =>
+        canonical_json::canonicalize(&json).map(FastPathOutput::Json),
+      result => result,
     }
   }
 
@@ -479,9 +3274,10 @@ impl OptimizedMultiCoreExecutor {
   fn execute_mathematical_optimized(
     function_code: &str,
     data: &serde_json::Value,
-    worker_id: usize
+    worker_id: usize,
+    format: &NumberFormat
   ) -> std::result::Result<String, String> {
-    let clean_fn = function_code.replace([' ', '\n', '\t'], "").to_lowercase();
+    let clean_fn = Self::clean_code(function_code);
 
     if let Some(num) = data.as_f64() {
       let result = if clean_fn.contains("*2") {
@@ -502,51 +3298,58 @@ impl OptimizedMultiCoreExecutor {
         return Err("Unsupported mathematical operation".to_string());
       };
 
-      return Ok(result.to_string());
+      return Ok(format.format(result, Self::is_integer(data)));
     }
 
     // Handle mathematical objects
-    if let Some(obj) = data.as_object() {
-      if
-        let (Some(a), Some(b)) = (
-          obj.get("a").and_then(|v| v.as_f64()),
-          obj.get("b").and_then(|v| v.as_f64()),
-        )
-      {
-        let result = if clean_fn.contains("*") {
-          a * b
-        } else if clean_fn.contains("+") {
-          a + b
-        } else if clean_fn.contains("-") {
-          a - b
-        } else if clean_fn.contains("/") && b != 0.0 {
-          a / b
-        } else {
-          return Err("Unsupported mathematical operation".to_string());
-        };
+    if
+      let Some(obj) = data.as_object() &&
+      let (Some(a_value), Some(b_value)) = (obj.get("a"), obj.get("b")) &&
+      let (Some(a), Some(b)) = (a_value.as_f64(), b_value.as_f64())
+    {
+      let result = if clean_fn.contains("*") {
+        a * b
+      } else if clean_fn.contains("+") {
+        a + b
+      } else if clean_fn.contains("-") {
+        a - b
+      } else if clean_fn.contains("/") && b != 0.0 {
+        a / b
+      } else {
+        return Err("Unsupported mathematical operation".to_string());
+      };
 
-        return Ok(
-          serde_json::json!({
-                    "result": result,
-                    "worker_id": worker_id,
-                    "optimization": "rust_mathematical"
-                }).to_string()
-        );
-      }
+      let integral = Self::is_integer(a_value) && Self::is_integer(b_value);
+      return Ok(Self::pair_output(result, integral, worker_id, format));
     }
 
     Err("Complex mathematical function - needs JavaScript worker".to_string())
   }
 
+  /// Result of an `{ a, b }` operation, also used by columnar batches
+  fn pair_output(result: f64, integral: bool, worker_id: usize, format: &NumberFormat) -> String {
+    serde_json::json!({
+      "result": format.to_json(result, integral),
+      "worker_id": worker_id,
+      "optimization": "rust_mathematical"
+    }).to_string()
+  }
+
   /// Optimized string operations
   fn execute_string_optimized(
     function_code: &str,
-    data: &serde_json::Value
+    data: &serde_json::Value,
+    locale: Option<&str>
   ) -> std::result::Result<String, String> {
-    let clean_fn = function_code.replace([' ', '\n', '\t'], "").to_lowercase();
+    let clean_fn = Self::clean_code(function_code);
+    let locale = locale.unwrap_or(locale::ROOT_LOCALE);
 
     if let Some(s) = data.as_str() {
-      let result = if clean_fn.contains("touppercase") {
+      let result = if clean_fn.contains("tolocaleuppercase") {
+        locale::to_uppercase(s, locale)?
+      } else if clean_fn.contains("tolocalelowercase") {
+        locale::to_lowercase(s, locale)?
+      } else if clean_fn.contains("touppercase") {
         s.to_uppercase()
       } else if clean_fn.contains("tolowercase") {
         s.to_lowercase()
@@ -560,7 +3363,19 @@ impl OptimizedMultiCoreExecutor {
         return Err("Unsupported string operation".to_string());
       };
 
-      return Ok(format!("\"{}\"", result));
+      return scratch::to_json(&result);
+    }
+
+    // a.localeCompare(b) over `{ a, b }`
+    if
+      clean_fn.contains("localecompare") &&
+      let Some(obj) = data.as_object() &&
+      let (Some(a), Some(b)) = (
+        obj.get("a").and_then(|v| v.as_str()),
+        obj.get("b").and_then(|v| v.as_str()),
+      )
+    {
+      return Ok(locale::compare(a, b, locale)?.to_string());
     }
 
     Err("Complex string function - needs JavaScript worker".to_string())
@@ -569,22 +3384,41 @@ impl OptimizedMultiCoreExecutor {
   /// Optimized array operations
   fn execute_array_optimized(
     function_code: &str,
-    data: &serde_json::Value
+    data: &serde_json::Value,
+    deterministic: bool,
+    format: &NumberFormat,
+    locale: Option<&str>
   ) -> std::result::Result<String, String> {
-    let clean_fn = function_code.replace([' ', '\n', '\t'], "").to_lowercase();
+    let clean_fn = Self::clean_code(function_code);
 
     if let Some(arr) = data.as_array() {
+      let integral_inputs = arr.iter().all(Self::is_integer);
+
       if clean_fn.contains("length") {
         return Ok(arr.len().to_string());
       }
 
+      // String sorts: collated with localeCompare, otherwise JS default code-unit order
+      if clean_fn.contains("sort") && arr.iter().all(|v| v.is_string()) {
+        let mut values: Vec<String> = arr
+          .iter()
+          .filter_map(|v| v.as_str().map(str::to_string))
+          .collect();
+        if clean_fn.contains("localecompare") {
+          locale::sort(&mut values, locale.unwrap_or(locale::ROOT_LOCALE))?;
+        } else {
+          locale::sort_code_units(&mut values);
+        }
+        return scratch::to_json(&values);
+      }
+
       // Simple array operations that can be done in Rust
       if clean_fn.contains("sum") && arr.iter().all(|v| v.is_number()) {
-        let sum: f64 = arr
-          .iter()
-          .filter_map(|v| v.as_f64())
-          .sum();
-        return Ok(sum.to_string());
+        let sum = scratch::with_numbers(|values| {
+          values.extend(arr.iter().filter_map(|v| v.as_f64()));
+          Self::sum_values(values, deterministic)
+        });
+        return Ok(format.format(sum, integral_inputs));
       }
 
       if clean_fn.contains("max") && arr.iter().all(|v| v.is_number()) {
@@ -592,7 +3426,7 @@ impl OptimizedMultiCoreExecutor {
           .iter()
           .filter_map(|v| v.as_f64())
           .fold(f64::NEG_INFINITY, f64::max);
-        return Ok(max.to_string());
+        return Ok(format.format(max, integral_inputs));
       }
 
       if clean_fn.contains("min") && arr.iter().all(|v| v.is_number()) {
@@ -600,91 +3434,887 @@ impl OptimizedMultiCoreExecutor {
           .iter()
           .filter_map(|v| v.as_f64())
           .fold(f64::INFINITY, f64::min);
-        return Ok(min.to_string());
+        return Ok(format.format(min, integral_inputs));
       }
     }
 
     Err("Complex array function - needs JavaScript worker".to_string())
   }
 
+  /// Functions that build bytes, returned raw rather than as JSON
+  fn execute_binary_optimized(
+    function_code: &str,
+    data: &serde_json::Value
+  ) -> std::result::Result<Vec<u8>, String> {
+    let clean_fn = Self::clean_code(function_code);
+
+    if let Some(s) = data.as_str() {
+      if clean_fn.contains("buffer.from") && clean_fn.contains("base64") {
+        return binary::from_base64(s);
+      }
+      if clean_fn.contains("buffer.from") && clean_fn.contains("hex") {
+        return binary::from_hex(s);
+      }
+      if clean_fn.contains("textencoder") || clean_fn.contains("buffer.from") {
+        return Ok(binary::utf8(s));
+      }
+    }
+
+    // Uint8Array.from(xs), new Uint8Array(xs) or Buffer.from(xs)
+    if
+      let Some(arr) = data.as_array() &&
+      (clean_fn.contains("uint8array") || clean_fn.contains("buffer.from"))
+    {
+      return binary::from_numbers(arr);
+    }
+
+    Err("Complex binary function - needs JavaScript worker".to_string())
+  }
+
+  /// `function_code` without spaces, tabs and newlines, lowercased, as the
+  /// native paths match it; borrowed if it's in that form already
+  fn clean_code(function_code: &str) -> Cow<'_, str> {
+    let clean = function_code
+      .bytes()
+      .all(|b| b.is_ascii() && !b.is_ascii_uppercase() && !matches!(b, b' ' | b'\n' | b'\t'));
+    if clean {
+      Cow::Borrowed(function_code)
+    } else {
+      Cow::Owned(function_code.replace([' ', '\n', '\t'], "").to_lowercase())
+    }
+  }
+
+  /// Whether a JSON value is an integer literal (as opposed to a float like `2.5`)
+  fn is_integer(value: &serde_json::Value) -> bool {
+    value.is_i64() || value.is_u64()
+  }
+
+  /// Sum large inputs in parallel. Rayon splits work adaptively, so the default
+  /// reduction order (and the last bits of the result) can vary between runs;
+  /// deterministic mode sums fixed-size chunks and folds the partials in order.
+  fn sum_values(values: &[f64], deterministic: bool) -> f64 {
+    if values.len() < PARALLEL_REDUCTION_THRESHOLD {
+      return values.iter().sum();
+    }
+
+    if deterministic {
+      let partials: Vec<f64> = values
+        .par_chunks(DETERMINISTIC_CHUNK_SIZE)
+        .map(|chunk| chunk.iter().sum())
+        .collect();
+      partials.iter().sum()
+    } else {
+      values.par_iter().sum()
+    }
+  }
+
   /// General optimized execution fallback
   fn execute_general_optimized(
     function_code: &str,
     data: &serde_json::Value,
-    worker_id: usize
-  ) -> std::result::Result<String, String> {
+    worker_id: usize,
+    deterministic: bool,
+    format: &NumberFormat,
+    locale: Option<&str>
+  ) -> std::result::Result<FastPathOutput, String> {
     // Try mathematical operations first
-    if let Ok(result) = Self::execute_mathematical_optimized(function_code, data, worker_id) {
-      return Ok(result);
+    if
+      let Ok(result) = Self::execute_mathematical_optimized(function_code, data, worker_id, format)
+    {
+      return Ok(FastPathOutput::Json(result));
     }
 
     // Try string operations
-    if let Ok(result) = Self::execute_string_optimized(function_code, data) {
-      return Ok(result);
+    if let Ok(result) = Self::execute_string_optimized(function_code, data, locale) {
+      return Ok(FastPathOutput::Json(result));
     }
 
     // Try array operations
-    if let Ok(result) = Self::execute_array_optimized(function_code, data) {
-      return Ok(result);
+    if
+      let Ok(result) = Self::execute_array_optimized(
+        function_code,
+        data,
+        deterministic,
+        format,
+        locale
+      )
+    {
+      return Ok(FastPathOutput::Json(result));
     }
 
     // Default fallback
     Err("General function requires JavaScript worker".to_string())
   }
 
-  /// Update optimization statistics
-  fn update_optimization_stats(&self, result: &OptimizedWorkerResult) {
-    if let Ok(mut stats) = self.optimization_stats.lock() {
-      stats.total_executions += 1;
+  fn function_profile(
+    function_hash: String,
+    execution_count: u32,
+    metric: Option<&PerformanceMetric>
+  ) -> FunctionProfile {
+    FunctionProfile {
+      function_hash,
+      execution_count,
+      last_latency_ms: metric.map(|m| m.execution_time as f64),
+      last_throughput: metric.map(|m| m.throughput).filter(|t| t.is_finite()),
+      optimization_type: metric.map(|m| m.optimization_type.clone()),
+      last_success: metric.map(|m| m.success),
+    }
+  }
+
+  /// Apply routing overrides and wrap a submission into a task ready for the queue
+  fn prepare_task(
+    &self,
+    function_code: String,
+    data: String,
+    mut hints: OptimizationHints,
+    meta: Option<serde_json::Value>,
+    timeout_ms: Option<u32>
+  ) -> Result<OptimizedWorkerTask> {
+    self.events.lock(&self.routing_overrides, "routing_overrides", None).apply(&mut hints);
+
+    let expires_at = self.queue_deadline(&hints);
+    Ok(OptimizedWorkerTask {
+      id: self.generate_task_id(),
+      function_code,
+      data,
+      timeout_ms: timeout_ms.or(self.default_timeout_ms).map(u64::from),
+      optimization_hints: hints,
+      meta,
+      reply: Reply::Shared,
+      epoch: self.cancellations.current_epoch(),
+      view: None,
+      columnar: None,
+      borrowed: None,
+      expires_at,
+      worker: None,
+      sub_batch: None,
+    })
+  }
+
+  /// Take the shared queue's tasks that can run in another process off it,
+  /// failing them here; the rest go back, in the same order
+  fn hand_off_queue(&self) -> Vec<QueuedTask> {
+    let sender_guard = self.events.lock(&self.task_sender, "task_sender", None);
+    let Some(sender) = sender_guard.as_ref() else {
+      return Vec::new();
+    };
+    let mut handed_off = Vec::new();
+    for (task, priority) in sender.take_all() {
+      match QueuedTask::from_task(&task, self.idempotency.key(&task.id)) {
+        Some(queued) => {
+          self.tasks.evict(&task.id, QueueId::Shared(priority));
+          handed_off.push(queued);
+          sender.shed(task, priority, ShedReason::HandedOff);
+        }
+        None => sender.send_admitted(task, priority),
+      }
+    }
+    if !handed_off.is_empty() {
+      self.events.emit(
+        "queue_handed_off",
+        None,
+        format!("Handed off {} queued tasks to export_state", handed_off.len())
+      );
+    }
+    handed_off
+  }
+
+  /// A task handed off by another executor, ready for this one's queue
+  fn handed_off_task(&self, queued: QueuedTask) -> Result<OptimizedWorkerTask> {
+    let mut hints = queued.hints;
+    hints.idempotency_key = None;
+    let mut task = self.prepare_task(queued.function_code, queued.data, hints, queued.meta, None)?;
+    task.id = queued.id;
+    task.timeout_ms = queued.timeout_ms;
+    if queued.fire {
+      task.reply = Reply::Discard;
+    }
+    Ok(task)
+  }
+
+  /// The task already holding `key`, or `None` once `task_id` holds it
+  fn claim_key(&self, key: Option<&str>, task_id: &str) -> Option<String> {
+    self.idempotency.claim(key?, task_id)
+  }
+
+  /// When a task submitted now with `hints` goes stale, if it asked to
+  fn queue_deadline(&self, hints: &OptimizationHints) -> Option<Instant> {
+    let max_age = Duration::from_millis(hints.max_queue_age_ms? as u64);
+    Some(self.clock.now() + max_age)
+  }
+
+  /// Cancel `task_id` when `signal` fires, and wake its pending `run_task` promise if any
+  #[cfg(feature = "node")]
+  fn cancel_on_abort(
+    &self,
+    env: &Env,
+    signal: &JsObject,
+    task_id: String,
+    abort_tx: Option<channel::Sender<()>>
+  ) -> Result<()> {
+    let cancellations = Arc::clone(&self.cancellations);
+    let tasks = Arc::clone(&self.tasks);
+    abort::on_abort(env, signal, move || {
+      if tasks.cancel(&task_id) {
+        cancellations.cancel(task_id.clone());
+      }
+      if let Some(abort_tx) = abort_tx.as_ref() {
+        let _ = abort_tx.try_send(());
+      }
+    })
+  }
+
+  /// Task running a registered function, with any routing override applied
+  fn registered_task(
+    &self,
+    function: RegisteredFunction,
+    data: String,
+    meta: Option<serde_json::Value>,
+    timeout_ms: Option<u32>
+  ) -> OptimizedWorkerTask {
+    let mut hints = function.hints;
+    self.events.lock(&self.routing_overrides, "routing_overrides", None).apply(&mut hints);
+
+    let expires_at = self.queue_deadline(&hints);
+    OptimizedWorkerTask {
+      id: self.generate_task_id(),
+      function_code: function.function_code,
+      data,
+      timeout_ms: timeout_ms.or(self.default_timeout_ms).map(u64::from),
+      optimization_hints: hints,
+      meta,
+      reply: Reply::Shared,
+      epoch: self.cancellations.current_epoch(),
+      view: None,
+      columnar: None,
+      borrowed: None,
+      expires_at,
+      worker: None,
+      sub_batch: None,
+    }
+  }
+
+  /// The lane a task with `hints` waits in. Every submission passes through
+  /// here, after those that honor `idempotency_key` have taken it out.
+  fn lane(&self, hints: &OptimizationHints) -> Result<Priority> {
+    if hints.idempotency_key.is_some() {
+      return Err(
+        Error::from_reason(
+          "idempotency_key is only honored by submit_optimized_task and submit_borrowed".to_string()
+        )
+      );
+    }
+    let priority = Priority::from_hints(hints).map_err(Error::from_reason)?;
+    Ok(self.discipline.lane(hints, priority))
+  }
+
+  /// Hand a task to the worker queue
+  fn enqueue_task(&self, task: OptimizedWorkerTask) -> Result<()> {
+    if self.faults.should_reject_submission() {
+      return Err(Error::from_reason("Task queue is full (injected fault)".to_string()));
+    }
+    // Workers busy with long tasks don't check, so submissions do too
+    if let Some(starvation) = self.starvation.as_ref() {
+      starvation.check(&self.events, None);
+    }
+
+    let priority = self.lane(&task.optimization_hints)?;
+
+    let inline = self.runs_inline(&task);
+    let fingerprint = if inline { None } else { dedup::fingerprint(&task) };
+    // Tasks waiting on a group take their place in the queue when they're released
+    let queued = usize::from(!inline && task.optimization_hints.after.is_none());
+    let sender_guard = self.sender_with_room(queued);
+    if let Some(sender) = sender_guard.as_ref() {
+      self.check_memory(sender)?;
+      self.in_flight.begin().map_err(Error::from_reason)?;
+      if inline {
+        // Nothing is queued, so other submitters needn't wait for it
+        drop(sender_guard);
+        self.run_inline(task, priority);
+        self.in_flight.end();
+        return Ok(());
+      }
+      // Nobody can ask about a fire-and-forget task, so it isn't tracked
+      let tracked = !matches!(task.reply, Reply::Discard);
+      let task_id = task.id.clone();
+      let task = match fingerprint {
+        Some(fingerprint) => {
+          // Marked first, since the execution it attaches to may finish right away
+          if tracked {
+            self.tasks.waiting(&task_id, priority);
+          }
+          match self.dedup.join(fingerprint, task, priority) {
+            Some(task) => task,
+            None => {
+              return Ok(());
+            }
+          }
+        }
+        None => task,
+      };
+      self.admit(sender, task, priority, tracked).inspect_err(|_| {
+        self.in_flight.end();
+        // Nothing attached yet: submitters attach only under the sender lock
+        self.dedup.finish(&task_id);
+      })
+    } else {
+      Err(Error::from_reason("Worker pool is shut down".to_string()))
+    }
+  }
+
+  /// Whether a task is cheap enough to run on the submitting thread: small, low
+  /// complexity data for a function that hasn't been measured taking a
+  /// millisecond or more, and that nothing else keeps off it
+  fn runs_inline(&self, task: &OptimizedWorkerTask) -> bool {
+    let hints = &task.optimization_hints;
+    let Some(threshold) = self.inline_threshold else {
+      return false;
+    };
+    if task.data.len() > threshold || hints.complexity != "low" {
+      return false;
+    }
+    let measured_slow = self.events
+      .lock(&self.performance_cache, "performance_cache", None)
+      .get(&hints.function_hash)
+      .is_some_and(|metric| metric.execution_time >= 1);
+    !measured_slow && self.may_run_inline(task)
+  }
+
+  /// Whether only its cost keeps a task off the submitting thread: a native
+  /// function that no group, ordering, chunking, tenant or limit holds back,
+  /// on data owned by the task
+  fn may_run_inline(&self, task: &OptimizedWorkerTask) -> bool {
+    let hints = &task.optimization_hints;
+    if
+      !hints.should_use_rust ||
+      hints.group.is_some() ||
+      hints.after.is_some() ||
+      hints.chunk_size.is_some() ||
+      hints.tenant.is_some() ||
+      hints.child.is_some() ||
+      task.view.is_some() ||
+      task.borrowed.is_some()
+    {
+      return false;
+    }
+    let hash = &hints.function_hash;
+    self.limits.get(hash).is_none() &&
+      self.rate_limits.get(&RateKey::Function(hash.clone())).is_none()
+  }
+
+  /// Run a task on the submitting thread and deliver its result as a worker
+  /// would. It reports `max_worker_count` as its `worker_id`, which no worker
+  /// has, and "inline" as its execution type; worker fault injection and
+  /// learning state don't apply.
+  fn run_inline(&self, task: OptimizedWorkerTask, priority: Priority) {
+    let worker_id = self.slots.capacity();
+    let queue = QueueId::Shared(priority);
+    let tracked = !matches!(task.reply, Reply::Discard);
+    if tracked {
+      self.tasks.queued(&task.id, queue);
+      self.tasks.start(&task.id, queue, worker_id);
+    }
+
+    let start_time = self.clock.now();
+    let compiled = self.background.compiled(&task);
+    let result = Self::execute_rust_optimized(
+      &task,
+      worker_id,
+      self.strict,
+      compiled.as_deref(),
+      &|_| {},
+      &|| {}
+    );
+    let (result, fallback) = match result {
+      Err(err) => {
+        let primary = self.clock.now().saturating_duration_since(start_time);
+        let execute = |fallback_task: &OptimizedWorkerTask| {
+          Self::execute_rust_optimized(fallback_task, worker_id, self.strict, None, &|_| {}, &|| {})
+        };
+        match self.fallbacks.run(&task, &err, primary, &self.clock, execute) {
+          Some((result, attempt)) => {
+            let message = attempt.describe(&task.id, result.is_ok());
+            self.events.publish("fallback_ran", None, message);
+            (result, Some(attempt))
+          }
+          None => (Err(err), None),
+        }
+      }
+      ok => (ok, None),
+    };
+    let duration = self.clock.now().saturating_duration_since(start_time).as_millis() as u64;
+    let (result, timed_out) = match task.timeout_ms {
+      Some(timeout_ms) if duration > timeout_ms => {
+        (Err(Self::timeout_error(timeout_ms, duration)), true)
+      }
+      _ => (result, false),
+    };
+    let optimization_used = match (&result, &fallback) {
+      (Ok(_), Some(_)) => "rust_fallback",
+      (Ok(_), None) => "rust_optimized",
+      (Err(_), _) => "rust_failed",
+    };
+
+    let reply = task.reply.clone();
+    let mut inline_result = Outcome {
+      result,
+      optimization_used: optimization_used.to_string(),
+      duration,
+      worker_id,
+      cache_hit: false,
+      timed_out,
+      fallback,
+    }.into_result(task, self.faults.seed());
+    inline_result.execution_type = "inline".to_string();
+    self.events.lock(&self.optimization_stats, "optimization_stats", None).record(&inline_result);
+
+    if tracked {
+      self.tasks.finish(&inline_result.id, inline_result.error.is_none());
+    }
+    reply.send(inline_result, &self.sink);
+  }
+
+  /// Fail graph nodes that can't run because a dependency failed, delivering an
+  /// error result for each as though it had run
+  fn fail_skipped(
+    skipped: Vec<(OptimizedWorkerTask, String)>,
+    worker_id: usize,
+    seed: u64,
+    tasks: &TaskTracker,
+    in_flight: &InFlight,
+    deliver: impl Fn(OptimizedWorkerResult)
+  ) {
+    for (task, reason) in skipped {
+      tasks.finish(&task.id, false);
+      let reply = task.reply.clone();
+      let result = Outcome {
+        result: Err(reason),
+        optimization_used: "dependency_failed".to_string(),
+        duration: 0,
+        worker_id,
+        cache_hit: false,
+        timed_out: false,
+        fallback: None,
+      }.into_result(task, seed);
+      match reply {
+        Reply::Shared => deliver(result),
+        Reply::Channel(reply) => {
+          let _ = reply.send(result);
+        }
+        Reply::Discard => {}
+      }
+      in_flight.end();
+    }
+  }
+
+  /// Deliver copies of a task's result to the identical tasks attached to it
+  fn fan_out(
+    followers: Vec<OptimizedWorkerTask>,
+    result: &OptimizedWorkerResult,
+    cancellations: &CancellationSet,
+    tasks: &TaskTracker,
+    in_flight: &InFlight,
+    shared: &ResultSink
+  ) {
+    for follower in followers {
+      if !cancellations.take(&follower.id, follower.epoch) {
+        tasks.finish(&follower.id, result.error.is_none());
+        let mut copy = result.clone();
+        copy.id = follower.id;
+        copy.meta = follower.meta;
+        follower.reply.send(copy, shared);
+      }
+      in_flight.end();
+    }
+  }
+
+  /// Join the task's group and its child executor's budget, then send it to the
+  /// shared queue or, with `after`, hold it until that group completes. Called
+  /// with the sender lock held.
+  fn admit(
+    &self,
+    sender: &LaneSender,
+    mut task: OptimizedWorkerTask,
+    priority: Priority,
+    tracked: bool
+  ) -> Result<()> {
+    let task_id = task.id.clone();
+    let group = task.optimization_hints.group.clone();
+    if let Some(group) = group.as_deref() {
+      self.groups.join(group, &task_id).map_err(Error::from_reason)?;
+    }
+    let child = task.optimization_hints.child.clone();
+    if let Err(e) = self.children.enter(&mut task) {
+      if let Some(group) = group.as_deref() {
+        self.groups.finish(group, &task_id);
+      }
+      return Err(match e {
+        ChildError::Full(message) => Error::new(Status::QueueFull, message),
+        ChildError::Closed(message) => Error::from_reason(message),
+      });
+    }
 
-      match result.optimization_used.as_str() {
-        "rust_native" | "rust_optimized" => {
-          stats.rust_native_hits += 1;
+    let task = match task.optimization_hints.after.clone() {
+      Some(after) => {
+        if tracked {
+          self.tasks.waiting(&task_id, priority);
         }
-        "needs_js_worker" => {
-          stats.js_worker_fallbacks += 1;
+        match self.groups.hold(&after, task, priority) {
+          None => {
+            return Ok(());
+          }
+          // The group already completed
+          Some(task) => {
+            self.tasks.release(&task_id, QueueId::Shared(priority));
+            task
+          }
+        }
+      }
+      None => {
+        if tracked {
+          self.tasks.queued(&task_id, QueueId::Shared(priority));
         }
-        _ => {}
+        task
+      }
+    };
+
+    self.send_to_queue(sender, task, priority).inspect_err(|_| {
+      self.tasks.forget(&task_id);
+      if let Some(group) = group.as_deref() {
+        self.groups.finish(group, &task_id);
+      }
+      if let Some(child) = child.as_deref() {
+        self.children.finish(child, &task_id);
       }
+    })
+  }
+
+  /// Queue without blocking: a full bounded queue rejects the task
+  fn send_to_queue(
+    &self,
+    sender: &LaneSender,
+    task: OptimizedWorkerTask,
+    priority: Priority
+  ) -> Result<()> {
+    sender.try_send(task, priority).map_err(|e| {
+      match (e, self.queue_capacity) {
+        (priority::SendError::Full, Some(capacity)) =>
+          Error::new(Status::QueueFull, format!("Task queue is full (capacity {})", capacity)),
+        _ => Error::from_reason("Worker pool is shut down".to_string()),
+      }
+    })
+  }
+
+  /// Estimated memory, with the queue's share from `sender` when it's running
+  fn memory_usage(&self, sender: Option<&LaneSender>) -> MemoryUsage {
+    let queued = sender.map_or(0, LaneSender::queued_bytes);
+    let results = self.results.buffered_bytes();
+    let cache = memory::cache_bytes(
+      self.events.lock(&self.performance_cache, "performance_cache", None).len()
+    );
+    MemoryUsage {
+      queued_bytes: queued as f64,
+      result_bytes: results as f64,
+      cache_bytes: cache as f64,
+      total_bytes: (queued + results + cache) as f64,
+      watermark_bytes: self.memory.limit().map(|limit| limit as f64),
+    }
+  }
+
+  /// Refuse a submission while estimated memory is over the watermark. Going
+  /// over clears the performance cache and is reported once, as an event and
+  /// to the `onMemoryPressure` listener.
+  fn check_memory(&self, sender: &LaneSender) -> Result<()> {
+    let Some(limit) = self.memory.limit() else {
+      return Ok(());
+    };
+    let usage = self.memory_usage(Some(sender));
+    match self.memory.check(usage.total_bytes as usize) {
+      Pressure::Below => {
+        return Ok(());
+      }
+      Pressure::Crossed => {
+        self.events.lock(&self.performance_cache, "performance_cache", None).clear();
+        self.events.emit(
+          "memory_pressure",
+          None,
+          format!(
+            "Estimated native memory of {} bytes is over the {} byte watermark; \
+             cleared the performance cache",
+            usage.total_bytes,
+            limit
+          )
+        );
+        self.memory.notify(usage);
+      }
+      Pressure::Above => {}
+    }
+    // Clearing the cache may have been enough
+    let total = self.memory_usage(Some(sender)).total_bytes as usize;
+    if self.memory.check(total) == Pressure::Below {
+      return Ok(());
+    }
+    Err(
+      Error::new(
+        Status::QueueFull,
+        format!(
+          "Estimated native memory of {} bytes is over the {} byte watermark; \
+           collect results or let queued tasks finish",
+          total,
+          limit
+        )
+      )
+    )
+  }
 
-      if result.cache_hit {
-        stats.cache_hits += 1;
+  /// The sender lock, once the shared queue has room for `needed` more tasks
+  /// under the overflow policy: waiting for workers to take tasks (`Block`) or
+  /// evicting queued ones (`DropOldest`). With `Reject`, or when `needed`
+  /// exceeds the capacity, the caller's own check rejects the submission.
+  fn sender_with_room(&self, needed: usize) -> MutexGuard<'_, Option<LaneSender>> {
+    let guard = self.events.lock(&self.task_sender, "task_sender", None);
+    let Some(capacity) = self.queue_capacity.filter(|&capacity| needed <= capacity) else {
+      return guard;
+    };
+    match self.overflow_policy {
+      OverflowPolicy::Reject => guard,
+      OverflowPolicy::Block => {
+        let mut guard = guard;
+        loop {
+          // Not held while waiting: workers take it to requeue held tasks
+          let space = match guard.as_ref() {
+            Some(sender) if sender.len() + needed > capacity => sender.space(),
+            _ => {
+              return guard;
+            }
+          };
+          drop(guard);
+          space.wait(Duration::from_millis(1));
+          guard = self.events.lock(&self.task_sender, "task_sender", None);
+        }
+      }
+      OverflowPolicy::DropOldest => {
+        if let Some(sender) = guard.as_ref() {
+          while sender.len() + needed > capacity {
+            let Some((task, priority)) = sender.evict_oldest() else {
+              break;
+            };
+            self.tasks.evict(&task.id, QueueId::Shared(priority));
+            self.events.emit(
+              "task_dropped",
+              None,
+              format!("Dropped queued task {} to make room in the full task queue", task.id)
+            );
+            sender.shed(task, priority, ShedReason::Overflow);
+          }
+        }
+        guard
       }
+    }
+  }
+
+  /// Structured error for a task that ran past its budget
+  fn timeout_error(timeout_ms: u64, duration_ms: u64) -> String {
+    format!("Task exceeded its {}ms timeout (took {}ms)", timeout_ms, duration_ms)
+  }
+
+  /// Result the watchdog reports for a task still running at its deadline
+  fn overrun_result(
+    task: &OptimizedWorkerTask,
+    worker_id: usize,
+    timeout_ms: u64
+  ) -> OptimizedWorkerResult {
+    OptimizedWorkerResult {
+      id: task.id.clone(),
+      result: None,
+      error: Some(format!("Task exceeded its {}ms timeout (still running)", timeout_ms)),
+      duration_ms: timeout_ms as f64,
+      worker_id: worker_id as u32,
+      execution_type: "timed_out".to_string(),
+      optimization_used: "timed_out".to_string(),
+      cache_hit: false,
+      timed_out: true,
+      meta: task.meta.clone(),
+      receipt: None,
+      typed: None,
+      binary: None,
+      fallback: None,
+    }
+  }
+
+  /// Segment handles for a batch that can run a column at a time: at least
+  /// `MIN_BATCH_ROWS` tasks sharing their hints and a function with a kernel.
+  /// Whether the rows share a shape is only checked when a worker converts them.
+  fn columnar_rows(
+    &self,
+    tasks: &[(String, String)],
+    hints: &[(OptimizationHints, Priority)],
+    per_task_hints: bool
+  ) -> Option<Vec<ColumnarRow>> {
+    let ((function_code, _), (hints, _)) = (tasks.first()?, hints.first()?);
+    if
+      per_task_hints ||
+      tasks.len() < columnar::MIN_BATCH_ROWS ||
+      !hints.should_use_rust ||
+      hints.chunk_size.is_some() ||
+      tasks.iter().any(|(code, _)| code != function_code)
+    {
+      return None;
+    }
+    let kernel = Kernel::for_function(function_code, &hints.operation_type)?;
+    let rows = tasks
+      .iter()
+      .map(|(_, data)| data.clone())
+      .collect();
+    Some(columnar::segment_rows(kernel, rows, self.scheduling.segment_rows))
+  }
 
-      // Update running averages
-      let current_latency = stats.avg_latency_ms;
-      let total = stats.total_executions as f64;
-      stats.avg_latency_ms =
-        (current_latency * (total - 1.0) + (result.duration_ms as f64)) / total;
+  /// Per-task batch arguments must line up one-to-one with the tasks
+  fn check_per_task_len(kind: &str, tasks: usize, entries: Option<usize>) -> Result<()> {
+    match entries {
+      Some(entries) if entries != tasks =>
+        Err(
+          Error::from_reason(format!("Expected {} {} entries, got {}", tasks, kind, entries))
+        ),
+      _ => Ok(()),
     }
   }
 
+  /// Seed derived from the clock, for components that need cheap randomness
+  fn clock_seed(clock: &SharedClock) -> u64 {
+    clock.unix_time().as_nanos() as u64
+  }
+
   /// Generate unique task ID
-  fn generate_task_id() -> String {
-    use std::time::{ SystemTime, UNIX_EPOCH };
-    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
-    format!("opt_task_{}", timestamp)
+  fn generate_task_id(&self) -> String {
+    static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+    // Reproducible, and still unique within the executor
+    if let Some(next) = self.seeded_ids.as_ref() {
+      let seq = next.fetch_add(1, Ordering::Relaxed);
+      return format!("opt_task_{:016x}_{}", self.faults.seed(), seq);
+    }
+
+    let timestamp = self.clock.unix_time().as_nanos();
+    let seq = SEQUENCE.fetch_add(1, Ordering::Relaxed);
+    format!("opt_task_{}_{}", timestamp, seq)
   }
 
   #[napi(getter)]
   pub fn worker_count(&self) -> u32 {
-    self.worker_count as u32
+    self.slots.target() as u32
+  }
+
+  /// Grow or shrink the pool to `workers`, up to `max_worker_count`, keeping
+  /// queued tasks. New workers start at once; surplus ones finish the task
+  /// they're running, and the sub-batch or reservation they're serving, then
+  /// retire, leaving what was routed to them to the rest.
+  #[napi]
+  pub fn set_worker_count(&self, workers: u32) -> Result<()> {
+    self.resizer
+      .resize(workers as usize, "by set_worker_count")
+      .map_err(Error::from_reason)
   }
 
+  /// Scale the pool with queue depth, wait time and worker utilization within
+  /// `config`'s bounds, replacing any running autoscaler, or stop with null
+  /// and keep the current count. While it runs, `set_worker_count` only
+  /// lasts until its next adjustment.
+  #[napi]
+  pub fn set_autoscale(&self, config: Option<AutoscaleConfig>) -> Result<()> {
+    // The old autoscaler stops before the new one looks at the pool
+    drop(self.events.lock(&self.autoscaler, "autoscaler", None).take());
+    let Some(config) = config else {
+      return Ok(());
+    };
+    let policy = config
+      .policy(self.slots.fewest(), self.slots.capacity())
+      .map_err(Error::from_reason)?;
+    let autoscaler = self.start_autoscaler(policy).map_err(Error::from_reason)?;
+    *self.events.lock(&self.autoscaler, "autoscaler", None) = Some(autoscaler);
+    Ok(())
+  }
+
+  fn start_autoscaler(
+    &self,
+    policy: autoscale::Policy
+  ) -> std::result::Result<Autoscaler, String> {
+    let Resizer { slots, sender, events, .. } = self.resizer.clone();
+    let tasks = Arc::clone(&self.tasks);
+    let sample = move || {
+      let queued = events.lock(&sender, "task_sender", None).as_ref().map_or(0, LaneSender::len);
+      let (_, oldest_wait, _) = tasks.shared_queue(false);
+      Load { queued, oldest_wait, busy: slots.busy() }
+    };
+    Autoscaler::start(policy, self.resizer.clone(), SharedClock::clone(&self.clock), sample)
+  }
+
+  /// Seed behind the executor's random choices, as hex; pass it as the config's
+  /// `seed` to replay them
+  #[napi]
+  pub fn get_seed(&self) -> String {
+    format!("{:016x}", self.faults.seed())
+  }
+
+  /// Agree with a client on a protocol version, encodings and operations (see
+  /// `get_protocol_support`); results are shaped for the agreement from then on
+  #[napi]
+  pub fn negotiate_protocol(&self, offer: ProtocolSupport) -> Result<ProtocolAgreement> {
+    let agreement = protocol::negotiate(&offer).map_err(Error::from_reason)?;
+    self.protocol.apply(&agreement);
+    Ok(agreement)
+  }
+
+  /// Stop accepting tasks, then resolve once every queued and running task has
+  /// finished: true when idle, false if `timeout_ms` passed first or the
+  /// executor was shut down meanwhile. Follow with `shutdown` to stop the workers.
+  #[cfg(feature = "node")]
+  #[napi(ts_return_type = "Promise<boolean>")]
+  pub fn drain(&self, timeout_ms: Option<u32>) -> AsyncTask<IdleWait> {
+    AsyncTask::new(self.start_drain(timeout_ms))
+  }
+
+  /// Reject new submissions; `wait` on the result to block until drained
+  pub fn start_drain(&self, timeout_ms: Option<u32>) -> IdleWait {
+    self.in_flight.start_draining();
+    self.idle_waiter(timeout_ms)
+  }
+
+  /// Resolve once no task is queued or running (true), or with false after
+  /// `timeout_ms`. Submissions stay open, so the executor may be busy again
+  /// by the time the promise settles.
+  #[cfg(feature = "node")]
+  #[napi(ts_return_type = "Promise<boolean>")]
+  pub fn wait_for_idle(&self, timeout_ms: Option<u32>) -> AsyncTask<IdleWait> {
+    AsyncTask::new(self.idle_waiter(timeout_ms))
+  }
+
+  /// `wait_for_idle` without the promise; `wait` on the result to block
+  pub fn idle_waiter(&self, timeout_ms: Option<u32>) -> IdleWait {
+    IdleWait::new(Arc::clone(&self.in_flight), timeout_ms)
+  }
+
+  /// Stop the workers. Queued tasks are discarded and results not yet
+  /// collected are dropped; `drain` first to let in-flight work finish. Idle
+  /// workers exit at once and are joined; busy ones are waited for up to a
+  /// second, then left to exit after their current task.
   #[napi]
   pub fn shutdown(&self) -> Result<()> {
-    if let Ok(mut active) = self.workers_active.lock() {
-      *active = false;
-    }
+    // Close every worker's bell so idle workers exit right away
+    self.doorbell.close();
 
-    if let Ok(mut sender_guard) = self.task_sender.lock() {
-      *sender_guard = None;
-    }
+    *self.events.lock(&self.task_sender, "task_sender", None) = None;
+    self.in_flight.close();
+    self.groups.close();
 
-    if let Ok(mut receiver_guard) = self.result_receiver.lock() {
-      *receiver_guard = None;
-    }
+    self.results.close();
+    self.stop_statsd_reporter();
+    self.events.lock(&self.autoscaler, "autoscaler", None).take();
+    self.events.lock(&self.supervisor, "supervisor", None).take();
+    self.events.lock(&self.watchdog, "watchdog", None).take();
 
+    self.worker_threads.join(WORKER_EXIT_GRACE);
     Ok(())
   }
 }
@@ -695,13 +4325,30 @@ pub fn is_optimized_multicore_available() -> bool {
   true
 }
 
-/// Get enhanced system information with optimization capabilities
+/// Run known-answer checks against every native fast path and report what this
+/// build supports; a failure means the module should not take production traffic
+#[napi]
+pub fn self_test() -> SelfTestReport {
+  self_test::run()
+}
+
+/// Protocol versions, encodings and operations this core speaks (see
+/// `negotiate_protocol`)
+#[napi]
+pub fn get_protocol_support() -> ProtocolSupport {
+  protocol::support()
+}
+
+/// Get enhanced system information with optimization capabilities, and under
+/// `host` each core's frequency, the caches, SIMD support, cgroup limits, NUMA
+/// layout and memory, probed on the first call
 #[napi]
 pub fn get_optimized_multicore_info() -> Result<String> {
   let info =
     serde_json::json!({
         "cpu_cores": num_cpus::get(),
         "physical_cores": num_cpus::get_physical(),
+        "host": host_info::get(),
         "rust_version": env!("CARGO_PKG_VERSION"),
         "optimization_engine": "Enhanced Rust + Node.js Workers with 2-Phase Pipeline",
         "features": [
@@ -0,0 +1,79 @@
+// src/locale.rs - Locale-aware casing and collation (ICU, behind the `icu` feature)
+
+/// Locale used by `toLocale*` calls when the hints don't name one
+pub const ROOT_LOCALE: &str = "und";
+
+#[cfg(feature = "icu")]
+mod imp {
+  use icu::casemap::CaseMapper;
+  use icu::collator::{ Collator, CollatorOptions };
+  use icu::locid::{ LanguageIdentifier, Locale };
+  use std::cmp::Ordering;
+
+  fn language(locale: &str) -> Result<LanguageIdentifier, String> {
+    locale.parse().map_err(|e| format!("Invalid locale '{}': {}", locale, e))
+  }
+
+  pub fn to_uppercase(value: &str, locale: &str) -> Result<String, String> {
+    Ok(CaseMapper::new().uppercase_to_string(value, &language(locale)?))
+  }
+
+  pub fn to_lowercase(value: &str, locale: &str) -> Result<String, String> {
+    Ok(CaseMapper::new().lowercase_to_string(value, &language(locale)?))
+  }
+
+  pub fn comparator(locale: &str) -> Result<impl Fn(&str, &str) -> Ordering, String> {
+    let locale: Locale = locale.parse().map_err(|e| format!("Invalid locale '{}': {}", locale, e))?;
+    let collator = Collator::try_new(&(&locale).into(), CollatorOptions::new()).map_err(|e|
+      format!("No collation data for '{}': {}", locale, e)
+    )?;
+    Ok(move |a: &str, b: &str| collator.compare(a, b))
+  }
+}
+
+#[cfg(not(feature = "icu"))]
+mod imp {
+  use std::cmp::Ordering;
+
+  const UNAVAILABLE: &str = "Locale-aware string operations require the `icu` feature";
+
+  pub fn to_uppercase(_value: &str, _locale: &str) -> Result<String, String> {
+    Err(UNAVAILABLE.to_string())
+  }
+
+  pub fn to_lowercase(_value: &str, _locale: &str) -> Result<String, String> {
+    Err(UNAVAILABLE.to_string())
+  }
+
+  pub fn comparator(_locale: &str) -> Result<fn(&str, &str) -> Ordering, String> {
+    Err(UNAVAILABLE.to_string())
+  }
+}
+
+/// `toLocaleUpperCase` for `locale`
+pub fn to_uppercase(value: &str, locale: &str) -> Result<String, String> {
+  imp::to_uppercase(value, locale)
+}
+
+/// `toLocaleLowerCase` for `locale`
+pub fn to_lowercase(value: &str, locale: &str) -> Result<String, String> {
+  imp::to_lowercase(value, locale)
+}
+
+/// `localeCompare` for `locale`, as -1, 0 or 1
+pub fn compare(a: &str, b: &str, locale: &str) -> Result<i32, String> {
+  let ordering = imp::comparator(locale)?(a, b);
+  Ok(ordering as i32)
+}
+
+/// Sort with `localeCompare` collation for `locale`
+pub fn sort(values: &mut [String], locale: &str) -> Result<(), String> {
+  let compare = imp::comparator(locale)?;
+  values.sort_by(|a, b| compare(a, b));
+  Ok(())
+}
+
+/// JS default `Array.prototype.sort` order for strings: by UTF-16 code unit
+pub fn sort_code_units(values: &mut [String]) {
+  values.sort_by(|a, b| a.encode_utf16().cmp(b.encode_utf16()));
+}
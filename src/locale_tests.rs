@@ -0,0 +1,42 @@
+// src/locale_tests.rs - Locale-aware casing and collation, with and without ICU
+use crate::locale;
+
+fn strings(values: &[&str]) -> Vec<String> {
+  values.iter().map(|value| value.to_string()).collect()
+}
+
+#[test]
+fn default_sort_orders_by_utf16_code_unit() {
+  // Astral characters sort by their surrogates, before the top of the BMP
+  let mut values = strings(&["b", "\u{ff5e}", "B", "\u{1f600}", "a"]);
+  locale::sort_code_units(&mut values);
+  assert_eq!(values, strings(&["B", "a", "b", "\u{1f600}", "\u{ff5e}"]));
+}
+
+#[cfg(feature = "icu")]
+#[test]
+fn casing_and_collation_follow_the_locale() {
+  assert_eq!(locale::to_uppercase("i", "tr").unwrap(), "\u{130}");
+  assert_eq!(locale::to_uppercase("i", "en").unwrap(), "I");
+  assert_eq!(locale::to_lowercase("I", "tr").unwrap(), "\u{131}");
+  assert_eq!(locale::compare("a", "B", "en").unwrap(), -1);
+  assert_eq!(locale::compare("a", "a", "en").unwrap(), 0);
+
+  let mut values = strings(&["b", "B", "a", "\u{e4}"]);
+  locale::sort(&mut values, "de").unwrap();
+  assert_eq!(values, strings(&["a", "\u{e4}", "b", "B"]));
+  // Swedish collates ä after z
+  let mut values = strings(&["\u{e4}", "z", "a"]);
+  locale::sort(&mut values, "sv").unwrap();
+  assert_eq!(values, strings(&["a", "z", "\u{e4}"]));
+  assert!(locale::to_uppercase("i", "not a locale!").is_err());
+}
+
+#[cfg(not(feature = "icu"))]
+#[test]
+fn locale_operations_need_icu() {
+  let unavailable = "Locale-aware string operations require the `icu` feature";
+  assert_eq!(locale::to_uppercase("i", "tr").unwrap_err(), unavailable);
+  assert_eq!(locale::compare("a", "b", "en").unwrap_err(), unavailable);
+  assert_eq!(locale::sort(&mut strings(&["b", "a"]), "en").unwrap_err(), unavailable);
+}
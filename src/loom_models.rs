@@ -0,0 +1,93 @@
+// src/loom_models.rs - Model checks of how idle workers sleep and wake
+//
+// Run with `cargo test --features loom --lib loom_models`. Each model explores
+// every interleaving of the idle flags, fences and bells in `priority`; a
+// wakeup lost between a worker's last look at the queue and its sleep shows
+// up as a deadlock. The queue's deques aren't instrumented, so each push or
+// pop happens at once as far as the model is concerned.
+use std::time::Instant;
+
+use crate::{ OptimizedWorkerTask, Reply };
+use crate::discipline::PriorityDiscipline;
+use crate::operation_pools::OperationPools;
+use crate::priority::{ self, LaneSender, Priority, WorkerLanes };
+use crate::sync::thread;
+use crate::task_spec;
+
+// Coroutine stack of each modelled thread; loom's default is too small for the
+// queue's deques in a debug build
+const STACK_SIZE: usize = 1 << 20;
+
+fn spawn<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> thread::JoinHandle<T> {
+  thread::Builder::new().stack_size(STACK_SIZE).spawn(f).expect("spawn")
+}
+
+// Explore every interleaving of `scenario`, run on a thread with a big stack
+fn model(scenario: fn()) {
+  loom::model(move || spawn(scenario).join().expect("scenario"));
+}
+
+// The queue of a single-worker pool
+fn single_worker() -> (LaneSender, WorkerLanes) {
+  let pools = OperationPools::default();
+  let discipline = PriorityDiscipline;
+  let (sender, _, mut workers) = priority::lanes(None, 1, 1, false, pools, &discipline, None);
+  (sender, workers.pop().expect("worker"))
+}
+
+fn task() -> OptimizedWorkerTask {
+  OptimizedWorkerTask {
+    id: "task".to_string(),
+    function_code: "x => x * 2".to_string(),
+    data: "21".to_string(),
+    timeout_ms: None,
+    optimization_hints: task_spec::resolve_hints("x => x * 2", &[]).expect("hints"),
+    meta: None,
+    reply: Reply::Shared,
+    epoch: 0,
+    view: None,
+    columnar: None,
+    borrowed: None,
+    expires_at: None,
+    worker: None,
+    sub_batch: None,
+  }
+}
+
+#[test]
+fn submit_wakes_parking_worker() {
+  model(|| {
+    let (sender, mut worker) = single_worker();
+    // Returns the sender, as dropping it closes the queue
+    let submitter = spawn(move || {
+      sender.try_send(task(), Priority::Normal).expect("send");
+      sender
+    });
+
+    // Look, park, look again: a lost wakeup leaves the worker asleep for good
+    while worker.try_recv(Instant::now()).is_err() {
+      assert!(worker.wait(true, None), "woken by shutdown, with no shutdown");
+    }
+    drop(submitter.join().expect("submitter"));
+  });
+}
+
+#[test]
+fn shutdown_wakes_worker_rung_at_the_same_time() {
+  model(|| {
+    let (sender, worker) = single_worker();
+    let doorbell = sender.doorbell();
+    let (ringer, slot) = (doorbell.clone(), worker.worker());
+    let shutdown = spawn(move || doorbell.close());
+    let ring = spawn(move || {
+      ringer.ring(slot);
+    });
+
+    // However the ring and the shutdown land, the worker ends up stopping
+    while worker.wait(false, None) {}
+    assert!(worker.is_shut_down());
+    shutdown.join().expect("shutdown");
+    ring.join().expect("ring");
+    drop(sender);
+  });
+}
@@ -0,0 +1,108 @@
+// src/memory.rs - Approximate native memory held by the executor
+//
+// Queued tasks, uncollected results and the performance cache all grow with
+// load, and a container counts them against its limit like any other memory.
+// Their size is estimated from the text each entry holds plus a fixed
+// overhead, and compared against `memory_watermark_mb` on every submission.
+// Above it the executor clears the performance cache, rejects submissions
+// with `QueueFull` until usage is back under, and tells JavaScript once per
+// excursion through a `memory_pressure` event and `onMemoryPressure`, so it
+// can relieve pressure on its side too.
+use std::sync::atomic::{ AtomicBool, Ordering };
+
+use crate::bindings::napi;
+use crate::sync::{ Mutex, lock_recovering };
+use crate::{ OptimizedWorkerResult, OptimizedWorkerTask, PerformanceMetric };
+
+// Per entry, beyond the text it holds: hash table and allocator bookkeeping
+// and short keys such as task IDs and function hashes, roughly
+const ENTRY_OVERHEAD: usize = 64;
+
+pub fn task_bytes(task: &OptimizedWorkerTask) -> usize {
+  size_of::<OptimizedWorkerTask>() +
+    ENTRY_OVERHEAD +
+    task.id.len() +
+    task.function_code.len() +
+    task.data.len()
+}
+
+pub fn result_bytes(result: &OptimizedWorkerResult) -> usize {
+  size_of::<OptimizedWorkerResult>() +
+    ENTRY_OVERHEAD +
+    result.id.len() +
+    result.result.as_ref().map_or(0, String::len) +
+    result.error.as_ref().map_or(0, String::len) +
+    result.binary.as_ref().map_or(0, |binary| binary.len())
+}
+
+/// Estimated memory of a performance cache holding `entries` functions
+pub fn cache_bytes(entries: usize) -> usize {
+  entries * (size_of::<PerformanceMetric>() + ENTRY_OVERHEAD)
+}
+
+/// Estimated native memory held by the executor, from `getMemoryUsage`
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct MemoryUsage {
+  /// Tasks waiting in the shared queue
+  pub queued_bytes: f64,
+  /// Results nobody has collected yet
+  pub result_bytes: f64,
+  pub cache_bytes: f64,
+  pub total_bytes: f64,
+  /// `memory_watermark_mb` in bytes, if set
+  pub watermark_bytes: Option<f64>,
+}
+
+/// Called with the usage that crossed the watermark
+pub type PressureListener = Box<dyn Fn(MemoryUsage) + Send + Sync>;
+
+/// Where usage stands against the watermark
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pressure {
+  Below,
+  /// Over it now, and wasn't at the last check
+  Crossed,
+  Above,
+}
+
+pub struct MemoryWatermark {
+  limit: Option<usize>,
+  // Set while over the watermark, so each excursion is reported once
+  above: AtomicBool,
+  listener: Mutex<Option<PressureListener>>,
+}
+
+impl MemoryWatermark {
+  pub fn new(limit_mb: Option<u32>) -> Self {
+    Self {
+      limit: limit_mb.map(|mb| (mb as usize) * 1024 * 1024),
+      above: AtomicBool::new(false),
+      listener: Mutex::new(None),
+    }
+  }
+
+  /// The watermark in bytes; `None` turns tracking off
+  pub fn limit(&self) -> Option<usize> {
+    self.limit
+  }
+
+  pub fn check(&self, total_bytes: usize) -> Pressure {
+    let over = self.limit.is_some_and(|limit| total_bytes > limit);
+    match (over, self.above.swap(over, Ordering::AcqRel)) {
+      (false, _) => Pressure::Below,
+      (true, false) => Pressure::Crossed,
+      (true, true) => Pressure::Above,
+    }
+  }
+
+  pub fn set_listener(&self, listener: Option<PressureListener>) {
+    *lock_recovering(&self.listener, || {}) = listener;
+  }
+
+  pub fn notify(&self, usage: MemoryUsage) {
+    if let Some(listener) = lock_recovering(&self.listener, || {}).as_ref() {
+      listener(usage);
+    }
+  }
+}
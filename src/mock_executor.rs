@@ -0,0 +1,1071 @@
+// src/mock_executor.rs - Synchronous stand-in for OptimizedMultiCoreExecutor
+use crossbeam::channel;
+use napi_derive::napi;
+use napi::bindgen_prelude::{ AsyncTask, Buffer };
+use napi::{ Env, JsFunction, JsObject, Result };
+use std::collections::{ HashMap, HashSet, VecDeque };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::time::Duration;
+
+use crate::abort;
+use crate::autoscale::AutoscaleConfig;
+use crate::background::BackgroundStats;
+use crate::binary::{ FastPathOutput, ResultBytes };
+use crate::child::{ ChildOptions, ChildStatus };
+use crate::clock::SystemClock;
+use crate::cpu_budget::CpuBudget;
+use crate::executor_config::ExecutorConfig;
+use crate::fallback::{ FallbackConfig, Fallbacks };
+use crate::health::HealthCheck;
+use crate::in_flight::{ IdleWait, InFlight };
+use crate::inline_batch;
+use crate::legacy;
+use crate::memory::{ self, MemoryUsage };
+use crate::priority::Priority;
+use crate::protocol::{ self, ProtocolAgreement, ProtocolSupport };
+use crate::rate_limit::{ RateKey, RateLimitConfig };
+use crate::receipt::ExecutionReceipt;
+use crate::result_router::Subscription;
+use crate::run_task::RunTask;
+use crate::stream_batch::StreamBatch;
+use crate::sync::{ Arc, Mutex };
+use crate::task_groups::GroupStatus;
+use crate::task_status::{ QueueStats, TaskState, TaskStatus };
+use crate::typed_result::TypedResult;
+use crate::{
+  ExecutorStats,
+  OptimizationHints,
+  OptimizationStats,
+  OptimizedMultiCoreExecutor,
+  OptimizedWorkerResult,
+  OptimizedWorkerTask,
+  Reply,
+};
+
+/// A scripted outcome handed to the next matching submission
+#[derive(Debug, Clone)]
+struct ScriptedOutcome {
+  result: std::result::Result<String, String>,
+  latency_ms: u64,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+  // Outcomes for any function, consumed in order
+  default_script: VecDeque<ScriptedOutcome>,
+  // Outcomes for a specific function hash, consumed before the default script
+  function_scripts: HashMap<String, VecDeque<ScriptedOutcome>>,
+  completed: VecDeque<OptimizedWorkerResult>,
+  submitted: Vec<String>,
+  // Final status of every task; mock tasks finish on submission
+  statuses: HashMap<String, TaskStatus>,
+  stats: OptimizationStats,
+  next_id: u64,
+  shut_down: bool,
+  // Set by `drain`: later submissions are rejected
+  draining: bool,
+  // Task groups named so far, and whether each is sealed
+  groups: HashMap<String, bool>,
+  // Recorded by `set_concurrency_limit`; mock tasks run one at a time anyway
+  concurrency_limits: HashMap<String, u32>,
+  // Recorded by the rate limit setters; mock tasks are never delayed
+  rate_limits: HashMap<RateKey, RateLimitConfig>,
+  // Open child executors, whose budgets never fill: mock tasks finish on submission
+  children: HashMap<String, ChildStatus>,
+  next_child_id: u64,
+  // Strict mode from the config, applied to the fast paths
+  strict: bool,
+  // Code run in place of failed fast paths; there's no registry to name
+  fallbacks: Fallbacks,
+}
+
+/// Mock executor with the same API as `OptimizedMultiCoreExecutor`.
+///
+/// Tasks run synchronously on submission and never spawn threads. Results
+/// come from scripted outcomes when any are queued, otherwise from the real
+/// Rust fast paths. Scripted latencies are reported, not slept.
+#[napi]
+pub struct MockExecutor {
+  worker_count: AtomicUsize,
+  // Fewest and most workers `set_worker_count` accepts
+  fewest_workers: usize,
+  max_workers: usize,
+  state: Mutex<MockState>,
+  cpu_budget: CpuBudget,
+}
+
+#[napi]
+impl MockExecutor {
+  /// Create a mock executor reporting `config.worker_count` workers (default 1)
+  #[napi(constructor)]
+  pub fn new(config: Option<ExecutorConfig>) -> Result<Self> {
+    // Mock tasks run inline, but an invalid config should still be rejected
+    let config = config.unwrap_or_default();
+    config.validate().map_err(napi::Error::from_reason)?;
+    let pools = config.operation_pools().map_err(napi::Error::from_reason)?;
+    let fewest_workers = pools.fewest_workers();
+    let worker_count = (config.worker_count.unwrap_or(1) as usize).max(fewest_workers);
+    let max_workers = config.max_worker_count
+      .map_or_else(|| worker_count.max(num_cpus::get()), |max| max as usize);
+    Ok(Self {
+      worker_count: AtomicUsize::new(worker_count),
+      fewest_workers,
+      max_workers,
+      state: Mutex::new(MockState {
+        strict: config.strict(),
+        ..MockState::default()
+      }),
+      cpu_budget: CpuBudget::new(num_cpus::get(), worker_count),
+    })
+  }
+
+  /// Script a successful result, optionally only for one function hash
+  #[napi]
+  pub fn script_result(
+    &self,
+    result: String,
+    latency_ms: Option<u32>,
+    function_hash: Option<String>
+  ) -> Result<()> {
+    self.push_outcome(Ok(result), latency_ms, function_hash)
+  }
+
+  /// Script an error, optionally only for one function hash
+  #[napi]
+  pub fn script_error(
+    &self,
+    error: String,
+    latency_ms: Option<u32>,
+    function_hash: Option<String>
+  ) -> Result<()> {
+    self.push_outcome(Err(error), latency_ms, function_hash)
+  }
+
+  /// Task IDs submitted so far, in submission order
+  #[napi]
+  pub fn get_submitted_task_ids(&self) -> Result<Vec<String>> {
+    Ok(self.lock_state()?.submitted.clone())
+  }
+
+  /// No-op, kept for API parity
+  #[napi]
+  pub fn warm_cores_for_workload(&self, _operation_type: String, _cores: u32) -> Result<()> {
+    Ok(())
+  }
+
+  /// Run a task synchronously and queue its result; an already-aborted `signal` throws
+  #[napi(
+    ts_args_type = "functionCode: string, data: string, optimizationHints: OptimizationHints, meta?: any | undefined | null, timeoutMs?: number | undefined | null, signal?: AbortSignal | undefined | null"
+  )]
+  pub fn submit_optimized_task(
+    &self,
+    function_code: String,
+    data: String,
+    optimization_hints: OptimizationHints,
+    meta: Option<serde_json::Value>,
+    timeout_ms: Option<u32>,
+    signal: Option<JsObject>
+  ) -> Result<String> {
+    if let Some(signal) = signal.as_ref() && abort::is_aborted(signal)? {
+      return Err(abort::abort_error());
+    }
+    self.submit_task(function_code, data, optimization_hints, meta, timeout_ms)
+  }
+
+  /// Run a task synchronously and queue its result; `submitOptimizedTask` for
+  /// Rust callers, like `OptimizedMultiCoreExecutor::submit_task`
+  pub fn submit_task(
+    &self,
+    function_code: String,
+    data: String,
+    optimization_hints: OptimizationHints,
+    meta: Option<serde_json::Value>,
+    timeout_ms: Option<u32>
+  ) -> Result<String> {
+    let mut state = self.lock_state()?;
+    let result = Self::execute(
+      &mut state,
+      function_code,
+      data,
+      optimization_hints,
+      meta,
+      timeout_ms
+    )?;
+    let task_id = result.id.clone();
+    state.completed.push_back(result);
+    Ok(task_id)
+  }
+
+  /// Run a task synchronously and queue its result, with hints and `meta` as JSON text
+  ///
+  /// @deprecated Use `submitOptimizedTask` with a hints object.
+  #[napi]
+  pub fn submit_optimized_task_json(
+    &self,
+    function_code: String,
+    data: String,
+    optimization_hints: String,
+    meta: Option<String>
+  ) -> Result<String> {
+    let hints = legacy::parse_hints(&optimization_hints).map_err(napi::Error::from_reason)?;
+    let meta = legacy::parse_meta(meta.as_deref()).map_err(napi::Error::from_reason)?;
+    self.submit_task(function_code, data, hints, meta, None)
+  }
+
+  /// Run a task synchronously, ignoring `delay_ms`: mock tasks are never delayed
+  #[napi]
+  pub fn submit_delayed(
+    &self,
+    function_code: String,
+    data: String,
+    optimization_hints: OptimizationHints,
+    _delay_ms: u32
+  ) -> Result<String> {
+    self.submit_optimized_task(function_code, data, optimization_hints, None, None, None)
+  }
+
+  /// Run a task synchronously and discard its result
+  #[napi]
+  pub fn submit_fire(
+    &self,
+    function_code: String,
+    data: String,
+    optimization_hints: OptimizationHints
+  ) -> Result<()> {
+    let mut state = self.lock_state()?;
+    Self::execute(&mut state, function_code, data, optimization_hints, None, None)?;
+    Ok(())
+  }
+
+  /// Run a task synchronously on a copy of `data`, which must be UTF-8 text
+  #[napi]
+  pub fn submit_borrowed(
+    &self,
+    function_code: String,
+    data: Buffer,
+    optimization_hints: OptimizationHints,
+    meta: Option<serde_json::Value>,
+    timeout_ms: Option<u32>
+  ) -> Result<String> {
+    let data = String::from_utf8(data.to_vec()).map_err(|e| {
+      napi::Error::from_reason(format!("Borrowed data is not valid UTF-8: {}", e.utf8_error()))
+    })?;
+    self.submit_optimized_task(function_code, data, optimization_hints, meta, timeout_ms, None)
+  }
+
+  /// Always false: mock tasks have read their data by the time this can be called
+  #[napi]
+  pub fn release_borrowed(&self, _task_id: String) -> bool {
+    false
+  }
+
+  /// Run a task synchronously and return an already-settled Promise of its result.
+  /// An already-aborted `signal` rejects with `AbortError` without running the task.
+  #[napi(
+    ts_args_type = "functionCode: string, data: string, optimizationHints: OptimizationHints, meta?: any | undefined | null, timeoutMs?: number | undefined | null, signal?: AbortSignal | undefined | null",
+    ts_return_type = "Promise<OptimizedWorkerResult>"
+  )]
+  pub fn run_task(
+    &self,
+    function_code: String,
+    data: String,
+    optimization_hints: OptimizationHints,
+    meta: Option<serde_json::Value>,
+    timeout_ms: Option<u32>,
+    signal: Option<JsObject>
+  ) -> Result<AsyncTask<RunTask>> {
+    let (reply_tx, reply_rx) = channel::bounded(1);
+    let (abort_tx, abort_rx) = channel::bounded(1);
+    let run = RunTask::new(reply_rx, timeout_ms).with_abort(abort_rx);
+
+    if let Some(signal) = signal.as_ref() && abort::is_aborted(signal)? {
+      let _ = abort_tx.send(());
+      return Ok(AsyncTask::new(run));
+    }
+
+    let mut state = self.lock_state()?;
+    let result = Self::execute(
+      &mut state,
+      function_code,
+      data,
+      optimization_hints,
+      meta,
+      timeout_ms
+    )?;
+    state.stats.record(&result);
+
+    let _ = reply_tx.send(result);
+    Ok(AsyncTask::new(run))
+  }
+
+  /// Run `function_code` on each of `items` synchronously and return their
+  /// results, parsed, in the same order; nothing is queued for collection
+  #[napi]
+  pub fn execute_inline_batch(
+    &self,
+    function_code: String,
+    items: Vec<serde_json::Value>,
+    optimization_hints: Option<OptimizationHints>
+  ) -> Result<Vec<serde_json::Value>> {
+    let hints = match optimization_hints {
+      Some(hints) => hints,
+      None => inline_batch::default_hints(&function_code).map_err(napi::Error::from_reason)?,
+    };
+    let mut state = self.lock_state()?;
+    let mut results = Vec::with_capacity(items.len());
+    for item in items {
+      let data = item.to_string();
+      let code = function_code.clone();
+      let result = Self::execute(&mut state, code, data, hints.clone(), None, None)?;
+      state.stats.record(&result);
+      results.push(Some(result));
+    }
+    inline_batch::values(results).map_err(napi::Error::from_reason)
+  }
+
+  /// Run a batch synchronously and queue its results. `reserved_workers` is
+  /// validated like the real executor's but otherwise has no effect.
+  #[napi]
+  pub fn submit_optimized_batch(
+    &self,
+    tasks: Vec<(String, String)>,
+    optimization_hints: OptimizationHints,
+    metas: Option<Vec<serde_json::Value>>,
+    task_hints: Option<Vec<Option<OptimizationHints>>>,
+    timeout_ms: Option<u32>,
+    reserved_workers: Option<u32>
+  ) -> Result<Vec<String>> {
+    if let Some(workers) = reserved_workers && workers as usize > self.workers() {
+      return Err(
+        napi::Error::from_reason(
+          format!("Cannot reserve {} of {} workers", workers, self.workers())
+        )
+      );
+    }
+    OptimizedMultiCoreExecutor::check_per_task_len(
+      "meta",
+      tasks.len(),
+      metas.as_ref().map(Vec::len)
+    )?;
+    OptimizedMultiCoreExecutor::check_per_task_len(
+      "hint",
+      tasks.len(),
+      task_hints.as_ref().map(Vec::len)
+    )?;
+    let mut metas = metas.map(|m| m.into_iter());
+    let mut task_hints = task_hints.map(|h| h.into_iter());
+
+    let mut state = self.lock_state()?;
+    let mut task_ids = Vec::with_capacity(tasks.len());
+    for (function_code, data) in tasks {
+      let meta = metas.as_mut().and_then(|m| m.next());
+      let hints = task_hints
+        .as_mut()
+        .and_then(|h| h.next())
+        .flatten()
+        .unwrap_or_else(|| optimization_hints.clone());
+      let result = Self::execute(&mut state, function_code, data, hints, meta, timeout_ms)?;
+      task_ids.push(result.id.clone());
+      state.completed.push_back(result);
+    }
+    Ok(task_ids)
+  }
+
+  /// Pop the next completed result; errors instead of blocking when none are queued
+  ///
+  /// @deprecated Use `getResultById`, or `runTask`.
+  #[napi]
+  pub fn get_optimized_result(&self, _timeout_ms: Option<u32>) -> Result<OptimizedWorkerResult> {
+    let mut state = self.lock_state()?;
+    let result = state.completed
+      .pop_front()
+      .ok_or_else(|| napi::Error::from_reason("No results available".to_string()))?;
+
+    state.stats.record(&result);
+    Ok(result)
+  }
+
+  /// Pop the next completed result as JSON text
+  ///
+  /// @deprecated Use `getResultById`, or `runTask`.
+  #[napi]
+  pub fn get_optimized_result_json(&self, timeout_ms: Option<u32>) -> Result<String> {
+    let result = self.get_optimized_result(timeout_ms)?;
+    legacy::result_json(&result).map_err(napi::Error::from_reason)
+  }
+
+  /// Pop every queued result (up to `max_results`)
+  #[napi]
+  pub fn poll_results(&self, max_results: Option<u32>) -> Result<Vec<OptimizedWorkerResult>> {
+    let mut state = self.lock_state()?;
+    let count = state.completed.len().min(max_results.map_or(usize::MAX, |m| m as usize));
+
+    let results: Vec<OptimizedWorkerResult> = state.completed.drain(..count).collect();
+    for result in &results {
+      state.stats.record(result);
+    }
+    Ok(results)
+  }
+
+  /// Pop the queued result of `task_id`; errors instead of blocking when it isn't queued
+  #[napi]
+  pub fn get_result_by_id(
+    &self,
+    task_id: String,
+    _timeout_ms: Option<u32>
+  ) -> Result<OptimizedWorkerResult> {
+    let mut state = self.lock_state()?;
+    let result = state.completed
+      .iter()
+      .position(|result| result.id == task_id)
+      .and_then(|position| state.completed.remove(position))
+      .ok_or_else(|| napi::Error::from_reason(format!("No result queued for task '{}'", task_id)))?;
+
+    state.stats.record(&result);
+    Ok(result)
+  }
+
+  /// Pop `task_count` completed results
+  ///
+  /// @deprecated Use `getResultById` for each task ID, or `streamBatchResults`.
+  #[napi]
+  pub fn get_optimized_batch_results(
+    &self,
+    task_count: u32,
+    timeout_ms: Option<u32>
+  ) -> Result<Vec<OptimizedWorkerResult>> {
+    (0..task_count).map(|_| self.get_optimized_result(timeout_ms)).collect()
+  }
+
+  /// Stream the queued results of `task_ids` to `callback`; errors up front if any
+  /// of them has no queued result, since nothing else will ever complete
+  #[napi(
+    ts_args_type = "taskIds: Array<string>, callback: (err: Error | null, result: OptimizedWorkerResult) => void, timeoutMs?: number | undefined | null",
+    ts_return_type = "Promise<number>"
+  )]
+  pub fn stream_batch_results(
+    &self,
+    env: Env,
+    task_ids: Vec<String>,
+    callback: JsFunction,
+    timeout_ms: Option<u32>
+  ) -> Result<AsyncTask<StreamBatch>> {
+    let mut state = self.lock_state()?;
+    let wanted: HashSet<&String> = task_ids.iter().collect();
+    let missing = wanted.iter().find(|&&id| state.completed.iter().all(|r| &r.id != id));
+    if let Some(missing) = missing {
+      return Err(napi::Error::from_reason(format!("No result queued for task '{}'", missing)));
+    }
+
+    let (sender, receiver) = channel::unbounded();
+    let (streamed, kept) = state.completed.drain(..).partition(|r| wanted.contains(&r.id));
+    state.completed = kept;
+    for result in streamed {
+      state.stats.record(&result);
+      let _ = sender.send(result);
+    }
+    let results = Subscription::of(receiver);
+    Ok(AsyncTask::new(StreamBatch::new(&env, callback, results, wanted.len(), timeout_ms)?))
+  }
+
+  /// Drop a queued result; tasks run on submission, so there is nothing else to cancel
+  #[napi]
+  pub fn cancel_task(&self, task_id: String) -> Result<()> {
+    let mut state = self.lock_state()?;
+    let queued = state.completed.len();
+    state.completed.retain(|result| result.id != task_id);
+    if state.completed.len() < queued {
+      Self::mark_cancelled(&mut state, &task_id);
+    }
+    Ok(())
+  }
+
+  /// Drop every queued result
+  #[napi]
+  pub fn cancel_all(&self) -> Result<()> {
+    let mut state = self.lock_state()?;
+    let dropped: Vec<OptimizedWorkerResult> = state.completed.drain(..).collect();
+    for result in dropped {
+      Self::mark_cancelled(&mut state, &result.id);
+    }
+    Ok(())
+  }
+
+  /// Status of a submitted task: always finished, since mock tasks run on submission.
+  /// Tasks whose queued result was dropped by `cancel_task`/`cancel_all` report cancelled.
+  #[napi]
+  pub fn get_task_status(&self, task_id: String) -> Result<TaskStatus> {
+    self.lock_state()?
+      .statuses.get(&task_id)
+      .cloned()
+      .ok_or_else(|| napi::Error::from_reason(format!("Unknown task ID '{}'", task_id)))
+  }
+
+  /// Always 0: mock tasks run on submission, so nothing ever waits in a queue
+  #[napi]
+  pub fn get_queue_depth(&self) -> u32 {
+    0
+  }
+
+  /// An empty queue: mock tasks run on submission
+  #[napi]
+  pub fn get_queue_stats(&self, include_task_ids: Option<bool>) -> QueueStats {
+    QueueStats {
+      task_ids: include_task_ids.unwrap_or(false).then(Vec::new),
+      ..QueueStats::default()
+    }
+  }
+
+  /// Memory of the uncollected results; the mock queues and caches nothing
+  #[napi]
+  pub fn get_memory_usage(&self) -> Result<MemoryUsage> {
+    let state = self.lock_state()?;
+    let results: usize = state.completed.iter().map(memory::result_bytes).sum();
+    Ok(MemoryUsage {
+      result_bytes: results as f64,
+      total_bytes: results as f64,
+      ..MemoryUsage::default()
+    })
+  }
+
+  /// No-op: the mock has no watermark to cross
+  #[napi(ts_args_type = "callback: ((usage: MemoryUsage) => void) | null")]
+  pub fn on_memory_pressure(&self, _callback: Option<JsFunction>) {}
+
+  /// Run `fallback` in place of a failed fast path like the real executor.
+  /// Registered fallbacks are rejected: the mock has no function registry.
+  #[napi]
+  pub fn set_fallback(
+    &self,
+    function_hash: String,
+    fallback: Option<FallbackConfig>
+  ) -> Result<()> {
+    self.lock_state()?
+      .fallbacks.set(function_hash, fallback)
+      .map_err(napi::Error::from_reason)
+  }
+
+  /// Record a concurrency limit like the real executor; mock tasks already run
+  /// one at a time
+  #[napi]
+  pub fn set_concurrency_limit(&self, function_hash: String, limit: Option<u32>) -> Result<()> {
+    let mut state = self.lock_state()?;
+    match limit {
+      Some(0) =>
+        Err(napi::Error::from_reason("Concurrency limit must be at least 1".to_string())),
+      Some(limit) => {
+        state.concurrency_limits.insert(function_hash, limit);
+        Ok(())
+      }
+      None => {
+        state.concurrency_limits.remove(&function_hash);
+        Ok(())
+      }
+    }
+  }
+
+  /// Concurrency limit recorded by `set_concurrency_limit`, if any
+  #[napi]
+  pub fn get_concurrency_limit(&self, function_hash: String) -> Result<Option<u32>> {
+    Ok(self.lock_state()?.concurrency_limits.get(&function_hash).copied())
+  }
+
+  /// Record a function rate limit like the real executor; mock tasks are never delayed
+  #[napi]
+  pub fn set_function_rate_limit(
+    &self,
+    function_hash: String,
+    limit: Option<RateLimitConfig>
+  ) -> Result<()> {
+    self.set_rate_limit(RateKey::Function(function_hash), limit)
+  }
+
+  /// Rate limit recorded for `function_hash`, if any
+  #[napi]
+  pub fn get_function_rate_limit(&self, function_hash: String) -> Result<Option<RateLimitConfig>> {
+    Ok(self.lock_state()?.rate_limits.get(&RateKey::Function(function_hash)).cloned())
+  }
+
+  /// Record a tenant rate limit like the real executor; mock tasks are never delayed
+  #[napi]
+  pub fn set_tenant_rate_limit(
+    &self,
+    tenant: String,
+    limit: Option<RateLimitConfig>
+  ) -> Result<()> {
+    self.set_rate_limit(RateKey::Tenant(tenant), limit)
+  }
+
+  /// Rate limit recorded for `tenant`, if any
+  #[napi]
+  pub fn get_tenant_rate_limit(&self, tenant: String) -> Result<Option<RateLimitConfig>> {
+    Ok(self.lock_state()?.rate_limits.get(&RateKey::Tenant(tenant)).cloned())
+  }
+
+  /// Open a child executor like the real one; its budget is only reported, since
+  /// mock tasks finish on submission
+  #[napi]
+  pub fn open_child(&self, options: Option<ChildOptions>) -> Result<String> {
+    let options = options.unwrap_or_default();
+    options.validate().map_err(napi::Error::from_reason)?;
+    let mut state = self.lock_state()?;
+    state.next_child_id += 1;
+    let child_id = format!("child_{}", state.next_child_id);
+    let status = ChildStatus {
+      child_id: child_id.clone(),
+      running: 0,
+      unfinished: 0,
+      bytes: 0.0,
+      max_concurrency: options.max_concurrency.map_or(self.workers() as u32, |max| {
+        max.min(self.workers() as u32)
+      }),
+      max_bytes: options.max_bytes.map(f64::from),
+      remaining_ms: options.time_budget_ms.map(f64::from),
+    };
+    state.children.insert(child_id.clone(), status);
+    Ok(child_id)
+  }
+
+  /// Close a child executor; later submissions to it are rejected
+  #[napi]
+  pub fn close_child(&self, child_id: String) -> Result<bool> {
+    Ok(self.lock_state()?.children.remove(&child_id).is_some())
+  }
+
+  /// Budget of an open child executor, with nothing ever in use
+  #[napi]
+  pub fn get_child_status(&self, child_id: String) -> Result<ChildStatus> {
+    self
+      .lock_state()?
+      .children.get(&child_id)
+      .cloned()
+      .ok_or_else(|| {
+        napi::Error::from_reason(format!("Unknown or closed child executor '{}'", child_id))
+      })
+  }
+
+  /// Seal a task group; its members have already finished
+  #[napi]
+  pub fn seal_group(&self, name: String) -> Result<()> {
+    self.lock_state()?.groups.insert(name, true);
+    Ok(())
+  }
+
+  /// A task group as the real executor reports it once every member has finished
+  #[napi]
+  pub fn get_group_status(&self, name: String) -> Result<Option<GroupStatus>> {
+    Ok(
+      self
+        .lock_state()?
+        .groups.get(&name)
+        .map(|&sealed| GroupStatus {
+          name: name.clone(),
+          pending: 0,
+          waiting: 0,
+          sealed,
+          complete: sealed,
+        })
+    )
+  }
+
+  /// Forget a sealed task group; unsealed ones have not completed
+  #[napi]
+  pub fn remove_group(&self, name: String) -> Result<bool> {
+    let mut state = self.lock_state()?;
+    match state.groups.get(&name) {
+      None => Ok(false),
+      Some(false) =>
+        Err(napi::Error::from_reason(format!("Task group '{}' has not completed", name))),
+      Some(true) => Ok(state.groups.remove(&name).is_some()),
+    }
+  }
+
+  /// Resolves true at once for a group some task has named: its members
+  /// finished on submission
+  #[napi(ts_return_type = "Promise<boolean>")]
+  pub fn wait_for_group(
+    &self,
+    name: String,
+    timeout_ms: Option<u32>
+  ) -> Result<AsyncTask<IdleWait>> {
+    if !self.lock_state()?.groups.contains_key(&name) {
+      return Err(napi::Error::from_reason(format!("Unknown task group '{}'", name)));
+    }
+    Ok(self.wait_for_idle(timeout_ms))
+  }
+
+  /// Always 0: group members finish on submission, so none are left to cancel
+  #[napi]
+  pub fn cancel_group(&self, _name: String) -> u32 {
+    0
+  }
+
+  /// Record a CPU cap like the real executor; mock tasks never sleep
+  #[napi]
+  pub fn set_max_cpu_fraction(&self, max_cpu_fraction: Option<f64>) -> Result<()> {
+    self.cpu_budget.configure(max_cpu_fraction).map_err(napi::Error::from_reason)
+  }
+
+  /// CPU cap recorded by `set_max_cpu_fraction`, if any
+  #[napi]
+  pub fn get_max_cpu_fraction(&self) -> Option<f64> {
+    self.cpu_budget.max_cpu_fraction()
+  }
+
+  /// Accepted for parity; the mock never detects throttling
+  #[napi]
+  pub fn set_throttle_detection(&self, _enabled: bool) {}
+
+  /// Always the full worker count
+  #[napi]
+  pub fn get_effective_parallelism(&self) -> u32 {
+    self.workers() as u32
+  }
+
+  /// Accepted for parity; the mock runs no background passes
+  #[napi]
+  pub fn set_background_optimization(&self, _enabled: bool) {}
+
+  /// No passes, as the mock runs none
+  #[napi]
+  pub fn get_background_stats(&self) -> BackgroundStats {
+    BackgroundStats::default()
+  }
+
+  /// Healthy with zero latency until shut down; no canary task is recorded
+  #[napi(ts_return_type = "Promise<HealthStatus>")]
+  pub fn health_check(&self, timeout_ms: Option<u32>) -> Result<AsyncTask<HealthCheck>> {
+    let probe = if self.lock_state()?.shut_down {
+      Err("Worker pool is shut down".to_string())
+    } else {
+      let (reply_tx, reply_rx) = channel::bounded(1);
+      let _ = reply_tx.send(OptimizedWorkerResult {
+        id: "mock_health_check".to_string(),
+        result: Some("42".to_string()),
+        error: None,
+        duration_ms: 0.0,
+        worker_id: 0,
+        execution_type: "mock_health_check".to_string(),
+        optimization_used: "mock_health_check".to_string(),
+        cache_hit: false,
+        timed_out: false,
+        meta: None,
+        receipt: None,
+        typed: None,
+        binary: None,
+        fallback: None,
+      });
+      Ok(reply_rx)
+    };
+    let workers = self.workers() as u32;
+    let clock = SystemClock::shared();
+    Ok(AsyncTask::new(HealthCheck::new(probe, timeout_ms, workers, workers, 0, clock)))
+  }
+
+  /// Stats in the same shape as the real executor
+  ///
+  /// @deprecated Use `getStatsObject`.
+  #[napi]
+  pub fn get_optimization_stats(&self) -> Result<String> {
+    Ok(self.lock_state()?.stats.to_json().to_string())
+  }
+
+  /// Stats as a typed object
+  #[napi]
+  pub fn get_stats_object(&self) -> Result<ExecutorStats> {
+    Ok(self.lock_state()?.stats.to_object())
+  }
+
+  /// Reset stats and drop queued results and scripts
+  #[napi]
+  pub fn reset_optimization_state(&self) -> Result<()> {
+    let mut state = self.lock_state()?;
+    state.stats = OptimizationStats::default();
+    state.completed.clear();
+    state.default_script.clear();
+    state.function_scripts.clear();
+    Ok(())
+  }
+
+  #[napi(getter)]
+  pub fn worker_count(&self) -> u32 {
+    self.workers() as u32
+  }
+
+  /// Report `workers` workers from now on, up to `max_worker_count`; tasks
+  /// still run inline
+  #[napi]
+  pub fn set_worker_count(&self, workers: u32) -> Result<()> {
+    if self.lock_state()?.shut_down {
+      return Err(napi::Error::from_reason("Worker pool is shut down".to_string()));
+    }
+    let workers = workers as usize;
+    if workers < self.fewest_workers || workers > self.max_workers {
+      return Err(
+        napi::Error::from_reason(
+          format!(
+            "Worker count must be between {} and {}, got {}",
+            self.fewest_workers,
+            self.max_workers,
+            workers
+          )
+        )
+      );
+    }
+    self.worker_count.store(workers, Ordering::Release);
+    self.cpu_budget.set_workers(workers);
+    Ok(())
+  }
+
+  /// Checks `config` like the real executor; the mock's load never changes,
+  /// so its worker count stays where it is
+  #[napi]
+  pub fn set_autoscale(&self, config: Option<AutoscaleConfig>) -> Result<()> {
+    if let Some(config) = config {
+      config.policy(self.fewest_workers, self.max_workers).map_err(napi::Error::from_reason)?;
+    }
+    Ok(())
+  }
+
+  fn workers(&self) -> usize {
+    self.worker_count.load(Ordering::Acquire)
+  }
+
+  /// Always zero, like the seed in mock receipts: the mock has no RNG
+  #[napi]
+  pub fn get_seed(&self) -> String {
+    format!("{:016x}", 0)
+  }
+
+  /// Negotiates like the real executor; mock results are JSON whatever is agreed
+  #[napi]
+  pub fn negotiate_protocol(&self, offer: ProtocolSupport) -> Result<ProtocolAgreement> {
+    protocol::negotiate(&offer).map_err(napi::Error::from_reason)
+  }
+
+  /// Reject later submissions and resolve true at once: mock tasks finish on submission
+  #[napi(ts_return_type = "Promise<boolean>")]
+  pub fn drain(&self, timeout_ms: Option<u32>) -> Result<AsyncTask<IdleWait>> {
+    self.lock_state()?.draining = true;
+    Ok(self.wait_for_idle(timeout_ms))
+  }
+
+  /// Resolves true at once; nothing is ever queued or running
+  #[napi(ts_return_type = "Promise<boolean>")]
+  pub fn wait_for_idle(&self, timeout_ms: Option<u32>) -> AsyncTask<IdleWait> {
+    AsyncTask::new(IdleWait::new(Arc::new(InFlight::new()), timeout_ms))
+  }
+
+  #[napi]
+  pub fn shutdown(&self) -> Result<()> {
+    self.lock_state()?.shut_down = true;
+    Ok(())
+  }
+
+  fn push_outcome(
+    &self,
+    result: std::result::Result<String, String>,
+    latency_ms: Option<u32>,
+    function_hash: Option<String>
+  ) -> Result<()> {
+    let outcome = ScriptedOutcome {
+      result,
+      latency_ms: latency_ms.unwrap_or(0) as u64,
+    };
+
+    let mut state = self.lock_state()?;
+    match function_hash {
+      Some(hash) => state.function_scripts.entry(hash).or_default().push_back(outcome),
+      None => state.default_script.push_back(outcome),
+    }
+    Ok(())
+  }
+
+  fn mark_cancelled(state: &mut MockState, task_id: &str) {
+    if let Some(status) = state.statuses.get_mut(task_id) {
+      status.status = TaskState::Cancelled.as_str().to_string();
+    }
+  }
+
+  fn execute(
+    state: &mut MockState,
+    function_code: String,
+    data: String,
+    hints: OptimizationHints,
+    meta: Option<serde_json::Value>,
+    timeout_ms: Option<u32>
+  ) -> Result<OptimizedWorkerResult> {
+    if state.shut_down {
+      return Err(napi::Error::from_reason("Worker pool is shut down".to_string()));
+    }
+    if state.draining {
+      return Err(napi::Error::from_reason("Executor is draining".to_string()));
+    }
+    // Mock tasks run on submission, but an invalid priority is still rejected
+    Priority::from_hints(&hints).map_err(napi::Error::from_reason)?;
+    // Members finish as they join, so a task waiting on a group never waits
+    if let Some(group) = hints.group.as_ref() {
+      if state.groups.get(group) == Some(&true) {
+        return Err(napi::Error::from_reason(format!("Task group '{}' is sealed", group)));
+      }
+      state.groups.entry(group.clone()).or_insert(false);
+    }
+    if let Some(after) = hints.after.as_ref() {
+      state.groups.entry(after.clone()).or_insert(false);
+    }
+    if let Some(child) = hints.child.as_ref() && !state.children.contains_key(child) {
+      return Err(
+        napi::Error::from_reason(format!("Unknown or closed child executor '{}'", child))
+      );
+    }
+
+    let task_id = format!("mock_task_{}", state.next_id);
+    state.next_id += 1;
+
+    let scripted = state.function_scripts
+      .get_mut(&hints.function_hash)
+      .and_then(|script| script.pop_front())
+      .or_else(|| state.default_script.pop_front());
+
+    let typed_result = hints.typed_result.unwrap_or(false);
+    // Receipt inputs are captured before the task takes ownership of them
+    let receipt_inputs = hints.include_receipt
+      .unwrap_or(false)
+      .then(|| (hints.function_hash.clone(), function_code.clone(), data.clone()));
+
+    let mut fallback = None;
+    let (mut result, duration_ms, optimization_used) = match scripted {
+      Some(outcome) =>
+        (
+          outcome.result.map(FastPathOutput::Json),
+          outcome.latency_ms as f64,
+          "mock_scripted".to_string(),
+        ),
+      None => {
+        let task = OptimizedWorkerTask {
+          id: task_id.clone(),
+          function_code,
+          data,
+          timeout_ms: None,
+          optimization_hints: hints,
+          meta: None,
+          reply: Reply::Shared,
+          epoch: 0,
+          view: None,
+          columnar: None,
+          borrowed: None,
+          expires_at: None,
+          worker: None,
+          sub_batch: None,
+        };
+        if task.optimization_hints.should_use_rust {
+          // The mock has no event log, so chunked tasks just return the whole map
+          let outcome = OptimizedMultiCoreExecutor::execute_rust_optimized(
+            &task,
+            0,
+            state.strict,
+            None,
+            &|_| {},
+            &|| {}
+          );
+          let execute = |fallback_task: &OptimizedWorkerTask| {
+            OptimizedMultiCoreExecutor::execute_rust_optimized(
+              fallback_task,
+              0,
+              state.strict,
+              None,
+              &|_| {},
+              &|| {}
+            )
+          };
+          let clock = SystemClock::shared();
+          match outcome {
+            Ok(result) => (Ok(result), 0.0, "rust_optimized".to_string()),
+            Err(err) =>
+              match state.fallbacks.run(&task, &err, Duration::ZERO, &clock, execute) {
+                Some((result, attempt)) => {
+                  fallback = Some(attempt);
+                  let used = if result.is_ok() { "rust_fallback" } else { "rust_failed" };
+                  (result, 0.0, used.to_string())
+                }
+                None => (Err(err), 0.0, "rust_failed".to_string()),
+              }
+          }
+        } else {
+          (Err("NEEDS_JS_WORKER_OPTIMIZED".to_string()), 0.0, "needs_js_worker".to_string())
+        }
+      }
+    };
+
+    // Scripted latencies past the budget surface exactly like real overruns
+    let timed_out = match timeout_ms {
+      Some(timeout_ms) if duration_ms > (timeout_ms as f64) => {
+        result = Err(
+          OptimizedMultiCoreExecutor::timeout_error(timeout_ms as u64, duration_ms as u64)
+        );
+        true
+      }
+      _ => false,
+    };
+
+    // The mock has no RNG, so its receipts always report a zero seed
+    let receipt = receipt_inputs.map(|(function_hash, function_code, data)| {
+      ExecutionReceipt::new(&function_hash, &function_code, &data, &optimization_used, 0)
+    });
+
+    let (result_ok, binary, result_err) = match result {
+      Ok(FastPathOutput::Json(val)) => (Some(val), None, None),
+      Ok(FastPathOutput::Bytes(bytes)) => (None, Some(ResultBytes(bytes)), None),
+      Err(err) => (None, None, Some(err)),
+    };
+    let typed = result_ok.as_deref().filter(|_| typed_result).map(TypedResult::decode);
+
+    state.submitted.push(task_id.clone());
+    let outcome = if result_err.is_none() { TaskState::Completed } else { TaskState::Failed };
+    state.statuses.insert(task_id.clone(), TaskStatus {
+      task_id: task_id.clone(),
+      status: outcome.as_str().to_string(),
+      queue_position: None,
+      elapsed_ms: duration_ms,
+      running_ms: Some(duration_ms),
+      worker_id: Some(0),
+    });
+
+    Ok(OptimizedWorkerResult {
+      id: task_id,
+      result: result_ok,
+      error: result_err,
+      duration_ms,
+      worker_id: 0,
+      execution_type: optimization_used.clone(),
+      optimization_used,
+      cache_hit: false,
+      timed_out,
+      meta,
+      receipt,
+      typed,
+      binary,
+      fallback,
+    })
+  }
+
+  fn set_rate_limit(&self, key: RateKey, limit: Option<RateLimitConfig>) -> Result<()> {
+    let mut state = self.lock_state()?;
+    match limit {
+      Some(limit) => {
+        limit.validate().map_err(napi::Error::from_reason)?;
+        state.rate_limits.insert(key, limit);
+      }
+      None => {
+        state.rate_limits.remove(&key);
+      }
+    }
+    Ok(())
+  }
+
+  fn lock_state(&self) -> Result<crate::sync::MutexGuard<'_, MockState>> {
+    self.state
+      .lock()
+      .map_err(|_| napi::Error::from_reason("Failed to access mock state".to_string()))
+  }
+}
@@ -0,0 +1,92 @@
+// src/mock_executor_tests.rs - The mock executor's scripted, synchronous behaviour
+use crate::OptimizationHints;
+use crate::executor_config::ExecutorConfig;
+use crate::mock_executor::MockExecutor;
+use crate::task_spec;
+
+fn mock() -> MockExecutor {
+  MockExecutor::new(None).expect("mock")
+}
+
+fn hints(function_code: &str) -> OptimizationHints {
+  task_spec::resolve_hints(function_code, &[]).expect("hints")
+}
+
+fn submit(
+  mock: &MockExecutor,
+  function_code: &str,
+  data: &str,
+  timeout_ms: Option<u32>
+) -> String {
+  let hints = hints(function_code);
+  mock
+    .submit_task(function_code.to_string(), data.to_string(), hints, None, timeout_ms)
+    .expect("submit")
+}
+
+#[test]
+fn scripted_outcomes_are_used_in_order_before_the_fast_paths() {
+  let mock = mock();
+  let doubled = hints("x => x * 2").function_hash;
+  mock.script_result("\"default\"".to_string(), None, None).unwrap();
+  mock.script_error("boom".to_string(), None, Some(doubled)).unwrap();
+
+  // A function's own script comes before the default one
+  let first = submit(&mock, "x => x * 2", "1", None);
+  let failed = mock.get_result_by_id(first, None).unwrap();
+  assert_eq!(failed.error.as_deref(), Some("boom"));
+  assert_eq!(failed.optimization_used, "mock_scripted");
+
+  let second = submit(&mock, "x => x * 2", "1", None);
+  let scripted = mock.get_result_by_id(second, None).unwrap();
+  assert_eq!(scripted.result.as_deref(), Some("\"default\""));
+
+  // With the scripts used up, the real fast path runs
+  let third = submit(&mock, "x => x * 2", "21", None);
+  let real = mock.get_result_by_id(third.clone(), None).unwrap();
+  assert_eq!(real.result.as_deref(), Some("42"));
+  assert_eq!(real.optimization_used, "rust_optimized");
+
+  assert_eq!(mock.get_submitted_task_ids().unwrap().len(), 3);
+  assert_eq!(mock.get_task_status(third).unwrap().status, "completed");
+}
+
+#[test]
+fn scripted_latency_past_the_timeout_is_a_timeout() {
+  let mock = mock();
+  mock.script_result("1".to_string(), Some(200), None).unwrap();
+  mock.script_result("2".to_string(), Some(50), None).unwrap();
+
+  let slow = submit(&mock, "x => x", "1", Some(100));
+  let overran = mock.get_result_by_id(slow, None).unwrap();
+  assert!(overran.timed_out);
+  assert!(overran.result.is_none());
+  assert_eq!(overran.duration_ms, 200.0);
+
+  let fast = submit(&mock, "x => x", "1", Some(100));
+  assert!(!mock.get_result_by_id(fast, None).unwrap().timed_out);
+}
+
+#[test]
+fn results_are_queued_without_blocking() {
+  let config = ExecutorConfig { worker_count: Some(3), ..ExecutorConfig::default() };
+  let mock = MockExecutor::new(Some(config)).expect("mock");
+  assert_eq!(mock.worker_count(), 3);
+  assert!(mock.get_optimized_result(Some(1000)).is_err());
+
+  let task_ids: Vec<String> = (0..3)
+    .map(|i| submit(&mock, "x => x + 1", &i.to_string(), None))
+    .collect();
+  let polled: Vec<String> = mock
+    .poll_results(Some(2))
+    .unwrap()
+    .into_iter()
+    .map(|result| result.id)
+    .collect();
+  assert_eq!(polled, task_ids[..2]);
+  assert!(mock.get_result_by_id(task_ids[0].clone(), None).is_err());
+
+  mock.shutdown().unwrap();
+  let rejected = mock.submit_task("x => x".into(), "1".into(), hints("x => x"), None, None);
+  assert!(rejected.is_err());
+}
@@ -0,0 +1,96 @@
+// src/number_format.rs - Output precision and rounding for numeric fast paths
+use crate::OptimizationHints;
+
+/// How a result is rounded to the requested precision
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+  /// Ties away from zero, like `Math.round` for positive values
+  HalfUp,
+  /// Ties to the nearest even digit (banker's rounding)
+  HalfEven,
+  Floor,
+  Ceil,
+  Trunc,
+}
+
+impl Rounding {
+  pub fn parse(value: &str) -> Result<Self, String> {
+    match value {
+      "half_up" => Ok(Rounding::HalfUp),
+      "half_even" => Ok(Rounding::HalfEven),
+      "floor" => Ok(Rounding::Floor),
+      "ceil" => Ok(Rounding::Ceil),
+      "trunc" => Ok(Rounding::Trunc),
+      other =>
+        Err(
+          format!(
+            "Unknown rounding mode '{}', expected half_up, half_even, floor, ceil or trunc",
+            other
+          )
+        ),
+    }
+  }
+
+  fn apply(self, value: f64) -> f64 {
+    match self {
+      Rounding::HalfUp => value.round(),
+      Rounding::HalfEven => value.round_ties_even(),
+      Rounding::Floor => value.floor(),
+      Rounding::Ceil => value.ceil(),
+      Rounding::Trunc => value.trunc(),
+    }
+  }
+}
+
+/// Formatting applied to every number a fast path returns
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NumberFormat {
+  /// Decimal places to keep; `None` keeps full precision unless a rounding mode is set
+  precision: Option<u32>,
+  rounding: Option<Rounding>,
+}
+
+impl NumberFormat {
+  /// Read `output_precision` and `rounding` from the hints. A rounding mode without
+  /// a precision rounds to an integer; a precision without a mode rounds half up.
+  pub fn from_hints(hints: &OptimizationHints) -> Result<Self, String> {
+    Ok(Self {
+      precision: hints.output_precision,
+      rounding: hints.rounding.as_deref().map(Rounding::parse).transpose()?,
+    })
+  }
+
+  /// Round `value` as configured. Integer inputs that produce an integral result are
+  /// rendered without a fractional part, so `2 * 3` stays `6` rather than `6.0`.
+  pub fn to_json(&self, value: f64, integral_inputs: bool) -> serde_json::Value {
+    let value = self.round(value);
+    match Self::as_integer(value, integral_inputs) {
+      Some(integer) => serde_json::Value::from(integer),
+      None => serde_json::Value::from(value),
+    }
+  }
+
+  /// Like `to_json`, but as the plain string the scalar fast paths return
+  pub fn format(&self, value: f64, integral_inputs: bool) -> String {
+    let value = self.round(value);
+    match Self::as_integer(value, integral_inputs) {
+      Some(integer) => integer.to_string(),
+      None => value.to_string(),
+    }
+  }
+
+  fn as_integer(value: f64, integral_inputs: bool) -> Option<i64> {
+    let integral = value.is_finite() && value.fract() == 0.0 && value.abs() < (i64::MAX as f64);
+    (integral_inputs && integral).then_some(value as i64)
+  }
+
+  fn round(&self, value: f64) -> f64 {
+    if !value.is_finite() || (self.precision.is_none() && self.rounding.is_none()) {
+      return value;
+    }
+
+    let rounding = self.rounding.unwrap_or(Rounding::HalfUp);
+    let scale = 10f64.powi(self.precision.unwrap_or(0).min(15) as i32);
+    rounding.apply(value * scale) / scale
+  }
+}
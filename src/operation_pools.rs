@@ -0,0 +1,98 @@
+// src/operation_pools.rs - Workers set aside for one operation type each
+//
+// Long array jobs sharing the pool with latency-sensitive math tasks can take
+// every worker and leave the math tasks waiting behind them. With
+// `operation_pools`, the first workers are split into a pool per listed
+// `operation_type`, in name order, that only takes tasks of that type; every
+// worker after them forms the general pool, which takes everything else. A
+// task never leaves its pool, not even to be stolen by an idle worker of
+// another. Resizing the pool only adds or removes general workers, and only
+// general workers are reserved for batches (see `reservation`).
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Operation types a pool can be dedicated to
+pub const POOLED_OPERATIONS: [&str; 3] = ["mathematical", "string_operations", "array_operations"];
+
+/// The dedicated pools, in worker order, followed by the general pool
+#[derive(Debug, Clone, Default)]
+pub struct OperationPools {
+  // Operation type and worker count of each dedicated pool
+  pools: Vec<(String, usize)>,
+  // Workers in all dedicated pools, which come first
+  dedicated: usize,
+}
+
+impl OperationPools {
+  /// Pools of the given sizes by operation type
+  pub fn new(sizes: &HashMap<String, u32>) -> Result<Self, String> {
+    let mut pools = Vec::with_capacity(sizes.len());
+    for (operation_type, &size) in sizes {
+      if !POOLED_OPERATIONS.contains(&operation_type.as_str()) {
+        return Err(
+          format!(
+            "Unknown operation pool '{}', expected one of {}",
+            operation_type,
+            POOLED_OPERATIONS.join(", ")
+          )
+        );
+      }
+      if size == 0 {
+        return Err(format!("Operation pool '{}' needs at least 1 worker", operation_type));
+      }
+      pools.push((operation_type.clone(), size as usize));
+    }
+    pools.sort();
+    let dedicated = pools.iter().map(|(_, size)| size).sum();
+    Ok(Self { pools, dedicated })
+  }
+
+  /// Workers in dedicated pools
+  pub fn dedicated(&self) -> usize {
+    self.dedicated
+  }
+
+  /// Fewest workers the executor can run with: every dedicated one and a
+  /// general one
+  pub fn fewest_workers(&self) -> usize {
+    self.dedicated + 1
+  }
+
+  /// Pools, the general one included
+  pub fn count(&self) -> usize {
+    self.pools.len() + 1
+  }
+
+  /// Pool that takes tasks with this `operation_type`
+  pub fn pool_of(&self, operation_type: &str) -> usize {
+    self.pools
+      .iter()
+      .position(|(pooled, _)| pooled == operation_type)
+      .unwrap_or(self.pools.len())
+  }
+
+  /// Pool `worker` belongs to
+  pub fn pool_of_worker(&self, worker: usize) -> usize {
+    let mut end = 0;
+    for (pool, (_, size)) in self.pools.iter().enumerate() {
+      end += size;
+      if worker < end {
+        return pool;
+      }
+    }
+    self.pools.len()
+  }
+
+  /// Slots of `pool`'s workers, where the general pool's run to `slots`. Pass
+  /// every slot to include retired workers, or the running ones to exclude them.
+  pub fn workers(&self, pool: usize, slots: usize) -> Range<usize> {
+    let start: usize = self.pools[..pool.min(self.pools.len())]
+      .iter()
+      .map(|(_, size)| size)
+      .sum();
+    match self.pools.get(pool) {
+      Some((_, size)) => start..start + size,
+      None => start..slots.max(start),
+    }
+  }
+}
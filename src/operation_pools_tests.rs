@@ -0,0 +1,48 @@
+// src/operation_pools_tests.rs - Workers split into pools by operation type
+use std::collections::HashMap;
+
+use crate::operation_pools::OperationPools;
+
+fn pools(sizes: &[(&str, u32)]) -> Result<OperationPools, String> {
+  let sizes: HashMap<String, u32> = sizes
+    .iter()
+    .map(|(operation_type, size)| (operation_type.to_string(), *size))
+    .collect();
+  OperationPools::new(&sizes)
+}
+
+#[test]
+fn dedicated_pools_come_first_in_name_order() {
+  let pools = pools(&[("string_operations", 1), ("mathematical", 2)]).unwrap();
+  assert_eq!((pools.dedicated(), pools.fewest_workers(), pools.count()), (3, 4, 3));
+  assert_eq!(pools.pool_of("mathematical"), 0);
+  assert_eq!(pools.pool_of("string_operations"), 1);
+  // Anything else goes to the general pool
+  assert_eq!(pools.pool_of("array_operations"), 2);
+  assert_eq!(pools.pool_of("general"), 2);
+
+  let by_worker: Vec<usize> = (0..5).map(|worker| pools.pool_of_worker(worker)).collect();
+  assert_eq!(by_worker, [0, 0, 1, 2, 2]);
+  assert_eq!((pools.workers(0, 5), pools.workers(1, 5), pools.workers(2, 5)), (0..2, 2..3, 3..5));
+}
+
+#[test]
+fn without_pools_every_worker_is_general() {
+  let pools = OperationPools::default();
+  assert_eq!((pools.dedicated(), pools.fewest_workers(), pools.count()), (0, 1, 1));
+  assert_eq!(pools.pool_of("mathematical"), 0);
+  assert_eq!(pools.workers(0, 4), 0..4);
+}
+
+#[test]
+fn unknown_and_empty_pools_are_refused() {
+  assert_eq!(
+    pools(&[("regex", 1)]).unwrap_err(),
+    "Unknown operation pool 'regex', expected one of mathematical, string_operations, \
+     array_operations"
+  );
+  assert_eq!(
+    pools(&[("mathematical", 0)]).unwrap_err(),
+    "Operation pool 'mathematical' needs at least 1 worker"
+  );
+}
@@ -0,0 +1,936 @@
+// src/priority.rs - Task priorities and the shared queue's per-priority lanes
+//
+// The shared queue is one lane per priority. Workers always take from the
+// highest non-empty lane, so a single urgent task overtakes a large low-value
+// batch instead of waiting behind it. Priority is strict: lower lanes are only
+// served while every higher lane is empty. A running task isn't interrupted,
+// except that chunked tasks let critical ones run between their chunks. Which
+// lane a task waits in, and whether a lane is served oldest or newest first, is
+// up to the queue discipline (see `discipline`). With `aging_ms`, a non-empty
+// lane that goes unserved moves up one priority for every `aging_ms` it waits,
+// and overtakes the lanes above it once it's higher than them, so a steady
+// stream of urgent work slows low-priority tasks down without starving them.
+//
+// Each lane is a shared injector that submissions go to, plus a deque per
+// worker that it refills with batches from the injector and that idle workers
+// steal from, so taking a task doesn't contend on a lock however many workers
+// there are. With `sticky_routing`, tasks go to an inbox of the worker their
+// function hash picks instead, as long as that worker keeps up, so what it has
+// cached for the function stays warm; other workers steal from it when idle.
+// A batch split across workers by task weight (see `batch_split`) or cut into
+// sub-batches (see `sub_batch`) uses the same inboxes. A lane served newest
+// first keeps its tasks in a stack instead of the injector.
+//
+// A worker that finds nothing to do sleeps until it's woken: each worker has a
+// bell, rung when a task goes to its inbox, and otherwise for each task sent
+// to the shared queue the first idle worker not already woken is. A worker
+// marks itself idle before its last look at the queue, and senders look for
+// idle workers after queuing, so one of the two always sees the other. Shutting
+// down closes every bell, which wakes its worker for good.
+//
+// With `operation_pools`, each lane has an injector per pool, and a worker
+// only takes and steals tasks from its own pool's injector and workers.
+//
+// The queue has a slot for each worker the pool can grow to (see `resize`).
+// Sticky routing and batch splits only send tasks to the inboxes of the
+// workers running now; what's left in a retired worker's deques and inbox is
+// stolen by the others.
+use crossbeam::channel;
+use crossbeam::deque::{ Injector, Steal, Stealer, Worker };
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{ Hash, Hasher };
+use std::ops::Range;
+use std::sync::PoisonError;
+use std::time::{ Duration, Instant };
+
+use crate::discipline::{ LaneOrder, QueueDiscipline };
+use crate::events::EventLog;
+use crate::memory;
+use crate::operation_pools::OperationPools;
+use crate::sub_batch::SubBatchId;
+use crate::sync::atomic::{ AtomicBool, AtomicUsize, Ordering, fence };
+use crate::sync::{ Arc, Condvar, Mutex, lock_recovering };
+use crate::task_status::{ QueueId, TaskTracker };
+use crate::{ OptimizationHints, OptimizedWorkerTask };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Priority {
+  Low,
+  #[default]
+  Normal,
+  High,
+  Critical,
+}
+
+impl Priority {
+  /// Every priority, highest first: the order lanes are served in
+  pub const DESCENDING: [Priority; 4] = [
+    Priority::Critical,
+    Priority::High,
+    Priority::Normal,
+    Priority::Low,
+  ];
+
+  pub fn parse(value: &str) -> Result<Self, String> {
+    match value {
+      "low" => Ok(Priority::Low),
+      "normal" => Ok(Priority::Normal),
+      "high" => Ok(Priority::High),
+      "critical" => Ok(Priority::Critical),
+      other => Err(format!("Unknown priority '{}', expected low, normal, high or critical", other)),
+    }
+  }
+
+  /// The `priority` hint, `Normal` when unset
+  pub fn from_hints(hints: &OptimizationHints) -> Result<Self, String> {
+    Ok(hints.priority.as_deref().map(Self::parse).transpose()?.unwrap_or_default())
+  }
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Priority::Low => "low",
+      Priority::Normal => "normal",
+      Priority::High => "high",
+      Priority::Critical => "critical",
+    }
+  }
+
+  fn lane(self) -> usize {
+    match self {
+      Priority::Critical => 0,
+      Priority::High => 1,
+      Priority::Normal => 2,
+      Priority::Low => 3,
+    }
+  }
+}
+
+/// Why the shared queue rejected a task; the task is dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendError {
+  Full,
+  Disconnected,
+}
+
+/// Lets submitters blocked on a full queue sleep until a worker takes a task
+#[derive(Debug, Default)]
+pub struct Space {
+  lock: Mutex<()>,
+  freed: Condvar,
+  // Notifying costs a syscall, so workers only do it while someone waits
+  waiters: AtomicUsize,
+}
+
+impl Space {
+  fn notify(&self) {
+    if self.waiters.load(Ordering::Acquire) > 0 {
+      let _lock = lock_recovering(&self.lock, || {});
+      self.freed.notify_all();
+    }
+  }
+
+  /// Sleep until a worker takes a task, or at most `timeout`, which also bounds
+  /// a wakeup missed between the caller's check and this call
+  pub fn wait(&self, timeout: Duration) {
+    self.waiters.fetch_add(1, Ordering::AcqRel);
+    let lock = lock_recovering(&self.lock, || {});
+    drop(self.freed.wait_timeout(lock, timeout));
+    self.waiters.fetch_sub(1, Ordering::AcqRel);
+  }
+}
+
+#[derive(Debug, Default)]
+struct BellState {
+  rung: bool,
+  closed: bool,
+}
+
+/// What an idle worker sleeps on. Holds one ring, so a worker rung before it
+/// sleeps doesn't; once closed, wakes its worker for good.
+#[derive(Debug, Default)]
+struct Bell {
+  state: Mutex<BellState>,
+  changed: Condvar,
+}
+
+impl Bell {
+  /// False if it's already been rung
+  fn ring(&self) -> bool {
+    let mut state = lock_recovering(&self.state, || {});
+    if state.rung {
+      return false;
+    }
+    state.rung = true;
+    self.changed.notify_all();
+    true
+  }
+
+  fn close(&self) {
+    lock_recovering(&self.state, || {}).closed = true;
+    self.changed.notify_all();
+  }
+
+  fn is_closed(&self) -> bool {
+    lock_recovering(&self.state, || {}).closed
+  }
+
+  /// Until `timeout` (if any) passes or, when `wake_on_ring`, the bell is rung
+  /// (true), or until it's closed (false). A ring is taken only by a wait it
+  /// ends.
+  fn wait(&self, timeout: Option<Duration>, wake_on_ring: bool) -> bool {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut state = lock_recovering(&self.state, || {});
+    loop {
+      if state.closed {
+        return false;
+      }
+      if wake_on_ring && state.rung {
+        state.rung = false;
+        return true;
+      }
+      state = match deadline {
+        None => self.changed.wait(state).unwrap_or_else(PoisonError::into_inner),
+        Some(deadline) => {
+          let Some(left) = deadline.checked_duration_since(Instant::now()) else {
+            return true;
+          };
+          match self.changed.wait_timeout(state, left) {
+            Ok((state, _)) => state,
+            Err(poisoned) => poisoned.into_inner().0,
+          }
+        }
+      };
+    }
+  }
+}
+
+/// Why a queued task was taken back off the queue unrun
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShedReason {
+  /// Dropped to make room in the full queue (`drop_oldest`)
+  Overflow,
+  /// Handed off to another executor by `export_state`
+  HandedOff,
+}
+
+type Shed = (OptimizedWorkerTask, Priority, ShedReason);
+
+// Tasks waiting on a worker, in its inbox and deque, at which sticky routing
+// stops sending it more and leaves them to whichever worker is free
+const SATURATED_AT: usize = 8;
+
+/// Queued tasks of one priority: newly sent ones in a shared injector or a
+/// worker's inbox, and batches that workers have moved from those into deques
+/// of their own
+#[derive(Debug)]
+struct Lane {
+  // One per operation pool, the general pool's last
+  injectors: Vec<Injector<OptimizedWorkerTask>>,
+  // One per worker, for tasks sent to it by sticky routing, a batch split or
+  // sub-batching
+  inboxes: Vec<Injector<OptimizedWorkerTask>>,
+  // One per worker, in worker order
+  stealers: Vec<Stealer<OptimizedWorkerTask>>,
+  // One per operation pool, instead of its injector when the lane is served
+  // newest first
+  stacks: Option<Vec<Mutex<Vec<OptimizedWorkerTask>>>>,
+  // Since when the lane has had tasks without being served, while aging
+  waiting_since: Mutex<Option<Instant>>,
+}
+
+impl Lane {
+  fn len(&self) -> usize {
+    self.injectors.iter().map(Injector::len).sum::<usize>() +
+      self.inboxes.iter().map(Injector::len).sum::<usize>() +
+      self.stealers.iter().map(Stealer::len).sum::<usize>() +
+      self.stacks.iter().flatten().map(|stack| lock_recovering(stack, || {}).len()).sum::<usize>()
+  }
+
+  fn is_empty(&self) -> bool {
+    self.injectors.iter().all(Injector::is_empty) &&
+      self.inboxes.iter().all(Injector::is_empty) &&
+      self.stealers.iter().all(Stealer::is_empty) &&
+      self.stacks.iter().flatten().all(|stack| lock_recovering(stack, || {}).is_empty())
+  }
+
+  /// Whether `pool`, whose workers are `members`, has nothing queued here
+  fn pool_is_empty(&self, pool: usize, members: Range<usize>) -> bool {
+    self.injectors[pool].is_empty() &&
+      self.inboxes[members.clone()].iter().all(Injector::is_empty) &&
+      self.stealers[members].iter().all(Stealer::is_empty) &&
+      self.stacks.as_ref().is_none_or(|stacks| lock_recovering(&stacks[pool], || {}).is_empty())
+  }
+
+  /// The worker whose inbox a task goes to, if any: the one a batch split
+  /// assigned it to, or with `sticky` routing the one its function hash picks
+  /// unless that worker is saturated. Only `workers` are picked.
+  fn inbox(
+    &self,
+    task: &OptimizedWorkerTask,
+    sticky: bool,
+    workers: Range<usize>
+  ) -> Option<usize> {
+    let count = workers.len();
+    if count == 0 {
+      return None;
+    }
+    if let Some(worker) = task.worker {
+      return Some(workers.start + (worker % count));
+    }
+    if !sticky {
+      return None;
+    }
+    let mut hasher = DefaultHasher::new();
+    task.optimization_hints.function_hash.hash(&mut hasher);
+    let home = workers.start + ((hasher.finish() % (count as u64)) as usize);
+    let waiting = self.inboxes[home].len() + self.stealers[home].len();
+    (waiting < SATURATED_AT).then_some(home)
+  }
+
+  /// Queue a task for `pool`, whose running workers are `workers`, returning
+  /// the worker whose inbox it went to, if any
+  fn push(
+    &self,
+    task: OptimizedWorkerTask,
+    pool: usize,
+    sticky: bool,
+    workers: Range<usize>
+  ) -> Option<usize> {
+    let inbox = self.inbox(&task, sticky, workers);
+    match (inbox, &self.stacks) {
+      (Some(worker), _) => self.inboxes[worker].push(task),
+      (None, Some(stacks)) => lock_recovering(&stacks[pool], || {}).push(task),
+      (None, None) => self.injectors[pool].push(task),
+    }
+    inbox
+  }
+
+  /// The newest task of `pool` in a lane served newest first, else a batch
+  /// from its injector
+  fn take_shared(
+    &self,
+    pool: usize,
+    local: &Worker<OptimizedWorkerTask>
+  ) -> Steal<OptimizedWorkerTask> {
+    match &self.stacks {
+      Some(stacks) => {
+        lock_recovering(&stacks[pool], || {}).pop().map_or(Steal::Empty, Steal::Success)
+      }
+      None => self.injectors[pool].steal_batch_and_pop(local),
+    }
+  }
+
+  /// A task for `worker` of `pool`, owner of `local`: its own oldest, else a
+  /// batch from its inbox or the pool's shared queue, else one stolen from
+  /// another of the pool's `members`. Inboxes of `idle` workers are left to them.
+  fn find(
+    &self,
+    worker: usize,
+    pool: usize,
+    members: Range<usize>,
+    local: &Worker<OptimizedWorkerTask>,
+    idle: &[AtomicBool]
+  ) -> Option<OptimizedWorkerTask> {
+    local.pop().or_else(|| {
+      Self::retry(|| {
+        let inbox = self.inboxes.get(worker).map_or(Steal::Empty, |inbox| {
+          inbox.steal_batch_and_pop(local)
+        });
+        inbox
+          .or_else(|| self.take_shared(pool, local))
+          .or_else(|| self.stealers[members.clone()].iter().map(Stealer::steal).collect())
+          .or_else(|| {
+            self.inboxes[members.clone()]
+              .iter()
+              .zip(&idle[members.clone()])
+              .filter(|(_, idle)| !idle.load(Ordering::Acquire))
+              .map(|(inbox, _)| inbox.steal())
+              .collect()
+          })
+      })
+    })
+  }
+
+  /// The oldest task of `pools`, whose workers are `members`, taken without a
+  /// deque of one's own. Tasks in the workers' deques were sent before any
+  /// still in an inbox or an injector.
+  fn steal(&self, pools: Range<usize>, members: Range<usize>) -> Option<OptimizedWorkerTask> {
+    Self::retry(|| {
+      self.stealers[members.clone()]
+        .iter()
+        .map(Stealer::steal)
+        .collect::<Steal<_>>()
+        .or_else(|| self.inboxes[members.clone()].iter().map(Injector::steal).collect())
+        .or_else(|| self.injectors[pools.clone()].iter().map(Injector::steal).collect())
+    }).or_else(|| {
+      self.stacks.as_ref()?[pools].iter().find_map(|stack| {
+        let mut stack = lock_recovering(stack, || {});
+        (!stack.is_empty()).then(|| stack.remove(0))
+      })
+    })
+  }
+
+  fn retry(
+    attempt: impl FnMut() -> Steal<OptimizedWorkerTask>
+  ) -> Option<OptimizedWorkerTask> {
+    std::iter::repeat_with(attempt).find(|steal| !steal.is_retry()).and_then(Steal::success)
+  }
+}
+
+/// State shared by every handle on the queue
+#[derive(Debug)]
+struct Lanes {
+  lanes: [Lane; 4],
+  space: Arc<Space>,
+  // Estimated memory held by queued tasks
+  bytes: AtomicUsize,
+  // Workers still taking tasks; once none are left, sends fail
+  workers: AtomicUsize,
+  // Workers the pool runs, the first of the slots; the rest are retired
+  active: AtomicUsize,
+  // Set once every sender is gone, so workers know no more tasks will come
+  closed: AtomicBool,
+  // One per worker, set while it finds nothing to do and will empty its inbox
+  // itself
+  idle: Vec<AtomicBool>,
+  // One per worker, rung to wake it while idle
+  bells: Vec<Bell>,
+  // Whether tasks go to workers by function hash (`sticky_routing`)
+  sticky: bool,
+  // Which workers take which tasks (`operation_pools`)
+  pools: OperationPools,
+  // Unserved time that raises a lane one priority (`aging_ms`)
+  aging: Option<Duration>,
+}
+
+impl Lanes {
+  fn lane(&self, priority: Priority) -> &Lane {
+    &self.lanes[priority.lane()]
+  }
+
+  /// Where each lane stands at `now`, lowest served first: its place among the
+  /// lanes less one for every `aging` it has waited unserved, empty ones last
+  fn ranks(&self, now: Instant) -> [(i128, usize); 4] {
+    std::array::from_fn(|index| {
+      let lane = &self.lanes[index];
+      let Some(aging) = self.aging.filter(|aging| !aging.is_zero()) else {
+        return (if lane.is_empty() { i128::MAX } else { index as i128 }, index);
+      };
+      let mut since = lock_recovering(&lane.waiting_since, || {});
+      if lane.is_empty() {
+        *since = None;
+        return (i128::MAX, index);
+      }
+      let waited = now.saturating_duration_since(*since.get_or_insert(now));
+      ((index as i128) - ((waited.as_nanos() / aging.as_nanos()) as i128), index)
+    })
+  }
+
+  /// Lanes in the order they're served at `now`: highest first, or with aging
+  /// by rank (see `ranks`)
+  fn order(&self, now: Instant) -> [Priority; 4] {
+    let mut order = Priority::DESCENDING;
+    if self.aging.is_some() {
+      let ranks = self.ranks(now);
+      order.sort_by_key(|priority| ranks[priority.lane()]);
+    }
+    order
+  }
+
+  /// A task was taken from `priority`'s lane at `now`; whatever's left there
+  /// starts aging afresh
+  fn served(&self, priority: Priority, now: Instant) {
+    if self.aging.is_some() {
+      *lock_recovering(&self.lane(priority).waiting_since, || {}) = Some(now);
+    }
+  }
+
+  fn is_empty(&self) -> bool {
+    self.lanes.iter().all(Lane::is_empty)
+  }
+
+  /// Slots of `pool`'s workers, retired ones included
+  fn members(&self, pool: usize) -> Range<usize> {
+    self.pools.workers(pool, self.idle.len())
+  }
+
+  /// Every pool and every slot, for taking tasks whatever pool they're in
+  fn everything(&self) -> (Range<usize>, Range<usize>) {
+    (0..self.pools.count(), 0..self.idle.len())
+  }
+
+  /// Account for a task a worker took off the queue
+  fn taken(&self, task: &OptimizedWorkerTask) {
+    self.bytes.fetch_sub(memory::task_bytes(task), Ordering::AcqRel);
+    self.space.notify();
+  }
+
+  /// Queue a task at `priority` in its operation type's pool and wake a worker
+  /// of that pool for it
+  fn push(&self, task: OptimizedWorkerTask, priority: Priority, sticky: bool) {
+    let pool = self.pools.pool_of(&task.optimization_hints.operation_type);
+    let workers = self.pools.workers(pool, self.active.load(Ordering::Acquire));
+    let inbox = self.lane(priority).push(task, pool, sticky, workers);
+    self.wake(self.members(pool), inbox);
+  }
+
+  /// Wake a worker for a task just queued: the one whose inbox it went to if
+  /// that one's idle, otherwise the first idle worker of `members` not already
+  /// woken, which takes the task from a busy worker's inbox if that's where it is
+  fn wake(&self, members: Range<usize>, inbox: Option<usize>) {
+    // Pairs with the fence in `WorkerLanes::try_recv`
+    fence(Ordering::SeqCst);
+    match inbox {
+      Some(worker) if self.idle[worker].load(Ordering::Acquire) => {
+        self.ring(worker);
+      }
+      _ => {
+        let _ = members.into_iter().find(|&worker| {
+          self.idle[worker].load(Ordering::Acquire) && self.ring(worker)
+        });
+      }
+    }
+  }
+
+  /// Wake every idle worker, e.g. to look again at when a delayed task is due
+  fn wake_idle(&self) {
+    fence(Ordering::SeqCst);
+    for worker in 0..self.idle.len() {
+      if self.idle[worker].load(Ordering::Acquire) {
+        self.ring(worker);
+      }
+    }
+  }
+
+  /// Ring `worker`'s bell; false if it's already been rung
+  fn ring(&self, worker: usize) -> bool {
+    self.bells.get(worker).is_some_and(Bell::ring)
+  }
+}
+
+/// Closes the queue when the last `LaneSender` clone is dropped
+#[derive(Debug)]
+struct SenderHandle(Arc<Lanes>);
+
+impl Drop for SenderHandle {
+  fn drop(&mut self) {
+    self.0.closed.store(true, Ordering::Release);
+  }
+}
+
+/// Sending half of the shared queue
+#[derive(Debug, Clone)]
+pub struct LaneSender {
+  lanes: Arc<Lanes>,
+  _handle: Arc<SenderHandle>,
+  // Dropped tasks, for a worker to fail
+  shed: channel::Sender<Shed>,
+  // Bound on the tasks queued across all lanes
+  capacity: Option<usize>,
+}
+
+impl LaneSender {
+  /// Tasks queued across all lanes
+  pub fn len(&self) -> usize {
+    self.lanes.lanes.iter().map(Lane::len).sum()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.lanes.is_empty()
+  }
+
+  /// Tasks queued in `priority`'s lane
+  pub fn depth(&self, priority: Priority) -> usize {
+    self.lanes.lane(priority).len()
+  }
+
+  /// Queue without blocking. The capacity check isn't atomic with the send, so
+  /// callers serialize sends (the executor holds its sender lock).
+  pub fn try_send(&self, task: OptimizedWorkerTask, priority: Priority) -> Result<(), SendError> {
+    if self.lanes.workers.load(Ordering::Acquire) == 0 {
+      return Err(SendError::Disconnected);
+    }
+    if let Some(capacity) = self.capacity && self.len() >= capacity {
+      return Err(SendError::Full);
+    }
+    self.send_admitted(task, priority);
+    Ok(())
+  }
+
+  /// Queue a task already counted against the capacity, such as one held back
+  /// at submission
+  pub fn send_admitted(&self, task: OptimizedWorkerTask, priority: Priority) {
+    self.lanes.bytes.fetch_add(memory::task_bytes(&task), Ordering::AcqRel);
+    self.lanes.push(task, priority, self.lanes.sticky);
+  }
+
+  /// Take the oldest task of the lowest non-empty lane back off the queue, so
+  /// routine work is shed before urgent work
+  pub fn evict_oldest(&self) -> Option<(OptimizedWorkerTask, Priority)> {
+    let (pools, members) = self.lanes.everything();
+    let evicted = Priority::DESCENDING.iter().rev().find_map(|&priority| {
+      let task = self.lanes.lane(priority).steal(pools.clone(), members.clone())?;
+      Some((task, priority))
+    });
+    if let Some((task, _)) = evicted.as_ref() {
+      self.lanes.bytes.fetch_sub(memory::task_bytes(task), Ordering::AcqRel);
+    }
+    evicted
+  }
+
+  /// Take every queued task back off the queue, highest lane first and oldest
+  /// first within a lane
+  pub fn take_all(&self) -> Vec<(OptimizedWorkerTask, Priority)> {
+    let mut taken = Vec::new();
+    let (pools, members) = self.lanes.everything();
+    for priority in Priority::DESCENDING {
+      while let Some(task) = self.lanes.lane(priority).steal(pools.clone(), members.clone()) {
+        self.lanes.bytes.fetch_sub(memory::task_bytes(&task), Ordering::AcqRel);
+        taken.push((task, priority));
+      }
+    }
+    taken
+  }
+
+  /// Estimated memory held by the tasks queued across all lanes
+  pub fn queued_bytes(&self) -> usize {
+    self.lanes.bytes.load(Ordering::Acquire)
+  }
+
+  /// Hand a task taken off the queue to the workers to fail
+  pub fn shed(&self, task: OptimizedWorkerTask, priority: Priority, reason: ShedReason) {
+    let _ = self.shed.send((task, priority, reason));
+    self.lanes.wake(0..self.lanes.idle.len(), None);
+  }
+
+  /// Signalled each time a worker takes a task
+  pub fn space(&self) -> Arc<Space> {
+    Arc::clone(&self.lanes.space)
+  }
+
+  /// Wakes idle workers for work that doesn't come through the queue
+  pub fn doorbell(&self) -> Doorbell {
+    Doorbell { lanes: Arc::clone(&self.lanes) }
+  }
+
+  /// Route tasks to the first `workers` workers only, after the pool is resized
+  pub fn set_workers(&self, workers: usize) {
+    self.lanes.active.store(workers, Ordering::Release);
+  }
+}
+
+/// Wakes idle workers for what they do besides taking queued tasks: serving a
+/// reservation, releasing delayed and rate-limited tasks when they come due.
+/// Doesn't keep the queue open.
+#[derive(Debug, Clone)]
+pub struct Doorbell {
+  lanes: Arc<Lanes>,
+}
+
+impl Doorbell {
+  /// Wake `worker`, idle or not, or have its next wait end at once
+  pub fn ring(&self, worker: usize) {
+    self.lanes.ring(worker);
+  }
+
+  /// Wake every idle worker
+  pub fn wake_idle(&self) {
+    self.lanes.wake_idle();
+  }
+
+  /// Wake every worker for good, on shutdown: their waits return false from
+  /// now on
+  pub fn close(&self) {
+    self.lanes.bells.iter().for_each(Bell::close);
+  }
+}
+
+/// Shared view of the queue's receiving side, for checking what's pending
+/// and taking tasks out of turn
+#[derive(Debug, Clone)]
+pub struct LaneReceiver {
+  lanes: Arc<Lanes>,
+  shed: channel::Receiver<Shed>,
+}
+
+impl LaneReceiver {
+  pub fn is_empty(&self) -> bool {
+    self.lanes.is_empty()
+  }
+
+  /// Whether `priority`'s lane is empty, without taking anything
+  pub fn lane_is_empty(&self, priority: Priority) -> bool {
+    self.lanes.lane(priority).is_empty()
+  }
+
+  /// Oldest task of `priority`'s lane alone in `worker`'s pool, for a worker
+  /// pausing a lower priority task to serve urgent ones
+  pub fn try_recv_from(&self, priority: Priority, worker: usize) -> Option<OptimizedWorkerTask> {
+    let pool = self.lanes.pools.pool_of_worker(worker);
+    let task = self.lanes.lane(priority).steal(pool..pool + 1, self.lanes.members(pool))?;
+    self.lanes.taken(&task);
+    Some(task)
+  }
+
+  /// A task taken off the queue unrun, with the priority it was queued at and why
+  pub fn try_recv_shed(&self) -> Option<Shed> {
+    self.shed.try_recv().ok()
+  }
+}
+
+/// One worker's end of the shared queue: a deque per priority that it refills
+/// in batches from the injectors, and that idle workers steal from. Taking a
+/// task locks nothing.
+#[derive(Debug)]
+pub struct WorkerLanes {
+  lanes: Arc<Lanes>,
+  worker: usize,
+  // Operation pool this worker takes tasks from, and that pool's workers
+  pool: usize,
+  members: Range<usize>,
+  local: [Worker<OptimizedWorkerTask>; 4],
+  // Taken while looking for the rest of a sub-batch, and not part of it; the
+  // next task `try_recv` returns
+  held: Option<(OptimizedWorkerTask, Priority)>,
+  // Pending dropped tasks, which any worker fails
+  shed: channel::Receiver<Shed>,
+}
+
+impl WorkerLanes {
+  /// The worker this end belongs to
+  pub fn worker(&self) -> usize {
+    self.worker
+  }
+
+  /// Leave this end for a later worker in the same slot: the task held for
+  /// later goes back to the shared queue, and idle workers are woken to take
+  /// what's left in its deques and inbox
+  pub fn hand_back(&mut self) {
+    if let Some((task, priority)) = self.held.take() {
+      self.lanes.push(task, priority, false);
+    }
+    self.lanes.idle[self.worker].store(false, Ordering::Release);
+    self.lanes.wake_idle();
+  }
+
+  /// Next task from the highest non-empty lane at `now`, counting aging, with
+  /// its priority. Disconnected only once every sender is gone and every lane is
+  /// empty.
+  pub fn try_recv(
+    &mut self,
+    now: Instant
+  ) -> Result<(OptimizedWorkerTask, Priority), channel::TryRecvError> {
+    if let Some((task, priority)) = self.held.take() {
+      self.lanes.taken(&task);
+      return Ok((task, priority));
+    }
+    for priority in self.lanes.order(now) {
+      let local = &self.local[priority.lane()];
+      let lane = self.lanes.lane(priority);
+      let members = self.members.clone();
+      if let Some(task) = lane.find(self.worker, self.pool, members, local, &self.lanes.idle) {
+        self.lanes.idle[self.worker].store(false, Ordering::Release);
+        self.lanes.served(priority, now);
+        self.lanes.taken(&task);
+        return Ok((task, priority));
+      }
+    }
+    self.lanes.idle[self.worker].store(true, Ordering::Release);
+    // Pairs with the fence in `Lanes::wake`: a sender queuing from here on
+    // sees this worker idle and rings it
+    fence(Ordering::SeqCst);
+    if self.lanes.closed.load(Ordering::Acquire) && self.lanes.is_empty() {
+      Err(channel::TryRecvError::Disconnected)
+    } else {
+      Err(channel::TryRecvError::Empty)
+    }
+  }
+
+  /// Sleep after `try_recv` came up empty, until this worker is rung, `timeout`
+  /// (if any) passes or the executor shuts down (false). A worker taking
+  /// `shared` tasks returns at once if any were queued for its pool since; one
+  /// serving a reservation only waits for its bell.
+  pub fn wait(&self, shared: bool, timeout: Option<Duration>) -> bool {
+    let pool_is_empty = || {
+      self.lanes.lanes.iter().all(|lane| lane.pool_is_empty(self.pool, self.members.clone()))
+    };
+    if shared && !(pool_is_empty() && self.shed.is_empty()) {
+      return true;
+    }
+    self.lanes.bells[self.worker].wait(timeout, true)
+  }
+
+  /// Whether the executor has shut down
+  pub fn is_shut_down(&self) -> bool {
+    self.lanes.bells[self.worker].is_closed()
+  }
+
+  /// Sleep for `duration` whatever is queued meanwhile, unless the executor
+  /// shuts down first (false)
+  pub fn sleep(&self, duration: Duration) -> bool {
+    self.lanes.bells[self.worker].wait(Some(duration), false)
+  }
+
+  /// Stop taking shared tasks for now, while reserved or parked: other
+  /// workers take those in this worker's inbox, and are woken instead of it
+  pub fn stand_aside(&self) {
+    self.lanes.idle[self.worker].store(false, Ordering::Release);
+  }
+
+  /// The next task of `sub_batch`, sent to this worker at `priority`, unless a
+  /// lane ranked above it at `now` has tasks or the sub-batch has no more here
+  pub fn next_in_pass(
+    &mut self,
+    priority: Priority,
+    sub_batch: SubBatchId,
+    now: Instant
+  ) -> Option<OptimizedWorkerTask> {
+    if self.held.is_some() {
+      return None;
+    }
+    let ranks = self.lanes.ranks(now);
+    if ranks.iter().any(|&rank| rank < ranks[priority.lane()] && rank.0 != i128::MAX) {
+      return None;
+    }
+    let lane = self.lanes.lane(priority);
+    let local = &self.local[priority.lane()];
+    let task = local.pop().or_else(|| {
+      let inbox = lane.inboxes.get(self.worker)?;
+      Lane::retry(|| inbox.steal_batch_and_pop(local))
+    })?;
+    if task.sub_batch != Some(sub_batch) {
+      self.held = Some((task, priority));
+      return None;
+    }
+    self.lanes.served(priority, now);
+    self.lanes.taken(&task);
+    Some(task)
+  }
+}
+
+impl Drop for WorkerLanes {
+  // Tasks left in this worker's deques and inbox are still stolen by the others
+  fn drop(&mut self) {
+    if let Some((task, priority)) = self.held.take() {
+      self.lanes.push(task, priority, false);
+    }
+    self.lanes.idle[self.worker].store(false, Ordering::Release);
+    self.lanes.workers.fetch_sub(1, Ordering::AcqRel);
+  }
+}
+
+/// A shared queue holding at most `capacity` tasks across all lanes, with one
+/// `WorkerLanes` for each of `slots` workers, of which the first `workers` run
+/// for now. `sticky` routes tasks to workers by function hash; `pools` sets
+/// workers aside by operation type; `discipline` says which lanes are served
+/// newest first; `aging` raises lanes left unserved.
+pub fn lanes(
+  capacity: Option<usize>,
+  slots: usize,
+  workers: usize,
+  sticky: bool,
+  pools: OperationPools,
+  discipline: &dyn QueueDiscipline,
+  aging: Option<Duration>
+) -> (LaneSender, LaneReceiver, Vec<WorkerLanes>) {
+  let locals: Vec<[Worker<OptimizedWorkerTask>; 4]> = (0..slots)
+    .map(|_| std::array::from_fn(|_| Worker::new_fifo()))
+    .collect();
+  let lanes = Arc::new(Lanes {
+    lanes: std::array::from_fn(|lane| Lane {
+      injectors: (0..pools.count()).map(|_| Injector::new()).collect(),
+      inboxes: (0..slots).map(|_| Injector::new()).collect(),
+      stealers: locals.iter().map(|local| local[lane].stealer()).collect(),
+      stacks: (discipline.order(Priority::DESCENDING[lane]) == LaneOrder::Lifo).then(|| {
+        (0..pools.count()).map(|_| Mutex::new(Vec::new())).collect()
+      }),
+      waiting_since: Mutex::new(None),
+    }),
+    space: Arc::new(Space::default()),
+    bytes: AtomicUsize::new(0),
+    workers: AtomicUsize::new(slots),
+    active: AtomicUsize::new(workers),
+    closed: AtomicBool::new(false),
+    idle: (0..slots).map(|_| AtomicBool::new(false)).collect(),
+    bells: (0..slots).map(|_| Bell::default()).collect(),
+    sticky,
+    pools,
+    aging,
+  });
+  let (shed_tx, shed_rx) = channel::unbounded();
+  let worker_lanes = locals
+    .into_iter()
+    .enumerate()
+    .map(|(worker, local)| WorkerLanes {
+      pool: lanes.pools.pool_of_worker(worker),
+      members: lanes.members(lanes.pools.pool_of_worker(worker)),
+      lanes: Arc::clone(&lanes),
+      worker,
+      local,
+      held: None,
+      shed: shed_rx.clone(),
+    })
+    .collect();
+  (
+    LaneSender {
+      lanes: Arc::clone(&lanes),
+      _handle: Arc::new(SenderHandle(Arc::clone(&lanes))),
+      shed: shed_tx,
+      capacity,
+    },
+    LaneReceiver { lanes, shed: shed_rx },
+    worker_lanes,
+  )
+}
+
+/// Returns held-back tasks to the shared queue: ones that waited on a task
+/// group or for a concurrency slot
+#[derive(Clone)]
+pub struct Requeue {
+  sender: Arc<Mutex<Option<LaneSender>>>,
+  tasks: Arc<TaskTracker>,
+  doorbell: Doorbell,
+  events: Arc<EventLog>,
+}
+
+impl Requeue {
+  pub fn new(
+    sender: Arc<Mutex<Option<LaneSender>>>,
+    tasks: Arc<TaskTracker>,
+    doorbell: Doorbell,
+    events: Arc<EventLog>
+  ) -> Self {
+    Self { sender, tasks, doorbell, events }
+  }
+
+  /// Wake idle workers to look again at when held-back tasks come due
+  pub fn wake_idle(&self) {
+    self.doorbell.wake_idle();
+  }
+
+  /// Must be called without holding the caller's own locks, since submitters
+  /// take the sender lock first. Tasks released once the executor has shut
+  /// down fail unrun, each reported with a `task_dropped` event.
+  pub fn send(&self, released: Vec<(OptimizedWorkerTask, Priority)>) {
+    if released.is_empty() {
+      return;
+    }
+    let sender = lock_recovering(&self.sender, || {});
+    let Some(sender) = sender.as_ref() else {
+      for (task, _) in released {
+        self.tasks.finish(&task.id, false);
+        self.events.emit(
+          "task_dropped",
+          None,
+          format!("Dropped task {}, released after the executor shut down", task.id)
+        );
+      }
+      return;
+    };
+    for (task, priority) in released {
+      self.tasks.release(&task.id, QueueId::Shared(priority));
+      // Admitted at submission, so the queue bound doesn't apply again
+      sender.send_admitted(task, priority);
+    }
+  }
+}
@@ -0,0 +1,170 @@
+// src/protocol.rs - Protocol version and capabilities, negotiated with clients
+//
+// A client (the JS package, the Python module, a remote agent) and the native
+// core needn't come from the same release. Before relying on more than the
+// plain JSON calls, a client passes `negotiate_protocol` what it speaks: a
+// range of protocol versions, the encodings it reads and the operations it
+// wants. The core agrees on the highest version both speak and the encodings
+// both read, and lists the operations it has and those it lacks, so the client
+// falls back for those (e.g. to JS workers) instead of failing on first use.
+// Negotiation fails only when no version or no encoding is shared.
+//
+// Versions:
+//   1  JSON text in and out; bytes as `binary` Buffers, read as `borrowed`
+//
+// New operations and encodings are added without a new version; the version
+// changes when an existing call or result changes shape. A client that leaves
+// out `binary` gets byte results as JSON arrays of numbers in `result`.
+use std::sync::atomic::{ AtomicBool, Ordering };
+
+use crate::OptimizedWorkerResult;
+use crate::bindings::napi;
+
+/// Newest protocol version this core speaks
+pub const PROTOCOL_VERSION: u32 = 1;
+/// Oldest protocol version this core still speaks
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Result and input encodings: JSON text, raw byte results, borrowed byte
+/// input and YAML task specs
+pub const ENCODINGS: [&str; 4] = ["json", "binary", "borrowed", "yaml"];
+
+/// Operations the core offers to every client
+const OPERATIONS: [&str; 20] = [
+  "submit",
+  "submit_batch",
+  "submit_delayed",
+  "submit_fire",
+  "submit_borrowed",
+  "submit_graph",
+  "submit_task_spec",
+  "submit_registered",
+  "submit_delta",
+  "cancel",
+  "task_status",
+  "task_groups",
+  "child_executors",
+  "reserved_workers",
+  "concurrency_limits",
+  "rate_limits",
+  "drain",
+  "health_check",
+  "stats",
+  "events",
+];
+
+/// Operations only a Node client can use
+#[cfg(feature = "node")]
+const NODE_OPERATIONS: [&str; 2] = ["run_task", "stream_batch_results"];
+#[cfg(not(feature = "node"))]
+const NODE_OPERATIONS: [&str; 0] = [];
+
+/// What one side of the protocol speaks. A client leaving out `min_version`
+/// speaks only `version`; leaving out `encodings` or `operations` asks for
+/// everything the core has.
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolSupport {
+  pub version: u32,
+  pub min_version: Option<u32>,
+  pub encodings: Option<Vec<String>>,
+  pub operations: Option<Vec<String>>,
+}
+
+/// Outcome of `negotiate_protocol`
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolAgreement {
+  pub version: u32,
+  /// Encodings both sides read, in the client's order of preference
+  pub encodings: Vec<String>,
+  /// Operations the client asked for that the core has
+  pub operations: Vec<String>,
+  /// Operations the client asked for that the core lacks
+  pub unsupported_operations: Vec<String>,
+  pub core_version: String,
+}
+
+fn operations() -> impl Iterator<Item = &'static str> {
+  OPERATIONS.into_iter().chain(NODE_OPERATIONS)
+}
+
+/// What this core speaks
+pub fn support() -> ProtocolSupport {
+  ProtocolSupport {
+    version: PROTOCOL_VERSION,
+    min_version: Some(MIN_PROTOCOL_VERSION),
+    encodings: Some(ENCODINGS.iter().map(|encoding| encoding.to_string()).collect()),
+    operations: Some(operations().map(str::to_string).collect()),
+  }
+}
+
+/// Agree with a client on what `offer` and this core both speak
+pub fn negotiate(offer: &ProtocolSupport) -> Result<ProtocolAgreement, String> {
+  let client_min = offer.min_version.unwrap_or(offer.version).min(offer.version);
+  let version = offer.version.min(PROTOCOL_VERSION);
+  if version < client_min.max(MIN_PROTOCOL_VERSION) {
+    return Err(
+      format!(
+        "No protocol version in common: the client speaks {}..={}, the core {}..={}",
+        client_min,
+        offer.version,
+        MIN_PROTOCOL_VERSION,
+        PROTOCOL_VERSION
+      )
+    );
+  }
+
+  let encodings: Vec<String> = match offer.encodings.as_ref() {
+    Some(offered) =>
+      offered
+        .iter()
+        .filter(|encoding| ENCODINGS.contains(&encoding.as_str()))
+        .cloned()
+        .collect(),
+    None => ENCODINGS.iter().map(|encoding| encoding.to_string()).collect(),
+  };
+  if encodings.is_empty() {
+    return Err(format!("No encoding in common: the core reads {}", ENCODINGS.join(", ")));
+  }
+
+  let (operations, unsupported_operations) = match offer.operations.as_ref() {
+    Some(offered) =>
+      offered
+        .iter()
+        .cloned()
+        .partition(|operation| operations().any(|known| known == operation)),
+    None => (operations().map(str::to_string).collect(), Vec::new()),
+  };
+  Ok(ProtocolAgreement {
+    version,
+    encodings,
+    operations,
+    unsupported_operations,
+    core_version: env!("CARGO_PKG_VERSION").to_string(),
+  })
+}
+
+/// What an executor agreed with its client; until it negotiates, everything
+#[derive(Debug, Default)]
+pub struct Negotiated {
+  // Byte results go out as JSON arrays instead of `binary`
+  bytes_as_json: AtomicBool,
+}
+
+impl Negotiated {
+  pub fn apply(&self, agreement: &ProtocolAgreement) {
+    let binary = agreement.encodings.iter().any(|encoding| encoding == "binary");
+    self.bytes_as_json.store(!binary, Ordering::Release);
+  }
+
+  /// Shape `result` for the client
+  pub fn adapt(&self, result: &mut OptimizedWorkerResult) {
+    if !self.bytes_as_json.load(Ordering::Acquire) {
+      return;
+    }
+    if let Some(bytes) = result.binary.take() {
+      result.result = Some(serde_json::json!(bytes.0).to_string());
+    }
+  }
+}
@@ -0,0 +1,59 @@
+// src/protocol_tests.rs - Agreeing on a protocol version, encodings and operations
+use crate::protocol::{
+  MIN_PROTOCOL_VERSION,
+  PROTOCOL_VERSION,
+  ProtocolSupport,
+  negotiate,
+  support,
+};
+
+fn offer(version: u32, min_version: Option<u32>) -> ProtocolSupport {
+  ProtocolSupport { version, min_version, encodings: None, operations: None }
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+  values.iter().map(|value| value.to_string()).collect()
+}
+
+#[test]
+fn highest_shared_version_is_agreed() {
+  // A newer client that still speaks this version
+  let agreement = negotiate(&offer(PROTOCOL_VERSION + 3, Some(1))).expect("agreement");
+  assert_eq!(agreement.version, PROTOCOL_VERSION);
+  assert_eq!(agreement.core_version, env!("CARGO_PKG_VERSION"));
+  // Nothing asked for, everything offered
+  assert_eq!(Some(agreement.encodings), support().encodings);
+  assert_eq!(Some(agreement.operations), support().operations);
+  assert!(agreement.unsupported_operations.is_empty());
+
+  let too_new = negotiate(&offer(PROTOCOL_VERSION + 2, None)).unwrap_err();
+  assert_eq!(
+    too_new,
+    format!(
+      "No protocol version in common: the client speaks {v}..={v}, the core {}..={}",
+      MIN_PROTOCOL_VERSION,
+      PROTOCOL_VERSION,
+      v = PROTOCOL_VERSION + 2
+    )
+  );
+  assert!(negotiate(&offer(MIN_PROTOCOL_VERSION - 1, None)).is_err());
+}
+
+#[test]
+fn encodings_and_operations_are_narrowed_to_what_both_have() {
+  let agreement = negotiate(&ProtocolSupport {
+    encodings: Some(strings(&["msgpack", "yaml", "json"])),
+    operations: Some(strings(&["submit", "teleport", "drain"])),
+    ..offer(PROTOCOL_VERSION, None)
+  }).expect("agreement");
+  // In the client's order of preference
+  assert_eq!(agreement.encodings, ["yaml", "json"]);
+  assert_eq!(agreement.operations, ["submit", "drain"]);
+  assert_eq!(agreement.unsupported_operations, ["teleport"]);
+
+  let unreadable = negotiate(&ProtocolSupport {
+    encodings: Some(strings(&["msgpack"])),
+    ..offer(PROTOCOL_VERSION, None)
+  });
+  assert!(unreadable.unwrap_err().starts_with("No encoding in common: the core reads json"));
+}
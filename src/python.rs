@@ -0,0 +1,254 @@
+// src/python.rs - Python bindings over the same engine
+//
+// The `python` feature builds the crate as a Python extension module named
+// `threader` (with maturin, see pyproject.toml), so Python services schedule
+// work on the same engine, config and task specs as the Node package. Like
+// `threader-cli` it builds without `node`:
+//
+//   maturin build --release
+//
+//   import threader
+//   with threader.Executor(workers=4) as executor:
+//       task_id = executor.submit("x => x * 2", "21", {"complexity": "low"})
+//       executor.result(task_id, timeout_ms=1000)["result"]  # "42"
+//
+// Hints are dicts of partial `OptimizationHints` fields, filled in like a task
+// spec's (see `task_spec`). Results and stats come back as dicts, task data
+// goes in as JSON text. Waiting on a result releases the GIL. Errors raise
+// `ThreaderError`, or its subclass `QueueFullError` when the queue is full.
+// napi's symbols only exist inside a Node process, so the module can't load
+#[cfg(feature = "node")]
+compile_error!(
+  "the python module runs without Node: build it with --no-default-features --features python"
+);
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use serde_json::{ Map, Value };
+
+use crate::{ OptimizationHints, OptimizedMultiCoreExecutor };
+use crate::bindings::{ Error, Status };
+use crate::executor_config::ExecutorConfig;
+use crate::task_spec;
+
+create_exception!(threader, ThreaderError, PyException, "An executor call failed");
+create_exception!(threader, QueueFullError, ThreaderError, "The shared queue had no room");
+
+fn raise(error: Error) -> PyErr {
+  match error.status {
+    Status::QueueFull => QueueFullError::new_err(error.reason),
+    Status::GenericFailure => ThreaderError::new_err(error.reason),
+  }
+}
+
+/// A Python value as JSON, through Python's own `json` module
+fn to_json(value: &Bound<'_, PyAny>) -> PyResult<Value> {
+  let text: String = PyModule::import(value.py(), "json")?
+    .call_method1("dumps", (value,))?
+    .extract()?;
+  serde_json::from_str(&text).map_err(|e| ThreaderError::new_err(e.to_string()))
+}
+
+/// JSON as a Python value
+fn from_json<'py>(py: Python<'py>, value: &impl serde::Serialize) -> PyResult<Bound<'py, PyAny>> {
+  let text = serde_json::to_string(value).map_err(|e| ThreaderError::new_err(e.to_string()))?;
+  PyModule::import(py, "json")?.call_method1("loads", (text,))
+}
+
+/// Hints for `function_code` from a dict of partial ones
+fn resolve_hints(
+  function_code: &str,
+  hints: Option<&Bound<'_, PyAny>>
+) -> PyResult<OptimizationHints> {
+  let layer: Option<Map<String, Value>> = match hints.map(to_json).transpose()? {
+    None | Some(Value::Null) => None,
+    Some(Value::Object(hints)) => Some(hints),
+    Some(_) => {
+      return Err(ThreaderError::new_err("Hints must be a dict"));
+    }
+  };
+  task_spec::resolve_hints(function_code, &layer.iter().collect::<Vec<_>>()).map_err(|e| {
+    ThreaderError::new_err(format!("Submission has {}", e))
+  })
+}
+
+/// Multi-core executor; see `OptimizedMultiCoreExecutor`
+#[pyclass(name = "Executor", module = "threader", frozen)]
+pub struct PyExecutor {
+  inner: OptimizedMultiCoreExecutor,
+}
+
+#[pymethods]
+impl PyExecutor {
+  /// Takes the `ExecutorConfig` fields most services set; the rest keep
+  /// their defaults
+  #[new]
+  #[pyo3(
+    signature = (
+      workers = None,
+      max_workers = None,
+      queue_capacity = None,
+      overflow_policy = None,
+      queue_discipline = None,
+      sticky_routing = None,
+      profile = None,
+      default_timeout_ms = None,
+    )
+  )]
+  #[allow(clippy::too_many_arguments)]
+  fn new(
+    workers: Option<u32>,
+    max_workers: Option<u32>,
+    queue_capacity: Option<u32>,
+    overflow_policy: Option<String>,
+    queue_discipline: Option<String>,
+    sticky_routing: Option<bool>,
+    profile: Option<String>,
+    default_timeout_ms: Option<u32>
+  ) -> PyResult<Self> {
+    let config = ExecutorConfig {
+      worker_count: workers,
+      max_worker_count: max_workers,
+      queue_capacity,
+      overflow_policy,
+      queue_discipline,
+      sticky_routing,
+      profile,
+      default_timeout_ms,
+      ..ExecutorConfig::default()
+    };
+    let inner = OptimizedMultiCoreExecutor::new(Some(config)).map_err(raise)?;
+    Ok(Self { inner })
+  }
+
+  #[getter]
+  fn worker_count(&self) -> u32 {
+    self.inner.worker_count()
+  }
+
+  /// Grow or shrink the pool to `workers`, up to `max_workers`, keeping
+  /// queued tasks
+  fn set_worker_count(&self, workers: u32) -> PyResult<()> {
+    self.inner.set_worker_count(workers).map_err(raise)
+  }
+
+  /// Queue a task; returns its ID
+  #[pyo3(signature = (function_code, data, hints = None, timeout_ms = None))]
+  fn submit(
+    &self,
+    function_code: String,
+    data: String,
+    hints: Option<&Bound<'_, PyAny>>,
+    timeout_ms: Option<u32>
+  ) -> PyResult<String> {
+    let hints = resolve_hints(&function_code, hints)?;
+    self.inner.submit_task(function_code, data, hints, None, timeout_ms).map_err(raise)
+  }
+
+  /// Queue `(function_code, data)` pairs sharing `hints`; returns their IDs in order
+  #[pyo3(signature = (tasks, hints = None, timeout_ms = None))]
+  fn submit_batch(
+    &self,
+    tasks: Vec<(String, String)>,
+    hints: Option<&Bound<'_, PyAny>>,
+    timeout_ms: Option<u32>
+  ) -> PyResult<Vec<String>> {
+    let Some((function_code, _)) = tasks.first() else {
+      return Ok(Vec::new());
+    };
+    let hints = resolve_hints(function_code, hints)?;
+    self.inner
+      .submit_optimized_batch(tasks, hints, None, None, timeout_ms, None)
+      .map_err(raise)
+  }
+
+  /// Queue a JSON or YAML task spec; returns its tasks' IDs in spec order
+  #[pyo3(signature = (spec, format = None))]
+  fn submit_spec(&self, spec: String, format: Option<String>) -> PyResult<Vec<String>> {
+    self.inner.submit_task_spec(spec, format).map_err(raise)
+  }
+
+  /// A task's result as a dict, waiting up to `timeout_ms` (forever if unset)
+  #[pyo3(signature = (task_id, timeout_ms = None))]
+  fn result<'py>(
+    &self,
+    py: Python<'py>,
+    task_id: String,
+    timeout_ms: Option<u32>
+  ) -> PyResult<Bound<'py, PyAny>> {
+    let result = py
+      .allow_threads(|| self.inner.get_result_by_id(task_id, timeout_ms))
+      .map_err(raise)?;
+    from_json(py, &result)
+  }
+
+  /// Results of `task_ids` as dicts, in order
+  #[pyo3(signature = (task_ids, timeout_ms = None))]
+  fn results<'py>(
+    &self,
+    py: Python<'py>,
+    task_ids: Vec<String>,
+    timeout_ms: Option<u32>
+  ) -> PyResult<Bound<'py, PyList>> {
+    let results = py
+      .allow_threads(|| {
+        task_ids
+          .into_iter()
+          .map(|task_id| self.inner.get_result_by_id(task_id, timeout_ms))
+          .collect::<Result<Vec<_>, _>>()
+      })
+      .map_err(raise)?;
+    let results = results
+      .iter()
+      .map(|result| from_json(py, result))
+      .collect::<PyResult<Vec<_>>>()?;
+    PyList::new(py, results)
+  }
+
+  /// Optimization statistics as a dict
+  fn stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    let stats = self.inner.stats_json().to_string();
+    PyModule::import(py, "json")?.call_method1("loads", (stats,))
+  }
+
+  fn cancel(&self, task_id: String) {
+    self.inner.cancel_task(task_id);
+  }
+
+  /// Wait up to `timeout_ms` for queued and running tasks to finish, refusing
+  /// new ones; whether they did
+  #[pyo3(signature = (timeout_ms = None))]
+  fn drain(&self, py: Python<'_>, timeout_ms: Option<u32>) -> bool {
+    py.allow_threads(|| self.inner.start_drain(timeout_ms).wait())
+  }
+
+  fn shutdown(&self) -> PyResult<()> {
+    self.inner.shutdown().map_err(raise)
+  }
+
+  fn __enter__(slf: Py<Self>) -> Py<Self> {
+    slf
+  }
+
+  /// Leaving a `with` block shuts the executor down
+  #[pyo3(signature = (_kind, _value, _traceback))]
+  fn __exit__(
+    &self,
+    _kind: Option<&Bound<'_, PyAny>>,
+    _value: Option<&Bound<'_, PyAny>>,
+    _traceback: Option<&Bound<'_, PyAny>>
+  ) -> PyResult<bool> {
+    self.shutdown()?;
+    Ok(false)
+  }
+}
+
+#[pymodule]
+fn threader(m: &Bound<'_, PyModule>) -> PyResult<()> {
+  m.add_class::<PyExecutor>()?;
+  m.add("ThreaderError", m.py().get_type::<ThreaderError>())?;
+  m.add("QueueFullError", m.py().get_type::<QueueFullError>())?;
+  Ok(())
+}
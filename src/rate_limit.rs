@@ -0,0 +1,248 @@
+// src/rate_limit.rs - Token-bucket rate limits per function and per tenant
+//
+// Limits how many tasks start per second, for functions that call a metered
+// downstream API. Each limit is a token bucket: it refills at the configured
+// rate up to `burst` tokens, and a task takes one token from every bucket that
+// applies to it before it runs. A worker that dequeues a task with no token
+// available parks it until the emptiest bucket refills; workers requeue due
+// tasks on every pass through their loop.
+use std::collections::HashMap;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::time::{ Duration, Instant };
+
+use crate::OptimizedWorkerTask;
+use crate::bindings::napi;
+use crate::clock::SharedClock;
+use crate::priority::{ Priority, Requeue };
+use crate::sync::{ Mutex, MutexGuard, lock_recovering };
+
+/// A rate limit: sustained tasks per second, with up to `burst` at once
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+  pub tasks_per_second: f64,
+  /// Tasks that may start back to back after an idle spell (default 1)
+  pub burst: Option<u32>,
+}
+
+impl RateLimitConfig {
+  pub fn validate(&self) -> Result<(), String> {
+    if !self.tasks_per_second.is_finite() || self.tasks_per_second <= 0.0 {
+      return Err(
+        format!("tasks_per_second must be a positive number, got {}", self.tasks_per_second)
+      );
+    }
+    if self.burst == Some(0) {
+      return Err("burst must be at least 1".to_string());
+    }
+    Ok(())
+  }
+}
+
+/// What a bucket limits
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RateKey {
+  /// Tasks with this `function_hash`
+  Function(String),
+  /// Tasks with this `tenant` hint
+  Tenant(String),
+}
+
+impl RateKey {
+  /// Buckets that can apply to `task`
+  fn for_task(task: &OptimizedWorkerTask) -> [Option<RateKey>; 2] {
+    let hints = &task.optimization_hints;
+    [
+      Some(RateKey::Function(hints.function_hash.clone())),
+      hints.tenant.clone().map(RateKey::Tenant),
+    ]
+  }
+}
+
+#[derive(Debug)]
+struct Bucket {
+  tasks_per_second: f64,
+  burst: f64,
+  tokens: f64,
+  refilled: Instant,
+}
+
+impl Bucket {
+  fn new(config: &RateLimitConfig, now: Instant) -> Result<Self, String> {
+    config.validate()?;
+    let burst = config.burst.unwrap_or(1);
+    Ok(Self {
+      tasks_per_second: config.tasks_per_second,
+      burst: burst as f64,
+      // A new limit starts full
+      tokens: burst as f64,
+      refilled: now,
+    })
+  }
+
+  fn refill(&mut self, now: Instant) {
+    let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+    self.tokens = (self.tokens + elapsed * self.tasks_per_second).min(self.burst);
+    self.refilled = now;
+  }
+
+  /// Time until a whole token is available; zero if one already is
+  fn wait(&self) -> Duration {
+    let missing = (1.0 - self.tokens).max(0.0);
+    Duration::from_secs_f64(missing / self.tasks_per_second)
+  }
+}
+
+#[derive(Debug, Default)]
+struct RateState {
+  buckets: HashMap<RateKey, Bucket>,
+  // Tasks waiting for tokens, with when they may try again
+  parked: Vec<(OptimizedWorkerTask, Priority, Instant)>,
+  // The soonest of those times
+  next_ready: Option<Instant>,
+}
+
+/// Rate limits by function and tenant. Like concurrency limits, they apply to
+/// the shared queue; reserved batches are exempt.
+pub struct RateLimits {
+  state: Mutex<RateState>,
+  // Lets workers skip the lock on every loop pass while nothing is parked
+  parked_count: AtomicUsize,
+  requeue: Requeue,
+  clock: SharedClock,
+}
+
+impl RateLimits {
+  pub fn new(requeue: Requeue, clock: SharedClock) -> Self {
+    Self {
+      state: Mutex::new(RateState::default()),
+      parked_count: AtomicUsize::new(0),
+      requeue,
+      clock,
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, RateState> {
+    lock_recovering(&self.state, || {})
+  }
+
+  /// Set or replace the limit for `key`, or lift it with `None`. Tasks parked on
+  /// a lifted limit retry on the next pass.
+  pub fn set(&self, key: RateKey, config: Option<&RateLimitConfig>) -> Result<(), String> {
+    let now = self.clock.now();
+    {
+      let mut state = self.lock();
+      match config {
+        Some(config) => {
+          state.buckets.insert(key, Bucket::new(config, now)?);
+          return Ok(());
+        }
+        None => {
+          state.buckets.remove(&key);
+          for (_, _, ready_at) in state.parked.iter_mut() {
+            *ready_at = now;
+          }
+          if state.next_ready.is_none() {
+            return Ok(());
+          }
+          state.next_ready = Some(now);
+        }
+      }
+    }
+    self.requeue.wake_idle();
+    Ok(())
+  }
+
+  pub fn get(&self, key: &RateKey) -> Option<RateLimitConfig> {
+    self
+      .lock()
+      .buckets.get(key)
+      .map(|bucket| RateLimitConfig {
+        tasks_per_second: bucket.tasks_per_second,
+        burst: Some(bucket.burst as u32),
+      })
+  }
+
+  /// Take a token from every bucket that applies to `task`, or park it until
+  /// they all have one. `on_park` runs before the lock is released, so a requeue
+  /// can't overtake it.
+  pub fn admit(
+    &self,
+    task: OptimizedWorkerTask,
+    priority: Priority,
+    on_park: impl FnOnce(&OptimizedWorkerTask)
+  ) -> Option<OptimizedWorkerTask> {
+    let mut state = self.lock();
+    if state.buckets.is_empty() {
+      return Some(task);
+    }
+
+    let now = self.clock.now();
+    let keys = RateKey::for_task(&task);
+    let mut wait = Duration::ZERO;
+    for key in keys.iter().flatten() {
+      if let Some(bucket) = state.buckets.get_mut(key) {
+        bucket.refill(now);
+        wait = wait.max(bucket.wait());
+      }
+    }
+
+    if wait > Duration::ZERO {
+      on_park(&task);
+      let ready_at = now + wait;
+      state.parked.push((task, priority, ready_at));
+      self.parked_count.fetch_add(1, Ordering::Release);
+      // Idle workers sleep until the old soonest time; have them wake for this one
+      let sooner = state.next_ready.is_none_or(|next| ready_at < next);
+      if sooner {
+        state.next_ready = Some(ready_at);
+        drop(state);
+        self.requeue.wake_idle();
+      }
+      return None;
+    }
+    for key in keys.iter().flatten() {
+      if let Some(bucket) = state.buckets.get_mut(key) {
+        bucket.tokens -= 1.0;
+      }
+    }
+    Some(task)
+  }
+
+  /// How long until a parked task may try again, if any is parked
+  pub fn next_ready(&self) -> Option<Duration> {
+    if self.parked_count.load(Ordering::Acquire) == 0 {
+      return None;
+    }
+    let now = self.clock.now();
+    self
+      .lock()
+      .next_ready.map(|next| next.saturating_duration_since(now))
+  }
+
+  /// Requeue parked tasks whose wait is over; they take their tokens when a
+  /// worker picks them up again
+  pub fn release_due(&self) {
+    if self.parked_count.load(Ordering::Acquire) == 0 {
+      return;
+    }
+    let now = self.clock.now();
+    let released: Vec<(OptimizedWorkerTask, Priority)> = {
+      let mut state = self.lock();
+      let (due, waiting) = std::mem::take(&mut state.parked)
+        .into_iter()
+        .partition(|(_, _, ready_at)| *ready_at <= now);
+      state.parked = waiting;
+      state.next_ready = state.parked
+        .iter()
+        .map(|(_, _, ready_at)| *ready_at)
+        .min();
+      self.parked_count.store(state.parked.len(), Ordering::Release);
+      due
+        .into_iter()
+        .map(|(task, priority, _)| (task, priority))
+        .collect()
+    };
+    self.requeue.send(released);
+  }
+}
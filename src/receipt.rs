@@ -0,0 +1,87 @@
+// src/receipt.rs - Reproducibility receipts attached to results on request
+use serde::{ Deserialize, Serialize };
+
+use crate::bindings::napi;
+
+/// Everything needed to reproduce a computation exactly
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionReceipt {
+  /// Caller-supplied function hash from the hints
+  pub function_hash: String,
+  /// FNV-1a hash of the exact function code, as hex
+  pub code_hash: String,
+  /// FNV-1a hash of the exact input data, as hex
+  pub data_hash: String,
+  /// Path that produced the result ("rust_optimized", "needs_js_worker", ...)
+  pub backend: String,
+  pub crate_version: String,
+  /// Architecture and OS the native module was built for
+  pub target: String,
+  /// Compile-time SIMD target features and crate features
+  pub features: Vec<String>,
+  /// Executor RNG seed (fault injection, traffic splitting), as hex
+  pub seed: String,
+}
+
+impl ExecutionReceipt {
+  pub fn new(
+    function_hash: &str,
+    function_code: &str,
+    data: &str,
+    backend: &str,
+    seed: u64
+  ) -> Self {
+    Self {
+      function_hash: function_hash.to_string(),
+      code_hash: format!("{:016x}", fnv1a(function_code.as_bytes())),
+      data_hash: format!("{:016x}", fnv1a(data.as_bytes())),
+      backend: backend.to_string(),
+      crate_version: env!("CARGO_PKG_VERSION").to_string(),
+      target: format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+      features: build_features(),
+      seed: format!("{:016x}", seed),
+    }
+  }
+}
+
+/// 64-bit FNV-1a; stable across Rust versions and platforms, unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+  bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+    (hash ^ (*byte as u64)).wrapping_mul(0x0100_0000_01b3)
+  })
+}
+
+/// SIMD target features the module was compiled with
+pub fn simd_features() -> Vec<&'static str> {
+  let features = [
+    ("sse2", cfg!(target_feature = "sse2")),
+    ("sse4.2", cfg!(target_feature = "sse4.2")),
+    ("avx", cfg!(target_feature = "avx")),
+    ("avx2", cfg!(target_feature = "avx2")),
+    ("fma", cfg!(target_feature = "fma")),
+    ("neon", cfg!(target_feature = "neon")),
+  ];
+
+  features
+    .iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(name, _)| *name)
+    .collect()
+}
+
+/// `simd_features` plus the crate features that change results
+pub fn build_features() -> Vec<String> {
+  let crate_features = [("icu", cfg!(feature = "icu")), ("loom", cfg!(feature = "loom"))];
+
+  simd_features()
+    .into_iter()
+    .chain(
+      crate_features
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+    )
+    .map(str::to_string)
+    .collect()
+}
@@ -0,0 +1,194 @@
+// src/reservation.rs - Exclusive worker reservations for time-critical batches
+use crossbeam::channel;
+use std::collections::VecDeque;
+use std::sync::atomic::{ AtomicU64, AtomicUsize, Ordering };
+
+use crate::OptimizedWorkerTask;
+use crate::priority::Doorbell;
+use crate::sync::{ Arc, Mutex, MutexGuard, lock_recovering };
+
+/// A batch's private queue, served only by the workers reserved for it
+#[derive(Debug)]
+pub struct Reservation {
+  id: u64,
+  workers: usize,
+  sender: channel::Sender<OptimizedWorkerTask>,
+  receiver: channel::Receiver<OptimizedWorkerTask>,
+  // Tasks not yet finished; the workers are released when it reaches zero
+  remaining: AtomicUsize,
+}
+
+impl Reservation {
+  /// Reservation ID, used for task status as `QueueId::Reserved`
+  pub fn id(&self) -> u64 {
+    self.id
+  }
+
+  /// Add a task; only valid before the reservation is requested
+  pub fn push(&self, task: OptimizedWorkerTask) {
+    self.remaining.fetch_add(1, Ordering::AcqRel);
+    // The reservation owns the receiver, so the channel never disconnects
+    let _ = self.sender.send(task);
+  }
+
+  pub fn try_recv(&self) -> Result<OptimizedWorkerTask, channel::TryRecvError> {
+    self.receiver.try_recv()
+  }
+}
+
+#[derive(Debug)]
+struct TableState {
+  // Reservation each worker is serving, by worker ID, for every slot the pool
+  // can grow to
+  assignments: Vec<Option<Arc<Reservation>>>,
+  // Workers the pool runs; only these are granted to reservations
+  workers: usize,
+  // The first workers, set aside for operation pools, are never granted
+  dedicated: usize,
+  // Reservations waiting for enough free workers, granted strictly in order so
+  // a large reservation is never starved by smaller ones behind it
+  pending: VecDeque<Arc<Reservation>>,
+}
+
+/// Hands workers to reservations and back to the shared queue.
+///
+/// A reserved worker stops taking shared tasks and only serves its
+/// reservation until every task in it has finished.
+#[derive(Debug)]
+pub struct ReservationTable {
+  state: Mutex<TableState>,
+  next_id: AtomicU64,
+  // Lets workers skip the lock while nothing is reserved
+  assigned_workers: AtomicUsize,
+  // Wakes workers as they're reserved and released
+  doorbell: Doorbell,
+}
+
+impl ReservationTable {
+  /// Table granting the workers from `dedicated` up to `worker_count` of `slots`
+  pub fn new(slots: usize, dedicated: usize, worker_count: usize, doorbell: Doorbell) -> Self {
+    Self {
+      state: Mutex::new(TableState {
+        assignments: vec![None; slots],
+        workers: worker_count,
+        dedicated,
+        pending: VecDeque::new(),
+      }),
+      next_id: AtomicU64::new(1),
+      assigned_workers: AtomicUsize::new(0),
+      doorbell,
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, TableState> {
+    lock_recovering(&self.state, || {})
+  }
+
+  /// Start a reservation of `workers` workers; push its tasks, then `request` it
+  pub fn create(&self, workers: usize) -> Arc<Reservation> {
+    let (sender, receiver) = channel::unbounded();
+    Arc::new(Reservation {
+      id: self.next_id.fetch_add(1, Ordering::Relaxed),
+      workers,
+      sender,
+      receiver,
+      remaining: AtomicUsize::new(0),
+    })
+  }
+
+  /// Grant the reservation its workers now if enough are free, otherwise queue it
+  pub fn request(&self, reservation: Arc<Reservation>) {
+    if reservation.remaining.load(Ordering::Acquire) == 0 {
+      return;
+    }
+
+    let mut state = self.lock();
+    state.pending.push_back(reservation);
+    self.grant_pending(&mut state);
+  }
+
+  /// Grant only the first `workers` workers from now on, after the pool is
+  /// resized. Workers already serving a reservation keep it until it's done;
+  /// one waiting for more workers than are left gets all of them.
+  pub fn set_workers(&self, workers: usize) {
+    let mut state = self.lock();
+    state.workers = workers;
+    self.grant_pending(&mut state);
+  }
+
+  /// Reservation `worker_id` is serving, if any
+  pub fn assigned(&self, worker_id: usize) -> Option<Arc<Reservation>> {
+    if self.assigned_workers.load(Ordering::Acquire) == 0 {
+      return None;
+    }
+    self.lock().assignments.get(worker_id).cloned().flatten()
+  }
+
+  /// Count a reserved task as finished once the returned guard drops, so
+  /// every exit path of the worker loop (skips, panics) is accounted for
+  pub fn track<'a>(&'a self, reservation: &Arc<Reservation>) -> TaskSlot<'a> {
+    TaskSlot {
+      table: self,
+      reservation: Arc::clone(reservation),
+    }
+  }
+
+  fn task_done(&self, reservation: &Arc<Reservation>) {
+    if reservation.remaining.fetch_sub(1, Ordering::AcqRel) != 1 {
+      return;
+    }
+
+    // Last task finished: return the workers and let queued reservations in
+    let mut state = self.lock();
+    let mut released = 0;
+    for (worker, slot) in state.assignments.iter_mut().enumerate() {
+      if slot.as_ref().is_some_and(|r| Arc::ptr_eq(r, reservation)) {
+        *slot = None;
+        released += 1;
+        self.doorbell.ring(worker);
+      }
+    }
+    self.assigned_workers.fetch_sub(released, Ordering::AcqRel);
+    self.grant_pending(&mut state);
+  }
+
+  fn grant_pending(&self, state: &mut TableState) {
+    let grantable = state.dedicated..state.workers.max(state.dedicated);
+    while let Some(next) = state.pending.front() {
+      let needed = next.workers.min(grantable.len());
+      let free = state.assignments[grantable.clone()]
+        .iter()
+        .filter(|slot| slot.is_none())
+        .count();
+      if free < needed {
+        break;
+      }
+
+      let next = state.pending.pop_front().expect("front was just checked");
+      // Counted first, so a worker woken below looks under the lock
+      self.assigned_workers.fetch_add(needed, Ordering::AcqRel);
+      for (worker, slot) in state.assignments
+        .iter_mut()
+        .enumerate()
+        .skip(grantable.start)
+        .take(grantable.len())
+        .filter(|(_, slot)| slot.is_none())
+        .take(needed) {
+        *slot = Some(Arc::clone(&next));
+        self.doorbell.ring(worker);
+      }
+    }
+  }
+}
+
+/// One in-flight reserved task; see `ReservationTable::track`
+pub struct TaskSlot<'a> {
+  table: &'a ReservationTable,
+  reservation: Arc<Reservation>,
+}
+
+impl Drop for TaskSlot<'_> {
+  fn drop(&mut self) {
+    self.table.task_done(&self.reservation);
+  }
+}
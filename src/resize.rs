@@ -0,0 +1,264 @@
+// src/resize.rs - Grows and shrinks the worker pool while it runs
+use std::sync::atomic::{ AtomicBool, AtomicU64, AtomicUsize, Ordering };
+use std::time::{ Duration, Instant };
+
+use crate::cpu_budget::CpuBudget;
+use crate::events::EventLog;
+use crate::priority::{ Doorbell, LaneSender, WorkerLanes };
+use crate::reservation::ReservationTable;
+use crate::supervisor::Flatline;
+use crate::sync::{ Arc, Condvar, Mutex, MutexGuard, lock_recovering, thread };
+use crate::throttle::ThrottleMonitor;
+
+/// Starts a worker thread on one slot's end of the queue. A std `Arc`, as
+/// loom's can't hold a closure.
+pub type SpawnWorker = std::sync::Arc<dyn Fn(WorkerLanes) -> Result<(), String> + Send + Sync>;
+
+/// The pool's worker slots, fixed at `max_worker_count`, of which the first
+/// `target` run a worker.
+///
+/// Shrinking doesn't stop anyone mid-task: a worker past the target retires
+/// the next time it looks for work, handing its slot's end of the queue back
+/// here. Growing starts a worker on each vacant slot below the new target.
+/// A worker still winding down when the pool grows back past it keeps running.
+#[derive(Debug)]
+pub struct WorkerSlots {
+  capacity: usize,
+  // Workers in operation pools and one general one, the least the pool runs
+  fewest: usize,
+  target: AtomicUsize,
+  // Each slot's end of the queue while no worker holds it
+  vacant: Mutex<Vec<Option<WorkerLanes>>>,
+  // Wakes idle surplus workers so they retire
+  doorbell: Doorbell,
+  // Time every worker has spent running tasks, for the autoscaler
+  busy_nanos: AtomicU64,
+}
+
+impl WorkerSlots {
+  /// One vacant slot for each of `lanes`, of which at least `fewest` must run;
+  /// `resize` starts the first workers
+  pub fn new(lanes: Vec<WorkerLanes>, fewest: usize, doorbell: Doorbell) -> Self {
+    Self {
+      capacity: lanes.len(),
+      fewest,
+      target: AtomicUsize::new(0),
+      vacant: Mutex::new(lanes.into_iter().map(Some).collect()),
+      doorbell,
+      busy_nanos: AtomicU64::new(0),
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, Vec<Option<WorkerLanes>>> {
+    lock_recovering(&self.vacant, || {})
+  }
+
+  /// Most workers the pool can grow to
+  pub fn capacity(&self) -> usize {
+    self.capacity
+  }
+
+  /// Fewest workers the pool can shrink to
+  pub fn fewest(&self) -> usize {
+    self.fewest
+  }
+
+  /// Workers the pool is running, or winding down to
+  pub fn target(&self) -> usize {
+    self.target.load(Ordering::Acquire)
+  }
+
+  /// Whether `worker` should retire instead of taking another task
+  pub fn is_surplus(&self, worker: usize) -> bool {
+    worker >= self.target()
+  }
+
+  /// Count time a worker spent running a task
+  pub fn record_busy(&self, busy: Duration) {
+    self.busy_nanos.fetch_add(busy.as_nanos() as u64, Ordering::Relaxed);
+  }
+
+  /// Time every worker has spent running tasks so far
+  pub fn busy(&self) -> Duration {
+    Duration::from_nanos(self.busy_nanos.load(Ordering::Relaxed))
+  }
+
+  /// Give up a surplus worker's slot, or get it back if the pool grew past
+  /// the worker again in the meantime
+  pub fn retire(&self, lanes: WorkerLanes) -> Option<WorkerLanes> {
+    let mut vacant = self.lock();
+    let worker = lanes.worker();
+    if worker < self.target() {
+      return Some(lanes);
+    }
+    vacant[worker] = Some(lanes);
+    None
+  }
+
+  /// Run `workers` workers: start one with `spawn` on each vacant slot below
+  /// it, and wake the idle ones past it to retire
+  pub fn resize(&self, workers: usize, spawn: &SpawnWorker) -> Result<(), String> {
+    let mut vacant = self.lock();
+    if workers < self.fewest || workers > self.capacity {
+      return Err(
+        format!(
+          "Worker count must be between {} and {}, got {}",
+          self.fewest,
+          self.capacity,
+          workers
+        )
+      );
+    }
+    self.target.store(workers, Ordering::Release);
+    for lanes in vacant[..workers].iter_mut() {
+      if let Some(lanes) = lanes.take() {
+        spawn(lanes)?;
+      }
+    }
+    for worker in workers..self.capacity {
+      self.doorbell.ring(worker);
+    }
+    Ok(())
+  }
+}
+
+/// Worker threads started and not yet joined, so shutdown can wait for them
+#[derive(Debug, Default)]
+pub struct WorkerThreads {
+  // Workers whose thread hasn't finished yet
+  running: Mutex<usize>,
+  exited: Condvar,
+  // Each thread, and whether it has finished
+  handles: Mutex<Vec<(Arc<AtomicBool>, thread::JoinHandle<()>)>>,
+}
+
+// A worker's place in `WorkerThreads`, given up when its thread ends
+struct Running {
+  threads: Arc<WorkerThreads>,
+  finished: Arc<AtomicBool>,
+}
+
+impl Drop for Running {
+  fn drop(&mut self) {
+    self.finished.store(true, Ordering::Release);
+    *lock_recovering(&self.threads.running, || {}) -= 1;
+    self.threads.exited.notify_all();
+  }
+}
+
+impl WorkerThreads {
+  /// Run `work` on a thread from `builder`, counted from before it starts so
+  /// a shutdown racing the start still waits for it. Forgets the threads of
+  /// retired and dead workers that have finished since.
+  pub fn spawn(
+    threads: &Arc<Self>,
+    builder: thread::Builder,
+    work: impl FnOnce() + Send + 'static
+  ) -> std::io::Result<()> {
+    let finished = Arc::new(AtomicBool::new(false));
+    *lock_recovering(&threads.running, || {}) += 1;
+    let running = Running { threads: Arc::clone(threads), finished: Arc::clone(&finished) };
+    let handle = builder.spawn(move || {
+      let _running = running;
+      work();
+    })?;
+
+    let mut handles = lock_recovering(&threads.handles, || {});
+    handles.retain(|(finished, _)| !finished.load(Ordering::Acquire));
+    handles.push((finished, handle));
+    Ok(())
+  }
+
+  /// Wait up to `timeout` for every worker to finish, then join the threads
+  /// that have. False if some were still running.
+  pub fn join(&self, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    let mut running = lock_recovering(&self.running, || {});
+    while *running > 0 {
+      let remaining = deadline.saturating_duration_since(Instant::now());
+      if remaining.is_zero() {
+        break;
+      }
+      running = self.exited
+        .wait_timeout(running, remaining)
+        .map(|(guard, _)| guard)
+        .unwrap_or_else(|poisoned| poisoned.into_inner().0);
+    }
+    drop(running);
+
+    let handles = std::mem::take(&mut *lock_recovering(&self.handles, || {}));
+    let (finished, unfinished): (Vec<_>, Vec<_>) = handles
+      .into_iter()
+      .partition(|(finished, _)| finished.load(Ordering::Acquire));
+    for (_, handle) in finished {
+      let _ = handle.join();
+    }
+    let all_finished = unfinished.is_empty();
+    lock_recovering(&self.handles, || {}).extend(unfinished);
+    all_finished
+  }
+}
+
+/// Everything a resize touches, shared by `set_worker_count` and the autoscaler
+#[derive(Clone)]
+pub struct Resizer {
+  pub slots: Arc<WorkerSlots>,
+  pub spawn: SpawnWorker,
+  pub sender: Arc<Mutex<Option<LaneSender>>>,
+  pub reservations: Arc<ReservationTable>,
+  pub cpu_budget: Arc<CpuBudget>,
+  pub throttle: Arc<ThrottleMonitor>,
+  pub warmed_cores: Arc<Mutex<Vec<bool>>>,
+  pub events: Arc<EventLog>,
+}
+
+impl Resizer {
+  /// Run `workers` workers, publishing a `workers_resized` event saying `why`.
+  /// Fails once the pool is shut down.
+  pub fn resize(&self, workers: usize, why: &str) -> Result<(), String> {
+    // Held throughout, so concurrent resizes apply in one order everywhere
+    let sender = self.events.lock(&self.sender, "task_sender", None);
+    let Some(sender) = sender.as_ref() else {
+      return Err("Worker pool is shut down".to_string());
+    };
+    let previous = self.slots.target();
+    self.slots.resize(workers, &self.spawn)?;
+    sender.set_workers(workers);
+    self.reservations.set_workers(workers);
+    self.cpu_budget.set_workers(workers);
+    self.throttle.set_workers(workers);
+    self.events.lock(&self.warmed_cores, "warmed_cores", None).resize(workers, false);
+    self.events.publish(
+      "workers_resized",
+      None,
+      format!("Worker pool resized from {} to {} workers {}", previous, workers, why)
+    );
+    Ok(())
+  }
+
+  /// Start a worker in place of a dead one, publishing a `worker_respawned`
+  /// event saying why it died, or `worker_died` if the pool has shrunk past
+  /// its slot since or the new thread failed to start. Fails once the pool is
+  /// shut down.
+  pub fn respawn(&self, flatline: Flatline) -> Result<(), String> {
+    let Flatline { lanes, cause, silent_for } = flatline;
+    let worker = lanes.worker();
+    let sender = self.events.lock(&self.sender, "task_sender", None);
+    if sender.is_none() {
+      return Err("Worker pool is shut down".to_string());
+    }
+    let died = format!(
+      "Worker {} died {}ms after its last heartbeat ({})",
+      worker,
+      silent_for.as_millis(),
+      cause
+    );
+    let (kind, message) = match self.slots.retire(lanes).map(|lanes| (self.spawn)(lanes)) {
+      Some(Ok(())) => ("worker_respawned", format!("{}; respawned", died)),
+      Some(Err(e)) => ("worker_died", format!("{}; respawning it failed: {}", died, e)),
+      None => ("worker_died", format!("{}; not respawned, as the pool has shrunk past it", died)),
+    };
+    self.events.emit(kind, Some(worker), message);
+    Ok(())
+  }
+}
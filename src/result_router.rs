@@ -0,0 +1,419 @@
+// src/result_router.rs - Delivers completed results to whoever asked for them
+//
+// Workers hand results over on a lock-free channel, so completions on
+// different cores never wait on each other or on the callers collecting them.
+// Those callers do the routing: one that takes a result off the channel it
+// wasn't after passes it to the caller waiting on that task ID, or sets it
+// aside for later, under the lock that keeps that by-ID bookkeeping.
+use crossbeam::channel;
+use std::collections::{ HashMap, HashSet, VecDeque };
+use std::time::{ Duration, Instant };
+
+use crate::OptimizedWorkerResult;
+use crate::bindings::Error;
+use crate::memory;
+use crate::sync::atomic::{ AtomicBool, AtomicUsize, Ordering };
+use crate::sync::{ Arc, Mutex, MutexGuard, lock_recovering };
+
+/// Why a result could not be returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouterError {
+  Timeout,
+  Closed,
+}
+
+impl RouterError {
+  pub fn into_error(self) -> Error {
+    match self {
+      Self::Timeout => Error::from_reason("Timed out waiting for result".to_string()),
+      Self::Closed => Error::from_reason("Worker pool is shut down".to_string()),
+    }
+  }
+}
+
+/// Stale places in the arrival order tolerated before it's compacted
+const STALE_SLACK: usize = 64;
+
+#[derive(Default)]
+struct RouterState {
+  // Callers blocked on a specific task ID, each sent a copy of its result, by
+  // a token that lets a caller giving up unregister only itself
+  waiters: HashMap<String, Vec<(u64, channel::Sender<OptimizedWorkerResult>)>>,
+  next_waiter: u64,
+  // Results taken off the channel that nobody has asked for by ID yet, with
+  // arrival order for `next`. Results claimed by ID leave their place in the
+  // order behind, until `compact` clears it out.
+  unclaimed: HashMap<String, OptimizedWorkerResult>,
+  arrival_order: VecDeque<String>,
+}
+
+impl RouterState {
+  fn insert(&mut self, result: OptimizedWorkerResult) {
+    self.arrival_order.push_back(result.id.clone());
+    self.unclaimed.insert(result.id.clone(), result);
+    self.compact();
+  }
+
+  /// Send `task_id`'s result to `sender` too; returns the waiter's token
+  fn wait(&mut self, task_id: &str, sender: channel::Sender<OptimizedWorkerResult>) -> u64 {
+    let token = self.next_waiter;
+    self.next_waiter += 1;
+    self.waiters.entry(task_id.to_string()).or_default().push((token, sender));
+    token
+  }
+
+  fn remove(&mut self, task_id: &str) -> Option<OptimizedWorkerResult> {
+    let result = self.unclaimed.remove(task_id)?;
+    self.compact();
+    Some(result)
+  }
+
+  /// Oldest result set aside
+  fn pop_oldest(&mut self) -> Option<OptimizedWorkerResult> {
+    while let Some(id) = self.arrival_order.pop_front() {
+      // IDs claimed through `take` leave stale entries in the order queue
+      if let Some(result) = self.unclaimed.remove(&id) {
+        return Some(result);
+      }
+    }
+    None
+  }
+
+  /// Drop the places of claimed results once they outnumber the unclaimed
+  /// ones, so callers that only collect by ID don't grow the order forever
+  fn compact(&mut self) {
+    if self.arrival_order.len() > 2 * self.unclaimed.len() + STALE_SLACK {
+      let unclaimed = &self.unclaimed;
+      self.arrival_order.retain(|id| unclaimed.contains_key(id));
+    }
+  }
+}
+
+/// Routes results either to a caller waiting on that task ID or into an
+/// arrival-ordered buffer served to `get_optimized_result`-style callers.
+pub struct ResultRouter {
+  // Delivered results not yet routed, in arrival order
+  arrivals: channel::Sender<OptimizedWorkerResult>,
+  arrived: channel::Receiver<OptimizedWorkerResult>,
+  state: Mutex<RouterState>,
+  closed: AtomicBool,
+  // Dropped on close, which disconnects `closing` and wakes every blocked caller
+  close_signal: Mutex<Option<channel::Sender<()>>>,
+  closing: channel::Receiver<()>,
+  // Results delivered but not yet collected, routed or not, and their
+  // estimated memory
+  buffered: AtomicUsize,
+  buffered_bytes: AtomicUsize,
+  // Most results buffered at once; the oldest is evicted to make room
+  buffer_limit: Option<usize>,
+}
+
+impl Default for ResultRouter {
+  fn default() -> Self {
+    Self::with_buffer_limit(None)
+  }
+}
+
+/// Results of a set of tasks, in completion order (see `ResultRouter::subscribe`)
+pub struct Subscription {
+  receiver: channel::Receiver<OptimizedWorkerResult>,
+  // Routes what arrives meanwhile; `None` when every result is already sent
+  router: Option<Arc<ResultRouter>>,
+}
+
+impl Subscription {
+  /// Subscription to results already sent to `receiver`
+  pub fn of(receiver: channel::Receiver<OptimizedWorkerResult>) -> Self {
+    Self { receiver, router: None }
+  }
+
+  /// Next result, waiting until `deadline` (forever if `None`)
+  pub fn recv(&self, deadline: Option<Instant>) -> Result<OptimizedWorkerResult, RouterError> {
+    match &self.router {
+      Some(router) => router.wait_routed(&self.receiver, deadline),
+      None => {
+        let timeout = deadline.map_or_else(channel::never, channel::at);
+        channel::select! {
+          recv(self.receiver) -> result => result.map_err(|_| RouterError::Closed),
+          recv(timeout) -> _ => Err(RouterError::Timeout),
+        }
+      }
+    }
+  }
+}
+
+impl ResultRouter {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Router that keeps at most `limit` uncollected results
+  pub fn with_buffer_limit(limit: Option<usize>) -> Self {
+    let (arrivals, arrived) = channel::unbounded();
+    let (close_signal, closing) = channel::bounded(0);
+    Self {
+      arrivals,
+      arrived,
+      state: Mutex::new(RouterState::default()),
+      closed: AtomicBool::new(false),
+      close_signal: Mutex::new(Some(close_signal)),
+      closing,
+      buffered: AtomicUsize::new(0),
+      buffered_bytes: AtomicUsize::new(0),
+      buffer_limit: limit,
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, RouterState> {
+    lock_recovering(&self.state, || {})
+  }
+
+  fn is_closed(&self) -> bool {
+    self.closed.load(Ordering::Acquire)
+  }
+
+  /// Hand a completed result over for routing, without taking the router's
+  /// lock unless the buffer is full. Returns the ID of the result evicted to
+  /// stay within the buffer limit, if any.
+  pub fn deliver(&self, result: OptimizedWorkerResult) -> Option<String> {
+    if self.is_closed() {
+      return None;
+    }
+
+    let evicted = match self.buffer_limit {
+      Some(limit) if self.buffered.load(Ordering::Acquire) >= limit => self.evict_oldest(limit),
+      _ => None,
+    };
+    self.buffered.fetch_add(1, Ordering::AcqRel);
+    self.buffered_bytes.fetch_add(memory::result_bytes(&result), Ordering::AcqRel);
+    let _ = self.arrivals.send(result);
+    evicted
+  }
+
+  /// Account for a result leaving the router
+  fn collected(&self, result: &OptimizedWorkerResult) {
+    // Saturating, as `close` zeroes the counts under results still in flight
+    let release = |held: &AtomicUsize, amount: usize| {
+      let _ = held.fetch_update(Ordering::AcqRel, Ordering::Acquire, |held| {
+        Some(held.saturating_sub(amount))
+      });
+    };
+    release(&self.buffered, 1);
+    release(&self.buffered_bytes, memory::result_bytes(result));
+  }
+
+  /// Hand `result` to the callers waiting on its ID, or set it aside
+  fn route(&self, state: &mut RouterState, result: OptimizedWorkerResult) {
+    // Waiters that gave up (e.g. a stream that timed out) leave the result buffered
+    let mut delivered = false;
+    for (_, waiter) in state.waiters.remove(&result.id).unwrap_or_default() {
+      delivered |= waiter.send(result.clone()).is_ok();
+    }
+    if delivered {
+      self.collected(&result);
+    } else {
+      state.insert(result);
+    }
+  }
+
+  /// Route every result delivered so far
+  fn route_arrived(&self, state: &mut RouterState) {
+    while let Ok(result) = self.arrived.try_recv() {
+      self.route(state, result);
+    }
+  }
+
+  /// Wait for a result sent to `receiver`, routing whatever else arrives meanwhile
+  fn wait_routed(
+    &self,
+    receiver: &channel::Receiver<OptimizedWorkerResult>,
+    deadline: Option<Instant>
+  ) -> Result<OptimizedWorkerResult, RouterError> {
+    let timeout = deadline.map_or_else(channel::never, channel::at);
+    loop {
+      channel::select! {
+        recv(receiver) -> result => return result.map_err(|_| RouterError::Closed),
+        recv(self.arrived) -> result => {
+          if let Ok(result) = result {
+            self.route(&mut self.lock(), result);
+          }
+        }
+        recv(self.closing) -> _ => return Err(RouterError::Closed),
+        recv(timeout) -> _ => return Err(RouterError::Timeout),
+      }
+    }
+  }
+
+  fn evict_oldest(&self, limit: usize) -> Option<String> {
+    let mut state = self.lock();
+    // Results on their way to a waiter don't need the room
+    self.route_arrived(&mut state);
+    if self.buffered.load(Ordering::Acquire) < limit {
+      return None;
+    }
+    let evicted = state.pop_oldest()?;
+    self.collected(&evicted);
+    Some(evicted.id)
+  }
+
+  /// Oldest buffered result, waiting up to `timeout` (forever if `None`)
+  pub fn next(&self, timeout: Option<Duration>) -> Result<OptimizedWorkerResult, RouterError> {
+    let deadline = timeout.map(|t| Instant::now() + t);
+    let timeout = deadline.map_or_else(channel::never, channel::at);
+
+    loop {
+      {
+        let mut state = self.lock();
+        if self.is_closed() {
+          return Err(RouterError::Closed);
+        }
+        // Results set aside arrived before any still on the channel
+        if let Some(result) = state.pop_oldest() {
+          self.collected(&result);
+          return Ok(result);
+        }
+      }
+
+      let result = channel::select! {
+        recv(self.arrived) -> result => result.map_err(|_| RouterError::Closed)?,
+        recv(self.closing) -> _ => return Err(RouterError::Closed),
+        recv(timeout) -> _ => return Err(RouterError::Timeout),
+      };
+      let mut state = self.lock();
+      if state.waiters.contains_key(&result.id) {
+        self.route(&mut state, result);
+      } else {
+        self.collected(&result);
+        return Ok(result);
+      }
+    }
+  }
+
+  /// Take buffered results in arrival order without waiting, up to `limit`
+  pub fn drain(&self, limit: usize) -> Result<Vec<OptimizedWorkerResult>, RouterError> {
+    let mut state = self.lock();
+    if self.is_closed() {
+      return Err(RouterError::Closed);
+    }
+
+    self.route_arrived(&mut state);
+    let mut drained = Vec::new();
+    while drained.len() < limit {
+      let Some(result) = state.pop_oldest() else {
+        break;
+      };
+      self.collected(&result);
+      drained.push(result);
+    }
+    Ok(drained)
+  }
+
+  /// Result for a specific task, waiting up to `timeout` (forever if `None`).
+  /// Callers waiting on the same task each get a copy.
+  pub fn take(
+    &self,
+    task_id: &str,
+    timeout: Option<Duration>
+  ) -> Result<OptimizedWorkerResult, RouterError> {
+    let (token, receiver) = {
+      let mut state = self.lock();
+      if self.is_closed() {
+        return Err(RouterError::Closed);
+      }
+      self.route_arrived(&mut state);
+      if let Some(result) = state.remove(task_id) {
+        self.collected(&result);
+        return Ok(result);
+      }
+
+      let (sender, receiver) = channel::bounded(1);
+      (state.wait(task_id, sender), receiver)
+    };
+
+    let deadline = timeout.map(|t| Instant::now() + t);
+    if let Ok(result) = self.wait_routed(&receiver, deadline) {
+      return Ok(result);
+    }
+
+    // Unregister, then check whether routing raced the timeout
+    {
+      let mut state = self.lock();
+      if let Some(waiters) = state.waiters.get_mut(task_id) {
+        waiters.retain(|(waiter, _)| *waiter != token);
+        if waiters.is_empty() {
+          state.waiters.remove(task_id);
+        }
+      }
+    }
+    match receiver.try_recv() {
+      Ok(result) => Ok(result),
+      Err(_) if self.is_closed() => Err(RouterError::Closed),
+      Err(_) => Err(RouterError::Timeout),
+    }
+  }
+
+  /// Route the results of `task_ids` into one subscription in completion order,
+  /// starting with any already buffered. Returns it and how many distinct results
+  /// it will yield; it disconnects early only if the router closes.
+  pub fn subscribe(
+    router: &Arc<Self>,
+    task_ids: &[String]
+  ) -> Result<(Subscription, usize), RouterError> {
+    let mut state = router.lock();
+    if router.is_closed() {
+      return Err(RouterError::Closed);
+    }
+
+    router.route_arrived(&mut state);
+    let (sender, receiver) = channel::unbounded();
+    let task_ids: HashSet<&String> = task_ids.iter().collect();
+    for &task_id in &task_ids {
+      match state.remove(task_id) {
+        Some(result) => {
+          router.collected(&result);
+          let _ = sender.send(result);
+        }
+        None => {
+          state.wait(task_id, sender.clone());
+        }
+      }
+    }
+    let subscription = Subscription { receiver, router: Some(Arc::clone(router)) };
+    Ok((subscription, task_ids.len()))
+  }
+
+  /// Drop a buffered result, when the caller gets a copy from elsewhere
+  pub fn discard(&self, task_id: &str) {
+    let mut state = self.lock();
+    self.route_arrived(&mut state);
+    if let Some(result) = state.remove(task_id) {
+      self.collected(&result);
+    }
+  }
+
+  /// Places held in the arrival order, including those of claimed results
+  #[cfg(all(test, not(feature = "loom")))]
+  pub(crate) fn arrival_order_len(&self) -> usize {
+    self.lock().arrival_order.len()
+  }
+
+  /// Estimated memory held by results nobody has collected yet
+  pub fn buffered_bytes(&self) -> usize {
+    self.buffered_bytes.load(Ordering::Acquire)
+  }
+
+  /// Drop buffered results and wake every waiter with `Closed`
+  pub fn close(&self) {
+    self.closed.store(true, Ordering::Release);
+    let mut state = self.lock();
+    state.unclaimed.clear();
+    state.arrival_order.clear();
+    while self.arrived.try_recv().is_ok() {}
+    self.buffered.store(0, Ordering::Release);
+    self.buffered_bytes.store(0, Ordering::Release);
+    // Dropping the senders disconnects waiting `take` calls
+    state.waiters.clear();
+    drop(state);
+    lock_recovering(&self.close_signal, || {}).take();
+  }
+}
@@ -0,0 +1,127 @@
+// src/result_router_tests.rs - How results reach the callers collecting them
+use std::sync::Arc;
+use std::thread;
+use std::time::{ Duration, Instant };
+
+use crate::OptimizedWorkerResult;
+use crate::result_router::{ ResultRouter, RouterError };
+
+fn result(id: &str) -> OptimizedWorkerResult {
+  OptimizedWorkerResult {
+    id: id.to_string(),
+    result: Some("42".to_string()),
+    error: None,
+    duration_ms: 0.0,
+    worker_id: 0,
+    execution_type: "rust_optimized".to_string(),
+    optimization_used: "rust_optimized".to_string(),
+    cache_hit: false,
+    timed_out: false,
+    meta: None,
+    receipt: None,
+    typed: None,
+    binary: None,
+    fallback: None,
+  }
+}
+
+#[test]
+fn collecting_by_id_keeps_the_arrival_order_bounded() {
+  let router = ResultRouter::new();
+  for i in 0..10_000 {
+    let id = format!("task-{}", i);
+    router.deliver(result(&id));
+    // Every other result is dropped rather than collected
+    if i % 2 == 0 {
+      router.take(&id, Some(Duration::ZERO)).expect("result");
+    } else {
+      router.discard(&id);
+    }
+  }
+  assert!(router.arrival_order_len() <= 64, "{}", router.arrival_order_len());
+  assert_eq!(router.buffered_bytes(), 0);
+}
+
+#[test]
+fn next_skips_results_claimed_by_id() {
+  let router = ResultRouter::new();
+  for i in 0..200 {
+    router.deliver(result(&format!("task-{}", i)));
+  }
+  for i in (0..200).filter(|i| i % 3 != 0) {
+    router.take(&format!("task-{}", i), None).expect("result");
+  }
+  // The rest come out in arrival order, however often the order was compacted
+  let rest = router.drain(usize::MAX).expect("drain");
+  let rest: Vec<&str> = rest.iter().map(|result| result.id.as_str()).collect();
+  let expected: Vec<String> = (0..200).step_by(3).map(|i| format!("task-{}", i)).collect();
+  assert_eq!(rest, expected);
+}
+
+#[test]
+fn concurrent_deliveries_reach_each_waiter_once() {
+  const THREADS: usize = 4;
+  const PER_THREAD: usize = 500;
+  let router = Arc::new(ResultRouter::new());
+  let ids = |thread: usize| (0..PER_THREAD).map(move |i| format!("task-{}-{}", thread, i));
+
+  // Collectors wait on IDs before, during and after their delivery
+  let collectors: Vec<_> = (0..THREADS)
+    .map(|thread| {
+      let router = Arc::clone(&router);
+      thread::spawn(move || {
+        ids(thread)
+          .map(|id| router.take(&id, Some(Duration::from_secs(5))).expect("result").id == id)
+          .filter(|matched| *matched)
+          .count()
+      })
+    })
+    .collect();
+  let workers: Vec<_> = (0..THREADS)
+    .map(|thread| {
+      let router = Arc::clone(&router);
+      thread::spawn(move || ids(thread).for_each(|id| drop(router.deliver(result(&id)))))
+    })
+    .collect();
+
+  workers.into_iter().for_each(|worker| worker.join().expect("worker"));
+  for collector in collectors {
+    assert_eq!(collector.join().expect("collector"), PER_THREAD);
+  }
+  assert!(router.drain(usize::MAX).expect("drain").is_empty());
+  assert_eq!(router.buffered_bytes(), 0);
+}
+
+#[test]
+fn subscription_routes_results_others_wait_on() {
+  let router = Arc::new(ResultRouter::new());
+  let ids = ["a".to_string(), "b".to_string()];
+  let (stream, expected) = ResultRouter::subscribe(&router, &ids).expect("subscribe");
+  assert_eq!(expected, 2);
+  let waiter = {
+    let router = Arc::clone(&router);
+    thread::spawn(move || router.take("c", Some(Duration::from_secs(5))))
+  };
+
+  for id in ["c", "a", "b"] {
+    router.deliver(result(id));
+  }
+  // Whichever of the two reads "c" off the channel hands it to its waiter
+  let deadline = Some(Instant::now() + Duration::from_secs(5));
+  let streamed = [stream.recv(deadline).expect("a").id, stream.recv(deadline).expect("b").id];
+  assert_eq!(streamed, ["a", "b"]);
+  assert_eq!(waiter.join().expect("waiter").expect("c").id, "c");
+}
+
+#[test]
+fn full_buffer_evicts_the_oldest_result() {
+  let router = ResultRouter::with_buffer_limit(Some(2));
+  assert_eq!(router.deliver(result("a")), None);
+  assert_eq!(router.deliver(result("b")), None);
+  assert_eq!(router.deliver(result("c")).as_deref(), Some("a"));
+  assert_eq!(router.next(None).expect("next").id, "b");
+
+  router.close();
+  assert_eq!(router.next(None).unwrap_err(), RouterError::Closed);
+  assert_eq!(router.buffered_bytes(), 0);
+}
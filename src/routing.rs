@@ -0,0 +1,63 @@
+// src/routing.rs - Operator overrides for Rust vs JavaScript routing
+use std::collections::HashMap;
+
+use crate::OptimizationHints;
+
+/// Backend a function is pinned to, regardless of its hints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoutingBackend {
+  Rust,
+  Js,
+}
+
+impl RoutingBackend {
+  /// Parse `"rust"` / `"js"`; `"auto"` means no override
+  pub fn parse(backend: &str) -> Result<Option<Self>, String> {
+    match backend {
+      "rust" => Ok(Some(Self::Rust)),
+      "js" => Ok(Some(Self::Js)),
+      "auto" => Ok(None),
+      other => Err(format!("Unknown routing backend '{}', expected rust, js or auto", other)),
+    }
+  }
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::Rust => "rust",
+      Self::Js => "js",
+    }
+  }
+}
+
+/// Per-function routing overrides, keyed by function hash
+#[derive(Debug, Default)]
+pub struct RoutingTable {
+  overrides: HashMap<String, RoutingBackend>,
+}
+
+impl RoutingTable {
+  pub fn set(&mut self, function_hash: String, backend: Option<RoutingBackend>) {
+    match backend {
+      Some(backend) => {
+        self.overrides.insert(function_hash, backend);
+      }
+      None => {
+        self.overrides.remove(&function_hash);
+      }
+    }
+  }
+
+  /// Rewrite `should_use_rust` if the function has an override
+  pub fn apply(&self, hints: &mut OptimizationHints) {
+    if let Some(backend) = self.overrides.get(&hints.function_hash) {
+      hints.should_use_rust = *backend == RoutingBackend::Rust;
+    }
+  }
+
+  pub fn to_map(&self) -> HashMap<String, String> {
+    self.overrides
+      .iter()
+      .map(|(hash, backend)| (hash.clone(), backend.as_str().to_string()))
+      .collect()
+  }
+}
@@ -0,0 +1,92 @@
+// src/run_task.rs - Promise-based task execution over napi AsyncTask
+use crossbeam::channel;
+use napi::{ Env, Result, Task };
+use std::time::Duration;
+
+use crate::OptimizedWorkerResult;
+use crate::abort::abort_error;
+use crate::protocol::Negotiated;
+use crate::sync::Arc;
+
+/// Waits for a single task's result off the JS thread.
+///
+/// Resolves with the `OptimizedWorkerResult`, or rejects with
+/// the task's error so callers can use plain `try`/`await`.
+pub struct RunTask {
+  receiver: channel::Receiver<OptimizedWorkerResult>,
+  timeout: Option<Duration>,
+  // Fires when the caller's AbortSignal does
+  abort: Option<channel::Receiver<()>>,
+  // Result shapes agreed with the client
+  protocol: Option<Arc<Negotiated>>,
+}
+
+impl RunTask {
+  pub fn new(receiver: channel::Receiver<OptimizedWorkerResult>, timeout_ms: Option<u32>) -> Self {
+    Self {
+      receiver,
+      timeout: timeout_ms.map(|t| Duration::from_millis(t as u64)),
+      abort: None,
+      protocol: None,
+    }
+  }
+
+  /// Reject with `AbortError` as soon as `abort` receives
+  pub fn with_abort(mut self, abort: channel::Receiver<()>) -> Self {
+    self.abort = Some(abort);
+    self
+  }
+
+  /// Shape the result as agreed by `negotiate_protocol`
+  pub fn with_protocol(mut self, protocol: Arc<Negotiated>) -> Self {
+    self.protocol = Some(protocol);
+    self
+  }
+
+  fn aborted(&self) -> bool {
+    self.abort.as_ref().is_some_and(|abort| abort.try_recv().is_ok())
+  }
+}
+
+impl Task for RunTask {
+  type Output = OptimizedWorkerResult;
+  type JsValue = OptimizedWorkerResult;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let timeout = self.timeout.map_or_else(channel::never, channel::after);
+    let mut abort = self.abort.clone().unwrap_or_else(channel::never);
+
+    let mut result = loop {
+      channel::select! {
+        recv(self.receiver) -> result => match result {
+          Ok(result) => break result,
+          // A cancelled task is dropped, disconnecting its reply channel
+          Err(_) if self.aborted() => return Err(abort_error()),
+          Err(e) => return Err(napi::Error::from_reason(e.to_string())),
+        },
+        recv(abort) -> signal => match signal {
+          Ok(()) => return Err(abort_error()),
+          // The signal was garbage collected without firing
+          Err(_) => abort = channel::never(),
+        },
+        recv(timeout) -> _ => {
+          return Err(
+            napi::Error::from_reason("timed out waiting on receive operation".to_string())
+          );
+        }
+      }
+    };
+
+    if let Some(protocol) = self.protocol.as_ref() {
+      protocol.adapt(&mut result);
+    }
+    match result.error {
+      Some(error) => Err(napi::Error::from_reason(error)),
+      None => Ok(result),
+    }
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
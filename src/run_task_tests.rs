@@ -0,0 +1,63 @@
+// src/run_task_tests.rs - How a runTask promise settles
+use crossbeam::channel;
+use napi::{ Status, Task };
+use std::thread;
+use std::time::Duration;
+
+use crate::OptimizedWorkerResult;
+use crate::run_task::RunTask;
+
+fn result(error: Option<&str>) -> OptimizedWorkerResult {
+  OptimizedWorkerResult {
+    id: "task_1".to_string(),
+    result: error.is_none().then(|| "42".to_string()),
+    error: error.map(str::to_string),
+    duration_ms: 0.0,
+    worker_id: 0,
+    execution_type: "rust_optimized".to_string(),
+    optimization_used: "rust_optimized".to_string(),
+    cache_hit: false,
+    timed_out: false,
+    meta: None,
+    receipt: None,
+    typed: None,
+    binary: None,
+    fallback: None,
+  }
+}
+
+#[test]
+fn resolves_with_the_result_and_rejects_with_its_error() {
+  let (sender, receiver) = channel::bounded(1);
+  let mut run = RunTask::new(receiver, None);
+  // Delivered after the wait has started
+  let delivering = thread::spawn(move || {
+    thread::sleep(Duration::from_millis(20));
+    sender.send(result(None)).unwrap();
+  });
+  assert_eq!(run.compute().expect("resolved").result.as_deref(), Some("42"));
+  delivering.join().unwrap();
+
+  let (sender, receiver) = channel::bounded(1);
+  sender.send(result(Some("Division by zero"))).unwrap();
+  let rejected = RunTask::new(receiver, None).compute().unwrap_err();
+  assert_eq!(rejected.reason, "Division by zero");
+}
+
+#[test]
+fn rejects_on_timeout_or_abort() {
+  let (_sender, receiver) = channel::bounded::<OptimizedWorkerResult>(1);
+  let timed_out = RunTask::new(receiver, Some(20)).compute().unwrap_err();
+  assert_eq!(timed_out.reason, "timed out waiting on receive operation");
+
+  let (_sender, receiver) = channel::bounded::<OptimizedWorkerResult>(1);
+  let (abort, aborted) = channel::bounded(1);
+  abort.send(()).unwrap();
+  let rejected = RunTask::new(receiver, None).with_abort(aborted).compute().unwrap_err();
+  assert_eq!(rejected.status, Status::Cancelled);
+
+  // A task dropped unanswered rejects instead of waiting forever
+  let (sender, receiver) = channel::bounded::<OptimizedWorkerResult>(1);
+  drop(sender);
+  assert!(RunTask::new(receiver, None).compute().is_err());
+}
@@ -0,0 +1,73 @@
+// src/scratch.rs - Per-thread buffers reused across tasks
+//
+// Fast paths collect numbers out of parsed arrays and serialize their output,
+// and allocating those buffers afresh for every task churns the allocator and
+// fragments the heap under sustained load. Each thread instead keeps its
+// buffers between tasks, grown to the largest size it has needed up to a cap.
+// A buffer that an outsized task grew past the cap is shrunk back afterwards,
+// so one huge payload doesn't pin its memory for the life of the worker.
+use std::cell::RefCell;
+
+use serde::Serialize;
+
+/// Bytes each scratch buffer keeps between tasks unless the config says otherwise
+pub const DEFAULT_LIMIT_BYTES: usize = 1024 * 1024;
+
+struct Scratch {
+  numbers: Vec<f64>,
+  json: Vec<u8>,
+  limit: usize,
+}
+
+impl Scratch {
+  /// Empty `buffer`, giving back whatever it holds beyond the cap
+  fn reset<T>(buffer: &mut Vec<T>, limit: usize) {
+    buffer.clear();
+    let keep = limit / size_of::<T>();
+    if buffer.capacity() > keep {
+      buffer.shrink_to(keep);
+    }
+  }
+}
+
+thread_local! {
+  static SCRATCH: RefCell<Scratch> = const {
+    RefCell::new(Scratch { numbers: Vec::new(), json: Vec::new(), limit: DEFAULT_LIMIT_BYTES })
+  };
+}
+
+/// Cap the bytes this thread's buffers keep between tasks; workers set it from
+/// `ExecutorConfig::scratch_limit_bytes` when they start
+pub fn set_limit(bytes: usize) {
+  SCRATCH.with_borrow_mut(|scratch| scratch.limit = bytes);
+}
+
+/// Run `f` with this thread's empty number buffer. A nested call, which
+/// can't share it, gets a fresh one.
+pub fn with_numbers<R>(f: impl FnOnce(&mut Vec<f64>) -> R) -> R {
+  SCRATCH.with(|scratch| {
+    let Ok(mut scratch) = scratch.try_borrow_mut() else {
+      return f(&mut Vec::new());
+    };
+    let scratch = &mut *scratch;
+    let result = f(&mut scratch.numbers);
+    Scratch::reset(&mut scratch.numbers, scratch.limit);
+    result
+  })
+}
+
+/// JSON text of `value`, written into this thread's buffer and copied out at
+/// its final size, instead of growing a new string step by step
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<String, String> {
+  SCRATCH.with(|scratch| {
+    let Ok(mut scratch) = scratch.try_borrow_mut() else {
+      return serde_json::to_string(value).map_err(|e| e.to_string());
+    };
+    let scratch = &mut *scratch;
+    let written = serde_json::to_writer(&mut scratch.json, value).map_err(|e| e.to_string());
+    // serde_json only writes UTF-8
+    let json = written.map(|()| String::from_utf8_lossy(&scratch.json).into_owned());
+    Scratch::reset(&mut scratch.json, scratch.limit);
+    json
+  })
+}
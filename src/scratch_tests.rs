@@ -0,0 +1,58 @@
+// src/scratch_tests.rs - Per-thread scratch buffers kept between tasks up to a cap
+use crate::scratch;
+
+// Run `f` where no other test's buffers or limit can be seen
+fn on_new_thread(f: impl FnOnce() + Send + 'static) {
+  std::thread::spawn(f).join().expect("test thread");
+}
+
+// Capacity of the number buffer as the next task finds it
+fn kept_capacity() -> usize {
+  scratch::with_numbers(|numbers| {
+    assert!(numbers.is_empty());
+    numbers.capacity()
+  })
+}
+
+#[test]
+fn number_buffer_is_reused_up_to_the_cap() {
+  on_new_thread(|| {
+    scratch::set_limit(1024);
+    let sum = scratch::with_numbers(|numbers| {
+      numbers.extend((1..=100).map(f64::from));
+      numbers.iter().sum::<f64>()
+    });
+    assert_eq!(sum, 5050.0);
+    assert!(kept_capacity() >= 100);
+
+    // 1024 bytes hold 128 numbers; an outsized task's growth is given back
+    scratch::with_numbers(|numbers| numbers.extend((0..10_000).map(f64::from)));
+    let kept = kept_capacity();
+    assert!((100..=128).contains(&kept), "{}", kept);
+  });
+}
+
+#[test]
+fn nested_calls_get_their_own_buffer() {
+  let (outer, inner) = scratch::with_numbers(|outer| {
+    outer.push(1.0);
+    let inner = scratch::with_numbers(|inner| {
+      inner.push(2.0);
+      inner.clone()
+    });
+    (outer.clone(), inner)
+  });
+  assert_eq!((outer, inner), (vec![1.0], vec![2.0]));
+}
+
+#[test]
+fn json_is_written_through_the_buffer() {
+  on_new_thread(|| {
+    scratch::set_limit(16);
+    let long: Vec<u32> = (0..1000).collect();
+    assert_eq!(scratch::to_json(&long).unwrap(), serde_json::to_string(&long).unwrap());
+    // Nothing of the previous output leaks into the next
+    assert_eq!(scratch::to_json(&["a", "b"]).unwrap(), r#"["a","b"]"#);
+    assert_eq!(scratch::to_json(&f64::NAN).unwrap(), "null");
+  });
+}
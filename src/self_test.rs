@@ -0,0 +1,367 @@
+// src/self_test.rs - Known-answer checks for every native backend and fast path
+use std::time::Instant;
+
+use crate::binary::FastPathOutput;
+use crate::bindings::napi;
+use crate::worker_pool::WorkerPool;
+use crate::{
+  OptimizationHints,
+  OptimizedMultiCoreExecutor,
+  OptimizedWorkerTask,
+  PARALLEL_REDUCTION_THRESHOLD,
+  Reply,
+  receipt,
+};
+
+/// One known-answer check
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct SelfTestCheck {
+  /// Fast path under test, e.g. "array.parallel_sum"
+  pub name: String,
+  /// "passed", "failed" or "skipped" (the feature it needs is not compiled in)
+  pub status: String,
+  pub expected: String,
+  pub actual: Option<String>,
+  /// Why the check failed or was skipped
+  pub error: Option<String>,
+}
+
+/// Outcome of `self_test`, with what this build of the native module can do
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct SelfTestReport {
+  /// No check failed; skipped checks don't count against it
+  pub passed: bool,
+  pub checks: Vec<SelfTestCheck>,
+  pub crate_version: String,
+  /// Architecture and OS the native module was built for
+  pub target: String,
+  /// Compile-time SIMD target features and crate features
+  pub features: Vec<String>,
+  pub cpu_cores: u32,
+  pub duration_ms: f64,
+}
+
+impl SelfTestReport {
+  /// Names of the failed checks with their errors, for a one-line message
+  pub fn failure_summary(&self) -> String {
+    self.checks
+      .iter()
+      .filter(|check| check.status == "failed")
+      .map(|check| format!("{}: {}", check.name, check.error.as_deref().unwrap_or("failed")))
+      .collect::<Vec<_>>()
+      .join("; ")
+  }
+}
+
+/// A fast-path input and the output it must produce
+struct FastPathCase {
+  name: &'static str,
+  operation_type: &'static str,
+  function_code: &'static str,
+  data: String,
+  /// Expected output as JSON; compared structurally, so key order doesn't matter
+  expected: String,
+  deterministic: bool,
+  output_precision: Option<u32>,
+  locale: Option<&'static str>,
+}
+
+impl FastPathCase {
+  fn new(
+    name: &'static str,
+    operation_type: &'static str,
+    function_code: &'static str,
+    data: &str,
+    expected: &str
+  ) -> Self {
+    Self {
+      name,
+      operation_type,
+      function_code,
+      data: data.to_string(),
+      expected: expected.to_string(),
+      deterministic: false,
+      output_precision: None,
+      locale: None,
+    }
+  }
+
+  fn task(&self) -> OptimizedWorkerTask {
+    OptimizedWorkerTask {
+      id: format!("self-test-{}", self.name),
+      function_code: self.function_code.to_string(),
+      data: self.data.clone(),
+      timeout_ms: None,
+      optimization_hints: OptimizationHints {
+        operation_type: self.operation_type.to_string(),
+        complexity: "low".to_string(),
+        expected_cores: 1,
+        should_use_rust: true,
+        is_hot_function: false,
+        estimated_memory: 0,
+        batch_size_hint: None,
+        function_hash: format!("__threader_self_test__{}", self.name),
+        include_receipt: None,
+        deterministic: Some(self.deterministic),
+        output_precision: self.output_precision,
+        rounding: None,
+        locale: self.locale.map(str::to_string),
+        canonical_json: None,
+        priority: None,
+        typed_result: None,
+        group: None,
+        after: None,
+        chunk_size: None,
+        tenant: None,
+        aggregate: None,
+        max_queue_age_ms: None,
+        dedupe: None,
+        child: None,
+        idempotency_key: None,
+      },
+      meta: None,
+      reply: Reply::Discard,
+      epoch: 0,
+      view: None,
+      columnar: None,
+      borrowed: None,
+      expires_at: None,
+      worker: None,
+      sub_batch: None,
+    }
+  }
+
+  fn run(&self) -> SelfTestCheck {
+    if self.locale.is_some() && !cfg!(feature = "icu") {
+      return SelfTestCheck {
+        name: self.name.to_string(),
+        status: "skipped".to_string(),
+        expected: self.expected.clone(),
+        actual: None,
+        error: Some("Built without the `icu` feature".to_string()),
+      };
+    }
+    // Bytes are compared as a JSON array of byte values
+    let outcome = OptimizedMultiCoreExecutor::execute_rust_optimized(
+      &self.task(),
+      0,
+      false,
+      None,
+      &|_| {},
+      &|| {}
+    ).map(|output| match output {
+      FastPathOutput::Json(json) => json,
+      FastPathOutput::Bytes(bytes) => serde_json::json!(bytes).to_string(),
+    });
+    compare(self.name, &self.expected, outcome)
+  }
+}
+
+/// Run every check on the calling thread. Takes a few milliseconds; the parallel
+/// sums briefly use the rayon pool.
+pub fn run() -> SelfTestReport {
+  let started = Instant::now();
+
+  let mut checks: Vec<SelfTestCheck> = fast_path_cases()
+    .iter()
+    .map(FastPathCase::run)
+    .collect();
+  checks.push(worker_pool_check());
+  checks.push(cpu_features_check());
+
+  SelfTestReport {
+    passed: checks.iter().all(|check| check.status != "failed"),
+    checks,
+    crate_version: env!("CARGO_PKG_VERSION").to_string(),
+    target: format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+    features: receipt::build_features(),
+    cpu_cores: num_cpus::get() as u32,
+    duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+  }
+}
+
+fn fast_path_cases() -> Vec<FastPathCase> {
+  // Large enough to take the parallel reduction; integral, so the sum is exact
+  let count = (PARALLEL_REDUCTION_THRESHOLD * 2) as u64;
+  let large_input = serde_json::to_string(&(0..count).collect::<Vec<_>>()).unwrap_or_default();
+  let large_sum = ((count * (count - 1)) / 2).to_string();
+
+  vec![
+    FastPathCase::new("mathematical.scalar", "mathematical", "x => x * 2", "21", "42"),
+    FastPathCase::new(
+      "mathematical.object",
+      "mathematical",
+      "({ a, b }) => a * b",
+      r#"{"a":6,"b":7}"#,
+      r#"{"result":42,"worker_id":0,"optimization":"rust_mathematical"}"#
+    ),
+    FastPathCase {
+      output_precision: Some(3),
+      ..FastPathCase::new(
+        "mathematical.precision",
+        "mathematical",
+        "x => Math.sqrt(x)",
+        "2",
+        "1.414"
+      )
+    },
+    FastPathCase::new(
+      "string.uppercase",
+      "string_operations",
+      "s => s.toUpperCase()",
+      r#""threader""#,
+      r#""THREADER""#
+    ),
+    FastPathCase::new(
+      "string.reverse",
+      "string_operations",
+      "s => s.split('').reverse().join('')",
+      r#""abc""#,
+      r#""cba""#
+    ),
+    FastPathCase::new(
+      "array.sum",
+      "array_operations",
+      "xs => xs.reduce((sum, x) => sum + x, 0)",
+      "[1,2,3,4]",
+      "10"
+    ),
+    FastPathCase::new("array.max", "array_operations", "xs => Math.max(...xs)", "[3,9,2]", "9"),
+    FastPathCase::new(
+      "array.sort",
+      "array_operations",
+      "xs => xs.sort()",
+      r#"["b","a","C"]"#,
+      r#"["C","a","b"]"#
+    ),
+    FastPathCase::new(
+      "array.parallel_sum",
+      "array_operations",
+      "xs => xs.reduce((sum, x) => sum + x, 0)",
+      &large_input,
+      &large_sum
+    ),
+    FastPathCase {
+      deterministic: true,
+      ..FastPathCase::new(
+        "array.deterministic_sum",
+        "array_operations",
+        "xs => xs.reduce((sum, x) => sum + x, 0)",
+        &large_input,
+        &large_sum
+      )
+    },
+    FastPathCase {
+      locale: Some("tr"),
+      ..FastPathCase::new(
+        "locale.uppercase",
+        "string_operations",
+        "s => s.toLocaleUpperCase('tr')",
+        r#""i""#,
+        r#""İ""#
+      )
+    },
+    FastPathCase {
+      locale: Some("en"),
+      ..FastPathCase::new(
+        "locale.compare",
+        "string_operations",
+        "({ a, b }) => a.localeCompare(b)",
+        r#"{"a":"a","b":"B"}"#,
+        "-1"
+      )
+    },
+    FastPathCase::new(
+      "binary.base64",
+      "binary_operations",
+      "s => Buffer.from(s, 'base64')",
+      r#""dGhyZWFkZXI=""#,
+      "[116,104,114,101,97,100,101,114]"
+    ),
+    FastPathCase::new("general.fallback", "general", "x => x + 5", "37", "42")
+  ]
+}
+
+/// The `TaskExecutor`'s pattern-matched functions
+fn worker_pool_check() -> SelfTestCheck {
+  let outcome = WorkerPool::execute_function("x => x * 2", &serde_json::json!(21), false).map(
+    |value| value.to_string()
+  );
+  compare("worker_pool", "42.0", outcome)
+}
+
+/// A build for a newer CPU than the one it runs on can fault in SIMD code paths;
+/// catch that here rather than on the first large task
+fn cpu_features_check() -> SelfTestCheck {
+  let compiled = receipt::simd_features();
+  let missing: Vec<&str> = compiled
+    .iter()
+    .copied()
+    .filter(|feature| !cpu_supports(feature))
+    .collect();
+
+  SelfTestCheck {
+    name: "cpu_features".to_string(),
+    status: (if missing.is_empty() { "passed" } else { "failed" }).to_string(),
+    expected: compiled.join(","),
+    actual: None,
+    error: (!missing.is_empty()).then(||
+      format!("Built for {} but this CPU lacks it", missing.join(", "))
+    ),
+  }
+}
+
+/// Whether this CPU supports a SIMD target feature; unknown ones count as supported
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub fn cpu_supports(feature: &str) -> bool {
+  match feature {
+    "sse2" => is_x86_feature_detected!("sse2"),
+    "sse4.2" => is_x86_feature_detected!("sse4.2"),
+    "avx" => is_x86_feature_detected!("avx"),
+    "avx2" => is_x86_feature_detected!("avx2"),
+    "fma" => is_x86_feature_detected!("fma"),
+    _ => true,
+  }
+}
+
+#[cfg(target_arch = "aarch64")]
+pub fn cpu_supports(feature: &str) -> bool {
+  match feature {
+    "neon" => std::arch::is_aarch64_feature_detected!("neon"),
+    _ => true,
+  }
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+pub fn cpu_supports(_feature: &str) -> bool {
+  true
+}
+
+fn compare(
+  name: &str,
+  expected: &str,
+  outcome: std::result::Result<String, String>
+) -> SelfTestCheck {
+  let (status, error) = match &outcome {
+    Err(error) => ("failed", Some(error.clone())),
+    Ok(actual) if same_json(expected, actual) => ("passed", None),
+    Ok(_) => ("failed", Some("Wrong result".to_string())),
+  };
+  SelfTestCheck {
+    name: name.to_string(),
+    status: status.to_string(),
+    expected: expected.to_string(),
+    actual: outcome.ok(),
+    error,
+  }
+}
+
+fn same_json(expected: &str, actual: &str) -> bool {
+  let parse = serde_json::from_str::<serde_json::Value>;
+  match (parse(expected), parse(actual)) {
+    (Ok(expected), Ok(actual)) => expected == actual,
+    _ => expected == actual,
+  }
+}
@@ -0,0 +1,50 @@
+// src/self_test_tests.rs - What the self-test reports about this build
+use crate::OptimizedMultiCoreExecutor;
+use crate::executor_config::ExecutorConfig;
+use crate::self_test::{ self, SelfTestCheck, SelfTestReport };
+
+fn status<'a>(report: &'a SelfTestReport, name: &str) -> &'a str {
+  let check = report.checks.iter().find(|check| check.name == name);
+  &check.unwrap_or_else(|| panic!("no {} check", name)).status
+}
+
+#[test]
+fn every_backend_passes_its_known_answers() {
+  let report = self_test::run();
+  assert!(report.passed, "{}", report.failure_summary());
+  assert_eq!(report.crate_version, env!("CARGO_PKG_VERSION"));
+  for name in ["mathematical.scalar", "array.parallel_sum", "binary.base64", "worker_pool"] {
+    assert_eq!(status(&report, name), "passed");
+  }
+
+  // Checks for features left out of the build are skipped, not failed
+  let locale = if cfg!(feature = "icu") { "passed" } else { "skipped" };
+  assert_eq!(status(&report, "locale.uppercase"), locale);
+  assert_eq!(report.features.contains(&"icu".to_string()), cfg!(feature = "icu"));
+
+  // Run at construction when the config asks for it
+  let config = ExecutorConfig { self_test: Some(true), ..ExecutorConfig::default() };
+  let executor = OptimizedMultiCoreExecutor::new(Some(config)).expect("self-tested executor");
+  executor.shutdown().unwrap();
+}
+
+#[test]
+fn failure_summary_names_each_failed_check() {
+  let check = |name: &str, status: &str, error: Option<&str>| SelfTestCheck {
+    name: name.to_string(),
+    status: status.to_string(),
+    expected: "42".to_string(),
+    actual: None,
+    error: error.map(str::to_string),
+  };
+  let report = SelfTestReport {
+    passed: false,
+    checks: vec![
+      check("array.sum", "failed", Some("Wrong result")),
+      check("locale.compare", "skipped", Some("Built without the `icu` feature")),
+      check("cpu_features", "failed", None)
+    ],
+    ..self_test::run()
+  };
+  assert_eq!(report.failure_summary(), "array.sum: Wrong result; cpu_features: failed");
+}
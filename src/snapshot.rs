@@ -0,0 +1,136 @@
+// src/snapshot.rs - Executor state handed from one process to the next
+//
+// To upgrade a long-running pipeline without losing warm state, the old
+// process calls `export_state` and the new one `import_state` with the text it
+// returned. Registered functions and staged rollouts, routing overrides, the
+// performance cache, hot-function counts and learned sub-batch sizes carry
+// over. With `hand_off_queue`, so do the tasks waiting in the shared queue:
+// they're taken off it in the order workers would have run them, fail here
+// with a `handed_off` result, and are queued by the importing executor under
+// the same task IDs (and idempotency keys), so callers switching over collect
+// them by ID there. Their `max_queue_age_ms` starts over on import.
+//
+// Tasks tied to this process stay queued here: those in a task group or child
+// executor, `dedupe` and `run_task` ones, sub-batches, deltas, columnar rows
+// and borrowed data. So do tasks not yet queued (delayed, held for a group,
+// a limit or a reservation); drain them before exporting.
+//
+// The text is JSON tagged with `STATE_VERSION`; importing any other fails.
+use serde::{ Deserialize, Serialize };
+use std::collections::HashMap;
+
+use crate::bindings::napi;
+use crate::{ OptimizationHints, OptimizedWorkerTask, Reply };
+
+/// Version of the exported state's shape
+pub const STATE_VERSION: u32 = 1;
+
+/// Everything `export_state` carries over. Sections missing from the text
+/// are empty, so a version mismatch is reported as one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutorState {
+  pub version: u32,
+  pub functions: Vec<FunctionState>,
+  pub routing_overrides: HashMap<String, String>,
+  pub performance_cache: Vec<CacheEntry>,
+  pub hot_functions: HashMap<String, u32>,
+  pub sub_batch_sizes: HashMap<String, usize>,
+  pub queued: Vec<QueuedTask>,
+}
+
+/// A registered function version, or with `traffic_fraction` a staged one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionState {
+  pub spec: String,
+  pub function_code: String,
+  pub hints: OptimizationHints,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub traffic_fraction: Option<f64>,
+}
+
+/// Last run of a function, as the performance cache keeps it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+  pub function_hash: String,
+  pub execution_time_ms: u64,
+  pub optimization_type: String,
+  pub success: bool,
+}
+
+/// A task handed off from the shared queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedTask {
+  pub id: String,
+  pub function_code: String,
+  pub data: String,
+  pub hints: OptimizationHints,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub meta: Option<serde_json::Value>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub timeout_ms: Option<u64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub idempotency_key: Option<String>,
+  /// Submitted with `submit_fire`, so its result is discarded
+  #[serde(default)]
+  pub fire: bool,
+}
+
+impl QueuedTask {
+  /// The task as it can run in another process, or `None` if it can't
+  pub fn from_task(task: &OptimizedWorkerTask, idempotency_key: Option<String>) -> Option<Self> {
+    let hints = &task.optimization_hints;
+    let tied =
+      hints.group.is_some() ||
+      hints.after.is_some() ||
+      hints.child.is_some() ||
+      hints.dedupe == Some(true) ||
+      task.view.is_some() ||
+      task.columnar.is_some() ||
+      task.borrowed.is_some() ||
+      task.sub_batch.is_some();
+    let fire = match task.reply {
+      Reply::Shared => false,
+      Reply::Discard => true,
+      Reply::Channel(_) => {
+        return None;
+      }
+    };
+    (!tied).then(|| QueuedTask {
+      id: task.id.clone(),
+      function_code: task.function_code.clone(),
+      data: task.data.clone(),
+      hints: hints.clone(),
+      meta: task.meta.clone(),
+      timeout_ms: task.timeout_ms,
+      idempotency_key,
+      fire,
+    })
+  }
+}
+
+/// What `import_state` took in
+#[napi(object)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateImport {
+  /// Registered and staged function versions
+  pub functions: u32,
+  pub routing_overrides: u32,
+  /// Performance cache entries
+  pub cache_entries: u32,
+  /// Handed-off tasks queued here
+  pub queued_tasks: u32,
+}
+
+/// Parse exported state, checking its version
+pub fn parse(state: &str) -> Result<ExecutorState, String> {
+  let state: ExecutorState = serde_json::from_str(state).map_err(|e| {
+    format!("Invalid executor state: {}", e)
+  })?;
+  if state.version != STATE_VERSION {
+    return Err(
+      format!("Unsupported executor state version {}, expected {}", state.version, STATE_VERSION)
+    );
+  }
+  Ok(state)
+}
@@ -0,0 +1,237 @@
+// src/soak.rs - Long-running soak test with leak detection
+use serde::Serialize;
+use std::time::{ Duration, Instant };
+
+use crate::bindings::{ Error, Result, napi };
+use crate::executor_config::ExecutorConfig;
+use crate::{ OptimizationHints, OptimizedMultiCoreExecutor };
+
+// Fixed set of functions so caches have a known steady-state size
+const SOAK_FUNCTIONS: [(&str, &str, &str); 4] = [
+  ("x => x * 2", "21", "mathematical"),
+  ("x => x.toUpperCase()", "\"soak\"", "string_operations"),
+  ("arr => arr.reduce((a, b) => a + b, 0) // sum", "[1,2,3,4]", "array_operations"),
+  ("x => Math.sqrt(x)", "16", "general"),
+];
+const SOAK_BATCH_SIZE: usize = 256;
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+const ROUND_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Growth allowances before a resource counts as leaking
+const RSS_GROWTH_LIMIT_KB: u64 = 64 * 1024;
+const RSS_GROWTH_LIMIT_RATIO: f64 = 0.5;
+
+#[derive(Debug, Clone, Serialize)]
+struct SoakSample {
+  elapsed_ms: u64,
+  rss_kb: Option<u64>,
+  threads: Option<u64>,
+  performance_cache_entries: usize,
+  hot_function_entries: usize,
+  tasks_completed: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct SoakReport {
+  passed: bool,
+  failures: Vec<String>,
+  duration_ms: u64,
+  tasks_completed: u64,
+  task_errors: u64,
+  baseline: SoakSample,
+  peak_rss_kb: Option<u64>,
+  final_sample: SoakSample,
+}
+
+/// Hammer a fresh executor for `duration_ms`, sampling RSS, thread count and
+/// cache sizes. Returns a JSON report, or an error carrying the report if any
+/// of them kept growing. Blocks the calling thread for the whole run.
+#[napi]
+pub fn run_soak_test(duration_ms: u32, worker_count: Option<u32>) -> Result<String> {
+  let executor = OptimizedMultiCoreExecutor::new(
+    Some(ExecutorConfig { worker_count, ..ExecutorConfig::default() })
+  )?;
+  let hint_sets = soak_hints();
+
+  let start = Instant::now();
+  let deadline = start + Duration::from_millis(duration_ms as u64);
+  let mut tasks_completed = 0u64;
+  let mut task_errors = 0u64;
+  let mut samples = Vec::new();
+  let mut next_sample = start;
+
+  // One warm-up round so the baseline includes steady-state caches and threads
+  tasks_completed += run_round(&executor, &hint_sets, &mut task_errors)?;
+  let baseline = take_sample(&executor, start, tasks_completed);
+
+  while Instant::now() < deadline {
+    tasks_completed += run_round(&executor, &hint_sets, &mut task_errors)?;
+
+    if Instant::now() >= next_sample {
+      samples.push(take_sample(&executor, start, tasks_completed));
+      next_sample += SAMPLE_INTERVAL;
+    }
+  }
+
+  let final_sample = take_sample(&executor, start, tasks_completed);
+  executor.shutdown()?;
+
+  let failures = detect_leaks(&baseline, &final_sample, executor.worker_count() as u64);
+  let report = SoakReport {
+    passed: failures.is_empty(),
+    failures,
+    duration_ms: start.elapsed().as_millis() as u64,
+    tasks_completed,
+    task_errors,
+    peak_rss_kb: samples.iter().filter_map(|s| s.rss_kb).max(),
+    baseline,
+    final_sample,
+  };
+
+  let report_json = serde_json
+    ::to_string(&report)
+    .map_err(|e| Error::from_reason(e.to_string()))?;
+
+  if report.passed {
+    Ok(report_json)
+  } else {
+    Err(Error::from_reason(format!("Soak test detected unbounded growth: {}", report_json)))
+  }
+}
+
+fn soak_hints() -> Vec<OptimizationHints> {
+  SOAK_FUNCTIONS.iter()
+    .enumerate()
+    .map(|(i, (_, _, operation_type))| OptimizationHints {
+      operation_type: operation_type.to_string(),
+      complexity: "low".to_string(),
+      expected_cores: 1,
+      should_use_rust: true,
+      is_hot_function: true,
+      estimated_memory: 0,
+      batch_size_hint: None,
+      function_hash: format!("soak_fn_{}", i),
+      include_receipt: None,
+      deterministic: None,
+      output_precision: None,
+      rounding: None,
+      locale: None,
+      canonical_json: None,
+      priority: None,
+      typed_result: None,
+      group: None,
+      after: None,
+      chunk_size: None,
+      tenant: None,
+      aggregate: None,
+      max_queue_age_ms: None,
+      dedupe: None,
+      child: None,
+      idempotency_key: None,
+    })
+    .collect()
+}
+
+/// Submit one batch per soak function and drain all results
+fn run_round(
+  executor: &OptimizedMultiCoreExecutor,
+  hint_sets: &[OptimizationHints],
+  task_errors: &mut u64
+) -> Result<u64> {
+  let mut submitted = Vec::new();
+
+  for ((function_code, data, _), hints) in SOAK_FUNCTIONS.iter().zip(hint_sets) {
+    let tasks = vec![(function_code.to_string(), data.to_string()); SOAK_BATCH_SIZE];
+    submitted.extend(
+      executor.submit_optimized_batch(tasks, hints.clone(), None, None, None, None)?
+    );
+  }
+
+  let deadline = Instant::now() + ROUND_TIMEOUT;
+  for task_id in &submitted {
+    let remaining = deadline.saturating_duration_since(Instant::now()).as_millis() as u32;
+    if executor.get_result_by_id(task_id.clone(), Some(remaining))?.error.is_some() {
+      *task_errors += 1;
+    }
+  }
+
+  Ok(submitted.len() as u64)
+}
+
+fn take_sample(
+  executor: &OptimizedMultiCoreExecutor,
+  start: Instant,
+  tasks_completed: u64
+) -> SoakSample {
+  SoakSample {
+    elapsed_ms: start.elapsed().as_millis() as u64,
+    rss_kb: read_proc_status_field("VmRSS"),
+    threads: read_proc_status_field("Threads"),
+    performance_cache_entries: executor.performance_cache
+      .lock()
+      .map(|c| c.len())
+      .unwrap_or(0),
+    hot_function_entries: executor.hot_functions
+      .lock()
+      .map(|h| h.len())
+      .unwrap_or(0),
+    tasks_completed,
+  }
+}
+
+fn detect_leaks(baseline: &SoakSample, last: &SoakSample, worker_count: u64) -> Vec<String> {
+  let mut failures = Vec::new();
+
+  if let (Some(before), Some(after)) = (baseline.rss_kb, last.rss_kb) {
+    let allowed = RSS_GROWTH_LIMIT_KB.max(((before as f64) * RSS_GROWTH_LIMIT_RATIO) as u64);
+    if after.saturating_sub(before) > allowed {
+      failures.push(format!("RSS grew from {} KB to {} KB", before, after));
+    }
+  }
+
+  // Node may start helper threads (libuv, GC) mid-run, so allow one pool's worth of slack
+  if
+    let (Some(before), Some(after)) = (baseline.threads, last.threads) &&
+    after > before + worker_count
+  {
+    failures.push(format!("Thread count grew from {} to {}", before, after));
+  }
+
+  if last.performance_cache_entries > SOAK_FUNCTIONS.len() {
+    failures.push(
+      format!(
+        "Performance cache holds {} entries for {} functions",
+        last.performance_cache_entries,
+        SOAK_FUNCTIONS.len()
+      )
+    );
+  }
+
+  if last.hot_function_entries > SOAK_FUNCTIONS.len() {
+    failures.push(
+      format!(
+        "Hot function table holds {} entries for {} functions",
+        last.hot_function_entries,
+        SOAK_FUNCTIONS.len()
+      )
+    );
+  }
+
+  failures
+}
+
+/// Read a numeric field (e.g. `VmRSS`, `Threads`) from /proc/self/status
+#[cfg(target_os = "linux")]
+fn read_proc_status_field(field: &str) -> Option<u64> {
+  let status = std::fs::read_to_string("/proc/self/status").ok()?;
+  status
+    .lines()
+    .find_map(|line| line.strip_prefix(field)?.strip_prefix(':'))
+    .and_then(|value| value.split_whitespace().next())
+    .and_then(|value| value.parse().ok())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_proc_status_field(_field: &str) -> Option<u64> {
+  None
+}
@@ -0,0 +1,52 @@
+// src/starvation.rs - Warnings for tasks left waiting in a queue
+use std::time::{ Duration, Instant };
+
+use crate::clock::SharedClock;
+use crate::events::EventLog;
+use crate::sync::{ Arc, Mutex, lock_recovering };
+use crate::task_status::{ QueueId, TaskTracker };
+
+/// Reports tasks queued longer than `starvation_warning_ms`.
+///
+/// Workers between tasks and submitters check at most four times per
+/// threshold, so a task is reported within a quarter of the threshold of
+/// crossing it, unless every worker is busy and nothing is being submitted.
+/// Each task is reported once per time it's queued, with a `task_starved`
+/// event. Fire-and-forget tasks aren't tracked, so they're never reported.
+pub struct StarvationMonitor {
+  threshold: Duration,
+  tasks: Arc<TaskTracker>,
+  clock: SharedClock,
+  next_check: Mutex<Instant>,
+}
+
+impl StarvationMonitor {
+  pub fn new(threshold_ms: u32, tasks: Arc<TaskTracker>, clock: SharedClock) -> Self {
+    let threshold = Duration::from_millis(threshold_ms as u64);
+    let next_check = Mutex::new(clock.now() + threshold / 4);
+    Self { threshold, tasks, clock, next_check }
+  }
+
+  /// Report newly starved tasks if a check is due
+  pub fn check(&self, events: &EventLog, worker_id: Option<usize>) {
+    let now = self.clock.now();
+    {
+      let mut next_check = lock_recovering(&self.next_check, || {});
+      if now < *next_check {
+        return;
+      }
+      *next_check = now + (self.threshold / 4).max(Duration::from_millis(1));
+    }
+    for (task_id, queue, waited) in self.tasks.starved(self.threshold) {
+      let queue = match queue {
+        QueueId::Shared(priority) => format!("the {} lane", priority.as_str()),
+        QueueId::Reserved(id) => format!("reservation {}", id),
+      };
+      events.emit(
+        "task_starved",
+        worker_id,
+        format!("Task {} has waited {}ms in {}", task_id, waited.as_millis(), queue)
+      );
+    }
+  }
+}
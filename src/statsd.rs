@@ -0,0 +1,169 @@
+// src/statsd.rs - Pushes executor metrics to a statsd/DogStatsD agent over UDP
+use crossbeam::channel;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::{ SocketAddr, ToSocketAddrs, UdpSocket };
+use std::thread;
+use std::time::Duration;
+
+use crate::bindings::napi;
+
+const DEFAULT_HOST: &str = "127.0.0.1";
+const DEFAULT_PORT: u16 = 8125;
+const DEFAULT_PREFIX: &str = "threader";
+const DEFAULT_INTERVAL_MS: u32 = 10_000;
+const MIN_INTERVAL_MS: u32 = 100;
+// Keeps each datagram within a typical MTU so the agent never gets fragments
+const MAX_PACKET_BYTES: usize = 1432;
+
+/// Where and how often to push metrics
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct StatsdConfig {
+  /// Defaults to "127.0.0.1"
+  pub host: Option<String>,
+  /// Defaults to 8125
+  pub port: Option<u32>,
+  /// Prepended to every metric name with a dot; defaults to "threader"
+  pub prefix: Option<String>,
+  /// Push interval; defaults to 10s
+  pub interval_ms: Option<u32>,
+  /// DogStatsD tags such as "env:prod", attached to every metric
+  pub tags: Option<Vec<String>>,
+}
+
+/// One reading taken at each push
+#[derive(Debug, Clone, Copy)]
+pub enum Metric {
+  /// Running total; the reporter pushes the increase since the last push
+  Counter(&'static str, u64),
+  Gauge(&'static str, f64),
+  /// Duration in milliseconds
+  Timer(&'static str, f64),
+}
+
+/// Background thread pushing `Metric`s to statsd until dropped.
+///
+/// Delivery is best effort, as usual for statsd: send errors are logged and
+/// the next push carries on.
+pub struct StatsdReporter {
+  // Dropping this wakes the thread and stops it
+  stop: Option<channel::Sender<()>>,
+  handle: Option<thread::JoinHandle<()>>,
+}
+
+impl StatsdReporter {
+  /// Resolve the agent address and start pushing whatever `sample` returns
+  pub fn start<F>(config: StatsdConfig, sample: F) -> Result<Self, String>
+    where F: Fn() -> Vec<Metric> + Send + 'static
+  {
+    let interval_ms = config.interval_ms.unwrap_or(DEFAULT_INTERVAL_MS);
+    if interval_ms < MIN_INTERVAL_MS {
+      return Err(
+        format!("interval_ms must be at least {}, got {}", MIN_INTERVAL_MS, interval_ms)
+      );
+    }
+    let port = match config.port {
+      Some(port) => u16::try_from(port).map_err(|_| format!("Invalid statsd port {}", port))?,
+      None => DEFAULT_PORT,
+    };
+    let host = config.host.as_deref().unwrap_or(DEFAULT_HOST);
+    let address = (host, port)
+      .to_socket_addrs()
+      .map_err(|e| format!("Cannot resolve statsd host '{}': {}", host, e))?
+      .next()
+      .ok_or_else(|| format!("Cannot resolve statsd host '{}'", host))?;
+    let socket = Self::connect(address).map_err(|e|
+      format!("Cannot open statsd socket to {}: {}", address, e)
+    )?;
+
+    let mut formatter = Formatter {
+      prefix: config.prefix.unwrap_or_else(|| DEFAULT_PREFIX.to_string()),
+      tags: config.tags.filter(|tags| !tags.is_empty()).map(|tags| tags.join(",")),
+      last_counters: HashMap::new(),
+    };
+    let interval = Duration::from_millis(interval_ms as u64);
+    let (stop, stopped) = channel::bounded::<()>(0);
+
+    let handle = thread::spawn(move || {
+      // Disconnects when the reporter is dropped
+      while let Err(channel::RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+        for packet in formatter.packets(&sample()) {
+          if let Err(e) = socket.send(packet.as_bytes()) {
+            tracing::debug!("statsd push to {} failed: {}", address, e);
+          }
+        }
+      }
+    });
+
+    Ok(Self {
+      stop: Some(stop),
+      handle: Some(handle),
+    })
+  }
+
+  fn connect(address: SocketAddr) -> std::io::Result<UdpSocket> {
+    let local: SocketAddr = if address.is_ipv4() {
+      ([0, 0, 0, 0], 0).into()
+    } else {
+      ([0u16; 8], 0).into()
+    };
+    let socket = UdpSocket::bind(local)?;
+    socket.connect(address)?;
+    Ok(socket)
+  }
+}
+
+impl Drop for StatsdReporter {
+  fn drop(&mut self) {
+    drop(self.stop.take());
+    if let Some(handle) = self.handle.take() {
+      let _ = handle.join();
+    }
+  }
+}
+
+struct Formatter {
+  prefix: String,
+  // Pre-joined DogStatsD tag list
+  tags: Option<String>,
+  last_counters: HashMap<&'static str, u64>,
+}
+
+impl Formatter {
+  /// Render metrics as newline-separated statsd lines, split into packets
+  fn packets(&mut self, metrics: &[Metric]) -> Vec<String> {
+    let mut packets = Vec::new();
+    let mut packet = String::new();
+
+    for metric in metrics {
+      let mut line = String::new();
+      let _ = match *metric {
+        Metric::Counter(name, total) => {
+          let last = self.last_counters.insert(name, total).unwrap_or(0);
+          // A counter that went backwards was reset; count from zero again
+          let delta = total.checked_sub(last).unwrap_or(total);
+          write!(line, "{}.{}:{}|c", self.prefix, name, delta)
+        }
+        Metric::Gauge(name, value) => write!(line, "{}.{}:{}|g", self.prefix, name, value),
+        Metric::Timer(name, ms) => write!(line, "{}.{}:{}|ms", self.prefix, name, ms),
+      };
+      if let Some(tags) = &self.tags {
+        let _ = write!(line, "|#{}", tags);
+      }
+
+      if !packet.is_empty() && packet.len() + 1 + line.len() > MAX_PACKET_BYTES {
+        packets.push(std::mem::take(&mut packet));
+      }
+      if !packet.is_empty() {
+        packet.push('\n');
+      }
+      packet.push_str(&line);
+    }
+
+    if !packet.is_empty() {
+      packets.push(packet);
+    }
+    packets
+  }
+}
@@ -0,0 +1,93 @@
+// src/stream_batch.rs - Streams batch results to a JS callback as they complete
+use napi::threadsafe_function::{
+  ErrorStrategy,
+  ThreadSafeCallContext,
+  ThreadsafeFunction,
+  ThreadsafeFunctionCallMode,
+};
+use napi::{ Env, JsFunction, Result, Status, Task };
+use std::time::{ Duration, Instant };
+
+use crate::OptimizedWorkerResult;
+use crate::async_context;
+use crate::result_router::{ RouterError, Subscription };
+
+// Results handed to JS but not yet processed; a full queue makes the stream wait
+const CALLBACK_QUEUE_SIZE: usize = 256;
+
+/// Forwards each result to `callback(err, result)` in completion order, off the
+/// JS thread, and resolves with the number of results streamed. The callback
+/// runs in the async context of the call that started the stream.
+///
+/// Only results JS hasn't processed yet are held in memory, so a 100k-task
+/// batch never has to be materialized as a single array.
+pub struct StreamBatch {
+  results: Subscription,
+  expected: usize,
+  timeout: Option<Duration>,
+  callback: ThreadsafeFunction<OptimizedWorkerResult, ErrorStrategy::CalleeHandled>,
+}
+
+impl StreamBatch {
+  pub fn new(
+    env: &Env,
+    callback: JsFunction,
+    results: Subscription,
+    expected: usize,
+    timeout_ms: Option<u32>
+  ) -> Result<Self> {
+    let callback = async_context::bind(env, callback, "threader_stream_batch")?;
+    let callback = callback.create_threadsafe_function(
+      CALLBACK_QUEUE_SIZE,
+      |ctx: ThreadSafeCallContext<OptimizedWorkerResult>| Ok(vec![ctx.value])
+    )?;
+
+    Ok(Self {
+      results,
+      expected,
+      timeout: timeout_ms.map(|t| Duration::from_millis(t as u64)),
+      callback,
+    })
+  }
+}
+
+impl Task for StreamBatch {
+  type Output = u32;
+  type JsValue = u32;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let deadline = self.timeout.map(|t| Instant::now() + t);
+
+    for streamed in 0..self.expected {
+      let result = match self.results.recv(deadline) {
+        Ok(result) => result,
+        Err(RouterError::Timeout) => {
+          return Err(
+            napi::Error::from_reason(
+              format!(
+                "Timed out waiting for result ({} of {} streamed)",
+                streamed,
+                self.expected
+              )
+            )
+          );
+        }
+        Err(RouterError::Closed) => {
+          return Err(RouterError::Closed.into_error());
+        }
+      };
+
+      // Blocking mode applies backpressure once JS falls behind
+      let status = self.callback.call(Ok(result), ThreadsafeFunctionCallMode::Blocking);
+      if status != Status::Ok {
+        return Err(napi::Error::new(status, "Failed to call stream callback".to_string()));
+      }
+    }
+
+    Ok(self.expected as u32)
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
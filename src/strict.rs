@@ -0,0 +1,285 @@
+// src/strict.rs - Strict mode: functions are recognized exactly or rejected
+//
+// Outside strict mode the native paths recognize functions by substring, so
+// `x => x * 20` runs as `x * 2`, and the async pool returns its input unchanged
+// for anything it doesn't know. Strict mode only accepts functions that match
+// one of the forms below exactly, for an input of the right shape, and runs
+// them through the fast paths in canonical form.
+use std::sync::atomic::{ AtomicBool, Ordering };
+
+use crate::bindings::napi;
+
+static DEFAULT_STRICT: AtomicBool = AtomicBool::new(false);
+
+/// Strict mode for executors and pools that don't choose for themselves
+pub fn set_default(enabled: bool) {
+  DEFAULT_STRICT.store(enabled, Ordering::Relaxed);
+}
+
+pub fn default_enabled() -> bool {
+  DEFAULT_STRICT.load(Ordering::Relaxed)
+}
+
+/// Make strict mode the default for executors created afterwards (unless their
+/// config sets `strict`) and for the `executeAllSettled`/`race`/`executeAny` pool
+#[napi]
+pub fn set_strict_default(enabled: bool) {
+  set_default(enabled);
+}
+
+/// Input a recognized function is defined for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+  Number,
+  /// `{ a, b }` with numeric fields
+  NumberPair,
+  /// `{ a, b }` with string fields
+  StringPair,
+  String,
+  Array,
+  NumberArray,
+  /// `Math.max(...[])` is -Infinity, which the fast path can't return as JSON
+  NonEmptyNumberArray,
+  StringArray,
+}
+
+impl Shape {
+  fn accepts(self, data: &serde_json::Value) -> bool {
+    let pair = |check: fn(&serde_json::Value) -> bool| {
+      data
+        .as_object()
+        .is_some_and(|obj| obj.get("a").is_some_and(check) && obj.get("b").is_some_and(check))
+    };
+    let array = |check: fn(&serde_json::Value) -> bool| {
+      data.as_array().is_some_and(|arr| arr.iter().all(check))
+    };
+
+    match self {
+      Shape::Number => data.is_number(),
+      Shape::NumberPair => pair(serde_json::Value::is_number),
+      Shape::StringPair => pair(serde_json::Value::is_string),
+      Shape::String => data.is_string(),
+      Shape::Array => data.is_array(),
+      Shape::NumberArray => array(serde_json::Value::is_number),
+      Shape::NonEmptyNumberArray =>
+        array(serde_json::Value::is_number) && data.as_array().is_some_and(|arr| !arr.is_empty()),
+      Shape::StringArray => array(serde_json::Value::is_string),
+    }
+  }
+}
+
+/// A recognized function: parameter and body patterns, the canonical form the
+/// fast paths run, and the input it is defined for. In patterns `$x`, `$a` and
+/// `$b` match any identifier, the same one wherever the name repeats; everything
+/// else, including case, must match exactly once whitespace is removed.
+struct Form {
+  param: &'static str,
+  body: &'static str,
+  canonical: &'static str,
+  shape: Shape,
+}
+
+const fn form(
+  param: &'static str,
+  body: &'static str,
+  canonical: &'static str,
+  shape: Shape
+) -> Form {
+  Form { param, body, canonical, shape }
+}
+
+const FORMS: &[Form] = &[
+  form("$x", "$x*2", "x => x * 2", Shape::Number),
+  form("$x", "2*$x", "x => x * 2", Shape::Number),
+  form("$x", "$x+5", "x => x + 5", Shape::Number),
+  form("$x", "5+$x", "x => x + 5", Shape::Number),
+  form("$x", "$x+10", "x => x + 10", Shape::Number),
+  form("$x", "10+$x", "x => x + 10", Shape::Number),
+  form("$x", "$x*$x", "x => x * x", Shape::Number),
+  form("$x", "$x**2", "x => x * x", Shape::Number),
+  form("$x", "Math.sin($x)", "x => Math.sin(x)", Shape::Number),
+  form("$x", "Math.cos($x)", "x => Math.cos(x)", Shape::Number),
+  form("$x", "Math.sqrt($x)", "x => Math.sqrt(x)", Shape::Number),
+  form("{a,b}", "a*b", "({ a, b }) => a * b", Shape::NumberPair),
+  form("{a,b}", "b*a", "({ a, b }) => a * b", Shape::NumberPair),
+  form("$x", "$x.a*$x.b", "({ a, b }) => a * b", Shape::NumberPair),
+  form("{a,b}", "a+b", "({ a, b }) => a + b", Shape::NumberPair),
+  form("{a,b}", "b+a", "({ a, b }) => a + b", Shape::NumberPair),
+  form("$x", "$x.a+$x.b", "({ a, b }) => a + b", Shape::NumberPair),
+  form("{a,b}", "a-b", "({ a, b }) => a - b", Shape::NumberPair),
+  form("$x", "$x.a-$x.b", "({ a, b }) => a - b", Shape::NumberPair),
+  form("{a,b}", "a/b", "({ a, b }) => a / b", Shape::NumberPair),
+  form("$x", "$x.a/$x.b", "({ a, b }) => a / b", Shape::NumberPair),
+  form("{a,b}", "a.localeCompare(b)", "({ a, b }) => a.localeCompare(b)", Shape::StringPair),
+  form("$x", "$x.toUpperCase()", "s => s.toUpperCase()", Shape::String),
+  form("$x", "$x.toLowerCase()", "s => s.toLowerCase()", Shape::String),
+  form("$x", "$x.toLocaleUpperCase()", "s => s.toLocaleUpperCase()", Shape::String),
+  form("$x", "$x.toLocaleLowerCase()", "s => s.toLocaleLowerCase()", Shape::String),
+  form("$x", "$x.trim()", "s => s.trim()", Shape::String),
+  // By code point; `split('')` splits UTF-16 code units, which the fast path doesn't
+  form("$x", "[...$x].reverse().join('')", "s => [...s].reverse().join('')", Shape::String),
+  form("$x", "Array.from($x).reverse().join('')", "s => [...s].reverse().join('')", Shape::String),
+  form("$x", "$x.length", "xs => xs.length", Shape::Array),
+  form("$x", "$x.sort()", "xs => xs.sort()", Shape::StringArray),
+  form(
+    "$x",
+    "$x.sort(($a,$b)=>$a.localeCompare($b))",
+    "xs => xs.sort((a, b) => a.localeCompare(b))",
+    Shape::StringArray
+  ),
+  form(
+    "$x",
+    "$x.reduce(($a,$b)=>$a+$b,0)",
+    "xs => xs.reduce((sum, x) => sum + x, 0)",
+    Shape::NumberArray
+  ),
+  form("$x", "Math.max(...$x)", "xs => Math.max(...xs)", Shape::NonEmptyNumberArray),
+  form("$x", "Math.min(...$x)", "xs => Math.min(...xs)", Shape::NonEmptyNumberArray),
+  form("$x", "new TextEncoder().encode($x)", "s => new TextEncoder().encode(s)", Shape::String),
+  form("$x", "Buffer.from($x)", "s => Buffer.from(s)", Shape::String),
+  form("$x", "Buffer.from($x,'base64')", "s => Buffer.from(s, 'base64')", Shape::String),
+  form("$x", "Buffer.from($x,'hex')", "s => Buffer.from(s, 'hex')", Shape::String),
+  form("$x", "Buffer.from($x)", "xs => Buffer.from(xs)", Shape::NumberArray),
+  form("$x", "Uint8Array.from($x)", "xs => Uint8Array.from(xs)", Shape::NumberArray),
+  form("$x", "new Uint8Array($x)", "xs => Uint8Array.from(xs)", Shape::NumberArray),
+];
+
+/// Canonical form of `function_code` for `data`, which the fast paths are
+/// guaranteed to interpret as written, or why strict mode rejects it
+pub fn canonical_function(
+  function_code: &str,
+  data: &serde_json::Value
+) -> Result<&'static str, String> {
+  recognize(function_code)?.canonical(data)
+}
+
+/// The forms `function_code` matches, whatever its input, or why strict mode
+/// rejects it
+pub fn recognize(function_code: &str) -> Result<Recognized, String> {
+  let code = normalize(function_code);
+  let unrecognized = || format!("Strict mode: unrecognized function '{}'", function_code.trim());
+  let (param, body) = split_function(code.trim_end_matches(';')).ok_or_else(unrecognized)?;
+
+  let forms: Vec<&'static Form> = FORMS.iter()
+    .filter(|form| {
+      let mut bindings = Vec::new();
+      matches(form.param, param, &mut bindings) && matches(form.body, body, &mut bindings)
+    })
+    .collect();
+  if forms.is_empty() {
+    return Err(unrecognized());
+  }
+  Ok(Recognized { forms })
+}
+
+/// A function strict mode recognized, kept so its code needn't be parsed again
+/// for each input
+#[derive(Clone)]
+pub struct Recognized {
+  // Never empty
+  forms: Vec<&'static Form>,
+}
+
+impl std::fmt::Debug for Recognized {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_list().entries(self.forms.iter().map(|form| form.canonical)).finish()
+  }
+}
+
+impl Recognized {
+  /// Canonical form for `data`, or why it doesn't apply
+  pub fn canonical(&self, data: &serde_json::Value) -> Result<&'static str, String> {
+    self.forms
+      .iter()
+      .find(|form| form.shape.accepts(data))
+      .map(|form| form.canonical)
+      .ok_or_else(|| {
+        format!("Strict mode: '{}' does not apply to this input", self.forms[0].canonical)
+      })
+  }
+}
+
+fn is_identifier(c: char) -> bool {
+  c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Drop whitespace, keeping one space where it separates identifiers (`return x`)
+fn normalize(code: &str) -> String {
+  let mut normalized = String::with_capacity(code.len());
+  let mut after_space = false;
+  for c in code.trim().chars() {
+    if c.is_whitespace() {
+      after_space = true;
+      continue;
+    }
+    if after_space && is_identifier(c) && normalized.ends_with(is_identifier) {
+      normalized.push(' ');
+    }
+    after_space = false;
+    normalized.push(c);
+  }
+  normalized
+}
+
+/// Parameter and returned expression of `p => expr`, `(p) => expr`,
+/// `p => { return expr }` or `function name(p) { return expr }`
+fn split_function(code: &str) -> Option<(&str, &str)> {
+  if let Some(rest) = code.strip_prefix("function") {
+    let open = rest.find('(')?;
+    if !rest[..open].trim_start().chars().all(is_identifier) {
+      return None;
+    }
+    let close = open + rest[open..].find(')')?;
+    let block = rest[close + 1..].strip_prefix('{')?.strip_suffix('}')?;
+    return Some((&rest[open + 1..close], returned(block)?));
+  }
+
+  let (head, body) = code.split_once("=>")?;
+  let param = head
+    .strip_prefix('(')
+    .and_then(|head| head.strip_suffix(')'))
+    .unwrap_or(head);
+  let body = match body.strip_prefix('{') {
+    Some(block) => returned(block.strip_suffix('}')?)?,
+    None => body,
+  };
+  Some((param, body))
+}
+
+/// Expression of a block that is a single `return` statement
+fn returned(block: &str) -> Option<&str> {
+  block.strip_prefix("return ").map(|expr| expr.trim_end_matches(';'))
+}
+
+/// Match all of `text` against `pattern`, binding placeholders as they appear.
+/// Different placeholders must bind different identifiers.
+fn matches<'t>(pattern: &str, text: &'t str, bindings: &mut Vec<(char, &'t str)>) -> bool {
+  let mut rest = text;
+  let mut pattern = pattern.chars();
+
+  while let Some(c) = pattern.next() {
+    if c != '$' {
+      match rest.strip_prefix(c) {
+        Some(tail) => rest = tail,
+        None => return false,
+      }
+      continue;
+    }
+
+    let Some(name) = pattern.next() else {
+      return false;
+    };
+    let (identifier, tail) = rest.split_at(rest.find(|c| !is_identifier(c)).unwrap_or(rest.len()));
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+      return false;
+    }
+    match bindings.iter().find(|(bound, _)| *bound == name) {
+      Some((_, bound)) if *bound != identifier => return false,
+      Some(_) => {}
+      None if bindings.iter().any(|(_, bound)| *bound == identifier) => return false,
+      None => bindings.push((name, identifier)),
+    }
+    rest = tail;
+  }
+  rest.is_empty()
+}
@@ -0,0 +1,55 @@
+// src/strict_tests.rs - Which functions strict mode accepts, and what it runs
+use serde_json::json;
+
+use crate::executor_config::ExecutorConfig;
+use crate::strict::canonical_function;
+use crate::worker_pool::WorkerPool;
+use crate::{ OptimizedMultiCoreExecutor, task_spec };
+
+#[test]
+fn functions_are_recognized_exactly_whatever_their_spelling() {
+  for spelling in ["x => x * 2", "(n)=>2*n", "function double(v) { return v * 2; }"] {
+    assert_eq!(canonical_function(spelling, &json!(21)), Ok("x => x * 2"), "{}", spelling);
+  }
+  // Same body, different input: the form that fits the data is chosen
+  assert_eq!(canonical_function("v => Buffer.from(v)", &json!([1])), Ok("xs => Buffer.from(xs)"));
+
+  let near_miss = canonical_function("x => x * 20", &json!(21)).unwrap_err();
+  assert_eq!(near_miss, "Strict mode: unrecognized function 'x => x * 20'");
+  // Each `$x` in a pattern must bind the same name
+  assert!(canonical_function("(x) => y * 2", &json!(21)).is_err());
+  let wrong_input = canonical_function("s => s.toUpperCase()", &json!(1)).unwrap_err();
+  assert_eq!(wrong_input, "Strict mode: 's => s.toUpperCase()' does not apply to this input");
+}
+
+#[test]
+fn strict_executors_fail_what_others_would_guess_at() {
+  let run = |strict: bool| {
+    let config = ExecutorConfig {
+      worker_count: Some(1),
+      strict: Some(strict),
+      ..ExecutorConfig::default()
+    };
+    let executor = OptimizedMultiCoreExecutor::new(Some(config)).expect("executor");
+    let hints = task_spec::resolve_hints("x => x * 2", &[]).expect("hints");
+    let task_id = executor
+      .submit_task("x => x * 20".into(), "21".into(), hints, None, None)
+      .expect("submit");
+    let result = executor.get_result_by_id(task_id, Some(5000)).expect("result");
+    executor.shutdown().unwrap();
+    (result.result, result.error)
+  };
+  // Taken for `x * 2` by substring
+  assert_eq!(run(false).0.as_deref(), Some("42"));
+  let (result, error) = run(true);
+  assert!(result.is_none());
+  assert_eq!(error.as_deref(), Some("Strict mode: unrecognized function 'x => x * 20'"));
+}
+
+#[test]
+fn strict_pool_rejects_unknown_functions_instead_of_echoing_input() {
+  let echoed = WorkerPool::execute_function("x => x - 1", &json!(5), false);
+  assert_eq!(echoed, Ok(json!(5)));
+  let rejected = WorkerPool::execute_function("x => x - 1", &json!(5), true);
+  assert_eq!(rejected, Err("Strict mode: unrecognized function 'x => x - 1'".to_string()));
+}
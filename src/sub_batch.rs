@@ -0,0 +1,148 @@
+// src/sub_batch.rs - Batches dispatched to workers a sub-batch at a time
+//
+// Queued one by one, a large batch of small tasks pays the shared queue's
+// per-task handoff and a worker's between-task checks for every task. With a
+// `batch_size_hint`, `submit_optimized_batch` cuts the batch into sub-batches
+// and sends each to one worker, which runs it in a single pass: one task after
+// another, going back to the queue only when the sub-batch is done or a
+// higher-priority task is waiting. Idle workers still steal from sub-batches
+// that haven't started. The hint only seeds the size: each function's size is
+// then tuned from the throughput its passes achieve, doubled or halved for as
+// long as that improves it, and turned around when it stops.
+use std::collections::HashMap;
+use std::sync::atomic::{ AtomicU64, AtomicUsize, Ordering };
+use std::time::{ Duration, Instant };
+
+use crate::priority::Priority;
+use crate::sync::{ Mutex, MutexGuard, lock_recovering };
+
+/// Identifies the tasks of one sub-batch
+pub type SubBatchId = u64;
+
+const MAX_SIZE: usize = 1024;
+// Passes at one size whose throughput is compared with the last size's
+const SAMPLE_PASSES: usize = 4;
+
+/// A sub-batch a worker is running in one pass
+#[derive(Debug)]
+pub struct Pass {
+  pub sub_batch: SubBatchId,
+  pub priority: Priority,
+  pub function_hash: String,
+  pub tasks: usize,
+  pub start: Instant,
+}
+
+#[derive(Debug)]
+struct Tuning {
+  size: usize,
+  growing: bool,
+  // Tasks per second at the previous size
+  last: Option<f64>,
+  tasks: usize,
+  busy: Duration,
+  passes: usize,
+}
+
+impl Tuning {
+  fn new(size: usize) -> Self {
+    Self { size, growing: true, last: None, tasks: 0, busy: Duration::ZERO, passes: 0 }
+  }
+}
+
+/// Sub-batch sizes per function, and where sub-batches go
+#[derive(Debug)]
+pub struct SubBatchTuner {
+  tunings: Mutex<HashMap<String, Tuning>>,
+  next_id: AtomicU64,
+  // Worker the next batch starts on, so small batches don't all land on the first
+  next_worker: AtomicUsize,
+}
+
+impl Default for SubBatchTuner {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl SubBatchTuner {
+  pub fn new() -> Self {
+    Self {
+      tunings: Mutex::new(HashMap::new()),
+      next_id: AtomicU64::new(0),
+      next_worker: AtomicUsize::new(0),
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, HashMap<String, Tuning>> {
+    lock_recovering(&self.tunings, || {})
+  }
+
+  /// Current sub-batch size for `function_hash`, starting from `hint`
+  pub fn size(&self, function_hash: &str, hint: u32) -> usize {
+    let hint = (hint as usize).clamp(1, MAX_SIZE);
+    self.lock()
+      .entry(function_hash.to_string())
+      .or_insert_with(|| Tuning::new(hint)).size
+  }
+
+  /// The worker and sub-batch of each of `len` tasks cut into sub-batches of
+  /// `size`, no bigger than needed to give every one of `workers` workers some
+  pub fn plan(&self, len: usize, size: usize, workers: usize) -> Vec<(usize, SubBatchId)> {
+    let workers = workers.max(1);
+    let size = size.min(len.div_ceil(workers)).max(1);
+    let batches = len.div_ceil(size);
+    let first_worker = self.next_worker.fetch_add(batches, Ordering::Relaxed);
+    let first_id = self.next_id.fetch_add(batches as u64, Ordering::Relaxed);
+    (0..len)
+      .map(|i| {
+        let batch = i / size;
+        ((first_worker + batch) % workers, first_id + (batch as u64))
+      })
+      .collect()
+  }
+
+  /// Size learned for each function, for `export_state`
+  pub fn sizes(&self) -> HashMap<String, usize> {
+    self.lock()
+      .iter()
+      .map(|(function_hash, tuning)| (function_hash.clone(), tuning.size))
+      .collect()
+  }
+
+  /// Start functions from sizes learned elsewhere, replacing what was learned here
+  pub fn seed(&self, sizes: HashMap<String, usize>) {
+    let mut tunings = self.lock();
+    for (function_hash, size) in sizes {
+      tunings.insert(function_hash, Tuning::new(size.clamp(1, MAX_SIZE)));
+    }
+  }
+
+  /// Forget what was learned; sizes start from the hints again
+  pub fn clear(&self) {
+    self.lock().clear();
+  }
+
+  /// A worker finished a pass at `now`
+  pub fn record(&self, pass: &Pass, now: Instant) {
+    let mut tunings = self.lock();
+    let Some(tuning) = tunings.get_mut(&pass.function_hash) else {
+      return;
+    };
+    tuning.tasks += pass.tasks;
+    tuning.busy += now.saturating_duration_since(pass.start);
+    tuning.passes += 1;
+    if tuning.passes < SAMPLE_PASSES || tuning.busy.is_zero() {
+      return;
+    }
+    let throughput = (tuning.tasks as f64) / tuning.busy.as_secs_f64();
+    if tuning.last.is_some_and(|last| throughput < last) {
+      tuning.growing = !tuning.growing;
+    }
+    tuning.size = if tuning.growing { tuning.size * 2 } else { tuning.size / 2 }.clamp(1, MAX_SIZE);
+    tuning.last = Some(throughput);
+    tuning.tasks = 0;
+    tuning.busy = Duration::ZERO;
+    tuning.passes = 0;
+  }
+}
@@ -0,0 +1,69 @@
+// src/sub_batch_tests.rs - Cutting batches into sub-batches and tuning their size
+use std::time::{ Duration, Instant };
+
+use crate::priority::Priority;
+use crate::sub_batch::{ Pass, SubBatchTuner };
+
+// Record `passes` passes of `tasks` tasks, each taking `each`
+fn record(tuner: &SubBatchTuner, passes: usize, tasks: usize, each: Duration) {
+  let start = Instant::now();
+  for _ in 0..passes {
+    let pass = Pass {
+      sub_batch: 0,
+      priority: Priority::Normal,
+      function_hash: "double".to_string(),
+      tasks,
+      start,
+    };
+    tuner.record(&pass, start + each);
+  }
+}
+
+#[test]
+fn sizes_start_from_the_hint_within_bounds() {
+  let tuner = SubBatchTuner::new();
+  assert_eq!(tuner.size("double", 16), 16);
+  // Only the first hint seeds a function's size
+  assert_eq!(tuner.size("double", 64), 16);
+  assert_eq!(tuner.size("tiny", 0), 1);
+  assert_eq!(tuner.size("huge", 1_000_000), 1024);
+
+  tuner.seed([("double".to_string(), 128)].into());
+  assert_eq!(tuner.sizes()["double"], 128);
+  tuner.clear();
+  assert_eq!(tuner.size("double", 8), 8);
+}
+
+#[test]
+fn plan_gives_every_worker_some_and_rotates_the_first() {
+  let tuner = SubBatchTuner::new();
+  // Sub-batches of 4 would leave worker 2 nothing, so they shrink to 3
+  let plan = tuner.plan(8, 4, 3);
+  let workers: Vec<usize> = plan.iter().map(|(worker, _)| *worker).collect();
+  assert_eq!(workers, [0, 0, 0, 1, 1, 1, 2, 2]);
+  let ids: Vec<u64> = plan.iter().map(|(_, sub_batch)| *sub_batch).collect();
+  assert_eq!(ids, [0, 0, 0, 1, 1, 1, 2, 2]);
+
+  // The next batch starts where this one left off, with new sub-batch IDs
+  assert_eq!(tuner.plan(2, 4, 3), [(0, 3), (1, 4)]);
+  assert_eq!(tuner.plan(1, 4, 3), [(2, 5)]);
+}
+
+#[test]
+fn size_follows_throughput() {
+  let tuner = SubBatchTuner::new();
+  tuner.size("double", 8);
+
+  // Nothing changes until a full sample of passes is in
+  record(&tuner, 3, 8, Duration::from_millis(8));
+  assert_eq!(tuner.size("double", 8), 8);
+  record(&tuner, 1, 8, Duration::from_millis(8));
+  assert_eq!(tuner.size("double", 8), 16);
+
+  // Faster at 16, so it keeps growing
+  record(&tuner, 4, 16, Duration::from_millis(8));
+  assert_eq!(tuner.size("double", 8), 32);
+  // Slower at 32, so it turns around
+  record(&tuner, 4, 32, Duration::from_millis(64));
+  assert_eq!(tuner.size("double", 8), 16);
+}
@@ -0,0 +1,122 @@
+// src/supervisor.rs - Respawns worker threads that die
+//
+// A worker thread that panics outside a task's error handling, or stops after
+// an injected crash, would otherwise leave its slot empty until the pool is
+// resized, losing that capacity for good. Every worker beats its heartbeat
+// each time it looks for work; one whose thread ends without retiring or
+// shutting down flatlines it instead, leaving its end of the queue and the
+// cause behind. The supervisor looks for flatlined heartbeats every interval
+// and starts a new worker on each of their slots.
+use crossbeam::channel;
+use std::any::Any;
+use std::sync::atomic::{ AtomicU64, Ordering };
+use std::thread;
+use std::time::{ Duration, Instant };
+
+use crate::clock::SharedClock;
+use crate::priority::WorkerLanes;
+use crate::resize::Resizer;
+use crate::sync::{ Arc, Mutex, lock_recovering };
+
+pub const DEFAULT_INTERVAL_MS: u32 = 100;
+
+/// Each worker slot's heartbeat
+pub struct Heartbeats {
+  clock: SharedClock,
+  epoch: Instant,
+  // When each slot's worker last looked for work, in nanoseconds since `epoch`
+  beats: Vec<AtomicU64>,
+  // Workers that died since the supervisor last looked
+  flatlined: Mutex<Vec<Flatline>>,
+}
+
+/// A dead worker's end of the queue, and why it died
+#[derive(Debug)]
+pub struct Flatline {
+  pub lanes: WorkerLanes,
+  pub cause: String,
+  /// From the worker's last heartbeat until it died
+  pub silent_for: Duration,
+}
+
+impl Heartbeats {
+  pub fn new(slots: usize, clock: SharedClock) -> Self {
+    let epoch = clock.now();
+    Self {
+      clock,
+      epoch,
+      beats: (0..slots).map(|_| AtomicU64::new(0)).collect(),
+      flatlined: Mutex::new(Vec::new()),
+    }
+  }
+
+  fn nanos_now(&self) -> u64 {
+    self.clock.now().saturating_duration_since(self.epoch).as_nanos() as u64
+  }
+
+  pub fn beat(&self, worker: usize) {
+    self.beats[worker].store(self.nanos_now(), Ordering::Relaxed);
+  }
+
+  /// Record that the worker holding `lanes` died of `cause`, for the
+  /// supervisor to start another in its place
+  pub fn flatline(&self, lanes: WorkerLanes, cause: String) {
+    let last_beat = self.beats[lanes.worker()].load(Ordering::Relaxed);
+    let silent_for = Duration::from_nanos(self.nanos_now().saturating_sub(last_beat));
+    lock_recovering(&self.flatlined, || {}).push(Flatline { lanes, cause, silent_for });
+  }
+
+  fn take_flatlined(&self) -> Vec<Flatline> {
+    std::mem::take(&mut *lock_recovering(&self.flatlined, || {}))
+  }
+}
+
+/// What a caught panic says about itself, as "panicked: <message>"
+pub fn panic_cause(payload: &(dyn Any + Send)) -> String {
+  let message = payload
+    .downcast_ref::<&str>()
+    .map(|message| message.to_string())
+    .or_else(|| payload.downcast_ref::<String>().cloned())
+    .unwrap_or_else(|| "no message".to_string());
+  format!("panicked: {}", message)
+}
+
+/// Thread respawning dead workers until dropped
+pub struct Supervisor {
+  // Dropping this wakes the thread and stops it
+  stop: Option<channel::Sender<()>>,
+  handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Supervisor {
+  /// Every `interval`, respawn the workers flatlined in `heartbeats` with `resizer`
+  pub fn start(interval: Duration, heartbeats: Arc<Heartbeats>, resizer: Resizer) -> Self {
+    let (stop, stopped) = channel::bounded::<()>(0);
+
+    let handle = thread::spawn(move || {
+      // Disconnects when the supervisor is dropped
+      while let Err(channel::RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+        for flatline in heartbeats.take_flatlined() {
+          // Fails only once the pool is shut down
+          if resizer.respawn(flatline).is_err() {
+            return;
+          }
+        }
+      }
+    });
+
+    Self {
+      stop: Some(stop),
+      handle: Some(handle),
+    }
+  }
+}
+
+impl Drop for Supervisor {
+  fn drop(&mut self) {
+    drop(self.stop.take());
+    if let Some(handle) = self.handle.take() {
+      let _ = handle.join();
+    }
+  }
+}
@@ -0,0 +1,45 @@
+// src/sync.rs - Synchronization primitives, swappable for model checking
+//
+// Executor code imports `Arc`, `Mutex`, `atomic` and `thread` from here instead
+// of `std`. Building with `--features loom` swaps them for loom's instrumented
+// versions, so scheduler and shutdown logic can be exercised inside
+// `loom::model` to explore every interleaving (see the models in `priority`).
+// crossbeam channels and deques are not instrumented; model checks should
+// drive the executor's locks, atomics and the workers' bells rather than the
+// queues themselves.
+
+#[cfg(not(feature = "loom"))]
+pub use std::sync::{ Arc, Condvar, Mutex, MutexGuard, atomic };
+
+#[cfg(not(feature = "loom"))]
+pub use std::thread;
+
+#[cfg(feature = "loom")]
+pub use loom::sync::{ Arc, Condvar, Mutex, MutexGuard, atomic };
+
+#[cfg(feature = "loom")]
+pub mod thread {
+  pub use loom::thread::*;
+
+  /// Time doesn't pass inside a model, so a sleep just lets other threads run
+  pub fn sleep(_duration: std::time::Duration) {
+    yield_now();
+  }
+}
+
+/// Lock a mutex, recovering its data if a previous holder panicked.
+///
+/// `on_poison` runs once per poisoning so callers can report it; the poison
+/// flag is cleared afterwards so later lockers see a healthy mutex.
+pub fn lock_recovering<'a, T>(mutex: &'a Mutex<T>, on_poison: impl FnOnce()) -> MutexGuard<'a, T> {
+  match mutex.lock() {
+    Ok(guard) => guard,
+    Err(poisoned) => {
+      on_poison();
+      let guard = poisoned.into_inner();
+      #[cfg(not(feature = "loom"))]
+      mutex.clear_poison();
+      guard
+    }
+  }
+}
@@ -0,0 +1,150 @@
+// src/task_api.rs - JS entry points for `TaskExecutor`'s combinators
+use napi_derive::napi;
+use std::sync::{ Arc, OnceLock };
+
+use crate::task_executor::{ SettledResult, Task, TaskExecutor, TaskExecutorError };
+use crate::worker_pool::WorkerPool;
+
+/// A task for the `TaskExecutor` entry points
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct ExecutorTask {
+  pub id: String,
+  pub function_string: String,
+  pub data: serde_json::Value,
+  /// Per-task timeout; defaults to the executor's 30s
+  pub timeout_ms: Option<u32>,
+}
+
+impl From<ExecutorTask> for Task {
+  fn from(task: ExecutorTask) -> Self {
+    Task {
+      id: task.id,
+      function_string: task.function_string,
+      data: task.data,
+      timeout: task.timeout_ms.map(u64::from),
+    }
+  }
+}
+
+/// Winner of `race` or `executeAny`
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct RaceWinner {
+  pub id: String,
+  pub value: serde_json::Value,
+  pub duration_ms: f64,
+}
+
+/// Error object for a task that produced no value
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct TaskErrorInfo {
+  /// "execution", "timeout" or "shutdown"
+  pub kind: String,
+  pub message: String,
+}
+
+impl From<&TaskExecutorError> for TaskErrorInfo {
+  fn from(error: &TaskExecutorError) -> Self {
+    TaskErrorInfo {
+      kind: error.kind().to_string(),
+      message: error.to_string(),
+    }
+  }
+}
+
+/// One entry of `executeAllSettled`, shaped like a `Promise.allSettled` result
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct SettledTaskResult {
+  pub id: String,
+  /// "fulfilled" or "rejected"
+  pub status: String,
+  pub value: Option<serde_json::Value>,
+  pub reason: Option<TaskErrorInfo>,
+  pub duration_ms: f64,
+}
+
+impl From<SettledResult> for SettledTaskResult {
+  fn from(settled: SettledResult) -> Self {
+    let (status, value, reason) = match settled.outcome {
+      Ok(value) => ("fulfilled", Some(value), None),
+      Err(error) => ("rejected", None, Some(TaskErrorInfo::from(&error))),
+    };
+
+    SettledTaskResult {
+      id: settled.id,
+      status: status.to_string(),
+      value,
+      reason,
+      duration_ms: settled.duration_ms as f64,
+    }
+  }
+}
+
+// Created on first use, from inside napi's tokio runtime
+static WORKER_POOL: OnceLock<Arc<WorkerPool>> = OnceLock::new();
+
+fn worker_pool() -> &'static Arc<WorkerPool> {
+  WORKER_POOL.get_or_init(|| Arc::new(WorkerPool::new(num_cpus::get())))
+}
+
+/// Run every task and report each outcome, like `Promise.allSettled`: a failing
+/// task yields a `rejected` entry instead of rejecting the whole batch
+#[napi]
+pub async fn execute_all_settled(tasks: Vec<ExecutorTask>) -> Vec<SettledTaskResult> {
+  TaskExecutor::new()
+    .execute_all_settled(worker_pool(), tasks.into_iter().map(Task::from).collect()).await
+    .into_iter()
+    .map(SettledTaskResult::from)
+    .collect()
+}
+
+/// Run tasks and settle with the first to finish, like `Promise.race`: resolves
+/// with its value, or rejects with its error. Losing tasks that haven't started
+/// are cancelled, so they stop taking workers once the winner is known.
+#[napi]
+pub async fn race(tasks: Vec<ExecutorTask>) -> napi::Result<RaceWinner> {
+  let winner = TaskExecutor::new()
+    .execute_race(worker_pool(), tasks.into_iter().map(Task::from).collect()).await
+    .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+
+  match winner.error {
+    Some(error) => Err(napi::Error::from_reason(error)),
+    None =>
+      Ok(RaceWinner {
+        id: winner.id,
+        value: winner.result,
+        duration_ms: winner.duration_ms as f64,
+      }),
+  }
+}
+
+/// Run tasks and resolve with the first to succeed, like `Promise.any`: failures
+/// are ignored while any task is still running. Rejects only if every task fails,
+/// listing each task's error. Remaining tasks are cancelled as in `race`.
+#[napi]
+pub async fn execute_any(tasks: Vec<ExecutorTask>) -> napi::Result<RaceWinner> {
+  let winner = TaskExecutor::new()
+    .execute_any(worker_pool(), tasks.into_iter().map(Task::from).collect()).await
+    .map_err(|e| {
+      let reason = match &e {
+        TaskExecutorError::AllFailed(failures) => {
+          let errors: Vec<String> = failures
+            .iter()
+            .filter_map(|f| f.outcome.as_ref().err().map(|error| format!("{}: {}", f.id, error)))
+            .collect();
+          format!("{}: {}", e, errors.join("; "))
+        }
+        _ => e.to_string(),
+      };
+      napi::Error::from_reason(reason)
+    })?;
+
+  Ok(RaceWinner {
+    id: winner.id,
+    value: winner.result,
+    duration_ms: winner.duration_ms as f64,
+  })
+}
@@ -2,8 +2,10 @@
 use crate::worker_pool::{ WorkerPool, WorkerTask, WorkerPoolError };
 use std::sync::Arc;
 use thiserror::Error;
+use std::time::Instant;
 use tokio::time::{ timeout, Duration };
 use futures::future;
+use futures::stream::{ FuturesUnordered, StreamExt };
 
 /// Errors that can occur during task execution
 #[derive(Error, Debug)]
@@ -13,6 +15,22 @@ pub enum TaskExecutorError {
   Timeout,
   #[error("No tasks provided")]
   NoTasks,
+  /// Every task of an `execute_any` failed; outcomes are in submission order
+  #[error("All {} tasks failed", .0.len())] AllFailed(Vec<SettledResult>),
+}
+
+impl TaskExecutorError {
+  /// Short machine-readable category, for error objects handed to JS
+  pub fn kind(&self) -> &'static str {
+    match self {
+      TaskExecutorError::WorkerPool(WorkerPoolError::TaskFailed(_)) => "execution",
+      TaskExecutorError::WorkerPool(WorkerPoolError::ShuttingDown) => "shutdown",
+      TaskExecutorError::WorkerPool(WorkerPoolError::Timeout) | TaskExecutorError::Timeout =>
+        "timeout",
+      TaskExecutorError::NoTasks => "no_tasks",
+      TaskExecutorError::AllFailed(_) => "all_failed",
+    }
+  }
 }
 
 /// A task to be executed
@@ -33,11 +51,26 @@ pub struct TaskResult {
   pub error: Option<String>,
 }
 
+/// Outcome of one task in `execute_all_settled` (like a `Promise.allSettled` entry)
+#[derive(Debug)]
+pub struct SettledResult {
+  pub id: String,
+  /// The value, or why there is none; a task that threw is `WorkerPoolError::TaskFailed`
+  pub outcome: Result<serde_json::Value, TaskExecutorError>,
+  pub duration_ms: u64,
+}
+
 /// Coordinates task execution across the worker pool
 pub struct TaskExecutor {
   default_timeout_ms: u64,
 }
 
+impl Default for TaskExecutor {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl TaskExecutor {
   /// Create a new task executor
   pub fn new() -> Self {
@@ -65,6 +98,20 @@ impl TaskExecutor {
     Ok(results)
   }
 
+  /// Execute all tasks in parallel and report every outcome (like Promise.allSettled).
+  /// Unlike `execute_all`, one failing task never fails the batch.
+  pub async fn execute_all_settled(
+    &self,
+    worker_pool: &Arc<WorkerPool>,
+    tasks: Vec<Task>
+  ) -> Vec<SettledResult> {
+    let futures = tasks
+      .into_iter()
+      .map(|task| self.execute_settled_task(worker_pool, task));
+
+    future::join_all(futures).await
+  }
+
   /// Execute tasks and return results as they complete
   pub async fn execute_stream(
     &self,
@@ -113,7 +160,9 @@ impl TaskExecutor {
     }
   }
 
-  /// Execute tasks and return the first completed result (like Promise.race)
+  /// Execute tasks and return the first completed result (like Promise.race).
+  /// Losers still waiting for a worker are dropped without running; ones already
+  /// executing can't be interrupted, but their results are discarded.
   pub async fn execute_race(
     &self,
     worker_pool: &Arc<WorkerPool>,
@@ -128,10 +177,55 @@ impl TaskExecutor {
       .map(|task| Box::pin(self.execute_single_task(worker_pool, task)))
       .collect();
 
-    let (result, _index, _remaining) = future::select_all(futures).await;
+    let (result, _index, losers) = future::select_all(futures).await;
+    // Dropping the losers closes their response channels, which the pool treats
+    // as cancellation
+    drop(losers);
     result
   }
 
+  /// Execute tasks and return the first to succeed (like Promise.any). Fails
+  /// only if every task fails, with all of their errors. Remaining tasks are
+  /// cancelled once there is a winner, as in `execute_race`.
+  pub async fn execute_any(
+    &self,
+    worker_pool: &Arc<WorkerPool>,
+    tasks: Vec<Task>
+  ) -> Result<TaskResult, TaskExecutorError> {
+    if tasks.is_empty() {
+      return Err(TaskExecutorError::NoTasks);
+    }
+
+    let mut pending: FuturesUnordered<_> = tasks
+      .into_iter()
+      .enumerate()
+      .map(|(index, task)| async move {
+        (index, self.execute_settled_task(worker_pool, task).await)
+      })
+      .collect();
+
+    let mut failures = Vec::new();
+    while let Some((index, settled)) = pending.next().await {
+      match settled {
+        SettledResult { id, outcome: Ok(result), duration_ms } => {
+          // Dropping `pending` cancels the rest, as in `execute_race`
+          return Ok(TaskResult { id, result, duration_ms, error: None });
+        }
+        failed => failures.push((index, failed)),
+      }
+    }
+
+    failures.sort_by_key(|(index, _)| *index);
+    Err(
+      TaskExecutorError::AllFailed(
+        failures
+          .into_iter()
+          .map(|(_, settled)| settled)
+          .collect()
+      )
+    )
+  }
+
   /// Execute a single task
   async fn execute_single_task(
     &self,
@@ -178,6 +272,28 @@ impl TaskExecutor {
     Ok(task_result)
   }
 
+  /// Execute a single task, folding every failure into its result
+  async fn execute_settled_task(
+    &self,
+    worker_pool: &Arc<WorkerPool>,
+    task: Task
+  ) -> SettledResult {
+    let id = task.id.clone();
+    let start_time = Instant::now();
+
+    let outcome = match self.execute_single_task(worker_pool, task).await {
+      Ok(TaskResult { error: Some(error), .. }) => Err(WorkerPoolError::TaskFailed(error).into()),
+      Ok(task_result) => Ok(task_result.result),
+      Err(error) => Err(error),
+    };
+
+    SettledResult {
+      id,
+      outcome,
+      duration_ms: start_time.elapsed().as_millis() as u64,
+    }
+  }
+
   /// Set the default timeout for tasks
   pub fn set_default_timeout(&mut self, timeout_ms: u64) {
     self.default_timeout_ms = timeout_ms;
@@ -0,0 +1,105 @@
+// src/task_executor_tests.rs - TaskExecutor's combinators over a live worker pool
+use serde_json::{ json, Value };
+use std::sync::Arc;
+
+use crate::task_executor::{ Task, TaskExecutor, TaskExecutorError };
+use crate::worker_pool::{ WorkerPool, WorkerPoolError };
+
+fn task(id: &str, function_string: &str, data: Value) -> Task {
+  Task { id: id.to_string(), function_string: function_string.to_string(), data, timeout: None }
+}
+
+fn pool(workers: usize) -> Arc<WorkerPool> {
+  Arc::new(WorkerPool::with_strict(workers, true))
+}
+
+#[tokio::test]
+async fn all_settled_reports_every_outcome_in_order() {
+  let tasks = vec![
+    task("ok", "x => x * 2", json!(21)),
+    task("threw", "x => x * 2", json!("twenty")),
+    task("unknown", "x => x ** 2", json!(3)),
+    task("also_ok", "x => x.length", json!([1, 2]))
+  ];
+  let settled = TaskExecutor::new().execute_all_settled(&pool(2), tasks).await;
+
+  let ids: Vec<&str> = settled.iter().map(|s| s.id.as_str()).collect();
+  assert_eq!(ids, ["ok", "threw", "unknown", "also_ok"]);
+  assert_eq!(settled[0].outcome.as_ref().unwrap(), &json!(42.0));
+  assert_eq!(settled[3].outcome.as_ref().unwrap(), &json!(2));
+  match &settled[1].outcome {
+    Err(error @ TaskExecutorError::WorkerPool(WorkerPoolError::TaskFailed(message))) => {
+      assert_eq!(message, "Expected number for multiplication");
+      assert_eq!(error.kind(), "execution");
+    }
+    other => panic!("expected an execution failure, got {:?}", other),
+  }
+  assert!(settled[2].outcome.is_err());
+
+  // An empty batch settles to nothing rather than failing
+  assert!(TaskExecutor::new().execute_all_settled(&pool(1), vec![]).await.is_empty());
+}
+
+#[tokio::test]
+async fn race_settles_with_the_first_to_finish() {
+  let pool = pool(1);
+  let tasks: Vec<Task> = (0..20)
+    .map(|i| task(&format!("t{}", i), "x => x * 2", json!(i)))
+    .collect();
+  let winner = TaskExecutor::new().execute_race(&pool, tasks).await.unwrap();
+  let index: f64 = winner.id[1..].parse().unwrap();
+  assert_eq!(winner.result, json!(index * 2.0));
+
+  // Losers stop taking the pool's only worker once the winner is known
+  for _ in 0..100 {
+    if pool.active_task_count().await == 0 {
+      break;
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+  }
+  assert_eq!(pool.active_task_count().await, 0);
+
+  // A winner that threw carries its error
+  let failed = TaskExecutor::new()
+    .execute_race(&pool, vec![task("threw", "x => x * 2", json!("two"))]).await
+    .unwrap();
+  assert_eq!(failed.error.as_deref(), Some("Expected number for multiplication"));
+
+  let empty = TaskExecutor::new().execute_race(&pool, vec![]).await;
+  assert!(matches!(empty, Err(TaskExecutorError::NoTasks)));
+}
+
+#[tokio::test]
+async fn any_resolves_with_the_first_success() {
+  let tasks = vec![
+    task("threw", "x => x * 2", json!("two")),
+    task("ok", "x => x + 1", json!(41)),
+    task("also_threw", "x => x.toUpperCase()", json!(1))
+  ];
+  let winner = TaskExecutor::new().execute_any(&pool(2), tasks).await.unwrap();
+  assert_eq!((winner.id.as_str(), winner.result), ("ok", json!(42.0)));
+  assert!(winner.error.is_none());
+}
+
+#[tokio::test]
+async fn any_rejects_with_every_error_once_all_fail() {
+  let tasks = vec![
+    task("first", "x => x * 2", json!("two")),
+    task("second", "x => x.length", json!(2))
+  ];
+  let error = TaskExecutor::new().execute_any(&pool(2), tasks).await.unwrap_err();
+  assert_eq!(error.kind(), "all_failed");
+  assert_eq!(error.to_string(), "All 2 tasks failed");
+  let TaskExecutorError::AllFailed(failures) = error else {
+    unreachable!();
+  };
+  // In submission order, whichever failed first
+  let failures: Vec<(String, String)> = failures
+    .into_iter()
+    .map(|settled| (settled.id, settled.outcome.unwrap_err().to_string()))
+    .collect();
+  assert_eq!(failures[0].0, "first");
+  assert!(failures[0].1.ends_with("Expected number for multiplication"), "{}", failures[0].1);
+  assert_eq!(failures[1].0, "second");
+  assert!(failures[1].1.ends_with("Expected array or string for length"), "{}", failures[1].1);
+}
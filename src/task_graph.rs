@@ -0,0 +1,340 @@
+// src/task_graph.rs - Tasks that run once the tasks they depend on succeed
+//
+// `submit_graph` takes a multi-stage workload as one submission: each node
+// names the nodes it depends on, and runs with their output as its input once
+// all of them have succeeded. Nodes without dependencies are queued straight
+// away; the rest are held here and released to the shared queue as their last
+// parent finishes. A failed or cancelled node fails everything downstream of
+// it without running.
+//
+// A node may also carry a compensation: code that undoes what the node did,
+// run on its output. When a node of the graph fails, the compensations of the
+// nodes that had succeeded run one after another on the thread that reported
+// the failure, latest success first, so a multi-step mutation rolls back
+// without the caller tracking which steps completed. A node that succeeds
+// after its graph failed is compensated as soon as it finishes.
+use std::collections::{ HashMap, HashSet };
+use std::sync::atomic::{ AtomicU64, AtomicUsize, Ordering };
+
+use crate::bindings::napi;
+use crate::events::EventLog;
+use crate::priority::{ Priority, Requeue };
+use crate::sync::{ Arc, Mutex, MutexGuard, lock_recovering };
+use crate::{ OptimizationHints, OptimizedWorkerTask };
+
+/// Runs a compensation task natively, discarding its output
+pub type Compensate = Box<dyn Fn(&OptimizedWorkerTask) -> Result<(), String> + Send + Sync>;
+
+/// One task of a `submit_graph` submission
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct GraphNode {
+  /// Name other nodes use in `depends_on`, unique within the graph
+  pub name: String,
+  pub function_code: String,
+  /// Input of a node without dependencies; other nodes take their parents' output
+  pub data: Option<String>,
+  pub depends_on: Option<Vec<String>>,
+  /// Replaces the graph's shared hints for this node
+  pub hints: Option<OptimizationHints>,
+  /// Code run on this node's output to undo it, should a node of the graph
+  /// fail after this one succeeded
+  pub compensation_code: Option<String>,
+}
+
+/// Check that `nodes` form a graph that can run, and return each node's
+/// parents as indices into `nodes`
+pub fn plan(nodes: &[GraphNode]) -> Result<Vec<Vec<usize>>, String> {
+  let mut index = HashMap::with_capacity(nodes.len());
+  for (i, node) in nodes.iter().enumerate() {
+    if index.insert(node.name.as_str(), i).is_some() {
+      return Err(format!("Graph has more than one node named '{}'", node.name));
+    }
+  }
+
+  let mut parents = Vec::with_capacity(nodes.len());
+  for node in nodes {
+    let depends_on = node.depends_on.as_deref().unwrap_or_default();
+    let mut seen = HashSet::new();
+    let mut node_parents = Vec::with_capacity(depends_on.len());
+    for parent in depends_on {
+      let Some(&i) = index.get(parent.as_str()) else {
+        return Err(format!("Node '{}' depends on unknown node '{}'", node.name, parent));
+      };
+      if !seen.insert(i) {
+        return Err(format!("Node '{}' depends on '{}' more than once", node.name, parent));
+      }
+      node_parents.push(i);
+    }
+    match (node_parents.is_empty(), node.data.is_some()) {
+      (true, false) => {
+        return Err(format!("Node '{}' has no dependencies, so it needs data", node.name));
+      }
+      (false, true) => {
+        return Err(format!("Node '{}' takes its input from its dependencies, not data", node.name));
+      }
+      _ => {}
+    }
+    parents.push(node_parents);
+  }
+
+  // Kahn's algorithm: every node must be reachable from the roots
+  let mut remaining: Vec<usize> = parents.iter().map(Vec::len).collect();
+  let mut children = vec![Vec::new(); nodes.len()];
+  for (child, node_parents) in parents.iter().enumerate() {
+    for &parent in node_parents {
+      children[parent].push(child);
+    }
+  }
+  let mut ready: Vec<usize> = (0..nodes.len()).filter(|&i| remaining[i] == 0).collect();
+  let mut visited = 0;
+  while let Some(i) = ready.pop() {
+    visited += 1;
+    for &child in &children[i] {
+      remaining[child] -= 1;
+      if remaining[child] == 0 {
+        ready.push(child);
+      }
+    }
+  }
+  if visited < nodes.len() {
+    let stuck = (0..nodes.len()).find(|&i| remaining[i] > 0).map_or("", |i| &nodes[i].name);
+    return Err(format!("Graph has a dependency cycle through node '{}'", stuck));
+  }
+  Ok(parents)
+}
+
+#[derive(Debug, Default)]
+struct Node {
+  name: String,
+  graph: u64,
+  // Held nodes that depend on this one
+  children: Vec<String>,
+  // Run on this node's output if the graph fails after it succeeded
+  compensation: Option<OptimizedWorkerTask>,
+}
+
+/// A node's compensation, ready to run
+#[derive(Debug)]
+struct Compensation {
+  node: String,
+  task: OptimizedWorkerTask,
+}
+
+#[derive(Debug, Default)]
+struct Saga {
+  unfinished: usize,
+  // Name of the first node that failed
+  failed: Option<String>,
+  // Compensations of the nodes that succeeded before any failed, in the order
+  // they succeeded
+  succeeded: Vec<Compensation>,
+}
+
+#[derive(Debug)]
+struct Held {
+  task: OptimizedWorkerTask,
+  priority: Priority,
+  // Parent task IDs in `depends_on` order, and their outputs so far
+  parents: Vec<String>,
+  inputs: Vec<Option<String>>,
+}
+
+impl Held {
+  /// The task, with its parents' output as its data: a single parent's as is,
+  /// several as a JSON array
+  fn into_task(self) -> (OptimizedWorkerTask, Priority) {
+    let mut task = self.task;
+    let mut inputs: Vec<String> = self.inputs.into_iter().flatten().collect();
+    task.data = if inputs.len() == 1 {
+      inputs.remove(0)
+    } else {
+      let values: Vec<serde_json::Value> = inputs
+        .into_iter()
+        .map(|input| serde_json::from_str(&input).unwrap_or(serde_json::Value::String(input)))
+        .collect();
+      serde_json::Value::Array(values).to_string()
+    };
+    (task, self.priority)
+  }
+}
+
+#[derive(Debug, Default)]
+struct GraphState {
+  // Graph tasks that haven't finished, by task ID
+  nodes: HashMap<String, Node>,
+  // Tasks waiting on their parents, by task ID
+  held: HashMap<String, Held>,
+  // Graphs with unfinished nodes, by graph ID
+  sagas: HashMap<u64, Saga>,
+}
+
+impl GraphState {
+  // Remove a finished node, counting it off its graph
+  fn remove(&mut self, task_id: &str) -> Option<Node> {
+    let node = self.nodes.remove(task_id)?;
+    if let Some(saga) = self.sagas.get_mut(&node.graph) {
+      saga.unfinished -= 1;
+    }
+    Some(node)
+  }
+}
+
+/// Every unfinished node of every submitted graph
+pub struct GraphTable {
+  state: Mutex<GraphState>,
+  // Lets workers skip the lock after each task while no graph is running
+  node_count: AtomicUsize,
+  next_graph: AtomicU64,
+  requeue: Requeue,
+  compensate: Compensate,
+  events: Arc<EventLog>,
+}
+
+impl GraphTable {
+  pub fn new(requeue: Requeue, compensate: Compensate, events: Arc<EventLog>) -> Self {
+    Self {
+      state: Mutex::new(GraphState::default()),
+      node_count: AtomicUsize::new(0),
+      next_graph: AtomicU64::new(0),
+      requeue,
+      compensate,
+      events,
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, GraphState> {
+    lock_recovering(&self.state, || {})
+  }
+
+  /// ID for the nodes of a new graph
+  pub fn open(&self) -> u64 {
+    self.next_graph.fetch_add(1, Ordering::Relaxed)
+  }
+
+  /// Add a node of `graph` before any task of it is queued. A node with
+  /// parents passes its task in to be held until they all succeed.
+  pub fn add(
+    &self,
+    graph: u64,
+    task_id: &str,
+    name: &str,
+    parents: Vec<String>,
+    held: Option<(OptimizedWorkerTask, Priority)>,
+    compensation: Option<OptimizedWorkerTask>
+  ) {
+    let mut state = self.lock();
+    let node = state.nodes.entry(task_id.to_string()).or_default();
+    node.name = name.to_string();
+    node.graph = graph;
+    node.compensation = compensation;
+    state.sagas.entry(graph).or_default().unfinished += 1;
+    for parent in &parents {
+      state.nodes.entry(parent.clone()).or_default().children.push(task_id.to_string());
+    }
+    if let Some((task, priority)) = held {
+      let inputs = vec![None; parents.len()];
+      state.held.insert(task_id.to_string(), Held { task, priority, parents, inputs });
+    }
+    self.node_count.store(state.nodes.len(), Ordering::Release);
+  }
+
+  /// A task finished with `output`, or failed with `None`. Children whose last
+  /// parent this was are requeued; when it failed, every held node downstream
+  /// is returned with the reason it can't run, after the compensations of the
+  /// nodes of its graph that succeeded have run.
+  pub fn finish(&self, task_id: &str, output: Option<&str>) -> Vec<(OptimizedWorkerTask, String)> {
+    if self.node_count.load(Ordering::Acquire) == 0 {
+      return Vec::new();
+    }
+    let mut released = Vec::new();
+    let mut skipped = Vec::new();
+    let mut compensations = Vec::new();
+    let failed_node;
+    {
+      let mut state = self.lock();
+      let Some(node) = state.remove(task_id) else {
+        return Vec::new();
+      };
+      let graph = node.graph;
+      let saga = state.sagas.entry(graph).or_default();
+      match (output, node.compensation) {
+        (Some(output), Some(mut task)) => {
+          task.data = output.to_string();
+          let compensation = Compensation { node: node.name.clone(), task };
+          match saga.failed {
+            Some(_) => compensations.push(compensation),
+            None => saga.succeeded.push(compensation),
+          }
+        }
+        (None, _) if saga.failed.is_none() => {
+          saga.failed = Some(node.name.clone());
+          compensations.extend(saga.succeeded.drain(..).rev());
+        }
+        _ => {}
+      }
+      failed_node = saga.failed.clone().unwrap_or_default();
+
+      match output {
+        Some(output) =>
+          for child in node.children {
+            let Some(held) = state.held.get_mut(&child) else {
+              continue;
+            };
+            for (parent, input) in held.parents.iter().zip(held.inputs.iter_mut()) {
+              if parent == task_id {
+                *input = Some(output.to_string());
+              }
+            }
+            if
+              held.inputs.iter().all(Option::is_some) &&
+              let Some(held) = state.held.remove(&child)
+            {
+              released.push(held.into_task());
+            }
+          }
+        None => {
+          let reason = format!("Skipped because dependency '{}' failed", node.name);
+          let mut downstream = node.children;
+          while let Some(child) = downstream.pop() {
+            // A child already skipped through another parent is gone
+            if let Some(held) = state.held.remove(&child) {
+              skipped.push((held.task, reason.clone()));
+            }
+            if let Some(node) = state.remove(&child) {
+              downstream.extend(node.children);
+            }
+          }
+        }
+      }
+      // Nothing is left to compensate once every node of the graph finished
+      if state.sagas.get(&graph).is_some_and(|saga| saga.unfinished == 0) {
+        state.sagas.remove(&graph);
+      }
+      self.node_count.store(state.nodes.len(), Ordering::Release);
+    }
+    self.requeue.send(released);
+    for Compensation { node, task } in compensations {
+      match (self.compensate)(&task) {
+        Ok(()) =>
+          self.events.publish(
+            "node_compensated",
+            None,
+            format!("Compensated graph node '{}' after node '{}' failed", node, failed_node)
+          ),
+        Err(error) =>
+          self.events.emit(
+            "compensation_failed",
+            None,
+            format!(
+              "Compensation of graph node '{}' after node '{}' failed: {}",
+              node,
+              failed_node,
+              error
+            )
+          ),
+      }
+    }
+    skipped
+  }
+}
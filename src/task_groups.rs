@@ -0,0 +1,265 @@
+// src/task_groups.rs - Named task groups that later tasks can wait on
+//
+// Tasks join a group with the `group` hint. Once the group is sealed (no more
+// members) and every member has finished, tasks submitted with `after` naming
+// it are released to the shared queue, so phased computations run back to back
+// without draining results to JavaScript in between. Callers can also join a
+// group themselves with `wait_for_group`, or cancel all of its members at once.
+#[cfg(feature = "node")]
+use napi::{ Env, Task };
+use std::collections::{ HashMap, HashSet };
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::time::{ Duration, Instant };
+
+use crate::OptimizedWorkerTask;
+use crate::bindings::napi;
+use crate::priority::{ Priority, Requeue };
+use crate::sync::{ Arc, Condvar, Mutex, MutexGuard, lock_recovering };
+
+/// Snapshot returned by `get_group_status`
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct GroupStatus {
+  pub name: String,
+  /// Members submitted but not yet finished
+  pub pending: u32,
+  /// Tasks held until the group completes
+  pub waiting: u32,
+  pub sealed: bool,
+  /// Sealed with every member finished; waiting tasks have been released
+  pub complete: bool,
+}
+
+#[derive(Debug, Default)]
+struct Group {
+  // IDs of members submitted but not yet finished
+  pending: HashSet<String>,
+  sealed: bool,
+  waiting: Vec<(OptimizedWorkerTask, Priority)>,
+}
+
+impl Group {
+  fn is_complete(&self) -> bool {
+    self.sealed && self.pending.is_empty()
+  }
+}
+
+/// Groups by name. A group exists from the first task that joins or waits on it.
+pub struct GroupTable {
+  groups: Mutex<HashMap<String, Group>>,
+  // Signalled whenever a group's last pending member finishes
+  finished: Condvar,
+  // Set by `shutdown`: pending members will never finish, so waiters give up
+  closed: AtomicBool,
+  requeue: Requeue,
+}
+
+impl GroupTable {
+  pub fn new(requeue: Requeue) -> Self {
+    Self {
+      groups: Mutex::new(HashMap::new()),
+      finished: Condvar::new(),
+      closed: AtomicBool::new(false),
+      requeue,
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, HashMap<String, Group>> {
+    lock_recovering(&self.groups, || {})
+  }
+
+  /// Count task `task_id` into `name`; sealed groups take no more members
+  pub fn join(&self, name: &str, task_id: &str) -> Result<(), String> {
+    let mut groups = self.lock();
+    let group = groups.entry(name.to_string()).or_default();
+    if group.sealed {
+      return Err(format!("Task group '{}' is sealed", name));
+    }
+    group.pending.insert(task_id.to_string());
+    Ok(())
+  }
+
+  pub fn is_sealed(&self, name: &str) -> bool {
+    self.lock().get(name).is_some_and(|group| group.sealed)
+  }
+
+  /// Hold `task` until `name` completes. Hands the task back if it already has.
+  pub fn hold(
+    &self,
+    name: &str,
+    task: OptimizedWorkerTask,
+    priority: Priority
+  ) -> Option<OptimizedWorkerTask> {
+    let mut groups = self.lock();
+    let group = groups.entry(name.to_string()).or_default();
+    if group.is_complete() {
+      return Some(task);
+    }
+    group.waiting.push((task, priority));
+    None
+  }
+
+  /// Member `task_id` of `name` finished, or never made it into a queue
+  pub fn finish(&self, name: &str, task_id: &str) {
+    let released = {
+      let mut groups = self.lock();
+      let Some(group) = groups.get_mut(name) else {
+        return;
+      };
+      if group.pending.remove(task_id) && group.pending.is_empty() {
+        self.finished.notify_all();
+      }
+      Self::take_released(group)
+    };
+    self.requeue.send(released);
+  }
+
+  /// IDs of the members of `name` that haven't finished
+  pub fn pending_members(&self, name: &str) -> Vec<String> {
+    self
+      .lock()
+      .get(name)
+      .map(|group| group.pending.iter().cloned().collect())
+      .unwrap_or_default()
+  }
+
+  /// Wait until every member of `name` submitted so far has finished, up to
+  /// `timeout` (forever if `None`). False if the deadline passed or the executor
+  /// shut down first; an error if no task has named the group.
+  pub fn wait(&self, name: &str, timeout: Option<Duration>) -> Result<bool, String> {
+    let deadline = timeout.map(|t| Instant::now() + t);
+    let mut groups = self.lock();
+
+    loop {
+      match groups.get(name) {
+        None => {
+          return Err(format!("Unknown task group '{}'", name));
+        }
+        Some(group) if group.pending.is_empty() => {
+          return Ok(true);
+        }
+        Some(_) => {}
+      }
+      if self.closed.load(Ordering::Acquire) {
+        return Ok(false);
+      }
+
+      groups = match deadline {
+        Some(deadline) => {
+          let remaining = deadline.saturating_duration_since(Instant::now());
+          if remaining.is_zero() {
+            return Ok(false);
+          }
+          self.finished
+            .wait_timeout(groups, remaining)
+            .map(|(guard, _)| guard)
+            .unwrap_or_else(|poisoned| poisoned.into_inner().0)
+        }
+        None => self.finished.wait(groups).unwrap_or_else(|poisoned| poisoned.into_inner()),
+      };
+    }
+  }
+
+  /// Wake every waiter for good; called on shutdown
+  pub fn close(&self) {
+    let groups = self.lock();
+    self.closed.store(true, Ordering::Release);
+    drop(groups);
+    self.finished.notify_all();
+  }
+
+  /// No more members may join `name`; completes it if none are pending
+  pub fn seal(&self, name: &str) {
+    let released = {
+      let mut groups = self.lock();
+      let group = groups.entry(name.to_string()).or_default();
+      group.sealed = true;
+      Self::take_released(group)
+    };
+    self.requeue.send(released);
+  }
+
+  pub fn status(&self, name: &str) -> Option<GroupStatus> {
+    self
+      .lock()
+      .get(name)
+      .map(|group| GroupStatus {
+        name: name.to_string(),
+        pending: group.pending.len() as u32,
+        waiting: group.waiting.len() as u32,
+        sealed: group.sealed,
+        complete: group.is_complete(),
+      })
+  }
+
+  /// Forget a completed group, returning whether it existed. Tasks submitted
+  /// later with `after` naming it wait for a new group of that name.
+  pub fn remove(&self, name: &str) -> Result<bool, String> {
+    let mut groups = self.lock();
+    match groups.get(name) {
+      None => Ok(false),
+      Some(group) if !group.is_complete() =>
+        Err(format!("Task group '{}' has not completed", name)),
+      Some(_) => Ok(groups.remove(name).is_some()),
+    }
+  }
+
+  fn take_released(group: &mut Group) -> Vec<(OptimizedWorkerTask, Priority)> {
+    if group.is_complete() { std::mem::take(&mut group.waiting) } else { Vec::new() }
+  }
+}
+
+/// A running task's group membership; finishes it when dropped
+pub struct Membership<'a> {
+  table: &'a GroupTable,
+  name: String,
+  task_id: String,
+}
+
+impl<'a> Membership<'a> {
+  pub fn new(table: &'a GroupTable, name: String, task_id: String) -> Self {
+    Self { table, name, task_id }
+  }
+}
+
+impl Drop for Membership<'_> {
+  fn drop(&mut self) {
+    self.table.finish(&self.name, &self.task_id);
+  }
+}
+
+/// Waits for a group's members to finish; off the JS thread when run as an `AsyncTask`
+pub struct GroupWait {
+  groups: Arc<GroupTable>,
+  name: String,
+  timeout: Option<Duration>,
+}
+
+impl GroupWait {
+  pub fn new(groups: Arc<GroupTable>, name: String, timeout_ms: Option<u32>) -> Self {
+    Self {
+      groups,
+      name,
+      timeout: timeout_ms.map(|t| Duration::from_millis(t as u64)),
+    }
+  }
+
+  /// Block until every member has finished (true) or the wait gives up (false)
+  pub fn wait(&self) -> Result<bool, String> {
+    self.groups.wait(&self.name, self.timeout)
+  }
+}
+
+#[cfg(feature = "node")]
+impl Task for GroupWait {
+  type Output = bool;
+  type JsValue = bool;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    self.wait().map_err(napi::Error::from_reason)
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    Ok(output)
+  }
+}
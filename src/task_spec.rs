@@ -0,0 +1,290 @@
+// src/task_spec.rs - Task and batch definitions as a JSON or YAML document
+//
+// Pipelines authored by tools or in other languages reach threader as a spec
+// document rather than API calls. A spec describes what `submit_graph` takes;
+// a batch is a spec whose tasks have no dependencies. Fields:
+//
+//   version     1
+//   functions   optional map of name to function code, for tasks to share
+//   data        optional map of name to input (JSON text), for tasks to share
+//   hints       optional hints shared by every task
+//   timeout_ms  optional execution budget for each task
+//   tasks       list of:
+//     name        unique within the spec
+//     function    function code, or
+//     function_ref  a name in `functions`
+//     data        input as JSON text, or
+//     data_ref    a name in `data`; tasks with dependencies take neither
+//     depends_on  optional names of tasks whose output is this one's input
+//     compensation  optional code undoing this task, run on its output when a
+//                 later task fails
+//     hints       optional hints for this task
+//
+// Hints use `OptimizationHints` field names and may be partial: a task's hints
+// are its own over the spec's over defaults (`general`, `medium` complexity,
+// one core, native, a function hash derived from the code). Unknown fields are
+// rejected, so a typo doesn't silently fall back to a default.
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{ BTreeMap, HashMap };
+use std::hash::{ Hash, Hasher };
+
+use serde::{ Deserialize, Serialize };
+use serde_json::{ Map, Value };
+
+use crate::OptimizationHints;
+use crate::bindings::{ Error, Result, napi };
+use crate::task_graph::GraphNode;
+
+/// Spec version this build reads and writes
+pub const SPEC_VERSION: u32 = 1;
+
+/// A spec's tasks as `submit_graph` takes them
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct TaskSpec {
+  pub nodes: Vec<GraphNode>,
+  /// Shared by every node without its own; imported nodes always have their own
+  pub hints: Option<OptimizationHints>,
+  pub timeout_ms: Option<u32>,
+}
+
+/// Document encoding of a spec
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecFormat {
+  Json,
+  Yaml,
+}
+
+impl SpecFormat {
+  /// Parse `"json"` (the default) or `"yaml"`
+  pub fn parse(format: Option<&str>) -> std::result::Result<Self, String> {
+    match format.unwrap_or("json") {
+      "json" => Ok(Self::Json),
+      "yaml" => Ok(Self::Yaml),
+      other => Err(format!("Unknown task spec format '{}', expected json or yaml", other)),
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpecDocument {
+  version: u32,
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  functions: BTreeMap<String, String>,
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  data: BTreeMap<String, String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  hints: Option<Map<String, Value>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  timeout_ms: Option<u32>,
+  tasks: Vec<SpecTask>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpecTask {
+  name: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  function: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  function_ref: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  data: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  data_ref: Option<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  depends_on: Vec<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  compensation: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  hints: Option<Map<String, Value>>,
+}
+
+/// Hints of a task that sets none, for `function_code`
+fn default_hints(function_code: &str) -> Map<String, Value> {
+  let mut hasher = DefaultHasher::new();
+  function_code.hash(&mut hasher);
+  let hints = serde_json::json!({
+    "operation_type": "general",
+    "complexity": "medium",
+    "expected_cores": 1,
+    "should_use_rust": true,
+    "is_hot_function": false,
+    "estimated_memory": 0,
+    "function_hash": format!("{:016x}", hasher.finish()),
+  });
+  match hints {
+    Value::Object(hints) => hints,
+    _ => Map::new(),
+  }
+}
+
+/// Partial hints for `function_code`, each layer over the last, over the
+/// defaults. Unknown fields are an error.
+pub fn resolve_hints(
+  function_code: &str,
+  layers: &[&Map<String, Value>]
+) -> std::result::Result<OptimizationHints, String> {
+  let mut hints = default_hints(function_code);
+  for layer in layers {
+    hints.extend((*layer).clone());
+  }
+  let hints: OptimizationHints = serde_json::from_value(Value::Object(hints)).map_err(|e| {
+    format!("invalid hints: {}", e)
+  })?;
+  // Serde skips fields it doesn't know, so compare against every field there is
+  let known = serde_json::to_value(&hints).map_err(|e| e.to_string())?;
+  let unknown = layers
+    .iter()
+    .flat_map(|layer| layer.keys())
+    .find(|key| known.get(key.as_str()).is_none());
+  match unknown {
+    Some(key) => Err(format!("unknown hint '{}'", key)),
+    None => Ok(hints),
+  }
+}
+
+/// One of `inline` or a `reference` into `named`, whichever the task gave
+fn pick<'a>(
+  task: &str,
+  field: &str,
+  inline: Option<&'a String>,
+  reference: Option<&String>,
+  named: &'a BTreeMap<String, String>
+) -> std::result::Result<Option<&'a String>, String> {
+  match (inline, reference) {
+    (Some(_), Some(_)) => Err(format!("Task '{}' sets both {} and {}_ref", task, field, field)),
+    (Some(value), None) => Ok(Some(value)),
+    (None, Some(name)) =>
+      named
+        .get(name)
+        .map(Some)
+        .ok_or_else(|| format!("Task '{}' refers to unknown {} '{}'", task, field, name)),
+    (None, None) => Ok(None),
+  }
+}
+
+fn decode(spec: &str, format: SpecFormat) -> std::result::Result<TaskSpec, String> {
+  let document: SpecDocument = match format {
+    SpecFormat::Json => serde_json::from_str(spec).map_err(|e| e.to_string()),
+    SpecFormat::Yaml => serde_yaml::from_str(spec).map_err(|e| e.to_string()),
+  }.map_err(|e| format!("Invalid task spec: {}", e))?;
+  if document.version != SPEC_VERSION {
+    return Err(
+      format!("Unsupported task spec version {}, expected {}", document.version, SPEC_VERSION)
+    );
+  }
+  if document.tasks.is_empty() {
+    return Err("Task spec has no tasks".to_string());
+  }
+
+  let mut nodes = Vec::with_capacity(document.tasks.len());
+  for task in document.tasks {
+    let function = pick(
+      &task.name,
+      "function",
+      task.function.as_ref(),
+      task.function_ref.as_ref(),
+      &document.functions
+    )?.ok_or_else(|| format!("Task '{}' needs a function or function_ref", task.name))?;
+    let data = pick(
+      &task.name,
+      "data",
+      task.data.as_ref(),
+      task.data_ref.as_ref(),
+      &document.data
+    )?;
+
+    let layers: Vec<_> = [document.hints.as_ref(), task.hints.as_ref()]
+      .into_iter()
+      .flatten()
+      .collect();
+    let hints = resolve_hints(function, &layers).map_err(|e| {
+      format!("Task '{}' has {}", task.name, e)
+    })?;
+
+    nodes.push(GraphNode {
+      name: task.name,
+      function_code: function.clone(),
+      data: data.cloned(),
+      depends_on: (!task.depends_on.is_empty()).then_some(task.depends_on),
+      hints: Some(hints),
+      compensation_code: task.compensation,
+    });
+  }
+  Ok(TaskSpec { nodes, hints: None, timeout_ms: document.timeout_ms })
+}
+
+/// Hints as a spec field
+fn hints_map(hints: &OptimizationHints) -> std::result::Result<Map<String, Value>, String> {
+  match serde_json::to_value(hints).map_err(|e| e.to_string())? {
+    // Unset options are left out rather than written as null
+    Value::Object(hints) => Ok(hints.into_iter().filter(|(_, value)| !value.is_null()).collect()),
+    _ => Ok(Map::new()),
+  }
+}
+
+fn encode(spec: &TaskSpec, format: SpecFormat) -> std::result::Result<String, String> {
+  // Functions used by more than one node are written once, named after the first
+  let mut uses: HashMap<&str, usize> = HashMap::new();
+  for node in &spec.nodes {
+    *uses.entry(node.function_code.as_str()).or_default() += 1;
+  }
+  let mut functions = BTreeMap::new();
+  let mut names: HashMap<&str, &str> = HashMap::new();
+  let mut tasks = Vec::with_capacity(spec.nodes.len());
+  for node in &spec.nodes {
+    let code = node.function_code.as_str();
+    let shared = uses.get(code).is_some_and(|&count| count > 1);
+    let function_ref = shared.then(|| {
+      let name = *names.entry(code).or_insert(&node.name);
+      functions.insert(name.to_string(), code.to_string());
+      name.to_string()
+    });
+    tasks.push(SpecTask {
+      name: node.name.clone(),
+      function: (!shared).then(|| code.to_string()),
+      function_ref,
+      data: node.data.clone(),
+      data_ref: None,
+      depends_on: node.depends_on.clone().unwrap_or_default(),
+      compensation: node.compensation_code.clone(),
+      hints: node.hints.as_ref().map(hints_map).transpose()?,
+    });
+  }
+  let document = SpecDocument {
+    version: SPEC_VERSION,
+    functions,
+    data: BTreeMap::new(),
+    hints: spec.hints.as_ref().map(hints_map).transpose()?,
+    timeout_ms: spec.timeout_ms,
+    tasks,
+  };
+  match format {
+    SpecFormat::Json => serde_json::to_string_pretty(&document).map_err(|e| e.to_string()),
+    SpecFormat::Yaml => serde_yaml::to_string(&document).map_err(|e| e.to_string()),
+  }
+}
+
+/// Read a JSON or YAML task spec (see `src/task_spec.rs` for the fields); every
+/// node comes back with its hints resolved
+pub fn import(spec: &str, format: Option<&str>) -> Result<TaskSpec> {
+  SpecFormat::parse(format)
+    .and_then(|format| decode(spec, format))
+    .map_err(Error::from_reason)
+}
+
+/// Parse a task spec document; `format` is `"json"` (default) or `"yaml"`
+#[napi]
+pub fn parse_task_spec(spec: String, format: Option<String>) -> Result<TaskSpec> {
+  import(&spec, format.as_deref())
+}
+
+/// Write tasks as a spec document; `format` is `"json"` (default) or `"yaml"`
+#[napi]
+pub fn export_task_spec(spec: TaskSpec, format: Option<String>) -> Result<String> {
+  SpecFormat::parse(format.as_deref())
+    .and_then(|format| encode(&spec, format))
+    .map_err(Error::from_reason)
+}
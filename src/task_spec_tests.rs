@@ -0,0 +1,90 @@
+// src/task_spec_tests.rs - Reading and writing task spec documents
+use crate::task_spec::{ export_task_spec, import, parse_task_spec };
+
+const PIPELINE_YAML: &str = r#"
+version: 1
+functions:
+  double: "x => x * 2"
+data:
+  ten: "10"
+hints:
+  operation_type: mathematical
+tasks:
+  - name: first
+    function_ref: double
+    data_ref: ten
+  - name: second
+    function_ref: double
+    depends_on: [first]
+    hints:
+      complexity: high
+"#;
+
+fn refused(spec: &str) -> String {
+  import(spec, None).unwrap_err().reason
+}
+
+#[test]
+fn references_and_hint_layers_are_resolved() {
+  let spec = import(PIPELINE_YAML, Some("yaml")).expect("spec");
+  let [first, second] = &spec.nodes[..] else { panic!("{:?}", spec.nodes) };
+  assert_eq!(first.function_code, "x => x * 2");
+  assert_eq!(first.data.as_deref(), Some("10"));
+  assert_eq!(second.depends_on.as_deref(), Some(&["first".to_string()][..]));
+  assert_eq!(second.data, None);
+
+  // The task's hints over the spec's over the defaults
+  let first_hints = first.hints.as_ref().unwrap();
+  let second_hints = second.hints.as_ref().unwrap();
+  assert_eq!(first_hints.operation_type, "mathematical");
+  assert_eq!(first_hints.complexity, "medium");
+  assert_eq!(second_hints.operation_type, "mathematical");
+  assert_eq!(second_hints.complexity, "high");
+  // The same code hashes the same
+  assert_eq!(first_hints.function_hash, second_hints.function_hash);
+}
+
+#[test]
+fn export_round_trips_and_shares_repeated_functions() {
+  let spec = import(PIPELINE_YAML, Some("yaml")).expect("spec");
+  for format in ["json", "yaml"] {
+    let document = export_task_spec(spec.clone(), Some(format.into())).expect("export");
+    let again = parse_task_spec(document.clone(), Some(format.into())).expect("import");
+    assert_eq!(format!("{:?}", again.nodes), format!("{:?}", spec.nodes), "{}", document);
+  }
+
+  let document = export_task_spec(spec, None).expect("export");
+  let document: serde_json::Value = serde_json::from_str(&document).unwrap();
+  assert_eq!(document["functions"]["first"], "x => x * 2");
+  assert_eq!(document["tasks"][1]["function_ref"], "first");
+  assert!(document["tasks"][1].get("function").is_none());
+}
+
+#[test]
+fn malformed_specs_are_refused() {
+  let task = |fields: &str| format!(r#"{{"version": 1, "tasks": [{{"name": "a", {}}}]}}"#, fields);
+
+  assert_eq!(
+    refused(r#"{"version": 2, "tasks": []}"#),
+    "Unsupported task spec version 2, expected 1"
+  );
+  assert_eq!(refused(r#"{"version": 1, "tasks": []}"#), "Task spec has no tasks");
+  assert_eq!(refused(&task(r#""data": "1""#)), "Task 'a' needs a function or function_ref");
+  assert_eq!(
+    refused(&task(r#""function": "x => x", "function_ref": "f""#)),
+    "Task 'a' sets both function and function_ref"
+  );
+  assert_eq!(
+    refused(&task(r#""function": "x => x", "data_ref": "missing""#)),
+    "Task 'a' refers to unknown data 'missing'"
+  );
+  assert_eq!(
+    refused(&task(r#""function": "x => x", "hints": {"complexty": "low"}"#)),
+    "Task 'a' has unknown hint 'complexty'"
+  );
+  assert!(refused(&task(r#""function": "x => x", "retries": 3"#)).starts_with("Invalid task spec"));
+  assert_eq!(
+    import(PIPELINE_YAML, Some("toml")).unwrap_err().reason,
+    "Unknown task spec format 'toml', expected json or yaml"
+  );
+}
@@ -0,0 +1,384 @@
+// src/task_status.rs - Lifecycle tracking behind `get_task_status`
+use serde::{ Deserialize, Serialize };
+use std::collections::{ HashMap, VecDeque };
+use std::time::{ Duration, Instant };
+
+use crate::bindings::napi;
+use crate::clock::SharedClock;
+use crate::priority::Priority;
+use crate::sync::{ Mutex, MutexGuard, lock_recovering };
+
+// Finished tasks are forgotten oldest-first past this many
+const MAX_FINISHED_TASKS: usize = 10_000;
+
+/// Queue a task waits in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueueId {
+  /// A priority lane of the shared queue
+  Shared(Priority),
+  /// A worker reservation's private queue, by reservation ID
+  Reserved(u64),
+}
+
+/// Where a task is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+  /// Held until the task group it waits on completes, or a concurrency slot frees
+  Waiting,
+  Queued,
+  Running,
+  Completed,
+  Failed,
+  Cancelled,
+}
+
+impl TaskState {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      TaskState::Waiting => "waiting",
+      TaskState::Queued => "queued",
+      TaskState::Running => "running",
+      TaskState::Completed => "completed",
+      TaskState::Failed => "failed",
+      TaskState::Cancelled => "cancelled",
+    }
+  }
+
+  pub fn is_finished(self) -> bool {
+    matches!(self, TaskState::Completed | TaskState::Failed | TaskState::Cancelled)
+  }
+}
+
+/// Snapshot returned by `get_task_status`
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskStatus {
+  pub task_id: String,
+  /// "waiting", "queued", "running", "completed", "failed" or "cancelled"
+  pub status: String,
+  /// Tasks ahead of this one, counting higher-priority lanes (0 = next up), while queued
+  pub queue_position: Option<u32>,
+  /// Time since submission, frozen once the task finishes
+  pub elapsed_ms: f64,
+  /// Time spent executing, once a worker has picked the task up
+  pub running_ms: Option<f64>,
+  pub worker_id: Option<u32>,
+}
+
+/// Snapshot returned by `get_queue_stats`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueueStats {
+  /// Tasks in the shared queue, across every priority
+  pub pending: u32,
+  /// Shared queue depth per priority
+  pub critical: u32,
+  pub high: u32,
+  pub normal: u32,
+  pub low: u32,
+  /// Tasks held for a task group, a delay, a limit or their dependencies
+  pub waiting: u32,
+  /// Time since submission of the task that has been queued longest
+  pub oldest_task_age_ms: Option<f64>,
+  /// Queued task IDs in the order workers will take them, when asked for
+  pub task_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug)]
+struct TaskRecord {
+  state: TaskState,
+  // Queue the task was sent to, and its position in that FIFO queue
+  queue: QueueId,
+  sequence: u64,
+  submitted: Instant,
+  // When it was last sent to its queue, and whether it was reported starved since
+  queued: Instant,
+  starved: bool,
+  started: Option<Instant>,
+  finished: Option<Instant>,
+  worker_id: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+struct QueueCounters {
+  next_sequence: u64,
+  // Tasks taken off the queue so far, started or dropped
+  dequeued: u64,
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+  tasks: HashMap<String, TaskRecord>,
+  finished_order: VecDeque<String>,
+  queues: HashMap<QueueId, QueueCounters>,
+}
+
+/// Records every task's state transitions so callers can tell a task stuck in
+/// the queue from one that is executing.
+///
+/// Queue positions rely on each task queue being FIFO: `queued` must be called
+/// in the same order tasks are sent, i.e. while holding the sender lock.
+pub struct TaskTracker {
+  state: Mutex<TrackerState>,
+  clock: SharedClock,
+}
+
+impl TaskTracker {
+  pub fn new(clock: SharedClock) -> Self {
+    Self {
+      state: Mutex::new(TrackerState::default()),
+      clock,
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, TrackerState> {
+    lock_recovering(&self.state, || {})
+  }
+
+  /// A task is about to be sent to `queue`
+  pub fn queued(&self, task_id: &str, queue: QueueId) {
+    let now = self.clock.now();
+    let mut state = self.lock();
+    let counters = state.queues.entry(queue).or_default();
+    let record = TaskRecord {
+      state: TaskState::Queued,
+      queue,
+      sequence: counters.next_sequence,
+      submitted: now,
+      queued: now,
+      starved: false,
+      started: None,
+      finished: None,
+      worker_id: None,
+    };
+    counters.next_sequence += 1;
+    state.tasks.insert(task_id.to_string(), record);
+  }
+
+  /// A task is held back at submission until a task group completes; `release`
+  /// queues it
+  pub fn waiting(&self, task_id: &str, priority: Priority) {
+    let now = self.clock.now();
+    let record = TaskRecord {
+      state: TaskState::Waiting,
+      queue: QueueId::Shared(priority),
+      // Assigned on release
+      sequence: 0,
+      submitted: now,
+      queued: now,
+      starved: false,
+      started: None,
+      finished: None,
+      worker_id: None,
+    };
+    self.lock().tasks.insert(task_id.to_string(), record);
+  }
+
+  /// A waiting task is about to be sent to `queue`. Its submission time is kept;
+  /// one cancelled while waiting stays cancelled.
+  pub fn release(&self, task_id: &str, queue: QueueId) {
+    let now = self.clock.now();
+    let mut state = self.lock();
+    let state = &mut *state;
+    let Some(record) = state.tasks.get_mut(task_id) else {
+      return;
+    };
+    let counters = state.queues.entry(queue).or_default();
+    record.queue = queue;
+    record.sequence = counters.next_sequence;
+    record.queued = now;
+    record.starved = false;
+    counters.next_sequence += 1;
+    if record.state == TaskState::Waiting {
+      record.state = TaskState::Queued;
+    }
+  }
+
+  /// Undo `queued` for the most recent task after its send failed
+  pub fn forget(&self, task_id: &str) {
+    let mut state = self.lock();
+    if
+      let Some(record) = state.tasks.remove(task_id) &&
+      let Some(counters) = state.queues.get_mut(&record.queue)
+    {
+      counters.next_sequence -= 1;
+    }
+  }
+
+  /// A worker took the task off `queue` and is executing it
+  pub fn start(&self, task_id: &str, queue: QueueId, worker_id: usize) {
+    let now = self.clock.now();
+    let mut state = self.lock();
+    Self::dequeue(&mut state, queue);
+    if let Some(record) = state.tasks.get_mut(task_id) {
+      record.state = TaskState::Running;
+      record.started = Some(now);
+      record.worker_id = Some(worker_id);
+    }
+  }
+
+  /// A worker took the task off `queue` but must hold it back; `release` queues
+  /// it again
+  pub fn park(&self, task_id: &str, queue: QueueId) {
+    let mut state = self.lock();
+    Self::dequeue(&mut state, queue);
+    if let Some(record) = state.tasks.get_mut(task_id) && record.state == TaskState::Queued {
+      record.state = TaskState::Waiting;
+    }
+  }
+
+  /// A worker took a cancelled task off `queue` without executing it
+  pub fn skip(&self, task_id: &str, queue: QueueId) {
+    let mut state = self.lock();
+    Self::dequeue(&mut state, queue);
+    self.finish_locked(&mut state, task_id, TaskState::Cancelled);
+  }
+
+  /// The task left `queue` unrun and failed: a full queue dropped it, or it expired
+  pub fn evict(&self, task_id: &str, queue: QueueId) {
+    let mut state = self.lock();
+    Self::dequeue(&mut state, queue);
+    self.finish_locked(&mut state, task_id, TaskState::Failed);
+  }
+
+  /// The task produced a result (or was lost with its worker)
+  pub fn finish(&self, task_id: &str, success: bool) {
+    let outcome = if success { TaskState::Completed } else { TaskState::Failed };
+    self.finish_locked(&mut self.lock(), task_id, outcome);
+  }
+
+  /// Mark a task cancelled; false if it's unknown or already finished
+  pub fn cancel(&self, task_id: &str) -> bool {
+    self.finish_locked(&mut self.lock(), task_id, TaskState::Cancelled)
+  }
+
+  /// Mark every waiting, queued or running task cancelled
+  pub fn cancel_all(&self) {
+    let mut state = self.lock();
+    let pending: Vec<String> = state.tasks
+      .iter()
+      .filter(|(_, record)| !record.state.is_finished())
+      .map(|(id, _)| id.clone())
+      .collect();
+    for task_id in pending {
+      self.finish_locked(&mut state, &task_id, TaskState::Cancelled);
+    }
+  }
+
+  /// Where a task is, or `None` for unknown (or long-finished) task IDs
+  pub fn state(&self, task_id: &str) -> Option<TaskState> {
+    self.lock().tasks.get(task_id).map(|record| record.state)
+  }
+
+  /// Current status, or `None` for unknown (or long-finished) task IDs
+  pub fn status(&self, task_id: &str) -> Option<TaskStatus> {
+    let now = self.clock.now();
+    let state = self.lock();
+    let record = state.tasks.get(task_id)?;
+
+    let end = record.finished.unwrap_or(now);
+    let millis = |d: Duration| d.as_secs_f64() * 1000.0;
+    let dequeued = state.queues.get(&record.queue).map_or(0, |counters| counters.dequeued);
+    // Everything waiting in higher-priority lanes is served first
+    let overtaking: u64 = match record.queue {
+      QueueId::Shared(priority) =>
+        Priority::DESCENDING.iter()
+          .take_while(|lane| **lane != priority)
+          .filter_map(|lane| state.queues.get(&QueueId::Shared(*lane)))
+          .map(|counters| counters.next_sequence.saturating_sub(counters.dequeued))
+          .sum(),
+      QueueId::Reserved(_) => 0,
+    };
+    Some(TaskStatus {
+      task_id: task_id.to_string(),
+      status: record.state.as_str().to_string(),
+      queue_position: (record.state == TaskState::Queued).then(|| {
+        let position = record.sequence.saturating_sub(dequeued) + overtaking;
+        position.min(u32::MAX as u64) as u32
+      }),
+      elapsed_ms: millis(end.saturating_duration_since(record.submitted)),
+      running_ms: record.started.map(|started| millis(end.saturating_duration_since(started))),
+      worker_id: record.worker_id.map(|id| id as u32),
+    })
+  }
+
+  /// Waiting tasks and the shared queue's tracked tasks: `(waiting, oldest
+  /// queued task's age, queued IDs in the order they'll run if `with_ids`)`.
+  /// Fire-and-forget tasks aren't tracked, so they never appear.
+  pub fn shared_queue(&self, with_ids: bool) -> (usize, Option<Duration>, Option<Vec<String>>) {
+    let now = self.clock.now();
+    let state = self.lock();
+    let mut waiting = 0;
+    let mut oldest: Option<Instant> = None;
+    let mut queued = Vec::new();
+    for (task_id, record) in &state.tasks {
+      match (record.state, record.queue) {
+        (TaskState::Waiting, _) => {
+          waiting += 1;
+        }
+        (TaskState::Queued, QueueId::Shared(priority)) => {
+          oldest = Some(oldest.map_or(record.submitted, |o| o.min(record.submitted)));
+          if with_ids {
+            queued.push((std::cmp::Reverse(priority), record.sequence, task_id.clone()));
+          }
+        }
+        _ => {}
+      }
+    }
+    let task_ids = with_ids.then(|| {
+      queued.sort_unstable();
+      queued.into_iter().map(|(_, _, task_id)| task_id).collect()
+    });
+    (waiting, oldest.map(|oldest| now.saturating_duration_since(oldest)), task_ids)
+  }
+
+  /// Queued tasks that have waited at least `threshold` since they were sent to
+  /// their queue, each reported once per wait: `(task ID, queue, time waited)`
+  pub fn starved(&self, threshold: Duration) -> Vec<(String, QueueId, Duration)> {
+    let now = self.clock.now();
+    let mut state = self.lock();
+    state.tasks
+      .iter_mut()
+      .filter(|(_, record)| record.state == TaskState::Queued && !record.starved)
+      .filter_map(|(task_id, record)| {
+        let waited = now.saturating_duration_since(record.queued);
+        (waited >= threshold).then(|| {
+          record.starved = true;
+          (task_id.clone(), record.queue, waited)
+        })
+      })
+      .collect()
+  }
+
+  fn dequeue(state: &mut TrackerState, queue: QueueId) {
+    let counters = state.queues.entry(queue).or_default();
+    counters.dequeued += 1;
+    // A reservation's tasks are all queued before any run, so a drained one is done
+    let reserved = matches!(queue, QueueId::Reserved(_));
+    if reserved && counters.dequeued >= counters.next_sequence {
+      state.queues.remove(&queue);
+    }
+  }
+
+  // First terminal state wins: a cancelled task stays cancelled even if its
+  // result arrives. False if the task was unknown or already finished.
+  fn finish_locked(&self, state: &mut TrackerState, task_id: &str, outcome: TaskState) -> bool {
+    let Some(record) = state.tasks.get_mut(task_id) else {
+      return false;
+    };
+    if record.state.is_finished() {
+      return false;
+    }
+    record.state = outcome;
+    record.finished = Some(self.clock.now());
+
+    state.finished_order.push_back(task_id.to_string());
+    if
+      state.finished_order.len() > MAX_FINISHED_TASKS &&
+      let Some(oldest) = state.finished_order.pop_front()
+    {
+      state.tasks.remove(&oldest);
+    }
+    true
+  }
+}
@@ -22,6 +22,15 @@ export interface ThreadConfig {
   timeout?: number
   enableValidation?: boolean
   transferMode?: 'auto' | 'clone' | 'transfer' | 'shared'
+  windowsThreadPriority?:
+    | 'idle'
+    | 'lowest'
+    | 'below_normal'
+    | 'normal'
+    | 'above_normal'
+    | 'highest'
+  muslStackSizeKb?: number
+  strict?: boolean
 }
 
 // ============================================================================
@@ -0,0 +1,118 @@
+// src/thread_tuning.rs - Platform-specific worker thread options
+use serde::{ Deserialize, Serialize };
+
+use crate::bindings::napi;
+use crate::sync::thread;
+
+// Below this even the executor's own frames risk overflowing
+const MIN_STACK_SIZE_KB: u32 = 64;
+
+/// Worker thread options that only some platforms support. Options for other
+/// platforms are still validated, then ignored, so one config works everywhere.
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThreadTuning {
+  /// Windows only: "idle", "lowest", "below_normal", "normal", "above_normal" or "highest"
+  pub windows_priority: Option<String>,
+  /// musl only: worker stack size in KiB (musl's 128 KiB default overflows easily)
+  pub musl_stack_size_kb: Option<u32>,
+}
+
+/// Validated `ThreadTuning`, applied to each worker thread
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WorkerThreadConfig {
+  // `THREAD_PRIORITY_*` value
+  priority: Option<i32>,
+  // Applies everywhere; `musl_stack_size` takes precedence on musl
+  stack_size: Option<usize>,
+  musl_stack_size: Option<usize>,
+}
+
+impl WorkerThreadConfig {
+  /// Validate `tuning`, on top of a `stack_size` in bytes for every platform
+  pub fn from_tuning(
+    tuning: Option<&ThreadTuning>,
+    stack_size: Option<usize>
+  ) -> Result<Self, String> {
+    let Some(tuning) = tuning else {
+      return Ok(Self { stack_size, ..Self::default() });
+    };
+
+    let priority = tuning.windows_priority.as_deref().map(Self::parse_priority).transpose()?;
+    let musl_stack_size = match tuning.musl_stack_size_kb {
+      Some(kb) if kb < MIN_STACK_SIZE_KB => {
+        return Err(
+          format!("musl_stack_size_kb must be at least {}, got {}", MIN_STACK_SIZE_KB, kb)
+        );
+      }
+      Some(kb) => Some((kb as usize) * 1024),
+      None => None,
+    };
+
+    Ok(Self { priority, stack_size, musl_stack_size })
+  }
+
+  fn parse_priority(value: &str) -> Result<i32, String> {
+    match value {
+      "idle" => Ok(-15),
+      "lowest" => Ok(-2),
+      "below_normal" => Ok(-1),
+      "normal" => Ok(0),
+      "above_normal" => Ok(1),
+      "highest" => Ok(2),
+      other =>
+        Err(
+          format!(
+            "Unknown windows_priority '{}', expected one of: {}",
+            other,
+            "idle, lowest, below_normal, normal, above_normal, highest"
+          )
+        ),
+    }
+  }
+
+  /// Builder for a worker thread, with the stack size for this platform
+  pub fn builder(&self, name: String) -> thread::Builder {
+    let builder = thread::Builder::new().name(name);
+    let musl_stack_size = self.musl_stack_size.filter(|_| cfg!(target_env = "musl"));
+    match musl_stack_size.or(self.stack_size) {
+      Some(stack_size) => builder.stack_size(stack_size),
+      None => builder,
+    }
+  }
+
+  /// Apply the priority to the calling thread on Windows; call from the worker itself
+  pub fn apply_to_current_thread(&self) {
+    if let Some(priority) = self.priority {
+      platform::set_current_thread_priority(priority);
+    }
+  }
+}
+
+#[cfg(windows)]
+mod platform {
+  use std::ffi::c_void;
+
+  unsafe extern "system" {
+    fn GetCurrentThread() -> *mut c_void;
+    fn SetThreadPriority(thread: *mut c_void, priority: i32) -> i32;
+  }
+
+  pub fn set_current_thread_priority(priority: i32) {
+    // SAFETY: GetCurrentThread returns a pseudo-handle that is always valid
+    // for the calling thread and never needs closing
+    let ok = unsafe { SetThreadPriority(GetCurrentThread(), priority) };
+    if ok == 0 {
+      tracing::warn!(
+        "SetThreadPriority({}) failed: {}",
+        priority,
+        std::io::Error::last_os_error()
+      );
+    }
+  }
+}
+
+#[cfg(not(windows))]
+mod platform {
+  pub fn set_current_thread_priority(_priority: i32) {}
+}
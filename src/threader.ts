@@ -35,6 +35,7 @@ export interface OptimizedThreader<T, R> {
       moduleDetections: string[]
       isHotFunction: boolean
       fnHash: string
+      inheritedFrom?: string // Hot function whose hints a near-duplicate inherited
     }
     batchStrategy: {
       optimalBatchSize: number
@@ -51,6 +52,9 @@ export interface OptimizedThreader<T, R> {
 
 export type Threader<T, R> = OptimizedThreader<T, R>
 
+type FunctionAnalysis =
+  OptimizedThreader<unknown, unknown>['optimizationData']['functionAnalysis']
+
 // ============================================================================
 // BINARY PROTOCOL (PURE FUNCTIONS)
 // ============================================================================
@@ -196,6 +200,68 @@ const deserializeData = (
 // Global state for function analysis cache
 let functionAnalysisCache = new Map<string, any>()
 let hotFunctionTracker = new Map<string, number>()
+let hotFunctionSignatures = new Map<string, number[]>()
+
+const HOT_FUNCTION_THRESHOLD = 10
+const SHINGLE_SIZE = 4
+const MINHASH_PERMUTATIONS = 64
+const NEAR_DUPLICATE_SIMILARITY = 0.95
+
+const isIdentifierChar = (char: string | undefined): boolean =>
+  char !== undefined && /[A-Za-z0-9_$]/.test(char)
+
+// Strip comments and insignificant whitespace so formatting-only edits keep
+// the same hash (and warm caches). String and template literals are kept verbatim.
+const normalizeFunctionCode = (fnString: string): string => {
+  let out = ''
+  let pendingSpace = false
+  let i = 0
+
+  const emit = (text: string) => {
+    if (pendingSpace) {
+      const prev = out[out.length - 1]
+      const next = text[0]
+      // Keep a space only where dropping it would merge tokens (`return x`, `a - -b`)
+      if (
+        (isIdentifierChar(prev) && isIdentifierChar(next)) ||
+        ((prev === '+' || prev === '-') && prev === next)
+      ) {
+        out += ' '
+      }
+      pendingSpace = false
+    }
+    out += text
+  }
+
+  while (i < fnString.length) {
+    const char = fnString.charAt(i)
+    const next = fnString.charAt(i + 1)
+
+    if (char === '/' && next === '/') {
+      while (i < fnString.length && fnString[i] !== '\n') i++
+      pendingSpace = out.length > 0
+    } else if (char === '/' && next === '*') {
+      const end = fnString.indexOf('*/', i + 2)
+      i = end === -1 ? fnString.length : end + 2
+      pendingSpace = out.length > 0
+    } else if (char === '"' || char === "'" || char === '`') {
+      let j = i + 1
+      while (j < fnString.length && fnString[j] !== char) {
+        j += fnString[j] === '\\' ? 2 : 1
+      }
+      emit(fnString.slice(i, j + 1))
+      i = j + 1
+    } else if (/\s/.test(char)) {
+      pendingSpace = out.length > 0
+      i++
+    } else {
+      emit(char)
+      i++
+    }
+  }
+
+  return out
+}
 
 const hashFunction = (fnString: string): string => {
   let hash = 0
@@ -207,6 +273,61 @@ const hashFunction = (fnString: string): string => {
   return hash.toString()
 }
 
+// FNV-1a over a shingle, as an unsigned 32-bit integer
+const hashShingle = (shingle: string): number => {
+  let hash = 0x811c9dc5
+  for (let i = 0; i < shingle.length; i++) {
+    hash = Math.imul(hash ^ shingle.charCodeAt(i), 0x01000193)
+  }
+  return hash >>> 0
+}
+
+// Murmur3 finalizer keyed by `seed`, standing in for one random permutation
+const permute = (hash: number, seed: number): number => {
+  let h = hash ^ Math.imul(seed + 1, 0x9e3779b9)
+  h = Math.imul(h ^ (h >>> 16), 0x85ebca6b)
+  h = Math.imul(h ^ (h >>> 13), 0xc2b2ae35)
+  return (h ^ (h >>> 16)) >>> 0
+}
+
+/**
+ * MinHash signature over token shingles of the normalized code. The share of
+ * matching slots between two signatures estimates their Jaccard similarity.
+ */
+const computeSignature = (normalizedCode: string): number[] => {
+  const tokens = normalizedCode.match(/[A-Za-z0-9_$]+|\S/g) || []
+  const shingleCount = Math.max(tokens.length - SHINGLE_SIZE + 1, 1)
+  const signature = new Array<number>(MINHASH_PERMUTATIONS).fill(0xffffffff)
+
+  for (let i = 0; i < shingleCount; i++) {
+    const shingle = hashShingle(tokens.slice(i, i + SHINGLE_SIZE).join(' '))
+    for (let p = 0; p < MINHASH_PERMUTATIONS; p++) {
+      signature[p] = Math.min(signature[p]!, permute(shingle, p))
+    }
+  }
+  return signature
+}
+
+const estimateSimilarity = (a: number[], b: number[]): number =>
+  a.filter((value, i) => value === b[i]).length / MINHASH_PERMUTATIONS
+
+// Most similar known hot function, if any is a near-duplicate
+const findNearDuplicate = (
+  signature: number[]
+): {fnHash: string; similarity: number} | null => {
+  let best: {fnHash: string; similarity: number} | null = null
+  hotFunctionSignatures.forEach((hotSignature, fnHash) => {
+    const similarity = estimateSimilarity(signature, hotSignature)
+    if (
+      similarity >= NEAR_DUPLICATE_SIMILARITY &&
+      (!best || similarity > best.similarity)
+    ) {
+      best = {fnHash, similarity}
+    }
+  })
+  return best
+}
+
 const detectModuleUsage = (fnString: string): string[] => {
   const modules: string[] = []
   const patterns = [
@@ -259,17 +380,26 @@ const trackHotFunction = (fnHash: string): number => {
 
 const analyzeFunction = <T, R>(fn: ThreadFunction<T, R>, dataSize: number) => {
   const fnString = fn.toString()
-  const fnHash = hashFunction(fnString)
+  const normalizedCode = normalizeFunctionCode(fnString)
+  const fnHash = hashFunction(normalizedCode)
 
   // Check cache first
   if (functionAnalysisCache.has(fnHash)) {
     const cached = functionAnalysisCache.get(fnHash)!
     const usage = trackHotFunction(fnHash)
-    return {...cached, isHotFunction: usage > 10, fnHash}
+    const isHotFunction = usage > HOT_FUNCTION_THRESHOLD
+
+    // Remember hot functions' shape so near-duplicates can start warm
+    if (isHotFunction && !hotFunctionSignatures.has(fnHash)) {
+      hotFunctionSignatures = new Map(
+        hotFunctionSignatures.set(fnHash, computeSignature(normalizedCode))
+      )
+    }
+    return {...cached, isHotFunction, fnHash}
   }
 
   // Analyze function
-  const analysis = {
+  const analysis: FunctionAnalysis = {
     complexity: estimateComplexity(fnString),
     isAsync: /async|await|Promise|\.then\(/i.test(fnString),
     estimatedMemory: estimateMemoryUsage(fnString, dataSize),
@@ -278,11 +408,30 @@ const analyzeFunction = <T, R>(fn: ThreadFunction<T, R>, dataSize: number) => {
     fnHash
   }
 
+  // A near-duplicate of a hot function inherits its complexity and warmth
+  // (hence batching, core and JIT hints). Async and module usage always come
+  // from the new code, since they decide whether Rust can run it at all.
+  const nearDuplicate =
+    hotFunctionSignatures.size > 0
+      ? findNearDuplicate(computeSignature(normalizedCode))
+      : null
+  if (nearDuplicate) {
+    const known = functionAnalysisCache.get(nearDuplicate.fnHash)
+    if (known) analysis.complexity = known.complexity
+    analysis.inheritedFrom = nearDuplicate.fnHash
+    hotFunctionTracker = new Map(
+      hotFunctionTracker.set(
+        fnHash,
+        hotFunctionTracker.get(nearDuplicate.fnHash) || 0
+      )
+    )
+  }
+
   // Cache the analysis
   functionAnalysisCache = new Map(functionAnalysisCache.set(fnHash, analysis))
 
   const usage = trackHotFunction(fnHash)
-  return {...analysis, isHotFunction: usage > 10}
+  return {...analysis, isHotFunction: usage > HOT_FUNCTION_THRESHOLD}
 }
 
 // ============================================================================
@@ -635,6 +784,7 @@ export const cache = {
     functionAnalysisCache = new Map()
     binaryProtocolStats = new Map()
     hotFunctionTracker = new Map()
+    hotFunctionSignatures = new Map()
     batchingHistory = new Map()
     optimalBatchSizes = new Map()
   },
@@ -642,7 +792,7 @@ export const cache = {
     functionAnalysisCache: functionAnalysisCache.size,
     binaryProtocolStats: binaryProtocolStats.size,
     hotFunctions: Array.from(hotFunctionTracker.entries()).filter(
-      ([, count]) => count > 10
+      ([, count]) => count > HOT_FUNCTION_THRESHOLD
     ).length,
     optimalBatchSizes: optimalBatchSizes.size
   })
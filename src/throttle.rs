@@ -0,0 +1,195 @@
+// src/throttle.rs - Detects CPU frequency throttling and scales parallelism down
+use std::hint::black_box;
+use std::sync::atomic::{ AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering };
+use std::time::{ Duration, Instant };
+
+use crate::events::EventLog;
+
+// Calibration kernel size; a few hundred microseconds on a modern core
+const PROBE_ITERATIONS: u64 = 200_000;
+// Best of several runs filters out preemption noise
+const PROBE_RUNS: usize = 3;
+const PROBE_INTERVAL: Duration = Duration::from_secs(2);
+// Slowdown vs calibration that counts as throttling, and the level it must
+// fall back under before full parallelism is restored
+const THROTTLE_SLOWDOWN: f64 = 1.25;
+const RECOVERY_SLOWDOWN: f64 = 1.1;
+// Slow probes in a row before parallelism is limited, so a single probe
+// preempted by another process doesn't park workers
+const THROTTLED_PROBES: u32 = 3;
+
+/// Watches for thermal or burst-credit throttling on laptops and burstable
+/// cloud instances.
+///
+/// Off until enabled, as the probes cost CPU and a busy machine can pass for a
+/// throttled one. A fixed CPU-bound kernel is timed when detection is turned
+/// on and re-timed every couple of seconds by whichever busy worker gets there
+/// first. Real time is used on purpose: the probe measures the hardware, not
+/// the executor's clock. Once the kernel has run `s` times slower than
+/// calibration on several probes in a row, only `workers / s` workers keep
+/// taking shared tasks until the slowdown clears.
+#[derive(Debug)]
+pub struct ThrottleMonitor {
+  workers: AtomicUsize,
+  enabled: AtomicBool,
+  // Fastest probe seen so far, in nanoseconds; 0 until first enabled
+  baseline_nanos: AtomicU64,
+  // Probes in a row that ran slow enough to count as throttling
+  slow_probes: AtomicU32,
+  allowed_workers: AtomicUsize,
+  // Milliseconds since `started` at which the next probe is due
+  next_probe_ms: AtomicU64,
+  started: Instant,
+}
+
+impl ThrottleMonitor {
+  /// A monitor for `workers` workers, disabled until `set_enabled`
+  pub fn new(workers: usize) -> Self {
+    let workers = workers.max(1);
+    Self {
+      workers: AtomicUsize::new(workers),
+      enabled: AtomicBool::new(false),
+      baseline_nanos: AtomicU64::new(0),
+      slow_probes: AtomicU32::new(0),
+      allowed_workers: AtomicUsize::new(workers),
+      next_probe_ms: AtomicU64::new(PROBE_INTERVAL.as_millis() as u64),
+      started: Instant::now(),
+    }
+  }
+
+  /// Turn detection on or off. Turning it on calibrates against the machine as
+  /// it runs now; turning it off restores full parallelism.
+  pub fn set_enabled(&self, enabled: bool) {
+    if !enabled {
+      self.enabled.store(false, Ordering::Release);
+      self.allowed_workers.store(self.workers(), Ordering::Release);
+      return;
+    }
+    if self.enabled.load(Ordering::Acquire) {
+      return;
+    }
+    self.baseline_nanos.store(Self::probe().as_nanos() as u64, Ordering::Release);
+    self.slow_probes.store(0, Ordering::Release);
+    let next_ms = (self.started.elapsed() + PROBE_INTERVAL).as_millis() as u64;
+    self.next_probe_ms.store(next_ms, Ordering::Release);
+    self.enabled.store(true, Ordering::Release);
+  }
+
+  /// Scale to a pool resized to `workers`, at full parallelism until the next
+  /// probe finds the machine still throttled
+  pub fn set_workers(&self, workers: usize) {
+    let workers = workers.max(1);
+    self.workers.store(workers, Ordering::Release);
+    self.allowed_workers.store(workers, Ordering::Release);
+  }
+
+  fn workers(&self) -> usize {
+    self.workers.load(Ordering::Acquire)
+  }
+
+  /// Workers currently allowed to take shared tasks
+  pub fn effective_parallelism(&self) -> usize {
+    self.allowed_workers.load(Ordering::Acquire)
+  }
+
+  /// Whether `worker_id` should sit out while the machine is throttled
+  pub fn is_parked(&self, worker_id: usize) -> bool {
+    worker_id >= self.effective_parallelism()
+  }
+
+  /// Re-measure if a probe is due, adjusting parallelism and reporting changes
+  pub fn maybe_probe(&self, events: &EventLog) {
+    if !self.enabled.load(Ordering::Acquire) {
+      return;
+    }
+
+    let now_ms = self.started.elapsed().as_millis() as u64;
+    let due_ms = self.next_probe_ms.load(Ordering::Acquire);
+    let next_ms = now_ms + (PROBE_INTERVAL.as_millis() as u64);
+    // Only one worker claims each probe slot
+    if
+      now_ms < due_ms ||
+      self.next_probe_ms
+        .compare_exchange(due_ms, next_ms, Ordering::AcqRel, Ordering::Acquire)
+        .is_err()
+    {
+      return;
+    }
+
+    self.measure(events);
+  }
+
+  /// Re-measure now, as idle workers do while nothing else runs, putting off
+  /// the next periodic probe
+  pub fn calibrate(&self, events: &EventLog) {
+    if !self.enabled.load(Ordering::Acquire) {
+      return;
+    }
+    let next_ms = (self.started.elapsed() + PROBE_INTERVAL).as_millis() as u64;
+    self.next_probe_ms.fetch_max(next_ms, Ordering::AcqRel);
+    self.measure(events);
+  }
+
+  fn measure(&self, events: &EventLog) {
+    let sample = Self::probe().as_nanos() as u64;
+    let baseline = self.baseline_nanos.fetch_min(sample, Ordering::AcqRel).min(sample).max(1);
+    self.adjust((sample as f64) / (baseline as f64), events);
+  }
+
+  /// Act on a probe that ran `slowdown` times slower than calibration
+  pub(crate) fn adjust(&self, slowdown: f64, events: &EventLog) {
+    let current = self.effective_parallelism();
+    let workers = self.workers();
+
+    if slowdown >= THROTTLE_SLOWDOWN {
+      let slow_probes = self.slow_probes.fetch_add(1, Ordering::AcqRel) + 1;
+      let target = ((workers as f64) / slowdown).ceil().max(1.0) as usize;
+      if slow_probes >= THROTTLED_PROBES && target < current {
+        self.allowed_workers.store(target, Ordering::Release);
+        events.emit(
+          "throttling_detected",
+          None,
+          format!(
+            "CPU running {:.2}x slower than calibration; limiting parallelism to {} of {} workers",
+            slowdown,
+            target,
+            workers
+          )
+        );
+      }
+      return;
+    }
+
+    self.slow_probes.store(0, Ordering::Release);
+    if slowdown <= RECOVERY_SLOWDOWN && current < workers {
+      self.allowed_workers.store(workers, Ordering::Release);
+      events.emit(
+        "throttling_recovered",
+        None,
+        format!(
+          "CPU back to {:.2}x of calibration; restored parallelism to {} workers",
+          slowdown,
+          workers
+        )
+      );
+    }
+  }
+
+  /// Best-of-N timing of the calibration kernel
+  fn probe() -> Duration {
+    (0..PROBE_RUNS)
+      .map(|_| {
+        let start = Instant::now();
+        let mut state = black_box(0x9e37_79b9_7f4a_7c15u64);
+        for _ in 0..PROBE_ITERATIONS {
+          state ^= state << 13;
+          state ^= state >> 7;
+          state ^= state << 17;
+        }
+        black_box(state);
+        start.elapsed()
+      })
+      .min()
+      .unwrap_or_default()
+  }
+}
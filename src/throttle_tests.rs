@@ -0,0 +1,45 @@
+// src/throttle_tests.rs - How probe slowdowns move a throttle monitor's parallelism
+use crate::clock::SystemClock;
+use crate::events::EventLog;
+use crate::throttle::ThrottleMonitor;
+
+fn kinds(events: &EventLog) -> Vec<String> {
+  events.drain().into_iter().map(|event| event.kind).collect()
+}
+
+#[test]
+fn throttling_needs_slow_probes_in_a_row() {
+  let (monitor, events) = (ThrottleMonitor::new(4), EventLog::new(SystemClock::shared()));
+  // A probe under the threshold breaks the run
+  monitor.adjust(2.0, &events);
+  monitor.adjust(2.0, &events);
+  monitor.adjust(1.2, &events);
+  monitor.adjust(2.0, &events);
+  monitor.adjust(2.0, &events);
+  assert_eq!(monitor.effective_parallelism(), 4);
+  assert!(kinds(&events).is_empty());
+
+  monitor.adjust(2.0, &events);
+  assert_eq!(monitor.effective_parallelism(), 2);
+  assert!(monitor.is_parked(2));
+  assert_eq!(kinds(&events), ["throttling_detected"]);
+}
+
+#[test]
+fn recovery_waits_for_the_lower_threshold() {
+  let (monitor, events) = (ThrottleMonitor::new(4), EventLog::new(SystemClock::shared()));
+  for _ in 0..3 {
+    monitor.adjust(2.0, &events);
+  }
+  kinds(&events);
+
+  // Between the thresholds the machine isn't throttled enough to limit
+  // further, nor recovered enough to restore
+  monitor.adjust(1.2, &events);
+  assert_eq!(monitor.effective_parallelism(), 2);
+  assert!(kinds(&events).is_empty());
+
+  monitor.adjust(1.1, &events);
+  assert_eq!(monitor.effective_parallelism(), 4);
+  assert_eq!(kinds(&events), ["throttling_recovered"]);
+}
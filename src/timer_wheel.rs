@@ -0,0 +1,142 @@
+// src/timer_wheel.rs - Timer wheel holding tasks submitted with a delay
+//
+// `submit_delayed` puts a task here instead of on the shared queue. The wheel
+// is a ring of slots, one per tick: a task goes in the slot its due tick maps
+// to, and one more than a rotation away stays put until the wheel comes round
+// to its tick again. Workers advance the wheel on every pass through their
+// loop and requeue whatever has come due, and idle ones sleep until the next
+// tick with a task in its slot, so a delay is only as precise as the tick.
+use std::sync::atomic::{ AtomicU64, AtomicUsize, Ordering };
+use std::time::{ Duration, Instant };
+
+use crate::OptimizedWorkerTask;
+use crate::clock::SharedClock;
+use crate::priority::{ Priority, Requeue };
+use crate::sync::{ Mutex, MutexGuard, lock_recovering };
+
+/// Wheel resolution; delays are rounded up to a whole tick
+pub const TICK: Duration = Duration::from_millis(5);
+// Slots in the ring, so one rotation covers 1.28s
+const SLOTS: usize = 256;
+
+#[derive(Debug)]
+struct Entry {
+  due: u64,
+  task: OptimizedWorkerTask,
+  priority: Priority,
+}
+
+#[derive(Debug)]
+struct Wheel {
+  slots: Vec<Vec<Entry>>,
+  // Latest tick whose due tasks have been released
+  tick: u64,
+}
+
+/// Delayed tasks, released to the shared queue as they come due
+pub struct TimerWheel {
+  wheel: Mutex<Wheel>,
+  // Lets workers skip the lock on every loop pass while nothing is scheduled
+  scheduled: AtomicUsize,
+  // Mirror of `Wheel::tick`, so workers skip the lock within a tick
+  advanced: AtomicU64,
+  // No task is due before this tick; `u64::MAX` while none is scheduled
+  next_due: AtomicU64,
+  start: Instant,
+  requeue: Requeue,
+  clock: SharedClock,
+}
+
+impl TimerWheel {
+  pub fn new(requeue: Requeue, clock: SharedClock) -> Self {
+    Self {
+      wheel: Mutex::new(Wheel {
+        slots: (0..SLOTS).map(|_| Vec::new()).collect(),
+        tick: 0,
+      }),
+      scheduled: AtomicUsize::new(0),
+      advanced: AtomicU64::new(0),
+      next_due: AtomicU64::new(u64::MAX),
+      start: clock.now(),
+      requeue,
+      clock,
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, Wheel> {
+    lock_recovering(&self.wheel, || {})
+  }
+
+  fn elapsed(&self) -> Duration {
+    self.clock.now().saturating_duration_since(self.start)
+  }
+
+  /// Hold `task` until `delay` has passed
+  pub fn schedule(&self, task: OptimizedWorkerTask, priority: Priority, delay: Duration) {
+    // Rounded up, so a task never comes due early
+    let due = (self.elapsed() + delay).as_nanos().div_ceil(TICK.as_nanos()) as u64;
+    let sooner = {
+      let mut wheel = self.lock();
+      let due = due.max(wheel.tick + 1);
+      wheel.slots[(due as usize) % SLOTS].push(Entry { due, task, priority });
+      self.scheduled.fetch_add(1, Ordering::Release);
+      self.next_due.fetch_min(due, Ordering::AcqRel) > due
+    };
+    // Idle workers sleep until the old next tick; have them wake for this one
+    if sooner {
+      self.requeue.wake_idle();
+    }
+  }
+
+  /// How long until the next tick that may release a task, if any is scheduled
+  pub fn next_due(&self) -> Option<Duration> {
+    if self.scheduled.load(Ordering::Acquire) == 0 {
+      return None;
+    }
+    let due = self.next_due.load(Ordering::Acquire);
+    (due != u64::MAX).then(|| {
+      Duration::from_nanos(due.saturating_mul(TICK.as_nanos() as u64)).saturating_sub(
+        self.elapsed()
+      )
+    })
+  }
+
+  /// Requeue tasks whose delay is over
+  pub fn release_due(&self) {
+    if self.scheduled.load(Ordering::Acquire) == 0 {
+      return;
+    }
+    let now = (self.elapsed().as_nanos() / TICK.as_nanos()) as u64;
+    if now <= self.advanced.load(Ordering::Acquire) {
+      return;
+    }
+    let released: Vec<(OptimizedWorkerTask, Priority)> = {
+      let mut wheel = self.lock();
+      if now <= wheel.tick {
+        return;
+      }
+      // After a full rotation without a pass, every slot is visited once
+      let first = (wheel.tick + 1).max(now.saturating_sub(SLOTS as u64 - 1));
+      let mut released = Vec::new();
+      for tick in first..=now {
+        let slot = &mut wheel.slots[(tick as usize) % SLOTS];
+        let (due, later): (Vec<Entry>, Vec<Entry>) = std::mem::take(slot)
+          .into_iter()
+          .partition(|entry| entry.due <= now);
+        *slot = later;
+        released.extend(due.into_iter().map(|entry| (entry.task, entry.priority)));
+      }
+      wheel.tick = now;
+      self.advanced.store(now, Ordering::Release);
+      // The first slot with tasks in the coming rotation; they may be due in a
+      // later one, but none is due sooner
+      let next_due = (now + 1..=now + (SLOTS as u64))
+        .find(|tick| !wheel.slots[(*tick as usize) % SLOTS].is_empty())
+        .unwrap_or(u64::MAX);
+      self.next_due.store(next_due, Ordering::Release);
+      self.scheduled.fetch_sub(released.len(), Ordering::Release);
+      released
+    };
+    self.requeue.send(released);
+  }
+}
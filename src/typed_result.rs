@@ -0,0 +1,106 @@
+// src/typed_result.rs - Results decoded into JavaScript values
+//
+// Fast paths return results as JSON text, so `42`, `"42"` and `"\"HELLO\""`
+// all arrive as strings. With the `typed_result` hint the result is also
+// attached decoded, tagged with its type, and crosses into JavaScript as a
+// real number, string, boolean, array or object.
+use serde::{ Deserialize, Deserializer, Serialize, Serializer };
+
+use crate::bindings::napi;
+
+/// A result and its JavaScript type
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypedResult {
+  /// "number", "string", "boolean", "array", "object" or "null"
+  pub r#type: String,
+  pub value: ResultValue,
+}
+
+/// A decoded result. JSON has no `NaN` or `Infinity`, which numeric fast paths
+/// can produce, so those are carried as plain floats.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResultValue {
+  Json(serde_json::Value),
+  NonFinite(f64),
+}
+
+impl TypedResult {
+  /// Decode a result as returned in `OptimizedWorkerResult::result`. Text that
+  /// isn't JSON (and isn't a non-finite number) is taken as a plain string.
+  pub fn decode(raw: &str) -> Self {
+    let value = match serde_json::from_str(raw) {
+      Ok(json) => ResultValue::Json(json),
+      Err(_) =>
+        match raw.trim() {
+          "NaN" => ResultValue::NonFinite(f64::NAN),
+          "inf" | "Infinity" => ResultValue::NonFinite(f64::INFINITY),
+          "-inf" | "-Infinity" => ResultValue::NonFinite(f64::NEG_INFINITY),
+          _ => ResultValue::Json(serde_json::Value::String(raw.to_string())),
+        }
+    };
+    Self { r#type: value.type_name().to_string(), value }
+  }
+}
+
+impl ResultValue {
+  pub fn type_name(&self) -> &'static str {
+    match self {
+      ResultValue::NonFinite(_) => "number",
+      ResultValue::Json(json) =>
+        match json {
+          serde_json::Value::Null => "null",
+          serde_json::Value::Bool(_) => "boolean",
+          serde_json::Value::Number(_) => "number",
+          serde_json::Value::String(_) => "string",
+          serde_json::Value::Array(_) => "array",
+          serde_json::Value::Object(_) => "object",
+        }
+    }
+  }
+}
+
+// Non-finite numbers serialize as `null`, as `JSON.stringify` does
+impl Serialize for ResultValue {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    match self {
+      ResultValue::Json(json) => json.serialize(serializer),
+      ResultValue::NonFinite(value) => value.serialize(serializer),
+    }
+  }
+}
+
+impl<'de> Deserialize<'de> for ResultValue {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    serde_json::Value::deserialize(deserializer).map(ResultValue::Json)
+  }
+}
+
+#[cfg(feature = "node")]
+mod js {
+  use napi::bindgen_prelude::{ FromNapiValue, ToNapiValue };
+  use napi::{ Result, sys };
+
+  use super::ResultValue;
+
+  impl ToNapiValue for ResultValue {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> Result<sys::napi_value> {
+      match val {
+        ResultValue::Json(json) => unsafe { serde_json::Value::to_napi_value(env, json) },
+        ResultValue::NonFinite(value) => unsafe { f64::to_napi_value(env, value) },
+      }
+    }
+  }
+
+  impl FromNapiValue for ResultValue {
+    unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> Result<Self> {
+      // serde_json has no form for non-finite numbers, so they are checked first
+      let number = unsafe { f64::from_napi_value(env, napi_val) };
+      if let Ok(value) = number && !value.is_finite() {
+        return Ok(ResultValue::NonFinite(value));
+      }
+      let json = unsafe { serde_json::Value::from_napi_value(env, napi_val)? };
+      Ok(ResultValue::Json(json))
+    }
+  }
+}
@@ -0,0 +1,54 @@
+// src/typed_result_tests.rs - Results decoded into the JavaScript types they stand for
+use serde_json::json;
+
+use crate::executor_config::ExecutorConfig;
+use crate::typed_result::{ ResultValue, TypedResult };
+use crate::{ OptimizedMultiCoreExecutor, task_spec };
+
+#[test]
+fn each_json_type_is_tagged_and_decoded() {
+  for (raw, r#type, value) in [
+    ("42", "number", json!(42)),
+    ("\"42\"", "string", json!("42")),
+    ("true", "boolean", json!(true)),
+    ("[1,\"a\"]", "array", json!([1, "a"])),
+    ("{\"a\":1}", "object", json!({ "a": 1 })),
+    ("null", "null", json!(null)),
+    // Not JSON at all: kept as the text it is
+    ("HELLO", "string", json!("HELLO")),
+  ] {
+    let typed = TypedResult::decode(raw);
+    assert_eq!((typed.r#type.as_str(), typed.value), (r#type, ResultValue::Json(value)), "{}", raw);
+  }
+}
+
+#[test]
+fn non_finite_numbers_stay_numbers() {
+  let typed = TypedResult::decode("-inf");
+  assert_eq!(typed.r#type, "number");
+  assert_eq!(typed.value, ResultValue::NonFinite(f64::NEG_INFINITY));
+  assert!(matches!(TypedResult::decode("NaN").value, ResultValue::NonFinite(n) if n.is_nan()));
+  // No JSON form, so `null` as `JSON.stringify` has it
+  assert_eq!(serde_json::to_string(&typed).unwrap(), r#"{"type":"number","value":null}"#);
+}
+
+#[test]
+fn typed_results_are_attached_only_when_asked_for() {
+  let config = ExecutorConfig { worker_count: Some(1), ..ExecutorConfig::default() };
+  let executor = OptimizedMultiCoreExecutor::new(Some(config)).expect("executor");
+  let run = |typed_result: Option<bool>| {
+    let mut hints = task_spec::resolve_hints("s => s.toUpperCase()", &[]).expect("hints");
+    hints.typed_result = typed_result;
+    let task_id = executor
+      .submit_task("s => s.toUpperCase()".into(), r#""hello""#.into(), hints, None, None)
+      .expect("submit");
+    executor.get_result_by_id(task_id, Some(5000)).expect("result")
+  };
+
+  let typed = run(Some(true));
+  assert_eq!(typed.result.as_deref(), Some(r#""HELLO""#));
+  let expected = TypedResult { r#type: "string".into(), value: ResultValue::Json(json!("HELLO")) };
+  assert_eq!(typed.typed, Some(expected));
+  assert!(run(None).typed.is_none());
+  executor.shutdown().unwrap();
+}
@@ -118,4 +118,19 @@ export interface ThreadConfig {
   enableValidation?: boolean
   /** Memory transfer mode */
   transferMode?: 'auto' | 'clone' | 'transfer' | 'shared'
+  /** Worker thread priority on Windows; ignored on other platforms */
+  windowsThreadPriority?:
+    | 'idle'
+    | 'lowest'
+    | 'below_normal'
+    | 'normal'
+    | 'above_normal'
+    | 'highest'
+  /** Worker stack size in KiB on musl (e.g. Alpine); ignored elsewhere */
+  muslStackSizeKb?: number
+  /**
+   * Only let the Rust backend run functions it recognizes exactly; anything
+   * else goes to JavaScript workers instead of being approximated
+   */
+  strict?: boolean
 }
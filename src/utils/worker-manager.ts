@@ -58,7 +58,7 @@ export interface WorkerStats {
 // RUST BACKEND INTEGRATION (Pure Functions)
 // ============================================================================
 
-const loadRustBackend = (): any | null => {
+const loadRustBackend = (config: ThreadConfig): any | null => {
   const possiblePaths = [
     // Platform-specific binaries
     path.resolve(process.cwd(), 'threader.darwin-arm64.node'),
@@ -81,8 +81,25 @@ const loadRustBackend = (): any | null => {
           backend?.OptimizedMultiCoreExecutor &&
           backend.is_optimized_multicore_available?.()
         ) {
+          // A module that computes wrong answers is worse than no module
+          const report = backend.selfTest?.()
+          if (report && !report.passed) {
+            const failed = report.checks
+              .filter((check: any) => check.status === 'failed')
+              .map((check: any) => `${check.name}: ${check.error}`)
+            console.warn(
+              `⚠️ Rust backend failed its self-test (${failed.join('; ')}), using JavaScript workers`
+            )
+            return null
+          }
           console.log(`🦀 Enhanced Rust backend loaded: ${binPath}`)
-          return new backend.OptimizedMultiCoreExecutor()
+          return new backend.OptimizedMultiCoreExecutor({
+            tuning: {
+              windowsPriority: config.windowsThreadPriority,
+              muslStackSizeKb: config.muslStackSizeKb
+            },
+            strict: config.strict
+          })
         }
         // Fallback to simple backend
         if (backend?.MultiCoreExecutor && backend.isMulticoreAvailable?.()) {
@@ -340,7 +357,7 @@ export const createWorkerManager = (
       enableValidation: config.enableValidation || false,
       transferMode: config.transferMode || 'auto'
     },
-    rustBackend: loadRustBackend(),
+    rustBackend: loadRustBackend(config),
     isShuttingDown: false
   }
 
@@ -0,0 +1,155 @@
+// src/watchdog.rs - Reports tasks that overrun their timeout as soon as they do
+//
+// Native execution can't be interrupted, so a task past its `timeout_ms` keeps
+// its worker until it finishes. Its caller needn't wait that long: a worker
+// watches each task with a timeout while it runs, and once the task's deadline
+// passes the watchdog thread reports the timeout in its place. Whichever of
+// the two takes the watch out of `Deadlines` first delivers the task's result;
+// the other's is discarded.
+use std::collections::HashMap;
+use std::thread;
+use std::time::{ Duration, Instant };
+
+use crate::clock::SharedClock;
+use crate::sync::{ Arc, Condvar, Mutex, MutexGuard, lock_recovering };
+
+/// Longest the watchdog sleeps while tasks are watched, so a clock driven by
+/// hand (`MockClock`) is never far behind
+const MAX_WAIT: Duration = Duration::from_millis(100);
+
+type OnTimeout = Box<dyn FnOnce() + Send>;
+
+/// Deadlines of the running tasks, shared by the workers and the watchdog
+#[derive(Default)]
+pub struct Deadlines {
+  watches: Mutex<Watches>,
+  changed: Condvar,
+}
+
+#[derive(Default)]
+struct Watches {
+  // Each watched task's deadline, and what reports it overran
+  due: HashMap<u64, (Instant, OnTimeout)>,
+  next_id: u64,
+  stopped: bool,
+}
+
+/// A running task's claim on its result, until the watchdog takes it
+pub struct Watch {
+  deadlines: Arc<Deadlines>,
+  id: u64,
+}
+
+impl Deadlines {
+  fn lock(&self) -> MutexGuard<'_, Watches> {
+    lock_recovering(&self.watches, || {})
+  }
+
+  /// Watch a task due at `due`: `on_timeout` runs on the watchdog thread if
+  /// the deadline passes before the returned watch is finished
+  pub fn watch(
+    deadlines: &Arc<Self>,
+    due: Instant,
+    on_timeout: impl FnOnce() + Send + 'static
+  ) -> Watch {
+    let mut watches = deadlines.lock();
+    let id = watches.next_id;
+    watches.next_id += 1;
+    watches.due.insert(id, (due, Box::new(on_timeout)));
+    drop(watches);
+    deadlines.changed.notify_one();
+    Watch { deadlines: Arc::clone(deadlines), id }
+  }
+
+  /// Take the watches due by `now`, and how long until the next one is
+  fn take_due(watches: &mut Watches, now: Instant) -> (Vec<OnTimeout>, Option<Duration>) {
+    let due: Vec<u64> = watches.due
+      .iter()
+      .filter(|(_, (due, _))| *due <= now)
+      .map(|(id, _)| *id)
+      .collect();
+    let overran = due
+      .into_iter()
+      .filter_map(|id| watches.due.remove(&id))
+      .map(|(_, on_timeout)| on_timeout)
+      .collect();
+    let next = watches.due
+      .values()
+      .map(|(due, _)| due.saturating_duration_since(now))
+      .min();
+    (overran, next)
+  }
+}
+
+impl Watch {
+  /// Move the deadline back by `paused`, time the task spent not running
+  pub fn postpone(&self, paused: Duration) {
+    if let Some((due, _)) = self.deadlines.lock().due.get_mut(&self.id) {
+      *due += paused;
+    }
+  }
+
+  /// Claim the task's result for its worker: false if the watchdog already
+  /// reported the task timed out
+  pub fn finish(self) -> bool {
+    self.deadlines.lock().due.remove(&self.id).is_some()
+  }
+}
+
+impl Drop for Watch {
+  // A task lost with its worker has nothing left to report
+  fn drop(&mut self) {
+    self.deadlines.lock().due.remove(&self.id);
+  }
+}
+
+/// Thread reporting overrun tasks until dropped
+pub struct Watchdog {
+  deadlines: Arc<Deadlines>,
+  handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Watchdog {
+  /// Report each task watched in `deadlines` once its deadline on `clock` passes
+  pub fn start(deadlines: Arc<Deadlines>, clock: SharedClock) -> Self {
+    let watched = Arc::clone(&deadlines);
+    let handle = thread::spawn(move || {
+      let mut watches = watched.lock();
+      while !watches.stopped {
+        let (overran, next) = Deadlines::take_due(&mut watches, clock.now());
+        if !overran.is_empty() {
+          // Reporting delivers results, which mustn't hold up the workers
+          drop(watches);
+          overran.into_iter().for_each(|on_timeout| on_timeout());
+          watches = watched.lock();
+          continue;
+        }
+        watches = match next {
+          Some(next) => watched.changed
+            .wait_timeout(watches, next.min(MAX_WAIT))
+            .map(|(guard, _)| guard)
+            .unwrap_or_else(|poisoned| poisoned.into_inner().0),
+          None => watched.changed
+            .wait(watches)
+            .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        };
+      }
+    });
+
+    Self {
+      deadlines,
+      handle: Some(handle),
+    }
+  }
+}
+
+impl Drop for Watchdog {
+  fn drop(&mut self) {
+    // Tasks still running then deliver their own results, however late
+    self.deadlines.lock().stopped = true;
+    self.deadlines.changed.notify_all();
+    if let Some(handle) = self.handle.take() {
+      let _ = handle.join();
+    }
+  }
+}
@@ -1,9 +1,11 @@
 // src/worker_pool.rs
 use std::sync::Arc;
-use tokio::sync::{ mpsc, oneshot, RwLock };
+use tokio::sync::{ mpsc, oneshot, RwLock, Semaphore };
 use std::collections::HashMap;
 use thiserror::Error;
 
+use crate::strict;
+
 /// Errors that can occur in the worker pool
 #[derive(Error, Debug)]
 pub enum WorkerPoolError {
@@ -40,10 +42,24 @@ pub struct WorkerPool {
 }
 
 impl WorkerPool {
-  /// Create a new worker pool with the specified number of threads
+  /// Create a new worker pool with the specified number of threads, in strict
+  /// mode whenever `strict::set_default` enables it
   pub fn new(num_workers: usize) -> Self {
+    Self::build(num_workers, None)
+  }
+
+  /// Worker pool that always (or never) runs in strict mode, where unknown
+  /// functions fail instead of returning their input
+  pub fn with_strict(num_workers: usize, strict: bool) -> Self {
+    Self::build(num_workers, Some(strict))
+  }
+
+  // `strict: None` follows the process-wide default at execution time
+  fn build(num_workers: usize, strict: Option<bool>) -> Self {
     let (task_sender, mut task_receiver) = mpsc::unbounded_channel();
     let active_tasks = Arc::new(RwLock::new(HashMap::new()));
+    // At most `num_workers` tasks execute at once; the rest wait for a permit
+    let permits = Arc::new(Semaphore::new(num_workers.max(1)));
 
     let pool = Self {
       task_sender,
@@ -56,8 +72,9 @@ impl WorkerPool {
     tokio::spawn(async move {
       while let Some(task) = task_receiver.recv().await {
         let active_tasks = Arc::clone(&active_tasks_clone);
+        let permits = Arc::clone(&permits);
         tokio::spawn(async move {
-          Self::process_task(task, active_tasks).await;
+          Self::process_task(task, active_tasks, permits, strict).await;
         });
       }
     });
@@ -87,6 +104,11 @@ impl WorkerPool {
     self.num_workers
   }
 
+  /// Number of tasks currently executing
+  pub async fn active_task_count(&self) -> usize {
+    self.active_tasks.read().await.len()
+  }
+
   /// Shutdown the worker pool
   pub async fn shutdown(&self) -> Result<(), WorkerPoolError> {
     // The task_sender will be dropped when this function completes
@@ -94,10 +116,31 @@ impl WorkerPool {
     Ok(())
   }
 
-  /// Process a single task
-  async fn process_task(task: WorkerTask, active_tasks: Arc<RwLock<HashMap<String, bool>>>) {
+  /// Process a single task. A task whose caller stops waiting before it gets a
+  /// worker (e.g. a loser of `execute_race`) is dropped without running.
+  async fn process_task(
+    mut task: WorkerTask,
+    active_tasks: Arc<RwLock<HashMap<String, bool>>>,
+    permits: Arc<Semaphore>,
+    strict: Option<bool>
+  ) {
     let task_id = task.id.clone();
 
+    let permit = match task.response_sender.as_mut() {
+      Some(response_sender) =>
+        tokio::select! {
+          permit = Arc::clone(&permits).acquire_owned() => permit,
+          _ = response_sender.closed() => {
+            tracing::debug!("Dropping task {} before execution: caller stopped waiting", task_id);
+            return;
+          }
+        },
+      None => permits.acquire_owned().await,
+    };
+    let Ok(_permit) = permit else {
+      return;
+    };
+
     // Register task as active
     {
       let mut tasks = active_tasks.write().await;
@@ -110,7 +153,8 @@ impl WorkerPool {
     let execution_result = tokio::task::spawn_blocking({
       let function_string = task.function_string.clone();
       let data = task.data.clone();
-      move || Self::execute_function(&function_string, &data)
+      let strict = strict.unwrap_or_else(strict::default_enabled);
+      move || Self::execute_function(&function_string, &data, strict)
     }).await;
 
     let result = match execution_result {
@@ -139,30 +183,33 @@ impl WorkerPool {
   }
 
   /// Execute a JavaScript function string with data
-  fn execute_function(
+  pub(crate) fn execute_function(
     function_string: &str,
-    data: &serde_json::Value
+    data: &serde_json::Value,
+    strict: bool
   ) -> Result<serde_json::Value, String> {
     // This is a simplified implementation for testing
     // In a real implementation, you would use a JavaScript engine like V8 or QuickJS
 
+    // Strict mode matches the whole function, so `x => x * 20` isn't taken for `x => x * 2`
+    let is = |pattern: &str| {
+      if strict { function_string.trim() == pattern } else { function_string.contains(pattern) }
+    };
+
     // Pattern matching for basic functions
-    if function_string.contains("x => x * 2") || function_string.contains("(x) => x * 2") {
+    if is("x => x * 2") || is("(x) => x * 2") {
       if let Some(num) = data.as_f64() {
         Ok(serde_json::json!(num * 2.0))
       } else {
         Err("Expected number for multiplication".to_string())
       }
-    } else if function_string.contains("x => x + 1") || function_string.contains("(x) => x + 1") {
+    } else if is("x => x + 1") || is("(x) => x + 1") {
       if let Some(num) = data.as_f64() {
         Ok(serde_json::json!(num + 1.0))
       } else {
         Err("Expected number for addition".to_string())
       }
-    } else if
-      function_string.contains("x => x.length") ||
-      function_string.contains("(x) => x.length")
-    {
+    } else if is("x => x.length") || is("(x) => x.length") {
       if let Some(arr) = data.as_array() {
         Ok(serde_json::json!(arr.len()))
       } else if let Some(s) = data.as_str() {
@@ -170,15 +217,14 @@ impl WorkerPool {
       } else {
         Err("Expected array or string for length".to_string())
       }
-    } else if
-      function_string.contains("x => x.toUpperCase()") ||
-      function_string.contains("(x) => x.toUpperCase()")
-    {
+    } else if is("x => x.toUpperCase()") || is("(x) => x.toUpperCase()") {
       if let Some(s) = data.as_str() {
         Ok(serde_json::json!(s.to_uppercase()))
       } else {
         Err("Expected string for toUpperCase".to_string())
       }
+    } else if strict {
+      Err(format!("Strict mode: unrecognized function '{}'", function_string.trim()))
     } else {
       // Default: return data as-is (identity function)
       tracing::warn!("Unknown function pattern, returning data as-is: {}", function_string);
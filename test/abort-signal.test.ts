@@ -0,0 +1,66 @@
+import {OptimizedMultiCoreExecutor} from '../index'
+
+const hints = {
+  operationType: 'mathematical',
+  complexity: 'low',
+  expectedCores: 1,
+  shouldUseRust: true,
+  isHotFunction: false,
+  estimatedMemory: 0,
+  functionHash: 'double'
+}
+
+describe('AbortSignal integration', () => {
+  let executor: OptimizedMultiCoreExecutor
+
+  beforeEach(() => {
+    executor = new OptimizedMultiCoreExecutor({workerCount: 1})
+  })
+
+  afterEach(() => {
+    executor.shutdown()
+  })
+
+  test('an already-aborted signal turns the task away', async () => {
+    const controller = new AbortController()
+    const {signal} = controller
+    controller.abort()
+
+    expect(() =>
+      executor.submitOptimizedTask('x => x * 2', '1', hints, null, null, signal)
+    ).toThrow(/AbortError/)
+    await expect(
+      executor.runTask('x => x * 2', '1', hints, null, null, signal)
+    ).rejects.toThrow(/AbortError/)
+    expect(executor.getQueueDepth()).toEqual(0)
+  })
+
+  test('aborting cancels queued tasks and rejects their promise', async () => {
+    // Holds the only worker while the next tasks queue behind it
+    executor.setFaultInjection(JSON.stringify({delay_rate: 1, delay_ms: 200}))
+    executor.submitOptimizedTask('x => x * 2', '1', hints)
+
+    const controller = new AbortController()
+    const {signal} = controller
+    const running = executor.runTask(
+      'x => x * 2',
+      '2',
+      hints,
+      null,
+      null,
+      signal
+    )
+    const taskId = executor.submitOptimizedTask(
+      'x => x * 2',
+      '3',
+      hints,
+      null,
+      null,
+      signal
+    )
+    controller.abort()
+
+    await expect(running).rejects.toThrow(/AbortError/)
+    expect(executor.getTaskStatus(taskId).status).toEqual('cancelled')
+  })
+})
@@ -0,0 +1,68 @@
+import {actor} from '../src/index'
+
+const counter = () =>
+  actor.spawn(
+    'counter',
+    (start: number) => ({count: start}),
+    (state: {count: number}, message: {by: number}) => {
+      if (message.by < 0) throw new Error('counters only go up')
+      state.count += message.by
+      return state.count
+    },
+    {initData: 10}
+  )
+
+// Replies after 200ms
+const sleep = () => new Promise(resolve => setTimeout(resolve, 200))
+
+describe('Actors', () => {
+  afterEach(async () => {
+    await actor.shutdown()
+  })
+
+  test('state persists across messages handled in arrival order', async () => {
+    await counter()
+
+    const replies = await Promise.all(
+      [1, 2, 3].map(by => actor.send('counter', {by}))
+    )
+    expect(replies).toEqual([11, 13, 16])
+    // A failed message leaves the state as it was
+    await expect(actor.send('counter', {by: -1})).rejects.toThrow(
+      'Actor handler failed: counters only go up'
+    )
+    await expect(actor.send('counter', {by: 0})).resolves.toBe(16)
+  })
+
+  test('stopping an actor rejects its unanswered sends', async () => {
+    await counter()
+    await expect(counter()).rejects.toThrow("Actor 'counter' already exists")
+    await actor.spawn('slow', () => ({}), sleep)
+    expect(actor.list()).toEqual(['counter', 'slow'])
+
+    const unanswered = actor.send('slow', 1)
+    await actor.stop('slow')
+    await expect(unanswered).rejects.toThrow("Actor 'slow' stopped")
+    await expect(actor.send('slow', 2)).rejects.toThrow("Unknown actor 'slow'")
+    expect(actor.list()).toEqual(['counter'])
+  })
+
+  test('init failures and slow replies are reported', async () => {
+    const failing = actor.spawn(
+      'broken',
+      () => {
+        throw new Error('no config')
+      },
+      () => 1
+    )
+    await expect(failing).rejects.toThrow(
+      "Actor 'broken' failed to initialize: no config"
+    )
+    expect(actor.list()).toEqual([])
+
+    await actor.spawn('sleepy', () => ({}), sleep, {timeout: 50})
+    await expect(actor.send('sleepy', 1)).rejects.toThrow(
+      "Actor 'sleepy' did not reply within 50ms"
+    )
+  })
+})
@@ -0,0 +1,78 @@
+import {AsyncLocalStorage} from 'async_hooks'
+import {OptimizedMultiCoreExecutor} from '../index'
+
+const hints = {
+  operationType: 'mathematical',
+  complexity: 'low',
+  expectedCores: 1,
+  shouldUseRust: true,
+  isHotFunction: false,
+  estimatedMemory: 0,
+  functionHash: 'double'
+}
+
+describe('Async context propagation', () => {
+  let executor: OptimizedMultiCoreExecutor
+
+  beforeEach(() => {
+    executor = new OptimizedMultiCoreExecutor({
+      workerCount: 2,
+      memoryWatermarkMb: 1
+    })
+  })
+
+  afterEach(() => {
+    executor.shutdown()
+  })
+
+  test('result callbacks see the store of the call that streamed them', async () => {
+    const storage = new AsyncLocalStorage<string>()
+    const stream = (request: string) =>
+      storage.run(request, () => {
+        const ids = [1, 2, 3].map(i =>
+          executor.submitOptimizedTask('x => x * 2', String(i), hints)
+        )
+        const seen: Array<string | undefined> = []
+        // The stream can resolve before JS has run the last callbacks
+        return new Promise<Array<string | undefined>>(resolve => {
+          executor.streamBatchResults(ids, () => {
+            seen.push(storage.getStore())
+            if (seen.length === ids.length) {
+              resolve(seen)
+            }
+          })
+        })
+      })
+
+    const [first, second] = await Promise.all([
+      stream('request-1'),
+      stream('request-2')
+    ])
+
+    expect(first).toEqual(['request-1', 'request-1', 'request-1'])
+    expect(second).toEqual(['request-2', 'request-2', 'request-2'])
+  })
+
+  test('memory pressure callbacks see the store they were registered in', async () => {
+    const storage = new AsyncLocalStorage<string>()
+    const seen = new Promise<string | undefined>(resolve => {
+      storage.run('registration', () =>
+        executor.onMemoryPressure(() => resolve(storage.getStore()))
+      )
+    })
+    // Keeps the process up until the unreferenced callback has run
+    const keepAlive = setTimeout(() => {}, 5000)
+
+    const big = JSON.stringify(Array(100000).fill(1))
+    storage.run('submission', () => {
+      expect(() => {
+        for (let i = 0; i < 50; i++) {
+          executor.submitOptimizedTask('x => x * 2', big, hints)
+        }
+      }).toThrow(/watermark/)
+    })
+
+    await expect(seen).resolves.toBe('registration')
+    clearTimeout(keepAlive)
+  })
+})
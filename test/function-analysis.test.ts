@@ -0,0 +1,69 @@
+import {threader, cache} from '../src/index'
+
+// A function whose `toString()` is exactly `source`, however it is compiled
+const fromSource = (source: string) => new Function(`return (${source})`)()
+
+const analyze = (source: string) =>
+  threader(fromSource(source), [1]).optimizationData.functionAnalysis
+
+const hashOf = (source: string): string => analyze(source).fnHash
+
+// A long function whose last term is multiplied by `last`
+const weightedSum = (last: number) => {
+  const terms = Array.from({length: 100}, (_, i) => {
+    const weight = i === 99 ? last : i + 1
+    return `total += xs[${i}] * ${weight};`
+  })
+  return `xs => { let total = 0; ${terms.join(' ')} return total }`
+}
+
+describe('Function hashing', () => {
+  beforeEach(() => {
+    cache.clear()
+  })
+
+  test('formatting-only edits keep the same hash', () => {
+    const hash = hashOf('x => x * 2')
+
+    expect(hashOf('x=>x*2')).toBe(hash)
+    expect(hashOf('x => x * 2 // doubled')).toBe(hash)
+    expect(hashOf('x /* input */ =>\n  x  *  2')).toBe(hash)
+  })
+
+  test('edits that change meaning change the hash', () => {
+    // String literals are kept verbatim, whitespace and all
+    expect(hashOf('s => s + " a"')).not.toBe(hashOf('s => s + "a"'))
+    // Spaces that separate tokens are significant
+    expect(hashOf('(a, b) => a - -b')).not.toBe(hashOf('(a, b) => a--b'))
+    expect(hashOf('x => { return x }')).not.toBe(hashOf('x => { returnx }'))
+  })
+})
+
+describe('Near-duplicate detection', () => {
+  beforeEach(() => {
+    cache.clear()
+  })
+
+  test('a near-duplicate of a hot function starts warm', () => {
+    const hot = weightedSum(100)
+    for (let i = 0; i < 11; i++) {
+      analyze(hot)
+    }
+    expect(analyze(hot).isHotFunction).toBe(true)
+
+    const edited = analyze(weightedSum(101))
+    expect(edited.fnHash).not.toBe(hashOf(hot))
+    expect(edited.inheritedFrom).toBe(hashOf(hot))
+    expect(edited.isHotFunction).toBe(true)
+  })
+
+  test('unrelated functions start cold', () => {
+    for (let i = 0; i < 11; i++) {
+      analyze(weightedSum(100))
+    }
+
+    const unrelated = analyze('x => x * 2')
+    expect(unrelated.inheritedFrom).toBeUndefined()
+    expect(unrelated.isHotFunction).toBe(false)
+  })
+})
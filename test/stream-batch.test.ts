@@ -0,0 +1,57 @@
+import {OptimizedMultiCoreExecutor} from '../index'
+
+const hints = {
+  operationType: 'mathematical',
+  complexity: 'low',
+  expectedCores: 1,
+  shouldUseRust: true,
+  isHotFunction: false,
+  estimatedMemory: 0,
+  functionHash: 'double'
+}
+
+describe('Streaming batch results', () => {
+  let executor: OptimizedMultiCoreExecutor
+
+  beforeEach(() => {
+    executor = new OptimizedMultiCoreExecutor({workerCount: 2})
+  })
+
+  afterEach(() => {
+    executor.shutdown()
+  })
+
+  test('each result of the batch reaches the callback once', async () => {
+    const submit = (data: string) =>
+      executor.submitOptimizedTask('x => x * 2', data, hints)
+    const ids = ['1', '2', '3', '4'].map(submit)
+    const other = submit('5')
+
+    const results: Array<string> = []
+    // The stream can resolve before JS has run the last callbacks
+    const streamed = new Promise<number>(resolve => {
+      executor.streamBatchResults(ids, (err, result) => {
+        results.push(`${result.id}=${result.result}`)
+        if (results.length === ids.length) {
+          resolve(results.length)
+        }
+      })
+    })
+
+    await expect(streamed).resolves.toBe(4)
+    const expected = ids.map((id, i) => `${id}=${(i + 1) * 2}`)
+    expect(results.sort()).toEqual(expected.sort())
+    // Results outside the batch stay queued for whoever asks for them
+    expect(executor.getResultById(other, 1000).result).toBe('10')
+  })
+
+  test('the stream rejects once its timeout passes', async () => {
+    executor.setFaultInjection(JSON.stringify({delay_rate: 1, delay_ms: 300}))
+    const slow = executor.submitOptimizedTask('x => x * 2', '1', hints)
+
+    const streamed = executor.streamBatchResults([slow], () => {}, 50)
+    await expect(streamed).rejects.toThrow(
+      /Timed out waiting for result \(0 of 1 streamed\)/
+    )
+  })
+})
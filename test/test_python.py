@@ -0,0 +1,64 @@
+# test/test_python.py - The Python module over the engine (see src/python.rs)
+#
+# Needs the module built and importable, e.g. after `maturin develop`:
+#
+#   python -m unittest discover -s test -p "test_*.py"
+import unittest
+
+import threader
+
+HINTS = {"operation_type": "mathematical", "complexity": "low"}
+
+
+class ExecutorTest(unittest.TestCase):
+    def test_tasks_and_batches_return_dict_results(self):
+        with threader.Executor(workers=2) as executor:
+            task_id = executor.submit("x => x * 2", "21", HINTS)
+            result = executor.result(task_id, timeout_ms=5000)
+            self.assertEqual(result["id"], task_id)
+            self.assertEqual(result["result"], "42")
+            self.assertIsNone(result.get("error"))
+
+            ids = executor.submit_batch([("x => x * 2", str(n)) for n in range(5)], HINTS)
+            results = executor.results(ids, timeout_ms=5000)
+            self.assertEqual([r["result"] for r in results], ["0", "2", "4", "6", "8"])
+            self.assertEqual(executor.submit_batch([]), [])
+            self.assertGreaterEqual(executor.stats()["total_executions"], 6)
+
+    def test_specs_run_as_graphs(self):
+        spec = """
+version: 1
+tasks:
+  - name: double
+    function: "x => x * 2"
+    data: "10"
+  - name: shift
+    function: "x => x + 5"
+    depends_on: [double]
+"""
+        with threader.Executor(workers=2) as executor:
+            ids = executor.submit_spec(spec, "yaml")
+            self.assertEqual(executor.result(ids[1], timeout_ms=5000)["result"], "25")
+
+    def test_config_and_resizing(self):
+        with threader.Executor(workers=1, max_workers=3, queue_discipline="lifo") as executor:
+            self.assertEqual(executor.worker_count, 1)
+            executor.set_worker_count(3)
+            self.assertEqual(executor.worker_count, 3)
+            self.assertTrue(executor.drain(timeout_ms=5000))
+
+    def test_failures_raise_threader_errors(self):
+        with self.assertRaisesRegex(threader.ThreaderError, "Unknown queue_discipline 'random'"):
+            threader.Executor(queue_discipline="random")
+
+        with threader.Executor(workers=1) as executor:
+            unknown = "Submission has unknown hint 'complexty'"
+            with self.assertRaisesRegex(threader.ThreaderError, unknown):
+                executor.submit("x => x * 2", "1", {"complexty": "low"})
+            with self.assertRaisesRegex(threader.ThreaderError, "Hints must be a dict"):
+                executor.submit("x => x * 2", "1", ["low"])
+        self.assertTrue(issubclass(threader.QueueFullError, threader.ThreaderError))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,83 @@
+// tests/threader_cli.rs - Running task specs with the threader-cli binary
+use std::path::PathBuf;
+use std::process::{ Command, Output };
+
+use serde_json::Value;
+
+// A scratch directory of its own for each test
+fn scratch(test: &str) -> PathBuf {
+  let dir = std::env::temp_dir().join(format!("threader-cli-{}-{}", test, std::process::id()));
+  std::fs::create_dir_all(&dir).expect("scratch dir");
+  dir
+}
+
+fn cli(args: &[&str]) -> Output {
+  Command::new(env!("CARGO_BIN_EXE_threader-cli")).args(args).output().expect("threader-cli")
+}
+
+fn read_json(path: &PathBuf) -> Value {
+  serde_json::from_str(&std::fs::read_to_string(path).expect("output file")).expect("JSON")
+}
+
+#[test]
+fn spec_results_and_metrics_are_written_to_files() {
+  let dir = scratch("files");
+  let spec = dir.join("pipeline.yaml");
+  std::fs::write(
+    &spec,
+    "version: 1\ntasks:\n\
+     - name: double\n  function: \"x => x * 2\"\n  data: \"10\"\n\
+     - name: shift\n  function: \"x => x + 5\"\n  depends_on: [double]\n"
+  ).unwrap();
+  let (results, metrics) = (dir.join("results.json"), dir.join("metrics.json"));
+
+  // YAML by the file's extension
+  let output = cli(&[
+    spec.to_str().unwrap(),
+    "--workers",
+    "2",
+    "--results",
+    results.to_str().unwrap(),
+    "--metrics",
+    metrics.to_str().unwrap(),
+  ]);
+  assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+
+  let results = read_json(&results);
+  let named: Vec<(&str, &str)> = results
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|result| (result["name"].as_str().unwrap(), result["result"].as_str().unwrap()))
+    .collect();
+  assert_eq!(named, [("double", "20"), ("shift", "25")]);
+  let metrics = read_json(&metrics);
+  assert_eq!((metrics["tasks"].as_u64(), metrics["failed"].as_u64()), (Some(2), Some(0)));
+  assert_eq!(metrics["workers"], 2);
+  std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn exit_code_tells_failed_tasks_from_invalid_input() {
+  let dir = scratch("exit");
+  let spec = dir.join("failing.json");
+  std::fs::write(
+    &spec,
+    r#"{"version": 1, "tasks": [{"name": "fetch", "function": "x => fetch(x)", "data": "1"}]}"#
+  ).unwrap();
+
+  // Results go to stdout without `--results`
+  let failed = cli(&[spec.to_str().unwrap()]);
+  assert_eq!(failed.status.code(), Some(1));
+  let results: Value = serde_json::from_slice(&failed.stdout).expect("JSON");
+  assert_eq!(results[0]["name"], "fetch");
+  assert!(results[0]["error"].is_string(), "{}", results);
+
+  let unknown = cli(&[spec.to_str().unwrap(), "--retries", "3"]);
+  assert_eq!(unknown.status.code(), Some(2));
+  assert!(String::from_utf8_lossy(&unknown.stderr).starts_with("Unknown option --retries\nUsage:"));
+  let missing = cli(&[dir.join("missing.yaml").to_str().unwrap()]);
+  assert_eq!(missing.status.code(), Some(2));
+  assert!(String::from_utf8_lossy(&missing.stderr).starts_with("Can't read"));
+  std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,12 @@
+// threader-macros/src/lib.rs - No-op stand-in for `napi_derive::napi`
+//
+// napi's `#[napi] impl` looks for literal `#[napi]` attributes on its methods,
+// so they can't be wrapped in `cfg_attr`. Without the `node` feature the crate
+// imports this attribute under the same name instead, leaving items untouched.
+use proc_macro::TokenStream;
+
+/// Accepts any `#[napi(...)]` arguments and returns the item unchanged
+#[proc_macro_attribute]
+pub fn napi(_args: TokenStream, item: TokenStream) -> TokenStream {
+  item
+}
@@ -0,0 +1,63 @@
+# threader.pyi - Types of the Python module (see src/python.rs)
+from types import TracebackType
+from typing import Any, Dict, List, Optional, Tuple, Type
+
+class ThreaderError(Exception):
+    """An executor call failed"""
+
+class QueueFullError(ThreaderError):
+    """The shared queue had no room"""
+
+class Executor:
+    """Multi-core executor; config fields as in `ExecutorConfig`"""
+
+    def __init__(
+        self,
+        workers: Optional[int] = None,
+        max_workers: Optional[int] = None,
+        queue_capacity: Optional[int] = None,
+        overflow_policy: Optional[str] = None,
+        queue_discipline: Optional[str] = None,
+        sticky_routing: Optional[bool] = None,
+        profile: Optional[str] = None,
+        default_timeout_ms: Optional[int] = None,
+    ) -> None: ...
+    @property
+    def worker_count(self) -> int: ...
+    def set_worker_count(self, workers: int) -> None:
+        """Grow or shrink the pool to `workers`, up to `max_workers`, keeping queued tasks"""
+    def submit(
+        self,
+        function_code: str,
+        data: str,
+        hints: Optional[Dict[str, Any]] = None,
+        timeout_ms: Optional[int] = None,
+    ) -> str:
+        """Queue a task; returns its ID. `hints` are partial `OptimizationHints`"""
+    def submit_batch(
+        self,
+        tasks: List[Tuple[str, str]],
+        hints: Optional[Dict[str, Any]] = None,
+        timeout_ms: Optional[int] = None,
+    ) -> List[str]:
+        """Queue `(function_code, data)` pairs sharing `hints`; returns their IDs in order"""
+    def submit_spec(self, spec: str, format: Optional[str] = None) -> List[str]:
+        """Queue a JSON or YAML task spec; returns its tasks' IDs in spec order"""
+    def result(self, task_id: str, timeout_ms: Optional[int] = None) -> Dict[str, Any]:
+        """A task's result, waiting up to `timeout_ms` (forever if unset)"""
+    def results(self, task_ids: List[str], timeout_ms: Optional[int] = None) -> List[Dict[str, Any]]:
+        """Results of `task_ids`, in order"""
+    def stats(self) -> Dict[str, Any]:
+        """Optimization statistics"""
+    def cancel(self, task_id: str) -> None: ...
+    def drain(self, timeout_ms: Optional[int] = None) -> bool:
+        """Wait for queued and running tasks to finish, refusing new ones; whether they did"""
+    def shutdown(self) -> None: ...
+    def __enter__(self) -> "Executor": ...
+    def __exit__(
+        self,
+        kind: Optional[Type[BaseException]],
+        value: Optional[BaseException],
+        traceback: Optional[TracebackType],
+    ) -> bool:
+        """Shuts the executor down"""